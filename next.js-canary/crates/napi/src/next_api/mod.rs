@@ -1,4 +1,5 @@
 pub mod endpoint;
 pub mod project;
+pub mod turbopack_cache;
 pub mod turbopack_ctx;
 pub mod utils;
@@ -122,6 +122,14 @@ pub struct NapiWatchOptions {
     /// Enable polling at a certain interval if the native file watching doesn't work (e.g.
     /// docker).
     pub poll_interval_ms: Option<f64>,
+
+    /// Skip invalidation when a change event leaves the content of a file unchanged (e.g. branch
+    /// switches or formatters).
+    pub content_hashing: Option<bool>,
+
+    /// When watching starts, only invalidate files whose content changed since they were read.
+    /// Requires `content_hashing`.
+    pub scan_on_startup: Option<bool>,
 }
 
 #[napi(object)]
@@ -265,6 +273,8 @@ impl From<NapiWatchOptions> for WatchOptions {
                 .poll_interval_ms
                 .filter(|interval| !interval.is_nan() && interval.is_finite() && *interval > 0.0)
                 .map(|interval| Duration::from_secs_f64(interval / 1000.0)),
+            content_hashing: val.content_hashing.unwrap_or(false),
+            scan_on_startup: val.scan_on_startup.unwrap_or(false),
         }
     }
 }
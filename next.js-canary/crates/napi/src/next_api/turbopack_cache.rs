@@ -0,0 +1,50 @@
+//! Exports and imports the persistent cache as a single archive, e.g. to restore it on another
+//! machine.
+
+use std::path::Path;
+
+use turbo_tasks_backend::{export_cache, import_cache};
+
+use crate::{
+    next_api::turbopack_ctx::{cache_path, cache_version_info},
+    register,
+    util::MapErr,
+};
+
+/// Packs the persistent cache in `dist_dir` into a single archive at `archive_path`. `root_path` is
+/// the root path of the project the cache was created for.
+#[napi]
+pub fn export_turbopack_cache(
+    dist_dir: String,
+    root_path: String,
+    archive_path: String,
+) -> napi::Result<()> {
+    register();
+    export_cache(
+        &cache_path(Path::new(&dist_dir)),
+        &cache_version_info(),
+        Path::new(&root_path),
+        Path::new(&archive_path),
+    )
+    .convert_err()?;
+    Ok(())
+}
+
+/// Restores an archive created by [`export_turbopack_cache`] into the persistent cache in
+/// `dist_dir`. Paths are rewritten when the archive was created for a different `root_path`.
+#[napi]
+pub fn import_turbopack_cache(
+    dist_dir: String,
+    root_path: String,
+    archive_path: String,
+) -> napi::Result<()> {
+    register();
+    import_cache(
+        &cache_path(Path::new(&dist_dir)),
+        &cache_version_info(),
+        Path::new(&root_path),
+        Path::new(&archive_path),
+    )
+    .convert_err()?;
+    Ok(())
+}
@@ -4,7 +4,7 @@ use std::{
     env,
     fs::OpenOptions,
     io::{self, BufRead, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex},
     time::Instant,
 };
@@ -179,22 +179,32 @@ impl NapiNextTurbopackCallbacks {
     }
 }
 
+/// The version of the persistent cache. A cache is only used by the same version of Next.js.
+pub fn cache_version_info() -> GitVersionInfo {
+    GitVersionInfo {
+        describe: env!("VERGEN_GIT_DESCRIBE"),
+        dirty: option_env!("CI").is_none_or(|value| value.is_empty())
+            && env!("VERGEN_GIT_DIRTY") == "true",
+    }
+}
+
+/// The directory of the persistent cache in the `distDir`.
+pub fn cache_path(output_path: &Path) -> PathBuf {
+    output_path.join("cache/turbopack")
+}
+
 pub fn create_turbo_tasks(
     output_path: PathBuf,
     persistent_caching: bool,
-    _memory_limit: usize,
+    memory_limit: usize,
     dependency_tracking: bool,
     is_ci: bool,
     is_short_session: bool,
 ) -> Result<NextTurboTasks> {
     Ok(if persistent_caching {
-        let version_info = GitVersionInfo {
-            describe: env!("VERGEN_GIT_DESCRIBE"),
-            dirty: option_env!("CI").is_none_or(|value| value.is_empty())
-                && env!("VERGEN_GIT_DIRTY") == "true",
-        };
+        let version_info = cache_version_info();
         let (backing_storage, cache_state) = default_backing_storage(
-            &output_path.join("cache/turbopack"),
+            &cache_path(&output_path),
             &version_info,
             is_ci,
             is_short_session,
@@ -207,6 +217,9 @@ pub fn create_turbo_tasks(
                     turbo_tasks_backend::StorageMode::ReadWrite
                 }),
                 dependency_tracking,
+                // Evicted tasks are restored from the persistent cache, so eviction is only
+                // possible with persistent caching.
+                memory_limit: (memory_limit != usize::MAX).then_some(memory_limit),
                 ..Default::default()
             },
             Either::Left(backing_storage),
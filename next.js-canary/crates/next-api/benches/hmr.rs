@@ -203,6 +203,8 @@ impl HmrBenchmark {
                 watch: WatchOptions {
                     enable: true,
                     poll_interval: None,
+                    content_hashing: false,
+                    scan_on_startup: false,
                 },
                 dev: true,
                 encryption_key: rcstr!("test-key"),
@@ -75,8 +75,12 @@ pub(crate) async fn collect_next_dynamic_chunks(
                 }
             };
 
-            let async_loader =
-                chunking_context.async_loader_chunk_item(*module, module_graph, availability_info);
+            let async_loader = chunking_context.async_loader_chunk_item(
+                *module,
+                module_graph,
+                availability_info,
+                Default::default(),
+            );
             let async_chunk_group = async_loader.references().to_resolved().await?;
 
             let module_id = dynamic_entry
@@ -643,6 +643,7 @@ impl PagesProject {
                 },
             )),
             EcmaScriptModulesReferenceSubType::Undefined,
+            Vec::new(),
             false,
             None,
         )
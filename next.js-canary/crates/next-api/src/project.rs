@@ -37,7 +37,7 @@ use turbo_tasks::{
 };
 use turbo_tasks_env::{EnvMap, ProcessEnv};
 use turbo_tasks_fs::{
-    DiskFileSystem, FileSystem, FileSystemPath, VirtualFileSystem, invalidation,
+    DiskFileSystem, DiskWatchOptions, FileSystem, FileSystemPath, VirtualFileSystem, invalidation,
     util::{join_path, unix_to_sys},
 };
 use turbopack::{
@@ -132,6 +132,24 @@ pub struct WatchOptions {
     /// Enable polling at a certain interval if the native file watching doesn't work (e.g.
     /// docker).
     pub poll_interval: Option<Duration>,
+
+    /// Skip invalidation when a change event leaves the content of a file unchanged (e.g. branch
+    /// switches or formatters).
+    pub content_hashing: bool,
+
+    /// When watching starts, only invalidate files whose content changed since they were read.
+    /// Requires `content_hashing`.
+    pub scan_on_startup: bool,
+}
+
+impl WatchOptions {
+    fn disk_watch_options(&self) -> DiskWatchOptions {
+        DiskWatchOptions {
+            poll_interval: self.poll_interval,
+            content_hashing: self.content_hashing,
+            scan_on_startup: self.scan_on_startup,
+        }
+    }
 }
 
 #[derive(
@@ -319,7 +337,7 @@ impl ProjectContainer {
             .await?;
         if watch.enable {
             project_fs
-                .start_watching_with_invalidation_reason(watch.poll_interval)
+                .start_watching_with_invalidation_reason(watch.disk_watch_options())
                 .await?;
         } else {
             project_fs.invalidate_with_reason(|path| invalidation::Initialize {
@@ -418,7 +436,7 @@ impl ProjectContainer {
             if watch.enable {
                 // TODO stop watching: prev_project_fs.stop_watching()?;
                 project_fs
-                    .start_watching_with_invalidation_reason(watch.poll_interval)
+                    .start_watching_with_invalidation_reason(watch.disk_watch_options())
                     .await?;
             } else {
                 project_fs.invalidate_with_reason(|path| invalidation::Initialize {
@@ -840,7 +858,8 @@ impl Project {
             self.project_path().owned().await?,
             node_execution_chunking_context,
             self.env(),
-        ))
+        )
+        .with_pool_warm_up(*self.next_config().turbopack_node_pool_warm_up().await?))
     }
 
     #[turbo_tasks::function]
@@ -1042,6 +1061,10 @@ impl Project {
             source_maps: self.next_config().client_source_maps(self.next_mode()),
             no_mangling: self.no_mangling(),
             scope_hoisting: self.next_config().turbo_scope_hoisting(self.next_mode()),
+            split_rules: self.next_config().turbopack_split_rules(),
+            service_worker_precache_manifest: self
+                .next_config()
+                .turbopack_service_worker_precache_manifest(),
         }))
     }
 
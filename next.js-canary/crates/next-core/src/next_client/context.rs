@@ -39,7 +39,7 @@ use crate::{
     mode::NextMode,
     next_build::get_postcss_package_mapping,
     next_client::runtime_entry::{RuntimeEntries, RuntimeEntry},
-    next_config::NextConfig,
+    next_config::{ChunkSplitRules, NextConfig},
     next_font::local::NextFontLocalResolvePlugin,
     next_import_map::{
         get_next_client_fallback_import_map, get_next_client_import_map,
@@ -58,7 +58,7 @@ use crate::{
             styled_jsx::get_styled_jsx_transform_rule,
             swc_ecma_transform_plugins::get_swc_ecma_transform_plugin_rule,
         },
-        webpack_rules::webpack_loader_options,
+        webpack_rules::{sass::native_sass_options, webpack_loader_options},
     },
     transform_options::{
         get_decorators_transform_options, get_jsx_transform_options,
@@ -150,6 +150,7 @@ pub async fn get_client_resolve_options_context(
     let custom_conditions = vec![mode.await?.condition().into()];
     let resolve_options_context = ResolveOptionsContext {
         enable_node_modules: Some(project_path.root().owned().await?),
+        enable_yarn_pnp: Some(project_path.root().owned().await?),
         custom_conditions,
         import_map: Some(next_client_import_map),
         fallback_import_map: Some(next_client_fallback_import_map),
@@ -255,6 +256,15 @@ pub async fn get_client_module_options_context(
     // Now creates a webpack rules that applies to all codes.
     let enable_webpack_loaders =
         webpack_loader_options(project_path.clone(), next_config, false, conditions).await?;
+    let enable_native_sass = if *next_config.turbopack_native_sass().await? {
+        Some(
+            native_sass_options(next_config.sass_config(), project_path.clone())
+                .to_resolved()
+                .await?,
+        )
+    } else {
+        None
+    };
 
     let tree_shaking_mode_for_user_code = *next_config
         .tree_shaking_mode_for_user_code(next_mode.is_development())
@@ -316,6 +326,7 @@ pub async fn get_client_module_options_context(
         },
         css: CssOptionsContext {
             source_maps,
+            enable_native_sass,
             ..Default::default()
         },
         environment: Some(env),
@@ -400,6 +411,8 @@ pub struct ClientChunkingContextOptions {
     pub source_maps: Vc<bool>,
     pub no_mangling: Vc<bool>,
     pub scope_hoisting: Vc<bool>,
+    pub split_rules: Vc<ChunkSplitRules>,
+    pub service_worker_precache_manifest: Vc<bool>,
 }
 
 #[turbo_tasks::function]
@@ -420,6 +433,8 @@ pub async fn get_client_chunking_context(
         source_maps,
         no_mangling,
         scope_hoisting,
+        split_rules,
+        service_worker_precache_manifest,
     } = options;
 
     let next_mode = mode.await?;
@@ -450,6 +465,8 @@ pub async fn get_client_chunking_context(
         SourceMapsType::None
     })
     .asset_base_path(asset_prefix)
+    .service_worker_root_path(client_root.join("static/service-workers")?)
+    .service_worker_precache_manifest(*service_worker_precache_manifest.await?)
     .current_chunk_method(CurrentChunkMethod::DocumentCurrentScript)
     .export_usage(*export_usage.await?)
     .module_id_strategy(module_id_strategy.to_resolved().await?);
@@ -467,6 +484,7 @@ pub async fn get_client_chunking_context(
                     min_chunk_size: 50_000,
                     max_chunk_count_per_group: 40,
                     max_merge_chunk_size: 200_000,
+                    split_rules: split_rules.owned().await?,
                     ..Default::default()
                 },
             )
@@ -16,6 +16,7 @@ use turbopack::module_options::{
     module_options_context::{MdxTransformOptions, OptionWebpackConditions},
 };
 use turbopack_core::{
+    chunk::ChunkSplitRule,
     issue::{Issue, IssueExt, IssueStage, OptionStyledString, StyledString},
     resolve::ResolveAliasMap,
 };
@@ -609,6 +610,8 @@ pub struct RuleConfigItemOptions {
     pub loaders: Vec<LoaderItem>,
     #[serde(default, alias = "as")]
     pub rename_as: Option<RcStr>,
+    #[serde(default)]
+    pub resolve_conditions: Vec<RcStr>,
 }
 
 #[derive(
@@ -804,6 +807,15 @@ pub struct ExperimentalConfig {
     turbopack_tree_shaking: Option<bool>,
     turbopack_scope_hoisting: Option<bool>,
     turbopack_use_system_tls_certs: Option<bool>,
+    /// The number of Node.js processes to spawn ahead of time for each pool running webpack
+    /// loaders or PostCSS.
+    turbopack_node_pool_warm_up: Option<usize>,
+    /// Compiles `.scss` and `.sass` files in Rust instead of with `sass-loader`.
+    turbopack_native_sass: Option<bool>,
+    /// Rules that move matching modules into their own client chunks in production builds.
+    turbopack_split_rules: Option<Vec<ChunkSplitRule>>,
+    /// Defines a precache manifest in the bootstrap scripts of service workers.
+    turbopack_service_worker_precache_manifest: Option<bool>,
     // Whether to enable the global-not-found convention
     global_not_found: Option<bool>,
     /// Defaults to false in development mode, true in production mode.
@@ -1120,6 +1132,9 @@ pub struct OptionSubResourceIntegrity(Option<SubResourceIntegrity>);
 #[turbo_tasks::value(transparent)]
 pub struct OptionServerActions(Option<ServerActions>);
 
+#[turbo_tasks::value(transparent)]
+pub struct ChunkSplitRules(Vec<ChunkSplitRule>);
+
 #[turbo_tasks::value(transparent)]
 pub struct OptionJsonValue(pub Option<serde_json::Value>);
 
@@ -1335,12 +1350,16 @@ impl NextConfig {
                         LoaderRuleItem {
                             loaders: transform_loaders(loaders),
                             rename_as: None,
+                            resolve_conditions: Vec::new(),
                         },
                     );
                 }
                 RuleConfigItemOrShortcut::Advanced(rule) => {
-                    if let FindRuleResult::Found(RuleConfigItemOptions { loaders, rename_as }) =
-                        find_rule(rule, &active_conditions)
+                    if let FindRuleResult::Found(RuleConfigItemOptions {
+                        loaders,
+                        rename_as,
+                        resolve_conditions,
+                    }) = find_rule(rule, &active_conditions)
                     {
                         // If the extension contains a wildcard, and the rename_as does not,
                         // emit an issue to prevent users from encountering duplicate module names.
@@ -1363,6 +1382,7 @@ impl NextConfig {
                             LoaderRuleItem {
                                 loaders: transform_loaders(loaders),
                                 rename_as: rename_as.clone(),
+                                resolve_conditions: resolve_conditions.clone(),
                             },
                         );
                     }
@@ -1691,6 +1711,35 @@ impl NextConfig {
         ))
     }
 
+    #[turbo_tasks::function]
+    pub fn turbopack_node_pool_warm_up(&self) -> Vc<usize> {
+        Vc::cell(self.experimental.turbopack_node_pool_warm_up.unwrap_or(0))
+    }
+
+    #[turbo_tasks::function]
+    pub fn turbopack_native_sass(&self) -> Vc<bool> {
+        Vc::cell(self.experimental.turbopack_native_sass.unwrap_or(false))
+    }
+
+    #[turbo_tasks::function]
+    pub fn turbopack_service_worker_precache_manifest(&self) -> Vc<bool> {
+        Vc::cell(
+            self.experimental
+                .turbopack_service_worker_precache_manifest
+                .unwrap_or(false),
+        )
+    }
+
+    #[turbo_tasks::function]
+    pub fn turbopack_split_rules(&self) -> Vc<ChunkSplitRules> {
+        Vc::cell(
+            self.experimental
+                .turbopack_split_rules
+                .clone()
+                .unwrap_or_default(),
+        )
+    }
+
     #[turbo_tasks::function]
     pub async fn turbo_scope_hoisting(&self, mode: Vc<NextMode>) -> Result<Vc<bool>> {
         Ok(Vc::cell(match *mode.await? {
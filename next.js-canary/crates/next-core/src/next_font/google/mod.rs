@@ -721,6 +721,7 @@ async fn get_mock_stylesheet(
         env,
         project_path: _,
         chunking_context,
+        pool_warm_up: _,
     } = *execution_context.await?;
     let asset_context = node_evaluate_asset_context(
         execution_context,
@@ -66,7 +66,7 @@ use crate::{
             styled_jsx::get_styled_jsx_transform_rule,
             swc_ecma_transform_plugins::get_swc_ecma_transform_plugin_rule,
         },
-        webpack_rules::webpack_loader_options,
+        webpack_rules::{sass::native_sass_options, webpack_loader_options},
     },
     transform_options::{
         get_decorators_transform_options, get_jsx_transform_options,
@@ -323,6 +323,7 @@ pub async fn get_server_resolve_options_context(
 
     let resolve_options_context = ResolveOptionsContext {
         enable_node_modules: Some(root_dir.clone()),
+        enable_yarn_pnp: Some(root_dir.clone()),
         enable_node_externals: true,
         enable_node_native_modules: true,
         module: true,
@@ -516,6 +517,15 @@ pub async fn get_server_module_options_context(
     // Now creates a webpack rules that applies to all codes.
     let enable_webpack_loaders =
         webpack_loader_options(project_path.clone(), next_config, false, conditions).await?;
+    let enable_native_sass = if *next_config.turbopack_native_sass().await? {
+        Some(
+            native_sass_options(next_config.sass_config(), project_path.clone())
+                .to_resolved()
+                .await?,
+        )
+    } else {
+        None
+    };
 
     let tree_shaking_mode_for_user_code = *next_config
         .tree_shaking_mode_for_user_code(next_mode.is_development())
@@ -583,6 +593,7 @@ pub async fn get_server_module_options_context(
         environment: Some(environment),
         css: CssOptionsContext {
             source_maps,
+            enable_native_sass,
             ..Default::default()
         },
         tree_shaking_mode: tree_shaking_mode_for_user_code,
@@ -20,7 +20,12 @@ pub async fn webpack_loader_options(
     let rules = *next_config
         .webpack_rules(condition_strs, project_path.clone())
         .await?;
-    let rules = *maybe_add_sass_loader(next_config.sass_config(), rules.map(|v| *v)).await?;
+    // The native Sass compiler handles `*.scss` and `*.sass` files, see `native_sass_options`
+    let rules = if *next_config.turbopack_native_sass().await? {
+        rules
+    } else {
+        *maybe_add_sass_loader(next_config.sass_config(), rules.map(|v| *v)).await?
+    };
     let rules = if foreign {
         rules
     } else {
@@ -1,12 +1,63 @@
-use std::mem::take;
+use std::{mem::take, path::Path};
 
 use anyhow::{Result, bail};
 use serde_json::Value as JsonValue;
-use turbo_rcstr::rcstr;
+use turbo_rcstr::{RcStr, rcstr};
 use turbo_tasks::{ResolvedVc, Vc};
-use turbopack::module_options::{LoaderRuleItem, OptionWebpackRules, WebpackRules};
+use turbo_tasks_fs::{FileSystemPath, to_sys_path};
+use turbopack::{
+    css::sass::SassOptions,
+    module_options::{LoaderRuleItem, OptionWebpackRules, WebpackRules},
+};
 use turbopack_node::transforms::webpack::WebpackLoaderItem;
 
+/// The options of the native Sass compiler from the `sassOptions` in `next.config.js`, used
+/// instead of `sass-loader` with `experimental.turbopackNativeSass`. Relative load paths are
+/// resolved from the project directory, absolute ones outside of the project file system are
+/// ignored.
+#[turbo_tasks::function]
+pub async fn native_sass_options(
+    sass_options: Vc<JsonValue>,
+    project_path: FileSystemPath,
+) -> Result<Vc<SassOptions>> {
+    let sass_options = sass_options.await?;
+    let Some(sass_options) = sass_options.as_object() else {
+        bail!("sass_options must be an object");
+    };
+    let root = project_path.root().owned().await?;
+    let root_sys_path = to_sys_path(root.clone()).await?;
+    let mut load_paths = Vec::new();
+    for path in sass_options
+        .get("loadPaths")
+        .or(sass_options.get("includePaths"))
+        .and_then(JsonValue::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(JsonValue::as_str)
+    {
+        if !Path::new(path).is_absolute() {
+            load_paths.push(project_path.join(path)?);
+        } else if let Some(relative) = root_sys_path
+            .as_ref()
+            .and_then(|root_sys_path| Path::new(path).strip_prefix(root_sys_path).ok())
+        {
+            load_paths.push(root.join(&relative.to_string_lossy().replace('\\', "/"))?);
+        }
+    }
+    // additionalData is a loader option but Next.js has it under `sassOptions` in
+    // `next.config.js`. Functions are only supported by sass-loader.
+    let additional_data = sass_options
+        .get("prependData")
+        .or(sass_options.get("additionalData"))
+        .and_then(JsonValue::as_str)
+        .map(RcStr::from);
+    Ok(SassOptions {
+        load_paths,
+        additional_data,
+    }
+    .cell())
+}
+
 #[turbo_tasks::function]
 pub async fn maybe_add_sass_loader(
     sass_options: Vc<JsonValue>,
@@ -87,6 +138,7 @@ pub async fn maybe_add_sass_loader(
                 LoaderRuleItem {
                     loaders: ResolvedVc::cell(vec![resolve_url_loader, sass_loader]),
                     rename_as: Some(format!("*{rename}").into()),
+                    resolve_conditions: Vec::new(),
                 },
             );
         }
@@ -450,4 +450,30 @@ internal
     )
   })
 
+internal
+  .command('turbo-cache-export')
+  .description(
+    'Packs the Turbopack persistent cache into a single archive file.'
+  )
+  .argument('<archive>', 'Path of the archive to create.')
+  .argument('[directory]', 'The project directory.')
+  .action((archive: string, directory?: string) => {
+    return import('../cli/internal/turbo-cache.js').then((mod) =>
+      mod.exportTurboCacheCli(archive, directory)
+    )
+  })
+
+internal
+  .command('turbo-cache-import')
+  .description(
+    'Restores the Turbopack persistent cache from an archive file, e.g. one created on another machine.'
+  )
+  .argument('<archive>', 'Path of the archive to import.')
+  .argument('[directory]', 'The project directory.')
+  .action((archive: string, directory?: string) => {
+    return import('../cli/internal/turbo-cache.js').then((mod) =>
+      mod.importTurboCacheCli(archive, directory)
+    )
+  })
+
 program.parse(process.argv)
@@ -97,6 +97,16 @@ export interface NapiWatchOptions {
    * docker).
    */
   pollIntervalMs?: number
+  /**
+   * Skip invalidation when a change event leaves the content of a file unchanged (e.g. branch
+   * switches or formatters).
+   */
+  contentHashing?: boolean
+  /**
+   * When watching starts, only invalidate files whose content changed since they were read.
+   * Requires `content_hashing`.
+   */
+  scanOnStartup?: boolean
 }
 export interface NapiProjectOptions {
   /**
@@ -364,6 +374,24 @@ export declare function projectGetSourceMapSync(
   project: { __napiType: 'Project' },
   filePath: RcStr
 ): string | null
+/**
+ * Packs the persistent cache in `dist_dir` into a single archive at `archive_path`. `root_path` is
+ * the root path of the project the cache was created for.
+ */
+export declare function exportTurbopackCache(
+  distDir: string,
+  rootPath: string,
+  archivePath: string
+): void
+/**
+ * Restores an archive created by [`export_turbopack_cache`] into the persistent cache in
+ * `dist_dir`. Paths are rewritten when the archive was created for a different `root_path`.
+ */
+export declare function importTurbopackCache(
+  distDir: string,
+  rootPath: string,
+  archivePath: string
+): void
 /**
  * A version of [`NapiNextTurbopackCallbacks`] that can accepted as an argument to a napi function.
  *
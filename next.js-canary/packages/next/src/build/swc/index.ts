@@ -14,6 +14,7 @@ import type {
   TurbopackRuleConfigItem,
   TurbopackRuleConfigItemOptions,
   TurbopackRuleConfigItemOrShortcut,
+  TurbopackSplitRule,
 } from '../../server/config-shared'
 import { isDeepStrictEqual } from 'util'
 import { type DefineEnvOptions, getDefineEnv } from '../define-env'
@@ -929,6 +930,25 @@ function bindingToApi(
       nextConfigSerializable.turbopack.conditions = serializedConditions
     }
 
+    const splitRules: TurbopackSplitRule[] | undefined =
+      nextConfigSerializable.experimental?.turbopackSplitRules
+    if (splitRules) {
+      nextConfigSerializable.experimental.turbopackSplitRules = splitRules.map(
+        (rule) => ({
+          ...rule,
+          test:
+            rule.test instanceof RegExp
+              ? {
+                  type: 'regex',
+                  value: rule.test.flags.includes('i')
+                    ? `(?i)${rule.test.source}`
+                    : rule.test.source,
+                }
+              : { type: 'glob', value: rule.test },
+        })
+      )
+    }
+
     return JSON.stringify(nextConfigSerializable, null, 2)
   }
 
@@ -1203,6 +1223,24 @@ async function loadWasm(importPath = '') {
           '`turbo.startTurbopackTraceServer` is not supported by the wasm bindings.'
         )
       },
+      exportTurbopackCache(
+        _distDir: string,
+        _rootPath: string,
+        _archivePath: string
+      ): void {
+        throw new Error(
+          '`turbo.exportTurbopackCache` is not supported by the wasm bindings.'
+        )
+      },
+      importTurbopackCache(
+        _distDir: string,
+        _rootPath: string,
+        _archivePath: string
+      ): void {
+        throw new Error(
+          '`turbo.importTurbopackCache` is not supported by the wasm bindings.'
+        )
+      },
     },
     mdx: {
       compile(src: string, options: any) {
@@ -1381,6 +1419,20 @@ function loadNative(importPath?: string) {
           )
           ;(customBindings ?? bindings).startTurbopackTraceServer(traceFilePath)
         },
+        exportTurbopackCache(distDir, rootPath, archivePath) {
+          ;(customBindings ?? bindings).exportTurbopackCache(
+            distDir,
+            rootPath,
+            archivePath
+          )
+        },
+        importTurbopackCache(distDir, rootPath, archivePath) {
+          ;(customBindings ?? bindings).importTurbopackCache(
+            distDir,
+            rootPath,
+            archivePath
+          )
+        },
       },
       mdx: {
         compile(src: string, options: any) {
@@ -17,6 +17,16 @@ export interface Binding {
       turboEngineOptions?: NapiTurboEngineOptions
     ): Promise<Project>
     startTurbopackTraceServer(traceFilePath: string): void
+    exportTurbopackCache(
+      distDir: string,
+      rootPath: string,
+      archivePath: string
+    ): void
+    importTurbopackCache(
+      distDir: string,
+      rootPath: string,
+      archivePath: string
+    ): void
 
     nextBuild?: any
   }
@@ -394,6 +404,8 @@ export interface ProjectOptions {
   watch: {
     enable: boolean
     pollIntervalMs?: number
+    contentHashing?: boolean
+    scanOnStartup?: boolean
   }
 
   /**
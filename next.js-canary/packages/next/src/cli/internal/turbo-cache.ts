@@ -0,0 +1,34 @@
+import path from 'path'
+import { loadBindings } from '../../build/swc'
+import loadConfig from '../../server/config'
+import { PHASE_PRODUCTION_BUILD } from '../../shared/lib/constants'
+import * as Log from '../../build/output/log'
+
+async function getCacheOptions(directory: string | undefined) {
+  const dir = path.resolve(directory || '.')
+  const config = await loadConfig(PHASE_PRODUCTION_BUILD, dir)
+  return {
+    distDir: path.join(dir, config.distDir),
+    rootPath: config.turbopack?.root || config.outputFileTracingRoot || dir,
+  }
+}
+
+export async function exportTurboCacheCli(
+  archive: string,
+  directory: string | undefined
+) {
+  const { distDir, rootPath } = await getCacheOptions(directory)
+  const bindings = await loadBindings()
+  bindings.turbo.exportTurbopackCache(distDir, rootPath, path.resolve(archive))
+  Log.info(`Exported the Turbopack cache to ${archive}`)
+}
+
+export async function importTurboCacheCli(
+  archive: string,
+  directory: string | undefined
+) {
+  const { distDir, rootPath } = await getCacheOptions(directory)
+  const bindings = await loadBindings()
+  bindings.turbo.importTurbopackCache(distDir, rootPath, path.resolve(archive))
+  Log.info(`Imported the Turbopack cache from ${archive}`)
+}
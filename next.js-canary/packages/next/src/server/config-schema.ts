@@ -115,6 +115,7 @@ const zTurboRuleConfigItemOptions: zod.ZodType<TurbopackRuleConfigItemOptions> =
   z.object({
     loaders: z.array(zTurboLoaderItem),
     as: z.string().optional(),
+    resolveConditions: z.array(z.string()).optional(),
   })
 
 const zTurboRuleConfigItem: zod.ZodType<TurbopackRuleConfigItem> = z.union([
@@ -461,6 +462,22 @@ export const configSchema: zod.ZodType<NextConfig> = z.lazy(() =>
         turbopackMinify: z.boolean().optional(),
         turbopackPersistentCaching: z.boolean().optional(),
         turbopackSourceMaps: z.boolean().optional(),
+        turbopackNodePoolWarmUp: z.number().int().nonnegative().optional(),
+        turbopackNativeSass: z.boolean().optional(),
+        turbopackSplitRules: z
+          .array(
+            z.strictObject({
+              name: z.string(),
+              test: z.union([z.string(), z.instanceof(RegExp)]),
+              priority: z.number().int().optional(),
+              minSize: z.number().int().nonnegative().optional(),
+              maxSize: z.number().int().nonnegative().optional(),
+              reuseExisting: z.boolean().optional(),
+              enforce: z.boolean().optional(),
+            })
+          )
+          .optional(),
+        turbopackServiceWorkerPrecacheManifest: z.boolean().optional(),
         turbopackTreeShaking: z.boolean().optional(),
         turbopackRemoveUnusedExports: z.boolean().optional(),
         turbopackScopeHoisting: z.boolean().optional(),
@@ -750,6 +767,8 @@ export const configSchema: zod.ZodType<NextConfig> = z.lazy(() =>
     watchOptions: z
       .strictObject({
         pollIntervalMs: z.number().positive().finite().optional(),
+        contentHashing: z.boolean().optional(),
+        scanOnStartup: z.boolean().optional(),
       })
       .optional(),
   })
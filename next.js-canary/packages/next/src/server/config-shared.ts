@@ -153,6 +153,43 @@ export type TurbopackRuleCondition = {
   path: string | RegExp
 }
 
+export type TurbopackSplitRule = {
+  /**
+   * The name of the rule, used as prefix for the file names of the chunks it creates.
+   */
+  name: string
+  /**
+   * Matches the paths of the modules that belong to this rule, e.g. `/node_modules/react/`. A
+   * string is a glob that has to match the whole path, a RegExp has to match a part of it. Only
+   * the `i` flag of a RegExp is supported.
+   */
+  test: string | RegExp
+  /**
+   * When a module matches multiple rules, the rule with the highest priority wins. Defaults to 0.
+   */
+  priority?: number
+  /**
+   * Only creates chunks when the matched modules of a chunk group have at least this size in
+   * bytes. Defaults to 0.
+   */
+  minSize?: number
+  /**
+   * Splits the matched modules into multiple chunks that are smaller than this size in bytes.
+   * Defaults to 0, which means no limit.
+   */
+  maxSize?: number
+  /**
+   * Splits the matched modules by the chunk groups they are shared with, so the same chunks are
+   * reused by these chunk groups. Defaults to false.
+   */
+  reuseExisting?: boolean
+  /**
+   * Always creates chunks for this rule, ignoring `minSize` and the maximum number of chunks per
+   * chunk group. Defaults to false.
+   */
+  enforce?: boolean
+}
+
 export type TurbopackRuleConfigItemOrShortcut =
   | TurbopackLoaderItem[]
   | TurbopackRuleConfigItem
@@ -160,6 +197,12 @@ export type TurbopackRuleConfigItemOrShortcut =
 export type TurbopackRuleConfigItemOptions = {
   loaders: TurbopackLoaderItem[]
   as?: string
+  /**
+   * Additional conditions for the `exports` and `imports` fields of packages
+   * when resolving the imports of the matched modules, e.g. `worker` for a
+   * subtree of the project.
+   */
+  resolveConditions?: string[]
 }
 
 export type TurbopackRuleConfigItem =
@@ -467,6 +510,35 @@ export interface ExperimentalConfig {
    */
   turbopackSourceMaps?: boolean
 
+  /**
+   * The number of Node.js processes to spawn ahead of time for each pool running webpack loaders
+   * or PostCSS. Defaults to 0.
+   */
+  turbopackNodePoolWarmUp?: number
+
+  /**
+   * Compile `.scss` and `.sass` files with the Sass compiler built into
+   * Turbopack instead of `sass-loader`. Uses the `loadPaths`/`includePaths`
+   * and string `additionalData` of `sassOptions`. Defaults to false.
+   */
+  turbopackNativeSass?: boolean
+
+  /**
+   * Rules that move matching modules into their own client chunks in production builds, similar
+   * to webpack's `splitChunks.cacheGroups`.
+   */
+  turbopackSplitRules?: TurbopackSplitRule[]
+
+  /**
+   * Defines `self.__TURBOPACK_PRECACHE_MANIFEST` in service workers, the URLs of the assets
+   * emitted for the service worker. Defaults to false.
+   *
+   * Service workers are emitted to `/_next/static/service-workers/<name>.js`. A service worker
+   * can only control pages outside of that folder when its script is served with a
+   * `Service-Worker-Allowed` header, e.g. `Service-Worker-Allowed: /` configured with `headers()`.
+   */
+  turbopackServiceWorkerPrecacheManifest?: boolean
+
   /**
    * Enable tree shaking for the turbopack dev server and build.
    */
@@ -1275,6 +1347,17 @@ export interface NextConfig extends Record<string, any> {
 
   watchOptions?: {
     pollIntervalMs?: number
+    /**
+     * Only supported with Turbopack. Skips rebuilding files when a change leaves their content
+     * unchanged, e.g. after switching branches or running a formatter.
+     */
+    contentHashing?: boolean
+    /**
+     * Only supported with Turbopack. Checks the content of all previously read files when
+     * watching starts, for file systems that don't deliver change events reliably. Requires
+     * `contentHashing`.
+     */
+    scanOnStartup?: boolean
   }
 
   /**
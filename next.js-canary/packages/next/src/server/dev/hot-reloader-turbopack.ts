@@ -228,6 +228,8 @@ export async function createHotReloaderTurbopack(
       watch: {
         enable: dev,
         pollIntervalMs: nextConfig.watchOptions?.pollIntervalMs,
+        contentHashing: nextConfig.watchOptions?.contentHashing,
+        scanOnStartup: nextConfig.watchOptions?.scanOnStartup,
       },
       dev,
       env: process.env as Record<string, string>,
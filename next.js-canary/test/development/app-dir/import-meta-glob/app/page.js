@@ -0,0 +1,16 @@
+const posts = import.meta.glob('./posts/*.js', {
+  eager: true,
+  import: 'title',
+})
+
+export default function Page() {
+  return (
+    <ul>
+      {Object.entries(posts).map(([key, title]) => (
+        <li key={key}>
+          {key}: {title}
+        </li>
+      ))}
+    </ul>
+  )
+}
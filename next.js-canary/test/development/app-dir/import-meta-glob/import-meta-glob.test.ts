@@ -0,0 +1,39 @@
+import { nextTestSetup } from 'e2e-utils'
+import { retry } from 'next-test-utils'
+
+// import.meta.glob is only supported by Turbopack
+;(process.env.IS_TURBOPACK_TEST ? describe : describe.skip)(
+  'import-meta-glob',
+  () => {
+    const { next } = nextTestSetup({
+      files: __dirname,
+    })
+
+    async function renderPosts() {
+      const $ = await next.render$('/')
+      return $('li')
+        .map((_, el) => $(el).text())
+        .get()
+    }
+
+    it('should update the matched modules when files are added or removed', async () => {
+      expect(await renderPosts()).toEqual(['./posts/first.js: First'])
+
+      await next.patchFile(
+        'app/posts/second.js',
+        "export const title = 'Second'"
+      )
+      await retry(async () => {
+        expect(await renderPosts()).toEqual([
+          './posts/first.js: First',
+          './posts/second.js: Second',
+        ])
+      })
+
+      await next.deleteFile('app/posts/first.js')
+      await retry(async () => {
+        expect(await renderPosts()).toEqual(['./posts/second.js: Second'])
+      })
+    })
+  }
+)
@@ -0,0 +1,25 @@
+'use client'
+
+import { useEffect, useState } from 'react'
+import sheet from './style.css' with { type: 'css' }
+import text from './message.txt' with { type: 'text' }
+import bytes from './data.bin' with { type: 'bytes' }
+
+export default function Client() {
+  const [adopted, setAdopted] = useState(false)
+  useEffect(() => {
+    document.adoptedStyleSheets = [...document.adoptedStyleSheets, sheet]
+    setAdopted(true)
+  }, [])
+
+  return (
+    <>
+      <p id="text">{text}</p>
+      <p id="bytes">{Array.from(bytes).join(',')}</p>
+      <p id="sheet">{adopted ? 'adopted' : 'pending'}</p>
+      <button id="button" className="button">
+        Button
+      </button>
+    </>
+  )
+}
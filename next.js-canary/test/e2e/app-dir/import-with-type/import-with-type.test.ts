@@ -0,0 +1,69 @@
+import { nextTestSetup } from 'e2e-utils'
+import { retry } from 'next-test-utils'
+
+// Import attributes other than `type: "json"` are only supported by Turbopack
+;(process.env.IS_TURBOPACK_TEST ? describe : describe.skip)(
+  'import-with-type',
+  () => {
+    const { next, isNextDev } = nextTestSetup({
+      files: __dirname,
+      skipDeployment: true,
+    })
+
+    it('should import text and bytes during server rendering', async () => {
+      const $ = await next.render$('/')
+
+      expect($('#text').text()).toBe('Hello world')
+      expect($('#bytes').text()).toBe('0,1,2,255')
+    })
+
+    it('should import text, bytes and a constructable style sheet in the browser', async () => {
+      const browser = await next.browser('/')
+
+      await retry(async () => {
+        expect(await browser.elementByCss('#sheet').text()).toBe('adopted')
+      })
+      expect(await browser.elementByCss('#text').text()).toBe('Hello world')
+      expect(await browser.elementByCss('#bytes').text()).toBe('0,1,2,255')
+      expect(
+        await browser.eval(
+          `document.adoptedStyleSheets[0] instanceof CSSStyleSheet`
+        )
+      ).toBe(true)
+      expect(
+        await browser.eval(
+          `getComputedStyle(document.getElementById('button')).color`
+        )
+      ).toBe('rgb(255, 0, 0)')
+    })
+
+    if (isNextDev) {
+      it('should update the adopted style sheet in place on changes', async () => {
+        const browser = await next.browser('/')
+        await retry(async () => {
+          expect(await browser.elementByCss('#sheet').text()).toBe('adopted')
+        })
+        // Detects full reloads
+        await browser.eval(`window.beforeUpdate = true`)
+
+        await next.patchFile(
+          'app/style.css',
+          (content) => content.replace('rgb(255, 0, 0)', 'rgb(0, 0, 255)'),
+          async () => {
+            await retry(async () => {
+              expect(
+                await browser.eval(
+                  `getComputedStyle(document.getElementById('button')).color`
+                )
+              ).toBe('rgb(0, 0, 255)')
+            })
+            expect(await browser.eval(`window.beforeUpdate`)).toBe(true)
+            expect(
+              await browser.eval(`document.adoptedStyleSheets.length`)
+            ).toBe(1)
+          }
+        )
+      })
+    }
+  }
+)
@@ -0,0 +1,11 @@
+module.exports = async function loader() {
+  const { greeting } = await this.importModule('./greeting.js')
+  const source = await new Promise((resolve, reject) => {
+    this.loadModule('./greeting.js', (err, source) =>
+      err ? reject(err) : resolve(String(source))
+    )
+  })
+  this.emitFile('loader-api/emitted.txt', `${greeting} from emitFile`)
+  return `export const greeting = ${JSON.stringify(greeting)}
+export const source = ${JSON.stringify(source)}`
+}
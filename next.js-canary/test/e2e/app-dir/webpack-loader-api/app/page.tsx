@@ -0,0 +1,11 @@
+// @ts-expect-error -- ignore
+import { greeting, source } from './data.api'
+
+export default function Page() {
+  return (
+    <>
+      <p id="greeting">{greeting}</p>
+      <pre id="source">{source}</pre>
+    </>
+  )
+}
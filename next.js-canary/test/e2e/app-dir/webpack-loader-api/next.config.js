@@ -0,0 +1,13 @@
+/** @type {import('next').NextConfig} */
+const nextConfig = {
+  turbopack: {
+    rules: {
+      '*.api': {
+        loaders: [require.resolve('./api-loader.js')],
+        as: '*.js',
+      },
+    },
+  },
+}
+
+module.exports = nextConfig
@@ -0,0 +1,45 @@
+import { nextTestSetup } from 'e2e-utils'
+import { retry } from 'next-test-utils'
+
+// The emitted files are placed in the output root of Turbopack
+;(process.env.IS_TURBOPACK_TEST ? describe : describe.skip)(
+  'webpack-loader-api',
+  () => {
+    const { next, isNextDev } = nextTestSetup({
+      files: __dirname,
+      skipDeployment: true,
+    })
+
+    it('should import and load modules', async () => {
+      const $ = await next.render$('/')
+
+      expect($('#greeting').text()).toBe('hello')
+      expect($('#source').text()).toContain("export const greeting = 'hello'")
+    })
+
+    it('should emit files referenced by the module', async () => {
+      await next.render$('/')
+
+      await retry(async () => {
+        expect(await next.readFile('.next/loader-api/emitted.txt')).toBe(
+          'hello from emitFile'
+        )
+      })
+    })
+
+    if (!isNextDev) return
+
+    it('should import the updated module after changes', async () => {
+      await next.patchFile(
+        'app/greeting.js',
+        "export const greeting = 'updated'",
+        async () => {
+          await retry(async () => {
+            const $ = await next.render$('/')
+            expect($('#greeting').text()).toBe('updated')
+          })
+        }
+      )
+    })
+  }
+)
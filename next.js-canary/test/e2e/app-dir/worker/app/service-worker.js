@@ -0,0 +1,17 @@
+// Service workers don't support dynamic imports
+import dep from './worker-dep'
+
+self.addEventListener('install', () => {
+  self.skipWaiting()
+})
+
+self.addEventListener('activate', (event) => {
+  event.waitUntil(self.clients.claim())
+})
+
+self.addEventListener('message', (event) => {
+  event.source.postMessage({
+    dep,
+    precacheManifest: self.__TURBOPACK_PRECACHE_MANIFEST ?? null,
+  })
+})
@@ -0,0 +1,37 @@
+'use client'
+import { useState } from 'react'
+
+export default function Home() {
+  const [state, setState] = useState('default')
+  const [scriptPath, setScriptPath] = useState('')
+  const [precacheManifest, setPrecacheManifest] = useState('')
+  return (
+    <div>
+      <button
+        onClick={async () => {
+          navigator.serviceWorker.addEventListener('message', (event) => {
+            setState(event.data.dep)
+            setPrecacheManifest(JSON.stringify(event.data.precacheManifest))
+          })
+          const registration = await navigator.serviceWorker.register(
+            new URL('../service-worker', import.meta.url),
+            { scope: '/' }
+          )
+          const worker =
+            registration.installing ||
+            registration.waiting ||
+            registration.active
+          setScriptPath(new URL(worker.scriptURL).pathname)
+          const { active } = await navigator.serviceWorker.ready
+          active.postMessage('ping')
+        }}
+      >
+        Get service worker data
+      </button>
+      <p>Worker state: </p>
+      <p id="worker-state">{state}</p>
+      <p id="worker-script">{scriptPath}</p>
+      <p id="precache-manifest">{precacheManifest}</p>
+    </div>
+  )
+}
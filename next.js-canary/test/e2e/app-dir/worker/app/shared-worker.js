@@ -0,0 +1,6 @@
+self.addEventListener('connect', (event) => {
+  const port = event.ports[0]
+  import('./worker-dep').then((mod) => {
+    port.postMessage('shared-worker.js:' + mod.default)
+  })
+})
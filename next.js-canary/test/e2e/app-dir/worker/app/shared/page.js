@@ -0,0 +1,25 @@
+'use client'
+import { useState } from 'react'
+
+export default function Home() {
+  const [state, setState] = useState('default')
+  return (
+    <div>
+      <button
+        onClick={() => {
+          const worker = new SharedWorker(
+            new URL('../shared-worker', import.meta.url)
+          )
+          worker.port.addEventListener('message', (event) => {
+            setState(event.data)
+          })
+          worker.port.start()
+        }}
+      >
+        Get shared worker data
+      </button>
+      <p>Worker state: </p>
+      <p id="worker-state">{state}</p>
+    </div>
+  )
+}
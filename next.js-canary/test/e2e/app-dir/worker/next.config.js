@@ -1,6 +1,19 @@
 /**
  * @type {import('next').NextConfig}
  */
-const nextConfig = {}
+const nextConfig = {
+  experimental: {
+    turbopackServiceWorkerPrecacheManifest: true,
+  },
+  async headers() {
+    return [
+      {
+        // Allows the service worker to control all pages
+        source: '/_next/static/:path*',
+        headers: [{ key: 'Service-Worker-Allowed', value: '/' }],
+      },
+    ]
+  },
+}
 
 module.exports = nextConfig
@@ -2,7 +2,7 @@ import { nextTestSetup } from 'e2e-utils'
 import { retry } from 'next-test-utils'
 
 describe('app dir - workers', () => {
-  const { next, skipped } = nextTestSetup({
+  const { next, skipped, isTurbopack } = nextTestSetup({
     files: __dirname,
     skipDeployment: true,
   })
@@ -49,4 +49,46 @@ describe('app dir - workers', () => {
       )
     )
   })
+
+  it('should support shared workers with dynamic imports', async () => {
+    const browser = await next.browser('/shared')
+    expect(await browser.elementByCss('#worker-state').text()).toBe('default')
+
+    await browser.elementByCss('button').click()
+
+    await retry(async () =>
+      expect(await browser.elementByCss('#worker-state').text()).toBe(
+        'shared-worker.js:worker-dep'
+      )
+    )
+  })
+
+  it('should support service workers', async () => {
+    const browser = await next.browser('/service')
+    expect(await browser.elementByCss('#worker-state').text()).toBe('default')
+
+    await browser.elementByCss('button').click()
+
+    await retry(async () =>
+      expect(await browser.elementByCss('#worker-state').text()).toBe(
+        'worker-dep'
+      )
+    )
+
+    if (isTurbopack) {
+      // The path of the script must not change between builds
+      expect(await browser.elementByCss('#worker-script').text()).toBe(
+        '/_next/static/service-workers/service-worker.js'
+      )
+
+      const precacheManifest = JSON.parse(
+        await browser.elementByCss('#precache-manifest').text()
+      )
+      expect(precacheManifest).toEqual(
+        expect.arrayContaining([
+          expect.stringMatching(/\/_next\/static\/chunks\/.+\.js$/),
+        ])
+      )
+    }
+  })
 })
@@ -1,22 +1,94 @@
 #![feature(iter_intersperse)]
 
-use std::path::PathBuf;
+use std::{collections::BTreeMap, fmt::Write, path::PathBuf};
 
 use anyhow::{Context, Result, bail};
-use turbo_persistence::{MetaFileEntryInfo, TurboPersistence};
+use clap::{Parser, Subcommand, ValueEnum};
+use turbo_persistence::{
+    MetaFileEntryInfo, OrphanedFileInfo, SstBlockSizeInfo, TurboPersistence, VerifyIssue,
+};
+
+/// Tools to inspect and maintain a TurboPersistence database directory.
+#[derive(Parser)]
+#[command()]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Prints the meta files and SST files of the database.
+    Inspect {
+        /// The path to the TurboPersistence directory.
+        path: PathBuf,
+    },
+    /// Reads all SST files and checks them for corruption. The SST format has no checksums, so
+    /// this decompresses all blocks and validates the entries against the meta files and AMQFs.
+    Verify {
+        /// The path to the TurboPersistence directory.
+        path: PathBuf,
+    },
+    /// Prints all key value pairs of a key family.
+    Dump {
+        /// The path to the TurboPersistence directory.
+        path: PathBuf,
+        /// The key family to dump.
+        #[arg(long)]
+        family: usize,
+        /// Only dump keys starting with this prefix (hex encoded).
+        #[arg(long)]
+        prefix: Option<String>,
+        #[arg(long, value_enum, default_value_t = DumpFormat::Hex)]
+        format: DumpFormat,
+    },
+    /// Prints size, compression ratio and obsolete file statistics per key family.
+    Stats {
+        /// The path to the TurboPersistence directory.
+        path: PathBuf,
+    },
+    /// Deletes SST and blob files that are no longer referenced by the database.
+    Gc {
+        /// The path to the TurboPersistence directory.
+        path: PathBuf,
+        /// Only print the files that would be deleted.
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DumpFormat {
+    /// One `key = value` line per entry, both hex encoded.
+    Hex,
+    /// One JSON object per line with hex encoded `key` and `value`.
+    Json,
+}
 
 fn main() -> Result<()> {
-    // Get CLI argument
-    let path = PathBuf::from(
-        std::env::args()
-            .nth(1)
-            .context("Please provide a path to the TurboPersistence directory")?,
-    );
+    let cli = Cli::parse();
+    match cli.command {
+        Commands::Inspect { path } => inspect(&open_read_only(path)?),
+        Commands::Verify { path } => verify(&open_read_only(path)?),
+        Commands::Dump {
+            path,
+            family,
+            prefix,
+            format,
+        } => dump(&open_read_only(path)?, family, prefix, format),
+        Commands::Stats { path } => stats(&open_read_only(path)?),
+        Commands::Gc { path, dry_run } => gc(path, dry_run),
+    }
+}
+
+fn open_read_only(path: PathBuf) -> Result<TurboPersistence> {
     if !path.exists() {
         bail!("The provided path does not exist: {}", path.display());
     }
+    TurboPersistence::open_read_only(path)
+}
 
-    let db = TurboPersistence::open_read_only(path)?;
+fn inspect(db: &TurboPersistence) -> Result<()> {
     let meta_info = db
         .meta_info()
         .context("Failed to retrieve meta information")?;
@@ -70,3 +142,145 @@ fn main() -> Result<()> {
     }
     Ok(())
 }
+
+fn verify(db: &TurboPersistence) -> Result<()> {
+    let issues = db.verify().context("Failed to verify database")?;
+    for VerifyIssue {
+        meta_sequence_number,
+        sst_sequence_number,
+        message,
+    } in issues.iter()
+    {
+        println!("{meta_sequence_number:08}.meta {sst_sequence_number:08}.sst: {message}");
+    }
+    if !issues.is_empty() {
+        bail!("{} corrupted SST files found", issues.len());
+    }
+    println!("No issues found");
+    Ok(())
+}
+
+fn dump(
+    db: &TurboPersistence,
+    family: usize,
+    prefix: Option<String>,
+    format: DumpFormat,
+) -> Result<()> {
+    let iter = match prefix {
+        Some(prefix) => db.prefix_iter(family, &decode_hex(&prefix)?)?,
+        None => db.iter(family)?,
+    };
+    for entry in iter {
+        let (key, value) = entry?;
+        match format {
+            DumpFormat::Hex => println!("{} = {}", encode_hex(&key), encode_hex(&value)),
+            DumpFormat::Json => println!(
+                r#"{{"key":"{}","value":"{}"}}"#,
+                encode_hex(&key),
+                encode_hex(&value)
+            ),
+        }
+    }
+    Ok(())
+}
+
+fn stats(db: &TurboPersistence) -> Result<()> {
+    #[derive(Default)]
+    struct FamilyStats {
+        sst_files: usize,
+        sst_size: u64,
+        compressed_size: u64,
+        uncompressed_size: u64,
+    }
+
+    let mut families = BTreeMap::<u32, FamilyStats>::new();
+    let meta_info = db
+        .meta_info()
+        .context("Failed to retrieve meta information")?;
+    for meta_file in meta_info {
+        let family = families.entry(meta_file.family).or_default();
+        family.sst_files += meta_file.entries.len();
+        family.sst_size += meta_file.entries.iter().map(|e| e.sst_size).sum::<u64>();
+    }
+    for SstBlockSizeInfo {
+        family,
+        compressed_size,
+        uncompressed_size,
+        ..
+    } in db
+        .block_size_info()
+        .context("Failed to retrieve block sizes")?
+    {
+        let family = families.entry(family).or_default();
+        family.compressed_size += compressed_size;
+        family.uncompressed_size += uncompressed_size;
+    }
+    for (family, stats) in families {
+        println!(
+            "FAMILY {family}: {} SST files, {} MiB, compression ratio {:.2} ({} MiB uncompressed)",
+            stats.sst_files,
+            stats.sst_size / 1024 / 1024,
+            stats.uncompressed_size as f64 / stats.compressed_size.max(1) as f64,
+            stats.uncompressed_size / 1024 / 1024,
+        );
+    }
+
+    let orphaned_files = db
+        .orphaned_files()
+        .context("Failed to find orphaned files")?;
+    let (sst, blob): (Vec<_>, Vec<_>) = orphaned_files
+        .iter()
+        .partition(|file| file.path.extension().is_some_and(|ext| ext == "sst"));
+    println!(
+        "OBSOLETE: {} SST files = {} MiB, {} blob files = {} MiB",
+        sst.len(),
+        sst.iter().map(|file| file.size).sum::<u64>() / 1024 / 1024,
+        blob.len(),
+        blob.iter().map(|file| file.size).sum::<u64>() / 1024 / 1024,
+    );
+    Ok(())
+}
+
+fn gc(path: PathBuf, dry_run: bool) -> Result<()> {
+    let files = if dry_run {
+        open_read_only(path)?.orphaned_files()
+    } else {
+        if !path.exists() {
+            bail!("The provided path does not exist: {}", path.display());
+        }
+        TurboPersistence::open(path)?.delete_orphaned_files()
+    }
+    .context("Failed to collect orphaned files")?;
+    for OrphanedFileInfo { path, size } in files.iter() {
+        println!("{} ({} KiB)", path.display(), size / 1024);
+    }
+    println!(
+        "{} {} files = {} MiB",
+        if dry_run { "Would delete" } else { "Deleted" },
+        files.len(),
+        files.iter().map(|file| file.size).sum::<u64>() / 1024 / 1024
+    );
+    Ok(())
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        write!(result, "{byte:02x}").unwrap();
+    }
+    result
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        bail!("Hex string must have an even length: {hex}");
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .with_context(|| format!("Invalid hex string: {hex}"))
+        })
+        .collect()
+}
@@ -0,0 +1,178 @@
+use std::{
+    cmp::max,
+    sync::Arc,
+    thread::{JoinHandle, available_parallelism},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use parking_lot::{Condvar, Mutex};
+
+use crate::{TurboPersistence, compaction::selector::CompactConfig, db::BackgroundCompactionRound};
+
+/// Configuration for [`TurboPersistence::start_background_compaction`].
+#[derive(Clone)]
+pub struct BackgroundCompactionConfig {
+    /// The configuration for a single compaction round. `max_merge_segment_count` and
+    /// `max_merge_bytes` limit the amount of work done per round, and therefore how long write
+    /// operations might need to wait for a running round.
+    pub compact_config: CompactConfig,
+
+    /// The number of threads used for compaction. Limits the CPU usage.
+    pub threads: usize,
+
+    /// The maximum number of bytes written per second on average. Limits the I/O usage. `None`
+    /// means unlimited.
+    pub max_bytes_per_second: Option<u64>,
+
+    /// How often the database is checked for the need of compaction.
+    pub check_interval: Duration,
+
+    /// Compaction is paused until no write batch has been committed for this duration.
+    pub write_cooldown: Duration,
+
+    /// Compaction is performed when the coverage of a key family exceeds this value. The coverage
+    /// is the average number of SST files that need to be read to find a key.
+    pub min_coverage: f32,
+
+    /// Compaction is performed when the possible duplication over all key families exceeds this
+    /// number of bytes.
+    pub min_duplication_bytes: u64,
+}
+
+impl Default for BackgroundCompactionConfig {
+    fn default() -> Self {
+        const MB: u64 = 1024 * 1024;
+        Self {
+            compact_config: CompactConfig {
+                max_merge_segment_count: 4,
+                ..Default::default()
+            },
+            threads: max(1, available_parallelism().map_or(1, |c| c.get() / 4)),
+            max_bytes_per_second: Some(100 * MB),
+            check_interval: Duration::from_secs(1),
+            write_cooldown: Duration::from_secs(5),
+            min_coverage: 4.0,
+            min_duplication_bytes: 10 * MB,
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    paused: bool,
+    stopped: bool,
+}
+
+/// A handle to a running background compaction. Compaction is stopped when the handle is dropped.
+pub struct BackgroundCompaction {
+    state: Arc<(Mutex<State>, Condvar)>,
+    join_handle: Option<JoinHandle<Result<()>>>,
+}
+
+impl BackgroundCompaction {
+    pub(crate) fn start(
+        db: Arc<TurboPersistence>,
+        config: BackgroundCompactionConfig,
+    ) -> Result<Self> {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(config.threads)
+            .thread_name(|i| format!("turbo-persistence compaction {i}"))
+            .build()
+            .context("Failed to create thread pool for background compaction")?;
+        let state = Arc::new((Mutex::new(State::default()), Condvar::new()));
+        let thread_state = state.clone();
+        let join_handle = std::thread::Builder::new()
+            .name("turbo-persistence compaction".to_string())
+            .spawn(move || run(&db, &config, &pool, &thread_state))
+            .context("Failed to spawn background compaction thread")?;
+        Ok(Self {
+            state,
+            join_handle: Some(join_handle),
+        })
+    }
+
+    /// Pauses the background compaction after the current round, e.g. during phases with heavy
+    /// writes.
+    pub fn pause(&self) {
+        self.state.0.lock().paused = true;
+    }
+
+    /// Resumes a paused background compaction.
+    pub fn resume(&self) {
+        self.state.0.lock().paused = false;
+        self.state.1.notify_all();
+    }
+
+    /// Stops the background compaction and waits for the current round to finish. Returns the
+    /// error that stopped the compaction, if any.
+    pub fn stop(mut self) -> Result<()> {
+        self.stop_and_join()
+    }
+
+    fn stop_and_join(&mut self) -> Result<()> {
+        self.state.0.lock().stopped = true;
+        self.state.1.notify_all();
+        if let Some(join_handle) = self.join_handle.take() {
+            join_handle.join().unwrap()?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for BackgroundCompaction {
+    fn drop(&mut self) {
+        let _ = self.stop_and_join();
+    }
+}
+
+/// Waits until the given time or until the compaction is stopped. Returns true if it was stopped.
+fn wait_until(state: &(Mutex<State>, Condvar), until: Instant) -> bool {
+    let mut guard = state.0.lock();
+    while !guard.stopped && (guard.paused || Instant::now() < until) {
+        if guard.paused {
+            state.1.wait(&mut guard);
+        } else {
+            state.1.wait_until(&mut guard, until);
+        }
+    }
+    guard.stopped
+}
+
+fn run(
+    db: &TurboPersistence,
+    config: &BackgroundCompactionConfig,
+    pool: &rayon::ThreadPool,
+    state: &(Mutex<State>, Condvar),
+) -> Result<()> {
+    let mut next_check = Instant::now() + config.check_interval;
+    loop {
+        if wait_until(state, next_check) {
+            return Ok(());
+        }
+        let start = Instant::now();
+        let round = pool.install(|| db.background_compaction_round(config))?;
+        next_check = match round {
+            BackgroundCompactionRound::Skipped | BackgroundCompactionRound::NotNeeded => {
+                Instant::now() + config.check_interval
+            }
+            BackgroundCompactionRound::Compacted { bytes_written: 0 } => {
+                // Nothing could be merged with the current configuration
+                Instant::now() + config.check_interval
+            }
+            BackgroundCompactionRound::Compacted { bytes_written } => {
+                // Continue immediately when there is more to compact, but stay within the I/O
+                // budget.
+                match config.max_bytes_per_second {
+                    Some(max_bytes_per_second) if max_bytes_per_second > 0 => {
+                        start
+                            + Duration::from_secs_f64(
+                                bytes_written as f64 / max_bytes_per_second as f64,
+                            )
+                    }
+                    _ => Instant::now(),
+                }
+            }
+        };
+    }
+}
@@ -0,0 +1,120 @@
+use anyhow::{Result, bail};
+
+use crate::{
+    constants::{
+        AMQF_CACHE_SIZE, DEFAULT_BLOCK_SIZE, KEY_BLOCK_CACHE_SIZE, MAX_BLOCK_SIZE, MIN_BLOCK_SIZE,
+        VALUE_BLOCK_CACHE_SIZE,
+    },
+    static_sorted_file::{CODEC_LZ4, CODEC_NONE, CODEC_ZSTD},
+};
+
+/// The compression codec for the blocks of SST files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Codec {
+    /// No compression. Best for values that don't compress, e.g. images or wasm binaries.
+    None,
+    /// LZ4 compression. Fast compression and decompression.
+    Lz4,
+    /// Zstd compression with the given level. Smaller files, but slower than LZ4.
+    Zstd { level: i32 },
+}
+
+impl Codec {
+    /// The identifier of the codec that is stored in the meta file.
+    pub(crate) fn id(&self) -> u8 {
+        match self {
+            Codec::None => CODEC_NONE,
+            Codec::Lz4 => CODEC_LZ4,
+            Codec::Zstd { .. } => CODEC_ZSTD,
+        }
+    }
+}
+
+/// Configuration for a key family.
+#[derive(Clone, Debug)]
+pub struct FamilyConfig {
+    /// The compression codec for the key and value blocks of new SST files. Existing SST files
+    /// keep their codec until they are compacted.
+    pub codec: Codec,
+
+    /// The maximum uncompressed size of key blocks and small value blocks in bytes. Larger blocks
+    /// compress better, but more data need to be read and decompressed to access a single entry.
+    /// Must be at least 8 KiB.
+    pub block_size: usize,
+
+    /// Whether compression dictionaries are trained for every new SST file. They improve the
+    /// compression of small blocks, but training costs time on writing.
+    pub compression_dictionary: bool,
+
+    /// Maximum RAM bytes for decompressed key blocks of this family.
+    pub key_block_cache_size: u64,
+
+    /// Maximum RAM bytes for decompressed value blocks of this family.
+    pub value_block_cache_size: u64,
+}
+
+impl Default for FamilyConfig {
+    fn default() -> Self {
+        Self {
+            codec: Codec::Lz4,
+            block_size: DEFAULT_BLOCK_SIZE,
+            compression_dictionary: true,
+            key_block_cache_size: KEY_BLOCK_CACHE_SIZE,
+            value_block_cache_size: VALUE_BLOCK_CACHE_SIZE,
+        }
+    }
+}
+
+/// Configuration for a database. See [`crate::TurboPersistence::open_with_config`].
+#[derive(Clone, Debug)]
+pub struct DbConfig {
+    /// Configuration per key family, indexed by the family. Each of these families has its own
+    /// block caches.
+    pub families: Vec<FamilyConfig>,
+
+    /// Configuration for all families that are not listed in `families`. These families share
+    /// their block caches.
+    pub default_family: FamilyConfig,
+
+    /// Maximum RAM bytes for the AMQF cache. It's shared by all families.
+    pub amqf_cache_size: u64,
+}
+
+impl Default for DbConfig {
+    fn default() -> Self {
+        Self {
+            families: Vec::new(),
+            default_family: FamilyConfig::default(),
+            amqf_cache_size: AMQF_CACHE_SIZE,
+        }
+    }
+}
+
+impl DbConfig {
+    /// Returns the configuration for a key family.
+    pub fn family(&self, family: u32) -> &FamilyConfig {
+        self.families
+            .get(family as usize)
+            .unwrap_or(&self.default_family)
+    }
+
+    pub(crate) fn validate(&self) -> Result<()> {
+        for (family, config) in self
+            .families
+            .iter()
+            .enumerate()
+            .map(|(family, config)| (Some(family), config))
+            .chain([(None, &self.default_family)])
+        {
+            if !(MIN_BLOCK_SIZE..=MAX_BLOCK_SIZE).contains(&config.block_size) {
+                bail!(
+                    "Invalid block size {} for {} (must be between {MIN_BLOCK_SIZE} and \
+                     {MAX_BLOCK_SIZE})",
+                    config.block_size,
+                    family.map_or_else(|| "default family".to_string(), |f| format!("family {f}"))
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -5,6 +5,18 @@ pub const MAX_MEDIUM_VALUE_SIZE: usize = 64 * 1024 * 1024;
 // Note this must fit into 2 bytes length
 pub const MAX_SMALL_VALUE_SIZE: usize = 64 * 1024 - 1;
 
+/// Default maximum bytes of key blocks and small value blocks
+pub const DEFAULT_BLOCK_SIZE: usize = 16 * 1024;
+
+/// Minimum configurable block size
+// Note this keeps the number of blocks of the largest SST files (see
+// DATA_THRESHOLD_PER_COMPACTED_FILE) within 2 bytes block indices
+pub const MIN_BLOCK_SIZE: usize = 8 * 1024;
+
+/// Maximum configurable block size
+// Note this must fit into 3 bytes length
+pub const MAX_BLOCK_SIZE: usize = (1 << 24) - 1;
+
 /// Maximum number of entries per SST file
 pub const MAX_ENTRIES_PER_INITIAL_FILE: usize = 256 * 1024;
 
@@ -21,14 +33,14 @@ pub const DATA_THRESHOLD_PER_COMPACTED_FILE: usize = 256 * 1024 * 1024;
 /// MAX_ENTRIES_PER_INITIAL_FILE and DATA_THRESHOLD_PER_INITIAL_FILE.
 pub const THREAD_LOCAL_SIZE_SHIFT: usize = 7;
 
-/// Maximum RAM bytes for AMQF cache
+/// Default maximum RAM bytes for AMQF cache
 pub const AMQF_CACHE_SIZE: u64 = 300 * 1024 * 1024;
 pub const AMQF_AVG_SIZE: usize = 37399;
 
-/// Maximum RAM bytes for key block cache
+/// Default maximum RAM bytes for key block cache
 pub const KEY_BLOCK_CACHE_SIZE: u64 = 400 * 1024 * 1024;
 pub const KEY_BLOCK_AVG_SIZE: usize = 16 * 1024;
 
-/// Maximum RAM bytes for value block cache
+/// Default maximum RAM bytes for value block cache
 pub const VALUE_BLOCK_CACHE_SIZE: u64 = 300 * 1024 * 1024;
 pub const VALUE_BLOCK_AVG_SIZE: usize = 132000;
@@ -1,5 +1,6 @@
 use std::{
     borrow::Cow,
+    cell::Cell,
     collections::HashSet,
     fs::{self, File, OpenOptions, ReadDir},
     io::{BufWriter, Write},
@@ -10,6 +11,7 @@ use std::{
         Arc,
         atomic::{AtomicBool, AtomicU32, Ordering},
     },
+    time::Instant,
 };
 
 use anyhow::{Context, Result, bail};
@@ -17,7 +19,7 @@ use byteorder::{BE, ReadBytesExt, WriteBytesExt};
 use jiff::Timestamp;
 use lzzzz::lz4::decompress;
 use memmap2::Mmap;
-use parking_lot::{Mutex, RwLock};
+use parking_lot::{Mutex, RwLock, RwLockReadGuard};
 use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
 use tracing::Span;
 
@@ -25,19 +27,20 @@ pub use crate::compaction::selector::CompactConfig;
 use crate::{
     QueryKey,
     arc_slice::ArcSlice,
+    background_compaction::{BackgroundCompaction, BackgroundCompactionConfig},
     compaction::selector::{Compactable, compute_metrics, get_merge_segments},
+    config::{DbConfig, FamilyConfig},
     constants::{
-        AMQF_AVG_SIZE, AMQF_CACHE_SIZE, DATA_THRESHOLD_PER_COMPACTED_FILE, KEY_BLOCK_AVG_SIZE,
-        KEY_BLOCK_CACHE_SIZE, MAX_ENTRIES_PER_COMPACTED_FILE, VALUE_BLOCK_AVG_SIZE,
-        VALUE_BLOCK_CACHE_SIZE,
+        AMQF_AVG_SIZE, DATA_THRESHOLD_PER_COMPACTED_FILE, KEY_BLOCK_AVG_SIZE,
+        MAX_ENTRIES_PER_COMPACTED_FILE, VALUE_BLOCK_AVG_SIZE,
     },
     key::{StoreKey, hash_key},
     lookup_entry::{LookupEntry, LookupValue},
     merge_iter::MergeIter,
-    meta_file::{AmqfCache, MetaFile, MetaLookupResult, StaticSortedFileRange},
+    meta_file::{AmqfCache, MetaEntry, MetaFile, MetaLookupResult, StaticSortedFileRange},
     meta_file_builder::MetaFileBuilder,
     sst_filter::SstFilter,
-    static_sorted_file::{BlockCache, SstLookupResult},
+    static_sorted_file::{BlockCache, SstLookupResult, StaticSortedFileIter},
     static_sorted_file_builder::{StaticSortedFileBuilderMeta, write_static_stored_file},
     write_batch::{FinishResult, WriteBatch},
 };
@@ -55,21 +58,33 @@ pub struct CacheStatistics {
 
 #[cfg(feature = "stats")]
 impl CacheStatistics {
-    fn new<Key, Val, We, B, L>(cache: &quick_cache::sync::Cache<Key, Val, We, B, L>) -> Self
+    /// Combines the statistics of multiple caches.
+    fn new<'a, Key, Val, We, B, L>(
+        caches: impl IntoIterator<Item = &'a quick_cache::sync::Cache<Key, Val, We, B, L>>,
+    ) -> Self
     where
-        Key: Eq + std::hash::Hash,
-        Val: Clone,
-        We: quick_cache::Weighter<Key, Val> + Clone,
-        B: std::hash::BuildHasher + Clone,
-        L: quick_cache::Lifecycle<Key, Val> + Clone,
+        Key: Eq + std::hash::Hash + 'a,
+        Val: Clone + 'a,
+        We: quick_cache::Weighter<Key, Val> + Clone + 'a,
+        B: std::hash::BuildHasher + Clone + 'a,
+        L: quick_cache::Lifecycle<Key, Val> + Clone + 'a,
     {
-        let size = cache.weight();
-        let hits = cache.hits();
-        let misses = cache.misses();
+        let mut size = 0;
+        let mut capacity = 0;
+        let mut items = 0;
+        let mut hits = 0;
+        let mut misses = 0;
+        for cache in caches {
+            size += cache.weight();
+            capacity += cache.capacity();
+            items += cache.len();
+            hits += cache.hits();
+            misses += cache.misses();
+        }
         Self {
             hit_rate: hits as f32 / (hits + misses) as f32,
-            fill: size as f32 / cache.capacity() as f32,
-            items: cache.len(),
+            fill: size as f32 / capacity as f32,
+            items,
             size,
             hits,
             misses,
@@ -91,6 +106,15 @@ pub struct Statistics {
     pub miss_range: u64,
     pub miss_amqf: u64,
     pub miss_key: u64,
+    /// Number of compaction rounds performed by the background compaction.
+    pub background_compaction_rounds: u64,
+    /// Number of background compaction checks that were skipped because of recent writes.
+    pub background_compaction_skipped: u64,
+    /// Bytes written by the background compaction.
+    pub background_compaction_bytes_written: u64,
+    /// Possible duplication in bytes at the last background compaction check. This is the
+    /// remaining work for the background compaction.
+    pub pending_duplication_bytes: u64,
 }
 
 #[cfg(feature = "stats")]
@@ -104,6 +128,39 @@ struct TrackedStats {
     miss_amqf: std::sync::atomic::AtomicU64,
     miss_key: std::sync::atomic::AtomicU64,
     miss_global: std::sync::atomic::AtomicU64,
+    background_compaction_rounds: std::sync::atomic::AtomicU64,
+    background_compaction_skipped: std::sync::atomic::AtomicU64,
+    background_compaction_bytes_written: std::sync::atomic::AtomicU64,
+    pending_duplication_bytes: std::sync::atomic::AtomicU64,
+}
+
+/// The block caches of a key family.
+struct FamilyCaches {
+    /// A cache for decompressed key blocks.
+    key_block_cache: BlockCache,
+    /// A cache for decompressed value blocks.
+    value_block_cache: BlockCache,
+}
+
+impl FamilyCaches {
+    fn new(config: &FamilyConfig) -> Self {
+        Self {
+            key_block_cache: BlockCache::with(
+                config.key_block_cache_size as usize / KEY_BLOCK_AVG_SIZE,
+                config.key_block_cache_size,
+                Default::default(),
+                Default::default(),
+                Default::default(),
+            ),
+            value_block_cache: BlockCache::with(
+                config.value_block_cache_size as usize / VALUE_BLOCK_AVG_SIZE,
+                config.value_block_cache_size,
+                Default::default(),
+                Default::default(),
+                Default::default(),
+            ),
+        }
+    }
 }
 
 /// TurboPersistence is a persistent key-value store. It is limited to a single writer at a time
@@ -119,12 +176,19 @@ pub struct TurboPersistence {
     /// A flag to indicate if a write operation is currently active. Prevents multiple concurrent
     /// write operations.
     active_write_operation: AtomicBool,
+    /// Held while a background compaction round is running. Write operations wait for it instead
+    /// of failing.
+    background_compaction_lock: Mutex<()>,
+    /// The time of the last committed write batch. Background compaction is paused for a while
+    /// after a write.
+    last_commit: Mutex<Option<Instant>>,
+    /// The configuration of the database.
+    config: DbConfig,
     /// A cache for deserialized AMQF filters.
     amqf_cache: AmqfCache,
-    /// A cache for decompressed key blocks.
-    key_block_cache: BlockCache,
-    /// A cache for decompressed value blocks.
-    value_block_cache: BlockCache,
+    /// The block caches for the families configured in `config.families`, followed by the shared
+    /// block caches of all other families.
+    family_caches: Vec<FamilyCaches>,
     /// Statistics for the database.
     #[cfg(feature = "stats")]
     stats: TrackedStats,
@@ -149,7 +213,13 @@ pub struct CommitOptions {
 }
 
 impl TurboPersistence {
-    fn new(path: PathBuf, read_only: bool) -> Self {
+    fn new(path: PathBuf, read_only: bool, config: DbConfig) -> Self {
+        let family_caches = config
+            .families
+            .iter()
+            .chain([&config.default_family])
+            .map(FamilyCaches::new)
+            .collect();
         Self {
             path,
             read_only,
@@ -158,27 +228,17 @@ impl TurboPersistence {
                 current_sequence_number: 0,
             }),
             active_write_operation: AtomicBool::new(false),
+            background_compaction_lock: Mutex::new(()),
+            last_commit: Mutex::new(None),
             amqf_cache: AmqfCache::with(
-                AMQF_CACHE_SIZE as usize / AMQF_AVG_SIZE,
-                AMQF_CACHE_SIZE,
-                Default::default(),
-                Default::default(),
-                Default::default(),
-            ),
-            key_block_cache: BlockCache::with(
-                KEY_BLOCK_CACHE_SIZE as usize / KEY_BLOCK_AVG_SIZE,
-                KEY_BLOCK_CACHE_SIZE,
-                Default::default(),
-                Default::default(),
-                Default::default(),
-            ),
-            value_block_cache: BlockCache::with(
-                VALUE_BLOCK_CACHE_SIZE as usize / VALUE_BLOCK_AVG_SIZE,
-                VALUE_BLOCK_CACHE_SIZE,
+                config.amqf_cache_size as usize / AMQF_AVG_SIZE,
+                config.amqf_cache_size,
                 Default::default(),
                 Default::default(),
                 Default::default(),
             ),
+            family_caches,
+            config,
             #[cfg(feature = "stats")]
             stats: TrackedStats::default(),
         }
@@ -189,7 +249,14 @@ impl TurboPersistence {
     /// properly. Cleanup only requires to read a few bytes from a few files and to delete
     /// files, so it's fast.
     pub fn open(path: PathBuf) -> Result<Self> {
-        let mut db = Self::new(path, false);
+        Self::open_with_config(path, DbConfig::default())
+    }
+
+    /// Open a TurboPersistence database at the given path with a custom configuration. The
+    /// configuration only affects newly written SST files, so it can be changed between sessions.
+    pub fn open_with_config(path: PathBuf, config: DbConfig) -> Result<Self> {
+        config.validate()?;
+        let mut db = Self::new(path, false, config);
         db.open_directory(false)?;
         Ok(db)
     }
@@ -197,11 +264,18 @@ impl TurboPersistence {
     /// Open a TurboPersistence database at the given path in read only mode.
     /// This will read the directory. No Cleanup is performed.
     pub fn open_read_only(path: PathBuf) -> Result<Self> {
-        let mut db = Self::new(path, true);
+        let mut db = Self::new(path, true, DbConfig::default());
         db.open_directory(false)?;
         Ok(db)
     }
 
+    /// Returns the block caches of a key family.
+    fn caches(&self, family: u32) -> &FamilyCaches {
+        // The last entry holds the caches of all families that are not configured explicitly.
+        let index = (family as usize).min(self.config.families.len());
+        &self.family_caches[index]
+    }
+
     /// Performs the initial check on the database directory.
     fn open_directory(&mut self, read_only: bool) -> Result<()> {
         match fs::read_dir(&self.path) {
@@ -402,6 +476,8 @@ impl TurboPersistence {
         if self.read_only {
             bail!("Cannot write to a read-only database");
         }
+        // Wait for a running background compaction round to finish
+        let _background_compaction_guard = self.background_compaction_lock.lock();
         if self
             .active_write_operation
             .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
@@ -413,7 +489,7 @@ impl TurboPersistence {
             );
         }
         let current = self.inner.read().current_sequence_number;
-        Ok(WriteBatch::new(self.path.clone(), current))
+        Ok(WriteBatch::new(self.path.clone(), current, &self.config))
     }
 
     fn open_log(&self) -> Result<BufWriter<File>> {
@@ -453,6 +529,7 @@ impl TurboPersistence {
             sequence_number,
             keys_written,
         })?;
+        *self.last_commit.lock() = Some(Instant::now());
         self.active_write_operation.store(false, Ordering::Release);
         Ok(())
     }
@@ -655,6 +732,8 @@ impl TurboPersistence {
             bail!("Compaction is not allowed on a read only database");
         }
         let _span = tracing::info_span!("compact database").entered();
+        // Wait for a running background compaction round to finish
+        let background_compaction_guard = self.background_compaction_lock.lock();
         if self
             .active_write_operation
             .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
@@ -665,7 +744,18 @@ impl TurboPersistence {
                  operations is allowed at a time)"
             );
         }
+        drop(background_compaction_guard);
+
+        self.compact_and_commit(compact_config)?;
+
+        self.active_write_operation.store(false, Ordering::Release);
+
+        Ok(())
+    }
 
+    /// Compacts the database and commits the result. Returns the number of bytes written. The
+    /// caller must hold the write operation.
+    fn compact_and_commit(&self, compact_config: &CompactConfig) -> Result<u64> {
         let mut sequence_number;
         let mut new_meta_files = Vec::new();
         let mut new_sst_files = Vec::new();
@@ -689,6 +779,11 @@ impl TurboPersistence {
             .context("Failed to compact database")?;
         }
 
+        let mut bytes_written = 0;
+        for (_, file) in new_sst_files.iter().chain(new_meta_files.iter()) {
+            bytes_written += file.metadata()?.len();
+        }
+
         if !new_meta_files.is_empty() {
             self.commit(CommitOptions {
                 new_meta_files,
@@ -702,9 +797,123 @@ impl TurboPersistence {
             .context("Failed to commit the database compaction")?;
         }
 
-        self.active_write_operation.store(false, Ordering::Release);
+        Ok(bytes_written)
+    }
 
-        Ok(())
+    /// Starts compacting the database in the background. Compaction runs in rounds on a separate
+    /// thread pool, whenever the SST files of a key family exceed the configured thresholds. See
+    /// [`BackgroundCompactionConfig`] for the available budgets.
+    ///
+    /// Write operations wait for a running compaction round to finish. Compaction stops when the
+    /// returned handle is stopped or dropped.
+    pub fn start_background_compaction(
+        self: &Arc<Self>,
+        config: BackgroundCompactionConfig,
+    ) -> Result<BackgroundCompaction> {
+        if self.read_only {
+            bail!("Compaction is not allowed on a read only database");
+        }
+        BackgroundCompaction::start(self.clone(), config)
+    }
+
+    /// Performs a single round of background compaction if needed and possible.
+    pub(crate) fn background_compaction_round(
+        &self,
+        config: &BackgroundCompactionConfig,
+    ) -> Result<BackgroundCompactionRound> {
+        let _guard = self.background_compaction_lock.lock();
+        if self
+            .last_commit
+            .lock()
+            .is_some_and(|last_commit| last_commit.elapsed() < config.write_cooldown)
+        {
+            #[cfg(feature = "stats")]
+            self.stats
+                .background_compaction_skipped
+                .fetch_add(1, Ordering::Relaxed);
+            return Ok(BackgroundCompactionRound::Skipped);
+        }
+
+        struct SstWithSize {
+            range: StaticSortedFileRange,
+            size: u64,
+        }
+
+        impl Compactable for SstWithSize {
+            fn range(&self) -> RangeInclusive<u64> {
+                self.range.min_hash..=self.range.max_hash
+            }
+
+            fn size(&self) -> u64 {
+                self.size
+            }
+        }
+
+        let (max_coverage, duplicated_size) = {
+            let inner = self.inner.read();
+            let mut ssts_by_family: Vec<Vec<SstWithSize>> = Vec::new();
+            for meta in inner.meta_files.iter() {
+                for entry in meta.entries() {
+                    let range = entry.range();
+                    let family = range.family as usize;
+                    if ssts_by_family.len() <= family {
+                        ssts_by_family.resize_with(family + 1, Vec::new);
+                    }
+                    ssts_by_family[family].push(SstWithSize {
+                        range,
+                        size: entry.size(),
+                    });
+                }
+            }
+            ssts_by_family
+                .iter()
+                .map(|ssts| compute_metrics(ssts, 0..=u64::MAX))
+                .fold((0.0f32, 0u64), |(coverage, duplicated_size), metrics| {
+                    (
+                        coverage.max(metrics.coverage),
+                        duplicated_size + metrics.duplicated_size,
+                    )
+                })
+        };
+        #[cfg(feature = "stats")]
+        self.stats
+            .pending_duplication_bytes
+            .store(duplicated_size, Ordering::Relaxed);
+        if max_coverage < config.min_coverage && duplicated_size < config.min_duplication_bytes {
+            return Ok(BackgroundCompactionRound::NotNeeded);
+        }
+
+        if self
+            .active_write_operation
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            // Some other write operation is active. We try again later.
+            #[cfg(feature = "stats")]
+            self.stats
+                .background_compaction_skipped
+                .fetch_add(1, Ordering::Relaxed);
+            return Ok(BackgroundCompactionRound::Skipped);
+        }
+        let _span = tracing::info_span!(
+            "background compaction",
+            coverage = max_coverage,
+            duplicated_size
+        )
+        .entered();
+        let result = self.compact_and_commit(&config.compact_config);
+        self.active_write_operation.store(false, Ordering::Release);
+        let bytes_written = result?;
+        #[cfg(feature = "stats")]
+        {
+            self.stats
+                .background_compaction_rounds
+                .fetch_add(1, Ordering::Relaxed);
+            self.stats
+                .background_compaction_bytes_written
+                .fetch_add(bytes_written, Ordering::Relaxed);
+        }
+        Ok(BackgroundCompactionRound::Compacted { bytes_written })
     }
 
     /// Internal function to perform a compaction.
@@ -772,8 +981,6 @@ impl TurboPersistence {
             sst_by_family[sst.range.family as usize].push(sst);
         }
 
-        let key_block_cache = &self.key_block_cache;
-        let value_block_cache = &self.value_block_cache;
         let path = &self.path;
 
         let log_mutex = Mutex::new(());
@@ -808,6 +1015,11 @@ impl TurboPersistence {
             .map(|(family, (ssts_with_ranges, merge_jobs))| {
                 let family = family as u32;
                 let _span = span.clone().entered();
+                let family_config = self.config.family(family);
+                let FamilyCaches {
+                    key_block_cache,
+                    value_block_cache,
+                } = self.caches(family);
 
                 if merge_jobs.is_empty() {
                     return Ok(PartialResultPerFamily {
@@ -886,6 +1098,7 @@ impl TurboPersistence {
                                 value_compression_dictionary_length: entry
                                     .value_compression_dictionary_length(),
                                 block_count: entry.block_count(),
+                                codec: entry.codec(),
                                 size: entry.size(),
                                 entries: 0,
                             };
@@ -901,6 +1114,7 @@ impl TurboPersistence {
                             total_value_size: usize,
                             path: &Path,
                             seq: u32,
+                            config: &FamilyConfig,
                         ) -> Result<(u32, File, StaticSortedFileBuilderMeta<'static>)>
                         {
                             let _span = tracing::trace_span!("write merged sst file").entered();
@@ -909,6 +1123,7 @@ impl TurboPersistence {
                                 total_key_size,
                                 total_value_size,
                                 &path.join(format!("{seq:08}.sst")),
+                                config,
                             )?;
                             Ok((seq, file, meta))
                         }
@@ -978,6 +1193,7 @@ impl TurboPersistence {
                                                 selected_total_value_size,
                                                 path,
                                                 seq,
+                                                family_config,
                                             )?);
 
                                             entries.clear();
@@ -1008,6 +1224,7 @@ impl TurboPersistence {
                                 total_value_size,
                                 path,
                                 seq,
+                                family_config,
                             )?);
                         } else
                         // If we have two sets of entries left, merge them and
@@ -1032,6 +1249,7 @@ impl TurboPersistence {
                                 last_entries_total_sizes.1 / 2,
                                 path,
                                 seq1,
+                                family_config,
                             )?);
 
                             keys_written += part2.len() as u64;
@@ -1041,6 +1259,7 @@ impl TurboPersistence {
                                 last_entries_total_sizes.1 / 2,
                                 path,
                                 seq2,
+                                family_config,
                             )?);
                         }
                         Ok(PartialMergeResult::Merged {
@@ -1137,19 +1356,31 @@ impl TurboPersistence {
         Ok(())
     }
 
+    /// Acquires the read lock on the database state. Only the threads that already hold it in a
+    /// [FamilyIter] acquire it recursively. Otherwise a waiting writer would deadlock with them,
+    /// while recursive reads on all threads would let a steady stream of readers starve commits.
+    fn read_inner(&self) -> RwLockReadGuard<'_, Inner> {
+        if ACTIVE_ITERATORS.get() > 0 {
+            self.inner.read_recursive()
+        } else {
+            self.inner.read()
+        }
+    }
+
     /// Get a value from the database. Returns None if the key is not found. The returned value
     /// might hold onto a block of the database and it should not be hold long-term.
     pub fn get<K: QueryKey>(&self, family: usize, key: &K) -> Result<Option<ArcSlice<u8>>> {
         let hash = hash_key(key);
-        let inner = self.inner.read();
+        let caches = self.caches(family as u32);
+        let inner = self.read_inner();
         for meta in inner.meta_files.iter().rev() {
             match meta.lookup(
                 family as u32,
                 hash,
                 key,
                 &self.amqf_cache,
-                &self.key_block_cache,
-                &self.value_block_cache,
+                &caches.key_block_cache,
+                &caches.value_block_cache,
             )? {
                 MetaLookupResult::FamilyMiss => {
                     #[cfg(feature = "stats")]
@@ -1194,6 +1425,53 @@ impl TurboPersistence {
         Ok(None)
     }
 
+    /// Iterates over all entries of a key family. Entries are yielded in the on-disk order, which
+    /// is sorted by key hash and key, not by key alone. Every key is only yielded once with its
+    /// most recent value and deleted keys are skipped.
+    ///
+    /// The iterator operates on a consistent snapshot of the database. It holds a read lock on the
+    /// database state, so commits and compactions will wait until the iterator is dropped. Reading
+    /// with [`TurboPersistence::get`] while iterating is fine, but committing from the same thread
+    /// will deadlock.
+    pub fn iter(&self, family: usize) -> Result<FamilyIter<'_>> {
+        self.iter_internal(family, None)
+    }
+
+    /// Iterates over all entries of a key family whose key starts with the given prefix. Since SST
+    /// files are sorted by key hash, this still needs to scan the whole key family. See
+    /// [`TurboPersistence::iter`] for ordering and snapshot semantics.
+    pub fn prefix_iter(&self, family: usize, prefix: &[u8]) -> Result<FamilyIter<'_>> {
+        self.iter_internal(family, Some(prefix.to_vec()))
+    }
+
+    fn iter_internal(&self, family: usize, prefix: Option<Vec<u8>>) -> Result<FamilyIter<'_>> {
+        // The read lock prevents commits and compactions from deleting the files of the snapshot
+        // while iterating. The SST iterators keep their files alive on their own.
+        let guard = self.read_inner();
+        let caches = self.caches(family as u32);
+        // The order of the iterators is oldest to newest, so the last entry of equal keys is the
+        // most recent one.
+        let iters = guard
+            .meta_files
+            .iter()
+            .filter(|meta| meta.family() == family as u32)
+            .flat_map(|meta| meta.entries().iter().map(move |entry| (meta, entry)))
+            .map(|(meta, entry)| {
+                entry
+                    .sst(meta)?
+                    .iter(&caches.key_block_cache, &caches.value_block_cache)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        ACTIVE_ITERATORS.set(ACTIVE_ITERATORS.get() + 1);
+        Ok(FamilyIter {
+            iter: MergeIter::new(iters.into_iter())?,
+            current: None,
+            prefix,
+            db: self,
+            _guard: guard,
+        })
+    }
+
     /// Returns database statistics.
     #[cfg(feature = "stats")]
     pub fn statistics(&self) -> Statistics {
@@ -1201,9 +1479,13 @@ impl TurboPersistence {
         Statistics {
             meta_files: inner.meta_files.len(),
             sst_files: inner.meta_files.iter().map(|m| m.entries().len()).sum(),
-            key_block_cache: CacheStatistics::new(&self.key_block_cache),
-            value_block_cache: CacheStatistics::new(&self.value_block_cache),
-            amqf_cache: CacheStatistics::new(&self.amqf_cache),
+            key_block_cache: CacheStatistics::new(
+                self.family_caches.iter().map(|c| &c.key_block_cache),
+            ),
+            value_block_cache: CacheStatistics::new(
+                self.family_caches.iter().map(|c| &c.value_block_cache),
+            ),
+            amqf_cache: CacheStatistics::new([&self.amqf_cache]),
             hits: self.stats.hits_deleted.load(Ordering::Relaxed)
                 + self.stats.hits_small.load(Ordering::Relaxed)
                 + self.stats.hits_blob.load(Ordering::Relaxed),
@@ -1212,6 +1494,19 @@ impl TurboPersistence {
             miss_range: self.stats.miss_range.load(Ordering::Relaxed),
             miss_amqf: self.stats.miss_amqf.load(Ordering::Relaxed),
             miss_key: self.stats.miss_key.load(Ordering::Relaxed),
+            background_compaction_rounds: self
+                .stats
+                .background_compaction_rounds
+                .load(Ordering::Relaxed),
+            background_compaction_skipped: self
+                .stats
+                .background_compaction_skipped
+                .load(Ordering::Relaxed),
+            background_compaction_bytes_written: self
+                .stats
+                .background_compaction_bytes_written
+                .load(Ordering::Relaxed),
+            pending_duplication_bytes: self.stats.pending_duplication_bytes.load(Ordering::Relaxed),
         }
     }
 
@@ -1253,6 +1548,191 @@ impl TurboPersistence {
             .collect())
     }
 
+    /// Returns the compressed and uncompressed block sizes of all SST files. This is used to
+    /// compute compression ratios.
+    pub fn block_size_info(&self) -> Result<Vec<SstBlockSizeInfo>> {
+        let inner = self.inner.read();
+        inner
+            .meta_files
+            .iter()
+            .flat_map(|meta| meta.entries().iter().map(move |entry| (meta, entry)))
+            .map(|(meta, entry)| {
+                let (compressed_size, uncompressed_size) = entry.sst(meta)?.block_sizes()?;
+                Ok(SstBlockSizeInfo {
+                    sequence_number: entry.sequence_number(),
+                    family: meta.family(),
+                    compressed_size,
+                    uncompressed_size,
+                })
+            })
+            .collect()
+    }
+
+    /// Verifies the integrity of all SST files. The SST format has no checksums, so this reads and
+    /// decompresses all blocks and checks that every entry is sorted, has the correct key hash, is
+    /// within the hash range of the SST file and is contained in its AMQF. It also checks that the
+    /// SST files and blob files referenced by the meta files exist. Returns all issues found, at
+    /// most one per SST file.
+    pub fn verify(&self) -> Result<Vec<VerifyIssue>> {
+        let inner = self.inner.read();
+        let ssts = inner
+            .meta_files
+            .iter()
+            .flat_map(|meta| meta.entries().iter().map(move |entry| (meta, entry)))
+            .collect::<Vec<_>>();
+        let span = Span::current();
+        let issues = ssts
+            .into_par_iter()
+            .with_min_len(1)
+            .filter_map(|(meta, entry)| {
+                let _span = span.enter();
+                let error = self.verify_sst(meta, entry).err()?;
+                Some(VerifyIssue {
+                    meta_sequence_number: meta.sequence_number(),
+                    sst_sequence_number: entry.sequence_number(),
+                    message: format!("{error:#}"),
+                })
+            })
+            .collect();
+        Ok(issues)
+    }
+
+    fn verify_sst(&self, meta: &MetaFile, entry: &MetaEntry) -> Result<()> {
+        let seq = entry.sequence_number();
+        let size = fs::metadata(self.path.join(format!("{seq:08}.sst")))
+            .with_context(|| {
+                format!(
+                    "{seq:08}.sst referenced from {:08}.meta can't be read",
+                    meta.sequence_number()
+                )
+            })?
+            .len();
+        if size != entry.size() {
+            bail!(
+                "{seq:08}.sst has a size of {size} bytes, but {:08}.meta expects {} bytes",
+                meta.sequence_number(),
+                entry.size()
+            );
+        }
+        let amqf = entry.amqf(meta, &self.amqf_cache)?;
+        let caches = self.caches(meta.family());
+        let mut last: Option<(u64, ArcSlice<u8>)> = None;
+        for lookup_entry in entry
+            .sst(meta)?
+            .iter(&caches.key_block_cache, &caches.value_block_cache)?
+        {
+            let LookupEntry { hash, key, value } =
+                lookup_entry.with_context(|| format!("Failed to read {seq:08}.sst"))?;
+            if hash != hash_key(&&*key) {
+                bail!("Key {key:x?} in {seq:08}.sst has an incorrect hash {hash:016x}");
+            }
+            if hash < entry.min_hash() || hash > entry.max_hash() {
+                bail!(
+                    "Key {key:x?} in {seq:08}.sst has hash {hash:016x} outside of the range \
+                     {:016x}-{:016x}",
+                    entry.min_hash(),
+                    entry.max_hash()
+                );
+            }
+            if !amqf.contains_fingerprint(hash) {
+                bail!("Key {key:x?} in {seq:08}.sst is missing in the AMQF");
+            }
+            if let Some((last_hash, last_key)) = &last
+                && (*last_hash, &**last_key) >= (hash, &*key)
+            {
+                bail!("Key {key:x?} in {seq:08}.sst is not sorted");
+            }
+            if let LookupValue::Blob { sequence_number } = value
+                && !fs::exists(self.path.join(format!("{sequence_number:08}.blob")))?
+            {
+                bail!(
+                    "Key {key:x?} in {seq:08}.sst references missing blob file \
+                     {sequence_number:08}.blob"
+                );
+            }
+            last = Some((hash, key));
+        }
+        Ok(())
+    }
+
+    /// Returns all SST and blob files in the database directory that are not referenced by the
+    /// database anymore. Blob files are found by reading all SST files.
+    pub fn orphaned_files(&self) -> Result<Vec<OrphanedFileInfo>> {
+        let inner = self.inner.read();
+        let mut referenced_ssts = HashSet::new();
+        let mut referenced_blobs = HashSet::new();
+        for meta in inner.meta_files.iter() {
+            for entry in meta.entries() {
+                referenced_ssts.insert(entry.sequence_number());
+                let caches = self.caches(meta.family());
+                for lookup_entry in entry
+                    .sst(meta)?
+                    .iter(&caches.key_block_cache, &caches.value_block_cache)?
+                {
+                    if let LookupValue::Blob { sequence_number } = lookup_entry?.value {
+                        referenced_blobs.insert(sequence_number);
+                    }
+                }
+            }
+        }
+
+        let mut orphaned_files = Vec::new();
+        for dir_entry in fs::read_dir(&self.path)? {
+            let path = dir_entry?.path();
+            let Some(referenced) = (match path.extension().and_then(|s| s.to_str()) {
+                Some("sst") => Some(&referenced_ssts),
+                Some("blob") => Some(&referenced_blobs),
+                _ => None,
+            }) else {
+                continue;
+            };
+            let Some(seq) = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| s.parse::<u32>().ok())
+            else {
+                continue;
+            };
+            // Files with a higher sequence number are not committed yet
+            if seq > inner.current_sequence_number || referenced.contains(&seq) {
+                continue;
+            }
+            let size = fs::metadata(&path)?.len();
+            orphaned_files.push(OrphanedFileInfo { path, size });
+        }
+        orphaned_files.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+        Ok(orphaned_files)
+    }
+
+    /// Deletes all SST and blob files that are not referenced by the database anymore. See
+    /// [`TurboPersistence::orphaned_files`]. Returns the deleted files.
+    pub fn delete_orphaned_files(&self) -> Result<Vec<OrphanedFileInfo>> {
+        if self.read_only {
+            bail!("Deleting files is not allowed on a read only database");
+        }
+        if self
+            .active_write_operation
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            bail!(
+                "Another write batch or compaction is already active (Only a single write \
+                 operations is allowed at a time)"
+            );
+        }
+        let result = self.orphaned_files().and_then(|orphaned_files| {
+            let mut log = self.open_log()?;
+            writeln!(log, "Time {}", Timestamp::now())?;
+            for file in orphaned_files.iter() {
+                fs::remove_file(&file.path)?;
+                writeln!(log, "{} ORPHAN DELETED", file.path.display())?;
+            }
+            Ok(orphaned_files)
+        });
+        self.active_write_operation.store(false, Ordering::Release);
+        result
+    }
+
     /// Shuts down the database. This will print statistics if the `print_stats` feature is enabled.
     pub fn shutdown(&self) -> Result<()> {
         #[cfg(feature = "print_stats")]
@@ -1261,6 +1741,87 @@ impl TurboPersistence {
     }
 }
 
+/// The outcome of a single background compaction round.
+pub(crate) enum BackgroundCompactionRound {
+    /// Compaction was skipped because of recent or active write operations.
+    Skipped,
+    /// The database doesn't need compaction.
+    NotNeeded,
+    /// A compaction was performed.
+    Compacted { bytes_written: u64 },
+}
+
+/// An iterator over the entries of a key family. See [`TurboPersistence::iter`].
+thread_local! {
+    /// The number of [FamilyIter]s alive on the current thread. The read guard makes them `!Send`,
+    /// so they are always dropped on the thread that created them.
+    static ACTIVE_ITERATORS: Cell<usize> = const { Cell::new(0) };
+}
+
+pub struct FamilyIter<'l> {
+    /// The merged iterator over all SST files of the key family.
+    iter: MergeIter<StaticSortedFileIter<'l>>,
+    /// The last entry read from `iter`. It's only yielded once we know that it isn't overridden by
+    /// a newer entry with the same key.
+    current: Option<LookupEntry>,
+    /// Only entries with keys starting with this prefix are yielded.
+    prefix: Option<Vec<u8>>,
+    db: &'l TurboPersistence,
+    /// Keeps commits from deleting the files of the snapshot while iterating.
+    _guard: RwLockReadGuard<'l, Inner>,
+}
+
+impl FamilyIter<'_> {
+    /// Returns the next entry with the most recent value for its key.
+    fn next_internal(&mut self) -> Result<Option<(ArcSlice<u8>, ArcSlice<u8>)>> {
+        while let Some(entry) = self.iter.next().transpose()? {
+            if let Some(prefix) = &self.prefix
+                && !entry.key.starts_with(prefix)
+            {
+                continue;
+            }
+            if let Some(current) = self.current.replace(entry) {
+                if self.current.as_ref().is_some_and(|e| e.key == current.key) {
+                    // Overridden by a newer value
+                    continue;
+                }
+                if let Some(result) = self.resolve(current)? {
+                    return Ok(Some(result));
+                }
+            }
+        }
+        if let Some(current) = self.current.take() {
+            return self.resolve(current);
+        }
+        Ok(None)
+    }
+
+    /// Reads the value of an entry. Returns None for deleted entries.
+    fn resolve(&self, entry: LookupEntry) -> Result<Option<(ArcSlice<u8>, ArcSlice<u8>)>> {
+        Ok(match entry.value {
+            LookupValue::Deleted => None,
+            LookupValue::Slice { value } => Some((entry.key, value)),
+            LookupValue::Blob { sequence_number } => {
+                Some((entry.key, self.db.read_blob(sequence_number)?))
+            }
+        })
+    }
+}
+
+impl Iterator for FamilyIter<'_> {
+    type Item = Result<(ArcSlice<u8>, ArcSlice<u8>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_internal().transpose()
+    }
+}
+
+impl Drop for FamilyIter<'_> {
+    fn drop(&mut self) {
+        ACTIVE_ITERATORS.set(ACTIVE_ITERATORS.get() - 1);
+    }
+}
+
 pub struct MetaFileInfo {
     pub sequence_number: u32,
     pub family: u32,
@@ -1268,6 +1829,24 @@ pub struct MetaFileInfo {
     pub entries: Vec<MetaFileEntryInfo>,
 }
 
+pub struct SstBlockSizeInfo {
+    pub sequence_number: u32,
+    pub family: u32,
+    pub compressed_size: u64,
+    pub uncompressed_size: u64,
+}
+
+pub struct VerifyIssue {
+    pub meta_sequence_number: u32,
+    pub sst_sequence_number: u32,
+    pub message: String,
+}
+
+pub struct OrphanedFileInfo {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
 pub struct MetaFileEntryInfo {
     pub sequence_number: u32,
     pub min_hash: u64,
@@ -5,9 +5,11 @@
 #![feature(iter_collect_into)]
 
 mod arc_slice;
+mod background_compaction;
 mod collector;
 mod collector_entry;
 mod compaction;
+mod config;
 mod constants;
 mod db;
 mod key;
@@ -25,7 +27,12 @@ mod tests;
 mod value_buf;
 
 pub use arc_slice::ArcSlice;
-pub use db::{CompactConfig, MetaFileEntryInfo, MetaFileInfo, TurboPersistence};
+pub use background_compaction::{BackgroundCompaction, BackgroundCompactionConfig};
+pub use config::{Codec, DbConfig, FamilyConfig};
+pub use db::{
+    CompactConfig, FamilyIter, MetaFileEntryInfo, MetaFileInfo, OrphanedFileInfo, SstBlockSizeInfo,
+    TurboPersistence, VerifyIssue,
+};
 pub use key::{KeyBase, QueryKey, StoreKey};
 pub use value_buf::ValueBuffer;
 pub use write_batch::WriteBatch;
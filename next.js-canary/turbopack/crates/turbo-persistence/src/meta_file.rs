@@ -16,9 +16,16 @@ use rustc_hash::FxHasher;
 
 use crate::{
     QueryKey,
-    static_sorted_file::{BlockCache, SstLookupResult, StaticSortedFile, StaticSortedFileMetaData},
+    static_sorted_file::{
+        BlockCache, CODEC_LZ4, SstLookupResult, StaticSortedFile, StaticSortedFileMetaData,
+    },
 };
 
+/// The magic number of meta files without codec information. All SST files use LZ4.
+pub const META_FILE_MAGIC_V1: u32 = 0xFE4ADA4A;
+/// The magic number of meta files that store the codec of every SST file.
+pub const META_FILE_MAGIC_V2: u32 = 0xFE4ADA4B;
+
 #[derive(Clone, Default)]
 pub struct AmqfWeighter;
 
@@ -52,7 +59,7 @@ pub struct MetaEntry {
     /// use the AMQF cache instead.
     amqf: OnceLock<qfilter::Filter>,
     /// The static sorted file that is lazily loaded
-    sst: OnceLock<StaticSortedFile>,
+    sst: OnceLock<Arc<StaticSortedFile>>,
 }
 
 impl MetaEntry {
@@ -112,14 +119,17 @@ impl MetaEntry {
         })
     }
 
-    pub fn sst(&self, meta: &MetaFile) -> Result<&StaticSortedFile> {
+    pub fn sst(&self, meta: &MetaFile) -> Result<&Arc<StaticSortedFile>> {
         self.sst.get_or_try_init(|| {
-            StaticSortedFile::open(&meta.db_path, self.sst_data.clone()).with_context(|| {
-                format!(
-                    "Unable to open static sorted file referenced from {:08}.meta",
-                    meta.sequence_number()
-                )
-            })
+            let sst = StaticSortedFile::open(&meta.db_path, self.sst_data.clone()).with_context(
+                || {
+                    format!(
+                        "Unable to open static sorted file referenced from {:08}.meta",
+                        meta.sequence_number()
+                    )
+                },
+            )?;
+            Ok(Arc::new(sst))
         })
     }
 
@@ -151,6 +161,10 @@ impl MetaEntry {
     pub fn block_count(&self) -> u16 {
         self.sst_data.block_count
     }
+
+    pub fn codec(&self) -> u8 {
+        self.sst_data.codec
+    }
 }
 
 /// The result of a lookup operation.
@@ -204,9 +218,11 @@ impl MetaFile {
     fn open_internal(db_path: PathBuf, sequence_number: u32, path: &Path) -> Result<Self> {
         let mut file = BufReader::new(File::open(path)?);
         let magic = file.read_u32::<BE>()?;
-        if magic != 0xFE4ADA4A {
-            bail!("Invalid magic number");
-        }
+        let has_codec = match magic {
+            META_FILE_MAGIC_V1 => false,
+            META_FILE_MAGIC_V2 => true,
+            _ => bail!("Invalid magic number"),
+        };
         let family = file.read_u32::<BE>()?;
         let obsolete_count = file.read_u32::<BE>()?;
         let mut obsolete_sst_files = Vec::with_capacity(obsolete_count as usize);
@@ -224,6 +240,11 @@ impl MetaFile {
                     key_compression_dictionary_length: file.read_u16::<BE>()?,
                     value_compression_dictionary_length: file.read_u16::<BE>()?,
                     block_count: file.read_u16::<BE>()?,
+                    codec: if has_codec {
+                        file.read_u8()?
+                    } else {
+                        CODEC_LZ4
+                    },
                 },
                 family,
                 min_hash: file.read_u64::<BE>()?,
@@ -7,7 +7,9 @@ use std::{
 use anyhow::{Context, Result};
 use byteorder::{BE, WriteBytesExt};
 
-use crate::static_sorted_file_builder::StaticSortedFileBuilderMeta;
+use crate::{
+    meta_file::META_FILE_MAGIC_V2, static_sorted_file_builder::StaticSortedFileBuilderMeta,
+};
 
 pub struct MetaFileBuilder<'a> {
     family: u32,
@@ -43,7 +45,7 @@ impl<'a> MetaFileBuilder<'a> {
 
     fn write_internal(mut self, file: &Path) -> io::Result<File> {
         let mut file = BufWriter::new(File::create(file)?);
-        file.write_u32::<BE>(0xFE4ADA4A)?; // Magic number
+        file.write_u32::<BE>(META_FILE_MAGIC_V2)?; // Magic number
         file.write_u32::<BE>(self.family)?;
 
         self.obsolete_sst_files.sort();
@@ -60,6 +62,7 @@ impl<'a> MetaFileBuilder<'a> {
             file.write_u16::<BE>(sst.key_compression_dictionary_length)?;
             file.write_u16::<BE>(sst.value_compression_dictionary_length)?;
             file.write_u16::<BE>(sst.block_count)?;
+            file.write_u8(sst.codec)?;
             file.write_u64::<BE>(sst.min_hash)?;
             file.write_u64::<BE>(sst.max_hash)?;
             file.write_u64::<BE>(sst.size)?;
@@ -14,6 +14,7 @@ use lzzzz::lz4::decompress_with_dict;
 use memmap2::Mmap;
 use quick_cache::sync::GuardResult;
 use rustc_hash::FxHasher;
+use zstd::dict::DecoderDictionary;
 
 use crate::{
     QueryKey,
@@ -35,6 +36,13 @@ pub const KEY_BLOCK_ENTRY_TYPE_DELETED: u8 = 2;
 /// The tag for a medium-sized value.
 pub const KEY_BLOCK_ENTRY_TYPE_MEDIUM: u8 = 3;
 
+/// The codec for LZ4 compressed blocks.
+pub const CODEC_LZ4: u8 = 0;
+/// The codec for uncompressed blocks.
+pub const CODEC_NONE: u8 = 1;
+/// The codec for zstd compressed blocks.
+pub const CODEC_ZSTD: u8 = 2;
+
 /// The result of a lookup operation.
 pub enum SstLookupResult {
     /// The key was found.
@@ -71,6 +79,8 @@ pub struct StaticSortedFileMetaData {
     pub value_compression_dictionary_length: u16,
     /// The number of blocks in the SST file.
     pub block_count: u16,
+    /// The compression codec of the blocks (one of the `CODEC_*` constants).
+    pub codec: u8,
 }
 
 impl StaticSortedFileMetaData {
@@ -104,6 +114,14 @@ pub struct StaticSortedFile {
     meta: StaticSortedFileMetaData,
     /// The memory mapped file.
     mmap: Mmap,
+    /// The prepared zstd dictionaries for key and value blocks, if the file is zstd compressed.
+    zstd_dictionaries: Option<ZstdDictionaries>,
+}
+
+/// Preparing a zstd dictionary is expensive, so it's done once per file instead of once per block.
+struct ZstdDictionaries {
+    key: DecoderDictionary<'static>,
+    value: DecoderDictionary<'static>,
 }
 
 impl StaticSortedFile {
@@ -120,18 +138,26 @@ impl StaticSortedFile {
         let mmap = unsafe { Mmap::map(&File::open(&path)?)? };
         #[cfg(unix)]
         mmap.advise(memmap2::Advice::Random)?;
-        let file = Self { meta, mmap };
+        let zstd_dictionaries = (meta.codec == CODEC_ZSTD).then(|| ZstdDictionaries {
+            key: DecoderDictionary::copy(&mmap[meta.key_compression_dictionary_range()]),
+            value: DecoderDictionary::copy(&mmap[meta.value_compression_dictionary_range()]),
+        });
+        let file = Self {
+            meta,
+            mmap,
+            zstd_dictionaries,
+        };
         Ok(file)
     }
 
-    /// Iterate over all entries in this file in sorted order.
+    /// Iterate over all entries in this file in sorted order. The iterator keeps the file alive.
     pub fn iter<'l>(
-        &'l self,
+        self: &Arc<Self>,
         key_block_cache: &'l BlockCache,
         value_block_cache: &'l BlockCache,
     ) -> Result<StaticSortedFileIter<'l>> {
         let mut iter = StaticSortedFileIter {
-            this: self,
+            this: self.clone(),
             key_block_cache,
             value_block_cache,
             stack: Vec::new(),
@@ -141,6 +167,33 @@ impl StaticSortedFile {
         Ok(iter)
     }
 
+    /// Returns the total compressed and uncompressed size of all blocks in this file. This only
+    /// reads the block headers and doesn't decompress anything.
+    pub fn block_sizes(&self) -> Result<(u64, u64)> {
+        let blocks_start = self.meta.blocks_start();
+        let block_offsets_start = self.meta.block_offsets_start(self.mmap.len());
+        let mut compressed_size = 0;
+        let mut uncompressed_size = 0;
+        let mut block_start = blocks_start;
+        for block_index in 0..self.meta.block_count as usize {
+            let offset = block_offsets_start + block_index * 4;
+            let block_end = blocks_start
+                + self
+                    .mmap
+                    .get(offset..offset + 4)
+                    .context("Block offset out of bounds")?
+                    .read_u32::<BE>()? as usize;
+            uncompressed_size += self
+                .mmap
+                .get(block_start..block_start + 4)
+                .context("Block header out of bounds")?
+                .read_u32::<BE>()? as u64;
+            compressed_size += block_end.saturating_sub(block_start) as u64;
+            block_start = block_end;
+        }
+        Ok((compressed_size, uncompressed_size))
+    }
+
     /// Looks up a key in this file.
     pub fn lookup<K: QueryKey>(
         &self,
@@ -326,6 +379,7 @@ impl StaticSortedFile {
         self.read_block(
             block_index,
             &self.mmap[self.meta.key_compression_dictionary_range()],
+            self.zstd_dictionaries.as_ref().map(|d| &d.key),
         )
     }
 
@@ -334,11 +388,17 @@ impl StaticSortedFile {
         self.read_block(
             block_index,
             &self.mmap[self.meta.value_compression_dictionary_range()],
+            self.zstd_dictionaries.as_ref().map(|d| &d.value),
         )
     }
 
     /// Reads a block from the file.
-    fn read_block(&self, block_index: u16, compression_dictionary: &[u8]) -> Result<ArcSlice<u8>> {
+    fn read_block(
+        &self,
+        block_index: u16,
+        compression_dictionary: &[u8],
+        zstd_dictionary: Option<&DecoderDictionary<'static>>,
+    ) -> Result<ArcSlice<u8>> {
         #[cfg(feature = "strict_checks")]
         if block_index >= self.meta.block_count {
             bail!(
@@ -388,21 +448,63 @@ impl StaticSortedFile {
         }
         let uncompressed_length =
             (&self.mmap[block_start..block_start + 4]).read_u32::<BE>()? as usize;
-        let block = self.mmap[block_start + 4..block_end].to_vec();
+        let block = &self.mmap[block_start + 4..block_end];
+
+        if self.meta.codec == CODEC_NONE {
+            if block.len() != uncompressed_length {
+                bail!(
+                    "Corrupted file seq:{} block:{} uncompressed block has {} bytes, but {} bytes \
+                     are expected",
+                    self.meta.sequence_number,
+                    block_index,
+                    block.len(),
+                    uncompressed_length
+                );
+            }
+            return Ok(ArcSlice::from(Arc::<[u8]>::from(block)));
+        }
 
+        let block = block.to_vec();
         let buffer = Arc::new_zeroed_slice(uncompressed_length);
         // Safety: MaybeUninit<u8> can be safely transmuted to u8.
         let mut buffer = unsafe { transmute::<Arc<[MaybeUninit<u8>]>, Arc<[u8]>>(buffer) };
         // Safety: We know that the buffer is not shared yet.
         let decompressed = unsafe { Arc::get_mut_unchecked(&mut buffer) };
-        decompress_with_dict(&block, decompressed, compression_dictionary)?;
+        match self.meta.codec {
+            CODEC_LZ4 => {
+                decompress_with_dict(&block, decompressed, compression_dictionary)?;
+            }
+            CODEC_ZSTD => {
+                let zstd_dictionary = zstd_dictionary
+                    .context("Missing zstd dictionary for a zstd compressed file")?;
+                let mut decompressor =
+                    zstd::bulk::Decompressor::with_prepared_dictionary(zstd_dictionary)?;
+                let length = decompressor.decompress_to_buffer(&block, decompressed)?;
+                if length != uncompressed_length {
+                    bail!(
+                        "Corrupted file seq:{} block:{} decompressed to {} bytes, but {} bytes \
+                         are expected",
+                        self.meta.sequence_number,
+                        block_index,
+                        length,
+                        uncompressed_length
+                    );
+                }
+            }
+            codec => {
+                bail!(
+                    "Unknown compression codec {codec} in file seq:{}",
+                    self.meta.sequence_number
+                );
+            }
+        }
         Ok(ArcSlice::from(buffer))
     }
 }
 
 /// An iterator over all entries in a SST file in sorted order.
 pub struct StaticSortedFileIter<'l> {
-    this: &'l StaticSortedFile,
+    this: Arc<StaticSortedFile>,
     key_block_cache: &'l BlockCache,
     value_block_cache: &'l BlockCache,
 
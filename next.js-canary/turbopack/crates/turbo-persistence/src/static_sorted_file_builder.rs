@@ -10,22 +10,20 @@ use anyhow::{Context, Result};
 use byteorder::{BE, ByteOrder, WriteBytesExt};
 use lzzzz::lz4::{ACC_LEVEL_DEFAULT, max_compressed_size};
 
-use crate::static_sorted_file::{
-    BLOCK_TYPE_INDEX, BLOCK_TYPE_KEY, KEY_BLOCK_ENTRY_TYPE_BLOB, KEY_BLOCK_ENTRY_TYPE_DELETED,
-    KEY_BLOCK_ENTRY_TYPE_MEDIUM, KEY_BLOCK_ENTRY_TYPE_SMALL,
+use crate::{
+    config::{Codec, FamilyConfig},
+    static_sorted_file::{
+        BLOCK_TYPE_INDEX, BLOCK_TYPE_KEY, KEY_BLOCK_ENTRY_TYPE_BLOB, KEY_BLOCK_ENTRY_TYPE_DELETED,
+        KEY_BLOCK_ENTRY_TYPE_MEDIUM, KEY_BLOCK_ENTRY_TYPE_SMALL,
+    },
 };
 
 /// The maximum number of entries that should go into a single key block
 const MAX_KEY_BLOCK_ENTRIES: usize = 100 * 1024;
-/// The maximum bytes that should go into a single key block
-// Note this must fit into 3 bytes length
-const MAX_KEY_BLOCK_SIZE: usize = 16 * 1024;
 /// Overhead of bytes that should be counted for entries in a key block in addition to the key size
 const KEY_BLOCK_ENTRY_META_OVERHEAD: usize = 8;
 /// The maximum number of entries that should go into a single small value block
 const MAX_SMALL_VALUE_BLOCK_ENTRIES: usize = 100 * 1024;
-/// The maximum bytes that should go into a single small value block
-const MAX_SMALL_VALUE_BLOCK_SIZE: usize = 16 * 1024;
 /// The aimed false positive rate for the AMQF
 const AMQF_FALSE_POSITIVE_RATE: f64 = 0.01;
 
@@ -88,6 +86,8 @@ pub struct StaticSortedFileBuilderMeta<'a> {
     pub value_compression_dictionary_length: u16,
     /// The number of blocks in the SST file
     pub block_count: u16,
+    /// The compression codec of the blocks
+    pub codec: u8,
     /// The file size of the SST file
     pub size: u64,
     /// The number of entries in the SST file
@@ -99,6 +99,7 @@ pub fn write_static_stored_file<E: Entry>(
     total_key_size: usize,
     total_value_size: usize,
     file: &Path,
+    config: &FamilyConfig,
 ) -> Result<(StaticSortedFileBuilderMeta<'static>, File)> {
     debug_assert!(entries.iter().map(|e| e.key_hash()).is_sorted());
 
@@ -108,12 +109,19 @@ pub fn write_static_stored_file<E: Entry>(
     // We use a shared buffer for all operations to avoid excessive allocations
     let mut buffer = Vec::with_capacity(capacity);
 
-    let key_dict = compute_key_compression_dictionary(entries, total_key_size, &mut buffer)?;
-    let value_dict = compute_value_compression_dictionary(entries, total_value_size, &mut buffer)?;
+    let (key_dict, value_dict) = if config.compression_dictionary && config.codec != Codec::None {
+        (
+            compute_key_compression_dictionary(entries, total_key_size, &mut buffer)?,
+            compute_value_compression_dictionary(entries, total_value_size, &mut buffer)?,
+        )
+    } else {
+        (Vec::new(), Vec::new())
+    };
     file.write_all(&key_dict)?;
     file.write_all(&value_dict)?;
 
-    let mut block_writer = BlockWriter::new(&mut file, &mut buffer);
+    let mut block_writer =
+        BlockWriter::new(&mut file, &mut buffer, config.codec, &key_dict, &value_dict)?;
 
     // Another shared buffer for the uncompressed blocks
     // The existing shared buffer will be used for compressed blocks
@@ -121,19 +129,26 @@ pub fn write_static_stored_file<E: Entry>(
     let mut buffer = Vec::new();
 
     let min_hash = entries.first().map_or(u64::MAX, |e| e.key_hash());
-    let value_locations = write_value_blocks(entries, &value_dict, &mut block_writer, &mut buffer)
-        .context("Failed to write value blocks")?;
+    let value_locations = write_value_blocks(
+        entries,
+        &value_dict,
+        config.block_size,
+        &mut block_writer,
+        &mut buffer,
+    )
+    .context("Failed to write value blocks")?;
     let amqf = write_key_blocks_and_compute_amqf(
         entries,
         &value_locations,
         &key_dict,
+        config.block_size,
         &mut block_writer,
         &mut buffer,
     )
     .context("Failed to write key blocks")?;
     let max_hash = entries.last().map_or(0, |e| e.key_hash());
 
-    let block_count = block_writer.block_count();
+    let block_count = block_writer.block_count()?;
     for offset in &block_writer.block_offsets {
         file.write_u32::<BE>(*offset)
             .context("Failed to write block offset")?;
@@ -146,6 +161,7 @@ pub fn write_static_stored_file<E: Entry>(
         key_compression_dictionary_length: key_dict.len().try_into().unwrap(),
         value_compression_dictionary_length: value_dict.len().try_into().unwrap(),
         block_count,
+        codec: config.codec.id(),
         size: file.stream_position()?,
         entries: entries.len() as u64,
     };
@@ -266,53 +282,89 @@ struct BlockWriter<'l> {
     buffer: &'l mut Vec<u8>,
     block_offsets: Vec<u32>,
     writer: &'l mut BufWriter<File>,
+    codec: Codec,
+    /// Zstd compressors for key and value blocks. Loading the dictionary is expensive, so they are
+    /// created once per file.
+    zstd_compressors: Option<ZstdCompressors>,
+}
+
+struct ZstdCompressors {
+    key: zstd::bulk::Compressor<'static>,
+    value: zstd::bulk::Compressor<'static>,
 }
 
 impl<'l> BlockWriter<'l> {
-    fn new(writer: &'l mut BufWriter<File>, buffer: &'l mut Vec<u8>) -> Self {
-        Self {
+    fn new(
+        writer: &'l mut BufWriter<File>,
+        buffer: &'l mut Vec<u8>,
+        codec: Codec,
+        key_dict: &[u8],
+        value_dict: &[u8],
+    ) -> Result<Self> {
+        let zstd_compressors = if let Codec::Zstd { level } = codec {
+            Some(ZstdCompressors {
+                key: zstd::bulk::Compressor::with_dictionary(level, key_dict)
+                    .context("Zstd compressor creation failed")?,
+                value: zstd::bulk::Compressor::with_dictionary(level, value_dict)
+                    .context("Zstd compressor creation failed")?,
+            })
+        } else {
+            None
+        };
+        Ok(Self {
             buffer,
             block_offsets: Vec::new(),
             writer,
-        }
+            codec,
+            zstd_compressors,
+        })
     }
 
-    fn next_block_index(&mut self) -> u16 {
-        self.block_offsets
-            .len()
-            .try_into()
-            .expect("Block index overflow")
+    fn next_block_index(&mut self) -> Result<u16> {
+        self.block_count()
     }
 
-    fn block_count(&self) -> u16 {
-        self.block_offsets
-            .len()
-            .try_into()
-            .expect("Block count overflow")
+    fn block_count(&self) -> Result<u16> {
+        self.block_offsets.len().try_into().with_context(|| {
+            format!(
+                "Too many blocks in a SST file (more than {}), the block size is too small",
+                u16::MAX
+            )
+        })
     }
 
     #[tracing::instrument(level = "trace", skip_all)]
     fn write_key_block(&mut self, block: &[u8], dict: &[u8]) -> Result<()> {
-        self.write_block(block, dict)
+        self.write_block(block, dict, false)
             .context("Failed to write key block")
     }
 
     #[tracing::instrument(level = "trace", skip_all)]
     fn write_index_block(&mut self, block: &[u8], dict: &[u8]) -> Result<()> {
-        self.write_block(block, dict)
+        self.write_block(block, dict, false)
             .context("Failed to write index block")
     }
 
     #[tracing::instrument(level = "trace", skip_all)]
     fn write_value_block(&mut self, block: &[u8], dict: &[u8]) -> Result<()> {
-        self.write_block(block, dict)
+        self.write_block(block, dict, true)
             .context("Failed to write value block")
     }
 
-    fn write_block(&mut self, block: &[u8], dict: &[u8]) -> Result<()> {
+    fn write_block(&mut self, block: &[u8], dict: &[u8], is_value: bool) -> Result<()> {
         let uncompressed_size = block.len().try_into().unwrap();
-        self.compress_block_into_buffer(block, dict);
-        let len = (self.buffer.len() + 4).try_into().unwrap();
+        let data = match self.codec {
+            Codec::None => block,
+            Codec::Lz4 => {
+                self.compress_block_into_buffer(block, dict);
+                &*self.buffer
+            }
+            Codec::Zstd { .. } => {
+                self.zstd_compress_block_into_buffer(block, is_value)?;
+                &*self.buffer
+            }
+        };
+        let len = (data.len() + 4).try_into().unwrap();
         let offset = self
             .block_offsets
             .last()
@@ -326,7 +378,7 @@ impl<'l> BlockWriter<'l> {
             .write_u32::<BE>(uncompressed_size)
             .context("Failed to write uncompressed size")?;
         self.writer
-            .write_all(self.buffer)
+            .write_all(data)
             .context("Failed to write compressed block")?;
         self.buffer.clear();
         Ok(())
@@ -342,6 +394,26 @@ impl<'l> BlockWriter<'l> {
             .next_to_vec(block, self.buffer, ACC_LEVEL_DEFAULT)
             .expect("Compression failed");
     }
+
+    /// Compresses a block with zstd and the key or value compression dictionary.
+    #[tracing::instrument(level = "trace", skip_all)]
+    fn zstd_compress_block_into_buffer(&mut self, block: &[u8], is_value: bool) -> Result<()> {
+        let compressors = self
+            .zstd_compressors
+            .as_mut()
+            .context("Missing zstd compressors")?;
+        let compressor = if is_value {
+            &mut compressors.value
+        } else {
+            &mut compressors.key
+        };
+        self.buffer
+            .reserve(zstd::zstd_safe::compress_bound(block.len()));
+        compressor
+            .compress_to_buffer(block, self.buffer)
+            .context("Zstd compression failed")?;
+        Ok(())
+    }
 }
 
 /// Splits the values of the entries into blocks and writes them to the writer.
@@ -349,6 +421,7 @@ impl<'l> BlockWriter<'l> {
 fn write_value_blocks(
     entries: &[impl Entry],
     value_compression_dictionary: &[u8],
+    max_block_size: usize,
     writer: &mut BlockWriter<'_>,
     buffer: &mut Vec<u8>,
 ) -> Result<Vec<(u16, u32)>> {
@@ -360,10 +433,11 @@ fn write_value_blocks(
     for (i, entry) in entries.iter().enumerate() {
         match entry.value() {
             EntryValue::Small { value } => {
-                if current_block_size + value.len() > MAX_SMALL_VALUE_BLOCK_SIZE
-                    || current_block_count + 1 >= MAX_SMALL_VALUE_BLOCK_ENTRIES
+                if current_block_count > 0
+                    && (current_block_size + value.len() > max_block_size
+                        || current_block_count + 1 >= MAX_SMALL_VALUE_BLOCK_ENTRIES)
                 {
-                    let block_index = writer.next_block_index();
+                    let block_index = writer.next_block_index()?;
                     buffer.reserve(current_block_size);
                     for j in current_block_start..i {
                         if let EntryValue::Small { value } = &entries[j].value() {
@@ -382,7 +456,7 @@ fn write_value_blocks(
                 current_block_count += 1;
             }
             EntryValue::Medium { value } => {
-                let block_index = writer.next_block_index();
+                let block_index = writer.next_block_index()?;
                 value_locations.push((block_index, 0));
                 writer.write_value_block(value, value_compression_dictionary)?;
             }
@@ -392,7 +466,7 @@ fn write_value_blocks(
         }
     }
     if current_block_count > 0 {
-        let block_index = writer.next_block_index();
+        let block_index = writer.next_block_index()?;
         buffer.reserve(current_block_size);
         for j in current_block_start..entries.len() {
             if let EntryValue::Small { value } = &entries[j].value() {
@@ -414,6 +488,7 @@ fn write_key_blocks_and_compute_amqf(
     entries: &[impl Entry],
     value_locations: &[(u16, u32)],
     key_compression_dictionary: &[u8],
+    max_block_size: usize,
     writer: &mut BlockWriter<'_>,
     buffer: &mut Vec<u8>,
 ) -> Result<Vec<u8>> {
@@ -464,7 +539,7 @@ fn write_key_blocks_and_compute_amqf(
         // Accumulate until the block is full
         if current_block_size > 0
                 && (current_block_size + entry.key_len() + KEY_BLOCK_ENTRY_META_OVERHEAD
-                    > MAX_KEY_BLOCK_SIZE
+                    > max_block_size
                     || i - current_block_start >= MAX_KEY_BLOCK_ENTRIES) &&
                     // avoid breaking the block in the middle of a hash conflict
                     last_hash != key_hash
@@ -477,7 +552,7 @@ fn write_key_blocks_and_compute_amqf(
             }
             key_block_boundaries.push((
                 entries[current_block_start].key_hash(),
-                writer.next_block_index(),
+                writer.next_block_index()?,
             ));
             block.finish();
             writer.write_key_block(buffer, key_compression_dictionary)?;
@@ -499,7 +574,7 @@ fn write_key_blocks_and_compute_amqf(
         }
         key_block_boundaries.push((
             entries[current_block_start].key_hash(),
-            writer.next_block_index(),
+            writer.next_block_index()?,
         ));
         block.finish();
         writer.write_key_block(buffer, key_compression_dictionary)?;
@@ -518,7 +593,7 @@ fn write_key_blocks_and_compute_amqf(
     for (hash, block) in &key_block_boundaries[1..] {
         index_block.put(*hash, *block);
     }
-    let _ = writer.next_block_index();
+    let _ = writer.next_block_index()?;
     index_block.finish();
     writer.write_index_block(buffer, key_compression_dictionary)?;
     buffer.clear();
@@ -1,11 +1,18 @@
-use std::{fs, time::Instant};
+use std::{
+    collections::BTreeMap,
+    fs,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use anyhow::Result;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 use crate::{
+    background_compaction::BackgroundCompactionConfig,
+    config::{Codec, DbConfig, FamilyConfig},
     constants::MAX_MEDIUM_VALUE_SIZE,
-    db::{CompactConfig, TurboPersistence},
+    db::{CompactConfig, FamilyIter, TurboPersistence},
     write_batch::WriteBatch,
 };
 
@@ -672,3 +679,308 @@ fn merge_file_removal() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn iteration() -> Result<()> {
+    let tempdir = tempfile::tempdir()?;
+    let path = tempdir.path();
+
+    fn collect(iter: FamilyIter<'_>) -> Result<BTreeMap<Vec<u8>, Vec<u8>>> {
+        iter.map(|entry| {
+            let (key, value) = entry?;
+            Ok((key.to_vec(), value.to_vec()))
+        })
+        .collect()
+    }
+
+    let mut expected = BTreeMap::new();
+    {
+        let db = TurboPersistence::open(path.to_path_buf())?;
+        let b = db.write_batch::<_, 2>()?;
+        for i in 0..100u8 {
+            b.put(0, vec![i % 10, i], vec![i].into())?;
+            expected.insert(vec![i % 10, i], vec![i]);
+            b.put(1, vec![i % 10, i], vec![0].into())?;
+        }
+        db.commit_write_batch(b)?;
+
+        let b = db.write_batch::<_, 2>()?;
+        for i in (0..100u8).step_by(3) {
+            b.put(0, vec![i % 10, i], vec![i, i].into())?;
+            expected.insert(vec![i % 10, i], vec![i, i]);
+            unsafe { b.flush(0)? };
+        }
+        db.commit_write_batch(b)?;
+
+        let b = db.write_batch::<_, 2>()?;
+        for i in (0..100u8).step_by(7) {
+            b.delete(0, vec![i % 10, i])?;
+            expected.remove(&[i % 10, i][..]);
+        }
+        db.commit_write_batch(b)?;
+
+        assert_eq!(collect(db.iter(0)?)?, expected);
+        db.shutdown()?;
+    }
+
+    {
+        let db = TurboPersistence::open(path.to_path_buf())?;
+        assert_eq!(collect(db.iter(0)?)?, expected);
+        assert_eq!(collect(db.iter(1)?)?.len(), 100);
+        assert_eq!(collect(db.iter(2)?)?.len(), 0);
+
+        let with_prefix = expected
+            .iter()
+            .filter(|(key, _)| key[0] == 3)
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect::<BTreeMap<_, _>>();
+        assert_eq!(collect(db.prefix_iter(0, &[3])?)?, with_prefix);
+
+        db.full_compact()?;
+        assert_eq!(collect(db.iter(0)?)?, expected);
+        assert_eq!(collect(db.prefix_iter(0, &[3])?)?, with_prefix);
+        db.shutdown()?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn get_while_iterating() -> Result<()> {
+    let tempdir = tempfile::tempdir()?;
+    let path = tempdir.path();
+
+    let db = TurboPersistence::open(path.to_path_buf())?;
+    let b = db.write_batch::<_, 1>()?;
+    for i in 0..10u8 {
+        b.put(0, vec![i], vec![i].into())?;
+    }
+    db.commit_write_batch(b)?;
+
+    let mut iter = db.iter(0)?;
+    let (key, _) = iter.next().unwrap()?;
+    let b = db.write_batch::<_, 1>()?;
+    b.put(0, vec![100], vec![100].into())?;
+    std::thread::scope(|scope| -> Result<()> {
+        // The commit waits for the iterator to be dropped
+        let commit = scope.spawn(|| db.commit_write_batch(b));
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(db.get(0, &&*key)?.as_deref(), Some(&key[..]));
+        assert_eq!(iter.by_ref().count(), 9);
+        drop(iter);
+        commit.join().unwrap()
+    })?;
+    assert_eq!(db.get(0, &vec![100u8])?.as_deref(), Some(&[100u8][..]));
+
+    db.shutdown()?;
+    Ok(())
+}
+
+#[test]
+fn verify_and_orphaned_files() -> Result<()> {
+    let tempdir = tempfile::tempdir()?;
+    let path = tempdir.path();
+
+    let db = TurboPersistence::open(path.to_path_buf())?;
+    let b = db.write_batch::<_, 1>()?;
+    for i in 0..1000u32 {
+        b.put(0, i.to_be_bytes().to_vec(), i.to_be_bytes().to_vec().into())?;
+    }
+    db.commit_write_batch(b)?;
+
+    assert!(db.verify()?.is_empty());
+    assert!(db.orphaned_files()?.is_empty());
+
+    // Simulate files that were left over by an interrupted cleanup
+    let sst = fs::read_dir(path)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .find(|path| path.extension().is_some_and(|ext| ext == "sst"))
+        .unwrap();
+    fs::copy(&sst, path.join("00000000.sst"))?;
+    fs::write(path.join("00000000.blob"), [0; 16])?;
+
+    let orphaned_files = db.orphaned_files()?;
+    assert_eq!(orphaned_files.len(), 2);
+    assert_eq!(db.delete_orphaned_files()?.len(), 2);
+    assert!(!fs::exists(path.join("00000000.sst"))?);
+    assert!(!fs::exists(path.join("00000000.blob"))?);
+    assert!(db.orphaned_files()?.is_empty());
+
+    assert!(db.verify()?.is_empty());
+    for i in 0..1000u32 {
+        assert_eq!(
+            db.get(0, &i.to_be_bytes())?.as_deref(),
+            Some(&i.to_be_bytes()[..])
+        );
+    }
+    db.shutdown()?;
+
+    Ok(())
+}
+
+#[test]
+fn background_compaction() -> Result<()> {
+    let tempdir = tempfile::tempdir()?;
+    let path = tempdir.path();
+
+    fn sst_count(db: &TurboPersistence) -> Result<usize> {
+        Ok(db.meta_info()?.iter().map(|meta| meta.entries.len()).sum())
+    }
+
+    let db = Arc::new(TurboPersistence::open(path.to_path_buf())?);
+    for i in 0..10u8 {
+        let b = db.write_batch::<_, 1>()?;
+        for j in 0..100u32 {
+            b.put(0, j.to_be_bytes(), vec![i].into())?;
+        }
+        db.commit_write_batch(b)?;
+    }
+    let initial_sst_count = sst_count(&db)?;
+    assert_eq!(initial_sst_count, 10);
+
+    let background_compaction = db.start_background_compaction(BackgroundCompactionConfig {
+        compact_config: CompactConfig {
+            min_merge_duplication_bytes: 1,
+            optimal_merge_duplication_bytes: 1,
+            ..Default::default()
+        },
+        threads: 1,
+        max_bytes_per_second: None,
+        check_interval: Duration::from_millis(10),
+        write_cooldown: Duration::ZERO,
+        min_coverage: 2.0,
+        min_duplication_bytes: 1,
+    })?;
+
+    // Writes are still possible while compaction is running
+    let b = db.write_batch::<_, 1>()?;
+    b.put(0, 1000u32.to_be_bytes(), vec![10].into())?;
+    db.commit_write_batch(b)?;
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while sst_count(&db)? > initial_sst_count / 2 {
+        assert!(
+            Instant::now() < deadline,
+            "Background compaction didn't finish"
+        );
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    background_compaction.stop()?;
+
+    for j in 0..100u32 {
+        assert_eq!(db.get(0, &j.to_be_bytes())?.as_deref(), Some(&[9][..]));
+    }
+    assert_eq!(
+        db.get(0, &1000u32.to_be_bytes())?.as_deref(),
+        Some(&[10][..])
+    );
+
+    Ok(())
+}
+
+#[test]
+fn codecs() -> Result<()> {
+    let tempdir = tempfile::tempdir()?;
+    let path = tempdir.path();
+
+    let config = DbConfig {
+        families: vec![
+            FamilyConfig {
+                codec: Codec::None,
+                ..Default::default()
+            },
+            FamilyConfig {
+                codec: Codec::Lz4,
+                block_size: 8 * 1024,
+                compression_dictionary: false,
+                ..Default::default()
+            },
+            FamilyConfig {
+                codec: Codec::Zstd { level: 3 },
+                ..Default::default()
+            },
+        ],
+        default_family: FamilyConfig {
+            codec: Codec::Zstd { level: 1 },
+            compression_dictionary: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    const FAMILIES: usize = 4;
+    const COUNT: u32 = 10_000;
+    fn put(db: &TurboPersistence, version: u8) -> Result<()> {
+        let b = db.write_batch::<_, FAMILIES>()?;
+        for family in 0..FAMILIES as u32 {
+            for i in 0..COUNT {
+                let value = [version; 100].into_iter().chain(i.to_be_bytes());
+                b.put(family, i.to_be_bytes(), value.collect::<Vec<_>>().into())?;
+            }
+            // A medium sized value is stored in its own block
+            b.put(family, COUNT.to_be_bytes(), vec![version; 100_000].into())?;
+        }
+        db.commit_write_batch(b)?;
+        Ok(())
+    }
+    fn check(db: &TurboPersistence, version: u8) -> Result<()> {
+        for family in 0..FAMILIES {
+            for i in 0..COUNT {
+                let value = db.get(family, &i.to_be_bytes())?.unwrap();
+                assert_eq!(&value[..100], &[version; 100][..]);
+                assert_eq!(&value[100..], &i.to_be_bytes()[..]);
+            }
+            assert_eq!(
+                db.get(family, &COUNT.to_be_bytes())?.as_deref(),
+                Some(&vec![version; 100_000][..])
+            );
+        }
+        Ok(())
+    }
+
+    {
+        let db = TurboPersistence::open_with_config(path.to_path_buf(), config.clone())?;
+        put(&db, 1)?;
+        check(&db, 1)?;
+        db.shutdown()?;
+    }
+
+    // The codec is stored per SST file, so the files can be read with any configuration
+    {
+        let db = TurboPersistence::open(path.to_path_buf())?;
+        check(&db, 1)?;
+        put(&db, 2)?;
+        check(&db, 2)?;
+        db.shutdown()?;
+    }
+
+    {
+        let db = TurboPersistence::open_with_config(path.to_path_buf(), config)?;
+        put(&db, 3)?;
+        db.full_compact()?;
+        check(&db, 3)?;
+        assert!(db.verify()?.is_empty());
+        db.shutdown()?;
+    }
+
+    {
+        let db = TurboPersistence::open(path.to_path_buf())?;
+        check(&db, 3)?;
+        db.shutdown()?;
+    }
+
+    for block_size in [0, 1024] {
+        let invalid_config = DbConfig {
+            default_family: FamilyConfig {
+                block_size,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(TurboPersistence::open_with_config(path.to_path_buf(), invalid_config).is_err());
+    }
+
+    Ok(())
+}
@@ -23,6 +23,7 @@ use crate::{
     ValueBuffer,
     collector::Collector,
     collector_entry::CollectorEntry,
+    config::{DbConfig, FamilyConfig},
     constants::{MAX_MEDIUM_VALUE_SIZE, THREAD_LOCAL_SIZE_SHIFT},
     key::StoreKey,
     meta_file_builder::MetaFileBuilder,
@@ -82,11 +83,13 @@ pub struct WriteBatch<K: StoreKey + Send, const FAMILIES: usize> {
     /// The list of new SST files that have been created.
     /// Tuple of (sequence number, file).
     new_sst_files: Mutex<Vec<(u32, File)>>,
+    /// The configuration for each family.
+    family_configs: [FamilyConfig; FAMILIES],
 }
 
 impl<K: StoreKey + Send + Sync, const FAMILIES: usize> WriteBatch<K, FAMILIES> {
     /// Creates a new write batch for a database.
-    pub(crate) fn new(path: PathBuf, current: u32) -> Self {
+    pub(crate) fn new(path: PathBuf, current: u32, config: &DbConfig) -> Self {
         const {
             assert!(FAMILIES <= usize_from_u32(u32::MAX));
         };
@@ -98,6 +101,7 @@ impl<K: StoreKey + Send + Sync, const FAMILIES: usize> WriteBatch<K, FAMILIES> {
                 .map(|_| Mutex::new(GlobalCollectorState::Unsharded(Collector::new()))),
             meta_collectors: [(); FAMILIES].map(|_| Mutex::new(Vec::new())),
             new_sst_files: Mutex::new(Vec::new()),
+            family_configs: std::array::from_fn(|family| config.family(family as u32).clone()),
         }
     }
 
@@ -409,9 +413,14 @@ impl<K: StoreKey + Send + Sync, const FAMILIES: usize> WriteBatch<K, FAMILIES> {
         let seq = self.current_sequence_number.fetch_add(1, Ordering::SeqCst) + 1;
 
         let path = self.db_path.join(format!("{seq:08}.sst"));
-        let (meta, file) =
-            write_static_stored_file(entries, total_key_size, total_value_size, &path)
-                .with_context(|| format!("Unable to write SST file {seq:08}.sst"))?;
+        let (meta, file) = write_static_stored_file(
+            entries,
+            total_key_size,
+            total_value_size,
+            &path,
+            &self.family_configs[usize_from_u32(family)],
+        )
+        .with_context(|| format!("Unable to write SST file {seq:08}.sst"))?;
 
         #[cfg(feature = "verify_sst_content")]
         {
@@ -435,6 +444,7 @@ impl<K: StoreKey + Send + Sync, const FAMILIES: usize> WriteBatch<K, FAMILIES> {
                     key_compression_dictionary_length: meta.key_compression_dictionary_length,
                     value_compression_dictionary_length: meta.value_compression_dictionary_length,
                     block_count: meta.block_count,
+                    codec: meta.codec,
                 },
             )?;
             let cache2 = BlockCache::with(
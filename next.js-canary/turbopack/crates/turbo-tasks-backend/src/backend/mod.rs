@@ -33,6 +33,7 @@ use turbo_tasks::{
         TransientTaskType, TurboTasksExecutionError, TypedCellContent,
     },
     event::{Event, EventListener},
+    invalidation_chain::{InvalidationChainsApi, InvalidationRoot},
     message_queue::TimingEvent,
     registry::{self, get_value_type_global_name},
     task_statistics::TaskStatisticsApi,
@@ -40,6 +41,7 @@ use turbo_tasks::{
     turbo_tasks,
     util::IdFactoryWithReuse,
 };
+use turbo_tasks_malloc::TurboMalloc;
 
 pub use self::{operation::AnyOperation, storage::TaskDataCategory};
 #[cfg(feature = "trace_task_dirty")]
@@ -142,6 +144,15 @@ pub struct BackendOptions {
 
     /// Avoid big preallocations for faster startup. Should only be used for testing purposes.
     pub small_preallocation: bool,
+
+    /// Memory budget in bytes for the in-memory task storage.
+    ///
+    /// When the process memory usage (as reported by `TurboMalloc`) exceeds this limit, the
+    /// backend persists all changes and drops cold tasks from memory. They are restored from
+    /// the backing storage on the next access.
+    ///
+    /// Only has an effect when `storage_mode` is `ReadWrite`.
+    pub memory_limit: Option<usize>,
 }
 
 impl Default for BackendOptions {
@@ -152,6 +163,7 @@ impl Default for BackendOptions {
             active_tracking: true,
             storage_mode: Some(StorageMode::ReadWrite),
             small_preallocation: false,
+            memory_limit: None,
         }
     }
 }
@@ -201,6 +213,7 @@ struct TurboTasksBackendInner<B: BackingStorage> {
     is_idle: AtomicBool,
 
     task_statistics: TaskStatisticsApi,
+    invalidation_chains: InvalidationChainsApi,
 
     backing_storage: B,
 
@@ -262,6 +275,7 @@ impl<B: BackingStorage> TurboTasksBackendInner<B> {
             #[cfg(feature = "verify_aggregation_graph")]
             is_idle: AtomicBool::new(false),
             task_statistics: TaskStatisticsApi::default(),
+            invalidation_chains: InvalidationChainsApi::default(),
             backing_storage,
             #[cfg(feature = "verify_aggregation_graph")]
             root_tasks: Default::default(),
@@ -376,6 +390,18 @@ impl<B: BackingStorage> TurboTasksBackendInner<B> {
         self.options.children_tracking
     }
 
+    fn should_evict(&self) -> bool {
+        self.should_persist() && self.options.memory_limit.is_some()
+    }
+
+    fn is_over_memory_limit(&self) -> bool {
+        self.should_persist()
+            && self
+                .options
+                .memory_limit
+                .is_some_and(|limit| TurboMalloc::memory_usage() > limit)
+    }
+
     fn track_cache_hit(&self, task_type: &CachedTaskType) {
         self.task_statistics
             .map(|stats| stats.increment_cache_hit(task_type.native_fn));
@@ -1181,6 +1207,24 @@ impl<B: BackingStorage> TurboTasksBackendInner<B> {
         Some((snapshot_time, new_items))
     }
 
+    /// Drops tasks from memory that are fully persisted, have no in-memory-only state (e.g. they
+    /// are not active or in progress) and haven't been accessed since the previous eviction. They
+    /// are restored from the backing storage on the next access.
+    ///
+    /// Should be called after a snapshot, so that all previously modified tasks are persisted.
+    fn evict_cold_tasks(&self) {
+        let span = tracing::trace_span!("evict cold tasks", evicted = Empty).entered();
+        let evicted = self.storage.evict_cold(|task_id, inner| {
+            !task_id.is_transient()
+                && !inner.state().any_modified()
+                && !inner.state().any_snapshot()
+                && inner
+                    .iter_all()
+                    .all(|(key, value)| key.is_persistent() && value.is_persistent())
+        });
+        span.record("evicted", evicted);
+    }
+
     fn startup(&self, turbo_tasks: &dyn TurboTasksBackendApi<TurboTasksBackend<B>>) {
         if self.should_restore() {
             // Continue all uncompleted operations
@@ -1443,6 +1487,9 @@ impl<B: BackingStorage> TurboTasksBackendInner<B> {
         if !self.should_track_dependencies() {
             panic!("Dependency tracking is disabled so invalidation is not allowed");
         }
+        if let Some(chains) = self.invalidation_chains.get() {
+            chains.record_invalidation(task_id, InvalidationRoot::Unknown);
+        }
         operation::InvalidateOperation::run(
             smallvec![task_id],
             #[cfg(feature = "trace_task_dirty")]
@@ -1459,6 +1506,11 @@ impl<B: BackingStorage> TurboTasksBackendInner<B> {
         if !self.should_track_dependencies() {
             panic!("Dependency tracking is disabled so invalidation is not allowed");
         }
+        if let Some(chains) = self.invalidation_chains.get() {
+            for &task_id in tasks.iter() {
+                chains.record_invalidation(task_id, InvalidationRoot::Unknown);
+            }
+        }
         operation::InvalidateOperation::run(
             tasks.iter().copied().collect(),
             #[cfg(feature = "trace_task_dirty")]
@@ -1475,6 +1527,11 @@ impl<B: BackingStorage> TurboTasksBackendInner<B> {
         if !self.should_track_dependencies() {
             panic!("Dependency tracking is disabled so invalidation is not allowed");
         }
+        if let Some(chains) = self.invalidation_chains.get() {
+            for &task_id in tasks.iter() {
+                chains.record_invalidation(task_id, InvalidationRoot::Unknown);
+            }
+        }
         operation::InvalidateOperation::run(
             tasks.iter().copied().collect(),
             #[cfg(feature = "trace_task_dirty")]
@@ -1642,6 +1699,10 @@ impl<B: BackingStorage> TurboTasksBackendInner<B> {
             }
         }
 
+        if let Some(chains) = self.invalidation_chains.get() {
+            chains.start_execution(task_id, || self.get_task_description(task_id));
+        }
+
         let (span, future) = match task_type {
             TaskType::Cached(task_type) => {
                 let CachedTaskType {
@@ -2127,6 +2188,7 @@ impl<B: BackingStorage> TurboTasksBackendInner<B> {
                     const FIRST_SNAPSHOT_WAIT: Duration = Duration::from_secs(60);
                     const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(30);
                     const IDLE_TIMEOUT: Duration = Duration::from_secs(2);
+                    const MEMORY_CHECK_INTERVAL: Duration = Duration::from_secs(1);
 
                     let time = if id == BACKEND_JOB_INITIAL_SNAPSHOT {
                         FIRST_SNAPSHOT_WAIT
@@ -2166,6 +2228,11 @@ impl<B: BackingStorage> TurboTasksBackendInner<B> {
                                             break;
                                         }
                                     },
+                                    _ = tokio::time::sleep(MEMORY_CHECK_INTERVAL), if self.should_evict() => {
+                                        if self.is_over_memory_limit() {
+                                            break;
+                                        }
+                                    },
                                 }
                             }
                         }
@@ -2175,6 +2242,10 @@ impl<B: BackingStorage> TurboTasksBackendInner<B> {
                     let snapshot = turbo_tasks::spawn_blocking(move || this.snapshot()).await;
                     if let Some((snapshot_start, new_data)) = snapshot {
                         last_snapshot = snapshot_start;
+                        if self.is_over_memory_limit() {
+                            let this = self.clone();
+                            turbo_tasks::spawn_blocking(move || this.evict_cold_tasks()).await;
+                        }
                         if new_data {
                             continue;
                         }
@@ -3033,6 +3104,10 @@ impl<B: BackingStorage> Backend for TurboTasksBackend<B> {
     fn task_statistics(&self) -> &TaskStatisticsApi {
         &self.0.task_statistics
     }
+
+    fn invalidation_chains(&self) -> &InvalidationChainsApi {
+        &self.0.invalidation_chains
+    }
 }
 
 enum DebugTraceTransientTask {
@@ -20,7 +20,9 @@ use smallvec::{SmallVec, smallvec};
     feature = "trace_find_and_schedule"
 ))]
 use tracing::{span::Span, trace_span};
-use turbo_tasks::{FxIndexMap, SessionId, TaskExecutionReason, TaskId};
+use turbo_tasks::{
+    FxIndexMap, SessionId, TaskExecutionReason, TaskId, invalidation_chain::InvalidationRoot,
+};
 
 #[cfg(feature = "trace_task_dirty")]
 use crate::backend::operation::invalidate::TaskDirtyCause;
@@ -872,6 +874,10 @@ impl AggregationUpdateQueue {
                     collectible_type,
                 } => {
                     for task_id in task_ids {
+                        if let Some(chains) = ctx.invalidation_chains() {
+                            chains
+                                .record_invalidation(task_id, InvalidationRoot::CollectiblesChange);
+                        }
                         make_task_dirty(
                             task_id,
                             #[cfg(feature = "trace_task_dirty")]
@@ -3,7 +3,7 @@ use std::mem::take;
 use rustc_hash::FxHashSet;
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
-use turbo_tasks::TaskId;
+use turbo_tasks::{TaskId, invalidation_chain::DependencyChange};
 
 #[cfg(feature = "trace_task_dirty")]
 use crate::backend::operation::invalidate::TaskDirtyCause;
@@ -218,12 +218,20 @@ impl Operation for CleanupOldEdgesOperation {
                                 }
                             }
                             OutdatedEdge::RemovedCellDependent {
-                                task_id,
+                                task_id: dependent_task_id,
                                 #[cfg(feature = "trace_task_dirty")]
                                 value_type_id,
                             } => {
+                                if let Some(chains) = ctx.invalidation_chains() {
+                                    chains.record_dependency_change(
+                                        task_id,
+                                        || ctx.get_task_description(task_id),
+                                        DependencyChange::Cell,
+                                        [dependent_task_id],
+                                    );
+                                }
                                 make_task_dirty(
-                                    task_id,
+                                    dependent_task_id,
                                     #[cfg(feature = "trace_task_dirty")]
                                     TaskDirtyCause::CellRemoved {
                                         value_type: value_type_id,
@@ -11,10 +11,13 @@ mod update_output;
 use std::{
     fmt::{Debug, Formatter},
     mem::transmute,
+    sync::Arc,
 };
 
 use serde::{Deserialize, Serialize};
-use turbo_tasks::{KeyValuePair, SessionId, TaskId, TurboTasksBackendApi};
+use turbo_tasks::{
+    KeyValuePair, SessionId, TaskId, TurboTasksBackendApi, invalidation_chain::InvalidationChains,
+};
 
 use crate::{
     backend::{
@@ -65,6 +68,7 @@ pub trait ExecuteContext<'e>: Sized {
     fn should_track_children(&self) -> bool;
     fn should_track_dependencies(&self) -> bool;
     fn should_track_activeness(&self) -> bool;
+    fn invalidation_chains(&self) -> Option<&Arc<InvalidationChains>>;
 }
 
 pub struct ExecuteContextImpl<'e, 'tx, B: BackingStorage>
@@ -159,7 +163,9 @@ where
                 task.state_mut().set_restored(TaskDataCategory::All);
             } else {
                 for category in category {
-                    if !task.state().is_restored(category) {
+                    // A task might be evicted while the lock is released, so we need to check again
+                    // after reacquiring it.
+                    while !task.state().is_restored(category) {
                         // Avoid holding the lock too long since this can also affect other tasks
                         drop(task);
 
@@ -202,31 +208,45 @@ where
         category: TaskDataCategory,
     ) -> (impl TaskGuard + 'e, impl TaskGuard + 'e) {
         let (mut task1, mut task2) = self.backend.storage.access_pair_mut(task_id1, task_id2);
-        let is_restored1 = task1.state().is_restored(category);
-        let is_restored2 = task2.state().is_restored(category);
-        if !is_restored1 || !is_restored2 {
+        if !task1.state().is_restored(category) || !task2.state().is_restored(category) {
             for category in category {
-                // Avoid holding the lock too long since this can also affect other tasks
-                drop(task1);
-                drop(task2);
-
-                let items1 = (!is_restored1).then(|| self.restore_task_data(task_id1, category));
-                let items2 = (!is_restored2).then(|| self.restore_task_data(task_id2, category));
-
-                let (t1, t2) = self.backend.storage.access_pair_mut(task_id1, task_id2);
-                task1 = t1;
-                task2 = t2;
-                if !task1.state().is_restored(category) {
-                    for item in items1.unwrap() {
-                        task1.add(item);
+                // A task might be evicted while the locks are released, so we need to check again
+                // after reacquiring them.
+                loop {
+                    let is_restored1 = task1.state().is_restored(category);
+                    let is_restored2 = task2.state().is_restored(category);
+                    if is_restored1 && is_restored2 {
+                        break;
                     }
-                    task1.state_mut().set_restored(category);
-                }
-                if !task2.state().is_restored(category) {
-                    for item in items2.unwrap() {
-                        task2.add(item);
+
+                    // Avoid holding the lock too long since this can also affect other tasks
+                    drop(task1);
+                    drop(task2);
+
+                    let items1 =
+                        (!is_restored1).then(|| self.restore_task_data(task_id1, category));
+                    let items2 =
+                        (!is_restored2).then(|| self.restore_task_data(task_id2, category));
+
+                    let (t1, t2) = self.backend.storage.access_pair_mut(task_id1, task_id2);
+                    task1 = t1;
+                    task2 = t2;
+                    if let Some(items1) = items1
+                        && !task1.state().is_restored(category)
+                    {
+                        for item in items1 {
+                            task1.add(item);
+                        }
+                        task1.state_mut().set_restored(category);
+                    }
+                    if let Some(items2) = items2
+                        && !task2.state().is_restored(category)
+                    {
+                        for item in items2 {
+                            task2.add(item);
+                        }
+                        task2.state_mut().set_restored(category);
                     }
-                    task2.state_mut().set_restored(category);
                 }
             }
         }
@@ -279,6 +299,10 @@ where
     fn should_track_activeness(&self) -> bool {
         self.backend.should_track_activeness()
     }
+
+    fn invalidation_chains(&self) -> Option<&Arc<InvalidationChains>> {
+        self.backend.invalidation_chains.get()
+    }
 }
 
 pub trait TaskGuard: Debug {
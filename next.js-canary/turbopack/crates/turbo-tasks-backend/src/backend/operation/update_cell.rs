@@ -1,4 +1,5 @@
-use turbo_tasks::{CellId, TaskId, backend::CellContent};
+use smallvec::SmallVec;
+use turbo_tasks::{CellId, TaskId, backend::CellContent, invalidation_chain::DependencyChange};
 
 #[cfg(feature = "trace_task_dirty")]
 use crate::backend::operation::invalidate::TaskDirtyCause;
@@ -39,7 +40,7 @@ impl UpdateCellOperation {
                 // This is a hack for the streaming hack. Stateful tasks are never recomputed, so this forces invalidation for them in case of this hack.
                 task.has_key(&CachedDataItemKey::Stateful {}))
         {
-            let dependent = get_many!(
+            let dependent: SmallVec<[TaskId; 4]> = get_many!(
                 task,
                 CellDependent { cell: dependent_cell, task }
                 if dependent_cell == cell
@@ -49,6 +50,15 @@ impl UpdateCellOperation {
             drop(task);
             drop(old_content);
 
+            if let Some(chains) = ctx.invalidation_chains() {
+                chains.record_dependency_change(
+                    task_id,
+                    || ctx.get_task_description(task_id),
+                    DependencyChange::Cell,
+                    dependent.iter().copied(),
+                );
+            }
+
             InvalidateOperation::run(
                 dependent,
                 #[cfg(feature = "trace_task_dirty")]
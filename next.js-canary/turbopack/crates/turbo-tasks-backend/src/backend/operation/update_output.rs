@@ -3,7 +3,9 @@ use std::mem::take;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
-use turbo_tasks::{RawVc, TaskId, backend::TurboTasksExecutionError};
+use turbo_tasks::{
+    RawVc, TaskId, backend::TurboTasksExecutionError, invalidation_chain::DependencyChange,
+};
 
 #[cfg(feature = "trace_task_dirty")]
 use crate::backend::operation::invalidate::TaskDirtyCause;
@@ -48,7 +50,7 @@ impl UpdateOutputOperation {
         output: Result<RawVc, TurboTasksExecutionError>,
         mut ctx: impl ExecuteContext,
     ) {
-        let mut dependent_tasks = Default::default();
+        let mut dependent_tasks: SmallVec<[TaskId; 4]> = Default::default();
         let mut children = Default::default();
         let mut queue = AggregationUpdateQueue::new();
 
@@ -129,6 +131,15 @@ impl UpdateOutputOperation {
             drop(old_content);
         }
 
+        if let Some(chains) = ctx.invalidation_chains() {
+            chains.record_dependency_change(
+                task_id,
+                || ctx.get_task_description(task_id),
+                DependencyChange::Output,
+                dependent_tasks.iter().copied(),
+            );
+        }
+
         UpdateOutputOperation::MakeDependentTasksDirty {
             #[cfg(feature = "trace_task_dirty")]
             task_id,
@@ -99,6 +99,8 @@ bitfield! {
     /// Item was modified after snapshot mode was entered. A snapshot was taken.
     pub meta_snapshot, set_meta_snapshot: 4;
     pub data_snapshot, set_data_snapshot: 5;
+    /// Item was accessed since the last eviction.
+    pub accessed, set_accessed: 6;
 }
 
 impl InnerStorageState {
@@ -784,11 +786,40 @@ impl Storage {
             .load(std::sync::atomic::Ordering::Acquire)
     }
 
+    /// Removes all items that haven't been accessed since the previous eviction and match the
+    /// given predicate. Accessed items are kept, but marked as unaccessed, so they are considered
+    /// by the next eviction.
+    ///
+    /// The predicate must ensure that the item can be restored from the backing storage.
+    /// Returns the number of removed items.
+    pub fn evict_cold(&self, can_evict: impl Fn(TaskId, &InnerStorage) -> bool) -> usize {
+        let mut evicted = 0;
+        self.map.retain(|key, inner| {
+            let state = inner.state_mut();
+            if state.accessed() {
+                state.set_accessed(false);
+                return true;
+            }
+            if can_evict(*key, inner) {
+                evicted += 1;
+                false
+            } else {
+                true
+            }
+        });
+        // Shrinking rehashes the whole map, so only do it when most of the capacity is unused.
+        if evicted > 0 && self.map.capacity() > self.map.len() * 4 {
+            self.map.shrink_to_fit();
+        }
+        evicted
+    }
+
     pub fn access_mut(&self, key: TaskId) -> StorageWriteGuard<'_> {
-        let inner = match self.map.entry(key) {
+        let mut inner = match self.map.entry(key) {
             dashmap::mapref::entry::Entry::Occupied(e) => e.into_ref(),
             dashmap::mapref::entry::Entry::Vacant(e) => e.insert(Box::new(InnerStorage::new())),
         };
+        inner.state_mut().set_accessed(true);
         StorageWriteGuard {
             storage: self,
             inner: inner.into(),
@@ -800,7 +831,10 @@ impl Storage {
         key1: TaskId,
         key2: TaskId,
     ) -> (StorageWriteGuard<'_>, StorageWriteGuard<'_>) {
-        let (a, b) = get_multiple_mut(&self.map, key1, key2, || Box::new(InnerStorage::new()));
+        let (mut a, mut b) =
+            get_multiple_mut(&self.map, key1, key2, || Box::new(InnerStorage::new()));
+        a.state_mut().set_accessed(true);
+        b.state_mut().set_accessed(true);
         (
             StorageWriteGuard {
                 storage: self,
@@ -1145,3 +1179,44 @@ where
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use turbo_tasks::TaskId;
+
+    use super::{Storage, TaskDataCategory};
+
+    #[test]
+    fn evict_cold() {
+        let storage = Storage::new(false);
+        let task1 = TaskId::try_from(1u32).unwrap();
+        let task2 = TaskId::try_from(2u32).unwrap();
+        for task_id in [task1, task2] {
+            storage
+                .access_mut(task_id)
+                .state_mut()
+                .set_restored(TaskDataCategory::All);
+        }
+
+        // Recently accessed tasks survive one pass
+        assert_eq!(storage.evict_cold(|_, _| true), 0);
+
+        drop(storage.access_mut(task1));
+        assert_eq!(storage.evict_cold(|_, _| true), 1);
+        assert_eq!(storage.evict_cold(|task_id, _| task_id != task1), 0);
+
+        // An evicted task is recreated empty and has to be restored again
+        assert!(
+            !storage
+                .access_mut(task2)
+                .state()
+                .is_restored(TaskDataCategory::Meta)
+        );
+        assert!(
+            storage
+                .access_mut(task1)
+                .state()
+                .is_restored(TaskDataCategory::All)
+        );
+    }
+}
@@ -0,0 +1,759 @@
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    ffi::OsStr,
+    fs::{self, File, read_dir, remove_dir_all, rename},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use byteorder::{BE, ReadBytesExt, WriteBytesExt};
+use pot::format::{Kind, read_atom_header, write_atom_header};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::registry;
+
+use crate::database::{
+    db_invalidation::{StartupCacheState, check_db_invalidation_and_cleanup},
+    db_versioning::{DELETION_PREFIX, GitVersionInfo},
+    key_value_database::{KeySpace, KeyValueDatabase},
+    turbo::TurboKeyValueDatabase,
+    write_batch::{BaseWriteBatch, SerialWriteBatch},
+};
+
+/// The magic bytes at the start of every cache archive.
+const ARCHIVE_MAGIC: &[u8; 8] = b"TTCACHE\0";
+
+/// The version of the archive layout. Increase this when the layout of the archive changes.
+const ARCHIVE_FORMAT_VERSION: u32 = 2;
+
+/// The manifest of a cache archive. It's stored at the start of the archive and describes the
+/// environment the cache was created in, so an import can check if the cache is usable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheArchiveManifest {
+    /// The version of the archive layout.
+    pub format_version: u32,
+    /// The version (`git describe`) of the binary that created the cache. The cache is only
+    /// imported by the same version.
+    pub version: String,
+    /// The global names of all task types (native functions) that were registered when the
+    /// cache was exported. Persisted tasks reference their task type by this name.
+    pub task_types: Vec<String>,
+    /// The global names of all value types that were registered when the cache was exported.
+    pub value_types: Vec<String>,
+    /// The global names of all trait types that were registered when the cache was exported.
+    pub trait_types: Vec<String>,
+    /// The absolute project root the cache was created for. Paths below it are rewritten when
+    /// the cache is imported for a different project root.
+    pub project_root: String,
+    /// The database files in the archive. Their contents follow the manifest in this order.
+    pub files: Vec<CacheArchiveFile>,
+}
+
+/// A database file in a cache archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheArchiveFile {
+    /// The file name relative to the versioned database directory.
+    pub name: String,
+    /// The size of the file in bytes.
+    pub size: u64,
+}
+
+impl CacheArchiveManifest {
+    /// Checks if a cache with this manifest can be used by the current binary. Returns an error
+    /// that describes the incompatibility otherwise.
+    pub fn check_compatibility(&self, version_info: &GitVersionInfo) -> Result<()> {
+        if self.format_version != ARCHIVE_FORMAT_VERSION {
+            bail!(
+                "The cache archive has format version {}, but only version \
+                 {ARCHIVE_FORMAT_VERSION} is supported",
+                self.format_version
+            );
+        }
+        if version_info.dirty {
+            bail!("The git repository is dirty, a cache archive can't be used");
+        }
+        if self.version != version_info.describe {
+            bail!(
+                "The cache archive was created by version {}, but this is version {}",
+                self.version,
+                version_info.describe
+            );
+        }
+        check_registered(
+            "task types",
+            &self.task_types,
+            registry::function_global_names(),
+        )?;
+        check_registered(
+            "value types",
+            &self.value_types,
+            registry::value_type_global_names(),
+        )?;
+        check_registered(
+            "trait types",
+            &self.trait_types,
+            registry::trait_type_global_names(),
+        )?;
+        Ok(())
+    }
+}
+
+/// Fails when some of `names` are not part of `registered`.
+fn check_registered(kind: &str, names: &[String], registered: Vec<&'static str>) -> Result<()> {
+    let registered = registered.into_iter().collect::<HashSet<_>>();
+    let missing = names
+        .iter()
+        .filter(|name| !registered.contains(name.as_str()))
+        .collect::<Vec<_>>();
+    if let Some(first) = missing.first() {
+        bail!(
+            "The cache archive references {} {kind} that are unknown to this binary, e.g. {first}",
+            missing.len()
+        );
+    }
+    Ok(())
+}
+
+fn sorted_names(names: Vec<&'static str>) -> Vec<String> {
+    let mut names = names.into_iter().map(str::to_string).collect::<Vec<_>>();
+    names.sort_unstable();
+    names
+}
+
+/// Packs the persistent cache for `version_info` into a single archive file at `archive_path`.
+///
+/// The archive only contains file names relative to the versioned database directory and records
+/// the `project_root` the cache was created for, so it can be imported with [`import_cache`] into a
+/// cache directory at a different location and for a different project root, e.g. on another
+/// machine. The database must not be opened while it's exported.
+pub fn export_cache(
+    base_path: &Path,
+    version_info: &GitVersionInfo,
+    project_root: &Path,
+    archive_path: &Path,
+) -> Result<CacheArchiveManifest> {
+    if version_info.dirty {
+        bail!("The git repository is dirty, the persistent cache can't be exported");
+    }
+    match check_db_invalidation_and_cleanup(base_path)
+        .context("Failed to check database invalidation and cleanup")?
+    {
+        StartupCacheState::Cached => {}
+        StartupCacheState::NoCache => bail!("There is no persistent cache in {base_path:?}"),
+        StartupCacheState::Invalidated { .. } => {
+            bail!("The persistent cache in {base_path:?} was invalidated")
+        }
+    }
+    let db_path = base_path.join(version_info.describe);
+    let mut files = Vec::new();
+    for entry in read_dir(&db_path)
+        .with_context(|| format!("No persistent cache for version {}", version_info.describe))?
+    {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if !metadata.is_file() {
+            continue;
+        }
+        let Ok(name) = entry.file_name().into_string() else {
+            bail!(
+                "Unexpected file name {:?} in {db_path:?}",
+                entry.file_name()
+            );
+        };
+        files.push(CacheArchiveFile {
+            name,
+            size: metadata.len(),
+        });
+    }
+    files.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+    let manifest = CacheArchiveManifest {
+        format_version: ARCHIVE_FORMAT_VERSION,
+        version: version_info.describe.to_string(),
+        task_types: sorted_names(registry::function_global_names()),
+        value_types: sorted_names(registry::value_type_global_names()),
+        trait_types: sorted_names(registry::trait_type_global_names()),
+        project_root: project_root_to_string(project_root)?,
+        files,
+    };
+
+    // Write to a temporary file first, so a partial archive is never picked up.
+    let mut partial_path = archive_path.as_os_str().to_owned();
+    partial_path.push(".partial");
+    let partial_path = PathBuf::from(partial_path);
+    write_archive(&manifest, &db_path, &partial_path)
+        .with_context(|| format!("Failed to write cache archive {archive_path:?}"))?;
+    rename(&partial_path, archive_path)?;
+    Ok(manifest)
+}
+
+fn write_archive(
+    manifest: &CacheArchiveManifest,
+    db_path: &Path,
+    archive_path: &Path,
+) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(archive_path)?);
+    writer.write_all(ARCHIVE_MAGIC)?;
+    let manifest_json = serde_json::to_vec(manifest)?;
+    writer.write_u32::<BE>(manifest_json.len().try_into()?)?;
+    writer.write_all(&manifest_json)?;
+    for file in &manifest.files {
+        let path = db_path.join(&file.name);
+        let copied = io::copy(&mut File::open(&path)?.take(file.size), &mut writer)?;
+        if copied != file.size {
+            bail!("{path:?} was modified during the export. Is the database still in use?");
+        }
+    }
+    let file = writer.into_inner()?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Reads the manifest of a cache archive without importing it.
+pub fn read_cache_archive_manifest(archive_path: &Path) -> Result<CacheArchiveManifest> {
+    let mut reader = BufReader::new(
+        File::open(archive_path)
+            .with_context(|| format!("Failed to open cache archive {archive_path:?}"))?,
+    );
+    read_manifest(&mut reader)
+        .with_context(|| format!("Failed to read the manifest of cache archive {archive_path:?}"))
+}
+
+fn read_manifest(reader: &mut impl Read) -> Result<CacheArchiveManifest> {
+    let mut magic = [0; ARCHIVE_MAGIC.len()];
+    reader.read_exact(&mut magic)?;
+    if &magic != ARCHIVE_MAGIC {
+        bail!("Not a cache archive");
+    }
+    let manifest_len = reader.read_u32::<BE>()?;
+    let mut manifest_json = vec![0; manifest_len as usize];
+    reader.read_exact(&mut manifest_json)?;
+    Ok(serde_json::from_slice(&manifest_json)?)
+}
+
+fn project_root_to_string(project_root: &Path) -> Result<String> {
+    if !project_root.is_absolute() {
+        bail!("The project root {project_root:?} must be an absolute path");
+    }
+    Ok(project_root
+        .to_str()
+        .with_context(|| format!("The project root {project_root:?} is not valid UTF-8"))?
+        .trim_end_matches(['/', '\\'])
+        .to_string())
+}
+
+/// Restores a cache archive created by [`export_cache`] into the cache directory at `base_path`.
+///
+/// The manifest of the archive is checked for compatibility with `version_info` and the registered
+/// task types before anything is written. When the cache was exported for a different project
+/// root, all paths below that root are rewritten to be below `project_root`. An existing database
+/// for the same version is replaced. The database must not be opened while it's imported.
+pub fn import_cache(
+    base_path: &Path,
+    version_info: &GitVersionInfo,
+    project_root: &Path,
+    archive_path: &Path,
+) -> Result<CacheArchiveManifest> {
+    let mut reader = BufReader::new(
+        File::open(archive_path)
+            .with_context(|| format!("Failed to open cache archive {archive_path:?}"))?,
+    );
+    let manifest = read_manifest(&mut reader).with_context(|| {
+        format!("Failed to read the manifest of cache archive {archive_path:?}")
+    })?;
+    manifest
+        .check_compatibility(version_info)
+        .context("The cache archive is not compatible")?;
+    let project_root = project_root_to_string(project_root)?;
+
+    // An invalidation marker would delete the imported database on the next start up.
+    check_db_invalidation_and_cleanup(base_path)
+        .context("Failed to check database invalidation and cleanup")?;
+    fs::create_dir_all(base_path)?;
+
+    // Unpack into a directory with the deletion prefix first, so a partially imported database is
+    // cleaned up on the next start up.
+    let staging_path = base_path.join(format!("{DELETION_PREFIX}import_{}", manifest.version));
+    if fs::exists(&staging_path)? {
+        remove_dir_all(&staging_path)?;
+    }
+    fs::create_dir(&staging_path)?;
+    for file in &manifest.files {
+        if Path::new(&file.name).file_name() != Some(OsStr::new(&file.name)) {
+            bail!("Invalid file name {:?} in cache archive", file.name);
+        }
+        let path = staging_path.join(&file.name);
+        let mut writer = File::create(&path)?;
+        let copied = io::copy(&mut (&mut reader).take(file.size), &mut writer)?;
+        if copied != file.size {
+            bail!("Cache archive {archive_path:?} is truncated");
+        }
+        writer.sync_all()?;
+    }
+
+    let staging_path = if manifest.project_root != project_root {
+        let relocated_path =
+            base_path.join(format!("{DELETION_PREFIX}relocate_{}", manifest.version));
+        if fs::exists(&relocated_path)? {
+            remove_dir_all(&relocated_path)?;
+        }
+        let source = TurboKeyValueDatabase::new(staging_path.clone(), false, true)?;
+        let target = TurboKeyValueDatabase::new(relocated_path.clone(), false, true)?;
+        relocate_database(&source, &target, &manifest.project_root, &project_root).with_context(
+            || {
+                format!(
+                    "Failed to relocate the cache from {} to {project_root}",
+                    manifest.project_root
+                )
+            },
+        )?;
+        target.shutdown()?;
+        source.shutdown()?;
+        remove_dir_all(&staging_path)?;
+        relocated_path
+    } else {
+        staging_path
+    };
+
+    let db_path = base_path.join(&manifest.version);
+    if fs::exists(&db_path)? {
+        // rename first, it's an atomic operation
+        let stale_path = base_path.join(format!("{DELETION_PREFIX}{}", manifest.version));
+        rename(&db_path, &stale_path)?;
+        // It's okay if this fails, the directory is cleaned up on the next start up.
+        let _ = remove_dir_all(&stale_path);
+    }
+    rename(&staging_path, &db_path)?;
+    Ok(manifest)
+}
+
+/// The names of the fields that hold absolute paths, e.g. the root of a `DiskFileSystem`. Strings
+/// in task data are only rewritten when they are the value of one of these fields.
+const PATH_FIELDS: &[&[u8]] = &[b"root", b"root_path"];
+
+/// Copies all entries of the database `source` to the database `target` and rewrites all paths
+/// below `from` to be below `to` on the way.
+///
+/// Only task types and [`PATH_FIELDS`] are rewritten. Task data may contain the old root in other
+/// places, e.g. in file contents or issue messages, where it can't be told apart from a path. The
+/// relocation fails in this case instead of rewriting or keeping it, as both could produce a cache
+/// that doesn't match a fresh build.
+fn relocate_database(
+    source: &impl KeyValueDatabase,
+    target: &impl KeyValueDatabase,
+    from: &str,
+    to: &str,
+) -> Result<()> {
+    const KEY_SPACES: [KeySpace; 5] = [
+        KeySpace::Infra,
+        KeySpace::TaskMeta,
+        KeySpace::TaskData,
+        KeySpace::ForwardTaskCache,
+        KeySpace::ReverseTaskCache,
+    ];
+
+    if from.is_empty() {
+        bail!("A cache created for the file system root can't be relocated");
+    }
+    let mut batch = target.write_batch()?;
+    for key_space in KEY_SPACES {
+        // The forward task cache maps task types to task ids, the reverse task cache the other way
+        // around.
+        let (key_kind, value_kind) = match key_space {
+            KeySpace::ForwardTaskCache => (ValueKind::TaskType, ValueKind::Data),
+            KeySpace::ReverseTaskCache => (ValueKind::Data, ValueKind::TaskType),
+            _ => (ValueKind::Data, ValueKind::Data),
+        };
+        source.for_each_entry(key_space, &mut |key, value| {
+            let key = relocate_value(key, key_kind, from, to)?;
+            let value = relocate_value(value, value_kind, from, to)?;
+            batch.put(key_space, key.into(), value.into())
+        })?;
+    }
+    batch.commit()
+}
+
+/// What a value in the database holds. This decides which strings are rewritten.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValueKind {
+    /// A serialized task type. Its arguments are positional, so all strings that are paths below
+    /// the old root are rewritten.
+    TaskType,
+    /// Any other value, e.g. task data. Only the values of [`PATH_FIELDS`] are rewritten.
+    Data,
+}
+
+/// The header of a pot encoded value. The last byte is the version.
+const POT_HEADER: &[u8; 3] = b"Pot";
+
+/// Rewrites paths in a value. Everything that isn't pot encoded, e.g. task ids, is stored in a
+/// fixed size binary encoding and can't contain paths.
+fn relocate_value<'a>(
+    bytes: &'a [u8],
+    kind: ValueKind,
+    from: &str,
+    to: &str,
+) -> Result<Cow<'a, [u8]>> {
+    if bytes.len() <= 4 || !bytes.starts_with(POT_HEADER) {
+        if contains_path(bytes, from) {
+            bail!("A binary value references the project root {from}");
+        }
+        return Ok(Cow::Borrowed(bytes));
+    }
+    relocate_pot(bytes, kind, from, to)
+}
+
+/// Walks the atoms of a pot encoded value and rewrites the strings that are paths below `from`.
+/// Strings are encoded as [`Kind::Bytes`] atoms. Symbols are used for field and variant names, the
+/// value of a field directly follows the symbol of its name.
+fn relocate_pot<'a>(
+    bytes: &'a [u8],
+    kind: ValueKind,
+    from: &str,
+    to: &str,
+) -> Result<Cow<'a, [u8]>> {
+    let (header, mut atoms) = bytes.split_at(4);
+    let mut output = Vec::with_capacity(bytes.len());
+    output.extend_from_slice(header);
+    let mut changed = false;
+    // Symbols are only written once per value, later atoms reference them by their index.
+    let mut symbols = Vec::new();
+    let mut field = None;
+    while !atoms.is_empty() {
+        let atom_start = atoms;
+        let (atom_kind, arg) = read_atom_header(&mut atoms)?;
+        let atom_header = &atom_start[..atom_start.len() - atoms.len()];
+        let payload_len = match atom_kind {
+            Kind::Int | Kind::UInt | Kind::Float => arg as usize + 1,
+            // A new symbol is followed by its name, a reference to a known symbol is not.
+            Kind::Symbol if arg & 1 == 0 => (arg >> 1) as usize,
+            Kind::Bytes => arg as usize,
+            Kind::Special | Kind::Sequence | Kind::Map | Kind::Symbol => 0,
+        };
+        let Some(payload) = atoms.get(..payload_len) else {
+            bail!("Truncated pot value");
+        };
+        atoms = &atoms[payload_len..];
+
+        let relocated = if atom_kind == Kind::Bytes && contains_path(payload, from) {
+            let is_path = match kind {
+                ValueKind::TaskType => true,
+                ValueKind::Data => field.is_some_and(|field| PATH_FIELDS.contains(&field)),
+            };
+            let relocated = is_path.then(|| relocate_path(payload, from, to)).flatten();
+            if relocated.is_none() {
+                bail!(
+                    "A value references the project root {from} outside of a path: {}",
+                    String::from_utf8_lossy(payload)
+                );
+            }
+            relocated
+        } else {
+            None
+        };
+        field = match atom_kind {
+            Kind::Symbol if arg & 1 == 0 => {
+                symbols.push(payload);
+                Some(payload)
+            }
+            Kind::Symbol => Some(
+                *symbols
+                    .get((arg >> 1) as usize)
+                    .context("Unknown symbol in pot value")?,
+            ),
+            _ => None,
+        };
+
+        if let Some(relocated) = relocated {
+            write_atom_header(&mut output, Kind::Bytes, relocated.len() as u64)?;
+            output.extend_from_slice(&relocated);
+            changed = true;
+        } else {
+            output.extend_from_slice(atom_header);
+            output.extend_from_slice(payload);
+        }
+    }
+    Ok(if changed {
+        Cow::Owned(output)
+    } else {
+        Cow::Borrowed(bytes)
+    })
+}
+
+/// Returns the rewritten path when `bytes` is the path `from` or a path below it.
+fn relocate_path(bytes: &[u8], from: &str, to: &str) -> Option<Vec<u8>> {
+    let rest = bytes.strip_prefix(from.as_bytes())?;
+    if !is_path_end(rest) {
+        return None;
+    }
+    Some([to.as_bytes(), rest].concat())
+}
+
+/// Returns true when `bytes` mentions the path `from` or a path below it anywhere, e.g. in a source
+/// map or an error message.
+fn contains_path(bytes: &[u8], from: &str) -> bool {
+    let from = from.as_bytes();
+    bytes
+        .windows(from.len())
+        .enumerate()
+        .any(|(i, window)| window == from && is_path_end(&bytes[i + from.len()..]))
+}
+
+/// Returns true when `rest`, the remainder after a path prefix, ends the prefix at a path
+/// component boundary.
+fn is_path_end(rest: &[u8]) -> bool {
+    rest.is_empty() || rest.starts_with(b"/") || rest.starts_with(b"\\")
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+    use turbo_persistence::TurboPersistence;
+
+    use super::*;
+
+    fn project_root() -> &'static Path {
+        Path::new(if cfg!(windows) {
+            "C:\\projects\\app"
+        } else {
+            "/projects/app"
+        })
+    }
+
+    #[test]
+    fn test_export_import() {
+        let export_dir = TempDir::new().unwrap();
+        let import_dir = TempDir::new().unwrap();
+        let version_info = GitVersionInfo {
+            describe: "mock-version",
+            dirty: false,
+        };
+
+        let db_path = export_dir.path().join("mock-version");
+        fs::create_dir(&db_path).unwrap();
+        fs::write(db_path.join("CURRENT"), [0, 0, 0, 2]).unwrap();
+        fs::write(db_path.join("00000001.sst"), vec![42; 10_000]).unwrap();
+        fs::write(db_path.join("00000002.meta"), b"meta").unwrap();
+
+        let archive_path = export_dir.path().join("cache.archive");
+        let manifest = export_cache(
+            export_dir.path(),
+            &version_info,
+            project_root(),
+            &archive_path,
+        )
+        .unwrap();
+        assert_eq!(manifest.files.len(), 3);
+        assert_eq!(
+            read_cache_archive_manifest(&archive_path)
+                .unwrap()
+                .files
+                .len(),
+            3
+        );
+
+        // An existing database is replaced
+        let imported_path = import_dir.path().join("mock-version");
+        fs::create_dir(&imported_path).unwrap();
+        fs::write(imported_path.join("00000003.sst"), b"stale").unwrap();
+
+        import_cache(
+            import_dir.path(),
+            &version_info,
+            project_root(),
+            &archive_path,
+        )
+        .unwrap();
+        for name in ["CURRENT", "00000001.sst", "00000002.meta"] {
+            assert_eq!(
+                fs::read(imported_path.join(name)).unwrap(),
+                fs::read(db_path.join(name)).unwrap()
+            );
+        }
+        assert!(!imported_path.join("00000003.sst").exists());
+        assert_eq!(fs::read_dir(import_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_import_incompatible_version() {
+        let export_dir = TempDir::new().unwrap();
+        let import_dir = TempDir::new().unwrap();
+        let version_info = GitVersionInfo {
+            describe: "mock-version",
+            dirty: false,
+        };
+        fs::create_dir(export_dir.path().join("mock-version")).unwrap();
+        let archive_path = export_dir.path().join("cache.archive");
+        export_cache(
+            export_dir.path(),
+            &version_info,
+            project_root(),
+            &archive_path,
+        )
+        .unwrap();
+
+        let other_version_info = GitVersionInfo {
+            describe: "other-version",
+            dirty: false,
+        };
+        assert!(
+            import_cache(
+                import_dir.path(),
+                &other_version_info,
+                project_root(),
+                &archive_path
+            )
+            .is_err()
+        );
+        assert_eq!(fs::read_dir(import_dir.path()).unwrap().count(), 0);
+    }
+
+    /// A stand-in for a `DiskFileSystem` cell, its root is a path field.
+    #[derive(Serialize)]
+    struct MockFileSystem {
+        name: &'static str,
+        root: String,
+    }
+
+    /// Creates a database with the given entries and exports it into an archive.
+    fn export_entries(
+        export_dir: &Path,
+        version_info: &GitVersionInfo,
+        entries: Vec<(KeySpace, Vec<u8>, Vec<u8>)>,
+    ) -> PathBuf {
+        let db = TurboPersistence::open(export_dir.join(version_info.describe)).unwrap();
+        let batch = db.write_batch::<Vec<u8>, 5>().unwrap();
+        for (key_space, key, value) in entries {
+            batch.put(key_space as u32, key, value.into()).unwrap();
+        }
+        db.commit_write_batch(batch).unwrap();
+        db.shutdown().unwrap();
+
+        let archive_path = export_dir.join("cache.archive");
+        export_cache(export_dir, version_info, project_root(), &archive_path).unwrap();
+        archive_path
+    }
+
+    #[test]
+    fn test_import_into_other_project_root() {
+        let export_dir = TempDir::new().unwrap();
+        let import_dir = TempDir::new().unwrap();
+        let version_info = GitVersionInfo {
+            describe: "mock-version",
+            dirty: false,
+        };
+        let old_root = project_root().to_str().unwrap();
+        let new_root = project_root().with_file_name("other-app");
+        let new_root = new_root.to_str().unwrap();
+        let sep = std::path::MAIN_SEPARATOR;
+
+        let task_type = |root: &str| {
+            pot::to_vec(&("DiskFileSystem::new", "project", root.to_string())).unwrap()
+        };
+        let task_data = |root: &str| {
+            pot::to_vec(&(
+                42u32,
+                MockFileSystem {
+                    name: "project",
+                    root: root.to_string(),
+                },
+                // Only paths below the root are rewritten
+                format!("{old_root}-other{sep}file.js"),
+                "[project]/src/index.js",
+            ))
+            .unwrap()
+        };
+        let task_id = 1u32.to_le_bytes().to_vec();
+        let archive_path = export_entries(
+            export_dir.path(),
+            &version_info,
+            vec![
+                (
+                    KeySpace::ForwardTaskCache,
+                    task_type(old_root),
+                    task_id.clone(),
+                ),
+                (
+                    KeySpace::ReverseTaskCache,
+                    task_id.clone(),
+                    task_type(old_root),
+                ),
+                (KeySpace::TaskData, task_id.clone(), task_data(old_root)),
+                (
+                    KeySpace::Infra,
+                    0u32.to_le_bytes().to_vec(),
+                    task_id.clone(),
+                ),
+            ],
+        );
+        let manifest = import_cache(
+            import_dir.path(),
+            &version_info,
+            Path::new(new_root),
+            &archive_path,
+        )
+        .unwrap();
+        assert_eq!(manifest.project_root, old_root);
+
+        let db = TurboPersistence::open(import_dir.path().join("mock-version")).unwrap();
+        assert!(
+            db.get(KeySpace::ForwardTaskCache as usize, &task_type(old_root))
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(
+            db.get(KeySpace::ForwardTaskCache as usize, &task_type(new_root))
+                .unwrap()
+                .as_deref(),
+            Some(&task_id[..])
+        );
+        assert_eq!(
+            db.get(KeySpace::ReverseTaskCache as usize, &task_id)
+                .unwrap()
+                .as_deref(),
+            Some(&task_type(new_root)[..])
+        );
+        assert_eq!(
+            db.get(KeySpace::TaskData as usize, &task_id)
+                .unwrap()
+                .as_deref(),
+            Some(&task_data(new_root)[..])
+        );
+        assert_eq!(
+            db.get(KeySpace::Infra as usize, &0u32.to_le_bytes().to_vec())
+                .unwrap()
+                .as_deref(),
+            Some(&task_id[..])
+        );
+        db.shutdown().unwrap();
+        assert_eq!(fs::read_dir(import_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_import_into_other_project_root_with_unknown_paths() {
+        let export_dir = TempDir::new().unwrap();
+        let import_dir = TempDir::new().unwrap();
+        let version_info = GitVersionInfo {
+            describe: "mock-version",
+            dirty: false,
+        };
+        let old_root = project_root().to_str().unwrap();
+        let new_root = project_root().with_file_name("other-app");
+        let sep = std::path::MAIN_SEPARATOR;
+
+        // File contents that reference the root must not be rewritten
+        let file_content = format!("import '{old_root}{sep}lib.js'");
+        let archive_path = export_entries(
+            export_dir.path(),
+            &version_info,
+            vec![(
+                KeySpace::TaskData,
+                1u32.to_le_bytes().to_vec(),
+                pot::to_vec(&(42u32, file_content)).unwrap(),
+            )],
+        );
+        assert!(import_cache(import_dir.path(), &version_info, &new_root, &archive_path).is_err());
+        assert!(!import_dir.path().join("mock-version").exists());
+    }
+}
@@ -28,7 +28,7 @@ const DEFAULT_MAX_OTHER_DB_VERSIONS: usize = 2;
 
 /// Directories are prefixed with this before being deleted, so that if we fail to fully delete the
 /// directory, we can pick up where we left off last time.
-const DELETION_PREFIX: &str = "__stale_";
+pub(crate) const DELETION_PREFIX: &str = "__stale_";
 
 /// Given a base path, creates a version directory for the given `version_info`. Automatically
 /// cleans up old/stale databases.
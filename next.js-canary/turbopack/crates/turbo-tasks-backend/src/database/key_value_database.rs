@@ -1,10 +1,10 @@
-use anyhow::Result;
+use anyhow::{Result, bail};
 
 use crate::database::write_batch::{
     ConcurrentWriteBatch, SerialWriteBatch, UnimplementedWriteBatch, WriteBatch,
 };
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum KeySpace {
     Infra = 0,
     TaskMeta = 1,
@@ -35,6 +35,16 @@ pub trait KeyValueDatabase {
         key: &[u8],
     ) -> Result<Option<Self::ValueBuffer<'l>>>;
 
+    /// Calls `f` with every entry of `key_space`. This is used to copy a whole database, e.g. when
+    /// a cache archive is imported.
+    fn for_each_entry(
+        &self,
+        _key_space: KeySpace,
+        _f: &mut dyn FnMut(&[u8], &[u8]) -> Result<()>,
+    ) -> Result<()> {
+        bail!("Iterating over all entries is not supported by this database")
+    }
+
     type SerialWriteBatch<'l>: SerialWriteBatch<'l>
         = UnimplementedWriteBatch
     where
@@ -1,5 +1,6 @@
 #[cfg(feature = "lmdb")]
 mod by_key_space;
+pub mod db_archive;
 pub mod db_invalidation;
 pub mod db_versioning;
 #[cfg(feature = "lmdb")]
@@ -10,6 +11,7 @@ pub mod lmdb;
 pub mod noop_kv;
 #[cfg(feature = "lmdb")]
 pub mod read_transaction_cache;
+pub mod remote_cache;
 #[cfg(feature = "lmdb")]
 pub mod startup_cache;
 pub mod turbo;
@@ -0,0 +1,1298 @@
+mod server;
+mod store;
+
+use std::{
+    borrow::Borrow,
+    collections::VecDeque,
+    mem::{size_of, take},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{Receiver, Sender, SyncSender, channel, sync_channel},
+    },
+    thread::JoinHandle,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use byteorder::{BE, ReadBytesExt, WriteBytesExt};
+use parking_lot::{Condvar, Mutex};
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use turbo_tasks_hash::hash_xxh3_hash128;
+
+pub use self::{
+    server::RemoteCacheServer,
+    store::{FileSystemRemoteStore, HttpRemoteStore, RemoteStore},
+};
+use crate::database::{
+    by_key_space::ByKeySpace,
+    key_value_database::{KeySpace, KeyValueDatabase},
+    write_batch::{
+        BaseWriteBatch, ConcurrentWriteBatch, SerialWriteBatch, WriteBatch, WriteBuffer,
+    },
+};
+
+/// The number of threads uploading entries to the remote store.
+const UPLOAD_THREADS: usize = 4;
+
+/// The maximum number of entries that wait for each upload thread. Commits wait when the remote
+/// store can't keep up.
+const UPLOAD_QUEUE_SIZE: usize = 1024;
+
+/// The number of threads fetching entries from the remote store on local misses.
+const FETCH_THREADS: usize = 4;
+
+/// The maximum number of entries that are fetched from the remote store in a single request.
+const MAX_FETCH_BATCH: usize = 256;
+
+/// The number of keys in a chunk of the index of a namespace.
+const INDEX_CHUNK_SIZE: usize = 16 * 1024;
+
+/// The maximum number of decoded index chunks that are kept in memory to look up local misses.
+const MAX_CACHED_INDEX_CHUNKS: usize = 8;
+
+/// Seeding is aborted when the index of the keys written in the session grows larger than this.
+const MAX_SEED_INDEX_BYTES: usize = 512 * 1024 * 1024;
+
+/// The backfill pauses until the next write batch when this many bytes of remote entries are
+/// waiting to be written to the local database.
+const MAX_PENDING_BYTES: usize = 64 * 1024 * 1024;
+
+/// The key of the read-through progress in the [`KeySpace::Infra`] key space of the local
+/// database. It's longer than the integer keys of the backing storage, so it can't collide.
+const LOCAL_STATE_KEY: &[u8] = b"remote_cache_state";
+
+/// How the remote store is used by a [`RemoteCacheLayer`]. This is decided once when the database
+/// is opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteCacheMode {
+    /// The local database is a partial copy of a complete namespace in the remote store. Reads
+    /// fall through to the remote store on local misses, and the remaining entries are copied in
+    /// the background.
+    ReadThrough,
+    /// The local database was empty and nobody has written the namespace yet. All writes of this
+    /// session are uploaded and the namespace is marked as complete on shutdown.
+    Seed,
+    /// The remote store is not used. Either the local database has its own complete state, or the
+    /// namespace is currently written by another session.
+    Disabled,
+}
+
+/// The SHA-256 hash of a value in the remote store.
+type ContentHash = [u8; 32];
+
+fn content_hash(value: &[u8]) -> ContentHash {
+    Sha256::digest(value).into()
+}
+
+/// Values are stored under their content hash, so equal values are only stored once and every
+/// downloaded value can be verified.
+fn content_key(hash: &ContentHash) -> String {
+    hash.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Reads values by their content hash. A value that doesn't match its hash, e.g. because it was
+/// truncated or modified in the remote store, is an error.
+fn get_verified(remote: &dyn RemoteStore, hashes: &[ContentHash]) -> Result<Vec<Option<Vec<u8>>>> {
+    let keys = hashes.iter().map(content_key).collect::<Vec<_>>();
+    let values = remote.get_many(&keys)?;
+    for (key, (hash, value)) in keys.iter().zip(hashes.iter().zip(&values)) {
+        if let Some(value) = value
+            && content_hash(value) != *hash
+        {
+            bail!("The remote cache entry {key} doesn't match its content hash");
+        }
+    }
+    Ok(values)
+}
+
+/// The key that marks a namespace as completely uploaded. Its value is a [`CompleteMarker`].
+fn complete_marker_key(namespace: &str) -> String {
+    format!("{:032x}", hash_xxh3_hash128((namespace, "complete")))
+}
+
+/// The key that is claimed by the session that seeds a namespace. Its value is the generation.
+fn claim_key(namespace: &str) -> String {
+    format!("{:032x}", hash_xxh3_hash128((namespace, "claim")))
+}
+
+/// Every seeding session writes a new generation of a namespace. Task ids are only valid within
+/// a generation, so every generation has its own index.
+fn new_generation(namespace: &str) -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_nanos());
+    format!("{namespace}@{nanos:x}-{:x}", std::process::id())
+}
+
+/// A chunk of the index of a generation. The index maps the keys of the local database to the
+/// hashes of their values. It's sorted by key, so the chunk that contains a key can be found by the
+/// first keys of the chunks.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct IndexChunkRef {
+    hash: ContentHash,
+    first_key_space: u8,
+    first_key: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CompleteMarker {
+    generation: String,
+    index: Vec<IndexChunkRef>,
+}
+
+/// The progress of a read-through database. It's stored in the local database, so the next
+/// session continues where this one stopped.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct LocalState {
+    generation: String,
+    index: Vec<IndexChunkRef>,
+    /// The number of index chunks whose entries have been copied to the local database.
+    backfilled_chunks: u32,
+}
+
+impl LocalState {
+    fn is_complete(&self) -> bool {
+        self.backfilled_chunks as usize >= self.index.len()
+    }
+}
+
+type IndexEntry = (KeySpace, Vec<u8>, ContentHash);
+
+fn encode_index_chunk(entries: &[(KeySpace, &[u8], &ContentHash)]) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    for (key_space, key, hash) in entries {
+        bytes.write_u8(*key_space as u8)?;
+        bytes.write_u32::<BE>(key.len().try_into()?)?;
+        bytes.extend_from_slice(key);
+        bytes.extend_from_slice(*hash);
+    }
+    Ok(bytes)
+}
+
+fn decode_index_chunk(mut bytes: &[u8]) -> Result<Vec<IndexEntry>> {
+    let mut keys = Vec::new();
+    while !bytes.is_empty() {
+        let key_space = match bytes.read_u8()? {
+            0 => KeySpace::Infra,
+            1 => KeySpace::TaskMeta,
+            2 => KeySpace::TaskData,
+            3 => KeySpace::ForwardTaskCache,
+            4 => KeySpace::ReverseTaskCache,
+            key_space => bail!("Unknown key space {key_space} in remote cache index"),
+        };
+        let len = bytes.read_u32::<BE>()? as usize;
+        let Some(key) = bytes.get(..len) else {
+            bail!("Truncated remote cache index");
+        };
+        let Some(hash) = bytes.get(len..len + size_of::<ContentHash>()) else {
+            bail!("Truncated remote cache index");
+        };
+        keys.push((key_space, key.to_vec(), hash.try_into()?));
+        bytes = &bytes[len + hash.len()..];
+    }
+    Ok(keys)
+}
+
+struct Upload {
+    hash: ContentHash,
+    value: Vec<u8>,
+}
+
+/// Background threads that upload entries to the remote store. Entries are sharded by their hash,
+/// so equal values are uploaded by the same thread.
+struct Uploader {
+    senders: Vec<SyncSender<Upload>>,
+    join_handles: Vec<JoinHandle<()>>,
+    failed: Arc<AtomicBool>,
+}
+
+impl Uploader {
+    fn new(remote: &Arc<dyn RemoteStore>) -> Result<Self> {
+        let failed = Arc::new(AtomicBool::new(false));
+        let mut senders = Vec::with_capacity(UPLOAD_THREADS);
+        let mut join_handles = Vec::with_capacity(UPLOAD_THREADS);
+        for i in 0..UPLOAD_THREADS {
+            let (sender, receiver) = sync_channel::<Upload>(UPLOAD_QUEUE_SIZE);
+            let remote = remote.clone();
+            let failed = failed.clone();
+            let join_handle = std::thread::Builder::new()
+                .name(format!("remote cache upload {i}"))
+                .spawn(move || {
+                    for Upload { hash, value } in receiver {
+                        if failed.load(Ordering::Relaxed) {
+                            // Drain the queue, the namespace won't be completed anyway
+                            continue;
+                        }
+                        if let Err(err) = remote.put(&content_key(&hash), &value) {
+                            println!("WARNING: Uploading to the remote cache failed: {err:?}");
+                            failed.store(true, Ordering::Relaxed);
+                        }
+                    }
+                })
+                .context("Failed to spawn remote cache upload thread")?;
+            senders.push(sender);
+            join_handles.push(join_handle);
+        }
+        Ok(Self {
+            senders,
+            join_handles,
+            failed,
+        })
+    }
+
+    fn upload(&self, uploads: Vec<Upload>) {
+        for upload in uploads {
+            let shard = usize::from(upload.hash[0]) % self.senders.len();
+            // Blocks while the queue is full. A closed channel means the thread has panicked, the
+            // namespace won't be completed.
+            let _ = self.senders[shard].send(upload);
+        }
+    }
+
+    /// Waits for all pending uploads. Returns true if all of them succeeded.
+    fn finish(self) -> bool {
+        drop(self.senders);
+        let mut success = true;
+        for join_handle in self.join_handles {
+            success &= join_handle.join().is_ok();
+        }
+        success && !self.failed.load(Ordering::Relaxed)
+    }
+}
+
+/// The state of a seeding session.
+struct Seed {
+    uploader: Mutex<Option<Uploader>>,
+    index: Mutex<SeedIndex>,
+}
+
+/// All keys written in a seeding session and the hashes of their values. They are uploaded as
+/// index of the generation on shutdown, so read-through sessions can find and copy the entries.
+struct SeedIndex {
+    keys: ByKeySpace<FxHashMap<Vec<u8>, ContentHash>>,
+    /// The approximate memory used by the keys.
+    bytes: usize,
+    /// The index has grown larger than [`MAX_SEED_INDEX_BYTES`] and seeding has been aborted.
+    overflowed: bool,
+}
+
+impl Seed {
+    /// Records a put with the hash of its value, or a delete. Returns false when seeding has been
+    /// aborted and the value doesn't need to be uploaded.
+    fn record_write(&self, key_space: KeySpace, key: &[u8], hash: Option<ContentHash>) -> bool {
+        let mut index = self.index.lock();
+        let SeedIndex {
+            keys,
+            bytes,
+            overflowed,
+        } = &mut *index;
+        if *overflowed {
+            return false;
+        }
+        let keys = keys.get_mut(key_space);
+        let entry_bytes = key.len() + size_of::<ContentHash>();
+        match hash {
+            Some(hash) => {
+                if keys.insert(key.to_vec(), hash).is_none() {
+                    *bytes += entry_bytes;
+                }
+            }
+            None => {
+                if keys.remove(key).is_some() {
+                    *bytes -= entry_bytes;
+                }
+            }
+        }
+        if *bytes <= MAX_SEED_INDEX_BYTES {
+            return true;
+        }
+        println!("WARNING: The cache is too large to be uploaded to the remote cache");
+        *overflowed = true;
+        index.keys = ByKeySpace::new(|_| FxHashMap::default());
+        drop(index);
+        if let Some(uploader) = &*self.uploader.lock() {
+            uploader.failed.store(true, Ordering::Relaxed);
+        }
+        false
+    }
+}
+
+/// Identifies a key of the local database in the set of written keys.
+fn local_key_hash(key_space: KeySpace, key: &[u8]) -> u128 {
+    hash_xxh3_hash128((key_space as u8, key))
+}
+
+type PendingEntries = FxHashMap<(KeySpace, Vec<u8>), Vec<u8>>;
+
+struct ReadThroughState {
+    /// Remote entries that are written to the local database with the next write batch.
+    pending: PendingEntries,
+    pending_bytes: usize,
+    /// Hashes of the keys written locally in this session. Remote entries never override them.
+    written: FxHashSet<u128>,
+    local_state: LocalState,
+    /// The progress has changed since it was last written to the local database.
+    local_state_changed: bool,
+}
+
+impl ReadThroughState {
+    fn add_pending(&mut self, key_space: KeySpace, key: Vec<u8>, value: Vec<u8>) {
+        if self.written.contains(&local_key_hash(key_space, &key)) {
+            return;
+        }
+        let key_len = key.len();
+        self.pending_bytes += key_len + value.len();
+        if let Some(old) = self.pending.insert((key_space, key), value) {
+            self.pending_bytes -= key_len + old.len();
+        }
+    }
+
+    fn is_known(&self, key_space: KeySpace, key: &[u8]) -> bool {
+        self.written.contains(&local_key_hash(key_space, key))
+            || self.pending.contains_key(&(key_space, key.to_vec()))
+    }
+}
+
+/// A decoded index chunk.
+type IndexChunk = FxHashMap<(KeySpace, Vec<u8>), ContentHash>;
+
+/// The index chunks that have been loaded to look up local misses. Only the most recently loaded
+/// chunks are kept.
+#[derive(Default)]
+struct IndexCache {
+    chunks: FxHashMap<usize, Arc<IndexChunk>>,
+    order: VecDeque<usize>,
+}
+
+/// The part of a read-through session that is shared with the fetch and backfill threads.
+struct ReadThroughShared {
+    remote: Arc<dyn RemoteStore>,
+    index: Vec<IndexChunkRef>,
+    index_cache: Mutex<IndexCache>,
+    state: Mutex<ReadThroughState>,
+    /// Notified when the pending entries have been written to the local database.
+    flushed: Condvar,
+    stopped: AtomicBool,
+}
+
+impl ReadThroughShared {
+    fn load_index_chunk(&self, chunk: usize) -> Result<Vec<IndexEntry>> {
+        let index = get_verified(&*self.remote, &[self.index[chunk].hash])?
+            .pop()
+            .flatten()
+            .with_context(|| format!("Index chunk {chunk} is missing in the remote cache"))?;
+        decode_index_chunk(&index)
+    }
+
+    /// Looks up the hash of the remote value of a key in the index.
+    fn lookup(&self, key_space: KeySpace, key: &[u8]) -> Result<Option<ContentHash>> {
+        let chunk = self.index.partition_point(|chunk| {
+            (chunk.first_key_space, &chunk.first_key[..]) <= (key_space as u8, key)
+        });
+        // Keys before the first key of the first chunk are not in the index
+        let Some(chunk) = chunk.checked_sub(1) else {
+            return Ok(None);
+        };
+        let cached = self.index_cache.lock().chunks.get(&chunk).cloned();
+        let entries = match cached {
+            Some(entries) => entries,
+            None => {
+                let entries = Arc::new(
+                    self.load_index_chunk(chunk)?
+                        .into_iter()
+                        .map(|(key_space, key, hash)| ((key_space, key), hash))
+                        .collect::<IndexChunk>(),
+                );
+                let mut cache = self.index_cache.lock();
+                if cache.chunks.insert(chunk, entries.clone()).is_none() {
+                    cache.order.push_back(chunk);
+                    if cache.order.len() > MAX_CACHED_INDEX_CHUNKS
+                        && let Some(evicted) = cache.order.pop_front()
+                    {
+                        cache.chunks.remove(&evicted);
+                    }
+                }
+                entries
+            }
+        };
+        Ok(entries.get(&(key_space, key.to_vec())).copied())
+    }
+
+    /// Copies all entries of the index chunks that haven't been copied yet into the pending
+    /// entries.
+    fn backfill(&self, start_chunk: u32) -> Result<()> {
+        for chunk in start_chunk as usize..self.index.len() {
+            let entries = self.load_index_chunk(chunk)?;
+            for entries in entries.chunks(MAX_FETCH_BATCH) {
+                if self.stopped.load(Ordering::Relaxed) {
+                    return Ok(());
+                }
+                let entries = {
+                    let state = self.state.lock();
+                    entries
+                        .iter()
+                        .filter(|(key_space, key, _)| !state.is_known(*key_space, key))
+                        .collect::<Vec<_>>()
+                };
+                let hashes = entries.iter().map(|(_, _, hash)| *hash).collect::<Vec<_>>();
+                let values = get_verified(&*self.remote, &hashes)?;
+                let mut state = self.state.lock();
+                for ((key_space, key, _), value) in entries.into_iter().zip(values) {
+                    if let Some(value) = value {
+                        state.add_pending(*key_space, key.clone(), value);
+                    }
+                }
+                while state.pending_bytes > MAX_PENDING_BYTES
+                    && !self.stopped.load(Ordering::Relaxed)
+                {
+                    self.flushed.wait(&mut state);
+                }
+            }
+            let mut state = self.state.lock();
+            state.local_state.backfilled_chunks = (chunk + 1).try_into()?;
+            state.local_state_changed = true;
+        }
+        Ok(())
+    }
+}
+
+struct Fetch {
+    key_space: KeySpace,
+    key: Vec<u8>,
+    /// Where the result is sent to. Prefetched entries are only added to the pending entries.
+    reply: Option<SyncSender<Result<Option<Vec<u8>>>>>,
+}
+
+/// Threads that fetch the remote entries for local misses. Concurrent misses are combined into a
+/// single request to the remote store.
+fn fetch_thread(shared: Arc<ReadThroughShared>, receiver: Arc<Mutex<Receiver<Fetch>>>) {
+    loop {
+        let fetches = {
+            let receiver = receiver.lock();
+            let Ok(first) = receiver.recv() else {
+                return;
+            };
+            let mut fetches = vec![first];
+            while fetches.len() < MAX_FETCH_BATCH
+                && let Ok(fetch) = receiver.try_recv()
+            {
+                fetches.push(fetch);
+            }
+            fetches
+        };
+        let values = fetches
+            .iter()
+            .map(|fetch| shared.lookup(fetch.key_space, &fetch.key))
+            .collect::<Result<Vec<_>>>()
+            .and_then(|hashes| {
+                let mut unique_hashes = hashes.iter().flatten().copied().collect::<Vec<_>>();
+                unique_hashes.sort_unstable();
+                unique_hashes.dedup();
+                let values = get_verified(&*shared.remote, &unique_hashes)?;
+                let values = unique_hashes
+                    .into_iter()
+                    .zip(values)
+                    .collect::<FxHashMap<_, _>>();
+                Ok(hashes
+                    .into_iter()
+                    .map(|hash| hash.and_then(|hash| values.get(&hash).cloned().flatten()))
+                    .collect::<Vec<_>>())
+            });
+        match values {
+            Ok(values) => {
+                for (fetch, value) in fetches.into_iter().zip(values) {
+                    match fetch.reply {
+                        Some(reply) => {
+                            let _ = reply.send(Ok(value));
+                        }
+                        None => {
+                            if let Some(value) = value {
+                                shared
+                                    .state
+                                    .lock()
+                                    .add_pending(fetch.key_space, fetch.key, value);
+                            }
+                        }
+                    }
+                }
+            }
+            Err(err) => {
+                for fetch in fetches {
+                    if let Some(reply) = fetch.reply {
+                        let _ = reply.send(Err(anyhow!("{err:?}")));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The state of a read-through session.
+struct ReadThrough {
+    shared: Arc<ReadThroughShared>,
+    fetch_sender: Mutex<Option<Sender<Fetch>>>,
+    fetch_threads: Mutex<Vec<JoinHandle<()>>>,
+    backfill_thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl ReadThrough {
+    fn new(remote: Arc<dyn RemoteStore>, local_state: LocalState, is_new: bool) -> Result<Self> {
+        let start_chunk = local_state.backfilled_chunks;
+        let shared = Arc::new(ReadThroughShared {
+            remote,
+            index: local_state.index.clone(),
+            index_cache: Mutex::default(),
+            state: Mutex::new(ReadThroughState {
+                pending: FxHashMap::default(),
+                pending_bytes: 0,
+                written: FxHashSet::default(),
+                local_state,
+                local_state_changed: is_new,
+            }),
+            flushed: Condvar::new(),
+            stopped: AtomicBool::new(false),
+        });
+        let (fetch_sender, receiver) = channel();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let mut fetch_threads = Vec::with_capacity(FETCH_THREADS);
+        for i in 0..FETCH_THREADS {
+            let shared = shared.clone();
+            let receiver = receiver.clone();
+            fetch_threads.push(
+                std::thread::Builder::new()
+                    .name(format!("remote cache fetch {i}"))
+                    .spawn(move || fetch_thread(shared, receiver))
+                    .context("Failed to spawn remote cache fetch thread")?,
+            );
+        }
+        let backfill_thread = std::thread::Builder::new()
+            .name("remote cache backfill".to_string())
+            .spawn({
+                let shared = shared.clone();
+                move || {
+                    if let Err(err) = shared.backfill(start_chunk) {
+                        println!("WARNING: Copying the remote cache failed: {err:?}");
+                    }
+                }
+            })
+            .context("Failed to spawn remote cache backfill thread")?;
+        Ok(Self {
+            shared,
+            fetch_sender: Mutex::new(Some(fetch_sender)),
+            fetch_threads: Mutex::new(fetch_threads),
+            backfill_thread: Mutex::new(Some(backfill_thread)),
+        })
+    }
+
+    /// Queues a fetch from the remote store. Returns false when the session has been stopped.
+    fn fetch(
+        &self,
+        key_space: KeySpace,
+        key: &[u8],
+        reply: Option<SyncSender<Result<Option<Vec<u8>>>>>,
+    ) -> bool {
+        let Some(fetch_sender) = &*self.fetch_sender.lock() else {
+            return false;
+        };
+        fetch_sender
+            .send(Fetch {
+                key_space,
+                key: key.to_vec(),
+                reply,
+            })
+            .is_ok()
+    }
+
+    fn get(&self, key_space: KeySpace, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        if let Some(value) = self
+            .shared
+            .state
+            .lock()
+            .pending
+            .get(&(key_space, key.to_vec()))
+        {
+            return Ok(Some(value.clone()));
+        }
+        let (reply, result) = sync_channel(1);
+        if !self.fetch(key_space, key, Some(reply)) {
+            // The cache is being shut down, missing entries are recomputed
+            return Ok(None);
+        }
+        let value = result
+            .recv()
+            .context("The remote cache fetch threads have stopped")?
+            .with_context(|| format!("Unable to read {key_space:?} entry from the remote cache"))?;
+        if let Some(value) = &value {
+            let mut state = self.shared.state.lock();
+            state.add_pending(key_space, key.to_vec(), value.clone());
+            // The meta and data of a task are read right after its id has been looked up. Task ids
+            // are the keys of these key spaces, so they can be fetched in the background already.
+            if matches!(key_space, KeySpace::ForwardTaskCache) {
+                let prefetch = [KeySpace::TaskMeta, KeySpace::TaskData]
+                    .into_iter()
+                    .filter(|key_space| !state.is_known(*key_space, value))
+                    .collect::<Vec<_>>();
+                drop(state);
+                for key_space in prefetch {
+                    self.fetch(key_space, value, None);
+                }
+            }
+        }
+        Ok(value)
+    }
+
+    fn record_write(&self, key_space: KeySpace, key: &[u8]) {
+        let mut state = self.shared.state.lock();
+        if let Some(old) = state.pending.remove(&(key_space, key.to_vec())) {
+            state.pending_bytes -= key.len() + old.len();
+        }
+        state.written.insert(local_key_hash(key_space, key));
+    }
+
+    /// Takes the pending entries and the progress, if it has changed, that need to be written to
+    /// the local database.
+    fn take_pending(&self) -> (PendingEntries, Option<LocalState>) {
+        let mut state = self.shared.state.lock();
+        let pending = take(&mut state.pending);
+        state.pending_bytes = 0;
+        let local_state = take(&mut state.local_state_changed).then(|| state.local_state.clone());
+        self.shared.flushed.notify_all();
+        (pending, local_state)
+    }
+
+    /// Stops fetching from the remote store. Entries that have already been fetched stay pending,
+    /// unless `discard` is set.
+    fn stop(&self, discard: bool) {
+        {
+            // Set under the lock, so the backfill can't miss the notification
+            let _state = self.shared.state.lock();
+            self.shared.stopped.store(true, Ordering::Relaxed);
+            self.shared.flushed.notify_all();
+        }
+        self.fetch_sender.lock().take();
+        let threads = take(&mut *self.fetch_threads.lock());
+        for thread in threads
+            .into_iter()
+            .chain(self.backfill_thread.lock().take())
+        {
+            let _ = thread.join();
+        }
+        if discard {
+            let mut state = self.shared.state.lock();
+            state.pending.clear();
+            state.pending_bytes = 0;
+            state.local_state_changed = false;
+        }
+    }
+
+    #[cfg(test)]
+    fn wait_for_backfill(&self) {
+        if let Some(thread) = self.backfill_thread.lock().take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+struct RemoteCache {
+    remote: Arc<dyn RemoteStore>,
+    namespace: String,
+    generation: String,
+    mode: RemoteCacheMode,
+    seed: Option<Seed>,
+    read_through: Option<ReadThrough>,
+}
+
+/// A [`KeyValueDatabase`] layer that shares the cache of a local database with other machines
+/// through a content-addressed [`RemoteStore`].
+///
+/// Task ids are allocated per database, so local and remote entries can't be mixed freely. The
+/// remote store is divided into namespaces, e.g. one per commit of a repository, and every
+/// namespace is written by a single session:
+///
+/// - The first session with an empty local database claims the namespace, uploads all its writes in
+///   the background and marks the namespace as complete on shutdown.
+/// - Later sessions with an empty local database read from the local database first and fall
+///   through to the remote store on misses. Remote entries are written to the local database, and
+///   the remaining entries of the namespace are copied in the background. Their own writes stay
+///   local and shadow the remote entries. The progress is stored in the local database, so the
+///   following sessions keep reading through until the local database is complete.
+/// - Sessions with a complete local database ignore the remote store.
+///
+/// Values are stored under the hash of their content and verified when they are read. The index of
+/// a namespace maps the keys of the local database to these hashes, and the namespace is marked as
+/// complete with a reference to its index. Deletes are not propagated to the remote store.
+pub struct RemoteCacheLayer<T: KeyValueDatabase> {
+    database: T,
+    remote_cache: RemoteCache,
+}
+
+impl<T: KeyValueDatabase> RemoteCacheLayer<T> {
+    pub fn new(database: T, remote: Arc<dyn RemoteStore>, namespace: String) -> Result<Self> {
+        let local_state = {
+            let tx = database.begin_read_transaction()?;
+            database
+                .get(&tx, KeySpace::Infra, LOCAL_STATE_KEY)?
+                .map(|value| serde_json::from_slice::<LocalState>(value.borrow()))
+                .transpose()
+                .context("Unable to read the remote cache state from the local database")?
+        };
+        let (mode, generation, local_state) = match local_state {
+            Some(local_state) if !local_state.is_complete() => (
+                RemoteCacheMode::ReadThrough,
+                local_state.generation.clone(),
+                Some((local_state, false)),
+            ),
+            _ if !database.is_empty() => (RemoteCacheMode::Disabled, String::new(), None),
+            _ => {
+                let select_mode = || -> Result<_> {
+                    if let Some(marker) = remote.get(&complete_marker_key(&namespace))? {
+                        let marker = serde_json::from_slice::<CompleteMarker>(&marker)?;
+                        let local_state = LocalState {
+                            generation: marker.generation.clone(),
+                            index: marker.index,
+                            backfilled_chunks: 0,
+                        };
+                        return Ok((
+                            RemoteCacheMode::ReadThrough,
+                            marker.generation,
+                            Some((local_state, true)),
+                        ));
+                    }
+                    let generation = new_generation(&namespace);
+                    if remote.put_if_absent(&claim_key(&namespace), generation.as_bytes())? {
+                        Ok((RemoteCacheMode::Seed, generation, None))
+                    } else {
+                        Ok((RemoteCacheMode::Disabled, String::new(), None))
+                    }
+                };
+                // The remote cache is an optimization, so an unreachable remote store is not fatal
+                select_mode().unwrap_or_else(|err| {
+                    println!("WARNING: The remote cache is not available: {err:?}");
+                    (RemoteCacheMode::Disabled, String::new(), None)
+                })
+            }
+        };
+        let seed = if mode == RemoteCacheMode::Seed {
+            Some(Seed {
+                uploader: Mutex::new(Some(Uploader::new(&remote)?)),
+                index: Mutex::new(SeedIndex {
+                    keys: ByKeySpace::new(|_| FxHashMap::default()),
+                    bytes: 0,
+                    overflowed: false,
+                }),
+            })
+        } else {
+            None
+        };
+        let read_through = local_state
+            .map(|(local_state, is_new)| ReadThrough::new(remote.clone(), local_state, is_new))
+            .transpose()?;
+        let this = Self {
+            database,
+            remote_cache: RemoteCache {
+                remote,
+                namespace,
+                generation,
+                mode,
+                seed,
+                read_through,
+            },
+        };
+        // Remember the generation right away, the local database refers to its task ids from now
+        this.flush_read_through()?;
+        Ok(this)
+    }
+
+    pub fn mode(&self) -> RemoteCacheMode {
+        self.remote_cache.mode
+    }
+
+    #[cfg(test)]
+    fn wait_for_backfill(&self) {
+        if let Some(read_through) = &self.remote_cache.read_through {
+            read_through.wait_for_backfill();
+        }
+    }
+
+    /// Writes the remote entries that have been read or copied in the background and the progress
+    /// to the local database. Entries that exist locally are not overridden.
+    fn flush_read_through(&self) -> Result<()> {
+        let Some(read_through) = &self.remote_cache.read_through else {
+            return Ok(());
+        };
+        let (pending, local_state) = read_through.take_pending();
+        if pending.is_empty() && local_state.is_none() {
+            return Ok(());
+        }
+        let mut batch = self.database.write_batch()?;
+        {
+            let tx = self.database.begin_read_transaction()?;
+            for ((key_space, key), value) in pending {
+                if self.database.get(&tx, key_space, &key)?.is_none() {
+                    batch.put(key_space, WriteBuffer::Vec(key), WriteBuffer::Vec(value))?;
+                }
+            }
+        }
+        if let Some(local_state) = local_state {
+            batch.put(
+                KeySpace::Infra,
+                WriteBuffer::Borrowed(LOCAL_STATE_KEY),
+                WriteBuffer::Vec(serde_json::to_vec(&local_state)?),
+            )?;
+        }
+        batch.commit()
+    }
+}
+
+pub enum RemoteCacheValueBuffer<L: Borrow<[u8]>> {
+    Local(L),
+    Remote(Vec<u8>),
+}
+
+impl<L: Borrow<[u8]>> Borrow<[u8]> for RemoteCacheValueBuffer<L> {
+    fn borrow(&self) -> &[u8] {
+        match self {
+            RemoteCacheValueBuffer::Local(value) => value.borrow(),
+            RemoteCacheValueBuffer::Remote(value) => value,
+        }
+    }
+}
+
+impl<T: KeyValueDatabase> KeyValueDatabase for RemoteCacheLayer<T> {
+    type ReadTransaction<'l>
+        = T::ReadTransaction<'l>
+    where
+        Self: 'l;
+
+    fn is_empty(&self) -> bool {
+        self.remote_cache.mode != RemoteCacheMode::ReadThrough && self.database.is_empty()
+    }
+
+    fn begin_read_transaction(&self) -> Result<Self::ReadTransaction<'_>> {
+        self.database.begin_read_transaction()
+    }
+
+    type ValueBuffer<'l>
+        = RemoteCacheValueBuffer<T::ValueBuffer<'l>>
+    where
+        Self: 'l;
+
+    fn get<'l, 'db: 'l>(
+        &'l self,
+        transaction: &'l Self::ReadTransaction<'db>,
+        key_space: KeySpace,
+        key: &[u8],
+    ) -> Result<Option<Self::ValueBuffer<'l>>> {
+        if let Some(value) = self.database.get(transaction, key_space, key)? {
+            return Ok(Some(RemoteCacheValueBuffer::Local(value)));
+        }
+        let Some(read_through) = &self.remote_cache.read_through else {
+            return Ok(None);
+        };
+        Ok(read_through
+            .get(key_space, key)?
+            .map(RemoteCacheValueBuffer::Remote))
+    }
+
+    type SerialWriteBatch<'l>
+        = RemoteCacheWriteBatch<'l, T::SerialWriteBatch<'l>>
+    where
+        Self: 'l;
+
+    type ConcurrentWriteBatch<'l>
+        = RemoteCacheWriteBatch<'l, T::ConcurrentWriteBatch<'l>>
+    where
+        Self: 'l;
+
+    fn write_batch(
+        &self,
+    ) -> Result<WriteBatch<'_, Self::SerialWriteBatch<'_>, Self::ConcurrentWriteBatch<'_>>> {
+        // Remote entries are committed first, so the writes of this batch override them
+        self.flush_read_through()?;
+        let uploads = self.remote_cache.seed.is_some().then(Mutex::default);
+        Ok(match self.database.write_batch()? {
+            WriteBatch::Serial(write_batch) => WriteBatch::serial(RemoteCacheWriteBatch {
+                write_batch,
+                remote_cache: &self.remote_cache,
+                uploads,
+            }),
+            WriteBatch::Concurrent(write_batch, _) => {
+                WriteBatch::concurrent(RemoteCacheWriteBatch {
+                    write_batch,
+                    remote_cache: &self.remote_cache,
+                    uploads,
+                })
+            }
+        })
+    }
+
+    fn prevent_writes(&self) {
+        // Don't complete the namespace with an invalidated cache
+        if let Some(seed) = &self.remote_cache.seed
+            && let Some(uploader) = seed.uploader.lock().take()
+        {
+            uploader.failed.store(true, Ordering::Relaxed);
+            uploader.finish();
+        }
+        if let Some(read_through) = &self.remote_cache.read_through {
+            read_through.stop(true);
+        }
+        self.database.prevent_writes()
+    }
+
+    fn shutdown(&self) -> Result<()> {
+        if let Some(read_through) = &self.remote_cache.read_through {
+            read_through.stop(false);
+            self.flush_read_through()?;
+        }
+        self.database.shutdown()?;
+        if let Some(seed) = &self.remote_cache.seed
+            && let Some(uploader) = seed.uploader.lock().take()
+        {
+            let _span = tracing::info_span!("wait for remote cache uploads").entered();
+            let RemoteCache {
+                remote,
+                namespace,
+                generation,
+                ..
+            } = &self.remote_cache;
+            let index = seed.index.lock();
+            let mut entries = index
+                .keys
+                .iter()
+                .flat_map(|(key_space, keys)| {
+                    keys.iter()
+                        .map(move |(key, hash)| (key_space, &key[..], hash))
+                })
+                .collect::<Vec<_>>();
+            entries.sort_unstable_by_key(|(key_space, key, _)| (*key_space as u8, *key));
+            let mut index_chunks = Vec::new();
+            let mut uploads = Vec::new();
+            for entries in entries.chunks(INDEX_CHUNK_SIZE) {
+                let value = encode_index_chunk(entries)?;
+                let hash = content_hash(&value);
+                let (first_key_space, first_key, _) = entries[0];
+                index_chunks.push(IndexChunkRef {
+                    hash,
+                    first_key_space: first_key_space as u8,
+                    first_key: first_key.to_vec(),
+                });
+                uploads.push(Upload { hash, value });
+            }
+            drop(index);
+            let marker = CompleteMarker {
+                generation: generation.clone(),
+                index: index_chunks,
+            };
+            uploader.upload(uploads);
+            if !uploader.finish() {
+                bail!("Uploading to the remote cache failed, the namespace is not completed");
+            }
+            remote
+                .put(
+                    &complete_marker_key(namespace),
+                    &serde_json::to_vec(&marker)?,
+                )
+                .context("Unable to mark the remote cache namespace as complete")?;
+        }
+        Ok(())
+    }
+}
+
+pub struct RemoteCacheWriteBatch<'a, B> {
+    write_batch: B,
+    remote_cache: &'a RemoteCache,
+    /// Entries that are uploaded after the batch is committed. Only set when seeding.
+    uploads: Option<Mutex<Vec<Upload>>>,
+}
+
+impl<B> RemoteCacheWriteBatch<'_, B> {
+    fn record_put(&self, key_space: KeySpace, key: &[u8], value: &[u8]) {
+        if let Some(seed) = &self.remote_cache.seed {
+            let hash = content_hash(value);
+            if seed.record_write(key_space, key, Some(hash))
+                && let Some(uploads) = &self.uploads
+            {
+                uploads.lock().push(Upload {
+                    hash,
+                    value: value.to_vec(),
+                });
+            }
+        }
+        self.record_local_write(key_space, key);
+    }
+
+    fn record_delete(&self, key_space: KeySpace, key: &[u8]) {
+        if let Some(seed) = &self.remote_cache.seed {
+            seed.record_write(key_space, key, None);
+        }
+        self.record_local_write(key_space, key);
+    }
+
+    fn record_local_write(&self, key_space: KeySpace, key: &[u8]) {
+        if let Some(read_through) = &self.remote_cache.read_through {
+            read_through.record_write(key_space, key);
+        }
+    }
+}
+
+impl<'a, B: BaseWriteBatch<'a>> BaseWriteBatch<'a> for RemoteCacheWriteBatch<'a, B> {
+    type ValueBuffer<'l>
+        = RemoteCacheValueBuffer<B::ValueBuffer<'l>>
+    where
+        Self: 'l,
+        'a: 'l;
+
+    fn get<'l>(&'l self, key_space: KeySpace, key: &[u8]) -> Result<Option<Self::ValueBuffer<'l>>>
+    where
+        'a: 'l,
+    {
+        if let Some(value) = self.write_batch.get(key_space, key)? {
+            return Ok(Some(RemoteCacheValueBuffer::Local(value)));
+        }
+        let Some(read_through) = &self.remote_cache.read_through else {
+            return Ok(None);
+        };
+        Ok(read_through
+            .get(key_space, key)?
+            .map(RemoteCacheValueBuffer::Remote))
+    }
+
+    fn commit(self) -> Result<()> {
+        self.write_batch.commit()?;
+        // Only upload committed data
+        if let Some(uploads) = self.uploads
+            && let Some(seed) = &self.remote_cache.seed
+            && let Some(uploader) = &*seed.uploader.lock()
+        {
+            uploader.upload(uploads.into_inner());
+        }
+        Ok(())
+    }
+}
+
+impl<'a, B: SerialWriteBatch<'a>> SerialWriteBatch<'a> for RemoteCacheWriteBatch<'a, B> {
+    fn put(
+        &mut self,
+        key_space: KeySpace,
+        key: WriteBuffer<'_>,
+        value: WriteBuffer<'_>,
+    ) -> Result<()> {
+        self.record_put(key_space, &key, &value);
+        self.write_batch.put(key_space, key, value)
+    }
+
+    fn delete(&mut self, key_space: KeySpace, key: WriteBuffer<'_>) -> Result<()> {
+        self.record_delete(key_space, &key);
+        self.write_batch.delete(key_space, key)
+    }
+
+    fn flush(&mut self, key_space: KeySpace) -> Result<()> {
+        self.write_batch.flush(key_space)
+    }
+}
+
+impl<'a, B: ConcurrentWriteBatch<'a>> ConcurrentWriteBatch<'a> for RemoteCacheWriteBatch<'a, B> {
+    fn put(&self, key_space: KeySpace, key: WriteBuffer<'_>, value: WriteBuffer<'_>) -> Result<()> {
+        self.record_put(key_space, &key, &value);
+        self.write_batch.put(key_space, key, value)
+    }
+
+    fn delete(&self, key_space: KeySpace, key: WriteBuffer<'_>) -> Result<()> {
+        self.record_delete(key_space, &key);
+        self.write_batch.delete(key_space, key)
+    }
+
+    unsafe fn flush(&self, key_space: KeySpace) -> Result<()> {
+        unsafe { self.write_batch.flush(key_space) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::database::turbo::TurboKeyValueDatabase;
+
+    fn open(
+        path: &TempDir,
+        remote: &Arc<dyn RemoteStore>,
+    ) -> Result<RemoteCacheLayer<TurboKeyValueDatabase>> {
+        let database = TurboKeyValueDatabase::new(path.path().to_path_buf(), false, true)?;
+        RemoteCacheLayer::new(database, remote.clone(), "test-namespace".to_string())
+    }
+
+    fn put(db: &RemoteCacheLayer<TurboKeyValueDatabase>, key: &[u8], value: &[u8]) -> Result<()> {
+        let WriteBatch::Concurrent(batch, _) = db.write_batch()? else {
+            panic!("expected a concurrent write batch");
+        };
+        batch.put(
+            KeySpace::TaskData,
+            WriteBuffer::Borrowed(key),
+            WriteBuffer::Borrowed(value),
+        )?;
+        batch.commit()
+    }
+
+    fn get(db: &RemoteCacheLayer<TurboKeyValueDatabase>, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let tx = db.begin_read_transaction()?;
+        Ok(db
+            .get(&tx, KeySpace::TaskData, key)?
+            .map(|value| Borrow::<[u8]>::borrow(&value).to_vec()))
+    }
+
+    #[test]
+    fn test_seed_and_read_through() -> Result<()> {
+        let remote_dir = TempDir::new()?;
+        let server = RemoteCacheServer::start(remote_dir.path().to_path_buf())?;
+        let remote: Arc<dyn RemoteStore> = Arc::new(HttpRemoteStore::new(&server.url())?);
+
+        let seed_dir = TempDir::new()?;
+        let db = open(&seed_dir, &remote)?;
+        assert_eq!(db.mode(), RemoteCacheMode::Seed);
+        put(&db, b"key1", b"value1")?;
+        put(&db, b"key2", b"value2")?;
+
+        // The namespace is claimed, but not complete until the seeding session shuts down
+        let other_dir = TempDir::new()?;
+        let other_db = open(&other_dir, &remote)?;
+        assert_eq!(other_db.mode(), RemoteCacheMode::Disabled);
+        other_db.shutdown()?;
+
+        db.shutdown()?;
+        drop(db);
+
+        let read_dir = TempDir::new()?;
+        let db = open(&read_dir, &remote)?;
+        assert_eq!(db.mode(), RemoteCacheMode::ReadThrough);
+        assert!(!db.is_empty());
+        assert_eq!(get(&db, b"key1")?.as_deref(), Some(&b"value1"[..]));
+        assert_eq!(get(&db, b"key3")?, None);
+
+        // Local writes shadow the remote entries
+        put(&db, b"key2", b"local")?;
+        assert_eq!(get(&db, b"key2")?.as_deref(), Some(&b"local"[..]));
+        db.shutdown()?;
+        drop(db);
+
+        // Remote entries that have been read are written to the local database
+        let empty_dir = TempDir::new()?;
+        let empty_remote: Arc<dyn RemoteStore> =
+            Arc::new(FileSystemRemoteStore::new(empty_dir.path().to_path_buf()));
+        let db = open(&read_dir, &empty_remote)?;
+        assert_eq!(get(&db, b"key1")?.as_deref(), Some(&b"value1"[..]));
+        assert_eq!(get(&db, b"key2")?.as_deref(), Some(&b"local"[..]));
+        db.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_backfill() -> Result<()> {
+        let remote_dir = TempDir::new()?;
+        let remote: Arc<dyn RemoteStore> =
+            Arc::new(FileSystemRemoteStore::new(remote_dir.path().to_path_buf()));
+
+        let seed_dir = TempDir::new()?;
+        let db = open(&seed_dir, &remote)?;
+        assert_eq!(db.mode(), RemoteCacheMode::Seed);
+        for i in 0..100u32 {
+            put(&db, &i.to_le_bytes(), format!("value{i}").as_bytes())?;
+        }
+        db.shutdown()?;
+        drop(db);
+
+        let read_dir = TempDir::new()?;
+        let db = open(&read_dir, &remote)?;
+        assert_eq!(db.mode(), RemoteCacheMode::ReadThrough);
+        put(&db, &0u32.to_le_bytes(), b"local")?;
+        db.wait_for_backfill();
+        db.shutdown()?;
+        drop(db);
+
+        // The local database is complete, so the remote store is not used anymore
+        drop(remote);
+        std::fs::remove_dir_all(remote_dir.path())?;
+        let remote: Arc<dyn RemoteStore> =
+            Arc::new(FileSystemRemoteStore::new(remote_dir.path().to_path_buf()));
+        let db = open(&read_dir, &remote)?;
+        assert_eq!(db.mode(), RemoteCacheMode::Disabled);
+        assert_eq!(
+            get(&db, &0u32.to_le_bytes())?.as_deref(),
+            Some(&b"local"[..])
+        );
+        for i in 1..100u32 {
+            assert_eq!(
+                get(&db, &i.to_le_bytes())?,
+                Some(format!("value{i}").into_bytes())
+            );
+        }
+        db.shutdown()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_remote_entries() -> Result<()> {
+        let remote_dir = TempDir::new()?;
+        let remote: Arc<dyn RemoteStore> =
+            Arc::new(FileSystemRemoteStore::new(remote_dir.path().to_path_buf()));
+
+        let seed_dir = TempDir::new()?;
+        let db = open(&seed_dir, &remote)?;
+        assert_eq!(db.mode(), RemoteCacheMode::Seed);
+        put(&db, b"key1", b"value1")?;
+        put(&db, b"key2", b"value1")?;
+        db.shutdown()?;
+        drop(db);
+
+        // Equal values are only stored once
+        let value_key = content_key(&content_hash(b"value1"));
+        remote.put(&value_key, b"modified")?;
+
+        let read_dir = TempDir::new()?;
+        let db = open(&read_dir, &remote)?;
+        assert_eq!(db.mode(), RemoteCacheMode::ReadThrough);
+        assert!(get(&db, b"key1").is_err());
+        assert!(get(&db, b"key2").is_err());
+        db.prevent_writes();
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_http_store_get_many() -> Result<()> {
+        let remote_dir = TempDir::new()?;
+        let server = RemoteCacheServer::start(remote_dir.path().to_path_buf())?;
+        let store = HttpRemoteStore::new(&server.url())?;
+        let key1 = content_key(&content_hash(b"value1"));
+        let key2 = content_key(&content_hash(b""));
+        store.put(&key1, b"value1")?;
+        store.put(&key2, b"")?;
+        assert_eq!(
+            store.get_many(&[key1.clone(), "00".repeat(16), key2])?,
+            vec![Some(b"value1".to_vec()), None, Some(Vec::new())]
+        );
+        assert_eq!(store.get_many(&[])?, Vec::<Option<Vec<u8>>>::new());
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_system_store() -> Result<()> {
+        let remote_dir = TempDir::new()?;
+        let store = FileSystemRemoteStore::new(remote_dir.path().to_path_buf());
+        let key = content_key(&content_hash(b"key"));
+        assert_eq!(store.get(&key)?, None);
+        assert!(store.put_if_absent(&key, b"first")?);
+        assert!(!store.put_if_absent(&key, b"second")?);
+        assert_eq!(store.get(&key)?.as_deref(), Some(&b"first"[..]));
+        store.put(&key, b"third")?;
+        assert_eq!(store.get(&key)?.as_deref(), Some(&b"third"[..]));
+        assert!(store.get("../escape").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::JoinHandle,
+};
+
+use anyhow::{Context, Result, bail};
+
+use crate::database::remote_cache::store::{
+    BATCH_KEY, FileSystemRemoteStore, RemoteStore, encode_batch_response,
+};
+
+/// A minimal HTTP server for [`super::HttpRemoteStore`] that stores the values in a local
+/// directory. It's a stand-in for a real remote cache in tests and local experiments, it handles
+/// one request at a time and doesn't implement any authentication.
+pub struct RemoteCacheServer {
+    addr: SocketAddr,
+    stopped: Arc<AtomicBool>,
+    join_handle: Option<JoinHandle<()>>,
+}
+
+impl RemoteCacheServer {
+    /// Starts the server on a random local port.
+    pub fn start(path: PathBuf) -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let stopped = Arc::new(AtomicBool::new(false));
+        let store = FileSystemRemoteStore::new(path);
+        let join_handle = std::thread::Builder::new()
+            .name("remote cache server".to_string())
+            .spawn({
+                let stopped = stopped.clone();
+                move || {
+                    for stream in listener.incoming() {
+                        if stopped.load(Ordering::Acquire) {
+                            break;
+                        }
+                        let Ok(stream) = stream else { continue };
+                        if let Err(err) = handle_connection(&store, stream) {
+                            eprintln!("remote cache server: {err:?}");
+                        }
+                    }
+                }
+            })
+            .context("Failed to spawn remote cache server thread")?;
+        Ok(Self {
+            addr,
+            stopped,
+            join_handle: Some(join_handle),
+        })
+    }
+
+    /// The base URL of the server, to be passed to [`super::HttpRemoteStore::new`].
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+}
+
+impl Drop for RemoteCacheServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Release);
+        // Wake up the accept loop
+        let _ = TcpStream::connect(self.addr);
+        if let Some(join_handle) = self.join_handle.take() {
+            let _ = join_handle.join();
+        }
+    }
+}
+
+fn handle_connection(store: &FileSystemRemoteStore, mut stream: TcpStream) -> Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        bail!("Invalid request line {request_line:?}");
+    };
+    let mut content_length = 0;
+    let mut if_none_match = false;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse()?;
+            } else if name.eq_ignore_ascii_case("if-none-match") {
+                if_none_match = value.trim() == "*";
+            }
+        }
+    }
+    let mut body = Vec::new();
+    reader.take(content_length).read_to_end(&mut body)?;
+
+    let key = path.rsplit('/').next().unwrap_or_default();
+    let (status, response) = match method {
+        "GET" => match store.get(key) {
+            Ok(Some(value)) => ("200 OK", value),
+            Ok(None) => ("404 Not Found", Vec::new()),
+            Err(_) => ("400 Bad Request", Vec::new()),
+        },
+        "POST" if key == BATCH_KEY => {
+            let keys = body
+                .split(|&b| b == b'\n')
+                .map(|key| String::from_utf8_lossy(key).into_owned())
+                .collect::<Vec<_>>();
+            match store
+                .get_many(&keys)
+                .and_then(|values| encode_batch_response(&values))
+            {
+                Ok(response) => ("200 OK", response),
+                Err(_) => ("400 Bad Request", Vec::new()),
+            }
+        }
+        "PUT" if if_none_match => match store.put_if_absent(key, &body) {
+            Ok(true) => ("201 Created", Vec::new()),
+            Ok(false) => ("412 Precondition Failed", Vec::new()),
+            Err(_) => ("400 Bad Request", Vec::new()),
+        },
+        "PUT" => match store.put(key, &body) {
+            Ok(()) => ("201 Created", Vec::new()),
+            Err(_) => ("400 Bad Request", Vec::new()),
+        },
+        _ => ("405 Method Not Allowed", Vec::new()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.len()
+    )?;
+    stream.write_all(&response)?;
+    stream.flush()?;
+    Ok(())
+}
@@ -0,0 +1,253 @@
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::sync_channel,
+    },
+    time::Duration,
+};
+
+use anyhow::{Context, Result, bail};
+use byteorder::{BE, ReadBytesExt, WriteBytesExt};
+use reqwest::{Method, RequestBuilder, Url};
+use tokio::runtime::Runtime;
+
+/// A remote key-value store. Keys are lowercase hex strings, values are opaque bytes. Most keys
+/// are the hash of their value, see [`super::RemoteCacheLayer`].
+pub trait RemoteStore: Send + Sync {
+    /// Reads the value for a key. Returns `None` when the key doesn't exist.
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Writes the value for a key. Overwrites an existing value.
+    fn put(&self, key: &str, value: &[u8]) -> Result<()>;
+
+    /// Writes the value for a key, but only when the key doesn't exist yet. Returns `false` when
+    /// the key already existed. This is used to coordinate between multiple writers.
+    fn put_if_absent(&self, key: &str, value: &[u8]) -> Result<bool>;
+
+    /// Reads the values for multiple keys. Stores with a high latency per request should
+    /// override this to read all keys at once.
+    fn get_many(&self, keys: &[String]) -> Result<Vec<Option<Vec<u8>>>> {
+        keys.iter().map(|key| self.get(key)).collect()
+    }
+}
+
+/// The key of the batch endpoint of [`HttpRemoteStore`]. It's not a valid key of an entry.
+pub(super) const BATCH_KEY: &str = "_batch";
+
+/// Encodes the response of the batch endpoint. Every value is prefixed with a byte that tells if
+/// it exists and its length as big endian `u32`.
+pub(super) fn encode_batch_response(values: &[Option<Vec<u8>>]) -> Result<Vec<u8>> {
+    let mut response = Vec::new();
+    for value in values {
+        match value {
+            Some(value) => {
+                response.write_u8(1)?;
+                response.write_u32::<BE>(value.len().try_into()?)?;
+                response.extend_from_slice(value);
+            }
+            None => {
+                response.write_u8(0)?;
+                response.write_u32::<BE>(0)?;
+            }
+        }
+    }
+    Ok(response)
+}
+
+fn decode_batch_response(mut response: &[u8], count: usize) -> Result<Vec<Option<Vec<u8>>>> {
+    let mut values = Vec::with_capacity(count);
+    for _ in 0..count {
+        let found = response.read_u8()? != 0;
+        let len = response.read_u32::<BE>()? as usize;
+        let Some(value) = response.get(..len) else {
+            bail!("Truncated batch response from remote cache");
+        };
+        values.push(found.then(|| value.to_vec()));
+        response = &response[len..];
+    }
+    if !response.is_empty() {
+        bail!("Unexpected data in batch response from remote cache");
+    }
+    Ok(values)
+}
+
+/// A [`RemoteStore`] in a local directory, e.g. a network drive shared by multiple machines.
+pub struct FileSystemRemoteStore {
+    path: PathBuf,
+}
+
+impl FileSystemRemoteStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    fn file_path(&self, key: &str) -> Result<PathBuf> {
+        if key.len() < 3 || !key.bytes().all(|b| b.is_ascii_hexdigit()) {
+            bail!("Invalid remote cache key {key:?}");
+        }
+        // Fan out into subdirectories to avoid huge directories
+        Ok(self.path.join(&key[..2]).join(key))
+    }
+}
+
+/// Writes `value` to a new temporary file next to `path`, so it can be moved into place
+/// atomically.
+fn write_temp_file(path: &Path, value: &[u8]) -> Result<PathBuf> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    fs::create_dir_all(path.parent().unwrap())?;
+    let temp_path = path.with_extension(format!(
+        "{}-{}.tmp",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::write(&temp_path, value)?;
+    Ok(temp_path)
+}
+
+impl RemoteStore for FileSystemRemoteStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match fs::read(self.file_path(key)?) {
+            Ok(value) => Ok(Some(value)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).with_context(|| format!("Unable to read remote cache key {key}")),
+        }
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> Result<()> {
+        let path = self.file_path(key)?;
+        // Write to a temporary file first, so readers never see a partial value
+        let temp_path = write_temp_file(&path, value)?;
+        fs::rename(&temp_path, &path)
+            .with_context(|| format!("Unable to write remote cache key {key}"))?;
+        Ok(())
+    }
+
+    fn put_if_absent(&self, key: &str, value: &[u8]) -> Result<bool> {
+        let path = self.file_path(key)?;
+        let temp_path = write_temp_file(&path, value)?;
+        // A rename would replace an existing file, a hard link fails instead. Either way readers
+        // only ever see the complete value.
+        let result = fs::hard_link(&temp_path, &path);
+        fs::remove_file(&temp_path)?;
+        match result {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => Ok(false),
+            Err(err) => Err(err).with_context(|| format!("Unable to write remote cache key {key}")),
+        }
+    }
+}
+
+/// A [`RemoteStore`] that talks to an HTTP server. Values are read with `GET {url}/{key}` and
+/// written with `PUT {url}/{key}`. `put_if_absent` sends an `If-None-Match: *` header and expects
+/// `412 Precondition Failed` when the key exists. Multiple values are read with
+/// `POST {url}/_batch`, the body contains one key per line.
+///
+/// See [`super::RemoteCacheServer`] for a compatible server.
+pub struct HttpRemoteStore {
+    client: reqwest::Client,
+    /// The base URL of all keys, without trailing slash.
+    url: String,
+    /// Requests are sent on a separate runtime, so the store can be used from any thread,
+    /// including the threads of another runtime.
+    runtime: Option<Runtime>,
+}
+
+impl HttpRemoteStore {
+    pub fn new(url: &str) -> Result<Self> {
+        let parsed =
+            Url::parse(url).with_context(|| format!("Invalid remote cache URL {url:?}"))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            bail!("Unsupported remote cache URL {url:?}, only http:// and https:// are supported");
+        }
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .context("Unable to create the remote cache HTTP client")?;
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("remote cache http")
+            .enable_all()
+            .build()
+            .context("Unable to create the remote cache runtime")?;
+        Ok(Self {
+            client,
+            url: url.trim_end_matches('/').to_string(),
+            runtime: Some(runtime),
+        })
+    }
+
+    fn request(&self, method: Method, key: &str) -> RequestBuilder {
+        self.client.request(method, format!("{}/{key}", self.url))
+    }
+
+    /// Sends a request and waits for the complete response body.
+    fn send(&self, request: RequestBuilder) -> Result<(u16, Vec<u8>)> {
+        let (sender, receiver) = sync_channel(1);
+        self.runtime.as_ref().unwrap().spawn(async move {
+            let result = async {
+                let response = request.send().await?;
+                let status = response.status().as_u16();
+                let body = response.bytes().await?;
+                anyhow::Ok((status, body.to_vec()))
+            }
+            .await;
+            let _ = sender.send(result);
+        });
+        receiver
+            .recv()
+            .context("The remote cache request was cancelled")?
+            .context("Unable to reach the remote cache")
+    }
+}
+
+impl Drop for HttpRemoteStore {
+    fn drop(&mut self) {
+        // Dropping a runtime blocks, which isn't allowed on the threads of another runtime
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+impl RemoteStore for HttpRemoteStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match self.send(self.request(Method::GET, key))? {
+            (200, body) => Ok(Some(body)),
+            (404, _) => Ok(None),
+            (status, _) => bail!("Remote cache responded with status {status} for GET {key}"),
+        }
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> Result<()> {
+        match self.send(self.request(Method::PUT, key).body(value.to_vec()))? {
+            (200 | 201 | 204, _) => Ok(()),
+            (status, _) => bail!("Remote cache responded with status {status} for PUT {key}"),
+        }
+    }
+
+    fn put_if_absent(&self, key: &str, value: &[u8]) -> Result<bool> {
+        let request = self
+            .request(Method::PUT, key)
+            .header("If-None-Match", "*")
+            .body(value.to_vec());
+        match self.send(request)? {
+            (200 | 201 | 204, _) => Ok(true),
+            (412, _) => Ok(false),
+            (status, _) => bail!("Remote cache responded with status {status} for PUT {key}"),
+        }
+    }
+
+    fn get_many(&self, keys: &[String]) -> Result<Vec<Option<Vec<u8>>>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let request = self.request(Method::POST, BATCH_KEY).body(keys.join("\n"));
+        match self.send(request)? {
+            (200, body) => decode_batch_response(&body, keys.len()),
+            (status, _) => bail!("Remote cache responded with status {status} for a batch GET"),
+        }
+    }
+}
@@ -8,7 +8,8 @@ use std::{
 use anyhow::Result;
 use parking_lot::Mutex;
 use turbo_persistence::{
-    ArcSlice, CompactConfig, KeyBase, StoreKey, TurboPersistence, ValueBuffer,
+    ArcSlice, BackgroundCompaction, BackgroundCompactionConfig, CompactConfig, KeyBase, StoreKey,
+    TurboPersistence, ValueBuffer,
 };
 
 use crate::database::{
@@ -30,8 +31,8 @@ const COMPACT_CONFIG: CompactConfig = CompactConfig {
 pub struct TurboKeyValueDatabase {
     db: Arc<TurboPersistence>,
     compact_join_handle: Mutex<Option<JoinHandle<Result<()>>>>,
+    background_compaction: Mutex<Option<BackgroundCompaction>>,
     is_ci: bool,
-    is_short_session: bool,
 }
 
 impl TurboKeyValueDatabase {
@@ -40,11 +41,25 @@ impl TurboKeyValueDatabase {
         let mut this = Self {
             db: db.clone(),
             compact_join_handle: Mutex::new(None),
+            background_compaction: Mutex::new(None),
             is_ci,
-            is_short_session,
         };
-        // start compaction in background if the database is not empty
-        if !db.is_empty() {
+        if !is_short_session {
+            // Long sessions compact continuously in the background
+            let background_compaction =
+                db.start_background_compaction(BackgroundCompactionConfig {
+                    compact_config: CompactConfig {
+                        max_merge_segment_count: available_parallelism()
+                            .map_or(4, |c| max(4, c.get() / 4)),
+                        ..COMPACT_CONFIG
+                    },
+                    ..Default::default()
+                })?;
+            this.background_compaction
+                .get_mut()
+                .replace(background_compaction);
+        } else if !db.is_empty() {
+            // start compaction in background if the database is not empty
             let handle = spawn(move || {
                 db.compact(&CompactConfig {
                     max_merge_segment_count: available_parallelism()
@@ -86,6 +101,18 @@ impl KeyValueDatabase for TurboKeyValueDatabase {
         self.db.get(key_space as usize, &key)
     }
 
+    fn for_each_entry(
+        &self,
+        key_space: KeySpace,
+        f: &mut dyn FnMut(&[u8], &[u8]) -> Result<()>,
+    ) -> Result<()> {
+        for entry in self.db.iter(key_space as usize)? {
+            let (key, value) = entry?;
+            f(&key, &value)?;
+        }
+        Ok(())
+    }
+
     type ConcurrentWriteBatch<'l>
         = TurboWriteBatch<'l>
     where
@@ -102,14 +129,16 @@ impl KeyValueDatabase for TurboKeyValueDatabase {
         Ok(WriteBatch::concurrent(TurboWriteBatch {
             batch: self.db.write_batch()?,
             db: &self.db,
-            compact_join_handle: (!self.is_short_session && !self.db.is_empty())
-                .then_some(&self.compact_join_handle),
         }))
     }
 
     fn prevent_writes(&self) {}
 
     fn shutdown(&self) -> Result<()> {
+        // Stop the background compaction
+        if let Some(background_compaction) = self.background_compaction.lock().take() {
+            background_compaction.stop()?;
+        }
         // Wait for the compaction to finish
         if let Some(join_handle) = self.compact_join_handle.lock().take() {
             join_handle.join().unwrap()?;
@@ -132,7 +161,6 @@ impl KeyValueDatabase for TurboKeyValueDatabase {
 pub struct TurboWriteBatch<'a> {
     batch: turbo_persistence::WriteBatch<WriteBuffer<'static>, 5>,
     db: &'a Arc<TurboPersistence>,
-    compact_join_handle: Option<&'a Mutex<Option<JoinHandle<Result<()>>>>>,
 }
 
 impl<'a> BaseWriteBatch<'a> for TurboWriteBatch<'a> {
@@ -153,19 +181,6 @@ impl<'a> BaseWriteBatch<'a> for TurboWriteBatch<'a> {
         // Commit the write batch
         self.db.commit_write_batch(self.batch)?;
 
-        if let Some(compact_join_handle) = self.compact_join_handle {
-            // Start a new compaction in the background
-            let db = self.db.clone();
-            let handle = spawn(move || {
-                db.compact(&CompactConfig {
-                    max_merge_segment_count: available_parallelism()
-                        .map_or(4, |c| max(4, c.get() / 2)),
-                    ..COMPACT_CONFIG
-                })
-            });
-            compact_join_handle.lock().replace(handle);
-        }
-
         Ok(())
     }
 }
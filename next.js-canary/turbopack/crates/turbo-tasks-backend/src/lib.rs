@@ -11,16 +11,26 @@ mod database;
 mod kv_backing_storage;
 mod utils;
 
-use std::path::Path;
+use std::{path::Path, sync::Arc};
 
 use anyhow::Result;
 
-use crate::database::{noop_kv::NoopKvDb, turbo::TurboKeyValueDatabase};
+use crate::database::{
+    noop_kv::NoopKvDb, remote_cache::RemoteCacheLayer, turbo::TurboKeyValueDatabase,
+};
 pub use crate::{
     backend::{BackendOptions, StorageMode, TurboTasksBackend},
     backing_storage::BackingStorage,
     database::{
-        db_invalidation, db_invalidation::StartupCacheState, db_versioning::GitVersionInfo,
+        db_archive,
+        db_archive::{CacheArchiveManifest, export_cache, import_cache},
+        db_invalidation,
+        db_invalidation::StartupCacheState,
+        db_versioning::GitVersionInfo,
+        remote_cache,
+        remote_cache::{
+            FileSystemRemoteStore, HttpRemoteStore, RemoteCacheMode, RemoteCacheServer, RemoteStore,
+        },
     },
     kv_backing_storage::KeyValueDatabaseBackingStorage,
 };
@@ -93,6 +103,34 @@ pub fn turbo_backing_storage(
     )
 }
 
+pub type RemoteCacheTurboBackingStorage =
+    KeyValueDatabaseBackingStorage<RemoteCacheLayer<TurboKeyValueDatabase>>;
+
+/// Creates a [`turbo_backing_storage`] that shares its cache with other machines through a
+/// [`RemoteStore`].
+///
+/// `namespace` identifies the state that is shared, e.g. the commit of the repository. It's
+/// combined with the version from `version_info`. See [`RemoteCacheLayer`] for details.
+pub fn remote_cache_turbo_backing_storage(
+    base_path: &Path,
+    version_info: &GitVersionInfo,
+    is_ci: bool,
+    is_short_session: bool,
+    remote: Arc<dyn RemoteStore>,
+    namespace: &str,
+) -> Result<(RemoteCacheTurboBackingStorage, StartupCacheState)> {
+    let namespace = format!("{}/{namespace}", version_info.describe);
+    KeyValueDatabaseBackingStorage::open_versioned_on_disk(
+        base_path.to_owned(),
+        version_info,
+        is_ci,
+        |path| {
+            let database = TurboKeyValueDatabase::new(path, is_ci, is_short_session)?;
+            RemoteCacheLayer::new(database, remote, namespace)
+        },
+    )
+}
+
 pub type NoopBackingStorage = KeyValueDatabaseBackingStorage<NoopKvDb>;
 
 /// Creates an no-op in-memory `BackingStorage` to be passed to [`TurboTasksBackend::new`].
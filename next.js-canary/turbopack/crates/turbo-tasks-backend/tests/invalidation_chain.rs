@@ -0,0 +1,70 @@
+#![feature(arbitrary_self_types)]
+#![feature(arbitrary_self_types_pointers)]
+#![allow(clippy::needless_return)] // tokio macro-generated code doesn't respect this
+
+use anyhow::Result;
+use turbo_tasks::{
+    State, Vc,
+    invalidation_chain::{DependencyChange, InvalidationRoot},
+};
+use turbo_tasks_testing::{Registration, register, run_without_cache_check};
+
+static REGISTRATION: Registration = register!();
+
+#[tokio::test]
+async fn records_chain_through_tasks() -> Result<()> {
+    run_without_cache_check(&REGISTRATION, async move {
+        let tt = turbo_tasks::turbo_tasks();
+        let chains = tt.invalidation_chains().enable().clone();
+
+        let input = ChangingInput {
+            state: State::new(1),
+        }
+        .cell();
+        assert_eq!(*double(input).strongly_consistent().await?, 2);
+        // Initial executions are not caused by invalidations
+        assert!(chains.take_recent_executions().is_empty());
+
+        input.await?.state.set(2);
+        assert_eq!(*double(input).strongly_consistent().await?, 4);
+
+        let executions = chains.take_recent_executions();
+        let read_execution = executions
+            .iter()
+            .find(|execution| execution.description.contains("read_state"))
+            .unwrap();
+        assert_eq!(read_execution.chain.root, InvalidationRoot::Unknown);
+        assert!(read_execution.chain.steps.is_empty());
+
+        let double_execution = executions
+            .iter()
+            .find(|execution| execution.description.contains("double"))
+            .unwrap();
+        assert_eq!(double_execution.chain.root, InvalidationRoot::Unknown);
+        let [step] = &double_execution.chain.steps[..] else {
+            panic!("unexpected chain {}", double_execution.chain);
+        };
+        assert_eq!(step.task, read_execution.task);
+        assert_eq!(step.change, DependencyChange::Cell);
+
+        let chain = chains.get(double_execution.task).unwrap();
+        assert_eq!(chain.steps.len(), 1);
+        anyhow::Ok(())
+    })
+    .await
+}
+
+#[turbo_tasks::value]
+struct ChangingInput {
+    state: State<u32>,
+}
+
+#[turbo_tasks::function]
+async fn read_state(input: Vc<ChangingInput>) -> Result<Vc<u32>> {
+    Ok(Vc::cell(*input.await?.state.get()))
+}
+
+#[turbo_tasks::function]
+async fn double(input: Vc<ChangingInput>) -> Result<Vc<u32>> {
+    Ok(Vc::cell(*read_state(input).await? * 2))
+}
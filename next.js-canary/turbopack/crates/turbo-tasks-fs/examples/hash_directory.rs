@@ -36,7 +36,7 @@ async fn main() -> Result<()> {
         Box::pin(async {
             let root = current_dir().unwrap().to_str().unwrap().into();
             let disk_fs = DiskFileSystem::new(rcstr!("project"), root);
-            disk_fs.await?.start_watching(None).await?;
+            disk_fs.await?.start_watching(Default::default()).await?;
 
             // Smart Pointer cast
             let fs: Vc<Box<dyn FileSystem>> = Vc::upcast(disk_fs);
@@ -0,0 +1,418 @@
+use std::io::{Cursor, Read};
+
+use anyhow::{Context, Result, bail};
+use auto_hash_map::AutoMap;
+use flate2::read::GzDecoder;
+use rustc_hash::FxHashMap;
+use tar::EntryType;
+use turbo_rcstr::RcStr;
+use turbo_tasks::{ValueToString, Vc};
+
+use crate::{
+    File, FileContent, FileMeta, FileSystem, FileSystemPath, LinkContent, LinkType,
+    RawDirectoryContent, RawDirectoryEntry, attach::AttachedFileSystem, util::normalize_path,
+};
+
+/// The maximum number of symlinks followed when resolving a path, to avoid endless loops.
+const MAX_SYMLINK_DEPTH: usize = 40;
+
+/// The maximum decompressed size of a single entry of an archive. Archives are decompressed into
+/// memory as a whole, so larger entries, e.g. from a zip bomb, are rejected.
+const MAX_ENTRY_SIZE: u64 = 256 * 1024 * 1024;
+
+/// The maximum decompressed size of all entries of an archive.
+const MAX_ARCHIVE_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// A read-only [FileSystem] for the contents of a `.zip` or `.tgz` archive, e.g. a package in the
+/// cache of Yarn PnP.
+///
+/// The archive is read and decompressed as a whole from its [FileSystemPath], so changes to the
+/// archive file invalidate all reads. Symlinks are followed when they are the last component of a
+/// path, but not in the middle of it.
+#[turbo_tasks::value]
+pub struct ArchiveFileSystem {
+    archive: FileSystemPath,
+}
+
+#[turbo_tasks::value_impl]
+impl ArchiveFileSystem {
+    /// Creates a new [ArchiveFileSystem] for the archive at `archive`. The format is detected
+    /// from the content of the file.
+    #[turbo_tasks::function]
+    pub fn new(archive: FileSystemPath) -> Vc<Self> {
+        ArchiveFileSystem { archive }.cell()
+    }
+
+    /// Mounts the archive as a directory tree at its own path, so e.g.
+    /// `.yarn/cache/pkg.zip/node_modules/pkg/index.js` reads `node_modules/pkg/index.js` from
+    /// the archive `.yarn/cache/pkg.zip`.
+    #[turbo_tasks::function]
+    pub fn attach(archive: FileSystemPath) -> Vc<AttachedFileSystem> {
+        AttachedFileSystem::new(archive.clone(), Vc::upcast(ArchiveFileSystem::new(archive)))
+    }
+
+    #[turbo_tasks::function]
+    async fn contents(&self) -> Result<Vc<ArchiveContents>> {
+        let FileContent::Content(file) = &*self.archive.read().await? else {
+            return Ok(ArchiveContents::default().cell());
+        };
+        let bytes = file.content().to_bytes().into_owned();
+        let path = self.archive.path.clone();
+        let entries = turbo_tasks::spawn_blocking(move || {
+            let _span = tracing::info_span!("read archive", name = &*path).entered();
+            read_archive(&bytes)
+        })
+        .await
+        .with_context(|| format!("reading archive {}", self.archive.path))?;
+        Ok(ArchiveContents { entries }.cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl FileSystem for ArchiveFileSystem {
+    #[turbo_tasks::function]
+    async fn read(self: Vc<Self>, fs_path: FileSystemPath) -> Result<Vc<FileContent>> {
+        let contents = self.contents().await?;
+        Ok(match contents.resolve(&fs_path.path) {
+            Some(ArchiveEntry::File(file)) => FileContent::Content(file.clone()).cell(),
+            _ => FileContent::NotFound.cell(),
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn read_link(self: Vc<Self>, fs_path: FileSystemPath) -> Result<Vc<LinkContent>> {
+        let contents = self.contents().await?;
+        let Some(ArchiveEntry::Symlink(target)) = contents.entries.get(fs_path.path.as_str())
+        else {
+            return Ok(LinkContent::NotFound.cell());
+        };
+        let mut link_type = LinkType::empty();
+        if target.starts_with('/') {
+            link_type |= LinkType::ABSOLUTE;
+        }
+        if let Some(ArchiveEntry::Directory(_)) = contents.resolve(&fs_path.path) {
+            link_type |= LinkType::DIRECTORY;
+        }
+        Ok(LinkContent::Link {
+            target: target.clone(),
+            link_type,
+        }
+        .cell())
+    }
+
+    #[turbo_tasks::function]
+    async fn raw_read_dir(
+        self: Vc<Self>,
+        fs_path: FileSystemPath,
+    ) -> Result<Vc<RawDirectoryContent>> {
+        let contents = self.contents().await?;
+        Ok(match contents.resolve(&fs_path.path) {
+            Some(ArchiveEntry::Directory(entries)) => RawDirectoryContent::new(entries.clone()),
+            _ => RawDirectoryContent::not_found(),
+        })
+    }
+
+    #[turbo_tasks::function]
+    fn write(&self, _fs_path: FileSystemPath, _content: Vc<FileContent>) -> Result<Vc<()>> {
+        bail!("Writing is not possible to the archive file system")
+    }
+
+    #[turbo_tasks::function]
+    fn write_link(&self, _fs_path: FileSystemPath, _target: Vc<LinkContent>) -> Result<Vc<()>> {
+        bail!("Writing is not possible to the archive file system")
+    }
+
+    #[turbo_tasks::function]
+    async fn metadata(self: Vc<Self>, fs_path: FileSystemPath) -> Result<Vc<FileMeta>> {
+        let contents = self.contents().await?;
+        Ok(match contents.resolve(&fs_path.path) {
+            Some(ArchiveEntry::File(file)) => file.meta().clone().cell(),
+            Some(_) => FileMeta::default().cell(),
+            None => bail!("path not found, can't read metadata"),
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for ArchiveFileSystem {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<RcStr>> {
+        Ok(Vc::cell(
+            format!("archive {}", self.archive.value_to_string().await?).into(),
+        ))
+    }
+}
+
+enum ArchiveEntry {
+    File(File),
+    Directory(AutoMap<RcStr, RawDirectoryEntry>),
+    Symlink(RcStr),
+}
+
+/// The decompressed entries of an archive by path. An archive that doesn't exist has no entries,
+/// not even a root directory.
+#[turbo_tasks::value(serialization = "none", eq = "manual", cell = "new")]
+#[derive(Default)]
+struct ArchiveContents {
+    #[turbo_tasks(trace_ignore, debug_ignore)]
+    entries: FxHashMap<RcStr, ArchiveEntry>,
+}
+
+impl ArchiveContents {
+    /// Looks up the entry at `path`, following symlinks.
+    fn resolve(&self, path: &str) -> Option<&ArchiveEntry> {
+        let mut path = path.to_string();
+        for _ in 0..MAX_SYMLINK_DEPTH {
+            match self.entries.get(path.as_str())? {
+                ArchiveEntry::Symlink(target) => {
+                    // Absolute targets point outside of the archive
+                    if target.starts_with('/') {
+                        return None;
+                    }
+                    let parent = path.rsplit_once('/').map_or("", |(parent, _)| parent);
+                    path = normalize_path(&format!("{parent}/{target}"))?;
+                }
+                entry => return Some(entry),
+            }
+        }
+        None
+    }
+}
+
+fn read_archive(bytes: &[u8]) -> Result<FxHashMap<RcStr, ArchiveEntry>> {
+    let mut builder = ArchiveContentsBuilder::default();
+    builder.directory("");
+    let mut total_size = 0;
+    if bytes.starts_with(b"PK") {
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes))?;
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i)?;
+            let path = file.name().to_string();
+            if file.is_dir() {
+                builder.directory(&path);
+                continue;
+            }
+            // The size is declared by the archive, don't trust it for allocating
+            let content = read_entry(&mut file, &path, &mut total_size)?;
+            let entry = if file.is_symlink() {
+                ArchiveEntry::Symlink(String::from_utf8(content)?.into())
+            } else {
+                ArchiveEntry::File(File::from(content))
+            };
+            builder.add(&path, entry);
+        }
+    } else if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut archive = tar::Archive::new(GzDecoder::new(bytes));
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let path = entry.path()?.to_string_lossy().into_owned();
+            match entry.header().entry_type() {
+                EntryType::Directory => {
+                    builder.directory(&path);
+                }
+                EntryType::Symlink => {
+                    let Some(target) = entry.link_name()? else {
+                        continue;
+                    };
+                    let target = target.to_string_lossy().as_ref().into();
+                    builder.add(&path, ArchiveEntry::Symlink(target));
+                }
+                EntryType::Regular | EntryType::Continuous => {
+                    let content = read_entry(&mut entry, &path, &mut total_size)?;
+                    builder.add(&path, ArchiveEntry::File(File::from(content)));
+                }
+                // Hard links, devices, etc. are not supported
+                _ => {}
+            }
+        }
+    } else {
+        bail!("unknown archive format, only .zip and .tgz are supported");
+    }
+    Ok(builder.entries)
+}
+
+/// Reads the decompressed content of an archive entry. Fails when the entry is larger than
+/// [MAX_ENTRY_SIZE] or all entries read so far are larger than [MAX_ARCHIVE_SIZE].
+fn read_entry(entry: impl Read, path: &str, total_size: &mut u64) -> Result<Vec<u8>> {
+    let mut content = Vec::new();
+    entry.take(MAX_ENTRY_SIZE + 1).read_to_end(&mut content)?;
+    let size = content.len() as u64;
+    if size > MAX_ENTRY_SIZE {
+        bail!("archive entry {path} is larger than {MAX_ENTRY_SIZE} bytes when decompressed");
+    }
+    *total_size += size;
+    if *total_size > MAX_ARCHIVE_SIZE {
+        bail!("archive is larger than {MAX_ARCHIVE_SIZE} bytes when decompressed");
+    }
+    Ok(content)
+}
+
+#[derive(Default)]
+struct ArchiveContentsBuilder {
+    entries: FxHashMap<RcStr, ArchiveEntry>,
+}
+
+impl ArchiveContentsBuilder {
+    /// Returns the listing of the directory at `path`, creating it and its parents if needed.
+    fn directory(&mut self, path: &str) -> &mut AutoMap<RcStr, RawDirectoryEntry> {
+        // Entries that would leave the archive are put at the root
+        let path = normalize_path(path).unwrap_or_default();
+        if !matches!(
+            self.entries.get(path.as_str()),
+            Some(ArchiveEntry::Directory(_))
+        ) {
+            if let Some((parent, name)) = split_path(&path) {
+                self.directory(parent)
+                    .insert(name.into(), RawDirectoryEntry::Directory);
+            }
+            self.entries.insert(
+                path.as_str().into(),
+                ArchiveEntry::Directory(AutoMap::new()),
+            );
+        }
+        let Some(ArchiveEntry::Directory(entries)) = self.entries.get_mut(path.as_str()) else {
+            unreachable!("directory was created above");
+        };
+        entries
+    }
+
+    fn add(&mut self, path: &str, entry: ArchiveEntry) {
+        let Some((parent, name)) = normalize_path(path).as_deref().and_then(split_path) else {
+            // The root or a path outside of the archive
+            return;
+        };
+        let kind = match &entry {
+            ArchiveEntry::File(_) => RawDirectoryEntry::File,
+            ArchiveEntry::Directory(_) => RawDirectoryEntry::Directory,
+            ArchiveEntry::Symlink(_) => RawDirectoryEntry::Symlink,
+        };
+        self.directory(parent).insert(name.into(), kind);
+        let path = if parent.is_empty() {
+            RcStr::from(name)
+        } else {
+            format!("{parent}/{name}").into()
+        };
+        self.entries.insert(path, entry);
+    }
+}
+
+/// Splits a normalized path into its parent directory and name. Returns `None` for the root.
+fn split_path(path: &str) -> Option<(&str, &str)> {
+    if path.is_empty() {
+        return None;
+    }
+    Some(path.rsplit_once('/').unwrap_or(("", path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    fn zip(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (path, content) in files {
+            writer
+                .start_file(*path, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn tgz(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::default(),
+        ));
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn listing(contents: &ArchiveContents, path: &str) -> Vec<(String, RawDirectoryEntry)> {
+        let Some(ArchiveEntry::Directory(entries)) = contents.resolve(path) else {
+            panic!("{path} is not a directory");
+        };
+        let mut entries = entries
+            .iter()
+            .map(|(name, entry)| (name.to_string(), entry.clone()))
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries
+    }
+
+    fn read(contents: &ArchiveContents, path: &str) -> Option<String> {
+        match contents.resolve(path)? {
+            ArchiveEntry::File(file) => Some(file.content().to_str().unwrap().into_owned()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn read_zip_and_tgz() {
+        let files = [
+            ("node_modules/pkg/package.json", "{}"),
+            ("node_modules/pkg/lib/index.js", "module.exports = 1"),
+        ];
+        for bytes in [zip(&files), tgz(&files)] {
+            let contents = ArchiveContents {
+                entries: read_archive(&bytes).unwrap(),
+            };
+            assert_eq!(
+                listing(&contents, ""),
+                vec![("node_modules".to_string(), RawDirectoryEntry::Directory)]
+            );
+            assert_eq!(
+                listing(&contents, "node_modules/pkg"),
+                vec![
+                    ("lib".to_string(), RawDirectoryEntry::Directory),
+                    ("package.json".to_string(), RawDirectoryEntry::File),
+                ]
+            );
+            assert_eq!(
+                read(&contents, "node_modules/pkg/lib/index.js").as_deref(),
+                Some("module.exports = 1")
+            );
+            assert_eq!(read(&contents, "node_modules/pkg/missing.js"), None);
+        }
+    }
+
+    #[test]
+    fn limit_decompressed_size() {
+        let mut total_size = 0;
+        assert_eq!(
+            read_entry(&b"content"[..], "a.js", &mut total_size).unwrap(),
+            b"content"
+        );
+        assert_eq!(total_size, 7);
+        let mut total_size = MAX_ARCHIVE_SIZE - 4;
+        assert!(read_entry(&b"content"[..], "b.js", &mut total_size).is_err());
+    }
+
+    #[test]
+    fn resolve_symlinks() {
+        let mut builder = ArchiveContentsBuilder::default();
+        builder.add("pkg/index.js", ArchiveEntry::File(File::from("content")));
+        builder.add("pkg/link.js", ArchiveEntry::Symlink("index.js".into()));
+        builder.add("link", ArchiveEntry::Symlink("pkg".into()));
+        builder.add("loop", ArchiveEntry::Symlink("loop".into()));
+        let contents = ArchiveContents {
+            entries: builder.entries,
+        };
+        assert_eq!(read(&contents, "pkg/link.js").as_deref(), Some("content"));
+        assert!(matches!(
+            contents.resolve("link"),
+            Some(ArchiveEntry::Directory(_))
+        ));
+        assert!(contents.resolve("loop").is_none());
+    }
+}
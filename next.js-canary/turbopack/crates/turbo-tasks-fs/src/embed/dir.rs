@@ -13,7 +13,7 @@ pub async fn directory_from_relative_path(
     path: RcStr,
 ) -> Result<Vc<Box<dyn FileSystem>>> {
     let disk_fs = DiskFileSystem::new(name, path);
-    disk_fs.await?.start_watching(None).await?;
+    disk_fs.await?.start_watching(Default::default()).await?;
 
     Ok(Vc::upcast(disk_fs))
 }
@@ -23,7 +23,7 @@ pub async fn content_from_relative_path(
         root_path.to_string_lossy().into(),
         root_path.to_string_lossy().into(),
     );
-    disk_fs.await?.start_watching(None).await?;
+    disk_fs.await?.start_watching(Default::default()).await?;
 
     let fs_path = disk_fs.root().await?.join(path)?;
     Ok(fs_path.read())
@@ -185,3 +185,47 @@ impl InvalidationReasonKind for WriteKind {
         )
     }
 }
+
+/// Invalidation was caused by setting or removing an entry of an
+/// [`OverlayFileSystem`][crate::overlay::OverlayFileSystem]
+#[derive(PartialEq, Eq, Hash, Clone)]
+pub(crate) struct OverlayChange {
+    pub path: RcStr,
+}
+
+impl InvalidationReason for OverlayChange {
+    fn kind(&self) -> Option<StaticOrArc<dyn InvalidationReasonKind>> {
+        Some(StaticOrArc::Static(&OVERLAY_CHANGE_KIND))
+    }
+}
+
+impl Display for OverlayChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} changed in overlay", self.path)
+    }
+}
+
+/// Invalidation kind for [OverlayChange]
+#[derive(PartialEq, Eq, Hash)]
+struct OverlayChangeKind;
+
+static OVERLAY_CHANGE_KIND: OverlayChangeKind = OverlayChangeKind;
+
+impl InvalidationReasonKind for OverlayChangeKind {
+    fn fmt(
+        &self,
+        reasons: &FxIndexSet<StaticOrArc<dyn InvalidationReason>>,
+        f: &mut Formatter<'_>,
+    ) -> std::fmt::Result {
+        let first_reason: &dyn InvalidationReason = &*reasons[0];
+        write!(
+            f,
+            "{} files changed in overlay ({}, ...)",
+            reasons.len(),
+            (first_reason as &dyn Any)
+                .downcast_ref::<OverlayChange>()
+                .unwrap()
+                .path
+        )
+    }
+}
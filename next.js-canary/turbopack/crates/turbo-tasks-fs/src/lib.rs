@@ -9,6 +9,7 @@
 #![feature(arbitrary_self_types_pointers)]
 #![allow(clippy::mutable_key_type)]
 
+pub mod archive;
 pub mod attach;
 pub mod embed;
 pub mod glob;
@@ -17,6 +18,7 @@ pub mod invalidation;
 mod invalidator_map;
 pub mod json;
 mod mutex_map;
+pub mod overlay;
 mod path_map;
 mod read_glob;
 mod retry;
@@ -36,7 +38,6 @@ use std::{
     mem::take,
     path::{MAIN_SEPARATOR, Path, PathBuf},
     sync::{Arc, LazyLock},
-    time::Duration,
 };
 
 use anyhow::{Context, Result, anyhow, bail};
@@ -55,8 +56,11 @@ use tracing::Instrument;
 use turbo_rcstr::{RcStr, rcstr};
 use turbo_tasks::{
     ApplyEffectsContext, Completion, InvalidationReason, Invalidator, NonLocalValue, ReadRef,
-    ResolvedVc, TaskInput, ValueToString, Vc, debug::ValueDebugFormat, effect,
-    mark_session_dependent, mark_stateful, trace::TraceRawVcs,
+    ResolvedVc, TaskInput, ValueToString, Vc,
+    debug::ValueDebugFormat,
+    effect, mark_session_dependent, mark_stateful,
+    replay::{self, ReplayDirectoryEntry, ReplayEvent},
+    trace::TraceRawVcs,
 };
 use turbo_tasks_hash::{DeterministicHash, DeterministicHasher, hash_xxh3_hash64};
 
@@ -69,9 +73,11 @@ use crate::{
     retry::retry_blocking,
     rope::{Rope, RopeReader},
     util::{extract_disk_access, join_path, normalize_path, sys_to_unix, unix_to_sys},
-    watcher::DiskWatcher,
+    watcher::{DiskWatcher, invalidate_path_and_children_execute},
+};
+pub use crate::{
+    read_glob::ReadGlobResult, virtual_fs::VirtualFileSystem, watcher::DiskWatchOptions,
 };
-pub use crate::{read_glob::ReadGlobResult, virtual_fs::VirtualFileSystem};
 
 /// A (somewhat arbitrary) filename limit that we should try to keep output file names below.
 ///
@@ -322,11 +328,17 @@ impl DiskFileSystemInner {
             .into_par_iter()
             .chain(dir_invalidator_map.into_par_iter())
             .flat_map(|(_, invalidators)| invalidators.into_par_iter());
-        iter.for_each(|(i, _)| {
-            let _span = span.clone().entered();
-            let _guard = handle.enter();
-            i.invalidate()
-        });
+        // A replay invalidates everything below the root again.
+        replay::apply_file_changes(
+            || vec![self.root_path().to_string_lossy().into_owned()],
+            || {
+                iter.for_each(|(i, _)| {
+                    let _span = span.clone().entered();
+                    let _guard = handle.enter();
+                    i.invalidate()
+                })
+            },
+        );
     }
 
     /// Invalidates every tracked file in the filesystem.
@@ -351,11 +363,16 @@ impl DiskFileSystemInner {
                     .into_par_iter()
                     .map(move |i| (reason_for_path.clone(), i))
             });
-        iter.for_each(|(reason, (invalidator, _))| {
-            let _span = span.clone().entered();
-            let _guard = handle.enter();
-            invalidator.invalidate_with_reason(reason)
-        });
+        replay::apply_file_changes(
+            || vec![self.root_path().to_string_lossy().into_owned()],
+            || {
+                iter.for_each(|(reason, (invalidator, _))| {
+                    let _span = span.clone().entered();
+                    let _guard = handle.enter();
+                    invalidator.invalidate_with_reason(reason)
+                })
+            },
+        );
     }
 
     fn invalidate_from_write(
@@ -363,7 +380,11 @@ impl DiskFileSystemInner {
         full_path: &Path,
         invalidators: Vec<(Invalidator, Option<WriteContent>)>,
     ) {
-        if !invalidators.is_empty() {
+        if invalidators.is_empty() {
+            return;
+        }
+        // Writes happen again when they are replayed, so their invalidations are not recorded.
+        replay::apply_file_changes(Vec::new, || {
             if let Some(path) = format_absolute_fs_path(full_path, &self.name, self.root_path()) {
                 if invalidators.len() == 1 {
                     let (invalidator, _) = invalidators.into_iter().next().unwrap();
@@ -378,14 +399,14 @@ impl DiskFileSystemInner {
                     invalidator.invalidate();
                 });
             }
-        }
+        });
     }
 
     #[tracing::instrument(level = "info", name = "start filesystem watching", skip_all, fields(path = %self.root))]
     async fn start_watching_internal(
         self: &Arc<Self>,
         report_invalidation_reason: bool,
-        poll_interval: Option<Duration>,
+        options: DiskWatchOptions,
     ) -> Result<()> {
         let root_path = self.root_path().to_path_buf();
 
@@ -401,7 +422,7 @@ impl DiskFileSystemInner {
         .await?;
 
         self.watcher
-            .start_watching(self.clone(), report_invalidation_reason, poll_interval)?;
+            .start_watching(self.clone(), report_invalidation_reason, options)?;
 
         Ok(())
     }
@@ -455,19 +476,59 @@ impl DiskFileSystem {
         self.inner.invalidate_with_reason(reason);
     }
 
-    pub async fn start_watching(&self, poll_interval: Option<Duration>) -> Result<()> {
-        self.inner
-            .start_watching_internal(false, poll_interval)
-            .await
+    pub async fn start_watching(&self, options: DiskWatchOptions) -> Result<()> {
+        self.inner.start_watching_internal(false, options).await
     }
 
     pub async fn start_watching_with_invalidation_reason(
         &self,
-        poll_interval: Option<Duration>,
+        options: DiskWatchOptions,
     ) -> Result<()> {
-        self.inner
-            .start_watching_internal(true, poll_interval)
-            .await
+        self.inner.start_watching_internal(true, options).await
+    }
+
+    /// Invalidates the paths of this filesystem that changed at the start of the current step of
+    /// the [`replay`]. Call this after [`replay::Replay::next_step`].
+    ///
+    /// File contents and directory listings are served from the replay, links are still read
+    /// from disk.
+    pub async fn apply_replayed_file_changes(&self) {
+        let Some(replay) = replay::replay() else {
+            return;
+        };
+        let _lock = self.inner.invalidation_lock.write().await;
+        let paths = replay
+            .file_changes()
+            .map(PathBuf::from)
+            .filter(|path| path.starts_with(self.inner.root_path()))
+            .collect::<Vec<_>>();
+        if paths.is_empty() {
+            return;
+        }
+        replay::apply_file_changes(Vec::new, || {
+            {
+                let mut invalidator_map = self.inner.invalidator_map.lock().unwrap();
+                invalidate_path_and_children_execute(
+                    &self.inner,
+                    true,
+                    &mut invalidator_map,
+                    paths.iter().cloned(),
+                );
+            }
+            {
+                let mut dir_invalidator_map = self.inner.dir_invalidator_map.lock().unwrap();
+                invalidate_path_and_children_execute(
+                    &self.inner,
+                    true,
+                    &mut dir_invalidator_map,
+                    paths
+                        .iter()
+                        .filter_map(|path| path.parent())
+                        .map(Path::to_path_buf)
+                        .chain(paths.iter().cloned()),
+                );
+            }
+        });
     }
 
     pub fn stop_watching(&self) {
@@ -548,6 +609,16 @@ impl FileSystem for DiskFileSystem {
         let full_path = self.to_sys_path(fs_path)?;
         self.inner.register_read_invalidator(&full_path)?;
 
+        if let Some(replay) = replay::replay()
+            && let Some(content) = replay.file_content(&full_path.to_string_lossy())
+        {
+            let content = match content {
+                Some(content) => FileContent::new(File::from(&*content)),
+                None => FileContent::NotFound,
+            };
+            return Ok(content.cell());
+        }
+
         let _lock = self.inner.lock_path(&full_path).await;
         let content = match retry_blocking(full_path.clone(), |path: &Path| File::from_path(path))
             .concurrency_limited(&self.inner.semaphore)
@@ -565,6 +636,16 @@ impl FileSystem for DiskFileSystem {
                 bail!(anyhow!(e).context(format!("reading file {}", full_path.display())))
             }
         };
+        self.inner
+            .watcher
+            .record_content(self.inner.root_path(), &full_path, &content);
+        replay::record(|| ReplayEvent::FileRead {
+            path: full_path.to_string_lossy().into_owned(),
+            content: match &content {
+                FileContent::Content(file) => Some(file.content().to_bytes().into_owned()),
+                FileContent::NotFound => None,
+            },
+        });
         Ok(content.cell())
     }
 
@@ -574,6 +655,28 @@ impl FileSystem for DiskFileSystem {
         let full_path = self.to_sys_path(fs_path)?;
         self.inner.register_dir_invalidator(&full_path)?;
 
+        if let Some(replay) = replay::replay()
+            && let Some(entries) = replay.directory_entries(&full_path.to_string_lossy())
+        {
+            let Some(entries) = entries else {
+                return Ok(RawDirectoryContent::not_found());
+            };
+            return Ok(RawDirectoryContent::new(
+                entries
+                    .iter()
+                    .map(|(name, entry)| {
+                        let entry = match entry {
+                            ReplayDirectoryEntry::File => RawDirectoryEntry::File,
+                            ReplayDirectoryEntry::Directory => RawDirectoryEntry::Directory,
+                            ReplayDirectoryEntry::Symlink => RawDirectoryEntry::Symlink,
+                            ReplayDirectoryEntry::Other => RawDirectoryEntry::Other,
+                        };
+                        (RcStr::from(&**name), entry)
+                    })
+                    .collect(),
+            ));
+        }
+
         // we use the sync std function here as it's a lot faster (600%) in
         // node-file-trace
         let read_dir = match retry_blocking(full_path.clone(), |path| {
@@ -590,6 +693,10 @@ impl FileSystem for DiskFileSystem {
                     || e.kind() == ErrorKind::NotADirectory
                     || e.kind() == ErrorKind::InvalidFilename =>
             {
+                replay::record(|| ReplayEvent::DirectoryRead {
+                    path: full_path.to_string_lossy().into_owned(),
+                    entries: None,
+                });
                 return Ok(RawDirectoryContent::not_found());
             }
             Err(e) => {
@@ -597,7 +704,7 @@ impl FileSystem for DiskFileSystem {
             }
         };
 
-        let entries = read_dir
+        let entries: AutoMap<RcStr, RawDirectoryEntry> = read_dir
             .filter_map(|r| {
                 let e = match r {
                     Ok(e) => e,
@@ -620,6 +727,28 @@ impl FileSystem for DiskFileSystem {
             .collect::<Result<_>>()
             .with_context(|| format!("reading directory item in {}", full_path.display()))?;
 
+        replay::record(|| {
+            // The order of entries returned by the OS is not stable
+            let mut entries = entries
+                .iter()
+                .filter_map(|(name, entry)| {
+                    let entry = match entry {
+                        RawDirectoryEntry::File => ReplayDirectoryEntry::File,
+                        RawDirectoryEntry::Directory => ReplayDirectoryEntry::Directory,
+                        RawDirectoryEntry::Symlink => ReplayDirectoryEntry::Symlink,
+                        RawDirectoryEntry::Other => ReplayDirectoryEntry::Other,
+                        RawDirectoryEntry::Error => return None,
+                    };
+                    Some((name.to_string(), entry))
+                })
+                .collect::<Vec<_>>();
+            entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+            ReplayEvent::DirectoryRead {
+                path: full_path.to_string_lossy().into_owned(),
+                entries: Some(entries),
+            }
+        });
+
         Ok(RawDirectoryContent::new(entries))
     }
 
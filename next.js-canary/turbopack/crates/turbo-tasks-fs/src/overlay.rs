@@ -0,0 +1,286 @@
+use std::{mem::take, sync::Arc};
+
+use anyhow::{Result, bail};
+use auto_hash_map::AutoMap;
+use parking_lot::Mutex;
+use rustc_hash::{FxHashMap, FxHashSet};
+use turbo_rcstr::RcStr;
+use turbo_tasks::{
+    Invalidator, ResolvedVc, ValueToString, Vc, mark_session_dependent, mark_stateful,
+};
+
+use crate::{
+    FileContent, FileMeta, FileSystem, FileSystemPath, LinkContent, RawDirectoryContent,
+    RawDirectoryEntry, invalidation::OverlayChange, util::normalize_path,
+};
+
+/// A [FileSystem] that shadows individual files of a base [FileSystem] with in-memory contents,
+/// e.g. the unsaved buffers of an editor.
+///
+/// Setting or removing an entry only invalidates the tasks that read this file and the listings of
+/// its parent directories. Writes are passed through to the base [FileSystem], an entry for the
+/// same path keeps shadowing the written file.
+#[turbo_tasks::value(serialization = "none", cell = "new", eq = "manual")]
+pub struct OverlayFileSystem {
+    name: RcStr,
+    base: ResolvedVc<Box<dyn FileSystem>>,
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    inner: Arc<OverlayFileSystemInner>,
+}
+
+#[derive(Default)]
+struct OverlayFileSystemInner {
+    /// The contents by path relative to the root. [FileContent::NotFound] hides the file of the
+    /// base [FileSystem].
+    entries: Mutex<FxHashMap<RcStr, FileContent>>,
+    /// The tasks that read the content, link or metadata of a path.
+    invalidators: Mutex<FxHashMap<RcStr, FxHashSet<Invalidator>>>,
+    /// The tasks that read the listing of a directory.
+    dir_invalidators: Mutex<FxHashMap<RcStr, FxHashSet<Invalidator>>>,
+}
+
+impl OverlayFileSystemInner {
+    /// Registers the path as an invalidator for the current task, has to be called within a
+    /// turbo-tasks function. Returns the entry for the path, if any.
+    fn read_entry(&self, path: &RcStr) -> Option<FileContent> {
+        self.invalidators
+            .lock()
+            .entry(path.clone())
+            .or_default()
+            .insert(turbo_tasks::get_invalidator());
+        self.entries.lock().get(path).cloned()
+    }
+
+    /// Moves the registration of the current task from `path` to the listing of its parent
+    /// directory. Setting an entry invalidates the listings of its parents, so this is enough for
+    /// paths that don't exist in the base [FileSystem]. Resolving probes many of them, they would
+    /// otherwise keep an entry each.
+    fn register_missing(&self, path: &RcStr) {
+        let invalidator = turbo_tasks::get_invalidator();
+        {
+            let mut invalidators = self.invalidators.lock();
+            if let Some(path_invalidators) = invalidators.get_mut(path) {
+                path_invalidators.remove(&invalidator);
+                if path_invalidators.is_empty() {
+                    invalidators.remove(path);
+                }
+            }
+        }
+        let parent = path.rsplit_once('/').map_or("", |(parent, _)| parent);
+        self.dir_invalidators
+            .lock()
+            .entry(parent.into())
+            .or_default()
+            .insert(invalidator);
+    }
+
+    /// Invalidates all tasks that read the path or the listing of one of its parent directories.
+    fn invalidate(&self, path: &RcStr) {
+        let mut invalidators = self.invalidators.lock().remove(path).unwrap_or_default();
+        {
+            let mut dir_invalidators = self.dir_invalidators.lock();
+            let mut dir = path.as_str();
+            while !dir.is_empty() {
+                dir = dir.rsplit_once('/').map_or("", |(parent, _)| parent);
+                if let Some(dir_invalidators) = dir_invalidators.remove(dir) {
+                    invalidators.extend(dir_invalidators);
+                }
+            }
+        }
+        for invalidator in invalidators {
+            invalidator.invalidate_with_reason(OverlayChange { path: path.clone() });
+        }
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OverlayFileSystem {
+    /// Creates a new [OverlayFileSystem] without any entries on top of `base`.
+    #[turbo_tasks::function]
+    pub fn new(name: RcStr, base: ResolvedVc<Box<dyn FileSystem>>) -> Vc<Self> {
+        mark_stateful();
+        Self::cell(OverlayFileSystem {
+            name,
+            base,
+            inner: Default::default(),
+        })
+    }
+}
+
+impl OverlayFileSystem {
+    /// Shadows the file at `path` (relative to the root of the file system) with `content`.
+    /// [FileContent::NotFound] hides the file of the base [FileSystem].
+    pub fn set(&self, path: &str, content: FileContent) -> Result<()> {
+        let path = normalize_overlay_path(path)?;
+        {
+            let mut entries = self.inner.entries.lock();
+            if entries.get(&path) == Some(&content) {
+                return Ok(());
+            }
+            entries.insert(path.clone(), content);
+        }
+        self.inner.invalidate(&path);
+        Ok(())
+    }
+
+    /// Removes the entry for `path`, so the file of the base [FileSystem] is visible again.
+    /// Returns `false` when there was no entry.
+    pub fn remove(&self, path: &str) -> Result<bool> {
+        let path = normalize_overlay_path(path)?;
+        let removed = self.inner.entries.lock().remove(&path).is_some();
+        if removed {
+            self.inner.invalidate(&path);
+        }
+        Ok(removed)
+    }
+
+    /// Removes all entries.
+    pub fn clear(&self) {
+        let entries = take(&mut *self.inner.entries.lock());
+        for path in entries.into_keys() {
+            self.inner.invalidate(&path);
+        }
+    }
+
+    /// Returns the paths that currently have an entry.
+    pub fn paths(&self) -> Vec<RcStr> {
+        self.inner.entries.lock().keys().cloned().collect()
+    }
+
+    fn base_path(&self, fs_path: &FileSystemPath) -> FileSystemPath {
+        FileSystemPath::new_normalized(self.base, fs_path.path.clone())
+    }
+}
+
+fn normalize_overlay_path(path: &str) -> Result<RcStr> {
+    let Some(normalized) = normalize_path(path) else {
+        bail!("path {path} leaves the root of the overlay file system");
+    };
+    Ok(normalized.into())
+}
+
+#[turbo_tasks::value_impl]
+impl FileSystem for OverlayFileSystem {
+    #[turbo_tasks::function(fs)]
+    async fn read(&self, fs_path: FileSystemPath) -> Result<Vc<FileContent>> {
+        mark_session_dependent();
+        if let Some(content) = self.inner.read_entry(&fs_path.path) {
+            return Ok(content.cell());
+        }
+        let content = self.base_path(&fs_path).read();
+        if let FileContent::NotFound = &*content.await? {
+            self.inner.register_missing(&fs_path.path);
+        }
+        Ok(content)
+    }
+
+    #[turbo_tasks::function(fs)]
+    async fn read_link(&self, fs_path: FileSystemPath) -> Result<Vc<LinkContent>> {
+        mark_session_dependent();
+        if self.inner.read_entry(&fs_path.path).is_some() {
+            // Entries are regular files, not links
+            return Ok(LinkContent::NotFound.cell());
+        }
+        let content = self.base_path(&fs_path).read_link();
+        if let LinkContent::NotFound = &*content.await? {
+            self.inner.register_missing(&fs_path.path);
+        }
+        Ok(content)
+    }
+
+    #[turbo_tasks::function(fs)]
+    async fn raw_read_dir(&self, fs_path: FileSystemPath) -> Result<Vc<RawDirectoryContent>> {
+        mark_session_dependent();
+        self.inner
+            .dir_invalidators
+            .lock()
+            .entry(fs_path.path.clone())
+            .or_default()
+            .insert(turbo_tasks::get_invalidator());
+        let prefix = if fs_path.path.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", fs_path.path)
+        };
+        // Entries directly in the directory or in one of its subdirectories
+        let overlay_entries = self
+            .inner
+            .entries
+            .lock()
+            .iter()
+            .filter_map(|(path, content)| {
+                let rest = path.strip_prefix(&prefix)?;
+                Some(match rest.split_once('/') {
+                    Some((dir, _)) => {
+                        // Hidden files don't create directories
+                        let FileContent::Content(_) = content else {
+                            return None;
+                        };
+                        (RcStr::from(dir), Some(RawDirectoryEntry::Directory))
+                    }
+                    None => (
+                        RcStr::from(rest),
+                        match content {
+                            FileContent::Content(_) => Some(RawDirectoryEntry::File),
+                            FileContent::NotFound => None,
+                        },
+                    ),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let base_content = self.base_path(&fs_path).raw_read_dir();
+        if overlay_entries.is_empty() {
+            return Ok(base_content);
+        }
+        let mut entries = match &*base_content.await? {
+            RawDirectoryContent::Entries(entries) => entries.clone(),
+            RawDirectoryContent::NotFound => AutoMap::new(),
+        };
+        for (name, entry) in overlay_entries {
+            match entry {
+                Some(RawDirectoryEntry::Directory) => {
+                    entries.entry(name).or_insert(RawDirectoryEntry::Directory);
+                }
+                Some(entry) => {
+                    entries.insert(name, entry);
+                }
+                None => {
+                    entries.remove(&name);
+                }
+            }
+        }
+        if entries.is_empty() && !fs_path.path.is_empty() {
+            return Ok(RawDirectoryContent::not_found());
+        }
+        Ok(RawDirectoryContent::new(entries))
+    }
+
+    #[turbo_tasks::function(fs)]
+    fn write(&self, fs_path: FileSystemPath, content: Vc<FileContent>) -> Vc<()> {
+        self.base_path(&fs_path).write(content)
+    }
+
+    #[turbo_tasks::function(fs)]
+    fn write_link(&self, fs_path: FileSystemPath, target: Vc<LinkContent>) -> Vc<()> {
+        self.base_path(&fs_path).write_link(target)
+    }
+
+    #[turbo_tasks::function(fs)]
+    fn metadata(&self, fs_path: FileSystemPath) -> Result<Vc<FileMeta>> {
+        mark_session_dependent();
+        Ok(match self.inner.read_entry(&fs_path.path) {
+            Some(FileContent::Content(file)) => file.meta().clone().cell(),
+            Some(FileContent::NotFound) => bail!("path not found, can't read metadata"),
+            None => self.base_path(&fs_path).metadata(),
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for OverlayFileSystem {
+    #[turbo_tasks::function]
+    fn to_string(&self) -> Vc<RcStr> {
+        Vc::cell(self.name.clone())
+    }
+}
@@ -1,10 +1,13 @@
 use std::{
     any::Any,
-    env, fmt,
+    env, fmt, fs,
+    hash::Hash,
+    io::ErrorKind,
     mem::take,
     path::{Path, PathBuf},
     sync::{
         Arc, LazyLock, Mutex, MutexGuard,
+        atomic::{AtomicBool, Ordering},
         mpsc::{Receiver, TryRecvError, channel},
     },
     time::Duration,
@@ -17,19 +20,20 @@ use notify::{
     event::{MetadataKind, ModifyKind, RenameMode},
 };
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet};
 use serde::{Deserialize, Serialize};
 use tracing::instrument;
 use turbo_rcstr::RcStr;
 use turbo_tasks::{
-    FxIndexSet, InvalidationReason, InvalidationReasonKind, Invalidator, spawn_thread,
+    FxIndexSet, InvalidationReason, InvalidationReasonKind, Invalidator, replay, spawn_thread,
     util::StaticOrArc,
 };
+use turbo_tasks_hash::hash_xxh3_hash64;
 
 use crate::{
-    DiskFileSystemInner, format_absolute_fs_path,
+    DiskFileSystemInner, FileContent, format_absolute_fs_path,
     invalidation::{WatchChange, WatchStart},
-    invalidator_map::LockedInvalidatorMap,
+    invalidator_map::{LockedInvalidatorMap, WriteContent},
     path_map::OrderedPathMapExt,
 };
 
@@ -61,6 +65,23 @@ static WATCH_RECURSIVE_MODE: LazyLock<RecursiveMode> = LazyLock::new(|| {
     }
 });
 
+/// Options for watching a [`DiskFileSystem`][crate::DiskFileSystem].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DiskWatchOptions {
+    /// Enable polling at a certain interval if the native file watching doesn't work (e.g.
+    /// docker).
+    pub poll_interval: Option<Duration>,
+    /// Keep hashes of the contents of read files and skip the invalidation of a file when a change
+    /// event leaves its content unchanged, e.g. after a branch switch or a formatter rewrote the
+    /// file. When polling, files are compared by content instead of by modification time.
+    pub content_hashing: bool,
+    /// When watching starts, only invalidate reads of files whose content differs from the
+    /// recorded hash, instead of all reads. This picks up changes that happened while no events
+    /// were delivered, e.g. on network file systems or before a persistent cache was restored.
+    /// Requires `content_hashing`.
+    pub scan_on_startup: bool,
+}
+
 /// A thin wrapper around [`RecommendedWatcher`] and [`PollWatcher`].
 enum DiskWatcherInternal {
     Recommended(RecommendedWatcher),
@@ -83,6 +104,15 @@ pub(crate) struct DiskWatcher {
 
     #[serde(skip, default = "NonRecursiveDiskWatcherState::try_new")]
     pub(crate) non_recursive_state: Option<NonRecursiveDiskWatcherState>,
+
+    /// Whether the hashes of read files are recorded, see [`DiskWatchOptions::content_hashing`].
+    #[serde(skip)]
+    content_hashing: AtomicBool,
+
+    /// The hashes of the contents of read files. They are persisted with the cache, so
+    /// [`DiskWatchOptions::scan_on_startup`] can compare against the contents of a previous
+    /// session.
+    content_hashes: ContentHashes,
 }
 
 impl Default for DiskWatcher {
@@ -90,7 +120,136 @@ impl Default for DiskWatcher {
         Self {
             internal: Mutex::new(None),
             non_recursive_state: NonRecursiveDiskWatcherState::try_new(),
+            content_hashing: AtomicBool::new(false),
+            content_hashes: Default::default(),
+        }
+    }
+}
+
+/// The maximum number of files with a recorded hash. Files without a recorded hash are considered
+/// changed, so forgetting all hashes when the limit is reached only costs some invalidations.
+const MAX_CONTENT_HASHES: usize = 100_000;
+
+/// The hash of the content of a file and the tasks that read this content.
+#[derive(Serialize, Deserialize)]
+#[serde(bound(deserialize = "R: Deserialize<'de> + Eq + Hash"))]
+struct ContentHash<R> {
+    /// `None` is a file that was not found.
+    hash: Option<u64>,
+    readers: FxHashSet<R>,
+}
+
+/// The hashes of the contents of files by path relative to the root of the filesystem, so they
+/// stay valid when the cache is moved to a different root.
+#[derive(Serialize, Deserialize)]
+#[serde(bound(deserialize = "R: Deserialize<'de> + Eq + Hash"))]
+struct ContentHashes<R = Invalidator>(Mutex<FxHashMap<PathBuf, ContentHash<R>>>);
+
+impl<R> Default for ContentHashes<R> {
+    fn default() -> Self {
+        Self(Default::default())
+    }
+}
+
+impl<R: Eq + Hash> ContentHashes<R> {
+    /// Records that `reader` read the content with `hash` from `path`. Paths outside of `root` are
+    /// not recorded.
+    fn insert(&self, root: &Path, path: &Path, hash: Option<u64>, reader: R) {
+        let Ok(key) = path.strip_prefix(root) else {
+            return;
+        };
+        let mut hashes = self.0.lock().unwrap();
+        if hashes.len() >= MAX_CONTENT_HASHES && !hashes.contains_key(key) {
+            hashes.clear();
+        }
+        let entry = hashes
+            .entry(key.to_path_buf())
+            .or_insert_with(|| ContentHash {
+                hash,
+                readers: FxHashSet::default(),
+            });
+        if entry.hash != hash {
+            entry.hash = hash;
+            entry.readers.clear();
+        }
+        entry.readers.insert(reader);
+    }
+
+    /// Returns `true` when the file at `path` didn't change since its hash was recorded. Entries
+    /// of changed files are removed, they are recorded again when the file is read.
+    fn is_unchanged(&self, root: &Path, path: &Path) -> bool {
+        let Ok(key) = path.strip_prefix(root) else {
+            return false;
+        };
+        let Some(recorded) = self.0.lock().unwrap().get(key).map(|entry| entry.hash) else {
+            return false;
+        };
+        let current = match fs::read(path) {
+            Ok(content) => Some(hash_xxh3_hash64(&*content)),
+            Err(err) if err.kind() == ErrorKind::NotFound => None,
+            // e.g. the file was replaced by a directory
+            Err(_) => {
+                self.0.lock().unwrap().remove(key);
+                return false;
+            }
+        };
+        if current == recorded {
+            return true;
         }
+        self.0.lock().unwrap().remove(key);
+        false
+    }
+
+    /// Returns `true` when `reader` read the recorded content of `path`.
+    fn has_reader(&self, root: &Path, path: &Path, reader: &R) -> bool {
+        let Ok(key) = path.strip_prefix(root) else {
+            return false;
+        };
+        self.0
+            .lock()
+            .unwrap()
+            .get(key)
+            .is_some_and(|entry| entry.readers.contains(reader))
+    }
+}
+
+/// The invalidators of reads that are kept when their files are invalidated, see
+/// [`take_unchanged_reads`].
+type KeptReads = Vec<(PathBuf, Vec<(Invalidator, Option<WriteContent>)>)>;
+
+/// Takes the invalidators of tasks that read the content of the `unchanged` files out of the
+/// `invalidator_map`, so they are not invalidated with the files. Other reads of the files, e.g.
+/// of their metadata, are still invalidated. The kept invalidators need to be restored with
+/// [`restore_reads`] afterwards.
+fn take_unchanged_reads(
+    invalidator_map: &mut LockedInvalidatorMap,
+    content_hashes: &ContentHashes,
+    root: &Path,
+    unchanged: &[PathBuf],
+) -> KeptReads {
+    unchanged
+        .iter()
+        .filter_map(|path| {
+            let invalidators = invalidator_map.get_mut(path)?;
+            let kept = invalidators
+                .extract_if(|invalidator, write_content| {
+                    write_content.is_none() && content_hashes.has_reader(root, path, invalidator)
+                })
+                .collect::<Vec<_>>();
+            if invalidators.is_empty() {
+                invalidator_map.remove(path);
+            }
+            Some((path.clone(), kept))
+        })
+        .collect()
+}
+
+fn restore_reads(invalidator_map: &mut LockedInvalidatorMap, kept: KeptReads) {
+    for (path, invalidators) in kept {
+        invalidator_map
+            .entry(path)
+            .or_default()
+            .extend(invalidators);
     }
 }
 
@@ -217,6 +376,33 @@ impl DiskWatcher {
         Default::default()
     }
 
+    /// Records the hash of the content that was read from `path` by the current task, if content
+    /// hashing is enabled. Has to be called within a turbo-tasks function.
+    pub(crate) fn record_content(&self, root: &Path, path: &Path, content: &FileContent) {
+        if !self.content_hashing.load(Ordering::Relaxed) {
+            return;
+        }
+        let hash = match content {
+            FileContent::Content(file) => Some(hash_xxh3_hash64(&*file.content().to_bytes())),
+            FileContent::NotFound => None,
+        };
+        self.content_hashes
+            .insert(root, path, hash, turbo_tasks::get_invalidator());
+    }
+
+    /// Returns the files whose content didn't change since it was read.
+    fn unchanged_files<'a>(
+        &self,
+        root: &Path,
+        paths: impl IntoParallelIterator<Item = &'a PathBuf>,
+    ) -> Vec<PathBuf> {
+        paths
+            .into_par_iter()
+            .filter(|path| self.content_hashes.is_unchanged(root, path))
+            .cloned()
+            .collect()
+    }
+
     /// Create a watcher and start watching by creating `debounced` watcher
     /// via `full debouncer`
     ///
@@ -236,12 +422,14 @@ impl DiskWatcher {
         &self,
         fs_inner: Arc<DiskFileSystemInner>,
         report_invalidation_reason: bool,
-        poll_interval: Option<Duration>,
+        options: DiskWatchOptions,
     ) -> Result<()> {
         let mut internal_guard = self.internal.lock().unwrap();
         if internal_guard.is_some() {
             return Ok(());
         }
+        self.content_hashing
+            .store(options.content_hashing, Ordering::Relaxed);
 
         // Create a channel to receive the events.
         let (tx, rx) = channel();
@@ -251,8 +439,10 @@ impl DiskWatcher {
         // we should track and invalidate each part of a symlink chain ourselves in turbo-tasks-fs
         config.with_follow_symlinks(false);
 
-        let mut internal = if let Some(poll_interval) = poll_interval {
-            let config = config.with_poll_interval(poll_interval);
+        let mut internal = if let Some(poll_interval) = options.poll_interval {
+            let config = config
+                .with_poll_interval(poll_interval)
+                .with_compare_contents(options.content_hashing);
 
             DiskWatcherInternal::Polling(PollWatcher::new(tx, config)?)
         } else {
@@ -273,8 +463,21 @@ impl DiskWatcher {
         {
             let span = tracing::info_span!("invalidate filesystem");
             let _span = span.clone().entered();
-            let invalidator_map = take(&mut *fs_inner.invalidator_map.lock().unwrap());
+            let mut invalidator_map = take(&mut *fs_inner.invalidator_map.lock().unwrap());
             let dir_invalidator_map = take(&mut *fs_inner.dir_invalidator_map.lock().unwrap());
+            if options.content_hashing && options.scan_on_startup {
+                // Keep the reads of files that didn't change since they were read
+                let root = fs_inner.root_path();
+                let unchanged =
+                    self.unchanged_files(root, invalidator_map.keys().collect::<Vec<_>>());
+                let kept = take_unchanged_reads(
+                    &mut invalidator_map,
+                    &self.content_hashes,
+                    root,
+                    &unchanged,
+                );
+                restore_reads(&mut fs_inner.invalidator_map.lock().unwrap(), kept);
+            }
             let iter = invalidator_map
                 .into_par_iter()
                 .chain(dir_invalidator_map.into_par_iter());
@@ -543,37 +746,72 @@ impl DiskWatcher {
                 }
             }
 
+            // Changes that leave the content of a file unchanged don't need to invalidate the reads
+            // of its content
+            let unchanged = if self.content_hashing.load(Ordering::Relaxed) {
+                self.unchanged_files(
+                    inner.root_path(),
+                    batched_invalidate_path
+                        .iter()
+                        .chain(batched_invalidate_path_and_children.iter())
+                        .collect::<Vec<_>>(),
+                )
+            } else {
+                Vec::new()
+            };
+
+            // Collected before the paths are drained below
+            let changed_paths = replay::recorder().map(|_| {
+                batched_invalidate_path
+                    .iter()
+                    .chain(batched_invalidate_path_and_children.iter())
+                    .filter(|path| !unchanged.contains(*path))
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+            });
             let _lock = inner.invalidation_lock.blocking_write();
-            {
-                let mut invalidator_map = inner.invalidator_map.lock().unwrap();
-                invalidate_path(
-                    &inner,
-                    report_invalidation_reason,
-                    &mut invalidator_map,
-                    batched_invalidate_path.drain(),
-                );
-                invalidate_path_and_children_execute(
-                    &inner,
-                    report_invalidation_reason,
-                    &mut invalidator_map,
-                    batched_invalidate_path_and_children.drain(),
-                );
-            }
-            {
-                let mut dir_invalidator_map = inner.dir_invalidator_map.lock().unwrap();
-                invalidate_path(
-                    &inner,
-                    report_invalidation_reason,
-                    &mut dir_invalidator_map,
-                    batched_invalidate_path_dir.drain(),
-                );
-                invalidate_path_and_children_execute(
-                    &inner,
-                    report_invalidation_reason,
-                    &mut dir_invalidator_map,
-                    batched_invalidate_path_and_children_dir.drain(),
-                );
-            }
+            replay::apply_file_changes(
+                move || changed_paths.unwrap_or_default(),
+                || {
+                    {
+                        let mut invalidator_map = inner.invalidator_map.lock().unwrap();
+                        let kept = take_unchanged_reads(
+                            &mut invalidator_map,
+                            &self.content_hashes,
+                            inner.root_path(),
+                            &unchanged,
+                        );
+                        invalidate_path(
+                            &inner,
+                            report_invalidation_reason,
+                            &mut invalidator_map,
+                            batched_invalidate_path.drain(),
+                        );
+                        invalidate_path_and_children_execute(
+                            &inner,
+                            report_invalidation_reason,
+                            &mut invalidator_map,
+                            batched_invalidate_path_and_children.drain(),
+                        );
+                        restore_reads(&mut invalidator_map, kept);
+                    }
+                    {
+                        let mut dir_invalidator_map = inner.dir_invalidator_map.lock().unwrap();
+                        invalidate_path(
+                            &inner,
+                            report_invalidation_reason,
+                            &mut dir_invalidator_map,
+                            batched_invalidate_path_dir.drain(),
+                        );
+                        invalidate_path_and_children_execute(
+                            &inner,
+                            report_invalidation_reason,
+                            &mut dir_invalidator_map,
+                            batched_invalidate_path_and_children_dir.drain(),
+                        );
+                    }
+                },
+            );
         }
     }
 }
@@ -609,7 +847,7 @@ fn invalidate_path(
     }
 }
 
-fn invalidate_path_and_children_execute(
+pub(crate) fn invalidate_path_and_children_execute(
     inner: &DiskFileSystemInner,
     report_invalidation_reason: bool,
     invalidator_map: &mut LockedInvalidatorMap,
@@ -667,3 +905,84 @@ impl InvalidationReasonKind for InvalidateRescanKind {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use turbo_tasks_hash::hash_xxh3_hash64;
+
+    use super::{ContentHashes, MAX_CONTENT_HASHES};
+
+    #[test]
+    fn content_hashes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let file = root.join("file.txt");
+        let missing = root.join("missing.txt");
+        fs::write(&file, "content").unwrap();
+
+        let hashes = ContentHashes::<u32>::default();
+        // Files without a recorded hash are always considered changed
+        assert!(!hashes.is_unchanged(root, &file));
+
+        let content_hash = Some(hash_xxh3_hash64(b"content".as_slice()));
+        hashes.insert(root, &file, content_hash, 1);
+        hashes.insert(root, &missing, None, 2);
+        // Rewriting identical bytes is not a change
+        fs::write(&file, "content").unwrap();
+        assert!(hashes.is_unchanged(root, &file));
+        assert!(hashes.is_unchanged(root, &missing));
+        assert!(hashes.has_reader(root, &file, &1));
+        assert!(!hashes.has_reader(root, &file, &2));
+
+        // Readers of a different content are forgotten
+        hashes.insert(root, &file, Some(hash_xxh3_hash64(b"other".as_slice())), 3);
+        assert!(!hashes.has_reader(root, &file, &1));
+        hashes.insert(root, &file, content_hash, 1);
+
+        fs::write(&file, "changed").unwrap();
+        fs::write(&missing, "created").unwrap();
+        assert!(!hashes.is_unchanged(root, &file));
+        assert!(!hashes.is_unchanged(root, &missing));
+        // The entries are removed until the files are read again
+        fs::write(&file, "content").unwrap();
+        assert!(!hashes.is_unchanged(root, &file));
+        assert!(!hashes.has_reader(root, &file, &1));
+    }
+
+    #[test]
+    fn content_hashes_are_bounded() {
+        let dir = tempfile::tempdir().unwrap();
+        let hashes = ContentHashes::<u32>::default();
+        for i in 0..=MAX_CONTENT_HASHES {
+            hashes.insert(dir.path(), &dir.path().join(i.to_string()), None, 0);
+        }
+        assert_eq!(hashes.0.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn content_hashes_are_relative_to_the_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+        let moved = dir.path().join("moved");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(&moved).unwrap();
+        fs::write(root.join("file.txt"), "content").unwrap();
+        fs::write(moved.join("file.txt"), "content").unwrap();
+
+        let hashes = ContentHashes::<u32>::default();
+        let content_hash = Some(hash_xxh3_hash64(b"content".as_slice()));
+        hashes.insert(&root, &root.join("file.txt"), content_hash, 1);
+        // Paths outside of the root are not recorded
+        hashes.insert(&root, &moved.join("file.txt"), content_hash, 1);
+        assert_eq!(hashes.0.lock().unwrap().len(), 1);
+
+        // The hashes are persisted and stay valid for a different root
+        let serialized = serde_json::to_string(&hashes).unwrap();
+        assert!(!serialized.contains(&*root.to_string_lossy()));
+        let restored: ContentHashes<u32> = serde_json::from_str(&serialized).unwrap();
+        assert!(restored.is_unchanged(&moved, &moved.join("file.txt")));
+        assert!(restored.has_reader(&moved, &moved.join("file.txt"), &1));
+    }
+}
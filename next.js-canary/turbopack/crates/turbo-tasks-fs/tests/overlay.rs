@@ -0,0 +1,154 @@
+#![feature(arbitrary_self_types)]
+#![feature(arbitrary_self_types_pointers)]
+#![allow(clippy::needless_return)] // tokio macro-generated code doesn't respect this
+
+use std::{
+    fs,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use anyhow::Result;
+use turbo_rcstr::{RcStr, rcstr};
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{
+    DiskFileSystem, File, FileContent, FileSystem, FileSystemPath, RawDirectoryContent,
+    overlay::OverlayFileSystem,
+};
+use turbo_tasks_testing::{Registration, register, run_without_cache_check};
+
+static REGISTRATION: Registration = register!(turbo_tasks_fs::register);
+
+static OTHER_READS: AtomicUsize = AtomicUsize::new(0);
+
+#[tokio::test(flavor = "multi_thread")]
+async fn overlay_shadows_files() {
+    run_without_cache_check(&REGISTRATION, async move {
+        let project = tempfile::tempdir()?;
+        let project_root = project.path().canonicalize()?;
+        fs::write(project_root.join("file.txt"), "on disk")?;
+        fs::write(project_root.join("other.txt"), "other")?;
+
+        let disk_fs = DiskFileSystem::new(
+            rcstr!("project"),
+            RcStr::from(project_root.to_str().unwrap()),
+        );
+        let overlay = OverlayFileSystem::new(rcstr!("overlay"), Vc::upcast(disk_fs));
+        let root = Vc::upcast::<Box<dyn FileSystem>>(overlay)
+            .root()
+            .owned()
+            .await?;
+        let file = root.join("file.txt")?;
+        let other = root.join("other.txt")?;
+
+        assert_eq!(
+            read_text(file.clone())
+                .strongly_consistent()
+                .await?
+                .as_str(),
+            "on disk"
+        );
+        assert_eq!(
+            read_text(other.clone())
+                .strongly_consistent()
+                .await?
+                .as_str(),
+            "other"
+        );
+        assert_eq!(OTHER_READS.load(Ordering::SeqCst), 1);
+
+        overlay
+            .await?
+            .set("file.txt", File::from("unsaved").into())?;
+        overlay
+            .await?
+            .set("src/new.txt", File::from("new").into())?;
+        assert_eq!(
+            read_text(file.clone())
+                .strongly_consistent()
+                .await?
+                .as_str(),
+            "unsaved"
+        );
+        assert_eq!(
+            read_text(root.join("src/new.txt")?)
+                .strongly_consistent()
+                .await?
+                .as_str(),
+            "new"
+        );
+        // Only the dependents of the overlaid file are recomputed
+        assert_eq!(
+            read_text(other.clone())
+                .strongly_consistent()
+                .await?
+                .as_str(),
+            "other"
+        );
+        assert_eq!(OTHER_READS.load(Ordering::SeqCst), 1);
+
+        // Paths that are missing in the base file system are invalidated by new entries as well
+        let missing = root.join("missing.txt")?;
+        assert_eq!(
+            read_text(missing.clone())
+                .strongly_consistent()
+                .await?
+                .as_str(),
+            "<not found>"
+        );
+        overlay
+            .await?
+            .set("missing.txt", File::from("created").into())?;
+        assert_eq!(
+            read_text(missing.clone())
+                .strongly_consistent()
+                .await?
+                .as_str(),
+            "created"
+        );
+        assert!(overlay.await?.remove("missing.txt")?);
+
+        overlay.await?.set("other.txt", FileContent::NotFound)?;
+        assert_eq!(read_dir(root.clone()).await?, vec!["file.txt", "src"]);
+        assert_eq!(
+            read_text(other.clone())
+                .strongly_consistent()
+                .await?
+                .as_str(),
+            "<not found>"
+        );
+
+        overlay.await?.clear();
+        assert_eq!(
+            read_text(file).strongly_consistent().await?.as_str(),
+            "on disk"
+        );
+        assert_eq!(read_dir(root).await?, vec!["file.txt", "other.txt"]);
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+async fn read_dir(path: FileSystemPath) -> Result<Vec<String>> {
+    let RawDirectoryContent::Entries(entries) = &*path.raw_read_dir().strongly_consistent().await?
+    else {
+        panic!("directory not found");
+    };
+    let mut names = entries
+        .keys()
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+    names.sort();
+    Ok(names)
+}
+
+#[turbo_tasks::function]
+async fn read_text(path: FileSystemPath) -> Result<Vc<RcStr>> {
+    if path.path == "other.txt" {
+        OTHER_READS.fetch_add(1, Ordering::SeqCst);
+    }
+    Ok(Vc::cell(match &*path.read().await? {
+        FileContent::Content(file) => file.content().to_str()?.as_ref().into(),
+        FileContent::NotFound => rcstr!("<not found>"),
+    }))
+}
@@ -0,0 +1,124 @@
+use std::{fs, path::Path, sync::Arc};
+
+use anyhow::Result;
+use turbo_rcstr::RcStr;
+use turbo_tasks::{
+    TurboTasks, Vc,
+    replay::{self, Replay},
+};
+use turbo_tasks_backend::{
+    BackendOptions, NoopBackingStorage, TurboTasksBackend, noop_backing_storage,
+};
+use turbo_tasks_fs::{DirectoryContent, DiskFileSystem, FileContent, FileSystem};
+
+type TestTurboTasks = Arc<TurboTasks<TurboTasksBackend<NoopBackingStorage>>>;
+
+fn turbo_tasks() -> TestTurboTasks {
+    TurboTasks::new(TurboTasksBackend::new(
+        BackendOptions::default(),
+        noop_backing_storage(),
+    ))
+}
+
+/// Reads `file.txt` and lists the root directory.
+async fn read(tt: &TestTurboTasks, root: &Path) -> Result<(Option<String>, Vec<String>)> {
+    let root = RcStr::from(root.to_str().unwrap());
+    tt.run_once(async move {
+        let fs = Vc::upcast::<Box<dyn FileSystem>>(DiskFileSystem::new("test".into(), root));
+        let root = fs.root().owned().await?;
+        let content = root.join("file.txt")?.read().strongly_consistent().await?;
+        let content = match &*content {
+            FileContent::Content(file) => Some(file.content().to_str()?.into_owned()),
+            FileContent::NotFound => None,
+        };
+        let entries = match &*root.read_dir().strongly_consistent().await? {
+            DirectoryContent::Entries(entries) => {
+                let mut entries = entries.keys().map(|name| name.to_string()).collect::<Vec<_>>();
+                entries.sort();
+                entries
+            }
+            DirectoryContent::NotFound => Vec::new(),
+        };
+        Ok((content, entries))
+    })
+    .await
+}
+
+/// Invalidates the filesystem, like a watcher does when files change.
+async fn invalidate(tt: &TestTurboTasks, root: &Path) -> Result<()> {
+    let root = RcStr::from(root.to_str().unwrap());
+    tt.run_once(async move {
+        DiskFileSystem::new("test".into(), root).await?.invalidate();
+        Ok(())
+    })
+    .await
+}
+
+async fn apply_replayed_file_changes(tt: &TestTurboTasks, root: &Path) -> Result<()> {
+    let root = RcStr::from(root.to_str().unwrap());
+    tt.run_once(async move {
+        DiskFileSystem::new("test".into(), root)
+            .await?
+            .apply_replayed_file_changes()
+            .await;
+        Ok(())
+    })
+    .await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn record_and_replay() -> Result<()> {
+    turbo_tasks_fs::register();
+
+    let project = tempfile::tempdir()?;
+    let root = project.path().canonicalize()?;
+    let log_dir = tempfile::tempdir()?;
+    let log_path = log_dir.path().join("replay.log");
+
+    fs::write(root.join("file.txt"), "first")?;
+    replay::start_recording(&log_path)?;
+    let tt = turbo_tasks();
+    assert_eq!(
+        read(&tt, &root).await?,
+        (Some("first".into()), vec!["file.txt".into()])
+    );
+    fs::write(root.join("file.txt"), "second")?;
+    fs::write(root.join("other.txt"), "other")?;
+    invalidate(&tt, &root).await?;
+    assert_eq!(
+        read(&tt, &root).await?,
+        (
+            Some("second".into()),
+            vec!["file.txt".into(), "other.txt".into()]
+        )
+    );
+    replay::stop()?;
+
+    // The replay serves the recorded contents and listings instead of the ones on disk
+    fs::write(root.join("file.txt"), "on disk")?;
+    fs::remove_file(root.join("other.txt"))?;
+    fs::write(root.join("extra.txt"), "extra")?;
+    let replay = replay::start_replay(Replay::load(&log_path)?)?;
+    assert_eq!(replay.step_count(), 2);
+    let tt = turbo_tasks();
+    assert_eq!(
+        read(&tt, &root).await?,
+        (Some("first".into()), vec!["file.txt".into()])
+    );
+
+    // The content of the second step must not leak into the first one
+    assert!(replay.next_step());
+    apply_replayed_file_changes(&tt, &root).await?;
+    replay.invalidate(&*tt)?;
+    assert_eq!(
+        read(&tt, &root).await?,
+        (
+            Some("second".into()),
+            vec!["file.txt".into(), "other.txt".into()]
+        )
+    );
+    assert!(!replay.next_step());
+    replay.verify()?;
+    replay::stop()?;
+    Ok(())
+}
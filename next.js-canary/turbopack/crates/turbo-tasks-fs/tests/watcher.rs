@@ -0,0 +1,174 @@
+#![feature(arbitrary_self_types)]
+#![feature(arbitrary_self_types_pointers)]
+#![allow(clippy::needless_return)] // tokio macro-generated code doesn't respect this
+
+use std::{
+    fs,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use turbo_rcstr::{RcStr, rcstr};
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{DiskFileSystem, DiskWatchOptions, FileContent, FileSystem, FileSystemPath};
+use turbo_tasks_testing::{Registration, register, run_without_cache_check};
+
+static REGISTRATION: Registration = register!(turbo_tasks_fs::register);
+
+static READS: AtomicUsize = AtomicUsize::new(0);
+static STARTUP_READS: AtomicUsize = AtomicUsize::new(0);
+
+/// Long enough for the watcher to deliver the events of a write.
+const WATCH_DELAY: Duration = Duration::from_millis(500);
+
+#[tokio::test(flavor = "multi_thread")]
+async fn content_hashing_skips_unchanged_files() {
+    run_without_cache_check(&REGISTRATION, async move {
+        let project = tempfile::tempdir()?;
+        let project_root = project.path().canonicalize()?;
+        let file_path = project_root.join("file.txt");
+        fs::write(&file_path, "content")?;
+
+        let disk_fs = DiskFileSystem::new(
+            rcstr!("project"),
+            RcStr::from(project_root.to_str().unwrap()),
+        );
+        disk_fs
+            .await?
+            .start_watching(DiskWatchOptions {
+                content_hashing: true,
+                ..Default::default()
+            })
+            .await?;
+        let file = Vc::upcast::<Box<dyn FileSystem>>(disk_fs)
+            .root()
+            .owned()
+            .await?
+            .join("file.txt")?;
+
+        assert_eq!(
+            read_text(file.clone())
+                .strongly_consistent()
+                .await?
+                .as_str(),
+            "content"
+        );
+        assert_eq!(READS.load(Ordering::SeqCst), 1);
+
+        // Rewriting identical bytes doesn't invalidate the read
+        fs::write(&file_path, "content")?;
+        tokio::time::sleep(WATCH_DELAY).await;
+        assert_eq!(
+            read_text(file.clone())
+                .strongly_consistent()
+                .await?
+                .as_str(),
+            "content"
+        );
+        assert_eq!(READS.load(Ordering::SeqCst), 1);
+
+        fs::write(&file_path, "changed")?;
+        let start = Instant::now();
+        while read_text(file.clone())
+            .strongly_consistent()
+            .await?
+            .as_str()
+            != "changed"
+        {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "change was not picked up"
+            );
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(READS.load(Ordering::SeqCst), 2);
+
+        disk_fs.await?.stop_watching();
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn scan_on_startup_skips_unchanged_files() {
+    run_without_cache_check(&REGISTRATION, async move {
+        let project = tempfile::tempdir()?;
+        let project_root = project.path().canonicalize()?;
+        fs::write(project_root.join("unchanged.txt"), "content")?;
+        fs::write(project_root.join("changed.txt"), "content")?;
+
+        let disk_fs = DiskFileSystem::new(
+            rcstr!("project"),
+            RcStr::from(project_root.to_str().unwrap()),
+        );
+        let options = DiskWatchOptions {
+            content_hashing: true,
+            scan_on_startup: true,
+            ..Default::default()
+        };
+        disk_fs.await?.start_watching(options).await?;
+        let root = Vc::upcast::<Box<dyn FileSystem>>(disk_fs)
+            .root()
+            .owned()
+            .await?;
+        let unchanged = root.join("unchanged.txt")?;
+        let changed = root.join("changed.txt")?;
+
+        read_text_on_startup(unchanged.clone())
+            .strongly_consistent()
+            .await?;
+        read_text_on_startup(changed.clone())
+            .strongly_consistent()
+            .await?;
+        assert_eq!(STARTUP_READS.load(Ordering::SeqCst), 2);
+
+        // Changes while not watching don't deliver any events
+        disk_fs.await?.stop_watching();
+        fs::write(project_root.join("unchanged.txt"), "content")?;
+        fs::write(project_root.join("changed.txt"), "changed")?;
+
+        // Only the reads of changed files are invalidated when watching starts again
+        disk_fs.await?.start_watching(options).await?;
+        assert_eq!(
+            read_text_on_startup(unchanged)
+                .strongly_consistent()
+                .await?
+                .as_str(),
+            "content"
+        );
+        assert_eq!(
+            read_text_on_startup(changed)
+                .strongly_consistent()
+                .await?
+                .as_str(),
+            "changed"
+        );
+        assert_eq!(STARTUP_READS.load(Ordering::SeqCst), 3);
+
+        disk_fs.await?.stop_watching();
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[turbo_tasks::function]
+async fn read_text(path: FileSystemPath) -> Result<Vc<RcStr>> {
+    READS.fetch_add(1, Ordering::SeqCst);
+    Ok(Vc::cell(text(&path).await?))
+}
+
+#[turbo_tasks::function]
+async fn read_text_on_startup(path: FileSystemPath) -> Result<Vc<RcStr>> {
+    STARTUP_READS.fetch_add(1, Ordering::SeqCst);
+    Ok(Vc::cell(text(&path).await?))
+}
+
+async fn text(path: &FileSystemPath) -> Result<RcStr> {
+    Ok(match &*path.read().await? {
+        FileContent::Content(file) => file.content().to_str()?.as_ref().into(),
+        FileContent::NotFound => rcstr!("<not found>"),
+    })
+}
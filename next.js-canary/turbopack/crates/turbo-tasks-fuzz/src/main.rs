@@ -91,7 +91,7 @@ async fn fuzz_fs_watcher(args: FsWatcher) -> anyhow::Result<()> {
             .await?;
         create_directory_tree(&mut FxHashSet::default(), &fs_root, args.depth, args.width)?;
 
-        project_fs.await?.start_watching(None).await?;
+        project_fs.await?.start_watching(Default::default()).await?;
 
         let read_all_paths_op =
             read_all_paths_operation(invalidations.clone(), project_root, args.depth, args.width);
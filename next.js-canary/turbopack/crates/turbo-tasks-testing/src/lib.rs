@@ -19,6 +19,7 @@ use turbo_tasks::{
     ReadConsistency, TaskId, TaskPersistence, TraitTypeId, TurboTasksApi, TurboTasksCallApi,
     backend::{CellContent, TaskCollectiblesMap, TypedCellContent},
     event::{Event, EventListener},
+    invalidation_chain::InvalidationChainsApi,
     message_queue::CompilationEvent,
     test_helpers::with_turbo_tasks_for_testing,
     util::{SharedError, StaticOrArc},
@@ -36,6 +37,8 @@ pub struct VcStorage {
     this: Weak<Self>,
     cells: Mutex<FxHashMap<(TaskId, CellId), CellContent>>,
     tasks: Mutex<Vec<Task>>,
+    /// Never enabled by the testing storage itself, there is no backend that records chains.
+    invalidation_chains: InvalidationChainsApi,
 }
 
 impl VcStorage {
@@ -306,6 +309,10 @@ impl TurboTasksApi for VcStorage {
         unimplemented!()
     }
 
+    fn invalidation_chains(&self) -> &InvalidationChainsApi {
+        &self.invalidation_chains
+    }
+
     fn stop_and_wait(&self) -> std::pin::Pin<Box<dyn Future<Output = ()> + Send + 'static>> {
         Box::pin(async {})
     }
@@ -21,6 +21,7 @@ use crate::{
     RawVc, ReadCellOptions, ReadRef, SharedReference, TaskId, TaskIdSet, TraitRef, TraitTypeId,
     TurboTasksPanic, ValueTypeId, VcRead, VcValueTrait, VcValueType,
     event::EventListener,
+    invalidation_chain::InvalidationChainsApi,
     macro_helpers::NativeFunction,
     magic_any::MagicAny,
     manager::{ReadConsistency, TurboTasksBackendApi},
@@ -727,4 +728,6 @@ pub trait Backend: Sync + Send {
     fn dispose_root_task(&self, task: TaskId, turbo_tasks: &dyn TurboTasksBackendApi<Self>);
 
     fn task_statistics(&self) -> &TaskStatisticsApi;
+
+    fn invalidation_chains(&self) -> &InvalidationChainsApi;
 }
@@ -0,0 +1,292 @@
+use std::{
+    collections::VecDeque,
+    fmt::{self, Display},
+    mem::take,
+    sync::{
+        Arc, OnceLock,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+
+use crate::{FxDashMap, TaskId};
+
+/// The maximum number of re-executions kept for [`InvalidationChains::take_recent_executions`].
+/// Older entries are dropped when nobody takes them.
+const MAX_RECENT_EXECUTIONS: usize = 100_000;
+
+/// The maximum number of tasks whose last chain is kept for [`InvalidationChains::get`] and to
+/// extend chains through them. When exceeded, the older half is dropped. Tasks that executed long
+/// ago are unlikely to invalidate anything, their chains continue with an unknown root instead.
+const MAX_EXECUTIONS: u64 = 100_000;
+
+/// An API for optionally recording why tasks are re-executed.
+///
+/// When enabled, the backend records the chain of changes that led to every re-execution of a
+/// task: the explicit invalidation (e.g. a file watcher event) that started it and the tasks it
+/// passed through.
+#[derive(Default)]
+pub struct InvalidationChainsApi {
+    inner: OnceLock<Arc<InvalidationChains>>,
+}
+
+impl InvalidationChainsApi {
+    pub fn enable(&self) -> &Arc<InvalidationChains> {
+        self.inner.get_or_init(Default::default)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.inner.get().is_some()
+    }
+
+    // Calls `func` if recording has been enabled (via
+    // [`InvalidationChainsApi::enable`]).
+    pub fn map<T>(&self, func: impl FnOnce(&Arc<InvalidationChains>) -> T) -> Option<T> {
+        self.get().map(func)
+    }
+
+    // Returns the recorded chains if recording has been enabled (via
+    // [`InvalidationChainsApi::enable`]).
+    pub fn get(&self) -> Option<&Arc<InvalidationChains>> {
+        self.inner.get()
+    }
+}
+
+/// What started an invalidation chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum InvalidationRoot {
+    /// An explicit invalidation with a reason, e.g. a file watcher event.
+    Reason(String),
+    /// A change of collectibles. The task that emitted them is not tracked.
+    CollectiblesChange,
+    /// An explicit invalidation without a reason, or a change made by a task that was not
+    /// executed because of an invalidation (e.g. its first execution).
+    Unknown,
+}
+
+impl Display for InvalidationRoot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidationRoot::Reason(reason) => write!(f, "{reason}"),
+            InvalidationRoot::CollectiblesChange => write!(f, "collectibles changed"),
+            InvalidationRoot::Unknown => write!(f, "unknown cause"),
+        }
+    }
+}
+
+/// The kind of data of a task that changed and invalidated the tasks depending on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum DependencyChange {
+    Output,
+    Cell,
+}
+
+impl DependencyChange {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DependencyChange::Output => "output",
+            DependencyChange::Cell => "cell",
+        }
+    }
+}
+
+/// A task an invalidation passed through. The task was re-executed and changed data the next task
+/// in the chain depends on.
+#[derive(Debug, Clone, Serialize)]
+pub struct InvalidationStep {
+    pub task: TaskId,
+    pub description: String,
+    pub change: DependencyChange,
+}
+
+/// The chain of changes that led to the re-execution of a task.
+#[derive(Debug, Clone, Serialize)]
+pub struct InvalidationChain {
+    pub root: InvalidationRoot,
+    /// The tasks the invalidation passed through, starting at the root. The re-executed task
+    /// itself is not included.
+    pub steps: Vec<InvalidationStep>,
+}
+
+impl Display for InvalidationChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.root)?;
+        for step in &self.steps {
+            write!(
+                f,
+                "\n  -> {} ({} changed)",
+                step.description,
+                step.change.as_str()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A re-execution of a task that was caused by an invalidation.
+#[derive(Debug, Clone, Serialize)]
+pub struct InvalidatedExecution {
+    pub task: TaskId,
+    pub description: String,
+    pub chain: Arc<InvalidationChain>,
+}
+
+/// The enabled state of [`InvalidationChainsApi`].
+#[derive(Default)]
+pub struct InvalidationChains {
+    /// Chains of tasks that were invalidated, but didn't start executing yet. The first
+    /// invalidation wins.
+    pending: FxDashMap<TaskId, Arc<InvalidationChain>>,
+    /// The chain of the last execution of each task, together with the sequence number of the
+    /// execution.
+    executions: FxDashMap<TaskId, (u64, Arc<InvalidationChain>)>,
+    next_execution: AtomicU64,
+    recent_executions: Mutex<VecDeque<InvalidatedExecution>>,
+}
+
+impl InvalidationChains {
+    /// Records an explicit invalidation of a task.
+    pub fn record_invalidation(&self, task: TaskId, root: InvalidationRoot) {
+        self.pending.entry(task).or_insert_with(|| {
+            Arc::new(InvalidationChain {
+                root,
+                steps: Vec::new(),
+            })
+        });
+    }
+
+    /// Records that `tasks` were invalidated because data of the executing task `source` changed.
+    pub fn record_dependency_change(
+        &self,
+        source: TaskId,
+        source_description: impl FnOnce() -> String,
+        change: DependencyChange,
+        tasks: impl IntoIterator<Item = TaskId>,
+    ) {
+        let mut tasks = tasks.into_iter().peekable();
+        if tasks.peek().is_none() {
+            return;
+        }
+        let (root, mut steps) = match self.executions.get(&source) {
+            Some(entry) => (entry.1.root.clone(), entry.1.steps.clone()),
+            None => (InvalidationRoot::Unknown, Vec::new()),
+        };
+        steps.push(InvalidationStep {
+            task: source,
+            description: source_description(),
+            change,
+        });
+        let chain = Arc::new(InvalidationChain { root, steps });
+        for task in tasks {
+            self.pending.entry(task).or_insert_with(|| chain.clone());
+        }
+    }
+
+    /// Records that a task started executing. Returns the chain that caused the execution, if it
+    /// was caused by an invalidation.
+    pub fn start_execution(
+        &self,
+        task: TaskId,
+        description: impl FnOnce() -> String,
+    ) -> Option<Arc<InvalidationChain>> {
+        let Some((_, chain)) = self.pending.remove(&task) else {
+            self.executions.remove(&task);
+            return None;
+        };
+        let execution = self.next_execution.fetch_add(1, Ordering::Relaxed);
+        self.executions.insert(task, (execution, chain.clone()));
+        if self.executions.len() as u64 > MAX_EXECUTIONS {
+            let oldest_kept = execution.saturating_sub(MAX_EXECUTIONS / 2);
+            self.executions
+                .retain(|_, (execution, _)| *execution >= oldest_kept);
+        }
+        let description = description();
+        let mut recent_executions = self.recent_executions.lock();
+        if recent_executions.len() >= MAX_RECENT_EXECUTIONS {
+            recent_executions.pop_front();
+        }
+        recent_executions.push_back(InvalidatedExecution {
+            task,
+            description,
+            chain: chain.clone(),
+        });
+        Some(chain)
+    }
+
+    /// Returns the chain that caused the last execution of `task`, or `None` when the task was not
+    /// re-executed because of an invalidation.
+    pub fn get(&self, task: TaskId) -> Option<Arc<InvalidationChain>> {
+        self.executions.get(&task).map(|entry| entry.1.clone())
+    }
+
+    /// Returns all re-executions caused by invalidations since the last call, in order of their
+    /// start.
+    pub fn take_recent_executions(&self) -> Vec<InvalidatedExecution> {
+        take(&mut *self.recent_executions.lock()).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: u32) -> TaskId {
+        TaskId::try_from(id).unwrap()
+    }
+
+    #[test]
+    fn chain_through_tasks() {
+        let chains = InvalidationChains::default();
+        chains.record_invalidation(
+            task(1),
+            InvalidationRoot::Reason("file changed".to_string()),
+        );
+        // A later invalidation doesn't replace the first one
+        chains.record_invalidation(task(1), InvalidationRoot::Unknown);
+        chains.start_execution(task(1), || "read".to_string());
+        chains.record_dependency_change(
+            task(1),
+            || "read".to_string(),
+            DependencyChange::Cell,
+            [task(2)],
+        );
+        chains.start_execution(task(2), || "parse".to_string());
+        chains.record_dependency_change(
+            task(2),
+            || "parse".to_string(),
+            DependencyChange::Output,
+            [task(3)],
+        );
+        chains.start_execution(task(3), || "transform".to_string());
+
+        let chain = chains.get(task(3)).unwrap();
+        assert_eq!(
+            chain.to_string(),
+            "file changed\n  -> read (cell changed)\n  -> parse (output changed)"
+        );
+        let recent = chains.take_recent_executions();
+        assert_eq!(
+            recent.iter().map(|e| e.task).collect::<Vec<_>>(),
+            vec![task(1), task(2), task(3)]
+        );
+        assert!(chains.take_recent_executions().is_empty());
+
+        // An execution that was not caused by an invalidation clears the chain
+        assert!(chains.start_execution(task(3), || unreachable!()).is_none());
+        assert!(chains.get(task(3)).is_none());
+    }
+
+    #[test]
+    fn prune_executions() {
+        let chains = InvalidationChains::default();
+        for id in 1..=(MAX_EXECUTIONS as u32 + 1) {
+            chains.record_invalidation(task(id), InvalidationRoot::Unknown);
+            chains.start_execution(task(id), String::new);
+        }
+        assert!(chains.executions.len() as u64 <= MAX_EXECUTIONS / 2 + 1);
+        assert!(chains.get(task(1)).is_none());
+        assert!(chains.get(task(MAX_EXECUTIONS as u32 + 1)).is_some());
+    }
+}
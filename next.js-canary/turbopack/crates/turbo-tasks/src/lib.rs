@@ -51,6 +51,7 @@ pub mod graph;
 mod id;
 mod id_factory;
 mod invalidation;
+pub mod invalidation_chain;
 mod join_iter_ext;
 mod key_value_pair;
 #[doc(hidden)]
@@ -70,6 +71,7 @@ mod raw_vc;
 mod read_options;
 mod read_ref;
 pub mod registry;
+pub mod replay;
 mod scope;
 mod serialization_invalidation;
 pub mod small_duration;
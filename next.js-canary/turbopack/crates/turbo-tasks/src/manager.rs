@@ -34,11 +34,12 @@ use crate::{
     event::{Event, EventListener},
     id::{BackendJobId, ExecutionId, LocalTaskId, TRANSIENT_TASK_BIT, TraitTypeId},
     id_factory::IdFactoryWithReuse,
+    invalidation_chain::{InvalidationChainsApi, InvalidationRoot},
     macro_helpers::NativeFunction,
     magic_any::MagicAny,
     message_queue::{CompilationEvent, CompilationEventQueue},
     raw_vc::{CellId, RawVc},
-    registry,
+    registry, replay,
     serialization_invalidation::SerializationInvalidator,
     task::local_task::{LocalTask, LocalTaskSpec, LocalTaskType},
     task_statistics::TaskStatisticsApi,
@@ -197,6 +198,9 @@ pub trait TurboTasksApi: TurboTasksCallApi + Sync + Send {
 
     fn task_statistics(&self) -> &TaskStatisticsApi;
 
+    /// Records why tasks are re-executed once enabled. See [`InvalidationChainsApi`].
+    fn invalidation_chains(&self) -> &InvalidationChainsApi;
+
     fn stop_and_wait(&self) -> Pin<Box<dyn Future<Output = ()> + Send>>;
 
     fn subscribe_to_compilation_events(
@@ -716,6 +720,9 @@ impl<B: Backend + 'static> TurboTasks<B> {
                     else {
                         return false;
                     };
+                    replay::record_execution(task_id, || {
+                        this.backend.get_task_description(task_id)
+                    });
 
                     async {
                         let (result, duration, memory_usage) = CaptureFuture::new(future).await;
@@ -1250,11 +1257,23 @@ impl<B: Backend + 'static> TurboTasksCallApi for TurboTasks<B> {
 impl<B: Backend + 'static> TurboTasksApi for TurboTasks<B> {
     #[instrument(level = Level::INFO, skip_all, name = "invalidate")]
     fn invalidate(&self, task: TaskId) {
+        if CURRENT_TASK_STATE.try_with(|_| {}).is_err() {
+            replay::record_invalidation(|| self.backend.get_task_description(task), None);
+        }
         self.backend.invalidate_task(task, self);
     }
 
     #[instrument(level = Level::INFO, skip_all, name = "invalidate", fields(name = display(&reason)))]
     fn invalidate_with_reason(&self, task: TaskId, reason: StaticOrArc<dyn InvalidationReason>) {
+        if CURRENT_TASK_STATE.try_with(|_| {}).is_err() {
+            replay::record_invalidation(
+                || self.backend.get_task_description(task),
+                Some(reason.to_string()),
+            );
+        }
+        self.backend.invalidation_chains().map(|chains| {
+            chains.record_invalidation(task, InvalidationRoot::Reason(reason.to_string()))
+        });
         {
             let (_, reason_set) = &mut *self.aggregated_update.lock().unwrap();
             reason_set.insert(reason);
@@ -1447,6 +1466,10 @@ impl<B: Backend + 'static> TurboTasksApi for TurboTasks<B> {
         self.backend.task_statistics()
     }
 
+    fn invalidation_chains(&self) -> &InvalidationChainsApi {
+        self.backend.invalidation_chains()
+    }
+
     fn stop_and_wait(&self) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>> {
         let this = self.pin();
         Box::pin(async move {
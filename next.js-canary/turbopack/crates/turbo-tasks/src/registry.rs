@@ -143,3 +143,18 @@ pub fn get_trait(id: TraitTypeId) -> &'static TraitType {
 pub fn get_trait_type_global_name(id: TraitTypeId) -> &'static str {
     TRAIT_TYPES.get(*id as usize).unwrap().1
 }
+
+/// Returns the global names of all registered functions.
+pub fn function_global_names() -> Vec<&'static str> {
+    NAME_TO_FUNCTION.read().unwrap().keys().copied().collect()
+}
+
+/// Returns the global names of all registered value types.
+pub fn value_type_global_names() -> Vec<&'static str> {
+    VALUE_TYPES_BY_NAME.iter().map(|e| *e.key()).collect()
+}
+
+/// Returns the global names of all registered trait types.
+pub fn trait_type_global_names() -> Vec<&'static str> {
+    TRAIT_TYPES_BY_NAME.iter().map(|e| *e.key()).collect()
+}
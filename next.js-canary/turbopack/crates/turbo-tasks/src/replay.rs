@@ -0,0 +1,597 @@
+//! Recording and deterministic replay of turbo-tasks sessions.
+//!
+//! While recording, task executions, invalidations and reads from filesystems are written to a
+//! log file as a stream of postcard encoded [`ReplayEvent`]s. A [`Replay`] of that log serves the
+//! recorded file contents and directory listings instead of reading from disk, applies the
+//! recorded changes step by step and verifies that the same tasks are executed in every step.
+//!
+//! Recording or replaying is a process-wide mode, see [`start_recording`] and [`start_replay`].
+//! Filesystem implementations use [`recorder`] and [`replay`] to take part in it.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Read, Write},
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+};
+
+use anyhow::{Context, Result, bail};
+use parking_lot::{Mutex, RwLock};
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{FxIndexSet, InvalidationReason, TaskId, TurboTasksApi};
+
+/// The first bytes of a replay log. The last byte is the version of the format.
+const MAGIC: &[u8] = b"turbo-tasks replay\x01";
+
+/// The type of an entry of a recorded directory listing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplayDirectoryEntry {
+    File,
+    Directory,
+    Symlink,
+    Other,
+}
+
+/// An event of a recorded session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplayEvent {
+    /// A task started executing.
+    Execution { task: String },
+    /// A task was invalidated explicitly, e.g. by an [`crate::Invalidator`]. Invalidations caused
+    /// by [`ReplayEvent::FileChanges`] are not recorded, they are replayed by the filesystem.
+    Invalidation {
+        task: String,
+        reason: Option<String>,
+    },
+    /// A file was read from a filesystem. `content` is `None` when the file didn't exist.
+    FileRead {
+        path: String,
+        content: Option<Vec<u8>>,
+    },
+    /// A directory was read from a filesystem. The entries are sorted by name. `entries` is
+    /// `None` when the directory didn't exist.
+    DirectoryRead {
+        path: String,
+        entries: Option<Vec<(String, ReplayDirectoryEntry)>>,
+    },
+    /// The file watcher reported a batch of changed paths.
+    FileChanges { paths: Vec<String> },
+}
+
+enum ReplayMode {
+    Recording(Arc<ReplayRecorder>),
+    Replaying(Arc<Replay>),
+}
+
+/// Fast path to avoid taking the lock when neither recording nor replaying.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static MODE: RwLock<Option<ReplayMode>> = RwLock::new(None);
+
+/// The number of file changes that are being applied. The invalidations they cause are replayed
+/// by applying the same file changes again, so they are not recorded separately. Filesystems might
+/// invalidate from multiple threads, so this is not thread-local.
+static APPLYING_FILE_CHANGES: AtomicUsize = AtomicUsize::new(0);
+
+fn set_mode(mode: ReplayMode) -> Result<()> {
+    let mut current = MODE.write();
+    if current.is_some() {
+        bail!("A turbo-tasks session is already being recorded or replayed");
+    }
+    *current = Some(mode);
+    ACTIVE.store(true, Ordering::Release);
+    Ok(())
+}
+
+/// Starts recording all events of this process to the file at `path`.
+pub fn start_recording(path: &Path) -> Result<Arc<ReplayRecorder>> {
+    let recorder = Arc::new(ReplayRecorder::create(path)?);
+    set_mode(ReplayMode::Recording(recorder.clone()))?;
+    Ok(recorder)
+}
+
+/// Starts replaying a recorded session in this process.
+pub fn start_replay(replay: Replay) -> Result<Arc<Replay>> {
+    let replay = Arc::new(replay);
+    set_mode(ReplayMode::Replaying(replay.clone()))?;
+    Ok(replay)
+}
+
+/// Stops recording or replaying. Flushes the recording.
+pub fn stop() -> Result<()> {
+    ACTIVE.store(false, Ordering::Release);
+    if let Some(ReplayMode::Recording(recorder)) = MODE.write().take() {
+        recorder.flush()?;
+    }
+    Ok(())
+}
+
+/// Returns the current recorder, if a session is being recorded.
+pub fn recorder() -> Option<Arc<ReplayRecorder>> {
+    if !ACTIVE.load(Ordering::Acquire) {
+        return None;
+    }
+    match &*MODE.read() {
+        Some(ReplayMode::Recording(recorder)) => Some(recorder.clone()),
+        _ => None,
+    }
+}
+
+/// Returns the current replay, if a session is being replayed.
+pub fn replay() -> Option<Arc<Replay>> {
+    if !ACTIVE.load(Ordering::Acquire) {
+        return None;
+    }
+    match &*MODE.read() {
+        Some(ReplayMode::Replaying(replay)) => Some(replay.clone()),
+        _ => None,
+    }
+}
+
+/// Records a read from a filesystem. `event` is only called when a session is recorded.
+pub fn record(event: impl FnOnce() -> ReplayEvent) {
+    if let Some(recorder) = recorder() {
+        recorder.record(&event());
+    }
+}
+
+/// Records that a task started executing. The replay uses this to find tasks by their description
+/// when replaying invalidations. Transient tasks, e.g. root and once tasks, are started by the host
+/// and their ids differ between sessions, so they are not recorded.
+pub fn record_execution(task: TaskId, description: impl FnOnce() -> String) {
+    if !ACTIVE.load(Ordering::Acquire) || task.is_transient() {
+        return;
+    }
+    match &*MODE.read() {
+        Some(ReplayMode::Recording(recorder)) => recorder.record(&ReplayEvent::Execution {
+            task: description(),
+        }),
+        Some(ReplayMode::Replaying(replay)) => replay.observe_execution(task, description()),
+        None => {}
+    }
+}
+
+/// Records an explicit invalidation of a task, unless it was caused by file changes. Invalidations
+/// made by executing tasks must not be recorded, they happen again when the tasks are replayed.
+pub fn record_invalidation(description: impl FnOnce() -> String, reason: Option<String>) {
+    if APPLYING_FILE_CHANGES.load(Ordering::Acquire) > 0 {
+        return;
+    }
+    record(|| ReplayEvent::Invalidation {
+        task: description(),
+        reason,
+    });
+}
+
+/// Records a batch of changed paths, unless it's empty, and calls `apply` to invalidate them.
+/// Filesystems use this for changes reported by their watcher and to apply
+/// [`Replay::file_changes`].
+pub fn apply_file_changes<T>(paths: impl FnOnce() -> Vec<String>, apply: impl FnOnce() -> T) -> T {
+    if let Some(recorder) = recorder() {
+        let paths = paths();
+        if !paths.is_empty() {
+            recorder.record(&ReplayEvent::FileChanges { paths });
+        }
+    }
+    APPLYING_FILE_CHANGES.fetch_add(1, Ordering::AcqRel);
+    let result = apply();
+    APPLYING_FILE_CHANGES.fetch_sub(1, Ordering::AcqRel);
+    result
+}
+
+/// Writes [`ReplayEvent`]s to a log file.
+pub struct ReplayRecorder {
+    writer: Mutex<BufWriter<File>>,
+    failed: AtomicBool,
+}
+
+impl ReplayRecorder {
+    pub fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Unable to create replay log {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        writer.write_all(MAGIC)?;
+        Ok(Self {
+            writer: Mutex::new(writer),
+            failed: AtomicBool::new(false),
+        })
+    }
+
+    pub fn record(&self, event: &ReplayEvent) {
+        let mut writer = self.writer.lock();
+        let result = postcard::to_io(event, &mut *writer);
+        if let Err(err) = result
+            && !self.failed.swap(true, Ordering::Relaxed)
+        {
+            // Only report the first error, the log is incomplete anyway
+            println!("WARNING: Unable to write to the replay log: {err:?}");
+        }
+    }
+
+    pub fn flush(&self) -> Result<()> {
+        self.writer.lock().flush()?;
+        if self.failed.load(Ordering::Relaxed) {
+            bail!("The replay log is incomplete because writing failed");
+        }
+        Ok(())
+    }
+}
+
+/// The entries of a recorded directory listing, sorted by name.
+pub type ReplayDirectoryListing = Arc<[(String, ReplayDirectoryEntry)]>;
+
+/// An external change that starts a step of a replay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayChange {
+    /// Paths that changed on disk. They are applied by the filesystem, see
+    /// [`Replay::file_changes`].
+    FileChanges(Vec<String>),
+    /// An explicit invalidation of the tasks with the description `task`.
+    Invalidation {
+        task: String,
+        reason: Option<String>,
+    },
+}
+
+/// The part of a recorded session between two batches of external changes.
+#[derive(Default)]
+struct ReplayStep {
+    /// The changes that started this step, in recorded order. Empty for the first step.
+    changes: Vec<ReplayChange>,
+    /// The content of the files read in this step.
+    files: FxHashMap<String, Option<Arc<[u8]>>>,
+    /// The directory listings read in this step.
+    directories: FxHashMap<String, Option<ReplayDirectoryListing>>,
+    /// The task executions of this step.
+    executions: Vec<String>,
+}
+
+impl ReplayStep {
+    fn has_started(&self) -> bool {
+        !self.executions.is_empty() || !self.files.is_empty() || !self.directories.is_empty()
+    }
+}
+
+/// The reason of a replayed invalidation.
+#[derive(PartialEq, Eq, Hash)]
+struct ReplayedInvalidation(String);
+
+impl std::fmt::Display for ReplayedInvalidation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (replayed)", self.0)
+    }
+}
+
+impl InvalidationReason for ReplayedInvalidation {}
+
+/// A recorded session that is fed back into a new session.
+///
+/// The session is divided into steps by the recorded external changes. Consecutive changes without
+/// any work in between belong to the same step. Within a step, reads return the content recorded
+/// in that step or the latest earlier one, so the result doesn't depend on the order in which
+/// tasks are scheduled.
+pub struct Replay {
+    steps: Vec<ReplayStep>,
+    step: AtomicUsize,
+    /// The tasks executed in this replay, by their description.
+    tasks: Mutex<FxHashMap<String, FxIndexSet<TaskId>>>,
+    /// The task executions of each step of this replay.
+    executions: Mutex<Vec<Vec<String>>>,
+}
+
+impl Replay {
+    /// Reads a log written by a [`ReplayRecorder`].
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut bytes = Vec::new();
+        File::open(path)
+            .and_then(|mut file| file.read_to_end(&mut bytes))
+            .with_context(|| format!("Unable to read replay log {}", path.display()))?;
+        let Some(mut bytes) = bytes.strip_prefix(MAGIC) else {
+            bail!(
+                "{} is not a replay log of this version of turbo-tasks",
+                path.display()
+            );
+        };
+        let mut events = Vec::new();
+        while !bytes.is_empty() {
+            let (event, rest) = postcard::take_from_bytes::<ReplayEvent>(bytes)
+                .with_context(|| format!("Invalid event {} in the replay log", events.len()))?;
+            events.push(event);
+            bytes = rest;
+        }
+        Ok(Self::new(events))
+    }
+
+    pub fn new(events: impl IntoIterator<Item = ReplayEvent>) -> Self {
+        let mut steps = vec![ReplayStep::default()];
+        for event in events {
+            let change = match event {
+                ReplayEvent::Execution { task } => {
+                    steps.last_mut().unwrap().executions.push(task);
+                    continue;
+                }
+                ReplayEvent::FileRead { path, content } => {
+                    // Keep only the first read of every step
+                    steps
+                        .last_mut()
+                        .unwrap()
+                        .files
+                        .entry(path)
+                        .or_insert_with(|| content.map(Arc::from));
+                    continue;
+                }
+                ReplayEvent::DirectoryRead { path, entries } => {
+                    steps
+                        .last_mut()
+                        .unwrap()
+                        .directories
+                        .entry(path)
+                        .or_insert_with(|| entries.map(Arc::from));
+                    continue;
+                }
+                ReplayEvent::Invalidation { task, reason } => {
+                    ReplayChange::Invalidation { task, reason }
+                }
+                ReplayEvent::FileChanges { paths } => ReplayChange::FileChanges(paths),
+            };
+            if steps.len() == 1 || steps.last().unwrap().has_started() {
+                steps.push(ReplayStep::default());
+            }
+            steps.last_mut().unwrap().changes.push(change);
+        }
+        Self {
+            steps,
+            step: AtomicUsize::new(0),
+            tasks: Mutex::default(),
+            executions: Mutex::new(vec![Vec::new()]),
+        }
+    }
+
+    /// The current step of the replay.
+    pub fn step(&self) -> usize {
+        self.step.load(Ordering::Acquire)
+    }
+
+    /// The number of steps of the recorded session.
+    pub fn step_count(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Returns the recorded content of a file for the current step. The outer `None` means that
+    /// the file was not read up to this step of the recorded session, the inner `None` that it
+    /// didn't exist.
+    pub fn file_content(&self, path: &str) -> Option<Option<Arc<[u8]>>> {
+        self.steps[..=self.step()]
+            .iter()
+            .rev()
+            .find_map(|step| step.files.get(path).cloned())
+    }
+
+    /// Returns the recorded entries of a directory for the current step, sorted by name. The outer
+    /// `None` means that the directory was not read up to this step of the recorded session, the
+    /// inner `None` that it didn't exist.
+    pub fn directory_entries(&self, path: &str) -> Option<Option<ReplayDirectoryListing>> {
+        self.steps[..=self.step()]
+            .iter()
+            .rev()
+            .find_map(|step| step.directories.get(path).cloned())
+    }
+
+    /// The changes that started the current step.
+    pub fn changes(&self) -> &[ReplayChange] {
+        &self.steps[self.step()].changes
+    }
+
+    /// The paths that changed on disk at the start of the current step.
+    pub fn file_changes(&self) -> impl Iterator<Item = &str> {
+        self.changes()
+            .iter()
+            .flat_map(|change| match change {
+                ReplayChange::FileChanges(paths) => &paths[..],
+                ReplayChange::Invalidation { .. } => &[],
+            })
+            .map(String::as_str)
+    }
+
+    /// Advances to the next step. Returns `false` when all steps have been replayed.
+    ///
+    /// Afterwards the filesystems need to apply the [`Replay::file_changes`] and
+    /// [`Replay::invalidate`] needs to be called to apply the other changes.
+    pub fn next_step(&self) -> bool {
+        let step = self.step();
+        if step + 1 >= self.steps.len() {
+            return false;
+        }
+        self.executions.lock().push(Vec::new());
+        self.step.store(step + 1, Ordering::Release);
+        true
+    }
+
+    /// Applies the recorded invalidations of the current step in order. The invalidated tasks are
+    /// found by their description, so they must have been executed in this replay before. Task
+    /// descriptions are not unique, all tasks with the same description are invalidated.
+    pub fn invalidate(&self, turbo_tasks: &dyn TurboTasksApi) -> Result<()> {
+        for change in self.changes() {
+            let ReplayChange::Invalidation { task, reason } = change else {
+                continue;
+            };
+            let Some(task_ids) = self.tasks.lock().get(task).cloned() else {
+                bail!(
+                    "Step {} of the replay invalidates {task}, but it was not executed",
+                    self.step()
+                );
+            };
+            for task_id in task_ids {
+                match reason {
+                    Some(reason) => turbo_tasks.invalidate_with_reason(
+                        task_id,
+                        (Arc::new(ReplayedInvalidation(reason.clone()))
+                            as Arc<dyn InvalidationReason>)
+                            .into(),
+                    ),
+                    None => turbo_tasks.invalidate(task_id),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn observe_execution(&self, task: TaskId, description: String) {
+        self.tasks
+            .lock()
+            .entry(description.clone())
+            .or_default()
+            .insert(task);
+        self.executions.lock().last_mut().unwrap().push(description);
+    }
+
+    /// Checks that every replayed step executed the same tasks as the recorded session. The
+    /// order within a step is ignored, as it depends on scheduling.
+    pub fn verify(&self) -> Result<()> {
+        let executions = self.executions.lock();
+        for (step, (recorded, replayed)) in self.steps.iter().zip(executions.iter()).enumerate() {
+            let mut recorded = recorded.executions.clone();
+            let mut replayed = replayed.clone();
+            recorded.sort_unstable();
+            replayed.sort_unstable();
+            if recorded != replayed {
+                let missing = recorded
+                    .iter()
+                    .filter(|task| !replayed.contains(task))
+                    .collect::<Vec<_>>();
+                let unexpected = replayed
+                    .iter()
+                    .filter(|task| !recorded.contains(task))
+                    .collect::<Vec<_>>();
+                bail!(
+                    "Step {step} of the replay diverged from the recording: {} tasks were \
+                     executed instead of {}, missing executions: {missing:?}, unexpected \
+                     executions: {unexpected:?}",
+                    replayed.len(),
+                    recorded.len()
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(path: &str, content: &str) -> ReplayEvent {
+        ReplayEvent::FileRead {
+            path: path.to_string(),
+            content: Some(content.as_bytes().to_vec()),
+        }
+    }
+
+    fn execution(task: &str) -> ReplayEvent {
+        ReplayEvent::Execution {
+            task: task.to_string(),
+        }
+    }
+
+    fn invalidation(task: &str) -> ReplayEvent {
+        ReplayEvent::Invalidation {
+            task: task.to_string(),
+            reason: None,
+        }
+    }
+
+    fn task(id: u32) -> TaskId {
+        TaskId::try_from(id).unwrap()
+    }
+
+    #[test]
+    fn steps() -> Result<()> {
+        let replay = Replay::new([
+            execution("read"),
+            read("/a", "1"),
+            read("/b", "x"),
+            ReplayEvent::FileChanges {
+                paths: vec!["/a".to_string()],
+            },
+            invalidation("env"),
+            execution("read"),
+            read("/a", "2"),
+            read("/c", "new"),
+            invalidation("env"),
+            execution("env"),
+        ]);
+        assert_eq!(replay.step_count(), 3);
+        assert!(replay.changes().is_empty());
+        assert_eq!(replay.file_content("/a"), Some(Some(Arc::from(&b"1"[..]))));
+        // Files first read in a later step are not known yet
+        assert_eq!(replay.file_content("/c"), None);
+        assert_eq!(replay.file_content("/d"), None);
+        replay.observe_execution(task(1), "read".to_string());
+
+        assert!(replay.next_step());
+        assert_eq!(
+            replay.changes(),
+            &[
+                ReplayChange::FileChanges(vec!["/a".to_string()]),
+                ReplayChange::Invalidation {
+                    task: "env".to_string(),
+                    reason: None
+                }
+            ]
+        );
+        assert_eq!(replay.file_changes().collect::<Vec<_>>(), vec!["/a"]);
+        assert_eq!(replay.file_content("/a"), Some(Some(Arc::from(&b"2"[..]))));
+        assert_eq!(replay.file_content("/b"), Some(Some(Arc::from(&b"x"[..]))));
+        assert_eq!(
+            replay.file_content("/c"),
+            Some(Some(Arc::from(&b"new"[..])))
+        );
+        assert!(replay.verify().is_err());
+        replay.observe_execution(task(1), "read".to_string());
+        replay.verify()?;
+
+        assert!(replay.next_step());
+        assert_eq!(replay.file_changes().count(), 0);
+        assert!(!replay.next_step());
+        Ok(())
+    }
+
+    #[test]
+    fn recorder_round_trip() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("replay.bin");
+        let events = vec![
+            execution("root"),
+            ReplayEvent::FileRead {
+                path: "/missing".to_string(),
+                content: None,
+            },
+            ReplayEvent::DirectoryRead {
+                path: "/dir".to_string(),
+                entries: Some(vec![
+                    ("a".to_string(), ReplayDirectoryEntry::File),
+                    ("b".to_string(), ReplayDirectoryEntry::Directory),
+                ]),
+            },
+            ReplayEvent::Invalidation {
+                task: "root".to_string(),
+                reason: Some("a changed".to_string()),
+            },
+        ];
+        let recorder = ReplayRecorder::create(&path)?;
+        for event in &events {
+            recorder.record(event);
+        }
+        recorder.flush()?;
+        let replay = Replay::load(&path)?;
+        assert_eq!(replay.step_count(), 2);
+        assert_eq!(replay.steps[0].executions, vec!["root".to_string()]);
+        assert_eq!(replay.file_content("/missing"), Some(None));
+        assert_eq!(replay.directory_entries("/dir").unwrap().unwrap().len(), 2);
+        Ok(())
+    }
+}
@@ -11,8 +11,8 @@ use turbo_tasks_hash::{DeterministicHash, hash_xxh3_hash64};
 use turbopack_core::{
     asset::{Asset, AssetContent},
     chunk::{
-        Chunk, ChunkGroupResult, ChunkItem, ChunkType, ChunkableModule, ChunkingConfig,
-        ChunkingConfigs, ChunkingContext, EntryChunkGroupResult, EvaluatableAsset,
+        AsyncChunkOptions, Chunk, ChunkGroupResult, ChunkItem, ChunkType, ChunkableModule,
+        ChunkingConfig, ChunkingConfigs, ChunkingContext, EntryChunkGroupResult, EvaluatableAsset,
         EvaluatableAssets, MinifyType, ModuleId, SourceMapsType,
         availability_info::AvailabilityInfo,
         chunk_group::{MakeChunkGroupResult, make_chunk_group},
@@ -115,6 +115,20 @@ impl BrowserChunkingContextBuilder {
         self
     }
 
+    pub fn service_worker_precache_manifest(
+        mut self,
+        enable_service_worker_precache_manifest: bool,
+    ) -> Self {
+        self.chunking_context
+            .enable_service_worker_precache_manifest = enable_service_worker_precache_manifest;
+        self
+    }
+
+    pub fn service_worker_root_path(mut self, service_worker_root_path: FileSystemPath) -> Self {
+        self.chunking_context.service_worker_root_path = Some(service_worker_root_path);
+        self
+    }
+
     pub fn asset_base_path(mut self, asset_base_path: Option<RcStr>) -> Self {
         self.chunking_context.asset_base_path = asset_base_path;
         self
@@ -212,6 +226,8 @@ pub struct BrowserChunkingContext {
     chunk_root_path: FileSystemPath,
     /// Static assets are placed at this path
     asset_root_path: FileSystemPath,
+    /// Bootstrap scripts of service workers are placed at this path, defaults to the output root
+    service_worker_root_path: Option<FileSystemPath>,
     /// Base path that will be prepended to all chunk URLs when loading them.
     /// This path will not appear in chunk paths or chunk data.
     chunk_base_path: Option<RcStr>,
@@ -229,6 +245,8 @@ pub struct BrowserChunkingContext {
     enable_module_merging: bool,
     /// Enable dynamic chunk content loading.
     enable_dynamic_chunk_content_loading: bool,
+    /// Enable the precache manifest in service worker bootstrap scripts.
+    enable_service_worker_precache_manifest: bool,
     /// The environment chunks will be evaluated in.
     environment: ResolvedVc<Environment>,
     /// The kind of runtime to include in the output.
@@ -272,6 +290,7 @@ impl BrowserChunkingContext {
                 chunk_root_path,
                 should_use_file_source_map_uris: false,
                 asset_root_path,
+                service_worker_root_path: None,
                 chunk_base_path: None,
                 chunk_suffix_path: None,
                 asset_base_path: None,
@@ -279,6 +298,7 @@ impl BrowserChunkingContext {
                 enable_tracing: false,
                 enable_module_merging: false,
                 enable_dynamic_chunk_content_loading: false,
+                enable_service_worker_precache_manifest: false,
                 environment,
                 runtime_type,
                 minify_type: MinifyType::NoMinify,
@@ -335,12 +355,13 @@ impl BrowserChunkingContext {
     async fn generate_chunk(
         self: Vc<Self>,
         chunk: Vc<Box<dyn Chunk>>,
+        name: Option<RcStr>,
     ) -> Result<Vc<Box<dyn OutputAsset>>> {
         Ok(
             if let Some(ecmascript_chunk) =
                 Vc::try_resolve_downcast_type::<EcmascriptChunk>(chunk).await?
             {
-                Vc::upcast(EcmascriptBrowserChunk::new(self, ecmascript_chunk))
+                Vc::upcast(EcmascriptBrowserChunk::new(self, ecmascript_chunk, name))
             } else if let Some(output_asset) =
                 Vc::try_resolve_sidecast::<Box<dyn OutputAsset>>(chunk).await?
             {
@@ -351,6 +372,76 @@ impl BrowserChunkingContext {
         )
     }
 
+    /// Creates a chunk group. When `name` is set, it's used as a prefix for the file names of the
+    /// ecmascript chunks.
+    #[turbo_tasks::function]
+    async fn chunk_group_with_name(
+        self: ResolvedVc<Self>,
+        name: Option<RcStr>,
+        ident: Vc<AssetIdent>,
+        chunk_group: ChunkGroup,
+        module_graph: Vc<ModuleGraph>,
+        availability_info: AvailabilityInfo,
+    ) -> Result<Vc<ChunkGroupResult>> {
+        let span = tracing::info_span!("chunking", name = ident.to_string().await?.to_string());
+        async move {
+            let this = self.await?;
+            let modules = chunk_group.entries();
+            let input_availability_info = availability_info;
+            let MakeChunkGroupResult {
+                chunks,
+                availability_info,
+            } = make_chunk_group(
+                modules,
+                module_graph,
+                ResolvedVc::upcast(self),
+                input_availability_info,
+            )
+            .await?;
+
+            let mut assets = chunks
+                .iter()
+                .map(|chunk| self.generate_chunk(**chunk, name.clone()).to_resolved())
+                .try_join()
+                .await?;
+
+            if this.enable_hot_module_replacement {
+                let mut ident = ident;
+                if let Some(name) = &name {
+                    ident = ident.with_modifier(format!("chunk name {name}").into());
+                }
+                match input_availability_info {
+                    AvailabilityInfo::Root => {}
+                    AvailabilityInfo::Untracked => {
+                        ident = ident.with_modifier(rcstr!("untracked"));
+                    }
+                    AvailabilityInfo::Complete { available_modules } => {
+                        ident =
+                            ident.with_modifier(available_modules.hash().await?.to_string().into());
+                    }
+                }
+                assets.push(
+                    self.generate_chunk_list_register_chunk(
+                        ident,
+                        EvaluatableAssets::empty(),
+                        Vc::cell(assets.clone()),
+                        EcmascriptDevChunkListSource::Dynamic,
+                    )
+                    .to_resolved()
+                    .await?,
+                );
+            }
+
+            Ok(ChunkGroupResult {
+                assets: ResolvedVc::cell(assets),
+                availability_info,
+            }
+            .cell())
+        }
+        .instrument(span)
+        .await
+    }
+
     #[turbo_tasks::function]
     pub fn current_chunk_method(&self) -> Vc<CurrentChunkMethod> {
         self.current_chunk_method.cell()
@@ -426,6 +517,15 @@ impl ChunkingContext for BrowserChunkingContext {
         self.chunk_root_path.clone().cell()
     }
 
+    #[turbo_tasks::function]
+    fn service_worker_root_path(&self) -> Vc<FileSystemPath> {
+        self.service_worker_root_path
+            .as_ref()
+            .unwrap_or(&self.output_root)
+            .clone()
+            .cell()
+    }
+
     #[turbo_tasks::function]
     async fn chunk_path(
         &self,
@@ -558,73 +658,43 @@ impl ChunkingContext for BrowserChunkingContext {
         Vc::cell(self.enable_dynamic_chunk_content_loading)
     }
 
+    #[turbo_tasks::function]
+    fn is_service_worker_precache_manifest_enabled(&self) -> Vc<bool> {
+        Vc::cell(self.enable_service_worker_precache_manifest)
+    }
+
     #[turbo_tasks::function]
     pub fn minify_type(&self) -> Vc<MinifyType> {
         self.minify_type.cell()
     }
 
     #[turbo_tasks::function]
-    async fn chunk_group(
-        self: ResolvedVc<Self>,
+    fn chunk_group(
+        self: Vc<Self>,
         ident: Vc<AssetIdent>,
         chunk_group: ChunkGroup,
         module_graph: Vc<ModuleGraph>,
         availability_info: AvailabilityInfo,
-    ) -> Result<Vc<ChunkGroupResult>> {
-        let span = tracing::info_span!("chunking", name = ident.to_string().await?.to_string());
-        async move {
-            let this = self.await?;
-            let modules = chunk_group.entries();
-            let input_availability_info = availability_info;
-            let MakeChunkGroupResult {
-                chunks,
-                availability_info,
-            } = make_chunk_group(
-                modules,
-                module_graph,
-                ResolvedVc::upcast(self),
-                input_availability_info,
-            )
-            .await?;
-
-            let mut assets = chunks
-                .iter()
-                .map(|chunk| self.generate_chunk(**chunk).to_resolved())
-                .try_join()
-                .await?;
-
-            if this.enable_hot_module_replacement {
-                let mut ident = ident;
-                match input_availability_info {
-                    AvailabilityInfo::Root => {}
-                    AvailabilityInfo::Untracked => {
-                        ident = ident.with_modifier(rcstr!("untracked"));
-                    }
-                    AvailabilityInfo::Complete { available_modules } => {
-                        ident =
-                            ident.with_modifier(available_modules.hash().await?.to_string().into());
-                    }
-                }
-                assets.push(
-                    self.generate_chunk_list_register_chunk(
-                        ident,
-                        EvaluatableAssets::empty(),
-                        Vc::cell(assets.clone()),
-                        EcmascriptDevChunkListSource::Dynamic,
-                    )
-                    .to_resolved()
-                    .await?,
-                );
-            }
+    ) -> Vc<ChunkGroupResult> {
+        self.chunk_group_with_name(None, ident, chunk_group, module_graph, availability_info)
+    }
 
-            Ok(ChunkGroupResult {
-                assets: ResolvedVc::cell(assets),
-                availability_info,
-            }
-            .cell())
-        }
-        .instrument(span)
-        .await
+    #[turbo_tasks::function]
+    fn named_chunk_group(
+        self: Vc<Self>,
+        name: RcStr,
+        ident: Vc<AssetIdent>,
+        chunk_group: ChunkGroup,
+        module_graph: Vc<ModuleGraph>,
+        availability_info: AvailabilityInfo,
+    ) -> Vc<ChunkGroupResult> {
+        self.chunk_group_with_name(
+            Some(name),
+            ident,
+            chunk_group,
+            module_graph,
+            availability_info,
+        )
     }
 
     #[turbo_tasks::function]
@@ -657,7 +727,7 @@ impl ChunkingContext for BrowserChunkingContext {
 
             let mut assets: Vec<ResolvedVc<Box<dyn OutputAsset>>> = chunks
                 .iter()
-                .map(|chunk| self.generate_chunk(**chunk).to_resolved())
+                .map(|chunk| self.generate_chunk(**chunk, None).to_resolved())
                 .try_join()
                 .await?;
 
@@ -725,17 +795,24 @@ impl ChunkingContext for BrowserChunkingContext {
         module: Vc<Box<dyn ChunkableModule>>,
         module_graph: Vc<ModuleGraph>,
         availability_info: AvailabilityInfo,
+        options: AsyncChunkOptions,
     ) -> Result<Vc<Box<dyn ChunkItem>>> {
         Ok(if self.await?.manifest_chunks {
-            let manifest_asset =
-                ManifestAsyncModule::new(module, module_graph, Vc::upcast(self), availability_info);
+            let manifest_asset = ManifestAsyncModule::new(
+                module,
+                module_graph,
+                Vc::upcast(self),
+                availability_info,
+                options,
+            );
             Vc::upcast(ManifestLoaderChunkItem::new(
                 manifest_asset,
                 module_graph,
                 Vc::upcast(self),
             ))
         } else {
-            let module = AsyncLoaderModule::new(module, Vc::upcast(self), availability_info);
+            let module =
+                AsyncLoaderModule::new(module, Vc::upcast(self), availability_info, options);
             Vc::upcast(module.as_chunk_item(module_graph, Vc::upcast(self)))
         })
     }
@@ -744,11 +821,12 @@ impl ChunkingContext for BrowserChunkingContext {
     async fn async_loader_chunk_item_id(
         self: Vc<Self>,
         module: Vc<Box<dyn ChunkableModule>>,
+        options: AsyncChunkOptions,
     ) -> Result<Vc<ModuleId>> {
         Ok(if self.await?.manifest_chunks {
-            self.chunk_item_id_from_ident(ManifestLoaderChunkItem::asset_ident_for(module))
+            self.chunk_item_id_from_ident(ManifestLoaderChunkItem::asset_ident_for(module, options))
         } else {
-            self.chunk_item_id_from_ident(AsyncLoaderModule::asset_ident_for(module))
+            self.chunk_item_id_from_ident(AsyncLoaderModule::asset_ident_for(module, options))
         })
     }
 
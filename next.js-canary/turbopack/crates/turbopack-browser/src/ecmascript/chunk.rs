@@ -20,6 +20,8 @@ use crate::{BrowserChunkingContext, ecmascript::content::EcmascriptBrowserChunkC
 pub struct EcmascriptBrowserChunk {
     chunking_context: ResolvedVc<BrowserChunkingContext>,
     chunk: ResolvedVc<EcmascriptChunk>,
+    /// The name of the chunk group, used as a prefix for the file name.
+    name: Option<RcStr>,
 }
 
 #[turbo_tasks::value_impl]
@@ -29,10 +31,12 @@ impl EcmascriptBrowserChunk {
     pub fn new(
         chunking_context: ResolvedVc<BrowserChunkingContext>,
         chunk: ResolvedVc<EcmascriptChunk>,
+        name: Option<RcStr>,
     ) -> Vc<Self> {
         EcmascriptBrowserChunk {
             chunking_context,
             chunk,
+            name,
         }
         .cell()
     }
@@ -101,9 +105,11 @@ impl OutputAsset for EcmascriptBrowserChunk {
     async fn path(self: Vc<Self>) -> Result<Vc<FileSystemPath>> {
         let this = self.await?;
         let ident = this.ident_for_path();
+        // The name of a split rule is more specific than the name of the chunk group
+        let name = this.chunk.await?.name.clone().or_else(|| this.name.clone());
         Ok(this
             .chunking_context
-            .chunk_path(Some(Vc::upcast(self)), ident, None, rcstr!(".js")))
+            .chunk_path(Some(Vc::upcast(self)), ident, name, rcstr!(".js")))
     }
 
     #[turbo_tasks::function]
@@ -5,6 +5,7 @@ use std::{
 
 use clap::{Args, Parser, ValueEnum};
 use serde::{Deserialize, Serialize};
+use turbo_rcstr::RcStr;
 use turbo_tasks::{NonLocalValue, TaskInput, trace::TraceRawVcs};
 use turbopack_cli_utils::issue::IssueSeverityCliOption;
 
@@ -13,6 +14,7 @@ use turbopack_cli_utils::issue::IssueSeverityCliOption;
 pub enum Arguments {
     Build(BuildArguments),
     Dev(DevArguments),
+    Resolve(ResolveArguments),
 }
 
 impl Arguments {
@@ -21,6 +23,7 @@ impl Arguments {
         match self {
             Arguments::Build(args) => args.common.dir.as_deref(),
             Arguments::Dev(args) => args.common.dir.as_deref(),
+            Arguments::Resolve(args) => args.dir.as_deref(),
         }
     }
 }
@@ -82,9 +85,33 @@ pub struct CommonArguments {
     // MB.
     // #[clap(long)]
     // pub memory_limit: Option<usize>,
+    /// Record task executions, invalidations and file reads to the given
+    /// file, so the session can be replayed deterministically.
+    #[clap(long)]
+    pub record_session: Option<PathBuf>,
+
     /// Whether to build for the `browser` or `node``
     #[clap(long)]
     pub target: Option<Target>,
+
+    /// Additional conditions for the "exports" and "imports" fields when
+    /// resolving the requests of the modules matching a glob, in the form
+    /// `<glob>=<condition>,...`, e.g. `src/workers/**=worker`. Relative to
+    /// the project's directory (`--dir`). Can be repeated.
+    #[clap(long, value_parser = parse_resolve_conditions)]
+    pub resolve_conditions: Vec<(RcStr, Vec<RcStr>)>,
+}
+
+fn parse_resolve_conditions(value: &str) -> Result<(RcStr, Vec<RcStr>), String> {
+    let (glob, conditions) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected `<glob>=<condition>,...`, got `{value}`"))?;
+    let conditions = conditions
+        .split(',')
+        .filter(|condition| !condition.is_empty())
+        .map(RcStr::from)
+        .collect();
+    Ok((glob.into(), conditions))
 }
 
 #[derive(Debug, Args)]
@@ -113,6 +140,11 @@ pub struct DevArguments {
     #[clap(long)]
     pub no_open: bool,
 
+    /// Print the chain of changes that caused tasks to be re-executed after
+    /// every update, e.g. to find out which edit caused a slow update.
+    #[clap(long)]
+    pub explain_invalidations: bool,
+
     // ==
     // = Inherited options from next-dev, need revisit later.
     // ==
@@ -140,9 +172,50 @@ pub struct BuildArguments {
     #[clap(long)]
     pub no_scope_hoist: bool,
 
+    /// Write a profile of the build to the given file. Files ending with
+    /// `.speedscope.json` use the speedscope format, other `.json` files the
+    /// Chrome Trace Event format, and any other file a raw Turbopack trace.
+    /// Spans are filtered by `TURBOPACK_TRACING` (default: `turbopack`).
+    #[clap(long)]
+    pub profile: Option<PathBuf>,
+
+    /// Replay a session recorded with `--record-session`. File contents and directory listings
+    /// are served from the recording, and the build is repeated for every recorded change. Fails
+    /// when the replayed builds execute other tasks than the recorded session.
+    #[clap(long, conflicts_with = "record_session")]
+    pub replay_session: Option<PathBuf>,
+
     /// Drop the `TurboTasks` object upon exit. By default we intentionally leak this memory, as
     /// we're about to exit the process anyways, but that can cause issues with valgrind or other
     /// leak detectors.
     #[clap(long, hide = true)]
     pub force_memory_cleanup: bool,
 }
+
+#[derive(Debug, Args)]
+#[clap(author, version, about, long_about = None)]
+pub struct ResolveArguments {
+    /// The request to resolve, e.g. `react`, `./utils` or `#internal`.
+    #[clap(value_parser)]
+    pub specifier: String,
+
+    /// The file the request is made from. Relative to the project's directory
+    /// (`--dir`).
+    #[clap(long, value_parser)]
+    pub from: String,
+
+    /// The directory of the application.
+    /// If no directory is provided, the current directory will be used.
+    #[clap(short, long, value_parser)]
+    pub dir: Option<PathBuf>,
+
+    /// The root directory of the project. Nothing outside of this directory can
+    /// be accessed. e. g. the monorepo root.
+    /// If no directory is provided, `dir` will be used.
+    #[clap(long, value_parser)]
+    pub root: Option<PathBuf>,
+
+    /// Resolve as a `require()` call instead of an `import`.
+    #[clap(long)]
+    pub require: bool,
+}
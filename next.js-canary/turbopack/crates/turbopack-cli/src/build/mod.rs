@@ -8,14 +8,15 @@ use std::{
 use anyhow::{Context, Result, bail};
 use rustc_hash::FxHashSet;
 use tracing::Instrument;
-use turbo_rcstr::RcStr;
+use turbo_rcstr::{RcStr, rcstr};
 use turbo_tasks::{
     ReadConsistency, ResolvedVc, TransientInstance, TryJoinIterExt, TurboTasks, Vc, apply_effects,
+    replay::{self, Replay},
 };
 use turbo_tasks_backend::{
     BackendOptions, NoopBackingStorage, TurboTasksBackend, noop_backing_storage,
 };
-use turbo_tasks_fs::FileSystem;
+use turbo_tasks_fs::{DiskFileSystem, FileSystem};
 use turbopack::{
     css::chunk::CssChunkType, ecmascript::chunk::EcmascriptChunkType,
     global_module_ids::get_global_module_id_strategy,
@@ -60,6 +61,7 @@ use crate::{
 
 type Backend = TurboTasksBackend<NoopBackingStorage>;
 
+#[derive(Clone)]
 pub struct TurbopackBuildBuilder {
     turbo_tasks: Arc<TurboTasks<Backend>>,
     project_dir: RcStr,
@@ -73,6 +75,7 @@ pub struct TurbopackBuildBuilder {
     minify_type: MinifyType,
     target: Target,
     scope_hoist: bool,
+    resolve_conditions: Vec<(RcStr, Vec<RcStr>)>,
 }
 
 impl TurbopackBuildBuilder {
@@ -94,6 +97,7 @@ impl TurbopackBuildBuilder {
             },
             target: Target::Node,
             scope_hoist: true,
+            resolve_conditions: vec![],
         }
     }
 
@@ -142,6 +146,11 @@ impl TurbopackBuildBuilder {
         self
     }
 
+    pub fn resolve_conditions(mut self, resolve_conditions: Vec<(RcStr, Vec<RcStr>)>) -> Self {
+        self.resolve_conditions = resolve_conditions;
+        self
+    }
+
     pub async fn build(self) -> Result<()> {
         let task = self.turbo_tasks.spawn_once_task::<(), _>(async move {
             let build_result_op = build_internal(
@@ -153,6 +162,7 @@ impl TurbopackBuildBuilder {
                 self.minify_type,
                 self.target,
                 self.scope_hoist,
+                self.resolve_conditions,
             );
 
             // Await the result to propagate any errors.
@@ -201,6 +211,7 @@ async fn build_internal(
     minify_type: MinifyType,
     target: Target,
     scope_hoist: bool,
+    resolve_conditions: Vec<(RcStr, Vec<RcStr>)>,
 ) -> Result<Vc<()>> {
     let output_fs = output_fs(project_dir.clone());
     let project_fs = project_fs(root_dir.clone(), /* watch= */ false);
@@ -255,6 +266,7 @@ async fn build_internal(
         compile_time_info,
         node_env,
         source_maps_type,
+        resolve_conditions,
     );
 
     let entry_requests = (*entry_requests
@@ -515,16 +527,26 @@ pub async fn build(args: &BuildArguments) -> Result<()> {
         root_dir,
     } = normalize_dirs(&args.common.dir, &args.common.root)?;
 
+    if let Some(record_session) = &args.common.record_session {
+        replay::start_recording(record_session)?;
+    }
+    let replay = args
+        .replay_session
+        .as_deref()
+        .map(|replay_session| replay::start_replay(Replay::load(replay_session)?))
+        .transpose()?;
+
     let tt = TurboTasks::new(TurboTasksBackend::new(
         BackendOptions {
-            dependency_tracking: false,
+            // The steps of a replay rebuild incrementally
+            dependency_tracking: replay.is_some(),
             storage_mode: None,
             ..Default::default()
         },
         noop_backing_storage(),
     ));
 
-    let mut builder = TurbopackBuildBuilder::new(tt.clone(), project_dir, root_dir)
+    let mut builder = TurbopackBuildBuilder::new(tt.clone(), project_dir, root_dir.clone())
         .log_detail(args.common.log_detail)
         .log_level(
             args.common
@@ -545,13 +567,33 @@ pub async fn build(args: &BuildArguments) -> Result<()> {
         })
         .scope_hoist(!args.no_scope_hoist)
         .target(args.common.target.unwrap_or(Target::Node))
+        .resolve_conditions(args.common.resolve_conditions.clone())
         .show_all(args.common.show_all);
 
     for entry in normalize_entries(&args.common.entries) {
         builder = builder.entry_request(EntryRequest::Relative(entry));
     }
 
-    builder.build().await?;
+    builder.clone().build().await?;
+
+    if let Some(replay) = replay {
+        while replay.next_step() {
+            let root_dir = root_dir.clone();
+            tt.run_once(async move {
+                DiskFileSystem::new(rcstr!("project"), root_dir)
+                    .await?
+                    .apply_replayed_file_changes()
+                    .await;
+                Ok(())
+            })
+            .await?;
+            replay.invalidate(&*tt)?;
+            builder.clone().build().await?;
+        }
+        replay.verify()?;
+    }
+
+    replay::stop()?;
 
     // Intentionally leak this `Arc`. Otherwise we'll waste time during process exit performing a
     // ton of drop calls.
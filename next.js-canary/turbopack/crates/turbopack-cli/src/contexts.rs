@@ -3,13 +3,14 @@ use std::fmt;
 use anyhow::Result;
 use turbo_rcstr::{RcStr, rcstr};
 use turbo_tasks::{ResolvedVc, Vc};
-use turbo_tasks_fs::{FileSystem, FileSystemPath};
+use turbo_tasks_fs::{FileSystem, FileSystemPath, glob::Glob};
 use turbopack::{
     ModuleAssetContext,
+    css::sass::SassOptions,
     ecmascript::TreeShakingMode,
     module_options::{
-        EcmascriptOptionsContext, JsxTransformOptions, ModuleOptionsContext,
-        TypescriptTransformOptions,
+        CssOptionsContext, EcmascriptOptionsContext, JsxTransformOptions, ModuleOptionsContext,
+        ModuleRule, ModuleRuleEffect, RuleCondition, TypescriptTransformOptions,
     },
 };
 use turbopack_browser::react_refresh::assert_can_resolve_react_refresh;
@@ -84,6 +85,7 @@ pub async fn get_client_resolve_options_context(
         .await?;
     let module_options_context = ResolveOptionsContext {
         enable_node_modules: Some(project_path.root().owned().await?),
+        enable_yarn_pnp: Some(project_path.clone()),
         custom_conditions: vec![node_env.await?.to_string().into(), rcstr!("browser")],
         import_map: Some(next_client_import_map),
         browser: true,
@@ -109,6 +111,7 @@ async fn get_client_module_options_context(
     env: ResolvedVc<Environment>,
     node_env: Vc<NodeEnv>,
     source_maps_type: SourceMapsType,
+    resolve_conditions: Vec<(RcStr, Vec<RcStr>)>,
 ) -> Result<Vc<ModuleOptionsContext>> {
     let is_dev = matches!(*node_env.await?, NodeEnv::Development);
     let module_options_context = ModuleOptionsContext {
@@ -127,6 +130,17 @@ async fn get_client_module_options_context(
             .await?
             .is_found();
 
+    let mut module_rules = Vec::with_capacity(resolve_conditions.len());
+    for (glob, conditions) in resolve_conditions {
+        module_rules.push(ModuleRule::new(
+            RuleCondition::ResourcePathGlob {
+                base: project_path.clone(),
+                glob: Glob::new(glob).await?,
+            },
+            vec![ModuleRuleEffect::ResolveConditions(conditions)],
+        ));
+    }
+
     let enable_jsx = Some(
         JsxTransformOptions {
             react_refresh: enable_react_refresh,
@@ -144,7 +158,12 @@ async fn get_client_module_options_context(
             source_maps: source_maps_type,
             ..module_options_context.ecmascript.clone()
         },
+        css: CssOptionsContext {
+            enable_native_sass: Some(SassOptions::default().resolved_cell()),
+            ..module_options_context.css.clone()
+        },
         enable_postcss_transform: Some(PostCssTransformOptions::default().resolved_cell()),
+        module_rules,
         rules: vec![(
             foreign_code_context_condition(),
             module_options_context.clone().resolved_cell(),
@@ -163,6 +182,7 @@ pub fn get_client_asset_context(
     compile_time_info: Vc<CompileTimeInfo>,
     node_env: Vc<NodeEnv>,
     source_maps_type: SourceMapsType,
+    resolve_conditions: Vec<(RcStr, Vec<RcStr>)>,
 ) -> Vc<Box<dyn AssetContext>> {
     let resolve_options_context =
         get_client_resolve_options_context(project_path.clone(), node_env);
@@ -172,6 +192,7 @@ pub fn get_client_asset_context(
         compile_time_info.environment(),
         node_env,
         source_maps_type,
+        resolve_conditions,
     );
 
     let asset_context: Vc<Box<dyn AssetContext>> = Vc::upcast(ModuleAssetContext::new(
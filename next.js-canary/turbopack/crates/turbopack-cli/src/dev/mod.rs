@@ -13,7 +13,10 @@ use owo_colors::OwoColorize;
 use rustc_hash::FxHashSet;
 use turbo_rcstr::{RcStr, rcstr};
 use turbo_tasks::{
-    NonLocalValue, OperationVc, ResolvedVc, TransientInstance, TurboTasks, UpdateInfo, Vc,
+    FxIndexMap, NonLocalValue, OperationVc, ResolvedVc, TransientInstance, TurboTasks,
+    TurboTasksApi, UpdateInfo, Vc,
+    invalidation_chain::InvalidatedExecution,
+    replay,
     trace::TraceRawVcs,
     util::{FormatBytes, FormatDuration},
 };
@@ -69,6 +72,7 @@ pub struct TurbopackDevServerBuilder {
     show_all: bool,
     log_detail: bool,
     allow_retry: bool,
+    resolve_conditions: Vec<(RcStr, Vec<RcStr>)>,
 }
 
 impl TurbopackDevServerBuilder {
@@ -93,6 +97,7 @@ impl TurbopackDevServerBuilder {
             show_all: false,
             log_detail: false,
             allow_retry: false,
+            resolve_conditions: vec![],
         }
     }
 
@@ -126,6 +131,14 @@ impl TurbopackDevServerBuilder {
         self
     }
 
+    pub fn resolve_conditions(
+        mut self,
+        resolve_conditions: Vec<(RcStr, Vec<RcStr>)>,
+    ) -> TurbopackDevServerBuilder {
+        self.resolve_conditions = resolve_conditions;
+        self
+    }
+
     pub fn show_all(mut self, show_all: bool) -> TurbopackDevServerBuilder {
         self.show_all = show_all;
         self
@@ -203,6 +216,7 @@ impl TurbopackDevServerBuilder {
         let show_all = self.show_all;
         let log_detail: bool = self.log_detail;
         let browserslist_query: RcStr = self.browserslist_query;
+        let resolve_conditions = self.resolve_conditions;
         let log_args = TransientInstance::new(LogOptions {
             current_dir: current_dir().unwrap(),
             project_dir: PathBuf::from(project_dir.clone()),
@@ -224,6 +238,7 @@ impl TurbopackDevServerBuilder {
             entry_requests: Arc<Vec<EntryRequest>>,
             eager_compile: bool,
             browserslist_query: RcStr,
+            resolve_conditions: Vec<(RcStr, Vec<RcStr>)>,
         }
         impl SourceProvider for ServerSourceProvider {
             fn get_source(&self) -> OperationVc<Box<dyn ContentSource>> {
@@ -233,6 +248,7 @@ impl TurbopackDevServerBuilder {
                     self.entry_requests.clone(),
                     self.eager_compile,
                     self.browserslist_query.clone(),
+                    self.resolve_conditions.clone(),
                 )
             }
         }
@@ -242,6 +258,7 @@ impl TurbopackDevServerBuilder {
             entry_requests,
             eager_compile,
             browserslist_query,
+            resolve_conditions,
         };
 
         let issue_reporter_arc = Arc::new(move || issue_provider.get_issue_reporter());
@@ -256,6 +273,7 @@ async fn source(
     entry_requests: Arc<Vec<EntryRequest>>,
     eager_compile: bool,
     browserslist_query: RcStr,
+    resolve_conditions: Vec<(RcStr, Vec<RcStr>)>,
 ) -> Result<Vc<Box<dyn ContentSource>>> {
     let project_relative = project_dir.strip_prefix(&*root_dir).unwrap();
     let project_relative: RcStr = project_relative
@@ -324,6 +342,7 @@ async fn source(
         NodeEnv::Development.cell(),
         Default::default(),
         browserslist_query,
+        resolve_conditions,
     )
     .to_resolved()
     .await?;
@@ -362,6 +381,10 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
         root_dir,
     } = normalize_dirs(&args.common.dir, &args.common.root)?;
 
+    if let Some(record_session) = &args.common.record_session {
+        replay::start_recording(record_session)?;
+    }
+
     let tt = TurboTasks::new(TurboTasksBackend::new(
         BackendOptions {
             storage_mode: None,
@@ -370,6 +393,10 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
         noop_backing_storage(),
     ));
 
+    let invalidation_chains = args
+        .explain_invalidations
+        .then(|| tt.invalidation_chains().enable().clone());
+
     let tt_clone = tt.clone();
 
     let mut server = TurbopackDevServerBuilder::new(tt, project_dir, root_dir)
@@ -378,6 +405,7 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
         .port(args.port)
         .log_detail(args.common.log_detail)
         .show_all(args.common.show_all)
+        .resolve_conditions(args.common.resolve_conditions.clone())
         .log_level(
             args.common
                 .log_level
@@ -484,6 +512,14 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
                         }
                     }
                 }
+                if let Some(invalidation_chains) = &invalidation_chains {
+                    print_invalidation_chains(&invalidation_chains.take_recent_executions());
+                }
+                if let Some(recorder) = replay::recorder()
+                    && let Err(err) = recorder.flush()
+                {
+                    println!("{} - {err:?}", "error".red());
+                }
             } else {
                 progress_counter += 1;
                 if args.common.log_detail {
@@ -508,6 +544,32 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
     Ok(())
 }
 
+/// Prints the invalidations that caused the re-executions of an update, each with the longest
+/// chain of tasks it passed through.
+fn print_invalidation_chains(executions: &[InvalidatedExecution]) {
+    let mut by_root: FxIndexMap<String, (usize, &InvalidatedExecution)> = FxIndexMap::default();
+    for execution in executions {
+        let (count, longest) = by_root
+            .entry(execution.chain.root.to_string())
+            .or_insert((0, execution));
+        *count += 1;
+        if execution.chain.steps.len() > longest.chain.steps.len() {
+            *longest = execution;
+        }
+    }
+    for (root, (count, longest)) in by_root {
+        println!(
+            "{event_type} - {count} tasks re-executed because of {root}",
+            event_type = "event".purple(),
+        );
+        println!(
+            "  {chain}\n  -> {task} (re-executed)",
+            chain = longest.chain,
+            task = longest.description
+        );
+    }
+}
+
 #[cfg(feature = "profile")]
 // When profiling, exits the process when no new updates have been received for
 // a given timeout and there are no more tasks in progress.
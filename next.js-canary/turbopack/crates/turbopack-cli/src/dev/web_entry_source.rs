@@ -109,6 +109,7 @@ pub async fn create_web_entry_source(
     node_env: Vc<NodeEnv>,
     source_maps_type: SourceMapsType,
     browserslist_query: RcStr,
+    resolve_conditions: Vec<(RcStr, Vec<RcStr>)>,
 ) -> Result<Vc<Box<dyn ContentSource>>> {
     let compile_time_info = get_client_compile_time_info(browserslist_query, node_env);
     let asset_context = get_client_asset_context(
@@ -117,6 +118,7 @@ pub async fn create_web_entry_source(
         compile_time_info,
         node_env,
         source_maps_type,
+        resolve_conditions,
     );
     let chunking_context = get_client_chunking_context(
         root_path.clone(),
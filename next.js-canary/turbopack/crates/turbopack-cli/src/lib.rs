@@ -8,6 +8,7 @@ pub mod build;
 pub(crate) mod contexts;
 pub mod dev;
 pub(crate) mod embed_js;
+pub mod resolve;
 pub(crate) mod util;
 
 pub fn register() {
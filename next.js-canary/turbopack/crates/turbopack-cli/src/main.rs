@@ -1,18 +1,23 @@
 #![feature(future_join)]
 #![feature(min_specialization)]
 
-use std::{cell::RefCell, path::Path, time::Instant};
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    time::Instant,
+};
 
 use anyhow::{Context, Result};
 use clap::Parser;
 use tracing_subscriber::{Registry, layer::SubscriberExt, util::SubscriberInitExt};
 use turbo_tasks_malloc::TurboMalloc;
 use turbopack_cli::{arguments::Arguments, register};
+use turbopack_trace_server::{ExportFormat, convert_trace_file};
 use turbopack_trace_utils::{
     exit::ExitHandler,
     filter_layer::FilterLayer,
     raw_trace::RawTraceLayer,
-    trace_writer::TraceWriter,
+    trace_writer::{TraceWriter, TraceWriterGuard},
     tracing_presets::{
         TRACING_OVERVIEW_TARGETS, TRACING_TURBO_TASKS_TARGETS, TRACING_TURBOPACK_TARGETS,
     },
@@ -52,8 +57,17 @@ fn main() {
 async fn main_inner(args: Arguments) -> Result<()> {
     let exit_handler = ExitHandler::listen();
 
-    let trace = std::env::var("TURBOPACK_TRACING").ok();
-    if let Some(mut trace) = trace.filter(|v| !v.is_empty()) {
+    let profile = match &args {
+        Arguments::Build(args) => args.profile.clone(),
+        Arguments::Dev(_) | Arguments::Resolve(_) => None,
+    };
+    let trace = std::env::var("TURBOPACK_TRACING")
+        .ok()
+        .filter(|v| !v.is_empty())
+        // Profiling a build records the turbopack preset by default
+        .or_else(|| profile.as_ref().map(|_| "turbopack".to_string()));
+    let mut profile_trace = None;
+    if let Some(mut trace) = trace {
         // Trace presets
         match trace.as_str() {
             "overview" => {
@@ -79,13 +93,26 @@ async fn main_inner(args: Arguments) -> Result<()> {
         std::fs::create_dir_all(&internal_dir)
             .context("Unable to create .turbopack directory")
             .unwrap();
-        let trace_file = internal_dir.join("trace.log");
-        let trace_writer = std::fs::File::create(trace_file).unwrap();
+        let trace_file = match &profile {
+            // The raw trace is converted after the build
+            Some(profile) if ExportFormat::from_path(profile).is_some() => {
+                internal_dir.join("profile.log")
+            }
+            Some(profile) => profile.clone(),
+            None => internal_dir.join("trace.log"),
+        };
+        let trace_writer = std::fs::File::create(&trace_file)
+            .with_context(|| format!("Unable to create trace file {}", trace_file.display()))
+            .unwrap();
         let (trace_writer, guard) = TraceWriter::new(trace_writer);
         let subscriber = subscriber.with(RawTraceLayer::new(trace_writer));
 
-        exit_handler
-            .on_exit(async move { tokio::task::spawn_blocking(|| drop(guard)).await.unwrap() });
+        if profile.is_some() {
+            profile_trace = Some((guard, trace_file));
+        } else {
+            exit_handler
+                .on_exit(async move { tokio::task::spawn_blocking(|| drop(guard)).await.unwrap() });
+        }
 
         subscriber.init();
     }
@@ -93,7 +120,32 @@ async fn main_inner(args: Arguments) -> Result<()> {
     register();
 
     match args {
-        Arguments::Build(args) => turbopack_cli::build::build(&args).await,
+        Arguments::Build(args) => {
+            let result = turbopack_cli::build::build(&args).await;
+            if let (Some(profile), Some((guard, trace_file))) = (args.profile, profile_trace) {
+                write_profile(guard, trace_file, profile).await?;
+            }
+            result
+        }
         Arguments::Dev(args) => turbopack_cli::dev::start_server(&args).await,
+        Arguments::Resolve(args) => turbopack_cli::resolve::resolve(&args).await,
     }
 }
+
+/// Flushes the trace of a profiled build and converts it to the format of the profile file.
+async fn write_profile(
+    guard: TraceWriterGuard,
+    trace_file: PathBuf,
+    profile: PathBuf,
+) -> Result<()> {
+    tokio::task::spawn_blocking(move || {
+        drop(guard);
+        if let Some(format) = ExportFormat::from_path(&profile) {
+            convert_trace_file(&trace_file, &profile, format)
+                .with_context(|| format!("Unable to write profile to {}", profile.display()))?;
+        }
+        println!("Profile written to {}", profile.display());
+        anyhow::Ok(())
+    })
+    .await?
+}
@@ -0,0 +1,164 @@
+use std::path::MAIN_SEPARATOR;
+
+use anyhow::Result;
+use turbo_rcstr::RcStr;
+use turbo_tasks::{TurboTasks, Vc};
+use turbo_tasks_backend::{BackendOptions, TurboTasksBackend, noop_backing_storage};
+use turbo_tasks_fs::FileSystem;
+use turbopack_core::{
+    introspect::Introspectable,
+    reference_type::{CommonJsReferenceSubType, EcmaScriptModulesReferenceSubType, ReferenceType},
+    resolve::{
+        parse::Request,
+        trace::{ResolveTrace, resolve_trace},
+    },
+};
+use turbopack_resolve::{
+    ecmascript::{apply_cjs_specific_options, apply_esm_specific_options},
+    resolve::resolve_options,
+};
+
+use crate::{
+    arguments::ResolveArguments,
+    contexts::{NodeEnv, get_client_resolve_options_context},
+    util::{NormalizedDirs, normalize_dirs, project_fs},
+};
+
+/// Resolves a single request with the resolve options of a client build and prints the steps
+/// taken: the import map entries checked, the `exports`/`imports` conditions evaluated, the paths
+/// probed and the plugins invoked.
+pub async fn resolve(args: &ResolveArguments) -> Result<()> {
+    println!("{}", trace(args).await?);
+    Ok(())
+}
+
+/// Returns the output of [resolve]: the title of the trace, followed by its steps and the result.
+async fn trace(args: &ResolveArguments) -> Result<String> {
+    let NormalizedDirs {
+        project_dir,
+        root_dir,
+    } = normalize_dirs(&args.dir, &args.root)?;
+
+    let tt = TurboTasks::new(TurboTasksBackend::new(
+        BackendOptions {
+            dependency_tracking: false,
+            storage_mode: None,
+            ..Default::default()
+        },
+        noop_backing_storage(),
+    ));
+
+    let specifier: RcStr = args.specifier.as_str().into();
+    let from: RcStr = args.from.replace(MAIN_SEPARATOR, "/").into();
+    let require = args.require;
+    tt.run_once(async move {
+        let trace_op = resolve_trace_operation(project_dir, root_dir, specifier, from, require);
+        trace_op.read_strongly_consistent().await?;
+
+        let trace = Vc::upcast::<Box<dyn Introspectable>>(trace_op.connect());
+        Ok(format!(
+            "{}\n{}",
+            trace.title().await?,
+            trace.details().await?
+        ))
+    })
+    .await
+}
+
+#[turbo_tasks::function(operation)]
+async fn resolve_trace_operation(
+    project_dir: RcStr,
+    root_dir: RcStr,
+    specifier: RcStr,
+    from: RcStr,
+    require: bool,
+) -> Result<Vc<ResolveTrace>> {
+    let project_fs = project_fs(root_dir.clone(), /* watch= */ false);
+    let project_relative = project_dir.strip_prefix(&*root_dir).unwrap();
+    let project_relative = project_relative
+        .strip_prefix(MAIN_SEPARATOR)
+        .unwrap_or(project_relative)
+        .replace(MAIN_SEPARATOR, "/");
+    let project_path = project_fs.root().await?.join(&project_relative)?;
+    let lookup_path = project_path.join(&from)?.parent();
+
+    let resolve_options_context =
+        get_client_resolve_options_context(project_path, NodeEnv::Development.cell());
+    let options = resolve_options(lookup_path.clone(), resolve_options_context);
+    let (reference_type, options) = if require {
+        (
+            ReferenceType::CommonJs(CommonJsReferenceSubType::Undefined),
+            apply_cjs_specific_options(options),
+        )
+    } else {
+        let reference_type =
+            ReferenceType::EcmaScriptModules(EcmaScriptModulesReferenceSubType::Import);
+        let options = apply_esm_specific_options(options, reference_type.clone());
+        (reference_type, options)
+    };
+
+    Ok(resolve_trace(
+        lookup_path,
+        reference_type,
+        Request::parse_string(specifier),
+        options,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use anyhow::{Result, bail};
+    use clap::Parser;
+
+    use super::trace;
+    use crate::arguments::{Arguments, ResolveArguments};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn resolve_subcommand() -> Result<()> {
+        crate::register();
+
+        let project = tempfile::tempdir()?;
+        let root = project.path().canonicalize()?;
+        let package = root.join("node_modules/pkg");
+        fs::create_dir_all(root.join("src"))?;
+        fs::create_dir_all(&package)?;
+        fs::write(root.join("src/index.js"), "import 'pkg'")?;
+        fs::write(
+            package.join("package.json"),
+            r#"{ "name": "pkg", "exports": { "import": "./esm.js", "require": "./cjs.js" } }"#,
+        )?;
+        fs::write(package.join("esm.js"), "export default 1")?;
+        fs::write(package.join("cjs.js"), "module.exports = 1")?;
+
+        let dir = root.to_str().unwrap();
+        let parse = |extra: &[&str]| -> Result<ResolveArguments> {
+            let args = [
+                "turbopack",
+                "resolve",
+                "pkg",
+                "--from",
+                "src/index.js",
+                "--dir",
+                dir,
+            ];
+            match Arguments::try_parse_from(args.iter().chain(extra))? {
+                Arguments::Resolve(args) => Ok(args),
+                args => bail!("unexpected arguments {args:?}"),
+            }
+        };
+
+        let output = trace(&parse(&[])?).await?;
+        assert!(output.starts_with("pkg from [project]/src"), "{output}");
+        assert!(
+            output.contains("exports field of [project]/node_modules/pkg/package.json"),
+            "{output}"
+        );
+        assert!(output.contains("targets: ./esm.js"), "{output}");
+
+        let output = trace(&parse(&["--require"])?).await?;
+        assert!(output.contains("targets: ./cjs.js"), "{output}");
+        Ok(())
+    }
+}
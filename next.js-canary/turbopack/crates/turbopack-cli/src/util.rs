@@ -63,7 +63,7 @@ pub fn normalize_entries(entries: &Option<Vec<String>>) -> Vec<RcStr> {
 pub async fn project_fs(project_dir: RcStr, watch: bool) -> Result<Vc<Box<dyn FileSystem>>> {
     let disk_fs = DiskFileSystem::new(rcstr!("project"), project_dir);
     if watch {
-        disk_fs.await?.start_watching(None).await?;
+        disk_fs.await?.start_watching(Default::default()).await?;
     }
     Ok(Vc::upcast(disk_fs))
 }
@@ -12,7 +12,7 @@ use super::{
 };
 use crate::{
     chunk::{
-        ChunkableModule, ChunkingType,
+        AsyncChunkOptions, ChunkableModule, ChunkingType,
         chunk_item_batch::{ChunkItemBatchGroup, ChunkItemOrBatchWithAsyncModuleInfo},
     },
     environment::ChunkLoading,
@@ -103,9 +103,9 @@ pub async fn make_chunk_group(
     // Insert async chunk loaders for every referenced async module
     let async_loaders = async_modules
         .into_iter()
-        .map(async |module| {
+        .map(async |(module, options)| {
             chunking_context
-                .async_loader_chunk_item(*module, module_graph, availability_info)
+                .async_loader_chunk_item(*module, module_graph, availability_info, options)
                 .to_resolved()
                 .await
         })
@@ -199,7 +199,7 @@ pub async fn chunk_group_content(
     struct TraverseState {
         unsorted_items: ModuleToChunkableMap,
         chunkable_items: FxIndexSet<ChunkableModuleOrBatch>,
-        async_modules: FxIndexSet<ResolvedVc<Box<dyn ChunkableModule>>>,
+        async_modules: FxIndexSet<(ResolvedVc<Box<dyn ChunkableModule>>, AsyncChunkOptions)>,
         traced_modules: FxIndexSet<ResolvedVc<Box<dyn Module>>>,
     }
 
@@ -280,11 +280,15 @@ pub async fn chunk_group_content(
                         GraphTraversalAction::Exclude
                     }
                 }
-                ChunkingType::Async => {
+                ChunkingType::Async(ref options) => {
                     if can_split_async {
                         let chunkable_module = ResolvedVc::try_downcast(edge.module.unwrap())
                             .context("Module in async chunking edge is not chunkable")?;
-                        state.async_modules.insert(chunkable_module);
+                        // Every reference gets a loader for its own options, as the referencing
+                        // code uses the loader id for the options it was created with.
+                        state
+                            .async_modules
+                            .insert((chunkable_module, options.clone()));
                         GraphTraversalAction::Exclude
                     } else if is_available {
                         GraphTraversalAction::Exclude
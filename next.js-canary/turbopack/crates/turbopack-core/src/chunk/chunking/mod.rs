@@ -22,6 +22,7 @@ use crate::{
         chunking::{
             dev::{app_vendors_split, expand_batches},
             production::make_production_chunks,
+            split_rules::split_rules_split,
             style_production::make_style_production_chunks,
         },
     },
@@ -31,6 +32,7 @@ use crate::{
 
 mod dev;
 mod production;
+mod split_rules;
 mod style_production;
 
 #[turbo_tasks::value]
@@ -336,6 +338,21 @@ pub async fn make_chunks(
 
             if let Some(chunking_config) = chunking_configs.get(&ty) {
                 // Production chunking
+                let remaining_chunk_items;
+                let (chunk_items, batch_groups) = if chunking_config.split_rules.is_empty() {
+                    (chunk_items, batch_groups)
+                } else {
+                    let (chunk_items, batch_groups) = split_rules_split(
+                        chunk_items,
+                        batch_groups,
+                        module_graph,
+                        chunking_config,
+                        &mut split_context,
+                    )
+                    .await?;
+                    remaining_chunk_items = chunk_items;
+                    (remaining_chunk_items.iter().collect(), batch_groups)
+                };
                 if *ty.is_style().await? {
                     make_style_production_chunks(
                         chunk_items,
@@ -405,16 +422,28 @@ struct SplitContext<'a> {
 }
 
 /// Creates a chunk with the given `chunk_items. `key` should be unique.
-#[tracing::instrument(level = Level::TRACE, skip_all, fields(key = display(key)))]
 async fn make_chunk<'l>(
     chunk_items: Vec<&'l ChunkItemOrBatchWithInfo>,
     batch_groups: Vec<ResolvedVc<ChunkItemBatchGroup>>,
     key: &mut String,
     split_context: &mut SplitContext<'_>,
+) -> Result<()> {
+    make_named_chunk(chunk_items, batch_groups, key, None, split_context).await
+}
+
+/// Like [make_chunk], but the file name of the chunk is prefixed with `name`.
+#[tracing::instrument(level = Level::TRACE, skip_all, fields(key = display(key)))]
+async fn make_named_chunk<'l>(
+    chunk_items: Vec<&'l ChunkItemOrBatchWithInfo>,
+    batch_groups: Vec<ResolvedVc<ChunkItemBatchGroup>>,
+    key: &mut String,
+    name: Option<RcStr>,
+    split_context: &mut SplitContext<'_>,
 ) -> Result<()> {
     split_context.chunks.push(
         split_context.ty.chunk(
             *split_context.chunking_context,
+            name,
             chunk_items
                 .into_iter()
                 .map(|item| match item {
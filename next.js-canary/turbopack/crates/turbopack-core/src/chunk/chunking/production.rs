@@ -12,7 +12,12 @@ use crate::{
         ChunkItemBatchGroup, ChunkItemWithAsyncModuleInfo, ChunkingConfig,
         chunking::{ChunkItemOrBatchWithInfo, SplitContext, make_chunk},
     },
-    module_graph::{ModuleGraph, chunk_group_info::RoaringBitmapWrapper},
+    module::Module,
+    module_graph::{
+        ModuleGraph,
+        chunk_group_info::{ChunkGroupInfo, RoaringBitmapWrapper},
+        merged_modules::MergedModuleInfo,
+    },
 };
 
 pub async fn make_production_chunks(
@@ -70,21 +75,11 @@ pub async fn make_production_chunks(
                             ..
                         },
                     ..
-                } => Some(
-                    chunk_group_info
-                        .module_chunk_groups
-                        .get(&ResolvedVc::upcast(module))
-                        .or_else(|| {
-                            // Merged modules don't have a chunk group in chunk_group_info, so
-                            // lookup using the original module.
-                            merged_modules
-                                .get_original_module(ResolvedVc::upcast(module))
-                                .and_then(|module| {
-                                    chunk_group_info.module_chunk_groups.get(&module)
-                                })
-                        })
-                        .context("every module should have a chunk group")?,
-                ),
+                } => Some(module_chunk_groups(
+                    &chunk_group_info,
+                    &merged_modules,
+                    ResolvedVc::upcast(module),
+                )?),
                 &ChunkItemOrBatchWithInfo::ChunkItem {
                     chunk_item: ChunkItemWithAsyncModuleInfo { module: None, .. },
                     ..
@@ -556,6 +551,25 @@ pub async fn make_production_chunks(
     .await
 }
 
+/// Returns the chunk groups that contain `module`.
+pub(super) fn module_chunk_groups<'a>(
+    chunk_group_info: &'a ChunkGroupInfo,
+    merged_modules: &MergedModuleInfo,
+    module: ResolvedVc<Box<dyn Module>>,
+) -> Result<&'a RoaringBitmapWrapper> {
+    chunk_group_info
+        .module_chunk_groups
+        .get(&module)
+        .or_else(|| {
+            // Merged modules don't have a chunk group in chunk_group_info, so lookup using the
+            // original module.
+            merged_modules
+                .get_original_module(module)
+                .and_then(|module| chunk_group_info.module_chunk_groups.get(&module))
+        })
+        .context("every module should have a chunk group")
+}
+
 struct ChunkCandidate<'l> {
     size: usize,
     chunk_items: Vec<&'l ChunkItemOrBatchWithInfo>,
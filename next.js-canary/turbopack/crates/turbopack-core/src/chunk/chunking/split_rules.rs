@@ -0,0 +1,283 @@
+use std::cmp::Reverse;
+
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+use rustc_hash::FxHashSet;
+use tracing::Level;
+use turbo_tasks::{FxIndexMap, FxIndexSet, ResolvedVc, TryJoinIterExt, Vc};
+use turbo_tasks_fs::glob::Glob;
+
+use crate::{
+    chunk::{
+        ChunkItemWithAsyncModuleInfo, ChunkSplitRule, ChunkSplitTest, ChunkableModule,
+        ChunkingConfig,
+        chunk_item_batch::{ChunkItemBatchGroup, ChunkItemOrBatchWithAsyncModuleInfo},
+        chunking::{
+            ChunkItemOrBatchWithInfo, SplitContext, dev::expand_batches, make_named_chunk,
+            production::module_chunk_groups,
+        },
+    },
+    module_graph::ModuleGraph,
+};
+
+enum Matcher {
+    Glob(Glob),
+    Regex(Regex),
+}
+
+impl Matcher {
+    fn new(test: &ChunkSplitTest) -> Result<Self> {
+        Ok(match test {
+            ChunkSplitTest::Glob(glob) => Matcher::Glob(Glob::parse(glob)?),
+            ChunkSplitTest::Regex(regex) => Matcher::Regex(Regex::new(regex)?),
+        })
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        match self {
+            Matcher::Glob(glob) => glob.matches(path),
+            Matcher::Regex(regex) => regex.is_match(path),
+        }
+    }
+}
+
+/// Creates the chunks of the [ChunkingConfig::split_rules]. Returns the chunk items and batch
+/// groups that don't belong to a rule, which are left to the heuristics. Rules match individual
+/// modules, so only the batches that contain a matched module are expanded.
+#[tracing::instrument(level = Level::TRACE, skip_all)]
+pub async fn split_rules_split(
+    chunk_items: Vec<&ChunkItemOrBatchWithInfo>,
+    batch_groups: FxIndexSet<ResolvedVc<ChunkItemBatchGroup>>,
+    module_graph: Vc<ModuleGraph>,
+    chunking_config: &ChunkingConfig,
+    split_context: &mut SplitContext<'_>,
+) -> Result<(
+    Vec<ChunkItemOrBatchWithInfo>,
+    FxIndexSet<ResolvedVc<ChunkItemBatchGroup>>,
+)> {
+    let mut rules = chunking_config
+        .split_rules
+        .iter()
+        .map(|rule| {
+            let matcher = Matcher::new(&rule.test)
+                .with_context(|| format!("invalid test in chunk split rule {}", rule.name))?;
+            Ok((rule, matcher))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    // The sort is stable, so rules with the same priority keep their order
+    rules.sort_by_key(|(rule, _)| Reverse(rule.priority));
+
+    // The rule indices of the chunk items, or of all chunk items of a batch
+    let rule_indices = chunk_items
+        .iter()
+        .copied()
+        .map(async |item| {
+            Ok(match item {
+                ChunkItemOrBatchWithInfo::ChunkItem { chunk_item, .. } => {
+                    vec![rule_index(&rules, chunk_item.module).await?]
+                }
+                &ChunkItemOrBatchWithInfo::Batch { batch, .. } => {
+                    batch
+                        .await?
+                        .chunk_items
+                        .iter()
+                        .map(|chunk_item| rule_index(&rules, chunk_item.module))
+                        .try_join()
+                        .await?
+                }
+            })
+        })
+        .try_join()
+        .await?;
+
+    // Expanded batches and chunk items moved into rule chunks. Batch groups that contain them are
+    // not passed to the heuristics.
+    let mut moved = FxHashSet::default();
+    let mut matched = vec![Vec::new(); rules.len()];
+    let mut remaining = Vec::new();
+    for (item, rule_indices) in chunk_items.into_iter().zip(rule_indices) {
+        let expanded = match item {
+            ChunkItemOrBatchWithInfo::ChunkItem { .. } => vec![item.clone()],
+            &ChunkItemOrBatchWithInfo::Batch { batch, .. } => {
+                if rule_indices.iter().all(Option::is_none) {
+                    remaining.push(item.clone());
+                    continue;
+                }
+                moved.insert(ChunkItemOrBatchWithAsyncModuleInfo::Batch(batch));
+                expand_batches(vec![item], split_context.ty, split_context.chunking_context).await?
+            }
+        };
+        for (item, rule_index) in expanded.into_iter().zip(rule_indices) {
+            match rule_index {
+                Some(index) => matched[index].push(item),
+                None => remaining.push(item),
+            }
+        }
+    }
+
+    let chunk_count_before = split_context.chunks.len();
+    for ((rule, _), mut chunk_items) in rules.into_iter().zip(matched) {
+        if chunk_items.is_empty() {
+            continue;
+        }
+        if !rule.enforce {
+            let size = chunk_items.iter().map(|item| item.size()).sum::<usize>();
+            let max_chunk_count = chunking_config.max_chunk_count_per_group;
+            let too_many_chunks = max_chunk_count != 0
+                && split_context.chunks.len() - chunk_count_before >= max_chunk_count;
+            if size < rule.min_size || too_many_chunks {
+                remaining.extend(chunk_items);
+                continue;
+            }
+        }
+
+        for item in &chunk_items {
+            if let ChunkItemOrBatchWithInfo::ChunkItem { chunk_item, .. } = item {
+                moved.insert(ChunkItemOrBatchWithAsyncModuleInfo::ChunkItem(
+                    chunk_item.clone(),
+                ));
+            }
+        }
+
+        // Sort by ident so the chunks only depend on the matched modules
+        chunk_items.sort_by(|a, b| asset_ident(a).cmp(asset_ident(b)));
+
+        if rule.reuse_existing {
+            let chunk_group_info = module_graph.chunk_group_info().await?;
+            let merged_modules = module_graph.merged_modules().await?;
+            let mut by_chunk_groups = FxIndexMap::<_, Vec<_>>::default();
+            for item in &chunk_items {
+                let ChunkItemOrBatchWithInfo::ChunkItem {
+                    chunk_item:
+                        ChunkItemWithAsyncModuleInfo {
+                            module: Some(module),
+                            ..
+                        },
+                    ..
+                } = item
+                else {
+                    bail!("only chunk items with a module are matched by split rules");
+                };
+                let chunk_groups = module_chunk_groups(
+                    &chunk_group_info,
+                    &merged_modules,
+                    ResolvedVc::upcast(*module),
+                )?;
+                by_chunk_groups.entry(chunk_groups).or_default().push(item);
+            }
+            for (i, chunk_items) in by_chunk_groups.into_values().enumerate() {
+                make_rule_chunks(
+                    chunk_items,
+                    rule,
+                    format!("{}-{i}", rule.name),
+                    split_context,
+                )
+                .await?;
+            }
+        } else {
+            make_rule_chunks(
+                chunk_items.iter().collect(),
+                rule,
+                rule.name.to_string(),
+                split_context,
+            )
+            .await?;
+        }
+    }
+
+    let batch_groups = if moved.is_empty() {
+        batch_groups
+    } else {
+        let batch_group_read_refs = batch_groups.iter().try_join().await?;
+        batch_groups
+            .into_iter()
+            .zip(batch_group_read_refs)
+            .filter(|(_, batch_group)| !batch_group.items.iter().any(|item| moved.contains(item)))
+            .map(|(batch_group, _)| batch_group)
+            .collect()
+    };
+
+    Ok((remaining, batch_groups))
+}
+
+/// Returns the index of the first rule that matches the path of `module`.
+async fn rule_index(
+    rules: &[(&ChunkSplitRule, Matcher)],
+    module: Option<ResolvedVc<Box<dyn ChunkableModule>>>,
+) -> Result<Option<usize>> {
+    // Async module loaders are specific to the chunk group, they are never moved
+    let Some(module) = module else {
+        return Ok(None);
+    };
+    // Rules match the module path, the ident would also contain the layer and modifiers
+    let path = module.ident().path().await?.value_to_string().await?;
+    Ok(rules.iter().position(|(_, matcher)| matcher.matches(&path)))
+}
+
+/// Creates chunks for the sorted `chunk_items` of a rule, respecting its `max_size`.
+async fn make_rule_chunks(
+    chunk_items: Vec<&ChunkItemOrBatchWithInfo>,
+    rule: &ChunkSplitRule,
+    name: String,
+    split_context: &mut SplitContext<'_>,
+) -> Result<()> {
+    let mut parts = vec![Vec::new()];
+    let mut part_size = 0;
+    for item in chunk_items {
+        let size = item.size();
+        let current = parts.last_mut().unwrap();
+        if rule.max_size != 0 && !current.is_empty() && part_size + size > rule.max_size {
+            parts.push(vec![item]);
+            part_size = size;
+        } else {
+            current.push(item);
+            part_size += size;
+        }
+    }
+    let single_part = parts.len() == 1;
+    for (i, part) in parts.into_iter().enumerate() {
+        let mut key = if single_part {
+            name.clone()
+        } else {
+            format!("{name}-{i}")
+        };
+        let name = key.clone().into();
+        make_named_chunk(part, Vec::new(), &mut key, Some(name), split_context).await?;
+    }
+    Ok(())
+}
+
+fn asset_ident(item: &ChunkItemOrBatchWithInfo) -> &str {
+    match item {
+        ChunkItemOrBatchWithInfo::ChunkItem { asset_ident, .. } => asset_ident,
+        ChunkItemOrBatchWithInfo::Batch { .. } => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Matcher;
+    use crate::chunk::ChunkSplitTest;
+
+    #[test]
+    fn test_matcher() {
+        let path = "[project]/node_modules/react/index.js";
+
+        let glob = Matcher::new(&ChunkSplitTest::Glob("**/node_modules/react/**".into())).unwrap();
+        assert!(glob.matches(path));
+        let glob =
+            Matcher::new(&ChunkSplitTest::Glob("**/node_modules/react/*.js".into())).unwrap();
+        assert!(glob.matches(path));
+        let glob = Matcher::new(&ChunkSplitTest::Glob("**/node_modules/lodash/**".into())).unwrap();
+        assert!(!glob.matches(path));
+
+        let regex = Matcher::new(&ChunkSplitTest::Regex(
+            r"/node_modules/(react|react-dom)/".into(),
+        ))
+        .unwrap();
+        assert!(regex.matches(path));
+        let regex = Matcher::new(&ChunkSplitTest::Regex(r"\.js$".into())).unwrap();
+        assert!(regex.matches(path));
+        assert!(Matcher::new(&ChunkSplitTest::Regex("(".into())).is_err());
+    }
+}
@@ -2,11 +2,15 @@ use anyhow::Result;
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use turbo_rcstr::RcStr;
-use turbo_tasks::{NonLocalValue, ResolvedVc, TaskInput, Upcast, Vc, trace::TraceRawVcs};
+use turbo_tasks::{
+    NonLocalValue, OperationValue, ResolvedVc, TaskInput, Upcast, Vc, trace::TraceRawVcs,
+};
 use turbo_tasks_fs::FileSystemPath;
 use turbo_tasks_hash::DeterministicHash;
 
-use super::{ChunkableModule, EvaluatableAssets, availability_info::AvailabilityInfo};
+use super::{
+    AsyncChunkOptions, ChunkableModule, EvaluatableAssets, availability_info::AvailabilityInfo,
+};
 use crate::{
     asset::Asset,
     chunk::{ChunkItem, ChunkType, ModuleId},
@@ -124,10 +128,88 @@ pub struct ChunkingConfig {
     /// This makes sure that code in big chunks is not duplicated in multiple chunks.
     pub max_merge_chunk_size: usize,
 
+    /// Rules that move matching modules into their own chunks before the heuristics above are
+    /// applied, similar to webpack's `splitChunks.cacheGroups`. This keeps e.g. a design system
+    /// or large vendor packages in chunks that stay stable across deployments.
+    pub split_rules: Vec<ChunkSplitRule>,
+
     #[allow(dead_code)]
     pub placeholder_for_future_extensions: (),
 }
 
+/// A rule of [ChunkingConfig::split_rules]. Every module is assigned to the matching rule with
+/// the highest priority.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    TraceRawVcs,
+    NonLocalValue,
+    OperationValue,
+    TaskInput,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkSplitRule {
+    /// The name of the rule. It's used as prefix for the file names of the chunks it creates.
+    pub name: RcStr,
+
+    /// Matches the paths of the modules that belong to this rule.
+    pub test: ChunkSplitTest,
+
+    /// When a module matches multiple rules, the rule with the highest priority wins. Rules with
+    /// the same priority are applied in the configured order.
+    #[serde(default)]
+    pub priority: i32,
+
+    /// Only creates chunks when the matched modules of a chunk group have at least this size.
+    /// Otherwise they are left to the heuristics.
+    #[serde(default)]
+    pub min_size: usize,
+
+    /// Splits the matched modules into multiple chunks that are smaller than this size (unless a
+    /// single module is bigger). 0 means no limit.
+    #[serde(default)]
+    pub max_size: usize,
+
+    /// Splits the matched modules by the chunk groups they are shared with, so chunks that are
+    /// needed by multiple chunk groups are reused instead of placing the same modules into
+    /// differently composed chunks.
+    #[serde(default)]
+    pub reuse_existing: bool,
+
+    /// Always creates chunks for this rule, ignoring `min_size` and
+    /// [ChunkingConfig::max_chunk_count_per_group].
+    #[serde(default)]
+    pub enforce: bool,
+}
+
+/// How a [ChunkSplitRule] matches module paths, e.g. `[project]/node_modules/react/index.js`.
+/// Layers and modifiers of the module are not part of the path.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    TraceRawVcs,
+    NonLocalValue,
+    OperationValue,
+    TaskInput,
+)]
+#[serde(tag = "type", content = "value", rename_all = "camelCase")]
+pub enum ChunkSplitTest {
+    /// A glob that has to match the whole path, e.g. `**/node_modules/react*/**`.
+    Glob(RcStr),
+    /// A regular expression that has to match a part of the path, e.g. `/node_modules/react/`.
+    Regex(RcStr),
+}
+
 #[turbo_tasks::value(transparent)]
 pub struct ChunkingConfigs(FxHashMap<ResolvedVc<Box<dyn ChunkType>>, ChunkingConfig>);
 
@@ -159,6 +241,16 @@ pub trait ChunkingContext {
     #[turbo_tasks::function]
     fn chunk_root_path(self: Vc<Self>) -> Vc<FileSystemPath>;
 
+    /// The folder where the bootstrap scripts of service workers are placed, as
+    /// `<file stem of the worker>.js`. Their paths must not change between builds, so they don't
+    /// contain a hash. A service worker can only control the pages below the folder of its
+    /// script, unless the script is served with a `Service-Worker-Allowed` header that allows a
+    /// broader scope, e.g. `Service-Worker-Allowed: /`. Defaults to the output root.
+    #[turbo_tasks::function]
+    fn service_worker_root_path(self: Vc<Self>) -> Vc<FileSystemPath> {
+        self.output_root()
+    }
+
     // TODO(alexkirsz) Remove this from the chunking context. This should be at the
     // discretion of chunking context implementors. However, we currently use this
     // in a couple of places in `turbopack-css`, so we need to remove that
@@ -229,6 +321,13 @@ pub trait ChunkingContext {
         Vc::cell(false)
     }
 
+    /// Whether the bootstrap script of a service worker defines a precache manifest
+    /// (`self.__TURBOPACK_PRECACHE_MANIFEST`) that lists the URLs of the assets emitted for it.
+    #[turbo_tasks::function]
+    fn is_service_worker_precache_manifest_enabled(self: Vc<Self>) -> Vc<bool> {
+        Vc::cell(false)
+    }
+
     #[turbo_tasks::function]
     fn minify_type(self: Vc<Self>) -> Vc<MinifyType> {
         MinifyType::NoMinify.cell()
@@ -240,9 +339,14 @@ pub trait ChunkingContext {
         module: Vc<Box<dyn ChunkableModule>>,
         module_graph: Vc<ModuleGraph>,
         availability_info: AvailabilityInfo,
+        options: AsyncChunkOptions,
     ) -> Vc<Box<dyn ChunkItem>>;
     #[turbo_tasks::function]
-    fn async_loader_chunk_item_id(&self, module: Vc<Box<dyn ChunkableModule>>) -> Vc<ModuleId>;
+    fn async_loader_chunk_item_id(
+        &self,
+        module: Vc<Box<dyn ChunkableModule>>,
+        options: AsyncChunkOptions,
+    ) -> Vc<ModuleId>;
 
     #[turbo_tasks::function]
     fn chunk_group(
@@ -253,6 +357,21 @@ pub trait ChunkingContext {
         availability_info: AvailabilityInfo,
     ) -> Vc<ChunkGroupResult>;
 
+    /// Like [ChunkingContext::chunk_group], but the chunks are named after `name`, e.g. from a
+    /// `webpackChunkName` comment. Chunking contexts that don't support named chunks ignore the
+    /// name.
+    #[turbo_tasks::function]
+    fn named_chunk_group(
+        self: Vc<Self>,
+        _name: RcStr,
+        ident: Vc<AssetIdent>,
+        chunk_group: ChunkGroup,
+        module_graph: Vc<ModuleGraph>,
+        availability_info: AvailabilityInfo,
+    ) -> Vc<ChunkGroupResult> {
+        self.chunk_group(ident, chunk_group, module_graph, availability_info)
+    }
+
     #[turbo_tasks::function]
     fn evaluated_chunk_group(
         self: Vc<Self>,
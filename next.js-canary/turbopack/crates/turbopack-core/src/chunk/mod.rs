@@ -28,8 +28,9 @@ pub use self::{
         ChunkItemOrBatchWithAsyncModuleInfo, batch_info,
     },
     chunking_context::{
-        ChunkGroupResult, ChunkGroupType, ChunkingConfig, ChunkingConfigs, ChunkingContext,
-        ChunkingContextExt, EntryChunkGroupResult, MangleType, MinifyType, SourceMapsType,
+        ChunkGroupResult, ChunkGroupType, ChunkSplitRule, ChunkSplitTest, ChunkingConfig,
+        ChunkingConfigs, ChunkingContext, ChunkingContextExt, EntryChunkGroupResult, MangleType,
+        MinifyType, SourceMapsType,
     },
     data::{ChunkData, ChunkDataOption, ChunksData},
     evaluate::{EvaluatableAsset, EvaluatableAssetExt, EvaluatableAssets},
@@ -283,7 +284,7 @@ pub enum ChunkingType {
     },
     /// An async loader is placed into the referencing chunk and loads the
     /// separate chunk group in which the module is placed.
-    Async,
+    Async(AsyncChunkOptions),
     /// Create a new chunk group in a separate context, merging references with the same tag into a
     /// single chunk group. It does not inherit the available modules from the parent.
     // TODO this is currently skipped in chunking
@@ -302,6 +303,76 @@ pub enum ChunkingType {
     Traced,
 }
 
+/// Options for the chunk group created by a [ChunkingType::Async] reference, e.g. from
+/// webpack-style magic comments on `import()`.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Hash,
+    TraceRawVcs,
+    Serialize,
+    Deserialize,
+    Eq,
+    PartialEq,
+    ValueDebugFormat,
+    NonLocalValue,
+    TaskInput,
+)]
+pub struct AsyncChunkOptions {
+    /// A name for the chunk group. Chunking contexts use it as a prefix for the file names of the
+    /// chunks, see [ChunkingContext::named_chunk_group].
+    pub name: Option<RcStr>,
+    /// A resource hint for the chunks of the chunk group, emitted when the referencing module is
+    /// evaluated.
+    pub hint: Option<ChunkLoadingHint>,
+}
+
+impl AsyncChunkOptions {
+    /// Adds the options as modifiers to `ident`, so that loaders of the same module with different
+    /// options get different idents.
+    pub fn apply_to_ident(&self, mut ident: Vc<AssetIdent>) -> Vc<AssetIdent> {
+        if let Some(name) = &self.name {
+            ident = ident.with_modifier(format!("chunk name {name}").into());
+        }
+        if let Some(hint) = self.hint {
+            ident = ident.with_modifier(hint.as_str().into());
+        }
+        ident
+    }
+}
+
+/// A resource hint that tells the browser to fetch chunks before they are loaded.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Hash,
+    TraceRawVcs,
+    Serialize,
+    Deserialize,
+    Eq,
+    PartialEq,
+    ValueDebugFormat,
+    NonLocalValue,
+    TaskInput,
+)]
+pub enum ChunkLoadingHint {
+    /// The chunks are likely needed for a future navigation (`<link rel="prefetch">`).
+    Prefetch,
+    /// The chunks are needed during the current navigation (`<link rel="preload">`).
+    Preload,
+}
+
+impl ChunkLoadingHint {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChunkLoadingHint::Prefetch => "prefetch",
+            ChunkLoadingHint::Preload => "preload",
+        }
+    }
+}
+
 impl Display for ChunkingType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -314,7 +385,12 @@ impl Display for ChunkingType {
                     "Parallel(inherit_async: {inherit_async}, hoisted: {hoisted})",
                 )
             }
-            ChunkingType::Async => write!(f, "Async"),
+            ChunkingType::Async(AsyncChunkOptions {
+                name: Some(name), ..
+            }) => {
+                write!(f, "Async(name: {name})")
+            }
+            ChunkingType::Async(_) => write!(f, "Async"),
             ChunkingType::Isolated {
                 _ty,
                 merge_tag: Some(merge_tag),
@@ -384,7 +460,7 @@ impl ChunkingType {
                 hoisted: *hoisted,
                 inherit_async: false,
             },
-            ChunkingType::Async => ChunkingType::Async,
+            ChunkingType::Async(options) => ChunkingType::Async(options.clone()),
             ChunkingType::Isolated { _ty, merge_tag } => ChunkingType::Isolated {
                 _ty: *_ty,
                 merge_tag: merge_tag.clone(),
@@ -429,7 +505,8 @@ pub trait ChunkableModuleReference: ModuleReference + ValueToString {
 pub struct ChunkGroupContent {
     pub chunkable_items: FxIndexSet<ChunkableModuleOrBatch>,
     pub batch_groups: FxIndexSet<ResolvedVc<ModuleBatchGroup>>,
-    pub async_modules: FxIndexSet<ResolvedVc<Box<dyn ChunkableModule>>>,
+    /// The modules referenced by async references, once for every distinct set of options.
+    pub async_modules: FxIndexSet<(ResolvedVc<Box<dyn ChunkableModule>>, AsyncChunkOptions)>,
     pub traced_modules: FxIndexSet<ResolvedVc<Box<dyn Module>>>,
     pub availability_info: AvailabilityInfo,
 }
@@ -474,11 +551,13 @@ pub trait ChunkType: ValueToString {
     #[turbo_tasks::function]
     fn is_style(self: Vc<Self>) -> Vc<bool>;
 
-    /// Create a new chunk for the given chunk items
+    /// Create a new chunk for the given chunk items. `name` is used as a prefix for the file name
+    /// of the chunk, e.g. for the chunks of a [ChunkSplitRule].
     #[turbo_tasks::function]
     fn chunk(
         &self,
         chunking_context: Vc<Box<dyn ChunkingContext>>,
+        name: Option<RcStr>,
         chunk_items: Vec<ChunkItemOrBatchWithAsyncModuleInfo>,
         batch_groups: Vec<ResolvedVc<ChunkItemBatchGroup>>,
         referenced_output_assets: Vc<OutputAssets>,
@@ -11,6 +11,7 @@ use crate::{
     chunk::{ChunkableModuleReference, ChunkingType},
     output::OutputAssets,
     reference::{ModuleReference, ModuleReferences},
+    resolve::trace::ResolveTracedReference,
 };
 
 fn reference_ty() -> RcStr {
@@ -41,6 +42,10 @@ fn traced_reference_ty() -> RcStr {
     rcstr!("traced reference")
 }
 
+fn resolve_trace_ty() -> RcStr {
+    rcstr!("resolve trace")
+}
+
 #[turbo_tasks::function]
 pub async fn content_to_details(content: Vc<AssetContent>) -> Result<Vc<RcStr>> {
     Ok(match &*content.await? {
@@ -80,7 +85,7 @@ pub async fn children_from_module_references(
                         parallel_reference_ty()
                     }
                 }
-                Some(ChunkingType::Async) => async_reference_ty(),
+                Some(ChunkingType::Async(_)) => async_reference_ty(),
                 Some(ChunkingType::Isolated { .. }) => isolated_reference_ty(),
                 Some(ChunkingType::Shared { .. }) => shared_reference_ty(),
                 Some(ChunkingType::Traced) => traced_reference_ty(),
@@ -115,6 +120,13 @@ pub async fn children_from_module_references(
                     .await?,
             ));
         }
+        if let Some(traced) = ResolvedVc::try_sidecast::<Box<dyn ResolveTracedReference>>(reference)
+        {
+            children.insert((
+                resolve_trace_ty(),
+                ResolvedVc::upcast(traced.resolve_trace().to_resolved().await?),
+            ));
+        }
     }
     Ok(Vc::cell(children))
 }
@@ -479,9 +479,9 @@ pub async fn compute_chunk_group_info(graph: &ModuleGraph) -> Result<Vc<ChunkGro
                             ChunkingType::Parallel { .. } => {
                                 ChunkGroupInheritance::Inherit(parent.module)
                             }
-                            ChunkingType::Async => ChunkGroupInheritance::ChunkGroup(Either::Left(
-                                std::iter::once(ChunkGroupKey::Async(node.module)),
-                            )),
+                            ChunkingType::Async(_) => ChunkGroupInheritance::ChunkGroup(
+                                Either::Left(std::iter::once(ChunkGroupKey::Async(node.module))),
+                            ),
                             ChunkingType::Isolated {
                                 merge_tag: None, ..
                             } => ChunkGroupInheritance::ChunkGroup(Either::Left(std::iter::once(
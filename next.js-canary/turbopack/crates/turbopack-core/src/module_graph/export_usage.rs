@@ -86,6 +86,16 @@ impl ModuleExportUsageInfo {
                 l.insert(r.clone());
             }
 
+            (Self::Evaluation, ExportUsage::PartialNamespaceObject(names)) => {
+                *self = Self::Exports(names.iter().cloned().collect());
+            }
+
+            (Self::Exports(l), ExportUsage::PartialNamespaceObject(r)) => {
+                for name in r {
+                    l.insert(name.clone());
+                }
+            }
+
             (_, ExportUsage::Evaluation) => {
                 // Ignore evaluation
             }
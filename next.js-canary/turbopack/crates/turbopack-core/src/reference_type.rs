@@ -60,6 +60,12 @@ pub enum CommonJsReferenceSubType {
 )]
 pub enum ImportWithType {
     Json,
+    /// A CSS module script, the default export is a constructable `CSSStyleSheet`.
+    Css,
+    /// The default export is the content of the file as a string.
+    Text,
+    /// The default export is the content of the file as a `Uint8Array`.
+    Bytes,
 }
 
 #[derive(
@@ -222,7 +228,8 @@ pub enum CssReferenceSubType {
     Compose,
     /// Reference from ModuleCssAsset to the CssModuleAsset
     Internal,
-    /// Used for generating the list of classes in a ModuleCssAsset
+    /// Used for generating the list of classes in a ModuleCssAsset and the style sheet of a CSS
+    /// module script
     Analyze,
     Custom(u8),
     #[default]
@@ -28,7 +28,9 @@ use self::{
     parse::Request,
     pattern::Pattern,
     plugin::BeforeResolvePlugin,
+    pnp::{PnpResolution, read_pnp_manifest},
     remap::{ExportsField, ImportsField},
+    trace::{PluginOutcome, ResolveTraceStep},
 };
 use crate::{
     context::AssetContext,
@@ -58,7 +60,9 @@ pub mod origin;
 pub mod parse;
 pub mod pattern;
 pub mod plugin;
+pub mod pnp;
 pub(crate) mod remap;
+pub mod trace;
 
 pub use alias_map::{
     AliasMap, AliasMapIntoIter, AliasMapLookupIterator, AliasMatch, AliasPattern, AliasTemplate,
@@ -106,6 +110,9 @@ impl ModuleResolveResultItem {
 #[derive(Debug, Clone, Default, Hash)]
 pub enum ExportUsage {
     Named(RcStr),
+    /// Only the listed exports of the namespace object are used, e.g. by a dynamic `import()`
+    /// with a `webpackExports` comment.
+    PartialNamespaceObject(Vec<RcStr>),
     /// This means the whole content of the module is used.
     #[default]
     All,
@@ -117,6 +124,9 @@ impl Display for ExportUsage {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             ExportUsage::Named(name) => write!(f, "export {name}"),
+            ExportUsage::PartialNamespaceObject(names) => {
+                write!(f, "exports {}", names.join(", "))
+            }
             ExportUsage::All => write!(f, "all"),
             ExportUsage::Evaluation => write!(f, "evaluation"),
         }
@@ -139,6 +149,11 @@ impl ExportUsage {
     pub fn named(name: RcStr) -> Vc<Self> {
         Self::Named(name).cell()
     }
+
+    #[turbo_tasks::function]
+    pub fn partial_namespace_object(names: Vec<RcStr>) -> Vc<Self> {
+        Self::PartialNamespaceObject(names).cell()
+    }
 }
 
 #[turbo_tasks::value(shared)]
@@ -563,6 +578,9 @@ impl RequestKey {
 pub struct ResolveResult {
     pub primary: SliceMap<RequestKey, ResolveResultItem>,
     pub affecting_sources: Box<[ResolvedVc<Box<dyn Source>>]>,
+    /// The steps taken to resolve the request, in order. Only recorded when
+    /// [ResolveOptions::trace] is set, see [resolve_trace](trace::resolve_trace).
+    pub trace_steps: Box<[ResolveTraceStep]>,
 }
 
 #[turbo_tasks::value_impl]
@@ -625,6 +643,7 @@ impl ResolveResult {
         ResolveResult {
             primary: Default::default(),
             affecting_sources: Default::default(),
+            trace_steps: Default::default(),
         }
         .resolved_cell()
     }
@@ -635,6 +654,7 @@ impl ResolveResult {
         ResolveResult {
             primary: Default::default(),
             affecting_sources: affecting_sources.into_boxed_slice(),
+            trace_steps: Default::default(),
         }
         .resolved_cell()
     }
@@ -650,6 +670,7 @@ impl ResolveResult {
         ResolveResult {
             primary: vec![(request_key, result)].into_boxed_slice(),
             affecting_sources: Default::default(),
+            trace_steps: Default::default(),
         }
         .resolved_cell()
     }
@@ -662,6 +683,7 @@ impl ResolveResult {
         ResolveResult {
             primary: vec![(request_key, result)].into_boxed_slice(),
             affecting_sources: affecting_sources.into_boxed_slice(),
+            trace_steps: Default::default(),
         }
         .resolved_cell()
     }
@@ -677,6 +699,7 @@ impl ResolveResult {
         ResolveResult {
             primary: vec![(request_key, ResolveResultItem::Source(source))].into_boxed_slice(),
             affecting_sources: Default::default(),
+            trace_steps: Default::default(),
         }
         .resolved_cell()
     }
@@ -689,6 +712,7 @@ impl ResolveResult {
         ResolveResult {
             primary: vec![(request_key, ResolveResultItem::Source(source))].into_boxed_slice(),
             affecting_sources: affecting_sources.into_boxed_slice(),
+            trace_steps: Default::default(),
         }
         .resolved_cell()
     }
@@ -699,6 +723,24 @@ impl ResolveResult {
         self.affecting_sources.iter().copied()
     }
 
+    /// Adds trace steps before and after the steps of `result`, see [ResolveResult::trace_steps].
+    async fn with_trace_steps(
+        result: Vc<ResolveResult>,
+        before: Vec<ResolveTraceStep>,
+        after: Vec<ResolveTraceStep>,
+    ) -> Result<Vc<ResolveResult>> {
+        if before.is_empty() && after.is_empty() {
+            return Ok(result);
+        }
+        let mut result = result.owned().await?;
+        result.trace_steps = before
+            .into_iter()
+            .chain(result.trace_steps.iter().cloned())
+            .chain(after)
+            .collect();
+        Ok(result.cell())
+    }
+
     pub fn is_unresolvable_ref(&self) -> bool {
         self.primary.is_empty()
     }
@@ -788,6 +830,7 @@ impl ResolveResult {
         ResolveResult {
             primary: new_primary,
             affecting_sources: self.affecting_sources.clone(),
+            trace_steps: self.trace_steps.clone(),
         }
     }
 
@@ -810,6 +853,7 @@ impl ResolveResult {
 struct ResolveResultBuilder {
     primary: FxIndexMap<RequestKey, ResolveResultItem>,
     affecting_sources: Vec<ResolvedVc<Box<dyn Source>>>,
+    trace_steps: Vec<ResolveTraceStep>,
 }
 
 impl From<ResolveResultBuilder> for ResolveResult {
@@ -817,6 +861,7 @@ impl From<ResolveResultBuilder> for ResolveResult {
         ResolveResult {
             primary: v.primary.into_iter().collect(),
             affecting_sources: v.affecting_sources.into_boxed_slice(),
+            trace_steps: v.trace_steps.into_boxed_slice(),
         }
     }
 }
@@ -825,6 +870,7 @@ impl From<ResolveResult> for ResolveResultBuilder {
         ResolveResultBuilder {
             primary: IntoIterator::into_iter(v.primary).collect(),
             affecting_sources: v.affecting_sources.into_vec(),
+            trace_steps: v.trace_steps.into_vec(),
         }
     }
 }
@@ -847,6 +893,7 @@ impl ResolveResultBuilder {
                 .filter(|source| !set.contains(source))
                 .copied(),
         );
+        self.trace_steps.extend(other.trace_steps.iter().cloned());
     }
 }
 
@@ -874,6 +921,7 @@ impl ResolveResult {
                 .copied()
                 .chain(std::iter::once(source))
                 .collect(),
+            trace_steps: self.trace_steps.clone(),
         }
         .cell())
     }
@@ -891,18 +939,22 @@ impl ResolveResult {
                 .copied()
                 .chain(sources)
                 .collect(),
+            trace_steps: self.trace_steps.clone(),
         }
         .cell())
     }
 
     /// Returns the first [ResolveResult] that is not
     /// [ResolveResult::Unresolvable] in the given list, while keeping track
-    /// of all the affecting_sources in all the [ResolveResult]s.
+    /// of all the affecting_sources and trace steps in all the [ResolveResult]s.
     #[turbo_tasks::function]
     async fn select_first(results: Vec<Vc<ResolveResult>>) -> Result<Vc<Self>> {
         let mut affecting_sources = vec![];
+        let mut trace_steps = vec![];
         for result in &results {
-            affecting_sources.extend(result.await?.get_affecting_sources());
+            let result = result.await?;
+            affecting_sources.extend(result.get_affecting_sources());
+            trace_steps.extend(result.trace_steps.iter().cloned());
         }
         for result in results {
             let result_ref = result.await?;
@@ -910,13 +962,17 @@ impl ResolveResult {
                 return Ok(Self {
                     primary: result_ref.primary.clone(),
                     affecting_sources: affecting_sources.into_boxed_slice(),
+                    trace_steps: trace_steps.into_boxed_slice(),
                 }
                 .cell());
             }
         }
-        Ok(*ResolveResult::unresolvable_with_affecting_sources(
-            affecting_sources,
-        ))
+        Ok(Self {
+            primary: Default::default(),
+            affecting_sources: affecting_sources.into_boxed_slice(),
+            trace_steps: trace_steps.into_boxed_slice(),
+        }
+        .cell())
     }
 
     #[turbo_tasks::function]
@@ -1032,6 +1088,7 @@ impl ResolveResult {
         Ok(ResolveResult {
             primary: new_primary,
             affecting_sources: self.affecting_sources.clone(),
+            trace_steps: self.trace_steps.clone(),
         }
         .into())
     }
@@ -1069,6 +1126,7 @@ impl ResolveResult {
         Ok(ResolveResult {
             primary: new_primary,
             affecting_sources: self.affecting_sources.clone(),
+            trace_steps: self.trace_steps.clone(),
         }
         .into())
     }
@@ -1093,6 +1151,7 @@ impl ResolveResult {
         ResolveResult {
             primary: new_primary,
             affecting_sources: self.affecting_sources.clone(),
+            trace_steps: self.trace_steps.clone(),
         }
         .into()
     }
@@ -1370,6 +1429,9 @@ enum FindPackageItem {
 struct FindPackageResult {
     packages: Vec<FindPackageItem>,
     affecting_sources: Vec<ResolvedVc<Box<dyn Source>>>,
+    /// Reasons why the package is not accessible, e.g. undeclared Yarn PnP dependencies.
+    errors: Vec<RcStr>,
+    trace_steps: Vec<ResolveTraceStep>,
 }
 
 #[turbo_tasks::function]
@@ -1380,6 +1442,8 @@ async fn find_package(
 ) -> Result<Vc<FindPackageResult>> {
     let mut packages = vec![];
     let mut affecting_sources = vec![];
+    let mut errors = vec![];
+    let mut trace_steps = vec![];
     let options = options.await?;
     for resolve_modules in &options.modules {
         match resolve_modules {
@@ -1393,9 +1457,15 @@ async fn find_package(
                         let fs_path = lookup_path.join(name)?;
                         if let Some(fs_path) = dir_exists(fs_path, &mut affecting_sources).await? {
                             let fs_path = fs_path.join(&package_name.clone())?;
-                            if let Some(fs_path) =
-                                dir_exists(fs_path.clone(), &mut affecting_sources).await?
-                            {
+                            let package_dir =
+                                dir_exists(fs_path.clone(), &mut affecting_sources).await?;
+                            if options.trace {
+                                trace_steps.push(ResolveTraceStep::PackageDirectory {
+                                    path: fs_path,
+                                    found: package_dir.is_some(),
+                                });
+                            }
+                            if let Some(fs_path) = package_dir {
                                 packages.push(FindPackageItem::PackageDirectory(fs_path));
                             }
                         }
@@ -1438,11 +1508,38 @@ async fn find_package(
                     }
                 }
             }
+            ResolveModules::Pnp(root) => {
+                let Some(manifest) = *read_pnp_manifest(root.clone()).await? else {
+                    continue;
+                };
+                let manifest = manifest.await?;
+                affecting_sources.push(ResolvedVc::upcast(
+                    FileSource::new(manifest.path.clone()).to_resolved().await?,
+                ));
+                match manifest.resolve(&lookup_path, &package_name).await? {
+                    PnpResolution::NotCovered => {}
+                    PnpResolution::Found(package_dir) => {
+                        let found = dir_exists(package_dir.clone(), &mut affecting_sources).await?;
+                        if options.trace {
+                            trace_steps.push(ResolveTraceStep::PackageDirectory {
+                                path: package_dir,
+                                found: found.is_some(),
+                            });
+                        }
+                        if let Some(package_dir) = found {
+                            packages.push(FindPackageItem::PackageDirectory(package_dir));
+                        }
+                    }
+                    PnpResolution::Error(error) => errors.push(error),
+                }
+            }
         }
     }
     Ok(FindPackageResult::cell(FindPackageResult {
         packages,
         affecting_sources,
+        errors,
+        trace_steps,
     }))
 }
 
@@ -1578,11 +1675,13 @@ pub async fn resolve_inline(
         )
     };
     async {
+        let mut trace_steps = vec![];
         let before_plugins_result = handle_before_resolve_plugins(
             lookup_path.clone(),
             reference_type.clone(),
             request,
             options,
+            &mut trace_steps,
         )
         .await?;
 
@@ -1594,6 +1693,7 @@ pub async fn resolve_inline(
                     .await?
             }
         };
+        let raw_result = ResolveResult::with_trace_steps(raw_result, trace_steps, vec![]).await?;
 
         let result =
             handle_after_resolve_plugins(lookup_path, reference_type, request, options, raw_result)
@@ -1659,17 +1759,37 @@ async fn handle_before_resolve_plugins(
     reference_type: ReferenceType,
     request: Vc<Request>,
     options: Vc<ResolveOptions>,
+    trace_steps: &mut Vec<ResolveTraceStep>,
 ) -> Result<Option<Vc<ResolveResult>>> {
-    for plugin in &options.await?.before_resolve_plugins {
+    let options = options.await?;
+    for (index, plugin) in options.before_resolve_plugins.iter().enumerate() {
         let condition = plugin.before_resolve_condition().resolve().await?;
         if !*condition.matches(request).await? {
+            if options.trace {
+                trace_steps.push(ResolveTraceStep::BeforeResolvePlugin {
+                    index,
+                    request: request.to_string().owned().await?,
+                    outcome: PluginOutcome::ConditionNotMatched,
+                });
+            }
             continue;
         }
 
-        if let Some(result) = *plugin
+        let result = *plugin
             .before_resolve(lookup_path.clone(), reference_type.clone(), request)
-            .await?
-        {
+            .await?;
+        if options.trace {
+            trace_steps.push(ResolveTraceStep::BeforeResolvePlugin {
+                index,
+                request: request.to_string().owned().await?,
+                outcome: if result.is_some() {
+                    PluginOutcome::Applied
+                } else {
+                    PluginOutcome::NoResult
+                },
+            });
+        }
+        if let Some(result) = result {
             return Ok(Some(*result));
         }
     }
@@ -1690,11 +1810,13 @@ async fn handle_after_resolve_plugins(
         reference_type: ReferenceType,
         request: Vc<Request>,
         options: Vc<ResolveOptions>,
+        trace_steps: &mut Vec<ResolveTraceStep>,
     ) -> Result<Option<Vc<ResolveResult>>> {
-        for plugin in &options.await?.after_resolve_plugins {
+        let options = options.await?;
+        for (index, plugin) in options.after_resolve_plugins.iter().enumerate() {
             let after_resolve_condition = plugin.after_resolve_condition().resolve().await?;
-            if *after_resolve_condition.matches(path.clone()).await?
-                && let Some(result) = *plugin
+            let result = if *after_resolve_condition.matches(path.clone()).await? {
+                *plugin
                     .after_resolve(
                         path.clone(),
                         lookup_path.clone(),
@@ -1702,7 +1824,28 @@ async fn handle_after_resolve_plugins(
                         request,
                     )
                     .await?
-            {
+            } else {
+                if options.trace {
+                    trace_steps.push(ResolveTraceStep::AfterResolvePlugin {
+                        index,
+                        path: path.clone(),
+                        outcome: PluginOutcome::ConditionNotMatched,
+                    });
+                }
+                continue;
+            };
+            if options.trace {
+                trace_steps.push(ResolveTraceStep::AfterResolvePlugin {
+                    index,
+                    path: path.clone(),
+                    outcome: if result.is_some() {
+                        PluginOutcome::Applied
+                    } else {
+                        PluginOutcome::NoResult
+                    },
+                });
+            }
+            if let Some(result) = result {
                 return Ok(Some(*result));
             }
         }
@@ -1714,6 +1857,7 @@ async fn handle_after_resolve_plugins(
 
     let mut new_primary = FxIndexMap::default();
     let mut new_affecting_sources = Vec::new();
+    let mut trace_steps = Vec::new();
 
     for (key, primary) in result_value.primary.iter() {
         if let &ResolveResultItem::Source(source) = primary {
@@ -1724,6 +1868,7 @@ async fn handle_after_resolve_plugins(
                 reference_type.clone(),
                 request,
                 options,
+                &mut trace_steps,
             )
             .await?
             {
@@ -1745,7 +1890,7 @@ async fn handle_after_resolve_plugins(
     }
 
     if !changed {
-        return Ok(result);
+        return ResolveResult::with_trace_steps(result, vec![], trace_steps).await;
     }
 
     let mut affecting_sources = result_value.affecting_sources.to_vec();
@@ -1754,6 +1899,12 @@ async fn handle_after_resolve_plugins(
     Ok(ResolveResult {
         primary: new_primary.into_iter().collect(),
         affecting_sources: affecting_sources.into_boxed_slice(),
+        trace_steps: result_value
+            .trace_steps
+            .iter()
+            .cloned()
+            .chain(trace_steps)
+            .collect(),
     }
     .cell())
 }
@@ -1786,6 +1937,8 @@ async fn resolve_internal_inline(
 
         let request_value = request.await?;
 
+        let mut trace_steps = vec![];
+
         // Apply import mappings if provided
         let mut has_alias = false;
         if let Some(import_map) = &options_value.import_map {
@@ -1798,6 +1951,10 @@ async fn resolve_internal_inline(
                     .await?
                     .lookup(lookup_path.clone(), *request)
                     .await?;
+                if options_value.trace {
+                    trace_steps
+                        .push(import_map_trace_step(&lookup_path, *request, &result, false).await?);
+                }
                 if !matches!(result, ImportMapResult::NoEntry) {
                     has_alias = true;
                     let resolved_result = resolve_import_map_result(
@@ -1818,7 +1975,7 @@ async fn resolve_internal_inline(
                     if let Some(result) = resolved_result
                         && !*result.is_unresolvable().await?
                     {
-                        return Ok(result);
+                        return ResolveResult::with_trace_steps(result, trace_steps, vec![]).await;
                     }
                 }
             }
@@ -2062,16 +2219,24 @@ async fn resolve_internal_inline(
             }
         };
 
+        let result = ResolveResult::with_trace_steps(result, trace_steps, vec![]).await?;
+
         // Apply fallback import mappings if provided
         if let Some(import_map) = &options_value.fallback_import_map
             && *result.is_unresolvable().await?
         {
-            let result = import_map
+            let fallback_result = import_map
                 .await?
                 .lookup(lookup_path.clone(), request)
                 .await?;
+            let mut fallback_trace_steps = vec![];
+            if options_value.trace {
+                fallback_trace_steps.push(
+                    import_map_trace_step(&lookup_path, request, &fallback_result, true).await?,
+                );
+            }
             let resolved_result = resolve_import_map_result(
-                &result,
+                &fallback_result,
                 lookup_path.clone(),
                 lookup_path.clone(),
                 request,
@@ -2079,11 +2244,21 @@ async fn resolve_internal_inline(
                 request.query().owned().await?,
             )
             .await?;
-            if let Some(result) = resolved_result
-                && !*result.is_unresolvable().await?
-            {
-                return Ok(result);
+            if let Some(resolved_result) = resolved_result {
+                if !*resolved_result.is_unresolvable().await? {
+                    let trace_steps = result
+                        .await?
+                        .trace_steps
+                        .iter()
+                        .cloned()
+                        .chain(fallback_trace_steps)
+                        .collect();
+                    return ResolveResult::with_trace_steps(resolved_result, trace_steps, vec![])
+                        .await;
+                }
+                fallback_trace_steps.extend(resolved_result.await?.trace_steps.iter().cloned());
             }
+            return ResolveResult::with_trace_steps(result, vec![], fallback_trace_steps).await;
         }
 
         Ok(result)
@@ -2092,6 +2267,20 @@ async fn resolve_internal_inline(
     .await
 }
 
+async fn import_map_trace_step(
+    lookup_path: &FileSystemPath,
+    request: Vc<Request>,
+    result: &ImportMapResult,
+    fallback: bool,
+) -> Result<ResolveTraceStep> {
+    Ok(ResolveTraceStep::ImportMap {
+        lookup_path: lookup_path.clone(),
+        request: request.to_string().owned().await?,
+        fallback,
+        result: result.clone().cell().to_string().owned().await?,
+    })
+}
+
 #[turbo_tasks::function]
 async fn resolve_into_folder(
     package_path: FileSystemPath,
@@ -2264,6 +2453,9 @@ async fn resolve_relative_request(
         new_path.normalize();
     }
 
+    let trace_pattern = options_value
+        .trace
+        .then(|| RcStr::from(format!("{new_path}")));
     let mut results = Vec::new();
     let matches = read_matches(
         lookup_path.clone(),
@@ -2273,6 +2465,25 @@ async fn resolve_relative_request(
     )
     .await?;
 
+    let trace_step = trace_pattern.map(|pattern| ResolveTraceStep::Probe {
+        lookup_path: lookup_path.clone(),
+        pattern,
+        files: matches
+            .iter()
+            .filter_map(|m| match m {
+                PatternMatch::File(matched_pattern, _) => Some(matched_pattern.clone()),
+                PatternMatch::Directory(..) => None,
+            })
+            .collect(),
+        directories: matches
+            .iter()
+            .filter_map(|m| match m {
+                PatternMatch::Directory(matched_pattern, _) => Some(matched_pattern.clone()),
+                PatternMatch::File(..) => None,
+            })
+            .collect(),
+    });
+
     for m in matches.iter() {
         if let PatternMatch::File(matched_pattern, path) = m {
             let mut pushed = false;
@@ -2369,7 +2580,12 @@ async fn resolve_relative_request(
         }
     }
 
-    Ok(merge_results(results))
+    ResolveResult::with_trace_steps(
+        merge_results(results),
+        trace_step.into_iter().collect(),
+        vec![],
+    )
+    .await
 }
 
 #[tracing::instrument(level = Level::TRACE, skip_all)]
@@ -2560,10 +2776,33 @@ async fn resolve_module_request(
     )
     .await?;
 
+    // Yarn PnP errors are also reported when another lookup found the package, e.g. in a
+    // `node_modules` directory. Yarn itself would fail to resolve it at runtime.
+    for error in &result.errors {
+        ResolvingIssue {
+            severity: if result.packages.is_empty() {
+                error_severity(options).await?
+            } else {
+                IssueSeverity::Warning
+            },
+            request_type: format!("Yarn PnP package request: `{module}`"),
+            request: request.to_resolved().await?,
+            file_path: lookup_path.clone(),
+            resolve_options: options.to_resolved().await?,
+            error_message: Some(error.to_string()),
+            source: None,
+        }
+        .resolved_cell()
+        .emit();
+    }
+
     if result.packages.is_empty() {
-        return Ok(*ResolveResult::unresolvable_with_affecting_sources(
-            result.affecting_sources.clone(),
-        ));
+        return ResolveResult::with_trace_steps(
+            *ResolveResult::unresolvable_with_affecting_sources(result.affecting_sources.clone()),
+            result.trace_steps.clone(),
+            vec![],
+        )
+        .await;
     }
 
     let mut results = vec![];
@@ -2603,9 +2842,13 @@ async fn resolve_module_request(
         }
     }
 
-    let module_result =
-        merge_results_with_affecting_sources(results, result.affecting_sources.clone())
-            .with_replaced_request_key(rcstr!("."), RequestKey::new(module.clone()));
+    let module_result = ResolveResult::with_trace_steps(
+        merge_results_with_affecting_sources(results, result.affecting_sources.clone()),
+        result.trace_steps.clone(),
+        vec![],
+    )
+    .await?
+    .with_replaced_request_key(rcstr!("."), RequestKey::new(module.clone()));
 
     if options_value.prefer_relative {
         let module_prefix: RcStr = format!("./{module}").into();
@@ -2676,6 +2919,7 @@ async fn resolve_into_package(
                         package_path.clone(),
                         package_json_path,
                         *options,
+                        "exports",
                         exports_field,
                         &path,
                         conditions,
@@ -2882,6 +3126,7 @@ async fn handle_exports_imports_field(
     package_path: FileSystemPath,
     package_json_path: FileSystemPath,
     options: Vc<ResolveOptions>,
+    field_name: &str,
     exports_imports_field: &AliasMap<SubpathValue>,
     path: &str,
     conditions: &BTreeMap<RcStr, ConditionValue>,
@@ -2909,6 +3154,34 @@ async fn handle_exports_imports_field(
         }
     }
 
+    let mut trace_steps = vec![];
+    if options.await?.trace {
+        let mut used_conditions = FxIndexSet::default();
+        for value in values.iter() {
+            value.add_conditions(&mut used_conditions);
+        }
+        trace_steps.push(ResolveTraceStep::ExportsImportsField {
+            package_json: package_json_path.clone(),
+            field: field_name.into(),
+            specifier: path.into(),
+            conditions: used_conditions
+                .into_iter()
+                .map(|condition| {
+                    let value = if condition == "default" {
+                        ConditionValue::Set
+                    } else {
+                        *conditions.get(condition).unwrap_or(unspecified_conditions)
+                    };
+                    (condition.into(), value)
+                })
+                .collect(),
+            targets: results
+                .iter()
+                .map(|(target, _)| format!("{target}").into())
+                .collect(),
+        });
+    }
+
     let mut resolved_results = Vec::new();
     for (result_path, conditions) in results {
         if let Some(result_path) = result_path.with_normalized_path() {
@@ -2936,12 +3209,17 @@ async fn handle_exports_imports_field(
     }
 
     // other options do not apply anymore when an exports field exist
-    Ok(merge_results_with_affecting_sources(
-        resolved_results,
-        vec![ResolvedVc::upcast(
-            FileSource::new(package_json_path).to_resolved().await?,
-        )],
-    ))
+    ResolveResult::with_trace_steps(
+        merge_results_with_affecting_sources(
+            resolved_results,
+            vec![ResolvedVc::upcast(
+                FileSource::new(package_json_path).to_resolved().await?,
+            )],
+        ),
+        trace_steps,
+        vec![],
+    )
+    .await
 }
 
 /// Resolves a `#dep` import using the containing package.json's `imports`
@@ -2985,6 +3263,7 @@ async fn resolve_package_internal_with_imports_field(
         package_json_path.parent(),
         package_json_path.clone(),
         resolve_options,
+        "imports",
         imports,
         specifier,
         conditions,
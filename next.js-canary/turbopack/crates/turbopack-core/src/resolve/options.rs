@@ -47,6 +47,9 @@ pub enum ResolveModules {
         dir: FileSystemPath,
         excluded_extensions: ResolvedVc<ExcludedExtensions>,
     },
+    /// resolve through the Yarn Plug'n'Play manifest (`.pnp.data.json` or `.pnp.cjs`) in that
+    /// directory, when the request is made from a file of a package in the manifest
+    Pnp(FileSystemPath),
 }
 
 #[derive(
@@ -474,8 +477,9 @@ impl ValueToString for ImportMapResult {
     async fn to_string(&self) -> Result<Vc<RcStr>> {
         match self {
             ImportMapResult::Result(_) => Ok(Vc::cell(rcstr!("Resolved by import map"))),
-            ImportMapResult::External(_, _, _) => Ok(Vc::cell(rcstr!("TODO external"))),
-            ImportMapResult::AliasExternal { .. } => Ok(Vc::cell(rcstr!("TODO external"))),
+            ImportMapResult::External(name, _, _) | ImportMapResult::AliasExternal { name, .. } => {
+                Ok(Vc::cell(format!("external {name}").into()))
+            }
             ImportMapResult::Alias(request, context) => {
                 let s = if let Some(path) = context {
                     let path = path.value_to_string().await?;
@@ -616,6 +620,10 @@ pub struct ResolveOptions {
     pub loose_errors: bool,
     /// Whether to parse data URIs into modules (as opposed to keeping them as externals)
     pub parse_data_uris: bool,
+    /// Record the steps taken while resolving in
+    /// [ResolveResult::trace_steps](super::ResolveResult::trace_steps). See
+    /// [resolve_trace](super::trace::resolve_trace).
+    pub trace: bool,
 
     pub placeholder_for_future_extensions: (),
 }
@@ -681,6 +689,17 @@ impl ResolveOptions {
         resolve_options.fully_specified = fully_specified;
         Ok(resolve_options.cell())
     }
+
+    /// Overrides the trace flag for resolving
+    #[turbo_tasks::function]
+    pub async fn with_trace(self: Vc<Self>, trace: bool) -> Result<Vc<Self>> {
+        let mut resolve_options = self.owned().await?;
+        if resolve_options.trace == trace {
+            return Ok(self);
+        }
+        resolve_options.trace = trace;
+        Ok(resolve_options.cell())
+    }
 }
 
 #[turbo_tasks::value(shared)]
@@ -688,6 +707,7 @@ impl ResolveOptions {
 pub struct ResolveModulesOptions {
     pub modules: Vec<ResolveModules>,
     pub extensions: Vec<RcStr>,
+    pub trace: bool,
 }
 
 #[turbo_tasks::function]
@@ -698,6 +718,7 @@ pub async fn resolve_modules_options(
     Ok(ResolveModulesOptions {
         modules: options.modules.clone(),
         extensions: options.extensions.clone(),
+        trace: options.trace,
     }
     .into())
 }
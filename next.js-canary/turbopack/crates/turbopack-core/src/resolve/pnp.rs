@@ -0,0 +1,515 @@
+//! Resolving bare specifiers with the manifest of [Yarn Plug'n'Play][pnp], which maps every
+//! package to its location and the packages it's allowed to access, instead of a `node_modules`
+//! folder.
+//!
+//! [pnp]: https://yarnpkg.com/advanced/pnp-spec
+
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::Deserialize;
+use turbo_rcstr::RcStr;
+use turbo_tasks::{ResolvedVc, Vc};
+use turbo_tasks_fs::{
+    FileContent, FileSystem, FileSystemPath, archive::ArchiveFileSystem, util::join_path,
+};
+
+/// The manifest with the package locations, written by `yarn install` next to the project root
+/// when `pnpEnableInlining` is disabled.
+const PNP_DATA_FILE: &str = ".pnp.data.json";
+/// The runtime of Yarn PnP, which embeds the manifest by default.
+const PNP_RUNTIME_FILE: &str = ".pnp.cjs";
+
+/// A parsed Yarn PnP manifest.
+#[turbo_tasks::value(serialization = "none", eq = "manual")]
+pub struct PnpManifest {
+    /// The `.pnp.data.json` or `.pnp.cjs` file the manifest was read from.
+    pub path: FileSystemPath,
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    data: PnpData,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionPnpManifest(Option<ResolvedVc<PnpManifest>>);
+
+/// Reads the Yarn PnP manifest in the `root` directory, preferring `.pnp.data.json` over the data
+/// embedded in `.pnp.cjs`.
+#[turbo_tasks::function]
+pub async fn read_pnp_manifest(root: FileSystemPath) -> Result<Vc<OptionPnpManifest>> {
+    for file_name in [PNP_DATA_FILE, PNP_RUNTIME_FILE] {
+        let path = root.join(file_name)?;
+        let FileContent::Content(file) = &*path.read().await? else {
+            continue;
+        };
+        let content = file.content().to_str()?;
+        let data = if file_name == PNP_DATA_FILE {
+            PnpData::parse(&root.path, &content)
+        } else {
+            PnpData::parse_runtime(&root.path, &content)
+        }
+        .with_context(|| format!("failed to read the Yarn PnP manifest {}", path.path))?;
+        let manifest = PnpManifest { path, data }.resolved_cell();
+        return Ok(Vc::cell(Some(manifest)));
+    }
+    Ok(Vc::cell(None))
+}
+
+/// The result of resolving a package name with a [PnpManifest].
+pub enum PnpResolution {
+    /// The issuer is not part of a package in the manifest, so it needs to be resolved in another
+    /// way.
+    NotCovered,
+    /// The path of the package directory.
+    Found(FileSystemPath),
+    /// The issuer isn't allowed to access the package.
+    Error(RcStr),
+}
+
+impl PnpManifest {
+    /// Resolves the directory of the package `name` when it's imported from `issuer`.
+    ///
+    /// Packages in `.zip` archives (e.g. in `.yarn/cache`) are returned on an
+    /// [turbo_tasks_fs::attach::AttachedFileSystem] which mounts the archive at its own path, so
+    /// files in there keep their paths and can be looked up in the manifest again.
+    pub async fn resolve(&self, issuer: &FileSystemPath, name: &str) -> Result<PnpResolution> {
+        let location = match self.data.resolve(&issuer.path, name) {
+            PnpLookup::NotCovered => return Ok(PnpResolution::NotCovered),
+            PnpLookup::Found(location) => location,
+            PnpLookup::Error(message) => return Ok(PnpResolution::Error(message.into())),
+        };
+        let root = self.path.parent();
+        let fs = match location.find(".zip/") {
+            Some(index) => {
+                let archive = root.root().await?.join(&location[..index + 4])?;
+                Vc::upcast::<Box<dyn FileSystem>>(ArchiveFileSystem::attach(archive))
+            }
+            None => *root.fs,
+        };
+        Ok(PnpResolution::Found(fs.root().await?.join(&location)?))
+    }
+}
+
+/// A package in the manifest. The top-level package of the project has neither a name nor a
+/// reference.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct PnpLocator {
+    name: Option<RcStr>,
+    reference: Option<RcStr>,
+}
+
+impl PnpLocator {
+    fn is_top_level(&self) -> bool {
+        self.name.is_none()
+    }
+}
+
+struct PnpPackage {
+    /// The path of the package directory, relative to the root of the file system.
+    location: RcStr,
+    /// The packages this package is allowed to access by name. `None` is a peer dependency that is
+    /// not provided by the ancestors of the package.
+    dependencies: FxHashMap<RcStr, Option<PnpLocator>>,
+}
+
+#[derive(Default)]
+struct PnpData {
+    enable_top_level_fallback: bool,
+    /// Matches paths (relative to the manifest) that are not handled by PnP.
+    ignore_pattern: Option<Regex>,
+    /// The packages that may not use the fallback.
+    fallback_exclusion_list: FxHashSet<PnpLocator>,
+    /// The dependencies that are available to packages which don't declare them, when the fallback
+    /// is enabled.
+    fallback_pool: FxHashMap<RcStr, Option<PnpLocator>>,
+    packages: FxHashMap<PnpLocator, PnpPackage>,
+    /// The package for every location, sorted from the longest to the shortest location so the
+    /// innermost package wins.
+    locations: Vec<(RcStr, PnpLocator)>,
+    /// The directory of the manifest, relative to the root of the file system.
+    root: RcStr,
+}
+
+enum PnpLookup {
+    NotCovered,
+    Found(RcStr),
+    Error(String),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPnpData {
+    #[serde(default)]
+    enable_top_level_fallback: bool,
+    #[serde(default)]
+    ignore_pattern_data: Option<String>,
+    #[serde(default)]
+    fallback_exclusion_list: Vec<(RcStr, Vec<RcStr>)>,
+    #[serde(default)]
+    fallback_pool: Vec<(RcStr, Option<RawDependencyTarget>)>,
+    package_registry_data: Vec<(Option<RcStr>, Vec<(Option<RcStr>, RawPackageInformation)>)>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPackageInformation {
+    package_location: RcStr,
+    #[serde(default)]
+    package_dependencies: Vec<(RcStr, Option<RawDependencyTarget>)>,
+    #[serde(default)]
+    discard_from_lookup: bool,
+}
+
+/// A dependency is either the reference of a package with the same name, or the name and
+/// reference of an aliased package.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawDependencyTarget {
+    Reference(RcStr),
+    Alias(RcStr, RcStr),
+}
+
+impl RawDependencyTarget {
+    fn into_locator(self, name: &RcStr) -> PnpLocator {
+        match self {
+            RawDependencyTarget::Reference(reference) => PnpLocator {
+                name: Some(name.clone()),
+                reference: Some(reference),
+            },
+            RawDependencyTarget::Alias(name, reference) => PnpLocator {
+                name: Some(name),
+                reference: Some(reference),
+            },
+        }
+    }
+}
+
+fn into_dependencies(
+    dependencies: Vec<(RcStr, Option<RawDependencyTarget>)>,
+) -> FxHashMap<RcStr, Option<PnpLocator>> {
+    dependencies
+        .into_iter()
+        .map(|(name, target)| {
+            let locator = target.map(|target| target.into_locator(&name));
+            (name, locator)
+        })
+        .collect()
+}
+
+impl PnpData {
+    /// Parses the content of a `.pnp.data.json` file in the directory `root`.
+    fn parse(root: &str, json: &str) -> Result<Self> {
+        let raw: RawPnpData = serde_json::from_str(json)?;
+        let mut data = PnpData {
+            enable_top_level_fallback: raw.enable_top_level_fallback,
+            ignore_pattern: raw.ignore_pattern_data.and_then(|pattern| {
+                // The pattern is generated for JavaScript and might use lookarounds, which are not
+                // supported
+                Regex::new(&pattern)
+                    .inspect_err(|err| {
+                        tracing::warn!("unsupported Yarn PnP ignore pattern {pattern}: {err}")
+                    })
+                    .ok()
+            }),
+            fallback_exclusion_list: raw
+                .fallback_exclusion_list
+                .into_iter()
+                .flat_map(|(name, references)| {
+                    references.into_iter().map(move |reference| PnpLocator {
+                        name: Some(name.clone()),
+                        reference: Some(reference),
+                    })
+                })
+                .collect(),
+            fallback_pool: into_dependencies(raw.fallback_pool),
+            root: root.into(),
+            ..Default::default()
+        };
+
+        // Like the PnP runtime, a location belongs to the last package with that location that
+        // isn't discarded from the lookup
+        let mut locations: FxHashMap<RcStr, (PnpLocator, bool)> = FxHashMap::default();
+        for (name, packages) in raw.package_registry_data {
+            for (reference, information) in packages {
+                if name.is_none() != reference.is_none() {
+                    bail!("the package {name:?} has the reference {reference:?}");
+                }
+                let Some(location) = join_path(root, &information.package_location) else {
+                    // Outside of the file system, e.g. the global cache of Yarn
+                    continue;
+                };
+                let location = RcStr::from(location);
+                let locator = PnpLocator {
+                    name: name.clone(),
+                    reference,
+                };
+                let discard = information.discard_from_lookup;
+                match locations.get_mut(&location) {
+                    Some(entry) => {
+                        entry.1 &= discard;
+                        if !discard {
+                            entry.0 = locator.clone();
+                        }
+                    }
+                    None => {
+                        locations.insert(location.clone(), (locator.clone(), discard));
+                    }
+                }
+                data.packages.insert(
+                    locator,
+                    PnpPackage {
+                        location,
+                        dependencies: into_dependencies(information.package_dependencies),
+                    },
+                );
+            }
+        }
+        data.locations = locations
+            .into_iter()
+            .filter(|(_, (_, discard))| !discard)
+            .map(|(location, (locator, _))| (location, locator))
+            .collect();
+        data.locations
+            .sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        Ok(data)
+    }
+
+    /// Parses the manifest embedded in a `.pnp.cjs` file in the directory `root`. It's stored as a
+    /// single quoted string literal in the `RAW_RUNTIME_STATE` constant.
+    fn parse_runtime(root: &str, source: &str) -> Result<Self> {
+        let Some((_, rest)) = source.split_once("RAW_RUNTIME_STATE =") else {
+            bail!(
+                "the Yarn PnP runtime doesn't contain the manifest, it might be in {PNP_DATA_FILE}"
+            );
+        };
+        let Some(rest) = rest.trim_start().strip_prefix('\'') else {
+            bail!("unexpected format of RAW_RUNTIME_STATE");
+        };
+        let mut json = String::with_capacity(rest.len());
+        let mut chars = rest.chars();
+        loop {
+            match chars.next() {
+                Some('\'') => break,
+                // Line continuations are skipped, other escapes are `\\` and `\'`
+                Some('\\') => match chars.next() {
+                    Some('\n') => {}
+                    Some(c) => json.push(c),
+                    None => bail!("unterminated RAW_RUNTIME_STATE"),
+                },
+                Some(c) => json.push(c),
+                None => bail!("unterminated RAW_RUNTIME_STATE"),
+            }
+        }
+        Self::parse(root, &json)
+    }
+
+    /// Finds the package that contains the file or directory `path`.
+    fn find_locator(&self, path: &str) -> Option<&PnpLocator> {
+        if let Some(ignore_pattern) = &self.ignore_pattern {
+            let relative = if self.root.is_empty() {
+                Some(path)
+            } else {
+                path.strip_prefix(&*self.root)
+                    .and_then(|path| path.strip_prefix('/').or(path.is_empty().then_some("")))
+            };
+            if let Some(relative) = relative
+                && ignore_pattern.is_match(relative)
+            {
+                return None;
+            }
+        }
+        self.locations
+            .iter()
+            .find(|(location, _)| {
+                location.is_empty()
+                    || path
+                        .strip_prefix(&**location)
+                        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+            .map(|(_, locator)| locator)
+    }
+
+    /// Resolves the location of the package `name` when it's imported from `issuer`, following
+    /// the resolution algorithm of the PnP specification.
+    fn resolve(&self, issuer: &str, name: &str) -> PnpLookup {
+        let Some(issuer_locator) = self.find_locator(issuer) else {
+            return PnpLookup::NotCovered;
+        };
+        let Some(issuer_package) = self.packages.get(issuer_locator) else {
+            return PnpLookup::NotCovered;
+        };
+        let issuer_name = issuer_locator.name.as_deref().unwrap_or("Your application");
+
+        let mut target = issuer_package.dependencies.get(name);
+        if target.is_none()
+            && self.enable_top_level_fallback
+            && !self.fallback_exclusion_list.contains(issuer_locator)
+        {
+            target = self
+                .packages
+                .get(&PnpLocator {
+                    name: None,
+                    reference: None,
+                })
+                .and_then(|top_level| top_level.dependencies.get(name))
+                .or_else(|| self.fallback_pool.get(name));
+        }
+
+        match target {
+            Some(Some(locator)) => match self.packages.get(locator) {
+                Some(package) => PnpLookup::Found(package.location.clone()),
+                None => PnpLookup::Error(format!(
+                    "{issuer_name} depends on {name}, but the package {}@{} is missing in the \
+                     Yarn PnP manifest. Try running `yarn install` again.",
+                    locator.name.as_deref().unwrap_or_default(),
+                    locator.reference.as_deref().unwrap_or_default(),
+                )),
+            },
+            Some(None) if issuer_locator.is_top_level() => PnpLookup::Error(format!(
+                "Your application tried to access {name} (a peer dependency); this isn't allowed \
+                 as the peer dependency cannot be provided by any parent package.\n\nRequired \
+                 package: {name}\nRequired by: {issuer}"
+            )),
+            Some(None) => PnpLookup::Error(format!(
+                "{issuer_name} tried to access {name} (a peer dependency) but it isn't provided \
+                 by its ancestors; this makes the require call ambiguous and unsound.\n\nRequired \
+                 package: {name}\nRequired by: {issuer_name}@{} (via {issuer})",
+                issuer_locator.reference.as_deref().unwrap_or_default(),
+            )),
+            None if issuer_locator.is_top_level() => PnpLookup::Error(format!(
+                "Your application tried to access {name}, but it isn't declared in your \
+                 dependencies; this makes the require call ambiguous and unsound.\n\nRequired \
+                 package: {name}\nRequired by: {issuer}"
+            )),
+            None => PnpLookup::Error(format!(
+                "{issuer_name} tried to access {name}, but it isn't declared in its dependencies; \
+                 this makes the require call ambiguous and unsound.\n\nRequired package: \
+                 {name}\nRequired by: {issuer_name}@{} (via {issuer})",
+                issuer_locator.reference.as_deref().unwrap_or_default(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUNTIME: &str = r#"#!/usr/bin/env node
+/* eslint-disable */
+"use strict";
+
+const RAW_RUNTIME_STATE =
+'{\
+  "__info": ["This file is automatically generated. Do not touch it, or risk\\n", "your modifications being lost."],\
+  "dependencyTreeRoots": [{"name": "app", "reference": "workspace:."}],\
+  "enableTopLevelFallback": true,\
+  "ignorePatternData": "^\\\\.yarn/sdks(/|$)",\
+  "fallbackExclusionList": [["app", ["workspace:."]]],\
+  "fallbackPool": [["shared", "npm:1.0.0"]],\
+  "packageRegistryData": [\
+    [null, [[null, {"packageLocation": "./", "packageDependencies": [["react", "npm:18.2.0"], ["shared", "npm:1.0.0"]], "linkType": "SOFT"}]]],\
+    ["app", [["workspace:.", {"packageLocation": "./", "packageDependencies": [["react", "npm:18.2.0"], ["lodash-es", ["lodash", "npm:4.17.21"]], ["peer", null]], "linkType": "SOFT"}]]],\
+    ["react", [["npm:18.2.0", {"packageLocation": "./.yarn/cache/react-npm-18.2.0-1a2b3c.zip/node_modules/react/", "packageDependencies": [["react", "npm:18.2.0"], ["loose-envify", "npm:1.4.0"]], "linkType": "HARD"}]]],\
+    ["loose-envify", [["npm:1.4.0", {"packageLocation": "./.yarn/unplugged/loose-envify-npm-1.4.0-4d5e6f/node_modules/loose-envify/", "packageDependencies": [["loose-envify", "npm:1.4.0"]], "linkType": "HARD"}]]],\
+    ["lodash", [["npm:4.17.21", {"packageLocation": "./.yarn/cache/lodash-npm-4.17.21-7a8b9c.zip/node_modules/lodash/", "packageDependencies": [["lodash", "npm:4.17.21"]], "linkType": "HARD"}]]],\
+    ["shared", [["npm:1.0.0", {"packageLocation": "./.yarn/cache/shared-npm-1.0.0-0d1e2f.zip/node_modules/shared/", "packageDependencies": [["shared", "npm:1.0.0"]], "linkType": "HARD"}]]]\
+  ]\
+}';
+
+function $$SETUP_STATE(hydrateRuntimeState, basePath) {
+  return hydrateRuntimeState(JSON.parse(RAW_RUNTIME_STATE), {basePath: basePath || __dirname});
+}
+"#;
+
+    fn found(lookup: PnpLookup) -> String {
+        match lookup {
+            PnpLookup::Found(location) => location.to_string(),
+            PnpLookup::NotCovered => panic!("expected a location, but the issuer isn't covered"),
+            PnpLookup::Error(message) => panic!("expected a location, got: {message}"),
+        }
+    }
+
+    fn error(lookup: PnpLookup) -> String {
+        match lookup {
+            PnpLookup::Error(message) => message,
+            _ => panic!("expected an error"),
+        }
+    }
+
+    #[test]
+    fn resolve_dependencies() {
+        let data = PnpData::parse_runtime("project", RUNTIME).unwrap();
+        assert_eq!(
+            found(data.resolve("project/src", "react")),
+            "project/.yarn/cache/react-npm-18.2.0-1a2b3c.zip/node_modules/react"
+        );
+        // Aliased dependency
+        assert_eq!(
+            found(data.resolve("project/src", "lodash-es")),
+            "project/.yarn/cache/lodash-npm-4.17.21-7a8b9c.zip/node_modules/lodash"
+        );
+        // From inside of a package
+        assert_eq!(
+            found(data.resolve(
+                "project/.yarn/cache/react-npm-18.2.0-1a2b3c.zip/node_modules/react/cjs",
+                "loose-envify"
+            )),
+            "project/.yarn/unplugged/loose-envify-npm-1.4.0-4d5e6f/node_modules/loose-envify"
+        );
+        // The fallback is available to packages, but not to the excluded workspace
+        assert_eq!(
+            found(data.resolve(
+                "project/.yarn/cache/react-npm-18.2.0-1a2b3c.zip/node_modules/react",
+                "shared"
+            )),
+            "project/.yarn/cache/shared-npm-1.0.0-0d1e2f.zip/node_modules/shared"
+        );
+        assert!(
+            error(data.resolve("project/src", "shared")).starts_with(
+                "app tried to access shared, but it isn't declared in its dependencies"
+            )
+        );
+        assert!(
+            error(data.resolve("project/src", "peer"))
+                .starts_with("app tried to access peer (a peer dependency)")
+        );
+        // Outside of the project and ignored paths
+        assert!(matches!(
+            data.resolve("other/src", "react"),
+            PnpLookup::NotCovered
+        ));
+        assert!(matches!(
+            data.resolve("project/.yarn/sdks/typescript", "react"),
+            PnpLookup::NotCovered
+        ));
+    }
+
+    #[test]
+    fn undeclared_dependency_of_package() {
+        let data = PnpData::parse(
+            "",
+            r#"{
+                "enableTopLevelFallback": false,
+                "packageRegistryData": [
+                    [null, [[null, {"packageLocation": "./", "packageDependencies": [["a", "npm:1.0.0"], ["b", "npm:1.0.0"]]}]]],
+                    ["a", [["npm:1.0.0", {"packageLocation": "./.yarn/cache/a.zip/node_modules/a/", "packageDependencies": []}]]],
+                    ["b", [["npm:1.0.0", {"packageLocation": "./.yarn/cache/b.zip/node_modules/b/", "packageDependencies": []}]]]
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            found(data.resolve("", "b")),
+            ".yarn/cache/b.zip/node_modules/b"
+        );
+        assert_eq!(
+            error(data.resolve(".yarn/cache/a.zip/node_modules/a/index.js", "b")),
+            "a tried to access b, but it isn't declared in its dependencies; this makes the \
+             require call ambiguous and unsound.\n\nRequired package: b\nRequired by: a@npm:1.0.0 \
+             (via .yarn/cache/a.zip/node_modules/a/index.js)"
+        );
+        assert!(error(data.resolve("src/index.js", "c")).starts_with(
+            "Your application tried to access c, but it isn't declared in your dependencies"
+        ));
+    }
+}
@@ -5,7 +5,7 @@ use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use turbo_rcstr::{RcStr, rcstr};
-use turbo_tasks::FxIndexMap;
+use turbo_tasks::{FxIndexMap, FxIndexSet};
 
 use super::{
     alias_map::{AliasMap, AliasMapIter, AliasPattern, AliasTemplate},
@@ -231,6 +231,25 @@ impl SubpathValue {
 }
 
 impl ReplacedSubpathValue {
+    /// Adds the names of all conditions used in the [ReplacedSubpathValue] to `target`, in the
+    /// order they would be evaluated.
+    pub fn add_conditions<'a>(&'a self, target: &mut FxIndexSet<&'a str>) {
+        match self {
+            ReplacedSubpathValue::Alternatives(list) => {
+                for value in list {
+                    value.add_conditions(target);
+                }
+            }
+            ReplacedSubpathValue::Conditional(list) => {
+                for (condition, value) in list {
+                    target.insert(condition);
+                    value.add_conditions(target);
+                }
+            }
+            ReplacedSubpathValue::Result(_) | ReplacedSubpathValue::Excluded => {}
+        }
+    }
+
     /// Walks the [ReplacedSubpathValue] and adds results to the `target`
     /// vector. It uses the `conditions` to skip or enter conditional
     /// results. The state of conditions is stored within
@@ -0,0 +1,400 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use turbo_rcstr::{RcStr, rcstr};
+use turbo_tasks::{
+    FxIndexSet, NonLocalValue, ResolvedVc, TryJoinIterExt, ValueToString, Vc, trace::TraceRawVcs,
+};
+use turbo_tasks_fs::FileSystemPath;
+
+use super::{
+    ResolveResult, ResolveResultItem,
+    options::{ConditionValue, ResolveOptions},
+    parse::Request,
+    resolve,
+};
+use crate::{
+    introspect::{Introspectable, IntrospectableChildren, source::IntrospectableSource},
+    reference_type::ReferenceType,
+};
+
+/// A single step taken while resolving a request. These are only recorded in
+/// [ResolveResult::trace_steps] when [ResolveOptions::trace] is set, use [resolve_trace] to
+/// collect them.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug)]
+pub enum ResolveTraceStep {
+    /// A [BeforeResolvePlugin](super::plugin::BeforeResolvePlugin) was considered for the request.
+    BeforeResolvePlugin {
+        index: usize,
+        request: RcStr,
+        outcome: PluginOutcome,
+    },
+    /// An [ImportMap](super::options::ImportMap) was checked for the request.
+    ImportMap {
+        lookup_path: FileSystemPath,
+        request: RcStr,
+        fallback: bool,
+        result: RcStr,
+    },
+    /// A `node_modules`-like directory was checked for a package.
+    PackageDirectory { path: FileSystemPath, found: bool },
+    /// An `exports` or `imports` field was used to map a specifier.
+    ExportsImportsField {
+        package_json: FileSystemPath,
+        field: RcStr,
+        specifier: RcStr,
+        /// The conditions of the matched entry in evaluation order, with their configured value.
+        conditions: Vec<(RcStr, ConditionValue)>,
+        targets: Vec<RcStr>,
+    },
+    /// A path pattern (including the extensions) was matched against the file system.
+    Probe {
+        lookup_path: FileSystemPath,
+        pattern: RcStr,
+        files: Vec<RcStr>,
+        directories: Vec<RcStr>,
+    },
+    /// An [AfterResolvePlugin](super::plugin::AfterResolvePlugin) was considered for a result.
+    AfterResolvePlugin {
+        index: usize,
+        path: FileSystemPath,
+        outcome: PluginOutcome,
+    },
+}
+
+#[derive(
+    TraceRawVcs, Hash, PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize, NonLocalValue,
+)]
+pub enum PluginOutcome {
+    ConditionNotMatched,
+    NoResult,
+    Applied,
+}
+
+impl ResolveTraceStep {
+    async fn describe(&self) -> Result<String> {
+        let mut description = String::new();
+        match self {
+            ResolveTraceStep::BeforeResolvePlugin {
+                index,
+                request,
+                outcome,
+            } => {
+                write!(
+                    description,
+                    "before resolve plugin #{index} for `{request}`: {}",
+                    outcome.as_str()
+                )?;
+            }
+            ResolveTraceStep::ImportMap {
+                lookup_path,
+                request,
+                fallback,
+                result,
+            } => {
+                write!(
+                    description,
+                    "{} for `{request}` in {}: {result}",
+                    if *fallback {
+                        "fallback import map"
+                    } else {
+                        "import map"
+                    },
+                    lookup_path.value_to_string().await?
+                )?;
+            }
+            ResolveTraceStep::PackageDirectory { path, found } => {
+                write!(
+                    description,
+                    "package directory {}: {}",
+                    path.value_to_string().await?,
+                    if *found { "found" } else { "not found" }
+                )?;
+            }
+            ResolveTraceStep::ExportsImportsField {
+                package_json,
+                field,
+                specifier,
+                conditions,
+                targets,
+            } => {
+                write!(
+                    description,
+                    "{field} field of {} for `{specifier}`",
+                    package_json.value_to_string().await?
+                )?;
+                if !conditions.is_empty() {
+                    description.push_str("\n  conditions: ");
+                    for (i, (condition, value)) in conditions.iter().enumerate() {
+                        if i > 0 {
+                            description.push_str(", ");
+                        }
+                        let value = match value {
+                            ConditionValue::Set => "set",
+                            ConditionValue::Unset => "unset",
+                            ConditionValue::Unknown => "unknown",
+                        };
+                        write!(description, "{condition} ({value})")?;
+                    }
+                }
+                if targets.is_empty() {
+                    description.push_str("\n  no matching target");
+                } else {
+                    write!(description, "\n  targets: {}", targets.join(", "))?;
+                }
+            }
+            ResolveTraceStep::Probe {
+                lookup_path,
+                pattern,
+                files,
+                directories,
+            } => {
+                write!(
+                    description,
+                    "probed {pattern} in {}",
+                    lookup_path.value_to_string().await?
+                )?;
+                if files.is_empty() && directories.is_empty() {
+                    description.push_str(": no match");
+                }
+                for file in files {
+                    write!(description, "\n  file {file}")?;
+                }
+                for directory in directories {
+                    write!(description, "\n  directory {directory}")?;
+                }
+            }
+            ResolveTraceStep::AfterResolvePlugin {
+                index,
+                path,
+                outcome,
+            } => {
+                write!(
+                    description,
+                    "after resolve plugin #{index} for {}: {}",
+                    path.value_to_string().await?,
+                    outcome.as_str()
+                )?;
+            }
+        }
+        Ok(description)
+    }
+}
+
+impl PluginOutcome {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PluginOutcome::ConditionNotMatched => "condition not matched",
+            PluginOutcome::NoResult => "no result",
+            PluginOutcome::Applied => "applied",
+        }
+    }
+}
+
+/// The result of [resolve_trace]: the result of the resolve call and the steps that led to it.
+#[turbo_tasks::value(shared)]
+#[derive(Debug)]
+pub struct ResolveTrace {
+    pub lookup_path: FileSystemPath,
+    pub request: RcStr,
+    pub result: ResolvedVc<ResolveResult>,
+    /// The descriptions of the steps, in the order they were taken.
+    pub steps: Vec<RcStr>,
+}
+
+/// Resolves `request` like [resolve] does, but with [ResolveOptions::trace] enabled, and collects
+/// the import map entries checked, the `exports`/`imports` conditions evaluated, the paths probed
+/// and the plugins invoked.
+#[turbo_tasks::function]
+pub async fn resolve_trace(
+    lookup_path: FileSystemPath,
+    reference_type: ReferenceType,
+    request: Vc<Request>,
+    options: Vc<ResolveOptions>,
+) -> Result<Vc<ResolveTrace>> {
+    let result = resolve(
+        lookup_path.clone(),
+        reference_type,
+        request,
+        options.with_trace(true),
+    );
+    let resolved_result = result.to_resolved().await?;
+    let steps = result
+        .await?
+        .trace_steps
+        .iter()
+        .map(|step| async move { Ok(step.describe().await?.into()) })
+        .try_join()
+        .await?;
+    Ok(ResolveTrace {
+        lookup_path,
+        request: request.to_string().owned().await?,
+        result: resolved_result,
+        steps,
+    }
+    .cell())
+}
+
+#[turbo_tasks::value_impl]
+impl Introspectable for ResolveTrace {
+    #[turbo_tasks::function]
+    fn ty(&self) -> Vc<RcStr> {
+        Vc::cell(rcstr!("resolve"))
+    }
+
+    #[turbo_tasks::function]
+    async fn title(&self) -> Result<Vc<RcStr>> {
+        Ok(Vc::cell(
+            format!(
+                "{} from {}",
+                self.request,
+                self.lookup_path.value_to_string().await?
+            )
+            .into(),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    async fn details(&self) -> Result<Vc<RcStr>> {
+        let mut details = String::new();
+        for step in &self.steps {
+            writeln!(details, "{step}")?;
+        }
+        write!(details, "result: {}", self.result.to_string().await?)?;
+        Ok(Vc::cell(details.into()))
+    }
+
+    #[turbo_tasks::function]
+    async fn children(&self) -> Result<Vc<IntrospectableChildren>> {
+        let mut children = FxIndexSet::default();
+        for (_, item) in self.result.await?.primary.iter() {
+            if let ResolveResultItem::Source(source) = item {
+                children.insert((
+                    rcstr!("result"),
+                    IntrospectableSource::new(**source).to_resolved().await?,
+                ));
+            }
+        }
+        Ok(Vc::cell(children))
+    }
+}
+
+/// An optional trait for [ModuleReference]s that resolve a request. The [ResolveTrace] of the
+/// request is listed with the referenced modules when introspecting the referencing module.
+///
+/// [ModuleReference]: crate::reference::ModuleReference
+#[turbo_tasks::value_trait]
+pub trait ResolveTracedReference {
+    #[turbo_tasks::function]
+    fn resolve_trace(self: Vc<Self>) -> Vc<ResolveTrace>;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use anyhow::Result;
+    use turbo_rcstr::{RcStr, rcstr};
+    use turbo_tasks::Vc;
+    use turbo_tasks_backend::{BackendOptions, TurboTasksBackend, noop_backing_storage};
+    use turbo_tasks_fs::{DiskFileSystem, FileSystem};
+
+    use super::resolve_trace;
+    use crate::{
+        reference_type::{EcmaScriptModulesReferenceSubType, ReferenceType},
+        resolve::{
+            ResolveResultItem,
+            options::{ConditionValue, ResolveIntoPackage, ResolveModules, ResolveOptions},
+            parse::Request,
+        },
+        source::Source,
+    };
+
+    #[tokio::test]
+    async fn steps_are_in_resolve_order() -> Result<()> {
+        crate::register();
+
+        let project = tempfile::tempdir()?;
+        let root = project.path().canonicalize()?;
+        let package = root.join("node_modules/pkg");
+        fs::create_dir_all(&package)?;
+        fs::write(
+            package.join("package.json"),
+            r#"{ "name": "pkg", "exports": { "import": "./esm.js", "require": "./cjs.js" } }"#,
+        )?;
+        fs::write(package.join("esm.js"), "export default 1")?;
+        fs::write(package.join("cjs.js"), "module.exports = 1")?;
+
+        let tt = turbo_tasks::TurboTasks::new(TurboTasksBackend::new(
+            BackendOptions::default(),
+            noop_backing_storage(),
+        ));
+        tt.run_once(async move {
+            let fs = Vc::upcast::<Box<dyn FileSystem>>(DiskFileSystem::new(
+                rcstr!("project"),
+                RcStr::from(root.to_str().unwrap()),
+            ));
+            let root = fs.root().owned().await?;
+            let options = ResolveOptions {
+                extensions: vec![rcstr!(".js")],
+                modules: vec![ResolveModules::Nested(
+                    root.clone(),
+                    vec![rcstr!("node_modules")],
+                )],
+                into_package: vec![ResolveIntoPackage::ExportsField {
+                    conditions: [
+                        (rcstr!("import"), ConditionValue::Set),
+                        (rcstr!("require"), ConditionValue::Unset),
+                    ]
+                    .into_iter()
+                    .collect(),
+                    unspecified_conditions: ConditionValue::Unset,
+                }],
+                ..Default::default()
+            }
+            .cell();
+
+            let trace = resolve_trace(
+                root.clone(),
+                ReferenceType::EcmaScriptModules(EcmaScriptModulesReferenceSubType::Import),
+                Request::parse_string(rcstr!("pkg")),
+                options,
+            )
+            .await?;
+
+            assert_eq!(trace.request, "pkg");
+            let position = |prefix: &str| {
+                trace
+                    .steps
+                    .iter()
+                    .position(|step| step.starts_with(prefix))
+                    .unwrap_or_else(|| panic!("no `{prefix}` step in {:#?}", trace.steps))
+            };
+            let package_directory = position("package directory [project]/node_modules/pkg");
+            let exports_field = position("exports field of [project]/node_modules/pkg");
+            let probe = position("probed");
+            assert!(package_directory < exports_field, "{:#?}", trace.steps);
+            assert!(exports_field < probe, "{:#?}", trace.steps);
+            assert!(
+                trace.steps[package_directory].ends_with(": found"),
+                "{:#?}",
+                trace.steps
+            );
+            assert!(
+                trace.steps[exports_field].contains("conditions: import (set), require (unset)"),
+                "{:#?}",
+                trace.steps
+            );
+
+            let result = trace.result.await?;
+            let [(_, ResolveResultItem::Source(source))] = &*result.primary else {
+                panic!("expected a single source, got {result:?}");
+            };
+            assert_eq!(source.ident().await?.path.path, "node_modules/pkg/esm.js");
+            Ok(())
+        })
+        .await
+    }
+}
@@ -1,6 +1,13 @@
-use turbo_tasks::{ResolvedVc, Vc};
+use anyhow::Result;
+use turbo_tasks::{ResolvedVc, TryJoinIterExt, Vc};
 
-use crate::{asset::Asset, ident::AssetIdent};
+use crate::{
+    asset::Asset,
+    chunk::ChunkingContext,
+    ident::AssetIdent,
+    output::{OutputAsset, OutputAssets},
+    virtual_output::VirtualOutputAsset,
+};
 
 /// (Unparsed) Source Code. Source Code is processed into [Module]s by the
 /// [AssetContext]. All [Source]s have content and an identifier.
@@ -12,6 +19,47 @@ pub trait Source: Asset {
     fn ident(&self) -> Vc<AssetIdent>;
 }
 
+/// A [Source] that emits additional files, e.g. the files a webpack loader emits with
+/// `this.emitFile`. Chunk items of modules created from it reference these files as output assets.
+#[turbo_tasks::value_trait]
+pub trait SourceWithEmittedFiles: Source {
+    /// The emitted files. The path of each file relative to the root of its filesystem is the
+    /// path relative to the output root it is emitted to.
+    #[turbo_tasks::function]
+    fn emitted_files(self: Vc<Self>) -> Vc<Sources>;
+}
+
+/// Returns the files emitted by the source as output assets in the output root of the chunking
+/// context.
+#[turbo_tasks::function]
+pub async fn emitted_files_output_assets(
+    source: ResolvedVc<Box<dyn Source>>,
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+) -> Result<Vc<OutputAssets>> {
+    let Some(source) = ResolvedVc::try_sidecast::<Box<dyn SourceWithEmittedFiles>>(source) else {
+        return Ok(OutputAssets::empty());
+    };
+    let output_root = chunking_context.output_root().owned().await?;
+    let assets = source
+        .emitted_files()
+        .await?
+        .iter()
+        .map(|file| {
+            let output_root = output_root.clone();
+            async move {
+                let path = output_root.join(&file.ident().path().await?.path)?;
+                Ok(ResolvedVc::upcast::<Box<dyn OutputAsset>>(
+                    VirtualOutputAsset::new(path, file.content().to_resolved().await?)
+                        .to_resolved()
+                        .await?,
+                ))
+            }
+        })
+        .try_join()
+        .await?;
+    Ok(Vc::cell(assets))
+}
+
 #[turbo_tasks::value(transparent)]
 pub struct OptionSource(Option<ResolvedVc<Box<dyn Source>>>);
 
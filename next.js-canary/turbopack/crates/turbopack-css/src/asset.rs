@@ -14,12 +14,12 @@ use turbopack_core::{
     reference::{ModuleReference, ModuleReferences},
     reference_type::ImportContext,
     resolve::origin::ResolveOrigin,
-    source::Source,
+    source::{Source, emitted_files_output_assets},
     source_map::GenerateSourceMap,
 };
 
 use crate::{
-    CssModuleAssetType,
+    CssModuleAssetType, ModuleCssAsset,
     chunk::{CssChunkItem, CssChunkItemContent, CssChunkPlaceable, CssChunkType, CssImport},
     code_gen::CodeGenerateable,
     process::{
@@ -241,6 +241,13 @@ impl ChunkItem for CssModuleChunkItem {
                 }
             }
         }
+        let module = self.module.await?;
+        references.extend(
+            emitted_files_output_assets(*module.source, *self.chunking_context)
+                .await?
+                .iter()
+                .copied(),
+        );
         Ok(Vc::cell(references))
     }
 }
@@ -320,9 +327,10 @@ impl CssChunkItem for CssModuleChunkItem {
             }
         }
 
+        let minify_type = *chunking_context.minify_type().await?;
         let result = self
             .module
-            .finalize_css(*chunking_context, *chunking_context.minify_type().await?)
+            .finalize_css(*chunking_context, minify_type)
             .await?;
 
         if let FinalCssResult::Ok {
@@ -331,11 +339,23 @@ impl CssChunkItem for CssModuleChunkItem {
             ..
         } = &*result
         {
+            let module = self.module.await?;
+            // Only minified chunks are optimized, see `CssChunk::code`.
+            let unused_classes = match module.ty {
+                CssModuleAssetType::Module if matches!(minify_type, MinifyType::Minify { .. }) => {
+                    ModuleCssAsset::new(*module.source, *module.asset_context)
+                        .unused_classes(*chunking_context)
+                        .owned()
+                        .await?
+                }
+                _ => Vec::new(),
+            };
             Ok(CssChunkItemContent {
                 inner_code: output_code.to_owned().into(),
                 imports,
-                import_context: self.module.await?.import_context,
+                import_context: module.import_context,
                 source_map: source_map.owned().await?,
+                unused_classes,
             }
             .into())
         } else {
@@ -348,6 +368,7 @@ impl CssChunkItem for CssModuleChunkItem {
                 imports: vec![],
                 import_context: None,
                 source_map: None,
+                unused_classes: vec![],
             }
             .into())
         }
@@ -1,13 +1,15 @@
+mod optimize;
 pub(crate) mod single_item_chunk;
 pub mod source_map;
 
-use std::fmt::Write;
+use std::{borrow::Cow, fmt::Write};
 
 use anyhow::{Result, bail};
 use swc_core::common::pass::Either;
 use turbo_rcstr::{RcStr, rcstr};
 use turbo_tasks::{
-    FxIndexSet, ResolvedVc, TryFlatJoinIterExt, TryJoinIterExt, ValueDefault, ValueToString, Vc,
+    FxIndexSet, ReadRef, ResolvedVc, TryFlatJoinIterExt, TryJoinIterExt, ValueDefault,
+    ValueToString, Vc,
 };
 use turbo_tasks_fs::{
     File, FileSystem, FileSystemPath,
@@ -35,13 +37,19 @@ use turbopack_core::{
     source_map::{GenerateSourceMap, OptionStringifiedSourceMap, utils::fileify_source_map},
 };
 
-use self::{single_item_chunk::chunk::SingleItemCssChunk, source_map::CssChunkSourceMapAsset};
+use self::{
+    optimize::{CssChunkItemToOptimize, OptimizedCssChunkItems, optimize_css_chunk_items},
+    single_item_chunk::chunk::SingleItemCssChunk,
+    source_map::CssChunkSourceMapAsset,
+};
 use crate::{ImportAssetReference, util::stringify_js};
 
 #[turbo_tasks::value]
 pub struct CssChunk {
     pub chunking_context: ResolvedVc<Box<dyn ChunkingContext>>,
     pub content: ResolvedVc<CssChunkContent>,
+    /// A prefix for the file name of the chunk.
+    pub name: Option<RcStr>,
 }
 
 #[turbo_tasks::value_impl]
@@ -50,10 +58,12 @@ impl CssChunk {
     pub fn new(
         chunking_context: ResolvedVc<Box<dyn ChunkingContext>>,
         content: ResolvedVc<CssChunkContent>,
+        name: Option<RcStr>,
     ) -> Vc<Self> {
         CssChunk {
             chunking_context,
             content,
+            name,
         }
         .cell()
     }
@@ -74,42 +84,70 @@ impl CssChunk {
             .reference_chunk_source_maps(Vc::upcast(self))
             .await?;
 
+        let minify_type = *this.chunking_context.minify_type().await?;
+        let contents = this
+            .content
+            .await?
+            .chunk_items
+            .iter()
+            .map(|css_item| css_item.content())
+            .try_join()
+            .await?;
+        let optimized = if matches!(minify_type, MinifyType::Minify { .. }) {
+            optimize_contents(&contents, source_maps).await?
+        } else {
+            OptimizedCssChunkItems::default()
+        };
+
         let mut code = CodeBuilder::new(source_maps);
         let mut body = CodeBuilder::new(source_maps);
         let mut external_imports = FxIndexSet::default();
-        for css_item in &this.content.await?.chunk_items {
-            let content = &css_item.content().await?;
+        for (i, (css_item, content)) in this
+            .content
+            .await?
+            .chunk_items
+            .iter()
+            .zip(&contents)
+            .enumerate()
+        {
             for import in &content.imports {
                 if let CssImport::External(external_import) = import {
                     external_imports.insert((*external_import.await?).to_string());
                 }
             }
 
-            if matches!(
-                &*this.chunking_context.minify_type().await?,
-                MinifyType::NoMinify
-            ) {
+            if matches!(minify_type, MinifyType::NoMinify) {
                 let id = css_item.asset_ident().to_string().await?;
                 writeln!(body, "/* {id} */")?;
             }
 
             let close = write_import_context(&mut body, content.import_context).await?;
 
+            let (inner_code, source_map) = match optimized.items.get(i) {
+                Some(Some(optimized)) => (
+                    Cow::Owned(Rope::from(optimized.code.clone())),
+                    optimized.source_map.as_ref(),
+                ),
+                _ => (
+                    Cow::Borrowed(&content.inner_code),
+                    content.source_map.as_ref(),
+                ),
+            };
             let source_map = if *self
                 .chunking_context()
                 .should_use_file_source_map_uris()
                 .await?
             {
                 fileify_source_map(
-                    content.source_map.as_ref(),
+                    source_map,
                     self.chunking_context().root_path().owned().await?,
                 )
                 .await?
             } else {
-                content.source_map.clone()
+                source_map.cloned()
             };
 
-            body.push_source(&content.inner_code, source_map);
+            body.push_source(&inner_code, source_map);
 
             if !close.is_empty() {
                 writeln!(body, "{close}")?;
@@ -120,6 +158,9 @@ impl CssChunk {
         for external_import in external_imports {
             writeln!(code, "@import {};", stringify_js(&external_import))?;
         }
+        if !optimized.layer_order.is_empty() {
+            writeln!(code, "@layer {};", optimized.layer_order.join(", "))?;
+        }
 
         let built = &body.build();
         code.push_code(built);
@@ -206,6 +247,59 @@ impl CssChunk {
     }
 }
 
+/// Runs the [optimize_css_chunk_items] pass over the contents of the chunk items of a minified
+/// chunk. The source maps of the changed items are kept when `source_maps` is set.
+async fn optimize_contents(
+    contents: &[ReadRef<CssChunkItemContent>],
+    source_maps: bool,
+) -> Result<OptimizedCssChunkItems> {
+    let mut codes = Vec::with_capacity(contents.len());
+    let mut item_source_maps = Vec::with_capacity(contents.len());
+    let mut contexts = Vec::with_capacity(contents.len());
+    for content in contents {
+        codes.push(content.inner_code.to_str()?);
+        item_source_maps.push(match &content.source_map {
+            Some(source_map) if source_maps => Some(source_map.to_str()?),
+            _ => None,
+        });
+        let import_context = match content.import_context {
+            Some(import_context) => Some(import_context.await?),
+            None => None,
+        };
+        contexts.push(import_context);
+    }
+    let items = contents
+        .iter()
+        .zip(&codes)
+        .zip(&item_source_maps)
+        .zip(&contexts)
+        .map(|(((content, code), source_map), import_context)| {
+            let (layer, conditional, context_key) = match import_context {
+                Some(import_context) => (
+                    (!import_context.layers.is_empty()).then(|| import_context.layers.join(".")),
+                    !import_context.media.is_empty() || !import_context.supports.is_empty(),
+                    format!(
+                        "{}|{}|{}",
+                        import_context.layers.join("."),
+                        import_context.media.join(" and "),
+                        import_context.supports.join(" and ")
+                    ),
+                ),
+                None => (None, false, String::new()),
+            };
+            CssChunkItemToOptimize {
+                code: code.as_ref(),
+                source_map: source_map.as_deref(),
+                layer,
+                conditional,
+                context_key,
+                unused_classes: &content.unused_classes,
+            }
+        })
+        .collect::<Vec<_>>();
+    Ok(optimize_css_chunk_items(&items, true))
+}
+
 pub async fn write_import_context(
     body: &mut impl std::io::Write,
     import_context: Option<ResolvedVc<ImportContext>>,
@@ -328,11 +422,12 @@ impl OutputAsset for CssChunk {
     #[turbo_tasks::function]
     async fn path(self: Vc<Self>) -> Result<Vc<FileSystemPath>> {
         let ident = self.ident_for_path();
+        let this = self.await?;
 
-        Ok(self.await?.chunking_context.chunk_path(
+        Ok(this.chunking_context.chunk_path(
             Some(Vc::upcast(self)),
             ident,
-            None,
+            this.name.clone(),
             rcstr!(".css"),
         ))
     }
@@ -419,6 +514,9 @@ pub struct CssChunkItemContent {
     pub imports: Vec<CssImport>,
     pub inner_code: Rope,
     pub source_map: Option<Rope>,
+    /// Local class names of a CSS module that no JS module reads. These are removed from
+    /// optimized chunks.
+    pub unused_classes: Vec<RcStr>,
 }
 
 #[turbo_tasks::value_trait]
@@ -503,6 +601,7 @@ impl ChunkType for CssChunkType {
     async fn chunk(
         &self,
         chunking_context: ResolvedVc<Box<dyn ChunkingContext>>,
+        name: Option<RcStr>,
         chunk_items_or_batches: Vec<ChunkItemOrBatchWithAsyncModuleInfo>,
         _batch_groups: Vec<ResolvedVc<ChunkItemBatchGroup>>,
         referenced_output_assets: ResolvedVc<OutputAssets>,
@@ -537,7 +636,7 @@ impl ChunkType for CssChunkType {
             referenced_output_assets,
         }
         .cell();
-        Ok(Vc::upcast(CssChunk::new(*chunking_context, content)))
+        Ok(Vc::upcast(CssChunk::new(*chunking_context, content, name)))
     }
 
     #[turbo_tasks::function]
@@ -0,0 +1,501 @@
+use std::convert::Infallible;
+
+use anyhow::Result;
+use lightningcss::{
+    rules::{CssRule, layer::LayerName},
+    selector::{Component, Selector},
+    stylesheet::{ParserOptions, PrinterOptions, StyleSheet},
+    traits::ToCss,
+    visit_types,
+    visitor::{Visit, Visitor},
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use swc_core::base::sourcemap::{SourceMap, SourceMapBuilder};
+use turbo_rcstr::RcStr;
+use turbo_tasks::FxIndexSet;
+use turbo_tasks_fs::rope::Rope;
+use turbopack_core::source_map::utils::add_default_ignore_list;
+
+/// A chunk item as seen by [optimize_css_chunk_items].
+pub(crate) struct CssChunkItemToOptimize<'a> {
+    pub code: &'a str,
+    /// The source map of `code`, which the source map of the optimized code maps through.
+    pub source_map: Option<&'a str>,
+    /// The `@layer` the item is wrapped in by its import context, as a dotted name.
+    pub layer: Option<String>,
+    /// Whether the item is wrapped in `@media` or `@supports` by its import context.
+    pub conditional: bool,
+    /// Identifies the wrappers of the import context, rules are only deduplicated within the
+    /// same wrappers.
+    pub context_key: String,
+    /// Local class names of a CSS module that no JS module reads.
+    pub unused_classes: &'a [RcStr],
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct OptimizedCssChunkItems {
+    /// The order of all named layers, to be declared once at the start of the chunk. Empty when
+    /// the layer declarations were left in place.
+    pub layer_order: Vec<String>,
+    /// The new code of the items that were changed, `None` for unchanged items.
+    pub items: Vec<Option<OptimizedCssChunkItem>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct OptimizedCssChunkItem {
+    pub code: String,
+    /// Maps the optimized code to the sources of the item, when the item had a source map.
+    pub source_map: Option<Rope>,
+}
+
+/// Optimizes the items of a CSS chunk across module boundaries:
+///
+/// - Style rules that only match local classes of a CSS module that no JS module reads are removed.
+/// - Style rules that are repeated verbatim by a later module (in the same import context) are
+///   removed, as the later occurrence wins the cascade anyway.
+/// - `@layer` statements are merged into a single statement with the canonical layer order. As the
+///   order of layers is defined by their first declaration, this is only done when all layer
+///   declarations are named and unconditional, otherwise they are left in place.
+///
+/// Items that can't be parsed are left untouched.
+pub(crate) fn optimize_css_chunk_items(
+    items: &[CssChunkItemToOptimize<'_>],
+    minify: bool,
+) -> OptimizedCssChunkItems {
+    let mut sheets = items
+        .iter()
+        .map(|item| StyleSheet::parse(item.code, ParserOptions::default()).ok())
+        .collect::<Vec<_>>();
+    let mut changed = vec![false; items.len()];
+
+    for ((item, sheet), changed) in items.iter().zip(sheets.iter_mut()).zip(changed.iter_mut()) {
+        if let Some(sheet) = sheet
+            && !item.unused_classes.is_empty()
+        {
+            let unused_classes = item
+                .unused_classes
+                .iter()
+                .map(|class| class.as_str())
+                .collect::<FxHashSet<_>>();
+            *changed |= remove_unused_class_rules(&mut sheet.rules.0, &unused_classes);
+        }
+    }
+
+    remove_duplicated_rules(items, &mut sheets, &mut changed);
+
+    let layer_order = hoist_layer_declarations(items, &mut sheets, &mut changed);
+
+    let items = items
+        .iter()
+        .zip(&sheets)
+        .zip(changed)
+        .map(|((item, sheet), changed)| {
+            let sheet = sheet.as_ref().filter(|_| changed)?;
+            let mut source_map = item
+                .source_map
+                .map(|_| parcel_sourcemap::SourceMap::new(""));
+            let code = sheet
+                .to_css(PrinterOptions {
+                    minify,
+                    source_map: source_map.as_mut(),
+                    ..Default::default()
+                })
+                .ok()?
+                .code;
+            let source_map = match (&source_map, item.source_map) {
+                (Some(source_map), Some(original)) => {
+                    map_through(source_map, original).ok().flatten()
+                }
+                _ => None,
+            };
+            Some(OptimizedCssChunkItem { code, source_map })
+        })
+        .collect();
+
+    OptimizedCssChunkItems { layer_order, items }
+}
+
+/// Maps the `printed` source map, which points into the code of an item, through the `original`
+/// source map of the item to its sources.
+fn map_through(printed: &parcel_sourcemap::SourceMap, original: &str) -> Result<Option<Rope>> {
+    let original = SourceMap::from_slice(original.as_bytes())?;
+    let mut builder = SourceMapBuilder::new(None);
+    for (index, source) in original.sources().enumerate() {
+        builder.add_source(source.clone());
+        builder.set_source_contents(
+            index as _,
+            original.get_source_contents(index as _).cloned(),
+        );
+    }
+    for mapping in printed.get_mappings() {
+        let Some(location) = mapping.original else {
+            continue;
+        };
+        let Some(token) = original
+            .lookup_token(location.original_line, location.original_column)
+            .filter(|token| token.has_source())
+        else {
+            continue;
+        };
+        builder.add_raw(
+            mapping.generated_line,
+            mapping.generated_column,
+            token.get_src_line(),
+            token.get_src_col(),
+            Some(token.get_src_id()),
+            None,
+            false,
+        );
+    }
+    let mut source_map = builder.into_sourcemap();
+    add_default_ignore_list(&mut source_map);
+    let mut result = Vec::new();
+    source_map.to_writer(&mut result)?;
+    Ok(Some(Rope::from(result)))
+}
+
+/// Removes the selectors that require one of the `unused_classes`, and the rules without any
+/// selector left.
+fn remove_unused_class_rules(rules: &mut Vec<CssRule>, unused_classes: &FxHashSet<&str>) -> bool {
+    let mut changed = false;
+    rules.retain_mut(|rule| match rule {
+        CssRule::Style(style) => {
+            let len = style.selectors.0.len();
+            style
+                .selectors
+                .0
+                .retain(|selector| !requires_class(selector, unused_classes));
+            changed |= style.selectors.0.len() != len;
+            !style.selectors.0.is_empty()
+        }
+        CssRule::Media(media) => {
+            changed |= remove_unused_class_rules(&mut media.rules.0, unused_classes);
+            !media.rules.0.is_empty()
+        }
+        CssRule::Supports(supports) => {
+            changed |= remove_unused_class_rules(&mut supports.rules.0, unused_classes);
+            !supports.rules.0.is_empty()
+        }
+        CssRule::Container(container) => {
+            changed |= remove_unused_class_rules(&mut container.rules.0, unused_classes);
+            !container.rules.0.is_empty()
+        }
+        // An empty layer block still declares the layer.
+        CssRule::LayerBlock(layer) => {
+            changed |= remove_unused_class_rules(&mut layer.rules.0, unused_classes);
+            true
+        }
+        _ => true,
+    });
+    changed
+}
+
+/// Whether an element has to have one of the `classes` to match the selector. Classes in
+/// arguments of pseudo classes like `:not()` or `:is()` are not considered.
+fn requires_class(selector: &Selector, classes: &FxHashSet<&str>) -> bool {
+    selector.iter_raw_parse_order_from(0).any(
+        |component| matches!(component, Component::Class(class) if classes.contains(&*class.0)),
+    )
+}
+
+/// Removes top-level style rules that occur again in a later item with the same import context.
+fn remove_duplicated_rules(
+    items: &[CssChunkItemToOptimize<'_>],
+    sheets: &mut [Option<StyleSheet>],
+    changed: &mut [bool],
+) {
+    let keys = sheets
+        .iter()
+        .map(|sheet| {
+            let Some(sheet) = sheet else {
+                return Vec::new();
+            };
+            sheet
+                .rules
+                .0
+                .iter()
+                .map(|rule| match rule {
+                    CssRule::Style(_) => rule
+                        .to_css_string(PrinterOptions {
+                            minify: true,
+                            ..Default::default()
+                        })
+                        .ok(),
+                    _ => None,
+                })
+                .collect()
+        })
+        .collect::<Vec<Vec<Option<String>>>>();
+
+    let mut last_occurrence = FxHashMap::default();
+    for (i, (item, keys)) in items.iter().zip(&keys).enumerate() {
+        for key in keys.iter().flatten() {
+            last_occurrence.insert((item.context_key.as_str(), key.as_str()), i);
+        }
+    }
+
+    for (i, ((item, keys), sheet)) in items.iter().zip(&keys).zip(sheets).enumerate() {
+        let Some(sheet) = sheet else {
+            continue;
+        };
+        let mut keys = keys.iter();
+        let len = sheet.rules.0.len();
+        sheet.rules.0.retain(|_| match keys.next() {
+            Some(Some(key)) => last_occurrence[&(item.context_key.as_str(), key.as_str())] == i,
+            _ => true,
+        });
+        changed[i] |= sheet.rules.0.len() != len;
+    }
+}
+
+/// Collects the canonical order of all layers and removes the `@layer` statements from the items.
+/// Returns an empty order and leaves the items unchanged when any layer is anonymous, declared
+/// conditionally or declared in an item that couldn't be parsed.
+fn hoist_layer_declarations(
+    items: &[CssChunkItemToOptimize<'_>],
+    sheets: &mut [Option<StyleSheet>],
+    changed: &mut [bool],
+) -> Vec<String> {
+    let mut order = FxIndexSet::default();
+    for (item, sheet) in items.iter().zip(sheets.iter_mut()) {
+        let Some(sheet) = sheet else {
+            if item.code.contains("@layer") {
+                return Vec::new();
+            }
+            continue;
+        };
+        let mut counter = LayerRuleCounter(0);
+        let _ = sheet.visit(&mut counter);
+        if counter.0 > 0 && item.conditional {
+            return Vec::new();
+        }
+        if let Some(layer) = &item.layer {
+            if layer.is_empty() || layer.split('.').any(str::is_empty) {
+                return Vec::new();
+            }
+            order.insert(layer.clone());
+        }
+        let Some(collected) = collect_layers(&sheet.rules.0, item.layer.as_deref(), &mut order)
+        else {
+            return Vec::new();
+        };
+        // Layer rules nested in anything but a named layer block are declared conditionally.
+        if collected != counter.0 {
+            return Vec::new();
+        }
+    }
+
+    for (sheet, changed) in sheets.iter_mut().zip(changed) {
+        if let Some(sheet) = sheet {
+            *changed |= remove_layer_statements(&mut sheet.rules.0);
+        }
+    }
+    order.into_iter().collect()
+}
+
+/// Collects the names of the layers declared by the statements and named blocks in `rules` (and
+/// nested named blocks) in order. Returns the number of layer rules seen, or `None` when an
+/// anonymous layer is found.
+fn collect_layers(
+    rules: &[CssRule],
+    prefix: Option<&str>,
+    order: &mut FxIndexSet<String>,
+) -> Option<usize> {
+    let qualify = |name: &LayerName| {
+        let name = name.to_css_string(PrinterOptions::default()).ok()?;
+        Some(match prefix {
+            Some(prefix) => format!("{prefix}.{name}"),
+            None => name,
+        })
+    };
+    let mut count = 0;
+    for rule in rules {
+        match rule {
+            CssRule::LayerStatement(statement) => {
+                count += 1;
+                for name in &statement.names {
+                    order.insert(qualify(name)?);
+                }
+            }
+            CssRule::LayerBlock(block) => {
+                count += 1;
+                let name = qualify(block.name.as_ref()?)?;
+                order.insert(name.clone());
+                count += collect_layers(&block.rules.0, Some(&name), order)?;
+            }
+            _ => {}
+        }
+    }
+    Some(count)
+}
+
+fn remove_layer_statements(rules: &mut Vec<CssRule>) -> bool {
+    let len = rules.len();
+    rules.retain(|rule| !matches!(rule, CssRule::LayerStatement(_)));
+    let mut changed = rules.len() != len;
+    for rule in rules {
+        if let CssRule::LayerBlock(block) = rule {
+            changed |= remove_layer_statements(&mut block.rules.0);
+        }
+    }
+    changed
+}
+
+/// Counts all `@layer` rules, at any depth.
+struct LayerRuleCounter(usize);
+
+impl<'i> Visitor<'i> for LayerRuleCounter {
+    type Error = Infallible;
+
+    fn visit_types(&self) -> lightningcss::visitor::VisitTypes {
+        visit_types!(RULES)
+    }
+
+    fn visit_rule(&mut self, rule: &mut CssRule<'i>) -> Result<(), Self::Error> {
+        if matches!(rule, CssRule::LayerStatement(_) | CssRule::LayerBlock(_)) {
+            self.0 += 1;
+        }
+        rule.visit_children(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::base::sourcemap::{SourceMap, SourceMapBuilder};
+    use turbo_rcstr::RcStr;
+
+    use super::{CssChunkItemToOptimize, OptimizedCssChunkItems, optimize_css_chunk_items};
+
+    fn item<'a>(code: &'a str, unused_classes: &'a [RcStr]) -> CssChunkItemToOptimize<'a> {
+        CssChunkItemToOptimize {
+            code,
+            source_map: None,
+            layer: None,
+            conditional: false,
+            context_key: String::new(),
+            unused_classes,
+        }
+    }
+
+    fn codes(result: &OptimizedCssChunkItems) -> Vec<Option<&str>> {
+        result
+            .items
+            .iter()
+            .map(|item| item.as_ref().map(|item| item.code.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn removes_unused_classes() {
+        let unused = [RcStr::from("a-unused")];
+        let result = optimize_css_chunk_items(
+            &[item(
+                ".a-used{display:block}.a-unused{display:none}.a-used,.a-unused \
+                 .x{display:flex}:not(.a-unused){display:grid}@media \
+                 print{.a-unused{display:block}}",
+                &unused,
+            )],
+            true,
+        );
+        assert_eq!(
+            codes(&result),
+            vec![Some(
+                ".a-used{display:block}.a-used{display:flex}:not(.a-unused){display:grid}"
+            )]
+        );
+    }
+
+    #[test]
+    fn removes_duplicated_rules() {
+        let mut other_context = item(".b{display:block}", &[]);
+        other_context.context_key = "@media print".to_string();
+        let result = optimize_css_chunk_items(
+            &[
+                item(".b{display:block}.c{display:none}", &[]),
+                item(".b{display:flex}", &[]),
+                item(".b{display:block}", &[]),
+                other_context,
+            ],
+            true,
+        );
+        assert_eq!(
+            codes(&result),
+            vec![Some(".c{display:none}"), None, None, None]
+        );
+    }
+
+    #[test]
+    fn hoists_layer_declarations() {
+        let mut wrapped = item("@layer inner{.d{display:block}}", &[]);
+        wrapped.layer = Some("outer".to_string());
+        let result = optimize_css_chunk_items(
+            &[
+                item("@layer base, components;.a{display:block}", &[]),
+                item("@layer utilities{.b{display:block}}@layer base;", &[]),
+                wrapped,
+            ],
+            true,
+        );
+        assert_eq!(
+            result.layer_order,
+            vec!["base", "components", "utilities", "outer", "outer.inner"]
+        );
+        assert_eq!(
+            codes(&result),
+            vec![
+                Some(".a{display:block}"),
+                Some("@layer utilities{.b{display:block}}"),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_conditional_layer_declarations() {
+        let result = optimize_css_chunk_items(
+            &[
+                item("@layer a;", &[]),
+                item("@media print{@layer b{.b{display:block}}}", &[]),
+            ],
+            true,
+        );
+        assert_eq!(result.layer_order, Vec::<String>::new());
+        assert_eq!(codes(&result), vec![None, None]);
+
+        let result = optimize_css_chunk_items(
+            &[
+                item("@layer a;", &[]),
+                item("@layer{.b{display:none}}", &[]),
+            ],
+            true,
+        );
+        assert_eq!(result.layer_order, Vec::<String>::new());
+    }
+
+    #[test]
+    fn maps_optimized_items_to_their_sources() {
+        let mut builder = SourceMapBuilder::new(None);
+        builder.add_source("turbopack:///[project]/a.module.css".into());
+        builder.add_raw(0, 0, 10, 0, Some(0), None, false);
+        builder.add_raw(1, 0, 20, 0, Some(0), None, false);
+        let mut source_map = Vec::new();
+        builder.into_sourcemap().to_writer(&mut source_map).unwrap();
+        let source_map = String::from_utf8(source_map).unwrap();
+
+        let unused = [RcStr::from("x")];
+        let mut item = item(".x{display:none}\n.y{display:block}\n", &unused);
+        item.source_map = Some(&source_map);
+        let result = optimize_css_chunk_items(&[item], true);
+        let optimized = result.items[0].as_ref().unwrap();
+        assert_eq!(optimized.code, ".y{display:block}");
+
+        let source_map =
+            SourceMap::from_slice(&optimized.source_map.as_ref().unwrap().to_bytes()).unwrap();
+        let token = source_map.lookup_token(0, 0).unwrap();
+        assert_eq!(
+            token.get_source().map(|source| &**source),
+            Some("turbopack:///[project]/a.module.css")
+        );
+        assert_eq!(token.get_src(), (20, 0));
+    }
+}
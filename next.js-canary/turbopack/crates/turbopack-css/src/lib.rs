@@ -11,12 +11,15 @@ mod code_gen;
 pub mod embed;
 mod lifetime_util;
 mod module_asset;
+mod module_script;
 pub(crate) mod process;
 pub(crate) mod references;
+pub mod sass;
 pub(crate) mod util;
 
 pub use asset::CssModuleAsset;
 pub use module_asset::ModuleCssAsset;
+pub use module_script::CssModuleScriptAsset;
 use serde::{Deserialize, Serialize};
 use turbo_tasks::{NonLocalValue, TaskInput, trace::TraceRawVcs};
 
@@ -3,9 +3,10 @@ use std::{fmt::Write, sync::Arc};
 use anyhow::{Context, Result, bail};
 use indoc::formatdoc;
 use lightningcss::css_modules::CssModuleReference;
+use rustc_hash::FxHashSet;
 use swc_core::common::{BytePos, FileName, LineCol, SourceMap};
 use turbo_rcstr::{RcStr, rcstr};
-use turbo_tasks::{FxIndexMap, IntoTraitRef, ResolvedVc, ValueToString, Vc};
+use turbo_tasks::{FxIndexMap, FxIndexSet, IntoTraitRef, ResolvedVc, ValueToString, Vc};
 use turbo_tasks_fs::{FileSystemPath, rope::Rope};
 use turbopack_core::{
     asset::{Asset, AssetContent},
@@ -221,6 +222,45 @@ impl ModuleCssAsset {
         Ok(Vc::cell(classes))
     }
 
+    /// The local class names of this module that no JS module reads, according to the export
+    /// usage information of the chunking context. Classes composed into a used class are used as
+    /// well.
+    #[turbo_tasks::function]
+    pub(crate) async fn unused_classes(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Result<Vc<Vec<RcStr>>> {
+        let export_usage = chunking_context
+            .module_export_usage(Vc::upcast(self))
+            .await?;
+        // The default export is the object of all classes.
+        if export_usage.is_export_used(&rcstr!("default")) {
+            return Ok(Vc::cell(Vec::new()));
+        }
+
+        let mut used_classes = FxHashSet::default();
+        let mut local_classes = FxIndexSet::default();
+        for (export_name, class_names) in &*self.classes().await? {
+            let is_used = export_usage.is_export_used(&RcStr::from(export_name.as_str()));
+            for class_name in class_names {
+                if let ModuleCssClass::Local { name } = class_name {
+                    if is_used {
+                        used_classes.insert(name);
+                    }
+                    local_classes.insert(name);
+                }
+            }
+        }
+
+        Ok(Vc::cell(
+            local_classes
+                .into_iter()
+                .filter(|name| !used_classes.contains(name))
+                .map(|name| RcStr::from(name.as_str()))
+                .collect(),
+        ))
+    }
+
     #[turbo_tasks::function]
     async fn module_references(self: Vc<Self>) -> Result<Vc<ModuleReferences>> {
         let mut references = vec![];
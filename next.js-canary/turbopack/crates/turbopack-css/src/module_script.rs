@@ -0,0 +1,232 @@
+use anyhow::{Context, Result, bail};
+use indoc::formatdoc;
+use turbo_rcstr::rcstr;
+use turbo_tasks::{IntoTraitRef, ResolvedVc, ValueToString, Vc};
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{ChunkItem, ChunkType, ChunkableModule, ChunkingContext},
+    context::AssetContext,
+    ident::AssetIdent,
+    module::Module,
+    module_graph::ModuleGraph,
+    output::OutputAssets,
+    reference_type::{CssReferenceSubType, ReferenceType},
+    resolve::origin::ResolveOrigin,
+    source::Source,
+};
+use turbopack_ecmascript::{
+    chunk::{
+        EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkPlaceable,
+        EcmascriptChunkType, EcmascriptExports,
+    },
+    runtime_functions::{TURBOPACK_EXPORT_VALUE, TURBOPACK_MODULE},
+    utils::StringifyJs,
+};
+
+use crate::{
+    process::{FinalCssResult, ParseCss, ParseCssResult, ProcessCss},
+    references::url::ReferencedAsset,
+};
+
+/// A CSS module script, as in `import sheet from "./style.css" with { type: "css" }`. The default
+/// export is a constructable `CSSStyleSheet` containing the processed CSS. The style sheet is not
+/// added to the document, it's up to the importer to adopt it.
+#[turbo_tasks::value]
+#[derive(Clone)]
+pub struct CssModuleScriptAsset {
+    pub source: ResolvedVc<Box<dyn Source>>,
+    pub asset_context: ResolvedVc<Box<dyn AssetContext>>,
+}
+
+#[turbo_tasks::value_impl]
+impl CssModuleScriptAsset {
+    #[turbo_tasks::function]
+    pub fn new(
+        source: ResolvedVc<Box<dyn Source>>,
+        asset_context: ResolvedVc<Box<dyn AssetContext>>,
+    ) -> Vc<Self> {
+        Self::cell(CssModuleScriptAsset {
+            source,
+            asset_context,
+        })
+    }
+
+    /// The CSS asset that provides the content of the style sheet.
+    #[turbo_tasks::function]
+    async fn inner(&self) -> Result<Vc<Box<dyn ProcessCss>>> {
+        let inner = self
+            .asset_context
+            .process(
+                *self.source,
+                ReferenceType::Css(CssReferenceSubType::Analyze),
+            )
+            .module();
+        Vc::try_resolve_sidecast::<Box<dyn ProcessCss>>(inner)
+            .await?
+            .context("inner asset of a CSS module script should be CSS processable")
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Module for CssModuleScriptAsset {
+    #[turbo_tasks::function]
+    async fn ident(&self) -> Result<Vc<AssetIdent>> {
+        Ok(self
+            .source
+            .ident()
+            .with_modifier(rcstr!("css module script"))
+            .with_layer(self.asset_context.into_trait_ref().await?.layer()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for CssModuleScriptAsset {
+    #[turbo_tasks::function]
+    fn content(&self) -> Result<Vc<AssetContent>> {
+        bail!("CSS module script asset has no contents")
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModule for CssModuleScriptAsset {
+    #[turbo_tasks::function]
+    fn as_chunk_item(
+        self: ResolvedVc<Self>,
+        _module_graph: ResolvedVc<ModuleGraph>,
+        chunking_context: ResolvedVc<Box<dyn ChunkingContext>>,
+    ) -> Vc<Box<dyn ChunkItem>> {
+        Vc::upcast(
+            CssModuleScriptChunkItem {
+                module: self,
+                chunking_context,
+            }
+            .cell(),
+        )
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for CssModuleScriptAsset {
+    #[turbo_tasks::function]
+    fn get_exports(&self) -> Vc<EcmascriptExports> {
+        EcmascriptExports::Value.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ResolveOrigin for CssModuleScriptAsset {
+    #[turbo_tasks::function]
+    fn origin_path(&self) -> Vc<FileSystemPath> {
+        self.source.ident().path()
+    }
+
+    #[turbo_tasks::function]
+    fn asset_context(&self) -> Vc<Box<dyn AssetContext>> {
+        *self.asset_context
+    }
+}
+
+#[turbo_tasks::value]
+struct CssModuleScriptChunkItem {
+    module: ResolvedVc<CssModuleScriptAsset>,
+    chunking_context: ResolvedVc<Box<dyn ChunkingContext>>,
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for CssModuleScriptChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> Vc<AssetIdent> {
+        self.module.ident()
+    }
+
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        Vc::upcast(*self.chunking_context)
+    }
+
+    #[turbo_tasks::function]
+    async fn ty(&self) -> Result<Vc<Box<dyn ChunkType>>> {
+        Ok(Vc::upcast(
+            Vc::<EcmascriptChunkType>::default().resolve().await?,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn module(&self) -> Vc<Box<dyn Module>> {
+        Vc::upcast(*self.module)
+    }
+
+    /// The assets referenced by `url()`s in the style sheet.
+    #[turbo_tasks::function]
+    async fn references(&self) -> Result<Vc<OutputAssets>> {
+        let mut references = Vec::new();
+        let inner = Vc::upcast::<Box<dyn ParseCss>>(self.module.inner());
+        if let ParseCssResult::Ok { url_references, .. } = &*inner.parse_css().await? {
+            for (_, reference) in url_references.await? {
+                if let ReferencedAsset::Some(asset) = *reference
+                    .get_referenced_asset(*self.chunking_context)
+                    .await?
+                {
+                    references.push(asset);
+                }
+            }
+        }
+        Ok(Vc::cell(references))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for CssModuleScriptChunkItem {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<EcmascriptChunkItemContent>> {
+        let result = self
+            .module
+            .inner()
+            .finalize_css(
+                *self.chunking_context,
+                *self.chunking_context.minify_type().await?,
+            )
+            .await?;
+
+        let css = match &*result {
+            FinalCssResult::Ok { output_code, .. } => output_code.clone(),
+            FinalCssResult::Unparsable | FinalCssResult::NotFound => format!(
+                "/* unparsable {} */",
+                self.module.ident().to_string().await?
+            ),
+        };
+
+        // During HMR the updated module replaces the rules of the style sheet created by the
+        // previous version, so documents that adopted it are updated in place. Outside of
+        // browsers (e.g. during server rendering) there is no `CSSStyleSheet`, the module exports
+        // an object with the `cssText` of the style sheet instead.
+        let code = formatdoc! {
+            r#"
+                const css = {css};
+                const hot = {TURBOPACK_MODULE}.hot;
+                let sheet;
+                if (typeof CSSStyleSheet === "function") {{
+                    sheet = hot?.data?.sheet ?? new CSSStyleSheet();
+                    sheet.replaceSync(css);
+                }} else {{
+                    sheet = {{ cssText: css }};
+                }}
+                if (hot) {{
+                    hot.dispose((data) => {{
+                        data.sheet = sheet;
+                    }});
+                    hot.accept();
+                }}
+                {TURBOPACK_EXPORT_VALUE}(sheet);
+            "#,
+            css = StringifyJs(&css),
+        };
+
+        Ok(EcmascriptChunkItemContent {
+            inner_code: code.into(),
+            ..Default::default()
+        }
+        .into())
+    }
+}
@@ -0,0 +1,1023 @@
+use std::{
+    io, iter,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use grass_compiler::{
+    InputSyntax,
+    codemap::{CodeMap, Span},
+    sass_ast::{
+        AstAtRootRule, AstEach, AstFor, AstForwardRule, AstFunctionDecl, AstImport, AstInclude,
+        AstMedia, AstMixin, AstRuleSet, AstStmt, AstStyle, AstSupportsRule, AstUnknownAtRule,
+        AstUseRule, AstWhile, StyleSheet,
+    },
+};
+use rustc_hash::FxHashMap;
+use swc_core::base::sourcemap::SourceMapBuilder;
+use turbo_rcstr::{RcStr, rcstr};
+use turbo_tasks::{ResolvedVc, Vc};
+use turbo_tasks_fs::{File, FileContent, FileSystemPath, rope::Rope};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    context::AssetContext,
+    ident::AssetIdent,
+    issue::{Issue, IssueExt, IssueStage, OptionStyledString, StyledString},
+    reference_type::{CssReferenceSubType, ReferenceType},
+    resolve::{options::ResolveOptions, parse::Request, resolve},
+    source::Source,
+    source_map::{GenerateSourceMap, OptionStringifiedSourceMap},
+};
+
+/// Options for the native Sass compiler.
+#[turbo_tasks::value(shared)]
+#[derive(Default, Clone, Debug)]
+pub struct SassOptions {
+    /// Directories that are searched for `@use`, `@forward` and `@import` urls that can't be
+    /// resolved relative to the importing file (`includePaths`/`loadPaths`).
+    pub load_paths: Vec<FileSystemPath>,
+    /// Code that is prepended to every entry file (`additionalData`).
+    pub additional_data: Option<RcStr>,
+}
+
+/// The CSS compiled from a Sass source, in the SCSS or the indented syntax. The urls of `@use`,
+/// `@forward` and `@import` rules are resolved through the resolver of the `asset_context`, so
+/// packages, import maps and exports fields are taken into account and all files that were read
+/// are tracked as dependencies.
+///
+/// The ident is renamed to `*.css` (`*.module.css` for `*.module.scss`), so the compiled code is
+/// processed by the CSS module rules.
+#[turbo_tasks::value]
+pub struct SassSource {
+    source: ResolvedVc<Box<dyn Source>>,
+    asset_context: ResolvedVc<Box<dyn AssetContext>>,
+    options: ResolvedVc<SassOptions>,
+}
+
+#[turbo_tasks::value_impl]
+impl SassSource {
+    #[turbo_tasks::function]
+    pub fn new(
+        source: ResolvedVc<Box<dyn Source>>,
+        asset_context: ResolvedVc<Box<dyn AssetContext>>,
+        options: ResolvedVc<SassOptions>,
+    ) -> Vc<Self> {
+        Self::cell(SassSource {
+            source,
+            asset_context,
+            options,
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn compile(&self) -> Result<Vc<SassCompileResult>> {
+        let path = self.source.ident().path().owned().await?;
+        let FileContent::Content(file) = &*self.source.content().file_content().await? else {
+            return Ok(SassCompileResult {
+                content: AssetContent::file(FileContent::NotFound.cell())
+                    .to_resolved()
+                    .await?,
+                source_map: ResolvedVc::cell(None),
+            }
+            .cell());
+        };
+        let options = self.options.await?;
+
+        let resolve_options = self
+            .asset_context
+            .resolve_options(
+                path.clone(),
+                ReferenceType::Css(CssReferenceSubType::AtImport(None)),
+            )
+            .to_resolved()
+            .await?;
+        let mut loader = SassLoader {
+            resolve_options,
+            load_paths: &options.load_paths,
+            files: FxHashMap::default(),
+            virtual_paths: FxHashMap::default(),
+            sources: Vec::new(),
+            errors: Vec::new(),
+        };
+        let mut code = loader
+            .annotate(&path, file.content().to_str()?.into_owned())
+            .await?;
+        if let Some(additional_data) = &options.additional_data {
+            code.insert_str(0, &format!("{additional_data}\n"));
+        }
+        let code = loader.rewrite_imports(&path, &code).await?;
+        for error in loader.errors.drain(..) {
+            SassIssue {
+                path: path.clone(),
+                message: error,
+            }
+            .resolved_cell()
+            .emit();
+        }
+
+        let fs = VirtualFs(loader.files);
+        let grass_options = grass_compiler::Options::default()
+            .fs(&fs)
+            .input_syntax(input_syntax(&path))
+            .style(grass_compiler::OutputStyle::Expanded)
+            .quiet(true);
+        let (css, source_map) = match grass_compiler::from_string(code, &grass_options) {
+            Ok(css) => {
+                let (css, source_map) = extract_source_map(&css, &loader.sources)?;
+                (css, source_map)
+            }
+            Err(err) => {
+                SassIssue {
+                    path,
+                    message: err.to_string().into(),
+                }
+                .resolved_cell()
+                .emit();
+                (String::new(), None)
+            }
+        };
+        Ok(SassCompileResult {
+            content: AssetContent::file(FileContent::Content(File::from(css)).cell())
+                .to_resolved()
+                .await?,
+            source_map: ResolvedVc::cell(source_map),
+        }
+        .cell())
+    }
+}
+
+#[turbo_tasks::value]
+struct SassCompileResult {
+    content: ResolvedVc<AssetContent>,
+    source_map: ResolvedVc<OptionStringifiedSourceMap>,
+}
+
+#[turbo_tasks::value_impl]
+impl Source for SassSource {
+    #[turbo_tasks::function]
+    async fn ident(&self) -> Result<Vc<AssetIdent>> {
+        // `foo.scss` becomes `foo.css` and `foo.module.sass` becomes `foo.module.css`. The
+        // original extension as modifier keeps the ident apart from a `foo.css` or another Sass
+        // file with the same stem next to it.
+        let mut ident = self.source.ident().owned().await?;
+        let extension = RcStr::from(ident.path.extension_ref().unwrap_or("sass"));
+        ident.path = ident.path.with_extension("css");
+        ident.add_modifier(extension);
+        Ok(AssetIdent::new(ident))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for SassSource {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        Ok(*self.compile().await?.content)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for SassSource {
+    #[turbo_tasks::function]
+    async fn generate_source_map(self: Vc<Self>) -> Result<Vc<OptionStringifiedSourceMap>> {
+        Ok(*self.compile().await?.source_map)
+    }
+}
+
+/// The syntax of a Sass file by its extension, like Sass determines it.
+fn input_syntax(path: &FileSystemPath) -> InputSyntax {
+    match path.extension_ref() {
+        Some("sass") => InputSyntax::Sass,
+        Some("css") => InputSyntax::Css,
+        _ => InputSyntax::Scss,
+    }
+}
+
+/// A file that was compiled, for the source map.
+struct SassSourceFile {
+    name: RcStr,
+    content: String,
+}
+
+struct SassLoader<'a> {
+    resolve_options: ResolvedVc<ResolveOptions>,
+    load_paths: &'a [FileSystemPath],
+    /// The rewritten code of all loaded files by their virtual path.
+    files: FxHashMap<PathBuf, Vec<u8>>,
+    /// The virtual path for a resolved file and the basename used in the url.
+    virtual_paths: FxHashMap<(FileSystemPath, String), PathBuf>,
+    /// The files that were annotated, indexed by the source in their markers.
+    sources: Vec<SassSourceFile>,
+    errors: Vec<RcStr>,
+}
+
+impl SassLoader<'_> {
+    /// Adds `code` to the sources of the source map and marks its style rules, see
+    /// [annotate_rules].
+    async fn annotate(&mut self, path: &FileSystemPath, code: String) -> Result<String> {
+        let annotated = annotate_rules(&code, self.sources.len(), input_syntax(path));
+        self.sources.push(SassSourceFile {
+            name: path.value_to_string().owned().await?,
+            content: code,
+        });
+        Ok(annotated)
+    }
+
+    /// Replaces the urls of all `@use`, `@forward` and `@import` rules in `code` with the virtual
+    /// paths of the resolved files, loading them (and their imports) in the process.
+    async fn rewrite_imports(&mut self, path: &FileSystemPath, code: &str) -> Result<String> {
+        let mut queue = Vec::new();
+        let code = self.rewrite_imports_of(path, code, &mut queue).await?;
+        while let Some((path, virtual_path)) = queue.pop() {
+            let FileContent::Content(file) = &*path.read().await? else {
+                self.errors
+                    .push(format!("Unable to read {}", path.value_to_string().await?).into());
+                continue;
+            };
+            let code = self
+                .annotate(&path, file.content().to_str()?.into_owned())
+                .await?;
+            let code = self.rewrite_imports_of(&path, &code, &mut queue).await?;
+            self.files.insert(virtual_path, code.into_bytes());
+        }
+        Ok(code)
+    }
+
+    async fn rewrite_imports_of(
+        &mut self,
+        path: &FileSystemPath,
+        code: &str,
+        queue: &mut Vec<(FileSystemPath, PathBuf)>,
+    ) -> Result<String> {
+        let mut result = String::with_capacity(code.len());
+        let mut last = 0;
+        for import in find_imports(code, input_syntax(path)) {
+            let url = &code[import.range.clone()];
+            if is_external_url(url, import.is_import) {
+                continue;
+            }
+            let Some(resolved) = self.resolve(path, url, import.is_import).await? else {
+                self.errors.push(
+                    format!(
+                        "Can't resolve '{url}' in {}",
+                        path.parent().value_to_string().await?
+                    )
+                    .into(),
+                );
+                continue;
+            };
+            let basename = url_basename(url);
+            let extension = resolved.extension_ref().unwrap_or("scss");
+            let key = (resolved.clone(), basename.to_string());
+            let virtual_path = match self.virtual_paths.get(&key) {
+                Some(virtual_path) => virtual_path.clone(),
+                None => {
+                    let virtual_path = PathBuf::from(format!(
+                        "/{}/{basename}.{extension}",
+                        self.virtual_paths.len()
+                    ));
+                    self.virtual_paths.insert(key, virtual_path.clone());
+                    queue.push((resolved, virtual_path.clone()));
+                    virtual_path
+                }
+            };
+            result.push_str(&code[last..import.range.start]);
+            result.push_str(&virtual_path.to_string_lossy());
+            last = import.range.end;
+        }
+        result.push_str(&code[last..]);
+        Ok(result)
+    }
+
+    /// Resolves a Sass url like Sass does: relative to the importing file, then in the load paths
+    /// and finally as a package. For every location the partial (`_name.scss`) and index
+    /// (`name/_index.scss`) variants are tried.
+    async fn resolve(
+        &self,
+        path: &FileSystemPath,
+        url: &str,
+        is_import: bool,
+    ) -> Result<Option<FileSystemPath>> {
+        let (url, is_module) = match url.strip_prefix('~') {
+            Some(url) => (url, true),
+            None => (url, false),
+        };
+        let is_relative = url.starts_with("./") || url.starts_with("../");
+        let lookup_paths = if is_module {
+            vec![]
+        } else {
+            let mut lookup_paths = vec![path.parent()];
+            if !is_relative {
+                lookup_paths.extend(self.load_paths.iter().cloned());
+            }
+            lookup_paths
+        };
+        let candidates = url_candidates(url, is_import);
+        for lookup_path in lookup_paths {
+            for candidate in &candidates {
+                let request = if is_relative {
+                    candidate.clone()
+                } else {
+                    format!("./{candidate}")
+                };
+                if let Some(resolved) = self.resolve_request(lookup_path.clone(), request).await? {
+                    return Ok(Some(resolved));
+                }
+            }
+        }
+        if !is_relative {
+            for candidate in candidates {
+                if let Some(resolved) = self.resolve_request(path.parent(), candidate).await? {
+                    return Ok(Some(resolved));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    async fn resolve_request(
+        &self,
+        lookup_path: FileSystemPath,
+        request: String,
+    ) -> Result<Option<FileSystemPath>> {
+        let result = resolve(
+            lookup_path,
+            ReferenceType::Css(CssReferenceSubType::AtImport(None)),
+            Request::parse_string(request.into()),
+            *self.resolve_options,
+        )
+        .await?;
+        Ok(match *result.first_source().await? {
+            Some(source) => Some(source.ident().path().owned().await?),
+            None => None,
+        })
+    }
+}
+
+/// Sass `@use`/`@forward` urls that are not loaded from the file system, and `@import` urls that
+/// are plain CSS imports.
+fn is_external_url(url: &str, is_import: bool) -> bool {
+    url.starts_with("sass:")
+        || url.starts_with("http://")
+        || url.starts_with("https://")
+        || url.starts_with("//")
+        || url.contains("#{")
+        || (is_import && url.ends_with(".css"))
+}
+
+/// The last segment of the url without extension, which Sass uses as the default namespace of a
+/// module.
+fn url_basename(url: &str) -> &str {
+    let name = url.rsplit_once('/').map_or(url, |(_, name)| name);
+    name.strip_suffix(".scss")
+        .or_else(|| name.strip_suffix(".sass"))
+        .or_else(|| name.strip_suffix(".css"))
+        .unwrap_or(name)
+}
+
+/// The paths Sass tries for a url, in order. `@import` can't load CSS files, since these are kept
+/// as plain CSS imports.
+fn url_candidates(url: &str, is_import: bool) -> Vec<String> {
+    let (prefix, name) = match url.rsplit_once('/') {
+        Some((dir, name)) => (format!("{dir}/"), name),
+        None => (String::new(), url),
+    };
+    if name.ends_with(".scss") || name.ends_with(".sass") || name.ends_with(".css") {
+        return vec![format!("{prefix}{name}"), format!("{prefix}_{name}")];
+    }
+    let mut candidates = Vec::new();
+    if is_import {
+        for extension in ["scss", "sass"] {
+            candidates.push(format!("{prefix}{name}.import.{extension}"));
+            candidates.push(format!("{prefix}_{name}.import.{extension}"));
+        }
+    }
+    for extension in ["scss", "sass"] {
+        candidates.push(format!("{prefix}{name}.{extension}"));
+        candidates.push(format!("{prefix}_{name}.{extension}"));
+    }
+    if !is_import {
+        candidates.push(format!("{prefix}{name}.css"));
+        candidates.push(format!("{prefix}_{name}.css"));
+    }
+    for extension in ["scss", "sass"] {
+        candidates.push(format!("{url}/index.{extension}"));
+        candidates.push(format!("{url}/_index.{extension}"));
+    }
+    candidates
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct SassImport {
+    /// The range of the url within the code, without quotes.
+    range: Range<usize>,
+    is_import: bool,
+}
+
+/// Parses `code` for the positions of its rules. Code that doesn't parse is left to the compiler,
+/// which reports the error.
+fn parse(code: &str, syntax: InputSyntax) -> Option<StyleSheet> {
+    grass_compiler::parse_stylesheet(
+        code.to_string(),
+        "stdin",
+        &grass_compiler::Options::default().input_syntax(syntax),
+    )
+    .ok()
+}
+
+/// The byte range of a span of a stylesheet returned by [parse]. The parser adds the code as the
+/// only file of a new code map, so its positions are relative to the start of such a file.
+fn span_range(span: Span) -> Range<usize> {
+    let file_start = CodeMap::new()
+        .add_file(String::new(), String::new())
+        .span
+        .low();
+    (span.low() - file_start) as usize..(span.high() - file_start) as usize
+}
+
+/// Calls `visit` for all statements in `body` and the statements nested in them, in source order.
+fn visit_statements<'a>(body: &'a [AstStmt], visit: &mut impl FnMut(&'a AstStmt)) {
+    for stmt in body {
+        visit(stmt);
+        match stmt {
+            AstStmt::RuleSet(AstRuleSet { body, .. })
+            | AstStmt::Style(AstStyle { body, .. })
+            | AstStmt::Media(AstMedia { body, .. })
+            | AstStmt::Supports(AstSupportsRule { body, .. })
+            | AstStmt::AtRootRule(AstAtRootRule { body, .. })
+            | AstStmt::Mixin(AstMixin { body, .. })
+            | AstStmt::FunctionDecl(AstFunctionDecl { body, .. })
+            | AstStmt::For(AstFor { body, .. })
+            | AstStmt::Each(AstEach { body, .. })
+            | AstStmt::While(AstWhile { body, .. })
+            | AstStmt::UnknownAtRule(AstUnknownAtRule {
+                body: Some(body), ..
+            }) => visit_statements(body, visit),
+            AstStmt::Include(AstInclude {
+                content: Some(content),
+                ..
+            }) => visit_statements(&content.body, visit),
+            AstStmt::If(rule) => {
+                for clause in &rule.if_clauses {
+                    visit_statements(&clause.body, visit);
+                }
+                if let Some(body) = &rule.else_clause {
+                    visit_statements(body, visit);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Finds the urls of all `@use`, `@forward` and `@import` rules with the spans of the Sass parser.
+/// Plain CSS imports are kept in the compiled CSS and skipped. The parser only accepts quoted urls,
+/// also for `@import` in the indented syntax.
+fn find_imports(code: &str, syntax: InputSyntax) -> Vec<SassImport> {
+    let Some(stylesheet) = parse(code, syntax) else {
+        return Vec::new();
+    };
+    let mut imports = Vec::new();
+    visit_statements(&stylesheet.body, &mut |stmt| match stmt {
+        AstStmt::Use(AstUseRule { span, .. }) | AstStmt::Forward(AstForwardRule { span, .. }) => {
+            if let Some(range) = at_rule_url(code, span_range(*span).start) {
+                imports.push(SassImport {
+                    range,
+                    is_import: false,
+                });
+            }
+        }
+        AstStmt::ImportRule(rule) => {
+            for import in &rule.imports {
+                // The span of an import starts with the quote of its url
+                if let AstImport::Sass(import) = import {
+                    imports.push(SassImport {
+                        range: skip_string(code.as_bytes(), span_range(import.span).start),
+                        is_import: true,
+                    });
+                }
+            }
+        }
+        _ => {}
+    });
+    imports
+}
+
+/// Returns the range of the url of the `@use` or `@forward` rule at `start`. The span of these
+/// rules starts with the `@`, the url follows after the keyword and whitespace or comments.
+fn at_rule_url(code: &str, start: usize) -> Option<Range<usize>> {
+    let bytes = code.as_bytes();
+    let mut i = start + 1;
+    while bytes.get(i).is_some_and(u8::is_ascii_alphabetic) {
+        i += 1;
+    }
+    loop {
+        while bytes.get(i).is_some_and(u8::is_ascii_whitespace) {
+            i += 1;
+        }
+        if code[i..].starts_with("/*") {
+            i = code[i + 2..]
+                .find("*/")
+                .map_or(bytes.len(), |end| i + end + 4);
+        } else if code[i..].starts_with("//") {
+            i = code[i..].find('\n').map_or(bytes.len(), |end| i + end);
+        } else {
+            break;
+        }
+    }
+    matches!(bytes.get(i), Some(b'"' | b'\'')).then(|| skip_string(bytes, i))
+}
+
+/// Returns the range of the content of the string starting with the quote at `start`.
+fn skip_string(bytes: &[u8], start: usize) -> Range<usize> {
+    let quote = bytes[start];
+    let mut i = start + 1;
+    while i < bytes.len() && bytes[i] != quote {
+        if bytes[i] == b'\\' {
+            i += 1;
+        }
+        i += 1;
+    }
+    start + 1..i.min(bytes.len())
+}
+
+/// Marks the start of a comment that [annotate_rules] adds to style rules.
+const MARKER: &str = "/*#turbopack-sass:";
+
+/// grass doesn't generate source maps and doesn't expose the spans of the compiled CSS. Instead
+/// every style rule gets a comment with the position of its selector,
+/// `/*#turbopack-sass:<source>:<line>:<column>*/`, as the first statement. The rules are found with
+/// the spans of the Sass parser. Sass keeps these comments in the compiled rules,
+/// [extract_source_map] removes them and maps the rules.
+fn annotate_rules(code: &str, source: usize, syntax: InputSyntax) -> String {
+    let Some(stylesheet) = parse(code, syntax) else {
+        return code.to_string();
+    };
+    let line_starts = iter::once(0)
+        .chain(code.match_indices('\n').map(|(index, _)| index + 1))
+        .collect::<Vec<_>>();
+    let mut insertions = Vec::new();
+    visit_statements(&stylesheet.body, &mut |stmt| {
+        let AstStmt::RuleSet(rule) = stmt else {
+            return;
+        };
+        if rule.body.is_empty() {
+            return;
+        }
+        let selector = span_range(rule.selector_span);
+        let line = line_starts.partition_point(|&start| start <= selector.start) - 1;
+        let column = code[line_starts[line]..selector.start]
+            .encode_utf16()
+            .count();
+        let marker = format!("{MARKER}{source}:{line}:{column}*/");
+        if syntax == InputSyntax::Sass {
+            // A line before the first child, with the same indentation
+            let Some(child_line) = line_starts[line + 1..]
+                .iter()
+                .copied()
+                .filter(|&start| start > selector.end)
+                .find(|&start| !code[start..].lines().next().unwrap_or("").trim().is_empty())
+            else {
+                return;
+            };
+            let indent = code[child_line..]
+                .find(|c| c != ' ' && c != '\t')
+                .unwrap_or(0);
+            insertions.push((
+                child_line,
+                format!("{}{marker}\n", &code[child_line..child_line + indent]),
+            ));
+        } else if let Some(open) = code[selector.end..].find('{') {
+            insertions.push((selector.end + open + 1, marker));
+        }
+    });
+    insertions.sort_by_key(|(offset, _)| *offset);
+
+    let mut result = String::with_capacity(code.len());
+    let mut last = 0;
+    for (offset, marker) in insertions {
+        result.push_str(&code[last..offset]);
+        result.push_str(&marker);
+        last = offset;
+    }
+    result.push_str(&code[last..]);
+    result
+}
+
+/// Removes the comments of [annotate_rules] from the compiled `css` and returns a source map that
+/// maps every line of a rule to the position of the selector of the rule in its source. Rules that
+/// only contained the comment are removed, Sass omits empty rules.
+///
+/// The mappings are only as precise as the comments:
+/// - Declarations map to the selector of the rule they are emitted in, also when they were included
+///   from a mixin.
+/// - Blocks that Sass emits for a rule without its first statement aren't mapped, e.g. the rule
+///   that wraps the declarations of a `@media` nested in a style rule.
+fn extract_source_map(css: &str, sources: &[SassSourceFile]) -> Result<(String, Option<Rope>)> {
+    let bytes = css.as_bytes();
+    // Ranges of `css` that are removed, in order
+    let mut removals: Vec<Range<usize>> = Vec::new();
+    // The lines of a rule and the position in its source, in order
+    let mut rules = Vec::new();
+    let mut search = 0;
+    while let Some(found) = css[search..].find(MARKER) {
+        let start = search + found;
+        let Some(end) = css[start..].find("*/").map(|end| start + end + 2) else {
+            break;
+        };
+        search = end;
+        let mut position = css[start + MARKER.len()..end - 2]
+            .split(':')
+            .map(|part| part.parse::<u32>());
+        let (Some(Ok(source)), Some(Ok(line)), Some(Ok(column))) =
+            (position.next(), position.next(), position.next())
+        else {
+            continue;
+        };
+
+        let line_start = css[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = css[end..].find('\n').map_or(css.len(), |i| end + i + 1);
+        let removal =
+            if css[line_start..start].trim().is_empty() && css[end..line_end].trim().is_empty() {
+                line_start..line_end
+            } else {
+                start..end
+            };
+        // Only the first statement of a rule is a marker for the rule
+        let Some(open) = css[..start]
+            .rfind('{')
+            .filter(|&open| css[open + 1..start].trim().is_empty())
+        else {
+            removals.push(removal);
+            continue;
+        };
+        let selector_start = css[..open]
+            .rfind(['{', '}', ';'])
+            .map_or(0, |i| i + 1)
+            .max(css[..open].rfind("*/").map_or(0, |i| i + 2));
+        let selector_start = selector_start + css[selector_start..open].len()
+            - css[selector_start..open].trim_start().len();
+        let rule_line_start = css[..selector_start].rfind('\n').map_or(0, |i| i + 1);
+        let rule_end = rule_end(bytes, open);
+        if css[removal.end..rule_end.saturating_sub(1)]
+            .trim()
+            .is_empty()
+        {
+            // Together with the blank line that separates it from the next or previous rule
+            let blank_before = css[..rule_line_start].ends_with("\n\n");
+            let mut rule_start = rule_line_start;
+            let rule_end = if blank_before && css[rule_end..].starts_with("\n\n") {
+                rule_end + 2
+            } else if css[rule_end..].trim().is_empty() {
+                if blank_before {
+                    rule_start -= 1;
+                }
+                css.len()
+            } else {
+                rule_end + usize::from(css[rule_end..].starts_with('\n'))
+            };
+            removals.push(rule_start..rule_end);
+            search = rule_end;
+        } else {
+            removals.push(removal);
+            rules.push((rule_line_start..rule_end, (source, line, column)));
+        }
+    }
+    if removals.is_empty() {
+        return Ok((css.to_string(), None));
+    }
+
+    let mut output = String::with_capacity(css.len());
+    // The offset in `css` of every line of the output
+    let mut line_offsets = Vec::new();
+    let mut at_line_start = true;
+    let mut offset = 0;
+    for removal in removals.iter().chain([&(css.len()..css.len())]) {
+        let mut segment_offset = offset;
+        for part in css[offset..removal.start.max(offset)].split_inclusive('\n') {
+            if at_line_start {
+                line_offsets.push(segment_offset);
+            }
+            output.push_str(part);
+            at_line_start = part.ends_with('\n');
+            segment_offset += part.len();
+        }
+        offset = offset.max(removal.end);
+    }
+
+    let mut builder = SourceMapBuilder::new(None);
+    for (index, source) in sources.iter().enumerate() {
+        builder.add_source(source.name.to_string().into());
+        builder.set_source_contents(index as _, Some(source.content.clone().into()));
+    }
+    let mut rules = rules.iter().peekable();
+    for (generated_line, line_offset) in line_offsets.into_iter().enumerate() {
+        while rules
+            .peek()
+            .is_some_and(|(lines, _)| lines.end <= line_offset)
+        {
+            rules.next();
+        }
+        if let Some((lines, (source, line, column))) = rules.peek()
+            && lines.start <= line_offset
+        {
+            builder.add_raw(
+                generated_line as u32,
+                0,
+                *line,
+                *column,
+                Some(*source),
+                None,
+                false,
+            );
+        }
+    }
+    let mut source_map = Vec::new();
+    builder.into_sourcemap().to_writer(&mut source_map)?;
+    Ok((output, Some(Rope::from(source_map))))
+}
+
+/// Returns the index after the `}` that closes the block starting with the `{` at `open`.
+fn rule_end(bytes: &[u8], open: usize) -> usize {
+    let mut depth = 0;
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' | b'\'' => i = skip_string(bytes, i).end,
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = bytes[i + 2..]
+                    .windows(2)
+                    .position(|window| window == b"*/")
+                    .map_or(bytes.len(), |end| i + end + 3);
+            }
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+/// Serves the loaded files to the Sass compiler. All urls are rewritten to the virtual paths of
+/// the loaded files, so the compiler never needs to search for a file.
+#[derive(Debug)]
+struct VirtualFs(FxHashMap<PathBuf, Vec<u8>>);
+
+impl grass_compiler::Fs for VirtualFs {
+    fn is_dir(&self, _path: &Path) -> bool {
+        false
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.0.contains_key(path)
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.0
+            .get(path)
+            .cloned()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        Ok(path.to_path_buf())
+    }
+}
+
+#[turbo_tasks::value]
+struct SassIssue {
+    path: FileSystemPath,
+    message: RcStr,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for SassIssue {
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path.clone().cell()
+    }
+
+    #[turbo_tasks::function]
+    fn stage(&self) -> Vc<IssueStage> {
+        IssueStage::Transform.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text(rcstr!("Compiling Sass failed")).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Text(self.message.clone()).resolved_cell(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use grass_compiler::InputSyntax;
+    use swc_core::base::sourcemap::SourceMap;
+
+    use super::{
+        SassSourceFile, annotate_rules, extract_source_map, find_imports, url_basename,
+        url_candidates,
+    };
+
+    fn urls(code: &str, syntax: InputSyntax) -> Vec<(&str, bool)> {
+        find_imports(code, syntax)
+            .into_iter()
+            .map(|import| (&code[import.range], import.is_import))
+            .collect()
+    }
+
+    /// A generated line and the source position it maps to.
+    type Mapping = (u32, (u32, u32));
+
+    /// The CSS and the mappings of the compiled `code`.
+    fn compile(code: &str) -> (String, Vec<Mapping>) {
+        let sources = [SassSourceFile {
+            name: "[project]/entry.scss".into(),
+            content: code.into(),
+        }];
+        let css = grass_compiler::from_string(
+            annotate_rules(code, 0, InputSyntax::Scss),
+            &grass_compiler::Options::default().style(grass_compiler::OutputStyle::Expanded),
+        )
+        .unwrap();
+        let (css, source_map) = extract_source_map(&css, &sources).unwrap();
+        let source_map = SourceMap::from_slice(&source_map.unwrap().to_bytes()).unwrap();
+        let mappings = source_map
+            .tokens()
+            .map(|token| (token.get_dst_line(), token.get_src()))
+            .collect();
+        (css, mappings)
+    }
+
+    #[test]
+    fn finds_imports() {
+        assert_eq!(
+            urls(
+                r#"
+                @use "sass:math";
+                @use 'pkg/colors' as c;
+                // @use "commented";
+                /* @import "commented"; */
+                @forward /* comment */ "./mixins" show button;
+                .a { content: "@import 'string'"; }
+                @import "a", 'b';
+                @import url(plain.css), "plain.css", "print" print;
+                .b { @import "nested"; }
+                "#,
+                InputSyntax::Scss
+            ),
+            vec![
+                ("sass:math", false),
+                ("pkg/colors", false),
+                ("./mixins", false),
+                ("a", true),
+                ("b", true),
+                ("nested", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_indented_imports() {
+        assert_eq!(
+            urls(
+                "@use \"pkg/colors\" as c\n@import \"a\", \"b/c\"\n@import url(plain.css)\n.a\n  \
+                 color: red\n",
+                InputSyntax::Sass
+            ),
+            vec![("pkg/colors", false), ("a", true), ("b/c", true)]
+        );
+        // grass doesn't parse unquoted urls, compiling reports the error
+        assert_eq!(urls("@import a\n", InputSyntax::Sass), vec![]);
+    }
+
+    #[test]
+    fn candidates() {
+        assert_eq!(
+            url_candidates("dir/name", false),
+            vec![
+                "dir/name.scss",
+                "dir/_name.scss",
+                "dir/name.sass",
+                "dir/_name.sass",
+                "dir/name.css",
+                "dir/_name.css",
+                "dir/name/index.scss",
+                "dir/name/_index.scss",
+                "dir/name/index.sass",
+                "dir/name/_index.sass",
+            ]
+        );
+        assert_eq!(
+            url_candidates("name.scss", true),
+            vec!["name.scss", "_name.scss"]
+        );
+        assert_eq!(url_basename("pkg/_colors.scss"), "_colors");
+        assert_eq!(url_basename("pkg/colors.sass"), "colors");
+        assert_eq!(url_basename("colors"), "colors");
+    }
+
+    #[test]
+    fn annotates_rules() {
+        assert_eq!(
+            annotate_rules(
+                ".a {\n  font: { family: serif; }\n  &:hover, .b { color: #{$c}; }\n  @media \
+                 print { top: 0; }\n}\n@mixin m { .c { top: 0; } }\n.empty {}\n",
+                3,
+                InputSyntax::Scss
+            ),
+            ".a {/*#turbopack-sass:3:0:0*/\n  font: { family: serif; }\n  &:hover, .b \
+             {/*#turbopack-sass:3:2:2*/ color: #{$c}; }\n  @media print { top: 0; }\n}\n@mixin m \
+             { .c {/*#turbopack-sass:3:5:11*/ top: 0; } }\n.empty {}\n"
+        );
+        assert_eq!(
+            annotate_rules(
+                ".a\n  color: red\n  .b,\n  .c\n\n    top: 0\n=mixin\n  top: 0\n",
+                0,
+                InputSyntax::Sass
+            ),
+            ".a\n  /*#turbopack-sass:0:0:0*/\n  color: red\n  .b,\n  .c\n\n    \
+             /*#turbopack-sass:0:2:2*/\n    top: 0\n=mixin\n  top: 0\n"
+        );
+    }
+
+    #[test]
+    fn extracts_source_map() {
+        let sources = [
+            SassSourceFile {
+                name: "[project]/_partial.scss".into(),
+                content: ".p { top: 0; }\n".into(),
+            },
+            SassSourceFile {
+                name: "[project]/entry.scss".into(),
+                content: ".a { .empty {} color: red; }\n".into(),
+            },
+        ];
+        let (css, source_map) = extract_source_map(
+            ".p {\n  /*#turbopack-sass:0:0:0*/\n  top: 0;\n}\n\n.a {\n  \
+             /*#turbopack-sass:1:0:0*/\n  color: red;\n}\n\n.a .empty {\n  \
+             /*#turbopack-sass:1:0:5*/\n}\n",
+            &sources,
+        )
+        .unwrap();
+        assert_eq!(css, ".p {\n  top: 0;\n}\n\n.a {\n  color: red;\n}\n");
+
+        let source_map = SourceMap::from_slice(&source_map.unwrap().to_bytes()).unwrap();
+        let mappings = source_map
+            .tokens()
+            .map(|token| (token.get_dst_line(), token.get_source(), token.get_src()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            mappings,
+            vec![
+                (0, Some("[project]/_partial.scss"), (0, 0)),
+                (1, Some("[project]/_partial.scss"), (0, 0)),
+                (2, Some("[project]/_partial.scss"), (0, 0)),
+                (4, Some("[project]/entry.scss"), (0, 0)),
+                (5, Some("[project]/entry.scss"), (0, 0)),
+                (6, Some("[project]/entry.scss"), (0, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn maps_mixin_declarations_to_the_including_rule() {
+        let (css, mappings) =
+            compile("@mixin m {\n  top: 0;\n  .b { left: 0; }\n}\n.a {\n  @include m;\n}\n");
+        assert_eq!(css, ".a {\n  top: 0;\n}\n.a .b {\n  left: 0;\n}\n");
+        assert_eq!(
+            mappings,
+            vec![
+                (0, (4, 0)),
+                (1, (4, 0)),
+                (2, (4, 0)),
+                (3, (2, 2)),
+                (4, (2, 2)),
+                (5, (2, 2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn leaves_rules_in_nested_media_unmapped() {
+        let (css, mappings) =
+            compile(".a {\n  color: red;\n  @media print {\n    top: 0;\n  }\n}\n");
+        assert_eq!(
+            css,
+            ".a {\n  color: red;\n}\n@media print {\n  .a {\n    top: 0;\n  }\n}\n"
+        );
+        assert_eq!(mappings, vec![(0, (0, 0)), (1, (0, 0)), (2, (0, 0))]);
+    }
+}
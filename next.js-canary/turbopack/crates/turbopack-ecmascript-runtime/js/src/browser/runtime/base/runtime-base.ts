@@ -91,6 +91,10 @@ interface RuntimeBackend {
     wasmChunkPath: ChunkPath,
     edgeModule: () => WebAssembly.Module
   ) => Promise<WebAssembly.Module>
+  /**
+   * Tells the browser to fetch a chunk ahead of time. Backends without a way to do so can omit it.
+   */
+  hintChunk?: (chunkUrl: ChunkUrl, hint: ChunkLoadingHint) => void
 }
 
 interface DevRuntimeBackend {
@@ -295,6 +299,20 @@ function loadChunkPath(
   return loadChunkByUrlInternal(sourceType, sourceData, url)
 }
 
+/**
+ * Emits a resource hint for chunks that are likely loaded later, e.g. for
+ * `import()`s with a `webpackPrefetch` or `webpackPreload` comment.
+ */
+function hintChunks(chunks: ChunkData[], hint: ChunkLoadingHint) {
+  if (BACKEND.hintChunk == null) {
+    return
+  }
+  for (const chunkData of chunks) {
+    BACKEND.hintChunk(getChunkRelativeUrl(getChunkPath(chunkData)), hint)
+  }
+}
+browserContextPrototype.H = hintChunks
+
 /**
  * Returns an absolute url to an asset.
  */
@@ -331,6 +349,17 @@ importScripts(...self.TURBOPACK_NEXT_CHUNK_URLS.map(c => self.TURBOPACK_WORKER_L
 }
 browserContextPrototype.b = getWorkerBlobURL
 
+/**
+ * Returns the URL of the bootstrap script of a shared or service worker. Unlike
+ * blob URLs, it's the same for every document, which is required to share a
+ * worker or to register a service worker.
+ * @param scriptPath path of the bootstrap script
+ */
+function getWorkerScriptURL(scriptPath: ChunkPath): string {
+  return getChunkRelativeUrl(scriptPath)
+}
+browserContextPrototype.o = getWorkerScriptURL
+
 /**
  * Instantiates a runtime module.
  */
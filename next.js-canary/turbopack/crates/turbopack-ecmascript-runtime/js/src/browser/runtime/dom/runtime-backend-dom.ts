@@ -94,6 +94,32 @@ const chunkResolvers: Map<ChunkUrl, ChunkResolver> = new Map()
 
       return await WebAssembly.compileStreaming(req)
     },
+
+    hintChunk(chunkUrl: ChunkUrl, hint: ChunkLoadingHint) {
+      if (typeof document === 'undefined') {
+        // Hints are not supported in web workers.
+        return
+      }
+      // Chunks that are already loaded or loading don't need a hint.
+      if (chunkResolvers.get(chunkUrl)?.loadingStarted) {
+        return
+      }
+      if (document.querySelector(`link[href="${chunkUrl}"]`) != null) {
+        return
+      }
+
+      const link = document.createElement('link')
+      link.rel = hint
+      link.href = chunkUrl
+      if (hint === 'preload') {
+        if (isCss(chunkUrl)) {
+          link.as = 'style'
+        } else if (isJs(chunkUrl)) {
+          link.as = 'script'
+        }
+      }
+      document.head.appendChild(link)
+    },
   }
 
   function getOrCreateResolver(chunkUrl: ChunkUrl): ChunkResolver {
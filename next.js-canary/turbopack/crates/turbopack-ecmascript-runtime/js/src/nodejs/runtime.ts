@@ -186,6 +186,18 @@ function getWorkerBlobURL(_chunks: ChunkPath[]): string {
 
 nodeContextPrototype.b = getWorkerBlobURL
 
+function getWorkerScriptURL(_scriptPath: ChunkPath): string {
+  throw new Error('Worker scripts are not implemented yet for Node.js')
+}
+
+nodeContextPrototype.o = getWorkerScriptURL
+
+function hintChunks(_chunks: ChunkData[], _hint: ChunkLoadingHint) {
+  // Resource hints only apply to browsers.
+}
+
+nodeContextPrototype.H = hintChunks
+
 function instantiateModule(
   id: ModuleId,
   sourceType: SourceType,
@@ -87,6 +87,9 @@ type AsyncModule = (
 
 type ResolveAbsolutePath = (modulePath?: string) => string
 type GetWorkerBlobURL = (chunks: ChunkPath[]) => string
+type GetWorkerScriptURL = (scriptPath: ChunkPath) => string
+type ChunkLoadingHint = 'prefetch' | 'preload'
+type HintChunks = (chunks: ChunkData[], hint: ChunkLoadingHint) => void
 
 type ExternalRequire = (
   id: DependencySpecifier,
@@ -135,6 +138,8 @@ interface TurbopackBaseContext<M> {
   P: ResolveAbsolutePath
   U: RelativeURL
   b: GetWorkerBlobURL
+  o: GetWorkerScriptURL
+  H: HintChunks
   x: ExternalRequire
   y: ExternalImport
   z: CommonJsRequire
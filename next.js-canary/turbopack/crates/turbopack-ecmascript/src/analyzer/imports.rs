@@ -2,6 +2,7 @@ use std::{collections::BTreeMap, fmt::Display};
 
 use once_cell::sync::Lazy;
 use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
 use swc_core::{
     common::{BytePos, Span, Spanned, SyntaxContext, comments::Comments, source_map::SmallPos},
     ecma::{
@@ -12,8 +13,8 @@ use swc_core::{
     },
 };
 use turbo_rcstr::{RcStr, rcstr};
-use turbo_tasks::{FxIndexMap, FxIndexSet, ResolvedVc};
-use turbopack_core::{issue::IssueSource, source::Source};
+use turbo_tasks::{FxIndexMap, FxIndexSet, NonLocalValue, ResolvedVc, trace::TraceRawVcs};
+use turbopack_core::{issue::IssueSource, reference_type::ImportWithType, source::Source};
 
 use super::{JsValue, ModuleValue, top_level_await::has_top_level_await};
 use crate::{
@@ -39,9 +40,12 @@ static ANNOTATION_TRANSITION: Lazy<Atom> =
 static ANNOTATION_CHUNKING_TYPE: Lazy<Atom> =
     Lazy::new(|| crate::annotations::ANNOTATION_CHUNKING_TYPE.into());
 
-/// Changes the type of the resolved module (only "json" is supported currently)
+/// Changes the type of the resolved module ("json", "css", "text" and "bytes" are supported)
 static ATTRIBUTE_MODULE_TYPE: Lazy<Atom> = Lazy::new(|| atom!("type"));
 
+/// Adds conditions for the "exports" and "imports" fields when resolving the annotated import
+static ATTRIBUTE_CONDITION: Lazy<Atom> = Lazy::new(|| atom!("condition"));
+
 impl ImportAnnotations {
     pub fn parse(with: Option<&ObjectLit>) -> ImportAnnotations {
         let Some(with) = with else {
@@ -115,6 +119,29 @@ impl ImportAnnotations {
         self.get(&ATTRIBUTE_MODULE_TYPE)
     }
 
+    /// Returns the [ImportWithType] of the type attribute, if it is one of the supported types.
+    pub fn import_with_type(&self) -> Option<ImportWithType> {
+        match self.module_type()? {
+            "json" => Some(ImportWithType::Json),
+            "css" => Some(ImportWithType::Css),
+            "text" => Some(ImportWithType::Text),
+            "bytes" => Some(ImportWithType::Bytes),
+            _ => None,
+        }
+    }
+
+    /// Returns the conditions of the condition attribute, multiple conditions are separated by
+    /// commas: `with { condition: "worker, react-native" }`
+    pub fn conditions(&self) -> Vec<RcStr> {
+        self.get(&ATTRIBUTE_CONDITION)
+            .into_iter()
+            .flat_map(|conditions| conditions.split(','))
+            .map(str::trim)
+            .filter(|condition| !condition.is_empty())
+            .map(RcStr::from)
+            .collect()
+    }
+
     pub fn get(&self, key: &Atom) -> Option<&str> {
         self.map.get(key).map(|w| w.as_str())
     }
@@ -191,7 +218,7 @@ pub(crate) struct ImportMap {
 /// behaviors.
 ///
 /// [magic]: https://webpack.js.org/api/module-methods/#magic-comments
-#[derive(Debug)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs, NonLocalValue)]
 pub struct ImportAttributes {
     /// Should we ignore this import expression when bundling? If so, the import expression will be
     /// left as-is in Turbopack's output.
@@ -204,11 +231,47 @@ pub struct ImportAttributes {
     /// const b = import(/* turbopackIgnore: true */ "b");
     /// ```
     pub ignore: bool,
+    /// The name of the async chunk group created for a dynamic `import()`, set by a
+    /// `webpackChunkName` comment.
+    pub chunk_name: Option<RcStr>,
+    /// Should the chunks of a dynamic `import()` be prefetched when the importing module is
+    /// evaluated? Set by a `webpackPrefetch` comment.
+    pub prefetch: bool,
+    /// Should the chunks of a dynamic `import()` be preloaded when the importing module is
+    /// evaluated? Set by a `webpackPreload` comment.
+    pub preload: bool,
+    /// How the modules of a dynamic `import()` are chunked, set by a `webpackMode` comment.
+    pub mode: Option<DynamicImportMode>,
+    /// The exports of the module that are used by a dynamic `import()`, set by a `webpackExports`
+    /// comment.
+    pub exports: Option<Vec<RcStr>>,
+}
+
+/// The value of a `webpackMode` comment.
+#[derive(
+    Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs, NonLocalValue,
+)]
+pub enum DynamicImportMode {
+    /// Each module is placed in its own async chunk group. This is the default.
+    Lazy,
+    /// All modules matched by an `import()` with a dynamic request are placed in a single async
+    /// chunk group.
+    LazyOnce,
+    /// No async chunk group is created, the modules are placed in the chunks of the importing
+    /// module.
+    Eager,
 }
 
 impl ImportAttributes {
     pub const fn empty() -> Self {
-        ImportAttributes { ignore: false }
+        ImportAttributes {
+            ignore: false,
+            chunk_name: None,
+            prefetch: false,
+            preload: false,
+            mode: None,
+            exports: None,
+        }
     }
 
     pub fn empty_ref() -> &'static Self {
@@ -216,6 +279,41 @@ impl ImportAttributes {
         static DEFAULT_VALUE: ImportAttributes = ImportAttributes::empty();
         &DEFAULT_VALUE
     }
+
+    /// Applies a single `key: value` entry of a magic comment. Returns `false` if the entry isn't
+    /// a known directive.
+    fn apply(&mut self, key: &str, value: MagicCommentValue) -> bool {
+        match (key, value) {
+            ("webpackIgnore" | "turbopackIgnore", MagicCommentValue::Bool(ignore)) => {
+                self.ignore = ignore;
+            }
+            ("webpackChunkName", MagicCommentValue::String(name)) => {
+                self.chunk_name = Some(name);
+            }
+            // a number specifies the order in webpack, which we don't support
+            ("webpackPrefetch", MagicCommentValue::Bool(prefetch)) => self.prefetch = prefetch,
+            ("webpackPrefetch", MagicCommentValue::Number(_)) => self.prefetch = true,
+            ("webpackPreload", MagicCommentValue::Bool(preload)) => self.preload = preload,
+            ("webpackPreload", MagicCommentValue::Number(_)) => self.preload = true,
+            ("webpackMode", MagicCommentValue::String(mode)) => {
+                self.mode = Some(match &*mode {
+                    "lazy" => DynamicImportMode::Lazy,
+                    "lazy-once" => DynamicImportMode::LazyOnce,
+                    "eager" => DynamicImportMode::Eager,
+                    // `weak` is not supported
+                    _ => return false,
+                });
+            }
+            ("webpackExports", MagicCommentValue::String(export)) => {
+                self.exports = Some(vec![export]);
+            }
+            ("webpackExports", MagicCommentValue::Array(exports)) => {
+                self.exports = Some(exports);
+            }
+            _ => return false,
+        }
+        true
+    }
 }
 
 impl Default for ImportAttributes {
@@ -731,17 +829,15 @@ impl Visit for Analyzer<'_> {
         }
     }
 
-    /// check if import or require contains an ignore comment
+    /// check if import or require contains magic comments
     ///
     /// We are checking for the following cases:
     /// - import(/* webpackIgnore: true */ "a")
     /// - require(/* webpackIgnore: true */ "a")
+    /// - import(/* webpackChunkName: "a", webpackPrefetch: true */ "a")
     ///
     /// We can do this by checking if any of the comment spans are between the
     /// callee and the first argument.
-    //
-    // potentially support more webpack magic comments in the future:
-    // https://webpack.js.org/api/module-methods/#magic-comments
     fn visit_call_expr(&mut self, n: &CallExpr) {
         // we could actually unwrap thanks to the optimisation above but it can't hurt to be safe...
         if let Some(comments) = self.comments {
@@ -751,15 +847,10 @@ impl Visit for Analyzer<'_> {
                 _ => None,
             };
 
-            let ignore_directive = parse_ignore_directive(comments, n.args.first());
+            let attributes = parse_magic_comments(comments, n.args.first());
 
-            if let Some((callee_span, ignore_directive)) = callee_span.zip(ignore_directive) {
-                self.data.attributes.insert(
-                    callee_span.lo,
-                    ImportAttributes {
-                        ignore: ignore_directive,
-                    },
-                );
+            if let Some((callee_span, attributes)) = callee_span.zip(attributes) {
+                self.data.attributes.insert(callee_span.lo, attributes);
             };
         }
 
@@ -770,19 +861,16 @@ impl Visit for Analyzer<'_> {
         // we could actually unwrap thanks to the optimisation above but it can't hurt to be safe...
         if let Some(comments) = self.comments {
             let callee_span = match &n.callee {
-                box Expr::Ident(Ident { sym, .. }) if sym == "Worker" => Some(n.span),
+                box Expr::Ident(Ident { sym, .. }) if sym == "Worker" || sym == "SharedWorker" => {
+                    Some(n.span)
+                }
                 _ => None,
             };
 
-            let ignore_directive = parse_ignore_directive(comments, n.args.iter().flatten().next());
+            let attributes = parse_magic_comments(comments, n.args.iter().flatten().next());
 
-            if let Some((callee_span, ignore_directive)) = callee_span.zip(ignore_directive) {
-                self.data.attributes.insert(
-                    callee_span.lo,
-                    ImportAttributes {
-                        ignore: ignore_directive,
-                    },
-                );
+            if let Some((callee_span, attributes)) = callee_span.zip(attributes) {
+                self.data.attributes.insert(callee_span.lo, attributes);
             };
         }
 
@@ -790,24 +878,93 @@ impl Visit for Analyzer<'_> {
     }
 }
 
-fn parse_ignore_directive(comments: &dyn Comments, value: Option<&ExprOrSpread>) -> Option<bool> {
-    // we are interested here in the last comment with a valid directive
-    value
-        .map(|arg| arg.span_lo())
-        .and_then(|comment_pos| comments.get_leading(comment_pos))
-        .iter()
-        .flatten()
-        .rev()
-        .filter_map(|comment| {
-            let (directive, value) = comment.text.trim().split_once(':')?;
-            // support whitespace between the colon
-            match (directive.trim(), value.trim()) {
-                ("webpackIgnore" | "turbopackIgnore", "true") => Some(true),
-                ("webpackIgnore" | "turbopackIgnore", "false") => Some(false),
-                _ => None, // ignore anything else
-            }
+/// A value of a magic comment entry, e.g. the `"name"` in `/* webpackChunkName: "name" */`.
+#[derive(Debug, PartialEq)]
+enum MagicCommentValue {
+    Bool(bool),
+    Number(f64),
+    String(RcStr),
+    Array(Vec<RcStr>),
+}
+
+/// Parses the magic comments in front of `value`. Returns `None` if there are no known
+/// directives. Later comments override earlier ones.
+fn parse_magic_comments(
+    comments: &dyn Comments,
+    value: Option<&ExprOrSpread>,
+) -> Option<ImportAttributes> {
+    let comments = comments.get_leading(value?.span_lo())?;
+    let mut attributes = ImportAttributes::empty();
+    let mut found = false;
+    for comment in &comments {
+        for (key, value) in parse_magic_comment(&comment.text) {
+            found |= attributes.apply(key, value);
+        }
+    }
+    found.then_some(attributes)
+}
+
+/// Parses the comma separated `key: value` entries of a single magic comment. Entries that can't
+/// be parsed are skipped.
+fn parse_magic_comment(text: &str) -> Vec<(&str, MagicCommentValue)> {
+    split_top_level(text, ',')
+        .into_iter()
+        .filter_map(|entry| {
+            let (key, value) = entry.split_once(':')?;
+            Some((key.trim(), parse_magic_comment_value(value.trim())?))
         })
-        .next()
+        .collect()
+}
+
+fn parse_magic_comment_value(value: &str) -> Option<MagicCommentValue> {
+    match value {
+        "true" => return Some(MagicCommentValue::Bool(true)),
+        "false" => return Some(MagicCommentValue::Bool(false)),
+        _ => {}
+    }
+    if let Some(string) = parse_string_literal(value) {
+        return Some(MagicCommentValue::String(string));
+    }
+    if let Some(items) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+        return split_top_level(items, ',')
+            .into_iter()
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(parse_string_literal)
+            .collect::<Option<Vec<_>>>()
+            .map(MagicCommentValue::Array);
+    }
+    value.parse().ok().map(MagicCommentValue::Number)
+}
+
+fn parse_string_literal(value: &str) -> Option<RcStr> {
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let inner = value.strip_prefix(quote)?.strip_suffix(quote)?;
+    (!inner.contains(quote)).then(|| inner.into())
+}
+
+/// Splits `text` at `separator`, ignoring separators inside of quotes or brackets.
+fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (index, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '[' | '{') => depth += 1,
+            (None, ']' | '}') => depth = depth.saturating_sub(1),
+            (None, c) if c == separator && depth == 0 => {
+                parts.push(&text[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
 }
 
 pub(crate) fn orig_name(n: &ModuleExportName) -> Atom {
@@ -849,3 +1006,35 @@ fn get_import_symbol_from_export(specifier: &ExportSpecifier) -> ImportedSymbol
         ExportSpecifier::Namespace(..) => ImportedSymbol::Exports,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{MagicCommentValue, parse_magic_comment};
+
+    #[test]
+    fn test_parse_magic_comment() {
+        assert_eq!(
+            parse_magic_comment(" webpackIgnore: true "),
+            vec![("webpackIgnore", MagicCommentValue::Bool(true))]
+        );
+        assert_eq!(
+            parse_magic_comment(r#" webpackChunkName: "a, b", webpackPrefetch: 1 "#),
+            vec![
+                ("webpackChunkName", MagicCommentValue::String("a, b".into())),
+                ("webpackPrefetch", MagicCommentValue::Number(1.0)),
+            ]
+        );
+        assert_eq!(
+            parse_magic_comment(r#" webpackExports: ["default", 'named'] "#),
+            vec![(
+                "webpackExports",
+                MagicCommentValue::Array(vec!["default".into(), "named".into()])
+            )]
+        );
+        assert_eq!(
+            parse_magic_comment(" webpackMode: 'lazy-once', webpackChunkName: unquoted "),
+            vec![("webpackMode", MagicCommentValue::String("lazy-once".into()))]
+        );
+        assert_eq!(parse_magic_comment(" just a comment "), vec![]);
+    }
+}
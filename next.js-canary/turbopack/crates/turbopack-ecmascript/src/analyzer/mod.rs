@@ -11,7 +11,7 @@ use std::{
     sync::Arc,
 };
 
-use anyhow::{Result, bail};
+use anyhow::{Result, anyhow, bail};
 use graph::VarGraph;
 use num_bigint::BigInt;
 use num_traits::identities::Zero;
@@ -1806,6 +1806,14 @@ impl JsValue {
                         "import.meta",
                         "The import.meta object"
                     ),
+                    WellKnownObjectKind::Navigator => (
+                        "navigator",
+                        "The standard navigator object: https://developer.mozilla.org/en-US/docs/Web/API/Navigator"
+                    ),
+                    WellKnownObjectKind::NavigatorServiceWorker => (
+                        "navigator.serviceWorker",
+                        "The standard ServiceWorkerContainer: https://developer.mozilla.org/en-US/docs/Web/API/ServiceWorkerContainer"
+                    ),
                 };
                 if depth > 0 {
                     let i = hints.len();
@@ -1843,6 +1851,7 @@ impl JsValue {
                     WellKnownFunctionKind::RequireContextRequire(..) => ("require.context(...)".to_string(), "The require.context(...) method from webpack: https://webpack.js.org/api/module-methods/#requirecontext"),
                     WellKnownFunctionKind::RequireContextRequireKeys(..) => ("require.context(...).keys".to_string(), "The require.context(...).keys method from webpack: https://webpack.js.org/guides/dependency-management/#requirecontext"),
                     WellKnownFunctionKind::RequireContextRequireResolve(..) => ("require.context(...).resolve".to_string(), "The require.context(...).resolve method from webpack: https://webpack.js.org/guides/dependency-management/#requirecontext"),
+                    WellKnownFunctionKind::ImportMetaGlob => ("import.meta.glob".to_string(), "The import.meta.glob method from Vite: https://vite.dev/guide/features.html#glob-import"),
                     WellKnownFunctionKind::Define => ("define".to_string(), "The define method from AMD"),
                     WellKnownFunctionKind::FsReadMethod(name) => (
                         format!("fs.{name}"),
@@ -1916,6 +1925,14 @@ impl JsValue {
                       "Worker".to_string(),
                       "The standard Worker constructor: https://developer.mozilla.org/en-US/docs/Web/API/Worker/Worker"
                     ),
+                    WellKnownFunctionKind::SharedWorkerConstructor => (
+                      "SharedWorker".to_string(),
+                      "The standard SharedWorker constructor: https://developer.mozilla.org/en-US/docs/Web/API/SharedWorker/SharedWorker"
+                    ),
+                    WellKnownFunctionKind::ServiceWorkerRegister => (
+                      "navigator.serviceWorker.register".to_string(),
+                      "Registers a service worker: https://developer.mozilla.org/en-US/docs/Web/API/ServiceWorkerContainer/register"
+                    ),
                     WellKnownFunctionKind::URLConstructor => (
                       "URL".to_string(),
                       "The standard URL constructor: https://developer.mozilla.org/en-US/docs/Web/API/URL/URL"
@@ -3730,6 +3747,8 @@ pub enum WellKnownObjectKind {
     NodeBuffer,
     RequireCache,
     ImportMeta,
+    Navigator,
+    NavigatorServiceWorker,
 }
 
 impl WellKnownObjectKind {
@@ -3747,6 +3766,8 @@ impl WellKnownObjectKind {
             Self::NodeBuffer => Some(&["Buffer"]),
             Self::RequireCache => Some(&["require", "cache"]),
             Self::ImportMeta => Some(&["import", "meta"]),
+            Self::Navigator => Some(&["navigator"]),
+            Self::NavigatorServiceWorker => Some(&["navigator", "serviceWorker"]),
             _ => None,
         }
     }
@@ -3806,6 +3827,142 @@ pub fn parse_require_context(args: &[JsValue]) -> Result<RequireContextOptions>
     })
 }
 
+#[derive(Debug, Clone)]
+pub struct ImportMetaGlobOptions {
+    /// The relative glob patterns, negated patterns are prefixed with `!`.
+    pub patterns: Vec<RcStr>,
+    pub eager: bool,
+    /// The named export to pick from each matched module.
+    pub import: Option<RcStr>,
+    /// The query appended to each request, including the leading `?`.
+    pub query: Option<RcStr>,
+}
+
+/// Parse the arguments passed to an import.meta.glob invocation, validate them
+/// and convert them to the appropriate rust values.
+pub fn parse_import_meta_glob(args: &[JsValue]) -> Result<ImportMetaGlobOptions> {
+    if !(1..=2).contains(&args.len()) {
+        bail!("import.meta.glob() only supports 1-2 arguments");
+    }
+
+    let patterns: Vec<RcStr> = match &args[0] {
+        JsValue::Array { items, .. } => items
+            .iter()
+            .map(|item| item.as_str().map(RcStr::from))
+            .collect::<Option<_>>(),
+        pattern => pattern.as_str().map(|s| vec![s.into()]),
+    }
+    .ok_or_else(|| {
+        anyhow!(
+            "import.meta.glob(patterns, ...) requires patterns to be a constant string or an \
+             array of constant strings"
+        )
+    })?;
+
+    for pattern in &patterns {
+        let pattern = pattern.strip_prefix('!').unwrap_or(pattern);
+        if !pattern.starts_with("./") && !pattern.starts_with("../") {
+            bail!(
+                "import.meta.glob(patterns, ...) only supports relative patterns starting with \
+                 `./` or `../`, found `{pattern}`"
+            );
+        }
+    }
+    if patterns.iter().all(|pattern| pattern.starts_with('!')) {
+        bail!("import.meta.glob(patterns, ...) requires at least one non-negated pattern");
+    }
+
+    let mut options = ImportMetaGlobOptions {
+        patterns,
+        eager: false,
+        import: None,
+        query: None,
+    };
+
+    let Some(options_arg) = args.get(1) else {
+        return Ok(options);
+    };
+    let JsValue::Object { parts, .. } = options_arg else {
+        bail!("import.meta.glob(..., options) requires options to be an object literal");
+    };
+    for part in parts {
+        let ObjectPart::KeyValue(key, value) = part else {
+            bail!("import.meta.glob(..., options) does not support spread properties");
+        };
+        match key.as_str() {
+            Some("eager") => {
+                let Some(eager) = value.as_bool() else {
+                    bail!(
+                        "import.meta.glob(..., {{ eager }}) requires eager to be a constant \
+                         boolean"
+                    );
+                };
+                options.eager = eager;
+            }
+            Some("import") => {
+                let Some(import) = value.as_str() else {
+                    bail!(
+                        "import.meta.glob(..., {{ import }}) requires import to be a constant \
+                         string"
+                    );
+                };
+                options.import = Some(import.into());
+            }
+            Some("query") => {
+                options.query = Some(parse_import_meta_glob_query(value)?);
+            }
+            Some(key) => {
+                bail!("import.meta.glob(..., options) does not support the `{key}` option");
+            }
+            None => {
+                bail!("import.meta.glob(..., options) requires option names to be constant");
+            }
+        }
+    }
+
+    Ok(options)
+}
+
+fn parse_import_meta_glob_query(value: &JsValue) -> Result<RcStr> {
+    if let Some(query) = value.as_str() {
+        return Ok(if query.starts_with('?') {
+            query.into()
+        } else {
+            format!("?{query}").into()
+        });
+    }
+    let JsValue::Object { parts, .. } = value else {
+        bail!(
+            "import.meta.glob(..., {{ query }}) requires query to be a constant string or object"
+        );
+    };
+    let mut query = String::new();
+    for part in parts {
+        let ObjectPart::KeyValue(key, value) = part else {
+            bail!("import.meta.glob(..., {{ query }}) does not support spread properties");
+        };
+        let Some(key) = key.as_str() else {
+            bail!("import.meta.glob(..., {{ query }}) requires query keys to be constant strings");
+        };
+        // Like Vite, `true` adds the key without a value and `false` omits it
+        let value = match (value.as_str(), value.as_bool()) {
+            (Some(value), _) => Some(value),
+            (None, Some(true)) => None,
+            (None, Some(false)) => continue,
+            (None, None) => bail!(
+                "import.meta.glob(..., {{ query }}) requires query entries to be constant strings \
+                 or booleans"
+            ),
+        };
+        query.push(if query.is_empty() { '?' } else { '&' });
+        query.push_str(key);
+        if let Some(value) = value {
+            write!(query, "={value}")?;
+        }
+    }
+    Ok(query.into())
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct RequireContextValue(FxIndexMap<RcStr, RcStr>);
 
@@ -3847,6 +4004,7 @@ pub enum WellKnownFunctionKind {
     RequireContextRequire(RequireContextValue),
     RequireContextRequireKeys(RequireContextValue),
     RequireContextRequireResolve(RequireContextValue),
+    ImportMetaGlob,
     Define,
     FsReadMethod(Atom),
     PathToFileUrl,
@@ -3866,6 +4024,8 @@ pub enum WellKnownFunctionKind {
     NodeResolveFrom,
     NodeProtobufLoad,
     WorkerConstructor,
+    SharedWorkerConstructor,
+    ServiceWorkerRegister,
     URLConstructor,
 }
 
@@ -3876,6 +4036,7 @@ impl WellKnownFunctionKind {
             Self::Require { .. } => Some(&["require"]),
             Self::RequireResolve => Some(&["require", "resolve"]),
             Self::RequireContext => Some(&["require", "context"]),
+            Self::ImportMetaGlob => Some(&["import", "meta", "glob"]),
             Self::Define => Some(&["define"]),
             _ => None,
         }
@@ -4019,9 +4180,16 @@ pub mod test_utils {
                     true,
                     "ignored Worker constructor",
                 ),
+                "SharedWorker" => JsValue::unknown_if(
+                    ignore,
+                    JsValue::WellKnownFunction(WellKnownFunctionKind::SharedWorkerConstructor),
+                    true,
+                    "ignored SharedWorker constructor",
+                ),
                 "define" => JsValue::WellKnownFunction(WellKnownFunctionKind::Define),
                 "URL" => JsValue::WellKnownFunction(WellKnownFunctionKind::URLConstructor),
                 "process" => JsValue::WellKnownObject(WellKnownObjectKind::NodeProcess),
+                "navigator" => JsValue::WellKnownObject(WellKnownObjectKind::Navigator),
                 "Object" => JsValue::WellKnownObject(WellKnownObjectKind::GlobalObject),
                 "Buffer" => JsValue::WellKnownObject(WellKnownObjectKind::NodeBuffer),
                 _ => v.into_unknown(true, "unknown global"),
@@ -4500,4 +4668,41 @@ mod tests {
         .await
         .unwrap()
     }
+
+    #[test]
+    fn import_meta_glob_query() {
+        use super::{ConstantValue, ObjectPart, parse_import_meta_glob_query};
+
+        let query = |parts: Vec<(&str, JsValue)>| {
+            parse_import_meta_glob_query(&JsValue::object(
+                parts
+                    .into_iter()
+                    .map(|(key, value)| ObjectPart::KeyValue(key.into(), value))
+                    .collect(),
+            ))
+            .unwrap()
+        };
+
+        assert_eq!(parse_import_meta_glob_query(&"raw".into()).unwrap(), "?raw");
+        assert_eq!(
+            parse_import_meta_glob_query(&"?url".into()).unwrap(),
+            "?url"
+        );
+        assert_eq!(query(vec![("raw", ConstantValue::True.into())]), "?raw");
+        assert_eq!(
+            query(vec![
+                ("lang", "ts".into()),
+                ("skip", ConstantValue::False.into()),
+                ("inline", ConstantValue::True.into()),
+            ]),
+            "?lang=ts&inline"
+        );
+        assert!(
+            parse_import_meta_glob_query(&JsValue::object(vec![ObjectPart::KeyValue(
+                "count".into(),
+                1.0.into(),
+            )]))
+            .is_err()
+        );
+    }
 }
@@ -596,6 +596,15 @@ pub async fn well_known_object_member(
         WellKnownObjectKind::NodePreGyp => node_pre_gyp(prop),
         WellKnownObjectKind::NodeExpressApp => express(prop),
         WellKnownObjectKind::NodeProtobufLoader => protobuf_loader(prop),
+        WellKnownObjectKind::ImportMeta if prop.as_str() == Some("glob") => {
+            JsValue::WellKnownFunction(WellKnownFunctionKind::ImportMetaGlob)
+        }
+        WellKnownObjectKind::Navigator if prop.as_str() == Some("serviceWorker") => {
+            JsValue::WellKnownObject(WellKnownObjectKind::NavigatorServiceWorker)
+        }
+        WellKnownObjectKind::NavigatorServiceWorker if prop.as_str() == Some("register") => {
+            JsValue::WellKnownFunction(WellKnownFunctionKind::ServiceWorkerRegister)
+        }
         #[allow(unreachable_patterns)]
         _ => {
             return Ok((
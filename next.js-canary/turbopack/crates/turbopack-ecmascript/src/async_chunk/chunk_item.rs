@@ -20,7 +20,7 @@ use crate::{
         EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkPlaceable,
         EcmascriptChunkType, data::EcmascriptChunkData,
     },
-    runtime_functions::{TURBOPACK_EXPORT_VALUE, TURBOPACK_LOAD},
+    runtime_functions::{TURBOPACK_EXPORT_VALUE, TURBOPACK_HINT_CHUNKS, TURBOPACK_LOAD},
     utils::{StringifyJs, StringifyModuleId},
 };
 
@@ -50,12 +50,28 @@ impl AsyncLoaderChunkItem {
                 return Ok(Vc::cell(vec![]));
             }
         }
-        Ok(self.chunking_context.chunk_group_assets(
-            module.inner.ident(),
-            ChunkGroup::Async(ResolvedVc::upcast(module.inner)),
-            *self.module_graph,
-            module.availability_info,
-        ))
+        let chunk_group = ChunkGroup::Async(ResolvedVc::upcast(module.inner));
+        Ok(match &module.options.name {
+            Some(name) => {
+                *self
+                    .chunking_context
+                    .named_chunk_group(
+                        name.clone(),
+                        module.inner.ident(),
+                        chunk_group,
+                        *self.module_graph,
+                        module.availability_info,
+                    )
+                    .await?
+                    .assets
+            }
+            None => self.chunking_context.chunk_group_assets(
+                module.inner.ident(),
+                chunk_group,
+                *self.module_graph,
+                module.availability_info,
+            ),
+        })
     }
 
     #[turbo_tasks::function]
@@ -95,7 +111,7 @@ impl EcmascriptChunkItem for AsyncLoaderChunkItem {
             .map(|chunk_data| EcmascriptChunkData::new(chunk_data))
             .collect();
 
-        let code = match (id, chunks_data.is_empty()) {
+        let mut code = match (id, chunks_data.is_empty()) {
             (Some(id), true) => {
                 formatdoc! {
                     r#"
@@ -142,6 +158,20 @@ impl EcmascriptChunkItem for AsyncLoaderChunkItem {
             }
         };
 
+        // The loader is required by the parent module when it is evaluated, so the hint is
+        // emitted before the chunks are actually loaded.
+        if let Some(hint) = module.options.hint
+            && !chunks_data.is_empty()
+        {
+            code = formatdoc! {
+                r#"
+                    {TURBOPACK_HINT_CHUNKS}({chunks:#}, {hint});
+                    {code}"#,
+                chunks = StringifyJs(&chunks_data),
+                hint = StringifyJs(hint.as_str()),
+            };
+        }
+
         Ok(EcmascriptChunkItemContent {
             inner_code: code.into(),
             ..Default::default()
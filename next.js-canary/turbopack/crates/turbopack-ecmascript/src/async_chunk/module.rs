@@ -3,7 +3,9 @@ use turbo_rcstr::rcstr;
 use turbo_tasks::{ResolvedVc, Vc};
 use turbopack_core::{
     asset::{Asset, AssetContent},
-    chunk::{ChunkableModule, ChunkingContext, availability_info::AvailabilityInfo},
+    chunk::{
+        AsyncChunkOptions, ChunkableModule, ChunkingContext, availability_info::AvailabilityInfo,
+    },
     ident::AssetIdent,
     module::Module,
     module_graph::ModuleGraph,
@@ -19,6 +21,7 @@ pub struct AsyncLoaderModule {
     pub inner: ResolvedVc<Box<dyn ChunkableModule>>,
     pub chunking_context: ResolvedVc<Box<dyn ChunkingContext>>,
     pub availability_info: AvailabilityInfo,
+    pub options: AsyncChunkOptions,
 }
 
 #[turbo_tasks::value_impl]
@@ -28,17 +31,22 @@ impl AsyncLoaderModule {
         module: ResolvedVc<Box<dyn ChunkableModule>>,
         chunking_context: ResolvedVc<Box<dyn ChunkingContext>>,
         availability_info: AvailabilityInfo,
+        options: AsyncChunkOptions,
     ) -> Vc<Self> {
         Self::cell(AsyncLoaderModule {
             inner: module,
             chunking_context,
             availability_info,
+            options,
         })
     }
 
     #[turbo_tasks::function]
-    pub fn asset_ident_for(module: Vc<Box<dyn ChunkableModule>>) -> Vc<AssetIdent> {
-        module.ident().with_modifier(rcstr!("async loader"))
+    pub fn asset_ident_for(
+        module: Vc<Box<dyn ChunkableModule>>,
+        options: AsyncChunkOptions,
+    ) -> Vc<AssetIdent> {
+        options.apply_to_ident(module.ident().with_modifier(rcstr!("async loader")))
     }
 }
 
@@ -46,7 +54,7 @@ impl AsyncLoaderModule {
 impl Module for AsyncLoaderModule {
     #[turbo_tasks::function]
     fn ident(&self) -> Vc<AssetIdent> {
-        Self::asset_ident_for(*self.inner)
+        Self::asset_ident_for(*self.inner, self.options.clone())
     }
 
     #[turbo_tasks::function]
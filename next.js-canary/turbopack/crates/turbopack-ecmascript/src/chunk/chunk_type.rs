@@ -35,6 +35,7 @@ impl ChunkType for EcmascriptChunkType {
     async fn chunk(
         &self,
         chunking_context: Vc<Box<dyn ChunkingContext>>,
+        name: Option<RcStr>,
         chunk_items: Vec<ChunkItemOrBatchWithAsyncModuleInfo>,
         batch_groups: Vec<ResolvedVc<ChunkItemBatchGroup>>,
         referenced_output_assets: Vc<OutputAssets>,
@@ -62,7 +63,11 @@ impl ChunkType for EcmascriptChunkType {
             referenced_output_assets: referenced_output_assets.owned().await?,
         }
         .cell();
-        Ok(Vc::upcast(EcmascriptChunk::new(chunking_context, content)))
+        Ok(Vc::upcast(EcmascriptChunk::new(
+            chunking_context,
+            content,
+            name,
+        )))
     }
 
     #[turbo_tasks::function]
@@ -43,6 +43,8 @@ pub use self::{
 pub struct EcmascriptChunk {
     pub chunking_context: ResolvedVc<Box<dyn ChunkingContext>>,
     pub content: ResolvedVc<EcmascriptChunkContent>,
+    /// A prefix for the file name of the chunk.
+    pub name: Option<RcStr>,
 }
 
 #[turbo_tasks::value_impl]
@@ -51,10 +53,12 @@ impl EcmascriptChunk {
     pub fn new(
         chunking_context: ResolvedVc<Box<dyn ChunkingContext>>,
         content: ResolvedVc<EcmascriptChunkContent>,
+        name: Option<RcStr>,
     ) -> Vc<Self> {
         EcmascriptChunk {
             chunking_context,
             content,
+            name,
         }
         .cell()
     }
@@ -32,6 +32,7 @@ use crate::{
             url::UrlAssetReferenceCodeGen,
         },
         ident::IdentReplacement,
+        import_meta_glob::ImportMetaGlobCodeGen,
         member::MemberReplacement,
         require_context::RequireContextAssetReferenceCodeGen,
         unreachable::Unreachable,
@@ -184,6 +185,7 @@ pub enum CodeGen {
     CjsRequireResolveAssetReferenceCodeGen(CjsRequireResolveAssetReferenceCodeGen),
     EsmAsyncAssetReferenceCodeGen(EsmAsyncAssetReferenceCodeGen),
     EsmModuleIdAssetReferenceCodeGen(EsmModuleIdAssetReferenceCodeGen),
+    ImportMetaGlobCodeGen(ImportMetaGlobCodeGen),
     RequireContextAssetReferenceCodeGen(RequireContextAssetReferenceCodeGen),
     UrlAssetReferenceCodeGen(UrlAssetReferenceCodeGen),
     WorkerAssetReferenceCodeGen(WorkerAssetReferenceCodeGen),
@@ -212,6 +214,7 @@ impl CodeGen {
             Self::CjsRequireResolveAssetReferenceCodeGen(v) => v.code_generation(ctx).await,
             Self::EsmAsyncAssetReferenceCodeGen(v) => v.code_generation(ctx).await,
             Self::EsmModuleIdAssetReferenceCodeGen(v) => v.code_generation(ctx).await,
+            Self::ImportMetaGlobCodeGen(v) => v.code_generation(ctx).await,
             Self::RequireContextAssetReferenceCodeGen(v) => v.code_generation(ctx).await,
             Self::UrlAssetReferenceCodeGen(v) => v.code_generation(ctx).await,
             Self::WorkerAssetReferenceCodeGen(v) => v.code_generation(ctx).await,
@@ -11,6 +11,7 @@
 pub mod analyzer;
 pub mod annotations;
 pub mod async_chunk;
+pub mod bytes;
 pub mod chunk;
 pub mod code_gen;
 mod errors;
@@ -99,13 +100,14 @@ use turbopack_core::{
     ident::AssetIdent,
     module::{Module, OptionModule},
     module_graph::ModuleGraph,
+    output::OutputAssets,
     reference::ModuleReferences,
     reference_type::InnerAssets,
     resolve::{
         FindContextFileResult, find_context_file, origin::ResolveOrigin, package_json,
         parse::Request,
     },
-    source::Source,
+    source::{Source, emitted_files_output_assets},
     source_map::GenerateSourceMap,
 };
 // TODO remove this
@@ -793,6 +795,14 @@ impl ChunkItem for ModuleChunkItem {
     fn module(&self) -> Vc<Box<dyn Module>> {
         *ResolvedVc::upcast(self.module)
     }
+
+    #[turbo_tasks::function]
+    async fn references(&self) -> Result<Vc<OutputAssets>> {
+        Ok(emitted_files_output_assets(
+            *self.module.await?.source,
+            *self.chunking_context,
+        ))
+    }
 }
 
 #[turbo_tasks::value_impl]
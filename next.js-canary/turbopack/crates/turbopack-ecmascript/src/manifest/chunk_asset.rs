@@ -4,7 +4,8 @@ use turbo_tasks::{ResolvedVc, TryJoinIterExt, Vc};
 use turbopack_core::{
     asset::{Asset, AssetContent},
     chunk::{
-        ChunkableModule, ChunkingContext, ChunkingContextExt, availability_info::AvailabilityInfo,
+        AsyncChunkOptions, ChunkableModule, ChunkingContext, ChunkingContextExt,
+        availability_info::AvailabilityInfo,
     },
     ident::AssetIdent,
     module::Module,
@@ -35,6 +36,7 @@ pub struct ManifestAsyncModule {
     pub module_graph: ResolvedVc<ModuleGraph>,
     pub chunking_context: ResolvedVc<Box<dyn ChunkingContext>>,
     pub availability_info: AvailabilityInfo,
+    pub options: AsyncChunkOptions,
 }
 
 #[turbo_tasks::value_impl]
@@ -45,23 +47,41 @@ impl ManifestAsyncModule {
         module_graph: ResolvedVc<ModuleGraph>,
         chunking_context: ResolvedVc<Box<dyn ChunkingContext>>,
         availability_info: AvailabilityInfo,
+        options: AsyncChunkOptions,
     ) -> Vc<Self> {
         Self::cell(ManifestAsyncModule {
             inner: module,
             module_graph,
             chunking_context,
             availability_info,
+            options,
         })
     }
 
     #[turbo_tasks::function]
-    pub(super) fn chunks(&self) -> Vc<OutputAssets> {
-        self.chunking_context.chunk_group_assets(
-            self.inner.ident(),
-            ChunkGroup::Async(ResolvedVc::upcast(self.inner)),
-            *self.module_graph,
-            self.availability_info,
-        )
+    pub(super) async fn chunks(&self) -> Result<Vc<OutputAssets>> {
+        let chunk_group = ChunkGroup::Async(ResolvedVc::upcast(self.inner));
+        Ok(match &self.options.name {
+            Some(name) => {
+                *self
+                    .chunking_context
+                    .named_chunk_group(
+                        name.clone(),
+                        self.inner.ident(),
+                        chunk_group,
+                        *self.module_graph,
+                        self.availability_info,
+                    )
+                    .await?
+                    .assets
+            }
+            None => self.chunking_context.chunk_group_assets(
+                self.inner.ident(),
+                chunk_group,
+                *self.module_graph,
+                self.availability_info,
+            ),
+        })
     }
 
     #[turbo_tasks::function]
@@ -91,12 +111,12 @@ impl ManifestAsyncModule {
 
     #[turbo_tasks::function]
     pub fn module_ident(&self) -> Vc<AssetIdent> {
-        self.inner.ident()
+        self.options.apply_to_ident(self.inner.ident())
     }
 
     #[turbo_tasks::function]
     pub async fn content_ident(&self) -> Result<Vc<AssetIdent>> {
-        let mut ident = self.inner.ident();
+        let mut ident = self.options.apply_to_ident(self.inner.ident());
         if let Some(available_modules) = self.availability_info.available_modules() {
             ident = ident.with_modifier(available_modules.hash().await?.to_string().into());
         }
@@ -112,9 +132,11 @@ fn manifest_chunk_reference_description() -> RcStr {
 impl Module for ManifestAsyncModule {
     #[turbo_tasks::function]
     fn ident(&self) -> Vc<AssetIdent> {
-        self.inner
-            .ident()
-            .with_modifier(manifest_chunk_reference_description())
+        self.options.apply_to_ident(
+            self.inner
+                .ident()
+                .with_modifier(manifest_chunk_reference_description()),
+        )
     }
 
     #[turbo_tasks::function]
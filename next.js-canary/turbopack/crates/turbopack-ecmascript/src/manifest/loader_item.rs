@@ -6,8 +6,8 @@ use turbo_rcstr::{RcStr, rcstr};
 use turbo_tasks::{ResolvedVc, TryJoinIterExt, Vc};
 use turbopack_core::{
     chunk::{
-        ChunkData, ChunkItem, ChunkType, ChunkableModule, ChunkingContext, ChunksData,
-        ModuleChunkItemIdExt,
+        AsyncChunkOptions, ChunkData, ChunkItem, ChunkType, ChunkableModule, ChunkingContext,
+        ChunksData, ModuleChunkItemIdExt,
     },
     ident::AssetIdent,
     module::Module,
@@ -21,7 +21,9 @@ use crate::{
         EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkPlaceable,
         EcmascriptChunkType, data::EcmascriptChunkData,
     },
-    runtime_functions::{TURBOPACK_EXPORT_VALUE, TURBOPACK_LOAD, TURBOPACK_REQUIRE},
+    runtime_functions::{
+        TURBOPACK_EXPORT_VALUE, TURBOPACK_HINT_CHUNKS, TURBOPACK_LOAD, TURBOPACK_REQUIRE,
+    },
     utils::{StringifyJs, StringifyModuleId},
 };
 
@@ -75,8 +77,13 @@ impl ManifestLoaderChunkItem {
     }
 
     #[turbo_tasks::function]
-    pub fn asset_ident_for(module: Vc<Box<dyn ChunkableModule>>) -> Vc<AssetIdent> {
-        module.ident().with_modifier(modifier())
+    pub fn asset_ident_for(
+        module: Vc<Box<dyn ChunkableModule>>,
+        options: AsyncChunkOptions,
+    ) -> Vc<AssetIdent> {
+        options
+            .apply_to_ident(module.ident())
+            .with_modifier(modifier())
     }
 }
 
@@ -135,6 +142,10 @@ impl EcmascriptChunkItem for ManifestLoaderChunkItem {
         // chunk is a servable file, which will contain the manifest chunk item, which
         // will perform the actual chunk traversal and generate load statements.
         let chunks_server_data = &*self.chunks_data().await?.iter().try_join().await?;
+        let chunks_server_data = chunks_server_data
+            .iter()
+            .map(|chunk_data| EcmascriptChunkData::new(chunk_data))
+            .collect::<Vec<_>>();
 
         // We also need the manifest chunk item's id, which points to a CJS module that
         // exports a promise for all of the necessary chunk loads.
@@ -152,6 +163,19 @@ impl EcmascriptChunkItem for ManifestLoaderChunkItem {
             .chunk_item_id(*ResolvedVc::upcast(manifest.chunking_context))
             .await?;
 
+        // The chunks of the chunk group are only known once the manifest chunk is loaded, so a
+        // resource hint can only cover the manifest chunk itself.
+        if let Some(hint) = manifest.options.hint
+            && !chunks_server_data.is_empty()
+        {
+            writeln!(
+                code,
+                "{TURBOPACK_HINT_CHUNKS}({chunks_server_data:#}, {hint});",
+                chunks_server_data = StringifyJs(&chunks_server_data),
+                hint = StringifyJs(hint.as_str()),
+            )?;
+        }
+
         // This is the code that will be executed when the dynamic import is reached.
         // It will load the manifest chunk, which will load all the chunks needed by
         // the dynamic import, and finally we'll be able to import the module we're
@@ -171,12 +195,7 @@ impl EcmascriptChunkItem for ManifestLoaderChunkItem {
                     }});
                 }});
             "#,
-            chunks_server_data = StringifyJs(
-                &chunks_server_data
-                    .iter()
-                    .map(|chunk_data| EcmascriptChunkData::new(chunk_data))
-                    .collect::<Vec<_>>()
-            ),
+            chunks_server_data = StringifyJs(&chunks_server_data),
             item_id = StringifyModuleId(item_id),
             dynamic_id = StringifyModuleId(dynamic_id),
         )?;
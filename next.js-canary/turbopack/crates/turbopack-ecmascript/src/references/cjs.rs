@@ -14,9 +14,14 @@ use turbopack_core::{
     issue::IssueSource,
     reference::ModuleReference,
     reference_type::CommonJsReferenceSubType,
-    resolve::{ModuleResolveResult, origin::ResolveOrigin, parse::Request},
+    resolve::{
+        ModuleResolveResult,
+        origin::ResolveOrigin,
+        parse::Request,
+        trace::{ResolveTrace, ResolveTracedReference},
+    },
 };
-use turbopack_resolve::ecmascript::cjs_resolve;
+use turbopack_resolve::ecmascript::{cjs_resolve, cjs_resolve_trace};
 
 use crate::{
     code_gen::{CodeGen, CodeGeneration, IntoCodeGenReference},
@@ -121,6 +126,18 @@ impl ModuleReference for CjsRequireAssetReference {
     }
 }
 
+#[turbo_tasks::value_impl]
+impl ResolveTracedReference for CjsRequireAssetReference {
+    #[turbo_tasks::function]
+    fn resolve_trace(&self) -> Vc<ResolveTrace> {
+        cjs_resolve_trace(
+            *self.origin,
+            *self.request,
+            CommonJsReferenceSubType::Undefined,
+        )
+    }
+}
+
 #[turbo_tasks::value_impl]
 impl ValueToString for CjsRequireAssetReference {
     #[turbo_tasks::function]
@@ -25,15 +25,16 @@ use turbopack_core::{
     module::Module,
     module_graph::export_usage::ModuleExportUsageInfo,
     reference::ModuleReference,
-    reference_type::{EcmaScriptModulesReferenceSubType, ImportWithType},
+    reference_type::EcmaScriptModulesReferenceSubType,
     resolve::{
         ExportUsage, ExternalType, ModulePart, ModuleResolveResult, ModuleResolveResultItem,
         RequestKey,
         origin::{ResolveOrigin, ResolveOriginExt},
         parse::Request,
+        trace::{ResolveTrace, ResolveTracedReference},
     },
 };
-use turbopack_resolve::ecmascript::esm_resolve;
+use turbopack_resolve::ecmascript::{esm_resolve, esm_resolve_trace};
 
 use super::export::{all_known_export_names, is_export_missing};
 use crate::{
@@ -384,8 +385,8 @@ impl EsmAssetReference {
 impl ModuleReference for EsmAssetReference {
     #[turbo_tasks::function]
     async fn resolve_reference(&self) -> Result<Vc<ModuleResolveResult>> {
-        let ty = if matches!(self.annotations.module_type(), Some("json")) {
-            EcmaScriptModulesReferenceSubType::ImportWithType(ImportWithType::Json)
+        let ty = if let Some(ty) = self.annotations.import_with_type() {
+            EcmaScriptModulesReferenceSubType::ImportWithType(ty)
         } else if let Some(part) = &self.export_name {
             EcmaScriptModulesReferenceSubType::ImportPart(part.clone())
         } else {
@@ -440,6 +441,7 @@ impl ModuleReference for EsmAssetReference {
             self.get_origin().resolve().await?,
             *self.request,
             ty,
+            self.annotations.conditions(),
             false,
             Some(self.issue_source),
         )
@@ -465,6 +467,26 @@ impl ModuleReference for EsmAssetReference {
     }
 }
 
+#[turbo_tasks::value_impl]
+impl ResolveTracedReference for EsmAssetReference {
+    #[turbo_tasks::function]
+    fn resolve_trace(&self) -> Vc<ResolveTrace> {
+        let ty = if let Some(ty) = self.annotations.import_with_type() {
+            EcmaScriptModulesReferenceSubType::ImportWithType(ty)
+        } else if let Some(part) = &self.export_name {
+            EcmaScriptModulesReferenceSubType::ImportPart(part.clone())
+        } else {
+            EcmaScriptModulesReferenceSubType::Import
+        };
+        esm_resolve_trace(
+            self.get_origin(),
+            *self.request,
+            ty,
+            self.annotations.conditions(),
+        )
+    }
+}
+
 #[turbo_tasks::value_impl]
 impl ValueToString for EsmAssetReference {
     #[turbo_tasks::function]
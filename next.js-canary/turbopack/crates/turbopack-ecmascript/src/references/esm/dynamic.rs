@@ -1,34 +1,45 @@
+use std::borrow::Cow;
+
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use swc_core::{
     common::{DUMMY_SP, util::take::Take},
-    ecma::ast::{CallExpr, Callee, Expr, ExprOrSpread, Lit},
-    quote_expr,
+    ecma::ast::{CallExpr, Callee, Expr, ExprOrSpread, Lit, Stmt},
+    quote, quote_expr,
 };
 use turbo_rcstr::RcStr;
 use turbo_tasks::{
-    NonLocalValue, ResolvedVc, ValueToString, Vc, debug::ValueDebugFormat, trace::TraceRawVcs,
+    NonLocalValue, ReadRef, ResolvedVc, ValueToString, Vc, debug::ValueDebugFormat,
+    trace::TraceRawVcs,
 };
 use turbopack_core::{
-    chunk::{ChunkableModuleReference, ChunkingContext, ChunkingType, ChunkingTypeOption},
+    chunk::{
+        AsyncChunkOptions, ChunkLoadingHint, ChunkableModuleReference, ChunkingContext,
+        ChunkingType, ChunkingTypeOption, ModuleChunkItemIdExt, ModuleId,
+    },
     environment::ChunkLoading,
     issue::IssueSource,
     reference::ModuleReference,
     reference_type::EcmaScriptModulesReferenceSubType,
     resolve::{
-        ModuleResolveResult,
+        ExportUsage, ModuleResolveResult,
         origin::{ResolveOrigin, ResolveOriginExt},
         parse::Request,
     },
 };
 use turbopack_resolve::ecmascript::esm_resolve;
 
-use super::super::pattern_mapping::{PatternMapping, ResolveType};
+use super::{
+    super::pattern_mapping::{PatternMapping, ResolveType, SinglePatternMapping},
+    lazy_once::LazyOnceContextModule,
+};
 use crate::{
-    analyzer::imports::ImportAnnotations,
-    code_gen::{CodeGen, CodeGeneration, IntoCodeGenReference},
+    analyzer::imports::{DynamicImportMode, ImportAnnotations, ImportAttributes},
+    code_gen::{CodeGen, CodeGeneration, CodeGenerationHoistedStmt, IntoCodeGenReference},
     create_visitor,
     references::AstPath,
+    runtime_functions::{TURBOPACK_MODULE_CONTEXT, TURBOPACK_REQUIRE},
+    utils::module_id_to_lit,
 };
 
 #[turbo_tasks::value]
@@ -37,6 +48,8 @@ pub struct EsmAsyncAssetReference {
     pub origin: ResolvedVc<Box<dyn ResolveOrigin>>,
     pub request: ResolvedVc<Request>,
     pub annotations: ImportAnnotations,
+    /// The magic comments of the `import()`, e.g. `webpackChunkName`.
+    pub attributes: ImportAttributes,
     pub issue_source: IssueSource,
     pub in_try: bool,
     pub import_externals: bool,
@@ -50,6 +63,42 @@ impl EsmAsyncAssetReference {
             *self.origin
         }
     }
+
+    /// The options for the async chunk group, from the magic comments of the `import()`.
+    fn async_chunk_options(&self) -> AsyncChunkOptions {
+        let attributes = &self.attributes;
+        AsyncChunkOptions {
+            name: attributes.chunk_name.clone(),
+            hint: if attributes.preload {
+                Some(ChunkLoadingHint::Preload)
+            } else if attributes.prefetch {
+                Some(ChunkLoadingHint::Prefetch)
+            } else {
+                None
+            },
+        }
+    }
+
+    /// The module bundling all modules matched by the request, when the import uses the
+    /// `lazy-once` mode and the request has dynamic parts.
+    async fn lazy_once_module(&self) -> Result<Option<ResolvedVc<LazyOnceContextModule>>> {
+        if self.attributes.mode != Some(DynamicImportMode::LazyOnce)
+            || !self.request.request_pattern().await?.has_dynamic_parts()
+        {
+            return Ok(None);
+        }
+        Ok(Some(
+            LazyOnceContextModule::new(
+                self.get_origin(),
+                *self.request,
+                self.issue_source,
+                self.in_try,
+                self.import_externals,
+            )
+            .to_resolved()
+            .await?,
+        ))
+    }
 }
 
 impl EsmAsyncAssetReference {
@@ -58,6 +107,7 @@ impl EsmAsyncAssetReference {
         request: ResolvedVc<Request>,
         issue_source: IssueSource,
         annotations: ImportAnnotations,
+        attributes: ImportAttributes,
         in_try: bool,
         import_externals: bool,
     ) -> Self {
@@ -66,6 +116,7 @@ impl EsmAsyncAssetReference {
             request,
             issue_source,
             annotations,
+            attributes,
             in_try,
             import_externals,
         }
@@ -76,10 +127,17 @@ impl EsmAsyncAssetReference {
 impl ModuleReference for EsmAsyncAssetReference {
     #[turbo_tasks::function]
     async fn resolve_reference(&self) -> Result<Vc<ModuleResolveResult>> {
+        if let Some(module) = self.lazy_once_module().await? {
+            return Ok(*ModuleResolveResult::module(ResolvedVc::upcast(module)));
+        }
         esm_resolve(
             self.get_origin().resolve().await?,
             *self.request,
-            EcmaScriptModulesReferenceSubType::DynamicImport,
+            match self.annotations.import_with_type() {
+                Some(ty) => EcmaScriptModulesReferenceSubType::ImportWithType(ty),
+                None => EcmaScriptModulesReferenceSubType::DynamicImport,
+            },
+            self.annotations.conditions(),
             self.in_try,
             Some(self.issue_source),
         )
@@ -101,7 +159,27 @@ impl ValueToString for EsmAsyncAssetReference {
 impl ChunkableModuleReference for EsmAsyncAssetReference {
     #[turbo_tasks::function]
     fn chunking_type(&self) -> Vc<ChunkingTypeOption> {
-        Vc::cell(Some(ChunkingType::Async))
+        Vc::cell(Some(
+            if self.attributes.mode == Some(DynamicImportMode::Eager) {
+                ChunkingType::Parallel {
+                    inherit_async: false,
+                    hoisted: false,
+                }
+            } else {
+                ChunkingType::Async(self.async_chunk_options())
+            },
+        ))
+    }
+
+    #[turbo_tasks::function]
+    async fn export_usage(&self) -> Result<Vc<ExportUsage>> {
+        Ok(match &self.attributes.exports {
+            // The namespace object of a lazy-once import is the context map
+            Some(exports) if self.lazy_once_module().await?.is_none() => {
+                ExportUsage::partial_namespace_object(exports.clone())
+            }
+            _ => ExportUsage::all(),
+        })
     }
 }
 
@@ -134,21 +212,61 @@ impl EsmAsyncAssetReferenceCodeGen {
     ) -> Result<CodeGeneration> {
         let reference = self.reference.await?;
 
-        let pm = PatternMapping::resolve_request(
-            *reference.request,
-            *reference.origin,
-            Vc::upcast(chunking_context),
-            self.reference.resolve_reference(),
-            if matches!(
+        let resolve_type = if reference.attributes.mode == Some(DynamicImportMode::Eager)
+            || matches!(
                 *chunking_context.environment().chunk_loading().await?,
                 ChunkLoading::Edge
             ) {
-                ResolveType::ChunkItem
-            } else {
-                ResolveType::AsyncChunkLoader
-            },
-        )
-        .await?;
+            ResolveType::ChunkItem
+        } else {
+            ResolveType::AsyncChunkLoader(reference.async_chunk_options())
+        };
+
+        let import = if let Some(module) = reference.lazy_once_module().await? {
+            DynamicImport::LazyOnce(match resolve_type {
+                ResolveType::AsyncChunkLoader(options) => SinglePatternMapping::ModuleLoader(
+                    chunking_context
+                        .async_loader_chunk_item_id(Vc::upcast(*module), options)
+                        .owned()
+                        .await?,
+                ),
+                ResolveType::ChunkItem => SinglePatternMapping::Module(
+                    module.chunk_item_id(chunking_context).owned().await?,
+                ),
+            })
+        } else {
+            DynamicImport::Pattern(
+                PatternMapping::resolve_request(
+                    *reference.request,
+                    *reference.origin,
+                    Vc::upcast(chunking_context),
+                    self.reference.resolve_reference(),
+                    resolve_type,
+                )
+                .await?,
+            )
+        };
+
+        // Requiring the async loaders when the module is evaluated emits the prefetch/preload
+        // hints for their chunks.
+        let hoisted_stmts = if reference.attributes.prefetch || reference.attributes.preload {
+            import
+                .loader_ids()
+                .into_iter()
+                .map(|id| {
+                    CodeGenerationHoistedStmt::new(
+                        format!("hint {id}").into(),
+                        quote!(
+                            "$turbopack_require($id);" as Stmt,
+                            turbopack_require: Expr = TURBOPACK_REQUIRE.into(),
+                            id: Expr = module_id_to_lit(id),
+                        ),
+                    )
+                })
+                .collect()
+        } else {
+            vec![]
+        };
 
         let import_externals = reference.import_externals;
 
@@ -160,7 +278,7 @@ impl EsmAsyncAssetReferenceCodeGen {
                         spread: None,
                         expr: key_expr,
                     }) => {
-                        *expr = pm.create_import(*key_expr, import_externals);
+                        *expr = import.create_import(*key_expr, import_externals);
                         return;
                     }
                     // These are SWC bugs: https://github.com/swc-project/swc/issues/5394
@@ -188,6 +306,42 @@ impl EsmAsyncAssetReferenceCodeGen {
             });
         });
 
-        Ok(CodeGeneration::visitors(vec![visitor]))
+        Ok(CodeGeneration::new(vec![visitor], hoisted_stmts, vec![]))
+    }
+}
+
+/// How the request of an `import()` maps to the module(s) it imports.
+enum DynamicImport {
+    Pattern(ReadRef<PatternMapping>),
+    /// The context module of a `lazy-once` import, its map is keyed by the request.
+    LazyOnce(SinglePatternMapping),
+}
+
+impl DynamicImport {
+    fn create_import(&self, key_expr: Expr, import_externals: bool) -> Expr {
+        match self {
+            DynamicImport::Pattern(pm) => pm.create_import(key_expr, import_externals),
+            DynamicImport::LazyOnce(pm) => quote!(
+                "$import.then((m) => $turbopack_module_context(m.default).import($key))" as Expr,
+                import: Expr = pm.create_import(Cow::Borrowed(&key_expr), import_externals),
+                turbopack_module_context: Expr = TURBOPACK_MODULE_CONTEXT.into(),
+                key: Expr = key_expr,
+            ),
+        }
+    }
+
+    /// The ids of the async loaders used by the import.
+    fn loader_ids(&self) -> Vec<&ModuleId> {
+        let loader_id = |pm: &SinglePatternMapping| match pm {
+            SinglePatternMapping::ModuleLoader(id) => Some(id),
+            _ => None,
+        };
+        match self {
+            DynamicImport::Pattern(pm) => match &**pm {
+                PatternMapping::Single(pm) => loader_id(pm).into_iter().collect(),
+                PatternMapping::Map(map) => map.values().filter_map(loader_id).collect(),
+            },
+            DynamicImport::LazyOnce(pm) => loader_id(pm).into_iter().collect(),
+        }
     }
 }
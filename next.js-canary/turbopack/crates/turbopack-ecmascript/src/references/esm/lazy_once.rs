@@ -0,0 +1,293 @@
+use std::{borrow::Cow, sync::Arc};
+
+use anyhow::{Result, bail};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::{
+        ast::{
+            Expr, ExprStmt, KeyValueProp, Lit, ModuleItem, ObjectLit, Prop, PropName, PropOrSpread,
+            Stmt, {self},
+        },
+        codegen::{Emitter, text_writer::JsWriter},
+    },
+    quote_expr,
+};
+use turbo_rcstr::RcStr;
+use turbo_tasks::{ResolvedVc, ValueToString, Vc};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{
+        ChunkItem, ChunkType, ChunkableModule, ChunkableModuleReference, ChunkingContext,
+        MinifyType,
+    },
+    ident::AssetIdent,
+    issue::IssueSource,
+    module::Module,
+    module_graph::ModuleGraph,
+    reference::{ModuleReference, ModuleReferences},
+    reference_type::EcmaScriptModulesReferenceSubType,
+    resolve::{ModuleResolveResult, origin::ResolveOrigin, parse::Request},
+};
+use turbopack_resolve::ecmascript::esm_resolve;
+
+use crate::{
+    EcmascriptChunkPlaceable,
+    chunk::{
+        EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkType, EcmascriptExports,
+    },
+    references::pattern_mapping::{PatternMapping, ResolveType},
+    runtime_functions::TURBOPACK_EXPORT_VALUE,
+};
+
+/// A module for a dynamic `import()` with a `webpackMode: "lazy-once"` comment. It references all
+/// modules matched by the request, so they are placed in a single async chunk group. It exports a
+/// context map that imports the matched modules by their request.
+#[turbo_tasks::value]
+pub struct LazyOnceContextModule {
+    origin: ResolvedVc<Box<dyn ResolveOrigin>>,
+    request: ResolvedVc<Request>,
+    issue_source: IssueSource,
+    in_try: bool,
+    import_externals: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl LazyOnceContextModule {
+    #[turbo_tasks::function]
+    pub fn new(
+        origin: ResolvedVc<Box<dyn ResolveOrigin>>,
+        request: ResolvedVc<Request>,
+        issue_source: IssueSource,
+        in_try: bool,
+        import_externals: bool,
+    ) -> Vc<Self> {
+        LazyOnceContextModule {
+            origin,
+            request,
+            issue_source,
+            in_try,
+            import_externals,
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn reference(&self) -> Vc<LazyOnceModuleReference> {
+        LazyOnceModuleReference {
+            origin: self.origin,
+            request: self.request,
+            issue_source: self.issue_source,
+            in_try: self.in_try,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Module for LazyOnceContextModule {
+    #[turbo_tasks::function]
+    async fn ident(&self) -> Result<Vc<AssetIdent>> {
+        Ok(
+            AssetIdent::from_path(self.origin.origin_path().owned().await?).with_modifier(
+                format!("lazy-once import {}", self.request.to_string().await?).into(),
+            ),
+        )
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self: Vc<Self>) -> Result<Vc<ModuleReferences>> {
+        Ok(Vc::cell(vec![ResolvedVc::upcast(
+            self.reference().to_resolved().await?,
+        )]))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for LazyOnceContextModule {
+    #[turbo_tasks::function]
+    fn content(&self) -> Result<Vc<AssetContent>> {
+        bail!("lazy-once context module has no contents")
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModule for LazyOnceContextModule {
+    #[turbo_tasks::function]
+    fn as_chunk_item(
+        self: ResolvedVc<Self>,
+        _module_graph: ResolvedVc<ModuleGraph>,
+        chunking_context: ResolvedVc<Box<dyn ChunkingContext>>,
+    ) -> Vc<Box<dyn ChunkItem>> {
+        Vc::upcast(
+            LazyOnceContextChunkItem {
+                module: self,
+                chunking_context,
+            }
+            .cell(),
+        )
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for LazyOnceContextModule {
+    #[turbo_tasks::function]
+    fn get_exports(&self) -> Vc<EcmascriptExports> {
+        EcmascriptExports::Value.cell()
+    }
+}
+
+/// The reference from a [LazyOnceContextModule] to the modules matched by its request. The modules
+/// are placed in the same chunk group as the context module.
+#[turbo_tasks::value]
+#[derive(Hash, Debug)]
+pub struct LazyOnceModuleReference {
+    origin: ResolvedVc<Box<dyn ResolveOrigin>>,
+    request: ResolvedVc<Request>,
+    issue_source: IssueSource,
+    in_try: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleReference for LazyOnceModuleReference {
+    #[turbo_tasks::function]
+    async fn resolve_reference(&self) -> Result<Vc<ModuleResolveResult>> {
+        esm_resolve(
+            *self.origin,
+            *self.request,
+            EcmaScriptModulesReferenceSubType::DynamicImport,
+            vec![],
+            self.in_try,
+            Some(self.issue_source),
+        )
+        .await
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for LazyOnceModuleReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<RcStr>> {
+        Ok(Vc::cell(
+            format!("lazy-once import {}", self.request.to_string().await?).into(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModuleReference for LazyOnceModuleReference {}
+
+#[turbo_tasks::value]
+struct LazyOnceContextChunkItem {
+    module: ResolvedVc<LazyOnceContextModule>,
+    chunking_context: ResolvedVc<Box<dyn ChunkingContext>>,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for LazyOnceContextChunkItem {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<EcmascriptChunkItemContent>> {
+        let module = self.module.await?;
+        let minify = self.chunking_context.minify_type().await?;
+
+        let pm = PatternMapping::resolve_request(
+            *module.request,
+            *module.origin,
+            *self.chunking_context,
+            self.module.reference().resolve_reference(),
+            ResolveType::ChunkItem,
+        )
+        .await?;
+
+        let mut context_map = ObjectLit {
+            span: DUMMY_SP,
+            props: vec![],
+        };
+
+        // A request without matches results in an empty map, the context throws a "Cannot find
+        // module" error when importing from it.
+        if let PatternMapping::Map(map) = &*pm {
+            for (key, pm) in map {
+                let key_expr = Expr::Lit(Lit::Str(key.as_str().into()));
+
+                let prop = KeyValueProp {
+                    key: PropName::Str(key.as_str().into()),
+                    value: quote_expr!(
+                        "{ id: () => $id, module: () => $module }",
+                        id: Expr =
+                            pm.create_id(Cow::Borrowed(&key_expr)),
+                        module: Expr =
+                            pm.create_import(Cow::Borrowed(&key_expr), module.import_externals),
+                    ),
+                };
+
+                context_map
+                    .props
+                    .push(PropOrSpread::Prop(Box::new(Prop::KeyValue(prop))));
+            }
+        }
+
+        let expr = quote_expr!(
+            "$turbopack_export_value($obj);",
+            turbopack_export_value: Expr = TURBOPACK_EXPORT_VALUE.into(),
+            obj: Expr = Expr::Object(context_map),
+        );
+
+        let program = ast::Module {
+            span: DUMMY_SP,
+            body: vec![ModuleItem::Stmt(Stmt::Expr(ExprStmt {
+                span: DUMMY_SP,
+                expr,
+            }))],
+            shebang: None,
+        };
+
+        let source_map: Arc<swc_core::common::SourceMap> = Default::default();
+
+        let mut bytes: Vec<u8> = vec![];
+        let mut wr: JsWriter<'_, &mut Vec<u8>> =
+            JsWriter::new(source_map.clone(), "\n", &mut bytes, None);
+        if matches!(*minify, MinifyType::Minify { .. }) {
+            wr.set_indent_str("");
+        }
+
+        let mut emitter = Emitter {
+            cfg: swc_core::ecma::codegen::Config::default(),
+            cm: source_map.clone(),
+            comments: None,
+            wr,
+        };
+
+        emitter.emit_module(&program)?;
+
+        Ok(EcmascriptChunkItemContent {
+            inner_code: bytes.into(),
+            ..Default::default()
+        }
+        .cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for LazyOnceContextChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> Vc<AssetIdent> {
+        self.module.ident()
+    }
+
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        *self.chunking_context
+    }
+
+    #[turbo_tasks::function]
+    async fn ty(&self) -> Result<Vc<Box<dyn ChunkType>>> {
+        Ok(Vc::upcast(
+            Vc::<EcmascriptChunkType>::default().resolve().await?,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn module(&self) -> Vc<Box<dyn Module>> {
+        *ResolvedVc::upcast(self.module)
+    }
+}
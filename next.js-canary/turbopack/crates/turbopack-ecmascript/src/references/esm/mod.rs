@@ -2,6 +2,7 @@ pub(crate) mod base;
 pub(crate) mod binding;
 pub(crate) mod dynamic;
 pub(crate) mod export;
+pub(crate) mod lazy_once;
 pub(crate) mod meta;
 pub(crate) mod module_id;
 pub(crate) mod module_item;
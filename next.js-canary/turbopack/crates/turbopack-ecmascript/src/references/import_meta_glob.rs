@@ -0,0 +1,318 @@
+use std::borrow::Cow;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{Expr, KeyValueProp, Lit, ObjectLit, Prop, PropName, PropOrSpread},
+    quote, quote_expr,
+};
+use turbo_rcstr::RcStr;
+use turbo_tasks::{
+    FxIndexMap, NonLocalValue, ResolvedVc, ValueToString, Vc, debug::ValueDebugFormat,
+    trace::TraceRawVcs,
+};
+use turbo_tasks_fs::{DirectoryEntry, FileSystemPath, ReadGlobResult, glob::Glob};
+use turbopack_core::{
+    chunk::{ChunkableModuleReference, ChunkingContext, ChunkingType, ChunkingTypeOption},
+    issue::IssueSource,
+    reference::ModuleReference,
+    reference_type::EcmaScriptModulesReferenceSubType,
+    resolve::{ModuleResolveResult, origin::ResolveOrigin, parse::Request},
+};
+use turbopack_resolve::ecmascript::esm_resolve;
+
+use crate::{
+    code_gen::{CodeGen, CodeGeneration},
+    create_visitor,
+    references::{
+        AstPath,
+        pattern_mapping::{PatternMapping, ResolveType, SinglePatternMapping},
+    },
+    runtime_functions::TURBOPACK_IMPORT,
+};
+
+/// Splits a relative glob pattern into the directory it is rooted in and the glob that is matched
+/// inside of that directory, e.g. `./pages/**/*.js` into `./pages` and `**/*.js`.
+fn split_glob_pattern(pattern: &str) -> (&str, &str) {
+    let first_meta = pattern
+        .find(['*', '?', '{', '}', '[', ']'])
+        .unwrap_or(pattern.len());
+    match pattern[..first_meta].rfind('/') {
+        Some(index) => (&pattern[..index], &pattern[index + 1..]),
+        None => (".", pattern),
+    }
+}
+
+/// Collects the files of a [ReadGlobResult], including the ones in nested directories.
+async fn collect_glob_files(
+    result: Vc<ReadGlobResult>,
+    files: &mut Vec<FileSystemPath>,
+) -> Result<()> {
+    let mut queue = vec![result.to_resolved().await?];
+    while let Some(result) = queue.pop() {
+        let result = result.await?;
+        for entry in result.results.values() {
+            if let DirectoryEntry::File(path) = entry {
+                files.push(path.clone());
+            }
+        }
+        queue.extend(result.inner.values().copied());
+    }
+    Ok(())
+}
+
+/// The requests for the files matched by an `import.meta.glob(..)` call, keyed by their path
+/// relative to the importing module.
+#[turbo_tasks::value(transparent)]
+pub struct ImportMetaGlobMap(FxIndexMap<RcStr, ResolvedVc<Request>>);
+
+#[turbo_tasks::value_impl]
+impl ImportMetaGlobMap {
+    /// Matches the `patterns` against the file system. Negated patterns (prefixed with `!`)
+    /// exclude files matched by the other patterns. The importing module itself is never
+    /// included.
+    #[turbo_tasks::function]
+    pub(crate) async fn generate(
+        origin_path: FileSystemPath,
+        patterns: Vec<RcStr>,
+        query: Option<RcStr>,
+    ) -> Result<Vc<Self>> {
+        let dir = origin_path.parent();
+
+        let mut files = Vec::new();
+        let mut negated = Vec::new();
+        for pattern in &patterns {
+            if let Some(pattern) = pattern.strip_prefix('!') {
+                negated.push(Glob::parse(pattern)?);
+                continue;
+            }
+            let (base, glob) = split_glob_pattern(pattern);
+            let read_glob = dir.join(base)?.read_glob(Glob::new(glob.into()));
+            collect_glob_files(read_glob, &mut files).await?;
+        }
+
+        let mut keys = files
+            .iter()
+            .filter(|path| **path != origin_path)
+            .filter_map(|path| dir.get_relative_path_to(path))
+            .filter(|key| !negated.iter().any(|glob| glob.matches(key)))
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys.dedup();
+
+        let mut map = FxIndexMap::default();
+        for key in keys {
+            let request: RcStr = match &query {
+                Some(query) => format!("{key}{query}").into(),
+                None => key.clone(),
+            };
+            let request = Request::parse(request.into()).to_resolved().await?;
+            map.insert(key, request);
+        }
+
+        Ok(Vc::cell(map))
+    }
+}
+
+/// A reference to a single module matched by `import.meta.glob(..)`.
+#[turbo_tasks::value]
+#[derive(Hash, Debug)]
+pub struct ImportMetaGlobAssetReference {
+    pub origin: ResolvedVc<Box<dyn ResolveOrigin>>,
+    pub request: ResolvedVc<Request>,
+    pub eager: bool,
+    pub issue_source: IssueSource,
+    pub in_try: bool,
+}
+
+impl ImportMetaGlobAssetReference {
+    pub fn new(
+        origin: ResolvedVc<Box<dyn ResolveOrigin>>,
+        request: ResolvedVc<Request>,
+        eager: bool,
+        issue_source: IssueSource,
+        in_try: bool,
+    ) -> Self {
+        ImportMetaGlobAssetReference {
+            origin,
+            request,
+            eager,
+            issue_source,
+            in_try,
+        }
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleReference for ImportMetaGlobAssetReference {
+    #[turbo_tasks::function]
+    async fn resolve_reference(&self) -> Result<Vc<ModuleResolveResult>> {
+        esm_resolve(
+            *self.origin,
+            *self.request,
+            if self.eager {
+                EcmaScriptModulesReferenceSubType::Import
+            } else {
+                EcmaScriptModulesReferenceSubType::DynamicImport
+            },
+            vec![],
+            self.in_try,
+            Some(self.issue_source),
+        )
+        .await
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for ImportMetaGlobAssetReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<RcStr>> {
+        Ok(Vc::cell(
+            format!("import.meta.glob {}", self.request.to_string().await?).into(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModuleReference for ImportMetaGlobAssetReference {
+    #[turbo_tasks::function]
+    fn chunking_type(&self) -> Vc<ChunkingTypeOption> {
+        Vc::cell(Some(if self.eager {
+            ChunkingType::Parallel {
+                inherit_async: false,
+                hoisted: false,
+            }
+        } else {
+            ChunkingType::Async(Default::default())
+        }))
+    }
+}
+
+/// Replaces an `import.meta.glob(..)` call with an object that maps the matched paths to the
+/// modules (eager) or to functions loading them (lazy).
+#[derive(PartialEq, Eq, Serialize, Deserialize, TraceRawVcs, ValueDebugFormat, NonLocalValue)]
+pub struct ImportMetaGlobCodeGen {
+    path: AstPath,
+    entries: Vec<(RcStr, ResolvedVc<ImportMetaGlobAssetReference>)>,
+    eager: bool,
+    import: Option<RcStr>,
+    import_externals: bool,
+}
+
+impl ImportMetaGlobCodeGen {
+    pub fn new(
+        path: AstPath,
+        entries: Vec<(RcStr, ResolvedVc<ImportMetaGlobAssetReference>)>,
+        eager: bool,
+        import: Option<RcStr>,
+        import_externals: bool,
+    ) -> Self {
+        ImportMetaGlobCodeGen {
+            path,
+            entries,
+            eager,
+            import,
+            import_externals,
+        }
+    }
+
+    pub async fn code_generation(
+        &self,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Result<CodeGeneration> {
+        let mut object = ObjectLit {
+            span: DUMMY_SP,
+            props: vec![],
+        };
+
+        for (key, reference) in &self.entries {
+            let reference_value = reference.await?;
+            let pm = PatternMapping::resolve_request(
+                *reference_value.request,
+                *reference_value.origin,
+                chunking_context,
+                reference.resolve_reference(),
+                if self.eager {
+                    ResolveType::ChunkItem
+                } else {
+                    ResolveType::AsyncChunkLoader(Default::default())
+                },
+            )
+            .await?;
+            let PatternMapping::Single(pm) = &*pm else {
+                continue;
+            };
+
+            let key_expr = Expr::Lit(Lit::Str(key.as_str().into()));
+            let value = if self.eager {
+                let module = match pm {
+                    SinglePatternMapping::Module(_) => quote!(
+                        "$turbopack_import($id)" as Expr,
+                        turbopack_import: Expr = TURBOPACK_IMPORT.into(),
+                        id: Expr = pm.create_id(Cow::Borrowed(&key_expr))
+                    ),
+                    _ => pm.create_require(Cow::Borrowed(&key_expr)),
+                };
+                match &self.import {
+                    Some(import) => quote_expr!(
+                        "$module[$import]",
+                        module: Expr = module,
+                        import: Expr = Expr::Lit(Lit::Str(import.as_str().into()))
+                    ),
+                    None => Box::new(module),
+                }
+            } else {
+                let promise = pm.create_import(Cow::Borrowed(&key_expr), self.import_externals);
+                match &self.import {
+                    Some(import) => quote_expr!(
+                        "() => $promise.then((m) => m[$import])",
+                        promise: Expr = promise,
+                        import: Expr = Expr::Lit(Lit::Str(import.as_str().into()))
+                    ),
+                    None => quote_expr!("() => $promise", promise: Expr = promise),
+                }
+            };
+
+            object
+                .props
+                .push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                    key: PropName::Str(key.as_str().into()),
+                    value,
+                }))));
+        }
+
+        let visitor = create_visitor!(self.path, visit_mut_expr, |expr: &mut Expr| {
+            if let Expr::Call(_) = expr {
+                *expr = Expr::Object(object.clone());
+            }
+        });
+
+        Ok(CodeGeneration::visitors(vec![visitor]))
+    }
+}
+
+impl From<ImportMetaGlobCodeGen> for CodeGen {
+    fn from(val: ImportMetaGlobCodeGen) -> Self {
+        CodeGen::ImportMetaGlobCodeGen(val)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_glob_pattern;
+
+    #[test]
+    fn test_split_glob_pattern() {
+        assert_eq!(
+            split_glob_pattern("./pages/**/*.js"),
+            ("./pages", "**/*.js")
+        );
+        assert_eq!(split_glob_pattern("./*.ts"), (".", "*.ts"));
+        assert_eq!(
+            split_glob_pattern("../dir/{a,b}.js"),
+            ("../dir", "{a,b}.js")
+        );
+        assert_eq!(split_glob_pattern("./dir/file.js"), ("./dir", "file.js"));
+    }
+}
@@ -7,6 +7,7 @@ pub mod dynamic_expression;
 pub mod esm;
 pub mod external_module;
 pub mod ident;
+pub mod import_meta_glob;
 pub mod member;
 pub mod node;
 pub mod pattern_mapping;
@@ -73,7 +74,7 @@ use turbopack_core::{
     issue::{IssueExt, IssueSeverity, IssueSource, StyledString, analyze::AnalyzeIssue},
     module::Module,
     reference::{ModuleReference, ModuleReferences, SourceMapReference},
-    reference_type::{CommonJsReferenceSubType, ReferenceType},
+    reference_type::{CommonJsReferenceSubType, ReferenceType, WorkerReferenceSubType},
     resolve::{
         FindContextFileResult, ModulePart, find_context_file,
         origin::{PlainResolveOrigin, ResolveOrigin, ResolveOriginExt},
@@ -90,7 +91,7 @@ use turbopack_resolve::{
 };
 use turbopack_swc_utils::emitter::IssueEmitter;
 use unreachable::Unreachable;
-use worker::WorkerAssetReference;
+use worker::{WorkerAssetReference, worker_expression_name};
 
 use self::{
     amd::{
@@ -133,8 +134,8 @@ use crate::{
         ConstantNumber, ConstantString, JsValueUrlKind, RequireContextValue,
         builtin::early_replace_builtin,
         graph::{ConditionalKind, EffectArg, EvalContext, VarGraph},
-        imports::{ImportAnnotations, ImportAttributes, ImportedSymbol, Reexport},
-        parse_require_context,
+        imports::{ImportAnnotations, ImportAttributes, ImportMap, ImportedSymbol, Reexport},
+        parse_import_meta_glob, parse_require_context,
         top_level_await::has_top_level_await,
     },
     chunk::EcmascriptExports,
@@ -150,6 +151,9 @@ use crate::{
             module_id::EsmModuleIdAssetReference,
         },
         ident::IdentReplacement,
+        import_meta_glob::{
+            ImportMetaGlobAssetReference, ImportMetaGlobCodeGen, ImportMetaGlobMap,
+        },
         member::MemberReplacement,
         node::PackageJsonReference,
         require_context::{RequireContextAssetReference, RequireContextMap},
@@ -441,6 +445,8 @@ struct AnalysisState<'a> {
     origin: ResolvedVc<Box<dyn ResolveOrigin>>,
     compile_time_info: ResolvedVc<CompileTimeInfo>,
     var_graph: &'a VarGraph,
+    /// The imports of the module, used to look up the magic comments of a call.
+    imports: &'a ImportMap,
     /// This is the current state of known values of function
     /// arguments.
     fun_args_values: Mutex<FxHashMap<u32, Vec<JsValue>>>,
@@ -1000,6 +1006,7 @@ pub(crate) async fn analyse_ecmascript_module_internal(
             origin,
             compile_time_info,
             var_graph: &var_graph,
+            imports: &eval_context.imports,
             fun_args_values: Default::default(),
             var_cache: Default::default(),
             first_import_meta: true,
@@ -1616,6 +1623,56 @@ async fn handle_call<G: Fn(Vec<Effect>) + Send + Sync>(
             .await
     };
 
+    let worker_type = match (&func, new) {
+        (JsValue::WellKnownFunction(WellKnownFunctionKind::WorkerConstructor), true) => {
+            Some(WorkerReferenceSubType::WebWorker)
+        }
+        (JsValue::WellKnownFunction(WellKnownFunctionKind::SharedWorkerConstructor), true) => {
+            Some(WorkerReferenceSubType::SharedWorker)
+        }
+        (JsValue::WellKnownFunction(WellKnownFunctionKind::ServiceWorkerRegister), false) => {
+            Some(WorkerReferenceSubType::ServiceWorker)
+        }
+        _ => None,
+    };
+    if let Some(worker_type) = worker_type {
+        let args = linked_args(args).await?;
+        if let Some(url @ JsValue::Url(_, JsValueUrlKind::Relative)) = args.first() {
+            let pat = js_value_to_pattern(url);
+            if !pat.has_constant_parts() {
+                let (args, hints) = explain_args(&args);
+                handler.span_warn_with_code(
+                    span,
+                    &format!(
+                        "{}({args}) is very dynamic{hints}",
+                        worker_expression_name(&worker_type)
+                    ),
+                    DiagnosticId::Lint(
+                        errors::failed_to_analyse::ecmascript::NEW_WORKER.to_string(),
+                    ),
+                );
+                if ignore_dynamic_requests {
+                    return Ok(());
+                }
+            }
+
+            if *compile_time_info.environment().rendering().await? == Rendering::Client {
+                analysis.add_reference_code_gen(
+                    WorkerAssetReference::new(
+                        origin,
+                        Request::parse(pat).to_resolved().await?,
+                        worker_type,
+                        issue_source(source, span),
+                        in_try,
+                    ),
+                    ast_path.to_vec().into(),
+                );
+            }
+        }
+        // Ignore (e.g. dynamic parameter or string literal), just as Webpack does
+        return Ok(());
+    }
+
     if new {
         match func {
             JsValue::WellKnownFunction(WellKnownFunctionKind::URLConstructor) => {
@@ -1659,41 +1716,6 @@ async fn handle_call<G: Fn(Vec<Effect>) + Send + Sync>(
                 }
                 return Ok(());
             }
-            JsValue::WellKnownFunction(WellKnownFunctionKind::WorkerConstructor) => {
-                let args = linked_args(args).await?;
-                if let Some(url @ JsValue::Url(_, JsValueUrlKind::Relative)) = args.first() {
-                    let pat = js_value_to_pattern(url);
-                    if !pat.has_constant_parts() {
-                        let (args, hints) = explain_args(&args);
-                        handler.span_warn_with_code(
-                            span,
-                            &format!("new Worker({args}) is very dynamic{hints}",),
-                            DiagnosticId::Lint(
-                                errors::failed_to_analyse::ecmascript::NEW_WORKER.to_string(),
-                            ),
-                        );
-                        if ignore_dynamic_requests {
-                            return Ok(());
-                        }
-                    }
-
-                    if *compile_time_info.environment().rendering().await? == Rendering::Client {
-                        analysis.add_reference_code_gen(
-                            WorkerAssetReference::new(
-                                origin,
-                                Request::parse(pat).to_resolved().await?,
-                                issue_source(source, span),
-                                in_try,
-                            ),
-                            ast_path.to_vec().into(),
-                        );
-                    }
-
-                    return Ok(());
-                }
-                // Ignore (e.g. dynamic parameter or string literal), just as Webpack does
-                return Ok(());
-            }
             _ => {}
         }
 
@@ -1773,6 +1795,7 @@ async fn handle_call<G: Fn(Vec<Effect>) + Send + Sync>(
                         Request::parse(pat).to_resolved().await?,
                         issue_source(source, span),
                         import_annotations,
+                        state.imports.get_attributes(span).clone(),
                         in_try,
                         state.import_externals,
                     ),
@@ -1915,6 +1938,54 @@ async fn handle_call<G: Fn(Vec<Effect>) + Send + Sync>(
             );
         }
 
+        JsValue::WellKnownFunction(WellKnownFunctionKind::ImportMetaGlob) => {
+            let args = linked_args(args).await?;
+            let options = match parse_import_meta_glob(&args) {
+                Ok(options) => options,
+                Err(err) => {
+                    let (args, hints) = explain_args(&args);
+                    handler.span_err_with_code(
+                        span,
+                        &format!(
+                            "import.meta.glob({args}) is not statically analyze-able: {}{hints}",
+                            PrettyPrintError(&err)
+                        ),
+                        DiagnosticId::Error(
+                            errors::failed_to_analyse::ecmascript::IMPORT_META_GLOB.to_string(),
+                        ),
+                    );
+                    return Ok(());
+                }
+            };
+
+            let map = ImportMetaGlobMap::generate(
+                origin.origin_path().owned().await?,
+                options.patterns,
+                options.query,
+            )
+            .await?;
+            let mut entries = Vec::with_capacity(map.len());
+            for (key, request) in map.iter() {
+                let reference = ImportMetaGlobAssetReference::new(
+                    origin,
+                    *request,
+                    options.eager,
+                    issue_source(source, span),
+                    in_try,
+                )
+                .resolved_cell();
+                analysis.add_reference(reference);
+                entries.push((key.clone(), reference));
+            }
+            analysis.add_code_gen(ImportMetaGlobCodeGen::new(
+                ast_path.to_vec().into(),
+                entries,
+                options.eager,
+                options.import,
+                state.import_externals,
+            ));
+        }
+
         JsValue::WellKnownFunction(WellKnownFunctionKind::FsReadMethod(name)) => {
             let args = linked_args(args).await?;
             if !args.is_empty() {
@@ -3004,9 +3075,16 @@ async fn value_visitor_inner(
                 true,
                 "ignored Worker constructor",
             ),
+            "SharedWorker" => JsValue::unknown_if(
+                ignore,
+                JsValue::WellKnownFunction(WellKnownFunctionKind::SharedWorkerConstructor),
+                true,
+                "ignored SharedWorker constructor",
+            ),
             "define" => JsValue::WellKnownFunction(WellKnownFunctionKind::Define),
             "URL" => JsValue::WellKnownFunction(WellKnownFunctionKind::URLConstructor),
             "process" => JsValue::WellKnownObject(WellKnownObjectKind::NodeProcess),
+            "navigator" => JsValue::WellKnownObject(WellKnownObjectKind::Navigator),
             "Object" => JsValue::WellKnownObject(WellKnownObjectKind::GlobalObject),
             "Buffer" => JsValue::WellKnownObject(WellKnownObjectKind::NodeBuffer),
             _ => return Ok((v, false)),
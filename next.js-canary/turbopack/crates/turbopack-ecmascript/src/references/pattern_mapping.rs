@@ -16,7 +16,7 @@ use turbo_tasks::{
     trace::TraceRawVcs,
 };
 use turbopack_core::{
-    chunk::{ChunkableModule, ChunkingContext, ModuleChunkItemIdExt, ModuleId},
+    chunk::{AsyncChunkOptions, ChunkableModule, ChunkingContext, ModuleChunkItemIdExt, ModuleId},
     issue::{
         IssueExt, IssueSeverity, StyledString, code_gen::CodeGenerationIssue,
         module::emit_unknown_module_type_error,
@@ -87,20 +87,11 @@ pub(crate) enum PatternMapping {
 }
 
 #[derive(
-    Copy,
-    Clone,
-    Debug,
-    Eq,
-    PartialEq,
-    Hash,
-    Serialize,
-    Deserialize,
-    TraceRawVcs,
-    TaskInput,
-    NonLocalValue,
+    Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize, TraceRawVcs, TaskInput, NonLocalValue,
 )]
 pub(crate) enum ResolveType {
-    AsyncChunkLoader,
+    /// The module is loaded through an async loader for a chunk group with the given options.
+    AsyncChunkLoader(AsyncChunkOptions),
     ChunkItem,
 }
 
@@ -362,8 +353,8 @@ async fn to_single_pattern_mapping(
     };
     if let Some(chunkable) = ResolvedVc::try_downcast::<Box<dyn ChunkableModule>>(module) {
         match resolve_type {
-            ResolveType::AsyncChunkLoader => {
-                let loader_id = chunking_context.async_loader_chunk_item_id(*chunkable);
+            ResolveType::AsyncChunkLoader(options) => {
+                let loader_id = chunking_context.async_loader_chunk_item_id(*chunkable, options);
                 return Ok(SinglePatternMapping::ModuleLoader(loader_id.owned().await?));
             }
             ResolveType::ChunkItem => {
@@ -421,11 +412,18 @@ impl PatternMapping {
                         let request = k.request.as_ref()?;
                         set.insert(request).then(|| (request.to_string(), v))
                     })
-                    .map(|(k, v)| async move {
-                        let single_pattern_mapping =
-                            to_single_pattern_mapping(origin, chunking_context, v, resolve_type)
-                                .await?;
-                        Ok((k, single_pattern_mapping))
+                    .map(|(k, v)| {
+                        let resolve_type = resolve_type.clone();
+                        async move {
+                            let single_pattern_mapping = to_single_pattern_mapping(
+                                origin,
+                                chunking_context,
+                                v,
+                                resolve_type,
+                            )
+                            .await?;
+                            Ok((k, single_pattern_mapping))
+                        }
                     })
                     .try_join()
                     .await?
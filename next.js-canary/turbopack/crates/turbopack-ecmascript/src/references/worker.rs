@@ -2,7 +2,7 @@ use anyhow::{Result, bail};
 use serde::{Deserialize, Serialize};
 use swc_core::{
     common::util::take::Take,
-    ecma::ast::{Expr, ExprOrSpread, Lit, NewExpr},
+    ecma::ast::{CallExpr, Expr, ExprOrSpread, Lit, NewExpr},
     quote_expr,
 };
 use turbo_rcstr::{RcStr, rcstr};
@@ -27,11 +27,23 @@ use crate::{
     worker_chunk::module::WorkerLoaderModule,
 };
 
+/// The expression that creates a worker of the given type, used in messages.
+pub fn worker_expression_name(worker_type: &WorkerReferenceSubType) -> &'static str {
+    match worker_type {
+        WorkerReferenceSubType::SharedWorker => "new SharedWorker",
+        WorkerReferenceSubType::ServiceWorker => "navigator.serviceWorker.register",
+        _ => "new Worker",
+    }
+}
+
+/// A reference to the entrypoint of a worker, as in `new Worker(new URL(...))`,
+/// `new SharedWorker(new URL(...))` or `navigator.serviceWorker.register(new URL(...))`.
 #[turbo_tasks::value]
 #[derive(Hash, Debug)]
 pub struct WorkerAssetReference {
     pub origin: ResolvedVc<Box<dyn ResolveOrigin>>,
     pub request: ResolvedVc<Request>,
+    pub worker_type: WorkerReferenceSubType,
     pub issue_source: IssueSource,
     pub in_try: bool,
 }
@@ -40,12 +52,14 @@ impl WorkerAssetReference {
     pub fn new(
         origin: ResolvedVc<Box<dyn ResolveOrigin>>,
         request: ResolvedVc<Request>,
+        worker_type: WorkerReferenceSubType,
         issue_source: IssueSource,
         in_try: bool,
     ) -> Self {
         WorkerAssetReference {
             origin,
             request,
+            worker_type,
             issue_source,
             in_try,
         }
@@ -59,8 +73,7 @@ impl WorkerAssetReference {
         let module = url_resolve(
             *self.origin,
             *self.request,
-            // TODO support more worker types
-            ReferenceType::Worker(WorkerReferenceSubType::WebWorker),
+            ReferenceType::Worker(self.worker_type.clone()),
             Some(self.issue_source),
             self.in_try,
         );
@@ -81,7 +94,10 @@ impl WorkerAssetReference {
             return Ok(None);
         };
 
-        Ok(Some(WorkerLoaderModule::new(*chunkable)))
+        Ok(Some(WorkerLoaderModule::new(
+            *chunkable,
+            self.worker_type.clone(),
+        )))
     }
 }
 
@@ -104,7 +120,12 @@ impl ValueToString for WorkerAssetReference {
     #[turbo_tasks::function]
     async fn to_string(&self) -> Result<Vc<RcStr>> {
         Ok(Vc::cell(
-            format!("new Worker {}", self.request.to_string().await?,).into(),
+            format!(
+                "{} {}",
+                worker_expression_name(&self.worker_type),
+                self.request.to_string().await?,
+            )
+            .into(),
         ))
     }
 }
@@ -136,7 +157,8 @@ impl WorkerAssetReferenceCodeGen {
         &self,
         chunking_context: Vc<Box<dyn ChunkingContext>>,
     ) -> Result<CodeGeneration> {
-        let Some(loader) = self.reference.await?.worker_loader_module().await? else {
+        let reference = self.reference.await?;
+        let Some(loader) = reference.worker_loader_module().await? else {
             bail!("Worker loader could not be created");
         };
 
@@ -144,47 +166,69 @@ impl WorkerAssetReferenceCodeGen {
             .chunk_item_id_from_ident(loader.ident())
             .await?;
 
+        let name = worker_expression_name(&reference.worker_type);
+        let is_shared_worker = reference.worker_type == WorkerReferenceSubType::SharedWorker;
         let visitor = create_visitor!(self.path, visit_mut_expr, |expr: &mut Expr| {
-            let message = if let Expr::New(NewExpr { args, .. }) = expr {
-                if let Some(args) = args {
-                    match args.first_mut() {
-                        Some(ExprOrSpread { spread: None, expr }) => {
-                            let item_id = module_id_to_lit(&item_id);
-                            *expr = quote_expr!(
-                                "$turbopack_require($item_id)",
-                                turbopack_require: Expr = TURBOPACK_REQUIRE.into(),
-                                item_id: Expr = item_id
-                            );
-
-                            if let Some(opts) = args.get_mut(1)
-                                && opts.spread.is_none()
-                            {
-                                *opts.expr = *quote_expr!(
+            let args = match expr {
+                Expr::New(NewExpr { args, .. }) => args.as_mut(),
+                Expr::Call(CallExpr { args, .. }) => Some(args),
+                _ => {
+                    *expr = throw_error("visitor must be executed on a NewExpr or a CallExpr");
+                    return;
+                }
+            };
+            let message = if let Some(args) = args {
+                match args.first_mut() {
+                    Some(ExprOrSpread { spread: None, expr }) => {
+                        let item_id = module_id_to_lit(&item_id);
+                        *expr = quote_expr!(
+                            "$turbopack_require($item_id)",
+                            turbopack_require: Expr = TURBOPACK_REQUIRE.into(),
+                            item_id: Expr = item_id
+                        );
+
+                        // The worker chunks are loaded with `importScripts()`, so it must be
+                        // started as a classic worker
+                        if let Some(opts) = args.get_mut(1)
+                            && opts.spread.is_none()
+                        {
+                            *opts.expr = if is_shared_worker {
+                                // The second argument of the SharedWorker constructor can also
+                                // be its name
+                                *quote_expr!(
+                                    "((o) => typeof o === \"string\" ? o : {...o, type: \
+                                     undefined})($opts)",
+                                    opts: Expr = (*opts.expr).take()
+                                )
+                            } else {
+                                *quote_expr!(
                                     "{...$opts, type: undefined}",
                                     opts: Expr = (*opts.expr).take()
-                                );
-                            }
-                            return;
+                                )
+                            };
                         }
-                        // These are SWC bugs: https://github.com/swc-project/swc/issues/5394
-                        Some(ExprOrSpread {
-                            spread: Some(_),
-                            expr: _,
-                        }) => "spread operator is illegal in new Worker() expressions.",
-                        _ => "new Worker() expressions require at least 1 argument",
+                        return;
                     }
-                } else {
-                    "new Worker() expressions require at least 1 argument"
+                    // These are SWC bugs: https://github.com/swc-project/swc/issues/5394
+                    Some(ExprOrSpread {
+                        spread: Some(_),
+                        expr: _,
+                    }) => format!("spread operator is illegal in {name}() expressions."),
+                    _ => format!("{name}() expressions require at least 1 argument"),
                 }
             } else {
-                "visitor must be executed on a NewExpr"
+                format!("{name}() expressions require at least 1 argument")
             };
-            *expr = *quote_expr!(
-                "(() => { throw new Error($message); })()",
-                message: Expr = Expr::Lit(Lit::Str(message.into()))
-            );
+            *expr = throw_error(&message);
         });
 
         Ok(CodeGeneration::visitors(vec![visitor]))
     }
 }
+
+fn throw_error(message: &str) -> Expr {
+    *quote_expr!(
+        "(() => { throw new Error($message); })()",
+        message: Expr = Expr::Lit(Lit::Str(message.into()))
+    )
+}
@@ -183,7 +183,9 @@ impl EcmascriptModulePartReference {
                     chunking_context,
                     match &*export_usage {
                         ExportUsage::Named(export) => Some(export.clone()),
-                        ExportUsage::All | ExportUsage::Evaluation => None,
+                        ExportUsage::All
+                        | ExportUsage::Evaluation
+                        | ExportUsage::PartialNamespaceObject(_) => None,
                     },
                     scope_hoisting_context,
                 )
@@ -1,16 +1,23 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use indoc::formatdoc;
-use turbo_rcstr::rcstr;
-use turbo_tasks::{ResolvedVc, TryJoinIterExt, Vc};
+use rustc_hash::FxHashMap;
+use turbo_rcstr::{RcStr, rcstr};
+use turbo_tasks::{FxIndexSet, ResolvedVc, TryJoinIterExt, Vc};
+use turbo_tasks_fs::{File, FileContent, FileSystemPath};
 use turbopack_core::{
+    asset::AssetContent,
     chunk::{
         ChunkData, ChunkItem, ChunkType, ChunkingContext, ChunkingContextExt, ChunksData,
         availability_info::AvailabilityInfo,
     },
     ident::AssetIdent,
+    issue::{IssueExt, IssueSeverity, StyledString, code_gen::CodeGenerationIssue},
     module::Module,
     module_graph::{ModuleGraph, chunk_group_info::ChunkGroup},
-    output::OutputAssets,
+    output::{OutputAsset, OutputAssets},
+    reference::all_assets_from_entries,
+    reference_type::WorkerReferenceSubType,
+    virtual_output::VirtualOutputAsset,
 };
 
 use super::module::WorkerLoaderModule;
@@ -19,10 +26,47 @@ use crate::{
         EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkType,
         data::EcmascriptChunkData,
     },
-    runtime_functions::{TURBOPACK_EXPORT_VALUE, TURBOPACK_WORKER_BLOB_URL},
+    runtime_functions::{
+        TURBOPACK_EXPORT_VALUE, TURBOPACK_WORKER_BLOB_URL, TURBOPACK_WORKER_SCRIPT_URL,
+    },
     utils::StringifyJs,
 };
 
+/// The file name of the bootstrap script of a service worker.
+fn service_worker_file_name(source_path: &FileSystemPath) -> RcStr {
+    format!("{}.js", source_path.file_stem().unwrap_or("service-worker")).into()
+}
+
+/// The source paths of the service workers in a module graph, keyed by the file names of their
+/// bootstrap scripts.
+#[turbo_tasks::value(transparent)]
+struct ServiceWorkerFileNames(FxHashMap<RcStr, FxIndexSet<FileSystemPath>>);
+
+#[turbo_tasks::function]
+async fn service_worker_file_names(
+    module_graph: Vc<ModuleGraph>,
+) -> Result<Vc<ServiceWorkerFileNames>> {
+    let mut file_names = FxHashMap::<RcStr, FxIndexSet<FileSystemPath>>::default();
+    for graph in module_graph.await?.get_graphs().await? {
+        for node in graph.iter_nodes() {
+            let Some(module) = ResolvedVc::try_downcast_type::<WorkerLoaderModule>(node.module)
+            else {
+                continue;
+            };
+            let module = module.await?;
+            if module.worker_type != WorkerReferenceSubType::ServiceWorker {
+                continue;
+            }
+            let source_path = module.inner.ident().path().owned().await?;
+            file_names
+                .entry(service_worker_file_name(&source_path))
+                .or_default()
+                .insert(source_path);
+        }
+    }
+    Ok(Vc::cell(file_names))
+}
+
 #[turbo_tasks::value(shared)]
 pub struct WorkerLoaderChunkItem {
     pub module: ResolvedVc<WorkerLoaderModule>,
@@ -35,9 +79,14 @@ impl WorkerLoaderChunkItem {
     #[turbo_tasks::function]
     async fn chunks(&self) -> Result<Vc<OutputAssets>> {
         let module = self.module.await?;
+        let modifier = match module.worker_type {
+            WorkerReferenceSubType::SharedWorker => rcstr!("shared worker"),
+            WorkerReferenceSubType::ServiceWorker => rcstr!("service worker"),
+            _ => rcstr!("worker"),
+        };
 
         Ok(self.chunking_context.evaluated_chunk_group_assets(
-            module.inner.ident().with_modifier(rcstr!("worker")),
+            module.inner.ident().with_modifier(modifier),
             ChunkGroup::Isolated(ResolvedVc::upcast(module.inner)),
             *self.module_graph,
             AvailabilityInfo::Root,
@@ -52,24 +101,157 @@ impl WorkerLoaderChunkItem {
             self.chunks(),
         ))
     }
+
+    /// The bootstrap script of a shared or service worker. It loads the chunks of the worker with
+    /// `importScripts()`, in the same way as the blob that is created for web workers.
+    #[turbo_tasks::function]
+    async fn bootstrap(self: Vc<Self>) -> Result<Vc<Box<dyn OutputAsset>>> {
+        let this = self.await?;
+        let module = this.module.await?;
+        let chunking_context = *this.chunking_context;
+
+        let hashed_path = || {
+            let modifier = if module.worker_type == WorkerReferenceSubType::ServiceWorker {
+                rcstr!("service worker")
+            } else {
+                rcstr!("shared worker")
+            };
+            chunking_context
+                .chunk_path(
+                    None,
+                    module.inner.ident().with_modifier(modifier),
+                    None,
+                    rcstr!(".js"),
+                )
+                .owned()
+        };
+        let path = if module.worker_type == WorkerReferenceSubType::ServiceWorker {
+            // The browser checks for service worker updates by fetching the script again, so its
+            // path must not change between builds.
+            let source_path = module.inner.ident().path().owned().await?;
+            let file_name = service_worker_file_name(&source_path);
+            let file_names = service_worker_file_names(*this.module_graph).await?;
+            let conflicts = file_names
+                .get(&file_name)
+                .is_some_and(|source_paths| source_paths.len() > 1);
+            if conflicts {
+                CodeGenerationIssue {
+                    severity: IssueSeverity::Error,
+                    title: StyledString::Text(rcstr!("Conflicting service worker file names"))
+                        .resolved_cell(),
+                    message: StyledString::Text(
+                        format!(
+                            "Multiple service workers would be emitted as {file_name}. Rename one \
+                             of them, the conflicting service workers are emitted with a hashed \
+                             file name that changes between builds."
+                        )
+                        .into(),
+                    )
+                    .resolved_cell(),
+                    path: source_path,
+                }
+                .resolved_cell()
+                .emit();
+                hashed_path().await?
+            } else {
+                chunking_context
+                    .service_worker_root_path()
+                    .await?
+                    .join(&file_name)?
+            }
+        } else {
+            hashed_path().await?
+        };
+        let dir = path.parent();
+
+        let chunks = self.chunks();
+        let mut chunk_paths = Vec::new();
+        for chunk in chunks.await?.iter() {
+            let chunk_path = chunk.path().await?;
+            if chunk_path.has_extension(".js") {
+                chunk_paths.push(
+                    dir.get_relative_path_to(&chunk_path)
+                        .context("worker chunks must be in the output file system")?,
+                );
+            }
+        }
+
+        let precache_manifest = if module.worker_type == WorkerReferenceSubType::ServiceWorker
+            && *chunking_context
+                .is_service_worker_precache_manifest_enabled()
+                .await?
+        {
+            let mut asset_paths = Vec::new();
+            for asset in all_assets_from_entries(chunks).await?.iter() {
+                let asset_path = asset.path().await?;
+                if !asset_path.has_extension(".map") {
+                    asset_paths.push(
+                        dir.get_relative_path_to(&asset_path)
+                            .context("worker assets must be in the output file system")?,
+                    );
+                }
+            }
+            formatdoc! {
+                r#"
+                    self.__TURBOPACK_PRECACHE_MANIFEST = {assets:#}.map((asset) => new URL(asset, self.location.href).href);
+                "#,
+                assets = StringifyJs(&asset_paths),
+            }
+        } else {
+            String::new()
+        };
+
+        // Chunk URLs are relative to the origin, like the ones of the blob in `getWorkerBlobURL`.
+        // The bootstrap script is requested with the chunk suffix, it's added to the chunk URLs.
+        let code = formatdoc! {
+            r#"
+                self.TURBOPACK_WORKER_LOCATION = self.location.origin;
+                self.TURBOPACK_NEXT_CHUNK_URLS = {chunks:#}.map((chunk) => new URL(chunk, self.location.href).pathname + self.location.search).reverse();
+                {precache_manifest}importScripts(...self.TURBOPACK_NEXT_CHUNK_URLS.map((c) => self.TURBOPACK_WORKER_LOCATION + c).reverse());
+            "#,
+            chunks = StringifyJs(&chunk_paths),
+        };
+
+        Ok(Vc::upcast(VirtualOutputAsset::new_with_references(
+            path,
+            AssetContent::file(FileContent::Content(File::from(code)).cell()),
+            chunks,
+        )))
+    }
 }
 
 #[turbo_tasks::value_impl]
 impl EcmascriptChunkItem for WorkerLoaderChunkItem {
     #[turbo_tasks::function]
     async fn content(self: Vc<Self>) -> Result<Vc<EcmascriptChunkItemContent>> {
-        let chunks_data = self.chunks_data().await?;
-        let chunks_data = chunks_data.iter().try_join().await?;
-        let chunks_data: Vec<_> = chunks_data
-            .iter()
-            .map(|chunk_data| EcmascriptChunkData::new(chunk_data))
-            .collect();
+        let this = self.await?;
+        let code = if this.module.await?.worker_type == WorkerReferenceSubType::WebWorker {
+            let chunks_data = self.chunks_data().await?;
+            let chunks_data = chunks_data.iter().try_join().await?;
+            let chunks_data: Vec<_> = chunks_data
+                .iter()
+                .map(|chunk_data| EcmascriptChunkData::new(chunk_data))
+                .collect();
 
-        let code = formatdoc! {
-            r#"
-                {TURBOPACK_EXPORT_VALUE}({TURBOPACK_WORKER_BLOB_URL}({chunks:#}));
-            "#,
-            chunks = StringifyJs(&chunks_data),
+            formatdoc! {
+                r#"
+                    {TURBOPACK_EXPORT_VALUE}({TURBOPACK_WORKER_BLOB_URL}({chunks:#}));
+                "#,
+                chunks = StringifyJs(&chunks_data),
+            }
+        } else {
+            let output_root = this.chunking_context.output_root().await?;
+            let bootstrap_path = self.bootstrap().path().await?;
+            let bootstrap_path = output_root
+                .get_path_to(&bootstrap_path)
+                .context("worker bootstrap script must be in the output root")?;
+
+            formatdoc! {
+                r#"
+                    {TURBOPACK_EXPORT_VALUE}({TURBOPACK_WORKER_SCRIPT_URL}({path}));
+                "#,
+                path = StringifyJs(bootstrap_path),
+            }
         };
 
         Ok(EcmascriptChunkItemContent {
@@ -93,8 +275,12 @@ impl ChunkItem for WorkerLoaderChunkItem {
     }
 
     #[turbo_tasks::function]
-    fn references(self: Vc<Self>) -> Vc<OutputAssets> {
-        self.chunks()
+    async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        if self.await?.module.await?.worker_type == WorkerReferenceSubType::WebWorker {
+            Ok(self.chunks())
+        } else {
+            Ok(Vc::cell(vec![self.bootstrap().to_resolved().await?]))
+        }
     }
 
     #[turbo_tasks::function]
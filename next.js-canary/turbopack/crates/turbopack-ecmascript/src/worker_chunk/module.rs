@@ -11,28 +11,46 @@ use turbopack_core::{
     module::Module,
     module_graph::ModuleGraph,
     reference::{ModuleReference, ModuleReferences},
+    reference_type::WorkerReferenceSubType,
     resolve::ModuleResolveResult,
 };
 
 use super::chunk_item::WorkerLoaderChunkItem;
 
 /// The WorkerLoaderModule is a module that creates a separate root chunk group for the given module
-/// and exports a URL to pass to the worker constructor.
+/// and exports a URL to pass to the worker constructor or to `navigator.serviceWorker.register()`.
+/// Web workers are started from a blob URL, shared and service workers from a bootstrap script
+/// with a URL that is the same for all documents.
 #[turbo_tasks::value]
 pub struct WorkerLoaderModule {
     pub inner: ResolvedVc<Box<dyn ChunkableModule>>,
+    pub worker_type: WorkerReferenceSubType,
 }
 
 #[turbo_tasks::value_impl]
 impl WorkerLoaderModule {
     #[turbo_tasks::function]
-    pub fn new(module: ResolvedVc<Box<dyn ChunkableModule>>) -> Vc<Self> {
-        Self::cell(WorkerLoaderModule { inner: module })
+    pub fn new(
+        module: ResolvedVc<Box<dyn ChunkableModule>>,
+        worker_type: WorkerReferenceSubType,
+    ) -> Vc<Self> {
+        Self::cell(WorkerLoaderModule {
+            inner: module,
+            worker_type,
+        })
     }
 
     #[turbo_tasks::function]
-    pub fn asset_ident_for(module: Vc<Box<dyn ChunkableModule>>) -> Vc<AssetIdent> {
-        module.ident().with_modifier(rcstr!("worker loader"))
+    pub fn asset_ident_for(
+        module: Vc<Box<dyn ChunkableModule>>,
+        worker_type: WorkerReferenceSubType,
+    ) -> Vc<AssetIdent> {
+        let modifier = match worker_type {
+            WorkerReferenceSubType::SharedWorker => rcstr!("shared worker loader"),
+            WorkerReferenceSubType::ServiceWorker => rcstr!("service worker loader"),
+            _ => rcstr!("worker loader"),
+        };
+        module.ident().with_modifier(modifier)
     }
 }
 
@@ -40,7 +58,7 @@ impl WorkerLoaderModule {
 impl Module for WorkerLoaderModule {
     #[turbo_tasks::function]
     fn ident(&self) -> Vc<AssetIdent> {
-        Self::asset_ident_for(*self.inner)
+        Self::asset_ident_for(*self.inner, self.worker_type.clone())
     }
 
     #[turbo_tasks::function]
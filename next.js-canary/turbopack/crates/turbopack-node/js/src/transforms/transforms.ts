@@ -30,12 +30,18 @@ export type IpcInfoMessage =
       }>
     }
 
-export type IpcRequestMessage = {
-  type: 'resolve'
-  options: any
-  lookupPath: string
-  request: string
-}
+export type IpcRequestMessage =
+  | {
+      type: 'resolve'
+      options: any
+      lookupPath: string
+      request: string
+    }
+  | {
+      type: 'importModule' | 'loadModule'
+      lookupPath: string
+      request: string
+    }
 
 export type TransformIpc = Ipc<IpcInfoMessage, IpcRequestMessage>
 
@@ -3,6 +3,7 @@ declare const __turbopack_external_require__: {
 } & ((id: string, thunk: () => any, esm?: boolean) => any)
 
 import type { Ipc } from '../ipc/evaluate'
+import { createRequire } from 'module'
 import { dirname, resolve as pathResolve } from 'path'
 import {
   StackFrame,
@@ -39,12 +40,18 @@ export type IpcInfoMessage =
       }>
     }
 
-export type IpcRequestMessage = {
-  type: 'resolve'
-  options: any
-  lookupPath: string
-  request: string
-}
+export type IpcRequestMessage =
+  | {
+      type: 'resolve'
+      options: any
+      lookupPath: string
+      request: string
+    }
+  | {
+      type: 'importModule' | 'loadModule'
+      lookupPath: string
+      request: string
+    }
 
 type LoaderConfig =
   | string
@@ -59,6 +66,9 @@ const {
 
 const contextDir = process.cwd()
 
+// Used to evaluate the entries emitted for `this.importModule`
+const requireEmitted = createRequire(pathResolve(contextDir, 'noop.js'))
+
 const LogType = Object.freeze({
   error: 'error',
   warn: 'warn',
@@ -139,7 +149,7 @@ type ResolveOptions = {
   useSyncFileSystemCalls?: boolean
   preferRelative?: boolean
   preferAbsolute?: boolean
-  restrictions?: unknown[]
+  restrictions?: Array<string | RegExp>
   roots?: string[]
   importFields?: string[]
 }
@@ -167,6 +177,13 @@ const transform = (
       trace: StackFrame[] | undefined
     }> = []
 
+    // Files emitted with `this.emitFile`, they are referenced by the transformed module
+    const assets: Array<{
+      file: string
+      content: string | { binary: string }
+      sourceMap: string | undefined
+    }> = []
+
     runLoaders(
       {
         resource: resource + query,
@@ -196,6 +213,13 @@ const transform = (
               mainFiles: undefined as undefined | string[],
               noModules: false,
               preferRelative: false,
+              dependencyType: undefined as undefined | string,
+              restrictions: undefined as
+                | undefined
+                | Array<
+                    | { type: 'path'; path: string }
+                    | { type: 'regex'; source: string; flags: string }
+                  >,
             }
             if (options.alias) {
               if (!Array.isArray(options.alias) || options.alias.length > 0) {
@@ -264,10 +288,34 @@ const transform = (
               rustOptions.noModules = true
             }
             if (options.restrictions) {
-              // TODO This is ignored for now
+              if (!Array.isArray(options.restrictions)) {
+                throw new Error('restrictions resolve option must be an array')
+              }
+              rustOptions.restrictions = options.restrictions.map(
+                (restriction) => {
+                  if (typeof restriction === 'string') {
+                    return { type: 'path', path: restriction }
+                  } else if (restriction instanceof RegExp) {
+                    return {
+                      type: 'regex',
+                      source: restriction.source,
+                      flags: restriction.flags,
+                    }
+                  } else {
+                    throw new Error(
+                      'restrictions resolve option must contain strings or regular expressions'
+                    )
+                  }
+                }
+              )
             }
             if (options.dependencyType) {
-              // TODO This is ignored for now
+              if (typeof options.dependencyType !== 'string') {
+                throw new Error(
+                  'dependencyType resolve option must be a string'
+                )
+              }
+              rustOptions.dependencyType = options.dependencyType
             }
             if (options.preferRelative) {
               if (typeof options.preferRelative !== 'boolean') {
@@ -313,6 +361,106 @@ const transform = (
               }
             }
           },
+          importModule(
+            request: string,
+            options?: unknown,
+            callback?: (err?: Error, exports?: unknown) => void
+          ) {
+            if (typeof options === 'function') {
+              callback = options as typeof callback
+            }
+            const promise = ipc
+              .sendRequest({
+                type: 'importModule',
+                lookupPath: toPath(this.context),
+                request,
+              })
+              .then((unknownResult) => {
+                let result = unknownResult as { path: string; chunks: string[] }
+                if (
+                  !result ||
+                  typeof result.path !== 'string' ||
+                  !Array.isArray(result.chunks)
+                ) {
+                  throw Error(
+                    'Expected { path: string, chunks: string[] } from importModule request'
+                  )
+                }
+                // The entry and the chunks it loads are rewritten when the module changes, so
+                // they must not be cached
+                for (const chunk of result.chunks) {
+                  delete requireEmitted.cache[chunk]
+                }
+                delete requireEmitted.cache[result.path]
+                return requireEmitted(result.path)
+              })
+            if (callback) {
+              promise
+                .then(
+                  (exports) => callback(undefined, exports),
+                  (err) => callback(err)
+                )
+                .catch((err) => {
+                  ipc.sendError(err)
+                })
+            } else {
+              return promise
+            }
+          },
+          loadModule(
+            request: string,
+            callback: (
+              err?: Error | null,
+              source?: string | Buffer,
+              sourceMap?: unknown,
+              module?: unknown
+            ) => void
+          ) {
+            ipc
+              .sendRequest({
+                type: 'loadModule',
+                lookupPath: toPath(this.context),
+                request,
+              })
+              .then((unknownResult) => {
+                let result = unknownResult as {
+                  source: string | { binary: string }
+                }
+                if (!result || result.source == null) {
+                  throw Error(
+                    'Expected { source: string | { binary: string } } from loadModule request'
+                  )
+                }
+                return typeof result.source === 'string'
+                  ? result.source
+                  : Buffer.from(result.source.binary, 'base64')
+              })
+              .then(
+                (source) => callback(null, source, null, {}),
+                (err) => callback(err)
+              )
+              .catch((err) => {
+                ipc.sendError(err)
+              })
+          },
+          emitFile(
+            name: string,
+            content: string | Buffer,
+            sourceMap?: string | object
+          ) {
+            assets.push({
+              file: name,
+              content: Buffer.isBuffer(content)
+                ? { binary: content.toString('base64') }
+                : content,
+              sourceMap:
+                typeof sourceMap === 'string'
+                  ? sourceMap
+                  : typeof sourceMap === 'object'
+                    ? JSON.stringify(sourceMap)
+                    : undefined,
+            })
+          },
           emitWarning: makeErrorEmitter('warning', ipc),
           emitError: makeErrorEmitter('error', ipc),
           getLogger(name: unknown) {
@@ -468,6 +616,7 @@ const transform = (
               : typeof map === 'object'
                 ? JSON.stringify(map)
                 : undefined,
+          assets,
         })
       }
     )
@@ -11,7 +11,7 @@ use futures_retry::{FutureRetry, RetryPolicy};
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use serde_json::Value as JsonValue;
-use turbo_rcstr::rcstr;
+use turbo_rcstr::{RcStr, rcstr};
 use turbo_tasks::{
     Completion, FxIndexMap, NonLocalValue, OperationVc, RawVc, ResolvedVc, TaskInput,
     TryJoinIterExt, Vc, VcValueType, apply_effects, duration_span, fxindexmap, mark_finished,
@@ -242,6 +242,7 @@ pub async fn get_evaluate_pool(
     additional_invalidation: ResolvedVc<Completion>,
     debug: bool,
     env_var_tracking: EnvVarTracking,
+    warm_up: usize,
 ) -> Result<Vc<NodeJsPool>> {
     let EmittedEvaluatePoolAssets {
         bootstrap,
@@ -291,6 +292,7 @@ pub async fn get_evaluate_pool(
         available_parallelism().map_or(1, |v| v.get()),
         debug,
     );
+    pool.warm_up(warm_up);
     additional_invalidation.await?;
     Ok(pool.cell())
 }
@@ -341,6 +343,11 @@ pub trait EvaluateContext {
     fn compute(self, sender: Vc<JavaScriptStreamSender>)
     -> impl Future<Output = Result<()>> + Send;
     fn pool(&self) -> OperationVc<NodeJsPool>;
+    /// Operations with the same key prefer to run in the same process, see
+    /// [NodeJsPool::operation_with_affinity].
+    fn pool_affinity(&self) -> Option<RcStr> {
+        None
+    }
     fn keep_alive(&self) -> bool {
         false
     }
@@ -474,6 +481,7 @@ pub async fn compute(
         // Assume this is a one-off operation, so we can kill the process
         // TODO use a better way to decide that.
         let kill = !evaluate_context.keep_alive();
+        let affinity_key = evaluate_context.pool_affinity();
 
         // Workers in the pool could be in a bad state that we didn't detect yet.
         // The bad state might even be unnoticeable until we actually send the job to the
@@ -482,7 +490,7 @@ pub async fn compute(
 
         let (mut operation, _) = FutureRetry::new(
             || async {
-                let mut operation = pool.operation().await?;
+                let mut operation = pool.operation_with_affinity(affinity_key.clone()).await?;
                 operation
                     .send(EvalJavaScriptOutgoingMessage::Evaluate {
                         args: args.iter().map(|v| &**v).collect(),
@@ -521,6 +529,7 @@ pub async fn compute(
         }
 
         evaluate_context.finish(state, &pool).await?;
+        tracing::trace!(stats = ?pool.stats(), "Node.js pool statistics");
 
         if kill {
             operation.wait_or_kill().await?;
@@ -641,6 +650,7 @@ impl EvaluateContext for BasicEvaluateContext {
             self.additional_invalidation,
             self.debug,
             EnvVarTracking::WholeEnvTracked,
+            0,
         )
     }
 
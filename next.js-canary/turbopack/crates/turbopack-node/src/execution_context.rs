@@ -8,6 +8,9 @@ pub struct ExecutionContext {
     pub project_path: FileSystemPath,
     pub chunking_context: ResolvedVc<Box<dyn ChunkingContext>>,
     pub env: ResolvedVc<Box<dyn ProcessEnv>>,
+    /// The number of Node.js processes to spawn ahead of time for each evaluation pool, see
+    /// [ExecutionContext::with_pool_warm_up].
+    pub pool_warm_up: usize,
 }
 
 #[turbo_tasks::value_impl]
@@ -22,6 +25,21 @@ impl ExecutionContext {
             project_path,
             chunking_context,
             env,
+            pool_warm_up: 0,
+        }
+        .cell()
+    }
+
+    /// Pre-spawns `pool_warm_up` processes when a Node.js pool for a loader or transform is
+    /// created, so the first operations don't wait for cold processes to boot. The number is
+    /// limited by the concurrency of the pool.
+    #[turbo_tasks::function]
+    pub fn with_pool_warm_up(&self, pool_warm_up: usize) -> Vc<Self> {
+        ExecutionContext {
+            project_path: self.project_path.clone(),
+            chunking_context: self.chunking_context,
+            env: self.env,
+            pool_warm_up,
         }
         .cell()
     }
@@ -7,7 +7,7 @@ use std::{
     mem::take,
     path::{Path, PathBuf},
     process::{ExitStatus, Stdio},
-    sync::Arc,
+    sync::{Arc, Weak},
     time::{Duration, Instant},
 };
 
@@ -16,7 +16,7 @@ use futures::join;
 use once_cell::sync::Lazy;
 use owo_colors::{OwoColorize, Style};
 use parking_lot::Mutex;
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use serde::{Serialize, de::DeserializeOwned};
 use tokio::{
     io::{
@@ -79,6 +79,11 @@ struct NodeJsPoolProcess {
     stderr_handler: OutputStreamHandler<ChildStderr, Stderr>,
     debug: bool,
     cpu_time_invested: Duration,
+    /// Number of evaluations this process has completed.
+    evaluations: u32,
+    /// The affinity keys of the operations this process has served. Operations prefer processes
+    /// that already served their key, since these have warm in-process caches.
+    affinity_keys: FxHashSet<RcStr>,
 }
 
 impl Ord for NodeJsPoolProcess {
@@ -463,6 +468,8 @@ impl NodeJsPoolProcess {
             stderr_handler,
             debug,
             cpu_time_invested: Duration::ZERO,
+            evaluations: 0,
+            affinity_keys: FxHashSet::default(),
         };
 
         drop(guard);
@@ -545,8 +552,10 @@ impl NodeJsPoolProcess {
     }
 }
 
-#[derive(Default)]
-struct NodeJsPoolStats {
+/// Statistics of a [NodeJsPool], used to decide when to spawn additional processes. A snapshot is
+/// available via [NodeJsPool::stats].
+#[derive(Default, Clone)]
+pub struct NodeJsPoolStats {
     pub total_bootup_time: Duration,
     pub bootup_count: u32,
     pub total_cold_process_time: Duration,
@@ -556,6 +565,17 @@ struct NodeJsPoolStats {
     pub workers: u32,
     pub booting_workers: u32,
     pub queued_tasks: u32,
+    /// Time operations spent waiting for a process, including the bootup of fresh processes.
+    pub total_queue_wait_time: Duration,
+    pub queue_wait_count: u32,
+    /// Processes spawned ahead of time by [NodeJsPool::warm_up].
+    pub warmed_up_workers: u32,
+    /// Operations with an affinity key that got an idle process which already served that key.
+    pub affinity_hits: u32,
+    /// Operations with an affinity key that got an idle process which didn't serve that key yet.
+    pub affinity_misses: u32,
+    /// The highest number of evaluations completed by a single process.
+    pub max_evaluations_per_process: u32,
 }
 
 impl NodeJsPoolStats {
@@ -581,6 +601,32 @@ impl NodeJsPoolStats {
         self.queued_tasks += 1;
     }
 
+    fn add_queue_wait_time(&mut self, time: Duration) {
+        self.total_queue_wait_time += time;
+        self.queue_wait_count += 1;
+    }
+
+    fn add_evaluation(&mut self, evaluations: u32) {
+        self.max_evaluations_per_process = max(self.max_evaluations_per_process, evaluations);
+    }
+
+    pub fn estimated_queue_wait_time(&self) -> Duration {
+        if self.queue_wait_count == 0 {
+            Duration::ZERO
+        } else {
+            self.total_queue_wait_time / self.queue_wait_count
+        }
+    }
+
+    /// The average number of evaluations per spawned process.
+    pub fn evaluations_per_process(&self) -> f64 {
+        if self.bootup_count == 0 {
+            0.0
+        } else {
+            (self.cold_process_count + self.warm_process_count) as f64 / self.bootup_count as f64
+        }
+    }
+
     fn add_cold_process_time(&mut self, time: Duration) {
         self.total_cold_process_time += time;
         self.cold_process_count += 1;
@@ -686,6 +732,15 @@ impl Debug for NodeJsPoolStats {
             .field("bootup_count", &self.bootup_count)
             .field("cold_process_count", &self.cold_process_count)
             .field("warm_process_count", &self.warm_process_count)
+            .field("queue_wait_time", &self.estimated_queue_wait_time())
+            .field("warmed_up_workers", &self.warmed_up_workers)
+            .field("affinity_hits", &self.affinity_hits)
+            .field("affinity_misses", &self.affinity_misses)
+            .field("evaluations_per_process", &self.evaluations_per_process())
+            .field(
+                "max_evaluations_per_process",
+                &self.max_evaluations_per_process,
+            )
             .finish()
     }
 }
@@ -712,6 +767,50 @@ type IdleProcessesList = Arc<Mutex<BinaryHeap<NodeJsPoolProcess>>>;
 /// This is used to scale down processes globally.
 static ACTIVE_POOLS: Lazy<Mutex<Vec<IdleProcessesList>>> = Lazy::new(Default::default);
 
+/// Adds a process to the idle processes of a pool and makes it available to waiting operations.
+fn push_idle_process(
+    processes: &IdleProcessesList,
+    idle_process_semaphore: &Semaphore,
+    process: NodeJsPoolProcess,
+) {
+    {
+        let mut processes_guard = processes.lock();
+        if processes_guard.is_empty() {
+            ACTIVE_POOLS.lock().push(processes.clone());
+        }
+        processes_guard.push(process);
+    }
+    idle_process_semaphore.add_permits(1);
+}
+
+/// Takes an idle process from a pool. Prefers a process that already served `affinity_key`,
+/// otherwise the process with the most invested CPU time is used. Returns whether the affinity key
+/// was matched.
+fn pop_idle_process(
+    processes: &IdleProcessesList,
+    affinity_key: Option<&RcStr>,
+) -> (NodeJsPoolProcess, bool) {
+    let mut processes_guard = processes.lock();
+    let affine = affinity_key.and_then(|key| {
+        let mut list = take(&mut *processes_guard).into_vec();
+        let position = list
+            .iter()
+            .position(|process| process.affinity_keys.contains(key));
+        let process = position.map(|position| list.swap_remove(position));
+        *processes_guard = BinaryHeap::from(list);
+        process
+    });
+    let hit = affine.is_some();
+    let process = affine.unwrap_or_else(|| processes_guard.pop().unwrap());
+    if processes_guard.is_empty() {
+        let mut pools = ACTIVE_POOLS.lock();
+        if let Some(idx) = pools.iter().position(|p| Arc::ptr_eq(p, processes)) {
+            pools.swap_remove(idx);
+        }
+    }
+    (process, hit)
+}
+
 /// A pool of Node.js workers operating on [entrypoint] with specific [cwd] and
 /// [env].
 ///
@@ -730,7 +829,8 @@ pub struct NodeJsPool {
     pub assets_root: FileSystemPath,
     pub project_dir: FileSystemPath,
     #[turbo_tasks(trace_ignore, debug_ignore)]
-    processes: Arc<Mutex<BinaryHeap<NodeJsPoolProcess>>>,
+    processes: IdleProcessesList,
+    concurrency: usize,
     /// Semaphore to limit the number of concurrent operations in general
     #[turbo_tasks(trace_ignore, debug_ignore)]
     concurrency_semaphore: Arc<Semaphore>,
@@ -771,6 +871,7 @@ impl NodeJsPool {
             assets_root,
             project_dir,
             processes: Arc::new(Mutex::new(BinaryHeap::new())),
+            concurrency,
             concurrency_semaphore: Arc::new(Semaphore::new(if debug { 1 } else { concurrency })),
             bootup_semaphore: Arc::new(Semaphore::new(1)),
             idle_process_semaphore: Arc::new(Semaphore::new(0)),
@@ -781,10 +882,104 @@ impl NodeJsPool {
         }
     }
 
-    async fn acquire_process(&self) -> Result<(NodeJsPoolProcess, AcquiredPermits)> {
+    /// Spawns up to `count` processes in the background, so that the first operations don't need
+    /// to wait for a process to boot. The number is limited by the concurrency of the pool, and a
+    /// booting process takes a concurrency permit like an operation that boots a process.
+    pub(super) fn warm_up(&self, count: usize) {
+        let count = if self.debug {
+            0
+        } else {
+            count.min(self.concurrency)
+        };
+        if count == 0 {
+            return;
+        }
+        let cwd = self.cwd.clone();
+        let env = self.env.clone();
+        let entrypoint = self.entrypoint.clone();
+        let assets_for_source_mapping = self.assets_for_source_mapping;
+        let assets_root = self.assets_root.clone();
+        let project_dir = self.project_dir.clone();
+        let shared_stdout = self.shared_stdout.clone();
+        let shared_stderr = self.shared_stderr.clone();
+        let concurrency_semaphore = self.concurrency_semaphore.clone();
+        let bootup_semaphore = self.bootup_semaphore.clone();
+        let idle_process_semaphore = self.idle_process_semaphore.clone();
+        let stats = self.stats.clone();
+        // The pool might be dropped before the warm up finishes, in which case the processes are
+        // not needed anymore.
+        let processes: Weak<_> = Arc::downgrade(&self.processes);
+        tokio::spawn(async move {
+            for _ in 0..count {
+                // Held until the process is idle. When all permits are taken, the running
+                // operations boot their own processes and warming up is not needed anymore.
+                let Ok(concurrency_permit) = concurrency_semaphore.clone().try_acquire_owned()
+                else {
+                    return;
+                };
+                let Ok(bootup_permit) = bootup_semaphore.clone().acquire_owned().await else {
+                    return;
+                };
+                if processes.strong_count() == 0 {
+                    return;
+                }
+                {
+                    stats.lock().add_booting_worker();
+                }
+                let start = Instant::now();
+                let result = NodeJsPoolProcess::new(
+                    cwd.as_path(),
+                    &env,
+                    entrypoint.as_path(),
+                    assets_for_source_mapping,
+                    assets_root.clone(),
+                    project_dir.clone(),
+                    shared_stdout.clone(),
+                    shared_stderr.clone(),
+                    false,
+                )
+                .await;
+                drop(bootup_permit);
+                {
+                    let mut stats = stats.lock();
+                    stats.finished_booting_worker();
+                    if result.is_ok() {
+                        stats.add_bootup_time(start.elapsed());
+                        stats.warmed_up_workers += 1;
+                    } else {
+                        stats.remove_worker();
+                    }
+                }
+                let process = match result {
+                    Ok(process) => process,
+                    Err(err) => {
+                        tracing::debug!("warming up a Node.js process failed: {err:?}");
+                        return;
+                    }
+                };
+                let Some(processes) = processes.upgrade() else {
+                    stats.lock().remove_worker();
+                    return;
+                };
+                push_idle_process(&processes, &idle_process_semaphore, process);
+                drop(concurrency_permit);
+            }
+        });
+    }
+
+    /// Returns a snapshot of the statistics of this pool.
+    pub fn stats(&self) -> NodeJsPoolStats {
+        self.stats.lock().clone()
+    }
+
+    async fn acquire_process(
+        &self,
+        affinity_key: Option<&RcStr>,
+    ) -> Result<(NodeJsPoolProcess, AcquiredPermits)> {
         {
             self.stats.lock().add_queued_task();
         }
+        let start = Instant::now();
 
         let concurrency_permit = self.concurrency_semaphore.clone().acquire_owned().await?;
 
@@ -798,18 +993,27 @@ impl NodeJsPool {
         select! {
             idle_process_permit = self.idle_process_semaphore.clone().acquire_owned() => {
                 let idle_process_permit = idle_process_permit.context("acquiring idle process permit")?;
-                let process = {
-                    let mut processes = self.processes.lock();
-                    let process = processes.pop().unwrap();
-                    if processes.is_empty() {
-                        let mut pools = ACTIVE_POOLS.lock();
-                        if let Some(idx) = pools.iter().position(|p| Arc::ptr_eq(p, &self.processes)) {
-                            pools.swap_remove(idx);
+                let (process, affinity_hit) = pop_idle_process(&self.processes, affinity_key);
+                idle_process_permit.forget();
+                let queue_wait_time = start.elapsed();
+                {
+                    let mut stats = self.stats.lock();
+                    stats.add_queue_wait_time(queue_wait_time);
+                    if affinity_key.is_some() {
+                        if affinity_hit {
+                            stats.affinity_hits += 1;
+                        } else {
+                            stats.affinity_misses += 1;
                         }
                     }
-                    process
-                };
-                idle_process_permit.forget();
+                }
+                tracing::trace!(
+                    queue_wait_time = ?queue_wait_time,
+                    affinity_key = affinity_key.map(|key| key.as_str()),
+                    affinity_hit,
+                    evaluations = process.evaluations,
+                    "acquired idle Node.js process"
+                );
                 Ok((process, AcquiredPermits::Idle { concurrency_permit }))
             },
             bootup_permit = bootup => {
@@ -819,11 +1023,18 @@ impl NodeJsPool {
                 }
                 let (process, bootup_time) = self.create_process().await?;
                 // Update the worker count
+                let queue_wait_time = start.elapsed();
                 {
                     let mut stats = self.stats.lock();
                     stats.add_bootup_time(bootup_time);
                     stats.finished_booting_worker();
+                    stats.add_queue_wait_time(queue_wait_time);
                 }
+                tracing::trace!(
+                    queue_wait_time = ?queue_wait_time,
+                    bootup_time = ?bootup_time,
+                    "acquired fresh Node.js process"
+                );
                 // Increase the allowed booting up processes
                 self.bootup_semaphore.add_permits(1);
                 Ok((process, AcquiredPermits::Fresh { concurrency_permit, bootup_permit }))
@@ -850,12 +1061,23 @@ impl NodeJsPool {
     }
 
     pub async fn operation(&self) -> Result<NodeJsOperation> {
+        self.operation_with_affinity(None).await
+    }
+
+    /// Like [NodeJsPool::operation], but prefers an idle process that already executed an
+    /// operation with the same `affinity_key`, e. g. the same loader chain. Such processes have
+    /// the modules of that operation loaded and their in-process caches populated.
+    pub async fn operation_with_affinity(
+        &self,
+        affinity_key: Option<RcStr>,
+    ) -> Result<NodeJsOperation> {
         // Acquire a running process (handles concurrency limits, boots up the process)
-        let (process, permits) = self.acquire_process().await?;
+        let (process, permits) = self.acquire_process(affinity_key.as_ref()).await?;
 
         Ok(NodeJsOperation {
             process: Some(process),
             permits,
+            affinity_key,
             processes: self.processes.clone(),
             idle_process_semaphore: self.idle_process_semaphore.clone(),
             start: Instant::now(),
@@ -890,7 +1112,8 @@ pub struct NodeJsOperation {
     // This is used for drop
     #[allow(dead_code)]
     permits: AcquiredPermits,
-    processes: Arc<Mutex<BinaryHeap<NodeJsPoolProcess>>>,
+    affinity_key: Option<RcStr>,
+    processes: IdleProcessesList,
     idle_process_semaphore: Arc<Semaphore>,
     start: Instant,
     stats: Arc<Mutex<NodeJsPoolStats>>,
@@ -996,24 +1219,162 @@ impl Drop for NodeJsOperation {
     fn drop(&mut self) {
         if let Some(mut process) = self.process.take() {
             let elapsed = self.start.elapsed();
+            process.evaluations += 1;
             {
                 let stats = &mut self.stats.lock();
                 match self.permits {
                     AcquiredPermits::Idle { .. } => stats.add_warm_process_time(elapsed),
                     AcquiredPermits::Fresh { .. } => stats.add_cold_process_time(elapsed),
                 }
+                stats.add_evaluation(process.evaluations);
             }
             if self.allow_process_reuse {
                 process.cpu_time_invested += elapsed;
-                {
-                    let mut processes = self.processes.lock();
-                    if processes.is_empty() {
-                        ACTIVE_POOLS.lock().push(self.processes.clone());
-                    }
-                    processes.push(process);
+                if let Some(affinity_key) = self.affinity_key.take() {
+                    process.affinity_keys.insert(affinity_key);
                 }
-                self.idle_process_semaphore.add_permits(1);
+                push_idle_process(&self.processes, &self.idle_process_semaphore, process);
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use turbo_rcstr::rcstr;
+    use turbo_tasks_fs::{FileSystem, VirtualFileSystem};
+
+    use super::*;
+
+    /// Connects to the pool and reports readiness like the IPC client, then stays idle.
+    const IDLE_PROCESS: &str = r#"
+const socket = require('net').connect(Number(process.argv[2]), '127.0.0.1', () => {
+  socket.write(Buffer.alloc(4), () => {
+    process.stderr.write('TURBOPACK_OUTPUT_D\n')
+    process.stdout.write('TURBOPACK_OUTPUT_D\n')
+  })
+})
+socket.once('close', () => process.exit(0))
+"#;
+
+    async fn idle_pool(dir: &Path, concurrency: usize) -> Result<NodeJsPool> {
+        let entrypoint = dir.join("idle.js");
+        std::fs::write(&entrypoint, IDLE_PROCESS)?;
+        let root = Vc::upcast::<Box<dyn FileSystem>>(VirtualFileSystem::new())
+            .root()
+            .owned()
+            .await?;
+        Ok(NodeJsPool::new(
+            dir.to_path_buf(),
+            entrypoint,
+            FxHashMap::default(),
+            ResolvedVc::cell(FxHashMap::default()),
+            root.clone(),
+            root,
+            concurrency,
+            false,
+        ))
+    }
+
+    async fn wait_for_warm_up(pool: &NodeJsPool, workers: u32) {
+        let start = Instant::now();
+        while pool.stats().warmed_up_workers < workers {
+            assert!(
+                start.elapsed() < Duration::from_secs(30),
+                "processes were not warmed up"
+            );
+            sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn warm_up_is_limited_by_concurrency() {
+        crate::register();
+
+        turbo_tasks_testing::VcStorage::with(async {
+            let dir = tempfile::tempdir()?;
+            let pool = idle_pool(dir.path(), 2).await?;
+            pool.warm_up(4);
+            wait_for_warm_up(&pool, 2).await;
+
+            let operation = pool.operation_with_affinity(None).await?;
+            assert!(matches!(operation.permits, AcquiredPermits::Idle { .. }));
+            drop(operation);
+
+            let stats = pool.stats();
+            assert_eq!(stats.warmed_up_workers, 2);
+            assert_eq!(stats.bootup_count, 2);
+            assert_eq!(stats.workers, 2);
+            assert_eq!(stats.warm_process_count, 1);
+            assert_eq!(stats.queue_wait_count, 1);
+            anyhow::Ok(())
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn warm_up_takes_concurrency_permits() {
+        crate::register();
+
+        turbo_tasks_testing::VcStorage::with(async {
+            let dir = tempfile::tempdir()?;
+            let pool = idle_pool(dir.path(), 1).await?;
+            let operation = pool.operation_with_affinity(None).await?;
+            // The only permit is taken by the operation
+            pool.warm_up(1);
+            sleep(Duration::from_millis(200)).await;
+            drop(operation);
+
+            let stats = pool.stats();
+            assert_eq!(stats.warmed_up_workers, 0);
+            assert_eq!(stats.bootup_count, 1);
+            assert_eq!(stats.workers, 1);
+            anyhow::Ok(())
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn operations_prefer_processes_of_their_affinity_key() {
+        crate::register();
+
+        turbo_tasks_testing::VcStorage::with(async {
+            let dir = tempfile::tempdir()?;
+            let pool = idle_pool(dir.path(), 2).await?;
+            pool.warm_up(2);
+            wait_for_warm_up(&pool, 2).await;
+
+            let a = pool.operation_with_affinity(Some(rcstr!("a"))).await?;
+            let b = pool.operation_with_affinity(Some(rcstr!("b"))).await?;
+            drop(a);
+            drop(b);
+
+            for _ in 0..3 {
+                let operation = pool.operation_with_affinity(Some(rcstr!("a"))).await?;
+                let process = operation.process.as_ref().unwrap();
+                assert!(process.affinity_keys.contains(&rcstr!("a")));
+                assert!(!process.affinity_keys.contains(&rcstr!("b")));
+            }
+
+            let stats = pool.stats();
+            assert_eq!(stats.affinity_misses, 2);
+            assert_eq!(stats.affinity_hits, 3);
+            assert_eq!(stats.max_evaluations_per_process, 4);
+            assert_eq!(stats.evaluations_per_process(), 2.5);
+            anyhow::Ok(())
+        })
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn estimates_queue_wait_time() {
+        let mut stats = NodeJsPoolStats::default();
+        assert_eq!(stats.estimated_queue_wait_time(), Duration::ZERO);
+        stats.add_queue_wait_time(Duration::from_millis(10));
+        stats.add_queue_wait_time(Duration::from_millis(30));
+        assert_eq!(stats.estimated_queue_wait_time(), Duration::from_millis(20));
+    }
+}
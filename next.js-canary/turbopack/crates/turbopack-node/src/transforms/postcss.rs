@@ -458,6 +458,7 @@ impl PostCssTransformedAsset {
             project_path,
             chunking_context,
             env,
+            pool_warm_up,
         } = &*self.execution_context.await?;
 
         // For this postcss transform, there is no guarantee that looking up for the
@@ -527,6 +528,8 @@ impl PostCssTransformedAsset {
             asset_context: evaluate_context,
             chunking_context: *chunking_context,
             resolve_options_context: None,
+            pool_warm_up: *pool_warm_up,
+            pool_affinity: None,
             args: vec![
                 ResolvedVc::cell(content.into()),
                 ResolvedVc::cell(css_path.into()),
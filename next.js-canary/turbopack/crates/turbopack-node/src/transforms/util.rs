@@ -1,8 +1,9 @@
 use std::collections::BTreeMap;
 
 use anyhow::Result;
+use either::Either;
 use serde::{Deserialize, Serialize};
-use serde_json::Value as JsonValue;
+use serde_with::serde_as;
 use turbo_rcstr::RcStr;
 use turbo_tasks::{NonLocalValue, ResolvedVc, TryJoinIterExt, trace::TraceRawVcs};
 use turbo_tasks_fs::{File, FileContent, FileSystem};
@@ -10,42 +11,89 @@ use turbopack_core::{
     asset::AssetContent, server_fs::ServerFileSystem, virtual_source::VirtualSource,
 };
 
+#[serde_as]
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TraceRawVcs, NonLocalValue,
+)]
+pub(crate) struct BytesBase64 {
+    #[serde_as(as = "serde_with::base64::Base64")]
+    pub binary: Vec<u8>,
+}
+
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, TraceRawVcs, NonLocalValue)]
 #[serde(rename_all = "camelCase")]
 pub struct EmittedAsset {
     file: RcStr,
-    content: RcStr,
-    source_map: Option<JsonValue>,
+    #[serde(with = "either::serde_untagged")]
+    content: Either<RcStr, BytesBase64>,
+    source_map: Option<RcStr>,
 }
 
+/// Creates a source for each emitted asset, and one for its source map next to it. The paths are
+/// relative to the root of the [ServerFileSystem].
 pub async fn emitted_assets_to_virtual_sources(
     assets: Option<Vec<EmittedAsset>>,
 ) -> Result<Vec<ResolvedVc<VirtualSource>>> {
     assets
         .into_iter()
         .flatten()
-        .map(
+        .flat_map(
             |EmittedAsset {
                  file,
                  content,
                  source_map,
-             }| (file, (content, source_map)),
+             }| {
+                let content = match content {
+                    Either::Left(str) => File::from(str),
+                    Either::Right(bytes) => File::from(bytes.binary),
+                };
+                let source_map =
+                    source_map.map(|map| (format!("{file}.map").into(), File::from(map)));
+                std::iter::once((file, content)).chain(source_map)
+            },
         )
         // Sort it to make it deterministic
-        .collect::<BTreeMap<_, _>>()
+        .collect::<BTreeMap<RcStr, _>>()
         .into_iter()
-        .map(|(file, (content, _source_map))| {
-            async move {
-                // TODO handle SourceMap
-                VirtualSource::new(
-                    ServerFileSystem::new().root().await?.join(&file)?,
-                    AssetContent::File(FileContent::Content(File::from(content)).resolved_cell())
-                        .cell(),
-                )
-                .to_resolved()
-                .await
-            }
+        .map(|(file, content)| async move {
+            VirtualSource::new(
+                ServerFileSystem::new().root().await?.join(&file)?,
+                AssetContent::File(FileContent::Content(content).resolved_cell()).cell(),
+            )
+            .to_resolved()
+            .await
         })
         .try_join()
         .await
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn parses_text_and_binary_content() {
+        let assets: Vec<EmittedAsset> = serde_json::from_value(json!([
+            { "file": "text.txt", "content": "text" },
+            { "file": "image.png", "content": { "binary": "AAE=" }, "sourceMap": "{}" },
+        ]))
+        .unwrap();
+        assert_eq!(
+            assets,
+            vec![
+                EmittedAsset {
+                    file: "text.txt".into(),
+                    content: Either::Left("text".into()),
+                    source_map: None,
+                },
+                EmittedAsset {
+                    file: "image.png".into(),
+                    content: Either::Right(BytesBase64 { binary: vec![0, 1] }),
+                    source_map: Some("{}".into()),
+                },
+            ]
+        );
+    }
+}
@@ -4,22 +4,23 @@ use anyhow::{Context, Result, bail};
 use base64::Engine;
 use either::Either;
 use futures::try_join;
+use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map as JsonMap, Value as JsonValue, json};
-use serde_with::serde_as;
 use turbo_rcstr::{RcStr, rcstr};
 use turbo_tasks::{
     Completion, NonLocalValue, OperationValue, OperationVc, ResolvedVc, TaskInput, TryJoinIterExt,
-    ValueToString, Vc, trace::TraceRawVcs,
+    ValueToString, Vc, apply_effects, trace::TraceRawVcs,
 };
 use turbo_tasks_bytes::stream::SingleValue;
 use turbo_tasks_env::ProcessEnv;
 use turbo_tasks_fs::{
-    File, FileContent, FileSystemPath, glob::Glob, json::parse_json_with_source_context, rope::Rope,
+    File, FileContent, FileSystemPath, glob::Glob, json::parse_json_with_source_context,
+    rope::Rope, to_sys_path,
 };
 use turbopack_core::{
     asset::{Asset, AssetContent},
-    chunk::ChunkingContext,
+    chunk::{ChunkingContext, ChunkingContextExt, EvaluatableAsset, EvaluatableAssets},
     context::{AssetContext, ProcessResult},
     file_source::FileSource,
     ident::AssetIdent,
@@ -28,14 +29,18 @@ use turbopack_core::{
         OptionStyledString, StyledString,
     },
     module::Module,
-    reference_type::{InnerAssets, ReferenceType},
+    module_graph::{ModuleGraph, chunk_group_info::ChunkGroupEntry},
+    output::{OutputAsset, OutputAssets},
+    reference_type::{
+        CommonJsReferenceSubType, EcmaScriptModulesReferenceSubType, InnerAssets, ReferenceType,
+    },
     resolve::{
         options::{ConditionValue, ResolveInPackage, ResolveIntoPackage, ResolveOptions},
         parse::Request,
         pattern::Pattern,
         resolve,
     },
-    source::Source,
+    source::{Source, SourceWithEmittedFiles, Sources},
     source_map::{
         GenerateSourceMap, OptionStringifiedSourceMap, utils::resolve_source_map_sources,
     },
@@ -43,31 +48,27 @@ use turbopack_core::{
     virtual_source::VirtualSource,
 };
 use turbopack_resolve::{
-    ecmascript::get_condition_maps, resolve::resolve_options,
+    ecmascript::{apply_cjs_specific_options, apply_esm_specific_options, get_condition_maps},
+    resolve::resolve_options,
     resolve_options_context::ResolveOptionsContext,
 };
 
-use super::util::{EmittedAsset, emitted_assets_to_virtual_sources};
+use super::util::{BytesBase64, EmittedAsset, emitted_assets_to_virtual_sources};
 use crate::{
     AssetsForSourceMapping,
     debug::should_debug,
     embed_js::embed_file_path,
+    emit,
     evaluate::{
         EnvVarTracking, EvaluateContext, EvaluationIssue, JavaScriptEvaluation,
         JavaScriptStreamSender, compute, custom_evaluate, get_evaluate_pool,
     },
     execution_context::ExecutionContext,
+    internal_assets,
     pool::{FormattingMode, NodeJsPool},
     source_map::{StackFrame, StructuredError},
 };
 
-#[serde_as]
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
-struct BytesBase64 {
-    #[serde_as(as = "serde_with::base64::Base64")]
-    binary: Vec<u8>,
-}
-
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 #[turbo_tasks::value(serialization = "custom")]
@@ -178,6 +179,21 @@ impl GenerateSourceMap for WebpackLoadersProcessedAsset {
     }
 }
 
+#[turbo_tasks::value_impl]
+impl SourceWithEmittedFiles for WebpackLoadersProcessedAsset {
+    #[turbo_tasks::function]
+    async fn emitted_files(self: Vc<Self>) -> Result<Vc<Sources>> {
+        Ok(Vc::cell(
+            self.process()
+                .await?
+                .assets
+                .iter()
+                .map(|&asset| ResolvedVc::upcast(asset))
+                .collect(),
+        ))
+    }
+}
+
 #[turbo_tasks::value]
 struct ProcessWebpackLoadersResult {
     content: ResolvedVc<AssetContent>,
@@ -210,6 +226,7 @@ impl WebpackLoadersProcessedAsset {
             project_path,
             chunking_context,
             env,
+            pool_warm_up,
         } = &*transform.execution_context.await?;
         let source_content = this.source.content();
         let AssetContent::File(file) = *source_content.await? else {
@@ -260,6 +277,17 @@ impl WebpackLoadersProcessedAsset {
             asset_context: evaluate_context,
             chunking_context: *chunking_context,
             resolve_options_context: Some(transform.resolve_options_context),
+            pool_warm_up: *pool_warm_up,
+            // Loaders usually keep caches in memory, so the same loader chain should run in the
+            // same process.
+            pool_affinity: Some(
+                loaders
+                    .iter()
+                    .map(|item| item.loader.as_str())
+                    .collect::<Vec<_>>()
+                    .join("!")
+                    .into(),
+            ),
             args: vec![
                 ResolvedVc::cell(content),
                 // We need to pass the query string to the loader
@@ -394,6 +422,53 @@ pub struct WebpackResolveOptions {
     main_files: Option<Vec<RcStr>>,
     no_modules: bool,
     prefer_relative: bool,
+    /// `esm` or `commonjs`, selects the conditions and reference type used to resolve.
+    dependency_type: Option<RcStr>,
+    /// The result needs to match at least one of these restrictions.
+    restrictions: Option<Vec<WebpackResolveRestriction>>,
+}
+
+#[derive(Debug, Clone, TaskInput, Hash, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum WebpackResolveRestriction {
+    /// The resolved path is required to be inside of this absolute path.
+    Path { path: RcStr },
+    /// A JavaScript regular expression tested against the absolute resolved path. Only the `i`
+    /// flag is supported.
+    Regex { source: RcStr, flags: RcStr },
+}
+
+impl WebpackResolveRestriction {
+    fn compile(&self) -> Result<ResolveRestriction<'_>> {
+        Ok(match self {
+            WebpackResolveRestriction::Path { path } => ResolveRestriction::Path(path),
+            WebpackResolveRestriction::Regex { source, flags } => ResolveRestriction::Regex(
+                RegexBuilder::new(source)
+                    .case_insensitive(flags.contains('i'))
+                    .build()
+                    .with_context(|| {
+                        format!("Unsupported resolve restriction /{source}/{flags}")
+                    })?,
+            ),
+        })
+    }
+}
+
+/// A [WebpackResolveRestriction] ready to be matched against resolved paths.
+enum ResolveRestriction<'a> {
+    Path(&'a str),
+    Regex(Regex),
+}
+
+impl ResolveRestriction<'_> {
+    fn matches(&self, path: &str) -> bool {
+        match self {
+            ResolveRestriction::Path(restriction) => path
+                .strip_prefix(restriction)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '\\'])),
+            ResolveRestriction::Regex(regex) => regex.is_match(path),
+        }
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -405,12 +480,31 @@ pub enum RequestMessage {
         lookup_path: RcStr,
         request: RcStr,
     },
+    /// `this.importModule`: compiles the module in the evaluation context and emits it as an
+    /// entry that can be required by the loader.
+    #[serde(rename_all = "camelCase")]
+    ImportModule { lookup_path: RcStr, request: RcStr },
+    /// `this.loadModule`: returns the source of the module after the module rules of the
+    /// evaluation context have been applied.
+    #[serde(rename_all = "camelCase")]
+    LoadModule { lookup_path: RcStr, request: RcStr },
 }
 
 #[derive(Serialize, Debug)]
 #[serde(untagged)]
 pub enum ResponseMessage {
-    Resolve { path: RcStr },
+    Resolve {
+        path: RcStr,
+    },
+    ImportModule {
+        path: RcStr,
+        /// All files of the entry, they need to be evicted from the require cache.
+        chunks: Vec<RcStr>,
+    },
+    LoadModule {
+        #[serde(with = "either::serde_untagged")]
+        source: Either<RcStr, BytesBase64>,
+    },
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, TaskInput, Serialize, Deserialize, Debug, TraceRawVcs)]
@@ -422,6 +516,8 @@ pub struct WebpackLoaderContext {
     pub asset_context: ResolvedVc<Box<dyn AssetContext>>,
     pub chunking_context: ResolvedVc<Box<dyn ChunkingContext>>,
     pub resolve_options_context: Option<ResolvedVc<ResolveOptionsContext>>,
+    pub pool_warm_up: usize,
+    pub pool_affinity: Option<RcStr>,
     pub args: Vec<ResolvedVc<JsonValue>>,
     pub additional_invalidation: ResolvedVc<Completion>,
 }
@@ -452,9 +548,14 @@ impl EvaluateContext for WebpackLoaderContext {
             // vars only. So the runtime code tracks which env vars are read and send a dependency
             // message for them.
             EnvVarTracking::Untracked,
+            self.pool_warm_up,
         )
     }
 
+    fn pool_affinity(&self) -> Option<RcStr> {
+        self.pool_affinity.clone()
+    }
+
     fn args(&self) -> &[ResolvedVc<serde_json::Value>] {
         &self.args
     }
@@ -575,28 +676,58 @@ impl EvaluateContext for WebpackLoaderContext {
                 let request = Request::parse(Pattern::Constant(request));
                 let options = resolve_options(lookup_path.clone(), *resolve_options_context);
 
+                let reference_type = match webpack_options.dependency_type.as_deref() {
+                    Some("esm") => {
+                        ReferenceType::EcmaScriptModules(EcmaScriptModulesReferenceSubType::Import)
+                    }
+                    Some("commonjs") => {
+                        ReferenceType::CommonJs(CommonJsReferenceSubType::Undefined)
+                    }
+                    _ => ReferenceType::Undefined,
+                };
+                let options = match reference_type {
+                    ReferenceType::EcmaScriptModules(_) => {
+                        apply_esm_specific_options(options, reference_type.clone())
+                    }
+                    ReferenceType::CommonJs(_) => apply_cjs_specific_options(options),
+                    _ => options,
+                };
+                let restrictions = webpack_options
+                    .restrictions
+                    .iter()
+                    .flatten()
+                    .map(WebpackResolveRestriction::compile)
+                    .collect::<Result<Vec<_>>>()?;
                 let options = apply_webpack_resolve_options(options, webpack_options);
 
-                let resolved = resolve(
-                    lookup_path.clone(),
-                    ReferenceType::Undefined,
-                    request,
-                    options,
-                );
-
-                if let Some(source) = *resolved.first_source().await? {
-                    if let Some(path) = self
-                        .cwd
-                        .get_relative_path_to(&*source.ident().path().await?)
-                    {
-                        Ok(ResponseMessage::Resolve { path })
-                    } else {
-                        bail!(
-                            "Resolving {} in {} ends up on a different filesystem",
-                            request.to_string().await?,
-                            lookup_path.value_to_string().await?
-                        );
+                let resolved = resolve(lookup_path.clone(), reference_type, request, options);
+
+                let mut found_on_other_filesystem = false;
+                for source in resolved.primary_sources().await?.iter() {
+                    let source_path = source.ident().path().await?;
+                    if !restrictions.is_empty() {
+                        let Some(sys_path) = to_sys_path((*source_path).clone()).await? else {
+                            continue;
+                        };
+                        let sys_path = sys_path.to_string_lossy();
+                        if !restrictions
+                            .iter()
+                            .any(|restriction| restriction.matches(&sys_path))
+                        {
+                            continue;
+                        }
                     }
+                    if let Some(path) = self.cwd.get_relative_path_to(&*source_path) {
+                        return Ok(ResponseMessage::Resolve { path });
+                    }
+                    found_on_other_filesystem = true;
+                }
+                if found_on_other_filesystem {
+                    bail!(
+                        "Resolving {} in {} ends up on a different filesystem",
+                        request.to_string().await?,
+                        lookup_path.value_to_string().await?
+                    );
                 } else {
                     bail!(
                         "Unable to resolve {} in {}",
@@ -605,6 +736,65 @@ impl EvaluateContext for WebpackLoaderContext {
                     );
                 }
             }
+            RequestMessage::ImportModule {
+                lookup_path,
+                request,
+            } => {
+                let module = self.resolve_module(lookup_path, request).await?;
+                let Some(module) = ResolvedVc::try_sidecast::<Box<dyn EvaluatableAsset>>(module)
+                else {
+                    bail!(
+                        "{} can't be imported by a loader since it is not evaluatable",
+                        module.ident().to_string().await?
+                    );
+                };
+                let emitted =
+                    emit_imported_module_with_effects_operation(module, self.chunking_context)
+                        .read_strongly_consistent()
+                        .await?;
+                let sys_path = async |path: &FileSystemPath| {
+                    let Some(path) = to_sys_path(path.clone()).await? else {
+                        bail!("Loaders can only import modules emitted to a disk filesystem");
+                    };
+                    Ok(RcStr::from(path.to_string_lossy()))
+                };
+                let mut chunks = Vec::with_capacity(emitted.files.len());
+                for file in &emitted.files {
+                    chunks.push(sys_path(file).await?);
+                }
+                Ok(ResponseMessage::ImportModule {
+                    path: sys_path(&emitted.entry).await?,
+                    chunks,
+                })
+            }
+            RequestMessage::LoadModule {
+                lookup_path,
+                request,
+            } => {
+                let module = self.resolve_module(lookup_path, request).await?;
+                let Some(asset) = ResolvedVc::try_sidecast::<Box<dyn Asset>>(module) else {
+                    bail!(
+                        "The source of {} is not available",
+                        module.ident().to_string().await?
+                    );
+                };
+                let AssetContent::File(file) = *asset.content().await? else {
+                    bail!("Loaders can only load modules backed by a file");
+                };
+                let FileContent::Content(file) = &*file.await? else {
+                    bail!(
+                        "{} does not exist",
+                        module.ident().path().await?.value_to_string().await?
+                    );
+                };
+                let source = match file.content().to_str() {
+                    Ok(source) => Either::Left(source.as_ref().into()),
+                    Err(_) => Either::Right(BytesBase64 {
+                        binary: file.content().to_bytes().into_owned(),
+                    }),
+                };
+                Ok(ResponseMessage::LoadModule { source })
+            }
         }
     }
 
@@ -645,6 +835,92 @@ impl EvaluateContext for WebpackLoaderContext {
     }
 }
 
+impl WebpackLoaderContext {
+    /// Resolves and processes a request in the evaluation context, like a reference from a module
+    /// in the directory `lookup_path` would.
+    async fn resolve_module(
+        &self,
+        lookup_path: RcStr,
+        request: RcStr,
+    ) -> Result<ResolvedVc<Box<dyn Module>>> {
+        let lookup_path = self.cwd.join(&lookup_path)?;
+        let request = Request::parse(Pattern::Constant(request));
+        let options = self
+            .asset_context
+            .resolve_options(lookup_path.clone(), ReferenceType::Undefined);
+        let resolved = self.asset_context.resolve_asset(
+            lookup_path.clone(),
+            request,
+            options,
+            ReferenceType::Undefined,
+        );
+        let Some(module) = *resolved.first_module().await? else {
+            bail!(
+                "Unable to resolve {} in {}",
+                request.to_string().await?,
+                lookup_path.value_to_string().await?
+            );
+        };
+        Ok(module)
+    }
+}
+
+#[turbo_tasks::value]
+struct EmittedImportedModule {
+    /// The entry that evaluates the module when required.
+    entry: FileSystemPath,
+    /// All files emitted for the entry, including the chunks it loads.
+    files: Vec<FileSystemPath>,
+}
+
+#[turbo_tasks::function(operation)]
+async fn emit_imported_module_operation(
+    module: ResolvedVc<Box<dyn EvaluatableAsset>>,
+    chunking_context: ResolvedVc<Box<dyn ChunkingContext>>,
+) -> Result<Vc<EmittedImportedModule>> {
+    let path = chunking_context
+        .chunk_path(
+            None,
+            module.ident().with_modifier(rcstr!("loader import module")),
+            None,
+            rcstr!(".js"),
+        )
+        .owned()
+        .await?;
+    let module_graph = ModuleGraph::from_modules(
+        Vc::cell(vec![ChunkGroupEntry::Entry(vec![ResolvedVc::upcast(
+            module,
+        )])]),
+        false,
+    );
+    let entry = chunking_context.root_entry_chunk_group_asset(
+        path.clone(),
+        EvaluatableAssets::one(module),
+        module_graph,
+        OutputAssets::empty(),
+    );
+    let output_root = chunking_context.output_root().owned().await?;
+    emit(entry, output_root.clone()).as_side_effect().await?;
+    let files = internal_assets(entry, output_root)
+        .await?
+        .iter()
+        .map(async |asset| asset.path().owned().await)
+        .try_join()
+        .await?;
+    Ok(EmittedImportedModule { entry: path, files }.cell())
+}
+
+#[turbo_tasks::function(operation)]
+async fn emit_imported_module_with_effects_operation(
+    module: ResolvedVc<Box<dyn EvaluatableAsset>>,
+    chunking_context: ResolvedVc<Box<dyn ChunkingContext>>,
+) -> Result<Vc<EmittedImportedModule>> {
+    let operation = emit_imported_module_operation(module, chunking_context);
+    let result = operation.resolve_strongly_consistent().await?;
+    apply_effects(operation).await?;
+    Ok(*result)
+}
+
 #[turbo_tasks::function]
 async fn apply_webpack_resolve_options(
     resolve_options: Vc<ResolveOptions>,
@@ -909,3 +1185,48 @@ impl Issue for EvaluateErrorLoggingIssue {
         Vc::cell(Some(self.source))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(path: &str) -> WebpackResolveRestriction {
+        WebpackResolveRestriction::Path { path: path.into() }
+    }
+
+    fn regex(source: &str, flags: &str) -> WebpackResolveRestriction {
+        WebpackResolveRestriction::Regex {
+            source: source.into(),
+            flags: flags.into(),
+        }
+    }
+
+    #[test]
+    fn path_restriction_matches_directory_contents() {
+        let restriction = path("/project/src").compile().unwrap();
+        assert!(restriction.matches("/project/src"));
+        assert!(restriction.matches("/project/src/index.js"));
+        assert!(restriction.matches("/project/src\\index.js"));
+        assert!(!restriction.matches("/project/src-other/index.js"));
+        assert!(!restriction.matches("/project/index.js"));
+    }
+
+    #[test]
+    fn regex_restriction_respects_flags() {
+        let restriction = regex(r"\.css$", "").compile().unwrap();
+        assert!(restriction.matches("/project/src/styles.css"));
+        assert!(!restriction.matches("/project/src/styles.CSS"));
+
+        let restriction = regex(r"\.css$", "i").compile().unwrap();
+        assert!(restriction.matches("/project/src/styles.CSS"));
+    }
+
+    #[test]
+    fn invalid_regex_restriction_fails_to_compile() {
+        let error = regex("(?<=a)b", "").compile().err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Unsupported resolve restriction /(?<=a)b/"
+        );
+    }
+}
@@ -6,9 +6,9 @@ use turbo_tasks_fs::FileSystemPath;
 use turbopack_core::{
     asset::Asset,
     chunk::{
-        Chunk, ChunkGroupResult, ChunkItem, ChunkType, ChunkableModule, ChunkingConfig,
-        ChunkingConfigs, ChunkingContext, EntryChunkGroupResult, EvaluatableAssets, MinifyType,
-        ModuleId, SourceMapsType,
+        AsyncChunkOptions, Chunk, ChunkGroupResult, ChunkItem, ChunkType, ChunkableModule,
+        ChunkingConfig, ChunkingConfigs, ChunkingContext, EntryChunkGroupResult, EvaluatableAssets,
+        MinifyType, ModuleId, SourceMapsType,
         availability_info::AvailabilityInfo,
         chunk_group::{MakeChunkGroupResult, make_chunk_group},
         module_id_strategies::{DevModuleIdStrategy, ModuleIdStrategy},
@@ -206,12 +206,13 @@ impl NodeJsChunkingContext {
     async fn generate_chunk(
         self: Vc<Self>,
         chunk: Vc<Box<dyn Chunk>>,
+        name: Option<RcStr>,
     ) -> Result<Vc<Box<dyn OutputAsset>>> {
         Ok(
             if let Some(ecmascript_chunk) =
                 Vc::try_resolve_downcast_type::<EcmascriptChunk>(chunk).await?
             {
-                Vc::upcast(EcmascriptBuildNodeChunk::new(self, ecmascript_chunk))
+                Vc::upcast(EcmascriptBuildNodeChunk::new(self, ecmascript_chunk, name))
             } else if let Some(output_asset) =
                 Vc::try_resolve_sidecast::<Box<dyn OutputAsset>>(chunk).await?
             {
@@ -222,6 +223,47 @@ impl NodeJsChunkingContext {
         )
     }
 
+    /// Creates a chunk group. When `name` is set, it's used as a prefix for the file names of the
+    /// ecmascript chunks.
+    #[turbo_tasks::function]
+    async fn chunk_group_with_name(
+        self: ResolvedVc<Self>,
+        name: Option<RcStr>,
+        ident: Vc<AssetIdent>,
+        chunk_group: ChunkGroup,
+        module_graph: Vc<ModuleGraph>,
+        availability_info: AvailabilityInfo,
+    ) -> Result<Vc<ChunkGroupResult>> {
+        let span = tracing::info_span!("chunking", name = ident.to_string().await?.to_string());
+        async move {
+            let modules = chunk_group.entries();
+            let MakeChunkGroupResult {
+                chunks,
+                availability_info,
+            } = make_chunk_group(
+                modules,
+                module_graph,
+                ResolvedVc::upcast(self),
+                availability_info,
+            )
+            .await?;
+
+            let assets = chunks
+                .iter()
+                .map(|chunk| self.generate_chunk(**chunk, name.clone()).to_resolved())
+                .try_join()
+                .await?;
+
+            Ok(ChunkGroupResult {
+                assets: ResolvedVc::cell(assets),
+                availability_info,
+            }
+            .cell())
+        }
+        .instrument(span)
+        .await
+    }
+
     /// Returns the kind of runtime to include in output chunks.
     ///
     /// This is defined directly on `NodeJsChunkingContext` so it is zero-cost
@@ -377,41 +419,32 @@ impl ChunkingContext for NodeJsChunkingContext {
     }
 
     #[turbo_tasks::function]
-    async fn chunk_group(
-        self: ResolvedVc<Self>,
+    fn chunk_group(
+        self: Vc<Self>,
         ident: Vc<AssetIdent>,
         chunk_group: ChunkGroup,
         module_graph: Vc<ModuleGraph>,
         availability_info: AvailabilityInfo,
-    ) -> Result<Vc<ChunkGroupResult>> {
-        let span = tracing::info_span!("chunking", name = ident.to_string().await?.to_string());
-        async move {
-            let modules = chunk_group.entries();
-            let MakeChunkGroupResult {
-                chunks,
-                availability_info,
-            } = make_chunk_group(
-                modules,
-                module_graph,
-                ResolvedVc::upcast(self),
-                availability_info,
-            )
-            .await?;
-
-            let assets = chunks
-                .iter()
-                .map(|chunk| self.generate_chunk(**chunk).to_resolved())
-                .try_join()
-                .await?;
+    ) -> Vc<ChunkGroupResult> {
+        self.chunk_group_with_name(None, ident, chunk_group, module_graph, availability_info)
+    }
 
-            Ok(ChunkGroupResult {
-                assets: ResolvedVc::cell(assets),
-                availability_info,
-            }
-            .cell())
-        }
-        .instrument(span)
-        .await
+    #[turbo_tasks::function]
+    fn named_chunk_group(
+        self: Vc<Self>,
+        name: RcStr,
+        ident: Vc<AssetIdent>,
+        chunk_group: ChunkGroup,
+        module_graph: Vc<ModuleGraph>,
+        availability_info: AvailabilityInfo,
+    ) -> Vc<ChunkGroupResult> {
+        self.chunk_group_with_name(
+            Some(name),
+            ident,
+            chunk_group,
+            module_graph,
+            availability_info,
+        )
     }
 
     #[turbo_tasks::function]
@@ -446,7 +479,7 @@ impl ChunkingContext for NodeJsChunkingContext {
             .chain(
                 chunks
                     .iter()
-                    .map(|chunk| self.generate_chunk(**chunk).to_resolved())
+                    .map(|chunk| self.generate_chunk(**chunk, None).to_resolved())
                     .try_join()
                     .await?,
             )
@@ -498,17 +531,24 @@ impl ChunkingContext for NodeJsChunkingContext {
         module: Vc<Box<dyn ChunkableModule>>,
         module_graph: Vc<ModuleGraph>,
         availability_info: AvailabilityInfo,
+        options: AsyncChunkOptions,
     ) -> Result<Vc<Box<dyn ChunkItem>>> {
         Ok(if self.await?.manifest_chunks {
-            let manifest_asset =
-                ManifestAsyncModule::new(module, module_graph, Vc::upcast(self), availability_info);
+            let manifest_asset = ManifestAsyncModule::new(
+                module,
+                module_graph,
+                Vc::upcast(self),
+                availability_info,
+                options,
+            );
             Vc::upcast(ManifestLoaderChunkItem::new(
                 manifest_asset,
                 module_graph,
                 Vc::upcast(self),
             ))
         } else {
-            let module = AsyncLoaderModule::new(module, Vc::upcast(self), availability_info);
+            let module =
+                AsyncLoaderModule::new(module, Vc::upcast(self), availability_info, options);
             Vc::upcast(module.as_chunk_item(module_graph, Vc::upcast(self)))
         })
     }
@@ -517,11 +557,12 @@ impl ChunkingContext for NodeJsChunkingContext {
     async fn async_loader_chunk_item_id(
         self: Vc<Self>,
         module: Vc<Box<dyn ChunkableModule>>,
+        options: AsyncChunkOptions,
     ) -> Result<Vc<ModuleId>> {
         Ok(if self.await?.manifest_chunks {
-            self.chunk_item_id_from_ident(ManifestLoaderChunkItem::asset_ident_for(module))
+            self.chunk_item_id_from_ident(ManifestLoaderChunkItem::asset_ident_for(module, options))
         } else {
-            self.chunk_item_id_from_ident(AsyncLoaderModule::asset_ident_for(module))
+            self.chunk_item_id_from_ident(AsyncLoaderModule::asset_ident_for(module, options))
         })
     }
 
@@ -20,6 +20,8 @@ use crate::NodeJsChunkingContext;
 pub(crate) struct EcmascriptBuildNodeChunk {
     chunking_context: ResolvedVc<NodeJsChunkingContext>,
     chunk: ResolvedVc<EcmascriptChunk>,
+    /// The name of the chunk group, used as a prefix for the file name.
+    name: Option<RcStr>,
 }
 
 #[turbo_tasks::value_impl]
@@ -29,10 +31,12 @@ impl EcmascriptBuildNodeChunk {
     pub fn new(
         chunking_context: ResolvedVc<NodeJsChunkingContext>,
         chunk: ResolvedVc<EcmascriptChunk>,
+        name: Option<RcStr>,
     ) -> Vc<Self> {
         EcmascriptBuildNodeChunk {
             chunking_context,
             chunk,
+            name,
         }
         .cell()
     }
@@ -80,9 +84,11 @@ impl OutputAsset for EcmascriptBuildNodeChunk {
     async fn path(self: Vc<Self>) -> Result<Vc<FileSystemPath>> {
         let this = self.await?;
         let ident = this.chunk.ident().with_modifier(modifier());
+        // The name of a split rule is more specific than the name of the chunk group
+        let name = this.chunk.await?.name.clone().or_else(|| this.name.clone());
         Ok(this
             .chunking_context
-            .chunk_path(Some(Vc::upcast(self)), ident, None, rcstr!(".js")))
+            .chunk_path(Some(Vc::upcast(self)), ident, name, rcstr!(".js")))
     }
 
     #[turbo_tasks::function]
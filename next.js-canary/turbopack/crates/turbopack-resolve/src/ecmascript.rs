@@ -1,5 +1,5 @@
 use anyhow::Result;
-use turbo_rcstr::rcstr;
+use turbo_rcstr::{RcStr, rcstr};
 use turbo_tasks::{ResolvedVc, Vc};
 use turbopack_core::{
     issue::IssueSource,
@@ -13,6 +13,7 @@ use turbopack_core::{
         origin::{ResolveOrigin, ResolveOriginExt},
         parse::Request,
         resolve,
+        trace::{ResolveTrace, resolve_trace},
     },
 };
 /// Retrieves the [ResolutionConditions] of the "into" and "in" package resolution options, so that
@@ -86,15 +87,38 @@ pub async fn apply_cjs_specific_options(options: Vc<ResolveOptions>) -> Result<V
     Ok(options.into())
 }
 
+/// Sets the `conditions` for the "exports" and "imports" fields, e.g. the conditions added by an
+/// import attribute.
+#[turbo_tasks::function]
+pub async fn apply_extra_conditions(
+    options: Vc<ResolveOptions>,
+    conditions: Vec<RcStr>,
+) -> Result<Vc<ResolveOptions>> {
+    if conditions.is_empty() {
+        return Ok(options);
+    }
+    let mut options: ResolveOptions = options.owned().await?;
+    for condition_map in get_condition_maps(&mut options) {
+        for condition in &conditions {
+            condition_map.insert(condition.clone(), ConditionValue::Set);
+        }
+    }
+    Ok(options.cell())
+}
+
+/// Resolves an ESM request. `extra_conditions` are set in addition to the conditions of the
+/// [ResolveOptions] of the `origin`.
 pub async fn esm_resolve(
     origin: Vc<Box<dyn ResolveOrigin>>,
     request: Vc<Request>,
     ty: EcmaScriptModulesReferenceSubType,
+    extra_conditions: Vec<RcStr>,
     is_optional: bool,
     issue_source: Option<IssueSource>,
 ) -> Result<Vc<ModuleResolveResult>> {
     let ty = ReferenceType::EcmaScriptModules(ty);
-    let options = apply_esm_specific_options(origin.resolve_options(ty.clone()).await?, ty.clone())
+    let options = apply_esm_specific_options(origin.resolve_options(ty.clone()).await?, ty.clone());
+    let options = apply_extra_conditions(options, extra_conditions)
         .resolve()
         .await?;
     specific_resolve(origin, request, options, ty, is_optional, issue_source).await
@@ -115,6 +139,42 @@ pub async fn cjs_resolve(
     specific_resolve(origin, request, options, ty, is_optional, issue_source).await
 }
 
+/// Traces resolving `request` with the options [esm_resolve] uses.
+#[turbo_tasks::function]
+pub async fn esm_resolve_trace(
+    origin: Vc<Box<dyn ResolveOrigin>>,
+    request: Vc<Request>,
+    ty: EcmaScriptModulesReferenceSubType,
+    extra_conditions: Vec<RcStr>,
+) -> Result<Vc<ResolveTrace>> {
+    let ty = ReferenceType::EcmaScriptModules(ty);
+    let options = apply_esm_specific_options(origin.resolve_options(ty.clone()).await?, ty.clone());
+    let options = apply_extra_conditions(options, extra_conditions);
+    Ok(resolve_trace(
+        origin.origin_path().await?.parent(),
+        ty,
+        request,
+        options,
+    ))
+}
+
+/// Traces resolving `request` with the options [cjs_resolve] uses.
+#[turbo_tasks::function]
+pub async fn cjs_resolve_trace(
+    origin: Vc<Box<dyn ResolveOrigin>>,
+    request: Vc<Request>,
+    ty: CommonJsReferenceSubType,
+) -> Result<Vc<ResolveTrace>> {
+    let ty = ReferenceType::CommonJs(ty);
+    let options = apply_cjs_specific_options(origin.resolve_options(ty.clone()).await?);
+    Ok(resolve_trace(
+        origin.origin_path().await?.parent(),
+        ty,
+        request,
+        options,
+    ))
+}
+
 #[turbo_tasks::function]
 pub async fn cjs_resolve_source(
     origin: ResolvedVc<Box<dyn ResolveOrigin>>,
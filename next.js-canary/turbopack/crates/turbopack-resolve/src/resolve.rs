@@ -237,6 +237,9 @@ async fn base_resolve_options(
             }
         } else {
             let mut mods = Vec::new();
+            if let Some(dir) = &opt.enable_yarn_pnp {
+                mods.push(ResolveModules::Pnp(dir.clone()));
+            }
             if let Some(dir) = &opt.enable_node_modules {
                 mods.push(ResolveModules::Nested(
                     dir.clone(),
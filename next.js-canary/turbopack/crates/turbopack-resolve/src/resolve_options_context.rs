@@ -32,6 +32,10 @@ pub struct ResolveOptionsContext {
     /// directory
     pub enable_node_modules: Option<FileSystemPath>,
     #[serde(default)]
+    /// Enable resolving of packages through the Yarn Plug'n'Play manifest
+    /// (`.pnp.cjs` or `.pnp.data.json`) in the provided directory
+    pub enable_yarn_pnp: Option<FileSystemPath>,
+    #[serde(default)]
     /// A specific path to a tsconfig.json file to use for resolving modules. If `None`, one will
     /// be looked up through the filesystem
     pub tsconfig_path: Option<FileSystemPath>,
@@ -25,12 +25,15 @@ use turbo_tasks_fs::{
 };
 use turbopack::{
     ModuleAssetContext,
-    css::chunk::CssChunkType,
+    css::{chunk::CssChunkType, sass::SassOptions},
     ecmascript::{TreeShakingMode, chunk::EcmascriptChunkType},
-    module_options::{EcmascriptOptionsContext, ModuleOptionsContext, TypescriptTransformOptions},
+    module_options::{
+        CssOptionsContext, EcmascriptOptionsContext, ModuleOptionsContext, ModuleRule,
+        ModuleRuleEffect, RuleCondition, TypescriptTransformOptions,
+    },
 };
 use turbopack_core::{
-    chunk::{ChunkingConfig, MangleType, MinifyType},
+    chunk::{ChunkSplitRule, ChunkingConfig, MangleType, MinifyType},
     compile_time_defines,
     compile_time_info::CompileTimeInfo,
     condition::ContextCondition,
@@ -260,6 +263,23 @@ struct TestOptions {
     scope_hoisting: Option<bool>,
     #[serde(default)]
     minify: bool,
+    resolve_conditions: Option<ResolveConditionsOptions>,
+    #[serde(default)]
+    enable_native_sass: bool,
+    /// See [ChunkingConfig::split_rules], applied to the ecmascript chunks.
+    #[serde(default)]
+    split_rules: Vec<ChunkSplitRule>,
+}
+
+/// Adds `conditions` to the requests of the modules in `directory`, see
+/// [ModuleRuleEffect::ResolveConditions].
+#[derive(
+    PartialEq, Eq, Debug, Serialize, Deserialize, TraceRawVcs, ValueDebugFormat, NonLocalValue,
+)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct ResolveConditionsOptions {
+    directory: RcStr,
+    conditions: Vec<RcStr>,
 }
 
 #[turbo_tasks::value]
@@ -396,8 +416,28 @@ async fn run_test_operation(prepared_test: ResolvedVc<PreparedTest>) -> Result<V
                 import_externals: true,
                 ..Default::default()
             },
+            css: CssOptionsContext {
+                enable_native_sass: options
+                    .enable_native_sass
+                    .then(|| SassOptions::default().resolved_cell()),
+                ..Default::default()
+            },
             environment: Some(env),
             tree_shaking_mode: options.tree_shaking_mode,
+            module_rules: options
+                .resolve_conditions
+                .iter()
+                .map(|resolve_conditions| {
+                    ModuleRule::new(
+                        RuleCondition::ResourcePathInDirectory(
+                            resolve_conditions.directory.to_string(),
+                        ),
+                        vec![ModuleRuleEffect::ResolveConditions(
+                            resolve_conditions.conditions.clone(),
+                        )],
+                    )
+                })
+                .collect(),
             rules: vec![(
                 ContextCondition::InDirectory("node_modules".into()),
                 ModuleOptionsContext {
@@ -475,6 +515,7 @@ async fn run_test_operation(prepared_test: ResolvedVc<PreparedTest>) -> Result<V
         Vc::<EcmascriptChunkType>::default().to_resolved().await?,
         ChunkingConfig {
             min_chunk_size: 10_000,
+            split_rules: options.split_rules.clone(),
             ..Default::default()
         },
     )
@@ -0,0 +1,30 @@
+import sheet from './style.css' with { type: 'css' }
+import text from './message.txt' with { type: 'text' }
+import bytes from './data.bin' with { type: 'bytes' }
+
+it('should import a CSS module script', () => {
+  // There is no `CSSStyleSheet` in Node.js, only the `cssText` is available
+  expect(sheet.cssText).toContain('.button')
+  expect(sheet.cssText).toContain('red')
+})
+
+it('should import text', () => {
+  expect(text).toBe('Hello world\n')
+})
+
+it('should import bytes', () => {
+  expect(Object.prototype.toString.call(bytes)).toBe('[object Uint8Array]')
+  expect(Array.from(bytes)).toEqual([0, 1, 2, 255])
+})
+
+it('should import with a type dynamically', async () => {
+  const { default: dynamicText } = await import('./message.txt', {
+    with: { type: 'text' },
+  })
+  expect(dynamicText).toBe(text)
+
+  const { default: dynamicBytes } = await import('./data.bin', {
+    with: { type: 'bytes' },
+  })
+  expect(Array.from(dynamicBytes)).toEqual([0, 1, 2, 255])
+})
@@ -0,0 +1,4 @@
+export const loadPrefetched = () =>
+  import(/* webpackPrefetch: true */ './prefetched.js')
+export const loadPreloaded = () =>
+  import(/* webpackPreload: true */ './preloaded.js')
@@ -0,0 +1,72 @@
+const contextPrototype = Object.getPrototypeOf(__turbopack_context__)
+
+const loadedChunks = new Set()
+const loadChunk = contextPrototype.l
+contextPrototype.l = function (chunkData) {
+  loadedChunks.add(typeof chunkData === 'string' ? chunkData : chunkData.path)
+  return loadChunk.call(this, chunkData)
+}
+
+const hints = []
+const hintChunks = contextPrototype.H
+contextPrototype.H = function (chunks, hint) {
+  hints.push(hint)
+  return hintChunks.call(this, chunks, hint)
+}
+
+it('should prefix the chunks with the webpackChunkName', async () => {
+  const { name } = await import(
+    /* webpackChunkName: "named-chunk" */ './named.js'
+  )
+  expect(name).toBe('named')
+  expect(
+    [...loadedChunks].some((chunk) =>
+      /(^|\/)named-chunk-[^/]*\.js$/.test(chunk)
+    )
+  ).toBe(true)
+})
+
+it('should load different modules with the same webpackChunkName', async () => {
+  const { name } = await import(
+    /* webpackChunkName: "named-chunk" */ './named-too.js'
+  )
+  expect(name).toBe('named-too')
+})
+
+it('should load a module imported both with and without a webpackChunkName', async () => {
+  const [named, unnamed] = await Promise.all([
+    import(/* webpackChunkName: "both-chunk" */ './both.js'),
+    import('./both.js'),
+  ])
+  expect(named.name).toBe('both')
+  expect(unnamed).toBe(named)
+})
+
+it('should emit resource hints when the importing module is evaluated', async () => {
+  const { loadPrefetched, loadPreloaded } = await import('./hints.js')
+  expect(hints).toContain('prefetch')
+  expect(hints).toContain('preload')
+  expect((await loadPrefetched()).name).toBe('prefetched')
+  expect((await loadPreloaded()).name).toBe('preloaded')
+})
+
+it('should not load chunks for eager imports', async () => {
+  const before = loadedChunks.size
+  const { name } = await import(/* webpackMode: "eager" */ './eager.js')
+  expect(name).toBe('eager')
+  expect(loadedChunks.size).toBe(before)
+})
+
+it('should load all modules of a lazy-once import at once', async () => {
+  const load = (name) =>
+    import(/* webpackMode: "lazy-once" */ `./lazy-once/${name}.js`)
+  expect((await load('a')).name).toBe('a')
+  const after = loadedChunks.size
+  expect((await load('b')).name).toBe('b')
+  expect(loadedChunks.size).toBe(after)
+})
+
+it('should provide the exports listed in webpackExports', async () => {
+  const { used } = await import(/* webpackExports: ["used"] */ './exports.js')
+  expect(used).toBe('used')
+})
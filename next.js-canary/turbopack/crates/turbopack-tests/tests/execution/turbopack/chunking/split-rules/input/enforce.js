@@ -0,0 +1 @@
+export { name } from './enforced/a.js'
@@ -0,0 +1,4 @@
+import { name as common } from './shared/common.js'
+import { name as onlyA } from './shared/only-a.js'
+
+export const names = [common, onlyA]
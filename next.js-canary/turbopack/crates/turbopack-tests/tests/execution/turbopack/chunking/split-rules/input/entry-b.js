@@ -0,0 +1,3 @@
+import { name as common } from './shared/common.js'
+
+export const names = [common]
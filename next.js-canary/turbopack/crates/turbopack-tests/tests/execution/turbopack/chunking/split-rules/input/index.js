@@ -0,0 +1,66 @@
+const contextPrototype = Object.getPrototypeOf(__turbopack_context__)
+
+let loadedChunks = []
+const loadChunk = contextPrototype.l
+contextPrototype.l = function (chunkData) {
+  loadedChunks.push(typeof chunkData === 'string' ? chunkData : chunkData.path)
+  return loadChunk.call(this, chunkData)
+}
+
+async function importWithChunks(load) {
+  loadedChunks = []
+  const exports = await load()
+  return { exports, chunks: loadedChunks }
+}
+
+function chunksNamed(chunks, name) {
+  const regex = new RegExp(`(^|/)${name}-[^/]*\\.js$`)
+  return chunks.filter((chunk) => regex.test(chunk))
+}
+
+it('should apply the rule with the highest priority', async () => {
+  const { exports, chunks } = await importWithChunks(
+    () => import('./priority.js')
+  )
+  expect(exports.name).toBe('vendor-special')
+  expect(chunksNamed(chunks, 'special')).toHaveLength(1)
+  expect(chunksNamed(chunks, 'vendor')).toHaveLength(0)
+})
+
+it('should leave modules smaller than minSize to the heuristics', async () => {
+  const { exports, chunks } = await importWithChunks(
+    () => import('./min-size.js')
+  )
+  expect(exports.name).toBe('small-a')
+  expect(chunksNamed(chunks, 'big-only')).toHaveLength(0)
+})
+
+it('should ignore minSize for enforced rules', async () => {
+  const { exports, chunks } = await importWithChunks(
+    () => import('./enforce.js')
+  )
+  expect(exports.name).toBe('enforced-a')
+  expect(chunksNamed(chunks, 'enforced')).toHaveLength(1)
+})
+
+it('should split the modules of a rule by maxSize', async () => {
+  const { exports, chunks } = await importWithChunks(
+    () => import('./max-size.js')
+  )
+  expect(exports.names).toEqual(['limited-a', 'limited-b'])
+  expect(chunksNamed(chunks, 'limited-0')).toHaveLength(1)
+  expect(chunksNamed(chunks, 'limited-1')).toHaveLength(1)
+})
+
+it('should reuse the chunks of modules shared with other chunk groups', async () => {
+  const a = await importWithChunks(() => import('./entry-a.js'))
+  expect(a.exports.names).toEqual(['shared-common', 'shared-only-a'])
+  const sharedA = chunksNamed(a.chunks, 'shared-\\d+')
+  expect(sharedA).toHaveLength(2)
+
+  const b = await importWithChunks(() => import('./entry-b.js'))
+  expect(b.exports.names).toEqual(['shared-common'])
+  const sharedB = chunksNamed(b.chunks, 'shared-\\d+')
+  expect(sharedB).toHaveLength(1)
+  expect(sharedA).toContain(sharedB[0])
+})
@@ -0,0 +1,4 @@
+import { name as a } from './limited/a.js'
+import { name as b } from './limited/b.js'
+
+export const names = [a, b]
@@ -0,0 +1 @@
+export { name } from './small/a.js'
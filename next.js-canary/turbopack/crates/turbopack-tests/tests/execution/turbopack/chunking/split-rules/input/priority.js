@@ -0,0 +1 @@
+export { name } from './vendor/special.js'
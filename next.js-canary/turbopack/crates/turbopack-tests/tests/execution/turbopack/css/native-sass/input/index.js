@@ -0,0 +1,22 @@
+import scss from './styles.module.scss'
+import sass from './styles.module.sass'
+
+it('should compile SCSS modules natively', () => {
+  expect(scss).toHaveProperty('button')
+  expect(scss).toHaveProperty('button-primary')
+  expect(scss).toHaveProperty('from-partial')
+  expect(scss).toHaveProperty('from-package')
+  expect(scss).not.toHaveProperty('unused')
+})
+
+it('should compile indented Sass modules natively', () => {
+  expect(sass).toHaveProperty('card')
+  expect(sass).toHaveProperty('card-title')
+  expect(sass).toHaveProperty('from-partial')
+  expect(sass).toHaveProperty('from-package')
+})
+
+it('should keep the class names of both modules apart', () => {
+  expect(scss.button).not.toBe(sass.card)
+  expect(scss['from-partial']).not.toBe(sass['from-partial'])
+})
@@ -0,0 +1,14 @@
+import withCondition from 'import-attribute-condition/target' with { condition: 'custom' }
+import withoutCondition from 'import-attribute-condition/target'
+
+it('should add the conditions of the import attribute', () => {
+  expect(withCondition).toBe('custom')
+  expect(withoutCondition).toBe('default')
+})
+
+it('should add the conditions of the import attribute to dynamic imports', async () => {
+  const { default: value } = await import('import-attribute-condition/target', {
+    with: { condition: 'custom' },
+  })
+  expect(value).toBe('custom')
+})
@@ -0,0 +1,69 @@
+it('should load matched modules lazily', async () => {
+  const modules = import.meta.glob('./modules/*.js')
+  expect(Object.keys(modules)).toEqual([
+    './modules/a.js',
+    './modules/b.js',
+    './modules/b.test.js',
+  ])
+  expect(typeof modules['./modules/a.js']).toBe('function')
+  const a = await modules['./modules/a.js']()
+  expect(a.default).toBe('a')
+  expect(a.name).toBe('module a')
+})
+
+it('should include modules in nested directories', () => {
+  const modules = import.meta.glob('./modules/**/*.js', { eager: true })
+  expect(Object.keys(modules)).toEqual([
+    './modules/a.js',
+    './modules/b.js',
+    './modules/b.test.js',
+    './modules/nested/c.js',
+  ])
+})
+
+it('should load matched modules eagerly', () => {
+  const modules = import.meta.glob('./modules/*.js', { eager: true })
+  expect(modules['./modules/a.js'].default).toBe('a')
+  expect(modules['./modules/b.js'].name).toBe('module b')
+})
+
+it('should pick the named import', async () => {
+  const eager = import.meta.glob('./modules/*.js', {
+    eager: true,
+    import: 'name',
+  })
+  expect(eager['./modules/a.js']).toBe('module a')
+
+  const lazy = import.meta.glob('./modules/*.js', { import: 'default' })
+  expect(await lazy['./modules/b.js']()).toBe('b')
+})
+
+it('should exclude modules matched by negated patterns', () => {
+  const modules = import.meta.glob(
+    ['./modules/**/*.js', '!./modules/**/*.test.js'],
+    { eager: true }
+  )
+  expect(Object.keys(modules)).toEqual([
+    './modules/a.js',
+    './modules/b.js',
+    './modules/nested/c.js',
+  ])
+})
+
+it('should not include the importing module', () => {
+  const modules = import.meta.glob('./*.js')
+  expect(Object.keys(modules)).toEqual([])
+})
+
+it('should add the query to the requests', () => {
+  const plain = import.meta.glob('./modules/*.js', { eager: true })
+  const withQuery = import.meta.glob('./modules/*.js', {
+    eager: true,
+    query: { variant: 'other', skip: false },
+  })
+  // The keys don't contain the query
+  expect(Object.keys(withQuery)).toEqual(Object.keys(plain))
+  // but the request does, so it's a different module instance
+  expect(withQuery['./modules/a.js'].default).toBe('a')
+  expect(withQuery['./modules/a.js']).not.toBe(plain['./modules/a.js'])
+})
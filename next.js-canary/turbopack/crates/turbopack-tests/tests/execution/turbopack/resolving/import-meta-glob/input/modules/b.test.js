@@ -0,0 +1,2 @@
+export default 'b test'
+export const name = 'module b test'
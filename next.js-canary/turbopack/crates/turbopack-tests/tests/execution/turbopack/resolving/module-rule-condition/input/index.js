@@ -0,0 +1,10 @@
+import main from './main.js'
+import worker from './worker/value.js'
+
+it('should add the conditions of a module rule to the requests of matched modules', () => {
+  expect(worker).toBe('custom')
+})
+
+it('should not add the conditions to the requests of other modules', () => {
+  expect(main).toBe('default')
+})
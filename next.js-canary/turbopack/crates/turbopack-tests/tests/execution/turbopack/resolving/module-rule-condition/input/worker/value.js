@@ -0,0 +1 @@
+export { default } from 'module-rule-condition/target'
@@ -658,6 +658,14 @@ function getWorkerBlobURL(_chunks) {
     throw new Error('Worker blobs are not implemented yet for Node.js');
 }
 nodeContextPrototype.b = getWorkerBlobURL;
+function getWorkerScriptURL(_scriptPath) {
+    throw new Error('Worker scripts are not implemented yet for Node.js');
+}
+nodeContextPrototype.o = getWorkerScriptURL;
+function hintChunks(_chunks, _hint) {
+    // Resource hints only apply to browsers.
+}
+nodeContextPrototype.H = hintChunks;
 function instantiateModule(id, sourceType, sourceData) {
     const moduleFactory = moduleFactories.get(id);
     if (typeof moduleFactory !== 'function') {
@@ -616,6 +616,18 @@ function loadChunkPath(sourceType, sourceData, chunkPath) {
     const url = getChunkRelativeUrl(chunkPath);
     return loadChunkByUrlInternal(sourceType, sourceData, url);
 }
+/**
+ * Emits a resource hint for chunks that are likely loaded later, e.g. for
+ * `import()`s with a `webpackPrefetch` or `webpackPreload` comment.
+ */ function hintChunks(chunks, hint) {
+    if (BACKEND.hintChunk == null) {
+        return;
+    }
+    for (const chunkData of chunks){
+        BACKEND.hintChunk(getChunkRelativeUrl(getChunkPath(chunkData)), hint);
+    }
+}
+browserContextPrototype.H = hintChunks;
 /**
  * Returns an absolute url to an asset.
  */ function resolvePathFromModule(moduleId) {
@@ -647,6 +659,15 @@ importScripts(...self.TURBOPACK_NEXT_CHUNK_URLS.map(c => self.TURBOPACK_WORKER_L
     return URL.createObjectURL(blob);
 }
 browserContextPrototype.b = getWorkerBlobURL;
+/**
+ * Returns the URL of the bootstrap script of a shared or service worker. Unlike
+ * blob URLs, it's the same for every document, which is required to share a
+ * worker or to register a service worker.
+ * @param scriptPath path of the bootstrap script
+ */ function getWorkerScriptURL(scriptPath) {
+    return getChunkRelativeUrl(scriptPath);
+}
+browserContextPrototype.o = getWorkerScriptURL;
 /**
  * Instantiates a runtime module.
  */ function instantiateRuntimeModule(moduleId, chunkPath) {
@@ -1587,6 +1608,30 @@ let BACKEND;
         async loadWebAssemblyModule (_sourceType, _sourceData, wasmChunkPath, _edgeModule) {
             const req = fetchWebAssembly(wasmChunkPath);
             return await WebAssembly.compileStreaming(req);
+        },
+        hintChunk (chunkUrl, hint) {
+            if (typeof document === 'undefined') {
+                // Hints are not supported in web workers.
+                return;
+            }
+            // Chunks that are already loaded or loading don't need a hint.
+            if (chunkResolvers.get(chunkUrl)?.loadingStarted) {
+                return;
+            }
+            if (document.querySelector(`link[href="${chunkUrl}"]`) != null) {
+                return;
+            }
+            const link = document.createElement('link');
+            link.rel = hint;
+            link.href = chunkUrl;
+            if (hint === 'preload') {
+                if (isCss(chunkUrl)) {
+                    link.as = 'style';
+                } else if (isJs(chunkUrl)) {
+                    link.as = 'script';
+                }
+            }
+            document.head.appendChild(link);
         }
     };
     function getOrCreateResolver(chunkUrl) {
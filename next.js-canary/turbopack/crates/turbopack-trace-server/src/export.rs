@@ -0,0 +1,357 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    sync::Arc,
+};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    FxIndexMap, FxIndexSet, reader::TraceReader, span::SpanIndex, span_ref::SpanRef, store::Store,
+    store_container::StoreContainer, timestamp::Timestamp,
+};
+
+/// The formats a trace can be converted to, so it can be opened in standard tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// The Chrome Trace Event format, which can be opened in `chrome://tracing`, Perfetto or the
+    /// Chrome DevTools performance panel. Contains the timeline of all spans.
+    ChromeTrace,
+    /// The speedscope format (https://www.speedscope.app). Contains a self time profile and a
+    /// profile aggregated by span name.
+    Speedscope,
+}
+
+impl ExportFormat {
+    /// Picks the format based on the file name: `*.speedscope.json` is written in the speedscope
+    /// format, other `*.json` files in the Chrome Trace Event format.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let file_name = path.file_name()?.to_str()?;
+        if file_name.ends_with(".speedscope.json") {
+            Some(Self::Speedscope)
+        } else if file_name.ends_with(".json") {
+            Some(Self::ChromeTrace)
+        } else {
+            None
+        }
+    }
+}
+
+/// Reads a Turbopack, Next.js or heaptrack trace file and writes it to `output` in the given
+/// format.
+pub fn convert_trace_file(input: &Path, output: &Path, format: ExportFormat) -> Result<()> {
+    let store = Arc::new(StoreContainer::new());
+    TraceReader::read_to_end(store.clone(), input.to_path_buf())?;
+    let store = store.read();
+    let file =
+        File::create(output).with_context(|| format!("Unable to create {}", output.display()))?;
+    let mut writer = BufWriter::new(file);
+    let name = input
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    match format {
+        ExportFormat::ChromeTrace => write_chrome_trace(&store, &mut writer)?,
+        ExportFormat::Speedscope => write_speedscope(&store, &name, &mut writer)?,
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct ChromeTraceEvent<'a> {
+    name: &'a str,
+    cat: &'a str,
+    ph: &'static str,
+    ts: f64,
+    dur: f64,
+    pid: u32,
+    tid: usize,
+    args: FxIndexMap<&'a str, Value>,
+}
+
+/// Writes all spans as complete events in the Chrome Trace Event format. Spans that run
+/// concurrently are spread over multiple threads ("lanes"), since events on a single thread need to
+/// be nested.
+pub fn write_chrome_trace(store: &Store, mut writer: impl Write) -> Result<()> {
+    let mut spans = all_spans(store);
+    spans.sort_by_key(|span| (span.start(), span.index()));
+    let lanes = assign_lanes(&spans);
+
+    writer.write_all(b"{\"displayTimeUnit\":\"ms\",\"traceEvents\":[")?;
+    writer.write_all(
+        b"{\"name\":\"process_name\",\"ph\":\"M\",\"pid\":1,\"args\":{\"name\":\"turbopack\"}}",
+    )?;
+    for (span, lane) in spans.iter().zip(lanes) {
+        let (category, name) = span.nice_name();
+        let mut args: FxIndexMap<&str, Value> = span
+            .args()
+            .map(|(key, value)| (key, Value::from(value)))
+            .collect();
+        args.insert(
+            "self time (μs)",
+            Value::from(span.corrected_self_time().as_micros()),
+        );
+        writer.write_all(b",")?;
+        serde_json::to_writer(
+            &mut writer,
+            &ChromeTraceEvent {
+                name,
+                cat: category,
+                ph: "X",
+                ts: span.start().as_micros(),
+                dur: span.end().saturating_sub(span.start()).as_micros(),
+                pid: 1,
+                tid: lane,
+                args,
+            },
+        )?;
+    }
+    writer.write_all(b"]}")?;
+    Ok(())
+}
+
+fn all_spans(store: &Store) -> Vec<SpanRef<'_>> {
+    let mut spans = Vec::new();
+    let mut queue = store.root_spans().collect::<Vec<_>>();
+    while let Some(span) = queue.pop() {
+        queue.extend(span.children());
+        spans.push(span);
+    }
+    spans
+}
+
+/// Assigns each span (sorted by start) to a lane, so that spans on the same lane are properly
+/// nested. A span is placed on the lane of its parent when the parent is the innermost open span
+/// there, otherwise on a free or new lane.
+fn assign_lanes(spans: &[SpanRef<'_>]) -> Vec<usize> {
+    // Every lane is a stack of the open spans with their end
+    let mut lanes: Vec<Vec<(Timestamp, SpanIndex)>> = Vec::new();
+    spans
+        .iter()
+        .map(|span| {
+            let start = span.start();
+            let end = span.end().max(start);
+            let parent = span.parent().map(|parent| parent.index());
+            let mut parent_lane = None;
+            let mut free_lane = None;
+            for (i, lane) in lanes.iter_mut().enumerate() {
+                while lane.last().is_some_and(|&(open_end, _)| open_end <= start) {
+                    lane.pop();
+                }
+                match lane.last() {
+                    None => {
+                        free_lane.get_or_insert(i);
+                    }
+                    Some(&(open_end, open)) => {
+                        if Some(open) == parent && end <= open_end {
+                            parent_lane = Some(i);
+                            break;
+                        }
+                    }
+                }
+            }
+            let lane = parent_lane.or(free_lane).unwrap_or_else(|| {
+                lanes.push(Vec::new());
+                lanes.len() - 1
+            });
+            lanes[lane].push((end, span.index()));
+            lane
+        })
+        .collect()
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpeedscopeFile<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    name: &'a str,
+    exporter: &'static str,
+    shared: SpeedscopeShared<'a>,
+    profiles: Vec<SpeedscopeProfile>,
+}
+
+#[derive(Serialize)]
+struct SpeedscopeShared<'a> {
+    frames: Vec<SpeedscopeFrame<'a>>,
+}
+
+#[derive(Serialize)]
+struct SpeedscopeFrame<'a> {
+    name: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpeedscopeProfile {
+    #[serde(rename = "type")]
+    ty: &'static str,
+    name: &'static str,
+    unit: &'static str,
+    start_value: f64,
+    end_value: f64,
+    samples: Vec<Vec<usize>>,
+    weights: Vec<f64>,
+}
+
+impl SpeedscopeProfile {
+    fn sampled(name: &'static str) -> Self {
+        Self {
+            ty: "sampled",
+            name,
+            unit: "microseconds",
+            start_value: 0.0,
+            end_value: 0.0,
+            samples: Vec::new(),
+            weights: Vec::new(),
+        }
+    }
+
+    fn add_sample(&mut self, stack: Vec<usize>, weight: Timestamp) {
+        let weight = weight.as_micros();
+        self.samples.push(stack);
+        self.weights.push(weight);
+        self.end_value += weight;
+    }
+}
+
+/// Writes the spans in the speedscope format with two profiles:
+///
+/// * "Self time": one sample per span with its stack of span names, weighted by the self time of
+///   the span (corrected for concurrency).
+/// * "Aggregated by name": one sample per span name, weighted by the summed self time of all spans
+///   with that name.
+pub fn write_speedscope(store: &Store, name: &str, writer: impl Write) -> Result<()> {
+    let mut frames = FxIndexSet::default();
+
+    let mut self_time = SpeedscopeProfile::sampled("Self time");
+    let mut by_name: FxIndexMap<&str, Timestamp> = FxIndexMap::default();
+    let mut stack = Vec::new();
+    let mut queue = store.root_spans().map(|span| (span, 0)).collect::<Vec<_>>();
+    queue.reverse();
+    while let Some((span, depth)) = queue.pop() {
+        stack.truncate(depth);
+        stack.push(frames.insert_full(span.nice_name().1).0);
+        let span_self_time = span.corrected_self_time();
+        if !span_self_time.is_zero() {
+            self_time.add_sample(stack.clone(), span_self_time);
+            *by_name.entry(span.group_name().1).or_default() += span_self_time;
+        }
+        queue.extend(span.children().rev().map(|child| (child, depth + 1)));
+    }
+
+    let mut aggregated = SpeedscopeProfile::sampled("Aggregated by name");
+    by_name.sort_by(|_, a, _, b| b.cmp(a));
+    for (group_name, time) in by_name {
+        aggregated.add_sample(vec![frames.insert_full(group_name).0], time);
+    }
+
+    serde_json::to_writer(
+        writer,
+        &SpeedscopeFile {
+            schema: "https://www.speedscope.app/file-format-schema.json",
+            name,
+            exporter: "turbopack-trace-server",
+            shared: SpeedscopeShared {
+                frames: frames
+                    .into_iter()
+                    .map(|name| SpeedscopeFrame { name })
+                    .collect(),
+            },
+            profiles: vec![self_time, aggregated],
+        },
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rustc_hash::FxHashSet;
+
+    use super::*;
+
+    /// Creates a store with a `build` span that has two concurrent `task` children.
+    fn create_store() -> Store {
+        let mut store = Store::new();
+        let mut outdated_spans = FxHashSet::default();
+        let mut add_span = |store: &mut Store, parent, start, end, name: &str| {
+            let span = store.add_span(
+                parent,
+                Timestamp::from_micros(start),
+                "".into(),
+                name.into(),
+                vec![],
+                &mut outdated_spans,
+            );
+            store.add_self_time(
+                span,
+                Timestamp::from_micros(start),
+                Timestamp::from_micros(end),
+                &mut outdated_spans,
+            );
+            store.complete_span(span);
+            span
+        };
+        let build = add_span(&mut store, None, 0, 10, "build");
+        add_span(&mut store, Some(build), 10, 30, "task");
+        add_span(&mut store, Some(build), 15, 25, "task");
+        store
+    }
+
+    #[test]
+    fn chrome_trace() {
+        let store = create_store();
+        let mut output = Vec::new();
+        write_chrome_trace(&store, &mut output).unwrap();
+        let trace: Value = serde_json::from_slice(&output).unwrap();
+        let events = trace["traceEvents"].as_array().unwrap();
+        let spans = events
+            .iter()
+            .filter(|event| event["ph"] == "X")
+            .map(|event| {
+                (
+                    event["name"].as_str().unwrap(),
+                    event["ts"].as_f64().unwrap(),
+                    event["dur"].as_f64().unwrap(),
+                    event["tid"].as_u64().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        // The overlapping task is moved to a separate lane
+        assert_eq!(
+            spans,
+            vec![
+                ("build", 0.0, 30.0, 0),
+                ("task", 10.0, 20.0, 0),
+                ("task", 15.0, 10.0, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn speedscope() {
+        let store = create_store();
+        let mut output = Vec::new();
+        write_speedscope(&store, "trace", &mut output).unwrap();
+        let file: Value = serde_json::from_slice(&output).unwrap();
+        let frames = file["shared"]["frames"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|frame| frame["name"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(frames, vec!["build", "task"]);
+        let profiles = file["profiles"].as_array().unwrap();
+        assert_eq!(profiles[0]["name"], "Self time");
+        assert_eq!(
+            profiles[0]["samples"],
+            serde_json::json!([[0], [0, 1], [0, 1]])
+        );
+        assert_eq!(profiles[1]["name"], "Aggregated by name");
+        assert_eq!(profiles[1]["samples"], serde_json::json!([[1], [0]]));
+    }
+}
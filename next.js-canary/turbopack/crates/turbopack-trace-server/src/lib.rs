@@ -5,9 +5,11 @@ use std::{hash::BuildHasherDefault, path::PathBuf, sync::Arc};
 
 use rustc_hash::FxHasher;
 
+pub use self::export::{ExportFormat, convert_trace_file};
 use self::{reader::TraceReader, server::serve, store_container::StoreContainer};
 
 mod bottom_up;
+mod export;
 mod reader;
 mod self_time_tree;
 mod server;
@@ -28,6 +30,7 @@ mod viewer;
     reason = "It's actually used, not sure why it is marked as dead code"
 )]
 type FxIndexMap<K, V> = indexmap::IndexMap<K, V, BuildHasherDefault<FxHasher>>;
+type FxIndexSet<T> = indexmap::IndexSet<T, BuildHasherDefault<FxHasher>>;
 
 pub fn start_turbopack_trace_server(path: PathBuf) {
     let store = Arc::new(StoreContainer::new());
@@ -1,14 +1,20 @@
 #![feature(iter_intersperse)]
 #![feature(box_patterns)]
 
-use std::{hash::BuildHasherDefault, sync::Arc};
+use std::{hash::BuildHasherDefault, path::Path, sync::Arc};
 
 use indexmap::{IndexMap, IndexSet};
 use rustc_hash::FxHasher;
 
-use self::{reader::TraceReader, server::serve, store_container::StoreContainer};
+use self::{
+    export::{ExportFormat, convert_trace_file},
+    reader::TraceReader,
+    server::serve,
+    store_container::StoreContainer,
+};
 
 mod bottom_up;
+mod export;
 mod reader;
 mod self_time_tree;
 mod server;
@@ -34,6 +40,23 @@ fn main() {
     let arg = iter
         .next()
         .expect("missing positional argument for the trace file path");
+
+    // turbo-trace-server convert <trace file> <output.json | output.speedscope.json>
+    if arg == "convert" {
+        let input = iter
+            .next()
+            .expect("missing positional argument for the trace file path");
+        let output = iter
+            .next()
+            .expect("missing positional argument for the output file path");
+        let output = Path::new(output);
+        let format = ExportFormat::from_path(output).expect(
+            "the output file must end with .json (Chrome trace) or .speedscope.json (speedscope)",
+        );
+        convert_trace_file(Path::new(input), output, format).unwrap();
+        return;
+    }
+
     let port = iter.next().map_or(5747, |s| s.parse().unwrap());
 
     let store = Arc::new(StoreContainer::new());
@@ -13,7 +13,7 @@ use std::{
     time::{Duration, Instant},
 };
 
-use anyhow::Result;
+use anyhow::{Result, bail};
 use flate2::bufread::GzDecoder;
 
 use crate::{
@@ -123,14 +123,33 @@ impl TraceFile {
 pub struct TraceReader {
     store: Arc<StoreContainer>,
     path: PathBuf,
+    /// Keep waiting for more data (or a new file) at the end of the file.
+    follow: bool,
 }
 
 impl TraceReader {
     pub fn spawn(store: Arc<StoreContainer>, path: PathBuf) -> JoinHandle<()> {
-        let mut reader = Self { store, path };
+        let mut reader = Self {
+            store,
+            path,
+            follow: true,
+        };
         std::thread::spawn(move || reader.run())
     }
 
+    /// Reads the trace file once until the end, without waiting for more data.
+    pub fn read_to_end(store: Arc<StoreContainer>, path: PathBuf) -> Result<()> {
+        let mut reader = Self {
+            store,
+            path,
+            follow: false,
+        };
+        if !reader.try_read() {
+            bail!("Unable to read trace file at {:?}", reader.path);
+        }
+        Ok(())
+    }
+
     pub fn run(&mut self) {
         let mut file_warning_printed = false;
         loop {
@@ -282,6 +301,12 @@ impl TraceReader {
                                 }
                             }
                             if current_read >= stop_at {
+                                if !self.follow {
+                                    println!(
+                                        "Stopped reading file as requested by STOP_AT env var."
+                                    );
+                                    return true;
+                                }
                                 println!(
                                     "Stopped reading file as requested by STOP_AT env var. \
                                      Waiting for new file..."
@@ -333,6 +358,9 @@ impl TraceReader {
                 );
             }
         }
+        if !self.follow {
+            return Some(true);
+        }
         loop {
             // No more data to read, sleep for a while to wait for more data
             thread::sleep(Duration::from_millis(100));
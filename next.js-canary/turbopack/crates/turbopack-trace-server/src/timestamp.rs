@@ -32,6 +32,10 @@ impl Timestamp {
     pub fn saturating_sub(self, rhs: Self) -> Self {
         Self(self.0.saturating_sub(rhs.0))
     }
+
+    pub fn as_micros(&self) -> f64 {
+        self.0 as f64 / DUR_VALUE_MICROSECOND as f64
+    }
 }
 
 impl Debug for Timestamp {
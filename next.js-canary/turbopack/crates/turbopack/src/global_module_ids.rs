@@ -35,7 +35,7 @@ pub async fn get_global_module_id_strategy(
                 if let (
                     _,
                     &RefData {
-                        chunking_type: ChunkingType::Async,
+                        chunking_type: ChunkingType::Async(ref options),
                         ..
                     },
                 ) = parent
@@ -43,7 +43,7 @@ pub async fn get_global_module_id_strategy(
                     let module =
                         ResolvedVc::try_sidecast::<Box<dyn ChunkableModule>>(current.module)
                             .context("expected chunkable module for async reference")?;
-                    async_idents.push(AsyncLoaderModule::asset_ident_for(*module));
+                    async_idents.push(AsyncLoaderModule::asset_ident_for(*module, options.clone()));
                 }
                 Ok(())
             })
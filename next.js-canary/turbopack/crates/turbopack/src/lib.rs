@@ -14,12 +14,14 @@ pub mod module_options;
 pub mod transition;
 
 use anyhow::{Result, bail};
-use css::{CssModuleAsset, ModuleCssAsset};
+use css::{CssModuleAsset, CssModuleScriptAsset, ModuleCssAsset, sass::SassSource};
 use ecmascript::{
     EcmascriptModuleAsset, EcmascriptModuleAssetType, TreeShakingMode,
+    bytes::BytesContentFileSource,
     chunk::EcmascriptChunkPlaceable,
     references::{FollowExportsResult, follow_reexports},
     side_effect_optimization::facade::module::EcmascriptModuleFacadeModule,
+    text::TextContentFileSource,
 };
 use graph::{AggregatedGraph, AggregatedGraphNodeContent, aggregate};
 use module_options::{ModuleOptions, ModuleOptionsContext, ModuleRuleEffect, ModuleType};
@@ -58,8 +60,11 @@ use turbopack_ecmascript::{
     tree_shake::asset::EcmascriptModulePartAsset,
 };
 use turbopack_json::JsonModuleAsset;
+use turbopack_resolve::{
+    ecmascript::apply_extra_conditions, resolve_options_context::ResolveOptionsContext,
+    typescript::type_resolve,
+};
 pub use turbopack_resolve::{resolve::resolve_options, resolve_options_context};
-use turbopack_resolve::{resolve_options_context::ResolveOptionsContext, typescript::type_resolve};
 use turbopack_static::{css::StaticUrlCssModule, ecma::StaticUrlJsModule};
 use turbopack_wasm::{module_asset::WebAssemblyModuleAsset, source::WebAssemblySource};
 
@@ -240,6 +245,11 @@ async fn apply_module_type(
                 .to_resolved()
                 .await?,
         ),
+        ModuleType::CssModuleScript => ResolvedVc::upcast(
+            CssModuleScriptAsset::new(*source, Vc::upcast(module_asset_context))
+                .to_resolved()
+                .await?,
+        ),
 
         ModuleType::Css { ty, environment } => ResolvedVc::upcast(
             CssModuleAsset::new(
@@ -252,6 +262,9 @@ async fn apply_module_type(
             .to_resolved()
             .await?,
         ),
+        ModuleType::Sass { .. } => {
+            bail!("Sass sources are compiled to CSS before a module is created")
+        }
         ModuleType::StaticUrlJs => {
             ResolvedVc::upcast(StaticUrlJsModule::new(*source).to_resolved().await?)
         }
@@ -320,6 +333,11 @@ pub struct ModuleAssetContext {
     /// Whether to replace external resolutions with CachedExternalModules. Used with
     /// ModuleOptionsContext.enable_externals_tracing to handle transitive external dependencies.
     replace_externals: bool,
+    /// The context this context was derived from by [ModuleAssetContext::with_resolve_conditions].
+    base: Option<ResolvedVc<ModuleAssetContext>>,
+    /// Conditions added to the requests of the modules of this context, see
+    /// [ModuleRuleEffect::ResolveConditions].
+    resolve_conditions: Vec<RcStr>,
 }
 
 #[turbo_tasks::value_impl]
@@ -340,6 +358,8 @@ impl ModuleAssetContext {
             transition: None,
             layer,
             replace_externals: true,
+            base: None,
+            resolve_conditions: Vec::new(),
         })
     }
 
@@ -360,6 +380,8 @@ impl ModuleAssetContext {
             layer,
             transition: Some(transition),
             replace_externals: true,
+            base: None,
+            resolve_conditions: Vec::new(),
         })
     }
 
@@ -379,9 +401,36 @@ impl ModuleAssetContext {
             transition: None,
             layer,
             replace_externals: false,
+            base: None,
+            resolve_conditions: Vec::new(),
         })
     }
 
+    /// Returns the context for a module whose requests use the additional `conditions`, see
+    /// [ModuleRuleEffect::ResolveConditions]. The conditions of this context are replaced, so the
+    /// modules referenced by that module don't inherit them.
+    #[turbo_tasks::function]
+    async fn with_resolve_conditions(self: Vc<Self>, conditions: Vec<RcStr>) -> Result<Vc<Self>> {
+        let this = self.await?;
+        if let Some(base) = this.base {
+            return Ok(base.with_resolve_conditions(conditions));
+        }
+        if conditions.is_empty() {
+            return Ok(self);
+        }
+        Ok(Self::cell(ModuleAssetContext {
+            transitions: this.transitions,
+            compile_time_info: this.compile_time_info,
+            module_options_context: this.module_options_context,
+            resolve_options_context: this.resolve_options_context,
+            layer: this.layer.clone(),
+            transition: this.transition,
+            replace_externals: this.replace_externals,
+            base: Some(self.to_resolved().await?),
+            resolve_conditions: conditions,
+        }))
+    }
+
     #[turbo_tasks::function]
     pub fn module_options_context(&self) -> Vc<ModuleOptionsContext> {
         *self.module_options_context
@@ -448,7 +497,7 @@ impl ModuleAssetContext {
         source: ResolvedVc<Box<dyn Source>>,
         reference_type: ReferenceType,
     ) -> Result<Vc<ProcessResult>> {
-        process_default(self, source, reference_type, Vec::new()).await
+        process_default(self, source, reference_type, Vec::new(), Vec::new()).await
     }
 }
 
@@ -457,6 +506,7 @@ async fn process_default(
     source: ResolvedVc<Box<dyn Source>>,
     reference_type: ReferenceType,
     processed_rules: Vec<usize>,
+    resolve_conditions: Vec<RcStr>,
 ) -> Result<Vc<ProcessResult>> {
     let span = tracing::info_span!(
         "process module",
@@ -474,6 +524,7 @@ async fn process_default(
         source,
         reference_type,
         processed_rules,
+        resolve_conditions,
     )
     .instrument(span)
     .await
@@ -484,6 +535,7 @@ async fn process_default_internal(
     source: ResolvedVc<Box<dyn Source>>,
     reference_type: ReferenceType,
     processed_rules: Vec<usize>,
+    resolve_conditions: Vec<RcStr>,
 ) -> Result<Vc<ProcessResult>> {
     let ident = source.ident().resolve().await?;
     let path_ref = ident.path().await?;
@@ -505,18 +557,41 @@ async fn process_default_internal(
     };
 
     let mut has_type_attribute = false;
+    // Collected from the rules matching the source and the sources it was transformed from
+    let mut resolve_conditions = resolve_conditions;
 
     let mut current_source = source;
-    let mut current_module_type = match &reference_type {
-        ReferenceType::EcmaScriptModules(EcmaScriptModulesReferenceSubType::ImportWithType(ty)) => {
-            has_type_attribute = true;
-
-            match ty {
-                ImportWithType::Json => Some(ModuleType::Json),
+    let mut current_module_type = None;
+    if let ReferenceType::EcmaScriptModules(EcmaScriptModulesReferenceSubType::ImportWithType(ty)) =
+        &reference_type
+    {
+        has_type_attribute = true;
+
+        current_module_type = match ty {
+            ImportWithType::Json => Some(ModuleType::Json),
+            ImportWithType::Css => Some(ModuleType::CssModuleScript),
+            // Text and bytes imports are plain JS modules generated from the content of the
+            // source.
+            ImportWithType::Text | ImportWithType::Bytes => {
+                let content_source: ResolvedVc<Box<dyn Source>> =
+                    if matches!(ty, ImportWithType::Text) {
+                        ResolvedVc::upcast(TextContentFileSource::new(*source).to_resolved().await?)
+                    } else {
+                        ResolvedVc::upcast(
+                            BytesContentFileSource::new(*source).to_resolved().await?,
+                        )
+                    };
+                return Box::pin(process_default(
+                    module_asset_context,
+                    content_source,
+                    ReferenceType::EcmaScriptModules(EcmaScriptModulesReferenceSubType::Import),
+                    processed_rules,
+                    resolve_conditions,
+                ))
+                .await;
             }
-        }
-        _ => None,
-    };
+        };
+    }
 
     for (i, rule) in options.await?.rules.iter().enumerate() {
         if has_type_attribute && current_module_type.is_some() {
@@ -556,6 +631,7 @@ async fn process_default_internal(
                                     current_source,
                                     reference_type,
                                     processed_rules,
+                                    resolve_conditions,
                                 ))
                                 .await;
                             }
@@ -564,6 +640,13 @@ async fn process_default_internal(
                     ModuleRuleEffect::ModuleType(module) => {
                         current_module_type = Some(module.clone());
                     }
+                    ModuleRuleEffect::ResolveConditions(conditions) => {
+                        for condition in conditions {
+                            if !resolve_conditions.contains(condition) {
+                                resolve_conditions.push(condition.clone());
+                            }
+                        }
+                    }
                     ModuleRuleEffect::ExtendEcmascriptTransforms {
                         preprocess: extend_preprocess,
                         main: extend_main,
@@ -651,6 +734,29 @@ async fn process_default_internal(
         return Ok(ProcessResult::Unknown(current_source).cell());
     };
 
+    if let ModuleType::Sass { options } = module_type {
+        // The compiled source is renamed to `*.css`, so the CSS rules apply to it.
+        let sass_source =
+            SassSource::new(*current_source, Vc::upcast(module_asset_context), *options)
+                .to_resolved()
+                .await?;
+        return Box::pin(process_default(
+            module_asset_context,
+            ResolvedVc::upcast(sass_source),
+            reference_type,
+            processed_rules,
+            resolve_conditions,
+        ))
+        .await;
+    }
+
+    // Applied to the requests of the module in `resolve_options`. The rules are matched with the
+    // reference type of the module itself, not with the types of its requests.
+    let module_asset_context = module_asset_context
+        .with_resolve_conditions(resolve_conditions)
+        .resolve()
+        .await?;
+
     Ok(apply_module_type(
         *current_source,
         module_asset_context,
@@ -732,10 +838,11 @@ impl AssetContext for ModuleAssetContext {
             self
         };
         // TODO move `apply_commonjs/esm_resolve_options` etc. to here
-        Ok(resolve_options(
+        let options = resolve_options(
             origin_path.parent(),
             *module_asset_context.await?.resolve_options_context,
-        ))
+        );
+        Ok(apply_extra_conditions(options, this.resolve_conditions.clone()).await?)
     }
 
     #[turbo_tasks::function]
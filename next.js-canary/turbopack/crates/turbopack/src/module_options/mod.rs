@@ -136,6 +136,7 @@ impl ModuleOptions {
             css:
                 CssOptionsContext {
                     enable_raw_css,
+                    enable_native_sass,
                     source_maps: css_source_maps,
                     ..
                 },
@@ -445,6 +446,16 @@ impl ModuleOptions {
             ),
         ];
 
+        if let Some(options) = enable_native_sass {
+            rules.push(ModuleRule::new(
+                RuleCondition::any(vec![
+                    RuleCondition::ResourcePathEndsWith(".scss".to_string()),
+                    RuleCondition::ResourcePathEndsWith(".sass".to_string()),
+                ]),
+                vec![ModuleRuleEffect::ModuleType(ModuleType::Sass { options })],
+            ));
+        }
+
         if enable_raw_css {
             rules.extend([
                 ModuleRule::new(
@@ -642,42 +653,53 @@ impl ModuleOptions {
                 )
             };
             for (key, rule) in webpack_loaders_options.rules.await?.iter() {
-                rules.push(ModuleRule::new(
-                    RuleCondition::All(vec![
-                        if key.starts_with("#") {
-                            // This is a custom marker requiring a corresponding condition entry
-                            let conditions = (*webpack_loaders_options.conditions.await?)
-                                .context(
-                                    "Expected a condition entry for the webpack loader rule \
-                                     matching {key}. Create a `conditions` mapping in your \
-                                     next.config.js",
-                                )?
-                                .await?;
-
-                            let condition = conditions.get(key).context(
+                let condition = RuleCondition::All(vec![
+                    if key.starts_with("#") {
+                        // This is a custom marker requiring a corresponding condition entry
+                        let conditions = (*webpack_loaders_options.conditions.await?)
+                            .context(
                                 "Expected a condition entry for the webpack loader rule matching \
-                                 {key}.",
-                            )?;
-
-                            match &condition.path {
-                                ConditionPath::Glob(glob) => RuleCondition::ResourcePathGlob {
-                                    base: execution_context.project_path().owned().await?,
-                                    glob: Glob::new(glob.clone()).await?,
-                                },
-                                ConditionPath::Regex(regex) => {
-                                    RuleCondition::ResourcePathEsRegex(regex.await?)
-                                }
-                            }
-                        } else if key.contains('/') {
-                            RuleCondition::ResourcePathGlob {
+                                 {key}. Create a `conditions` mapping in your next.config.js",
+                            )?
+                            .await?;
+
+                        let condition = conditions.get(key).context(
+                            "Expected a condition entry for the webpack loader rule matching \
+                             {key}.",
+                        )?;
+
+                        match &condition.path {
+                            ConditionPath::Glob(glob) => RuleCondition::ResourcePathGlob {
                                 base: execution_context.project_path().owned().await?,
-                                glob: Glob::new(key.clone()).await?,
+                                glob: Glob::new(glob.clone()).await?,
+                            },
+                            ConditionPath::Regex(regex) => {
+                                RuleCondition::ResourcePathEsRegex(regex.await?)
                             }
-                        } else {
-                            RuleCondition::ResourceBasePathGlob(Glob::new(key.clone()).await?)
-                        },
-                        RuleCondition::not(RuleCondition::ResourceIsVirtualSource),
-                    ]),
+                        }
+                    } else if key.contains('/') {
+                        RuleCondition::ResourcePathGlob {
+                            base: execution_context.project_path().owned().await?,
+                            glob: Glob::new(key.clone()).await?,
+                        }
+                    } else {
+                        RuleCondition::ResourceBasePathGlob(Glob::new(key.clone()).await?)
+                    },
+                    RuleCondition::not(RuleCondition::ResourceIsVirtualSource),
+                ]);
+                if !rule.resolve_conditions.is_empty() {
+                    rules.push(ModuleRule::new(
+                        condition.clone(),
+                        vec![ModuleRuleEffect::ResolveConditions(
+                            rule.resolve_conditions.clone(),
+                        )],
+                    ));
+                }
+                if rule.loaders.await?.is_empty() && rule.rename_as.is_none() {
+                    continue;
+                }
+                rules.push(ModuleRule::new(
+                    condition,
                     vec![ModuleRuleEffect::SourceTransforms(ResolvedVc::cell(vec![
                         ResolvedVc::upcast(
                             WebpackLoaders::new(
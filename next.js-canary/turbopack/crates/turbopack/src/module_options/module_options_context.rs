@@ -9,6 +9,7 @@ use turbopack_core::{
     chunk::SourceMapsType, compile_time_info::CompileTimeInfo, condition::ContextCondition,
     environment::Environment, resolve::options::ImportMapping,
 };
+use turbopack_css::sass::SassOptions;
 use turbopack_ecmascript::{TreeShakingMode, references::esm::UrlRewriteBehavior};
 pub use turbopack_mdx::MdxTransformOptions;
 use turbopack_node::{
@@ -22,6 +23,9 @@ use super::ModuleRule;
 pub struct LoaderRuleItem {
     pub loaders: ResolvedVc<WebpackLoaderItems>,
     pub rename_as: Option<RcStr>,
+    /// Additional resolve conditions for the requests of the matched modules, see
+    /// [super::ModuleRuleEffect::ResolveConditions].
+    pub resolve_conditions: Vec<RcStr>,
 }
 
 #[derive(Default)]
@@ -211,6 +215,10 @@ pub struct CssOptionsContext {
     /// the module graph, but neither asset types can be emitted directly.
     pub enable_raw_css: bool,
 
+    /// Compiles `.scss` and `.sass` files natively instead of with `sass-loader`. The compiled
+    /// code is processed like a `.css` (or `.module.css` for `.module.scss`) file.
+    pub enable_native_sass: Option<ResolvedVc<SassOptions>>,
+
     /// Specifies how Source Maps are handled.
     pub source_maps: SourceMapsType,
 
@@ -1,12 +1,13 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use turbo_rcstr::RcStr;
 use turbo_tasks::{NonLocalValue, ResolvedVc, trace::TraceRawVcs};
 use turbo_tasks_fs::FileSystemPath;
 use turbopack_core::{
     environment::Environment, reference_type::ReferenceType, source::Source,
     source_transform::SourceTransforms,
 };
-use turbopack_css::CssModuleAssetType;
+use turbopack_css::{CssModuleAssetType, sass::SassOptions};
 use turbopack_ecmascript::{EcmascriptInputTransforms, EcmascriptOptions};
 use turbopack_wasm::source::WebAssemblySourceType;
 
@@ -78,6 +79,10 @@ pub enum ModuleRuleEffect {
     },
     SourceTransforms(ResolvedVc<SourceTransforms>),
     Ignore,
+    /// Sets additional conditions for the "exports" and "imports" fields when resolving the
+    /// requests of the matched modules, e.g. `worker` or `react-native` for a subtree of the
+    /// project.
+    ResolveConditions(Vec<RcStr>),
 }
 
 #[turbo_tasks::value(shared)]
@@ -120,10 +125,17 @@ pub enum ModuleType {
     Json,
     Raw,
     CssModule,
+    /// A CSS module script (`with { type: "css" }`) exporting a constructable `CSSStyleSheet`.
+    CssModuleScript,
     Css {
         ty: CssModuleAssetType,
         environment: Option<ResolvedVc<Environment>>,
     },
+    /// SCSS or indented Sass that is compiled natively to CSS, which is then processed by the CSS
+    /// rules.
+    Sass {
+        options: ResolvedVc<SassOptions>,
+    },
     StaticUrlJs,
     StaticUrlCss,
     WebAssembly {